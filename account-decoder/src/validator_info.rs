@@ -1,18 +1,112 @@
-use solana_config_program::ConfigState;
+use crate::parse_account_data::{ParsableAccount, ParseAccountError};
+use bincode::deserialize;
+use solana_config_program::{get_config_data, ConfigKeys};
+use solana_sdk::{account::Account, pubkey::Pubkey};
 
-pub const MAX_SHORT_FIELD_LENGTH: usize = 70;
-pub const MAX_LONG_FIELD_LENGTH: usize = 300;
-pub const MAX_VALIDATOR_INFO: u64 = 576;
+pub use solana_config_program::validator_info::{
+    check_id, id, IdentityProof, ValidatorInfo, ValidatorMetadata, ID, MAX_LONG_FIELD_LENGTH,
+    MAX_SHORT_FIELD_LENGTH, MAX_VALIDATOR_INFO,
+};
 
-solana_sdk::declare_id!("Va1idator1nfo111111111111111111111111111111");
+/// Parse a config account into validator info, checking its identity proof.  Accounts that do not
+/// match the validator info schema are not parsable; accounts with a missing or bad identity proof
+/// parse with `verified` unset.
+pub fn parse_validator_info(
+    info_pubkey: &Pubkey,
+    account: &Account,
+) -> Result<UiValidatorInfo, ParseAccountError> {
+    let not_parsable = || ParseAccountError::AccountNotParsable(ParsableAccount::Config);
+    if account.owner != solana_config_program::id() {
+        return Err(not_parsable());
+    }
+    let key_list: ConfigKeys = deserialize(&account.data).map_err(|_| not_parsable())?;
+    if !solana_config_program::validator_info::is_validator_info(&key_list.keys) {
+        return Err(not_parsable());
+    }
+    let identity = solana_config_program::validator_info::get_identity(&key_list.keys)
+        .map_err(|_| not_parsable())?;
+    let info = get_config_data(&account.data)
+        .ok()
+        .and_then(|data| deserialize::<ValidatorInfo>(data).ok())
+        .and_then(|validator_info| validator_info.metadata().ok())
+        .ok_or_else(not_parsable)?;
+    Ok(UiValidatorInfo {
+        info_pubkey: info_pubkey.to_string(),
+        identity_pubkey: identity.to_string(),
+        verified: info.verify(&identity),
+        info,
+    })
+}
 
-#[derive(Debug, Deserialize, PartialEq, Serialize, Default)]
-pub struct ValidatorInfo {
-    pub info: String,
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UiValidatorInfo {
+    pub info_pubkey: String,
+    pub identity_pubkey: String,
+    pub info: ValidatorMetadata,
+    pub verified: bool,
 }
 
-impl ConfigState for ValidatorInfo {
-    fn max_space() -> u64 {
-        MAX_VALIDATOR_INFO
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_config_program::create_config_account;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_parse_validator_info() {
+        let identity_keypair = Keypair::new();
+        let identity = identity_keypair.pubkey();
+        let info_pubkey = solana_sdk::pubkey::new_rand();
+        let keys = vec![(id(), false), (identity, true)];
+        let mut metadata = ValidatorMetadata {
+            name: "Alice".to_string(),
+            ..ValidatorMetadata::default()
+        };
+        let account = |metadata: &ValidatorMetadata| {
+            let validator_info = ValidatorInfo {
+                info: serde_json::to_string(metadata).unwrap(),
+            };
+            create_config_account(keys.clone(), &validator_info, 10)
+        };
+
+        assert_eq!(
+            parse_validator_info(&info_pubkey, &account(&metadata)).unwrap(),
+            UiValidatorInfo {
+                info_pubkey: info_pubkey.to_string(),
+                identity_pubkey: identity.to_string(),
+                info: metadata.clone(),
+                verified: false,
+            }
+        );
+
+        metadata.identity_proof = Some(IdentityProof::new(
+            &Keypair::new(),
+            &identity,
+            "https://example.com/proof".to_string(),
+        ));
+        assert!(
+            parse_validator_info(&info_pubkey, &account(&metadata))
+                .unwrap()
+                .verified
+        );
+
+        metadata.identity_proof = Some(IdentityProof::new(
+            &Keypair::new(),
+            &info_pubkey,
+            "https://example.com/proof".to_string(),
+        ));
+        assert!(
+            !parse_validator_info(&info_pubkey, &account(&metadata))
+                .unwrap()
+                .verified
+        );
+
+        let mut bad_owner = account(&metadata);
+        bad_owner.owner = solana_sdk::pubkey::new_rand();
+        assert!(parse_validator_info(&info_pubkey, &bad_owner).is_err());
+
+        let no_keys = create_config_account(vec![], &ValidatorInfo::default(), 10);
+        assert!(parse_validator_info(&info_pubkey, &no_keys).is_err());
     }
 }
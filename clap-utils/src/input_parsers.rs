@@ -1,10 +1,11 @@
 use crate::keypair::{
-    keypair_from_seed_phrase, pubkey_from_path, resolve_signer_from_path, signer_from_path,
-    ASK_KEYWORD, SKIP_SEED_PHRASE_VALIDATION_ARG,
+    derivation_path_from_prompt_uri, keypair_from_seed_phrase, parse_keypair_path,
+    pubkey_from_path, resolve_signer_from_path, signer_from_path, KeypairUrl,
+    SKIP_SEED_PHRASE_VALIDATION_ARG,
 };
 use chrono::DateTime;
 use clap::ArgMatches;
-use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_remote_wallet::{remote_signer::RemoteSigner, remote_wallet::RemoteWalletManager};
 use solana_sdk::{
     clock::UnixTimestamp,
     commitment_config::CommitmentConfig,
@@ -50,31 +51,30 @@ pub fn unix_timestamp_from_rfc3339_datetime(
     })
 }
 
-// Return the keypair for an argument with filename `name` or None if not present.
-pub fn keypair_of(matches: &ArgMatches<'_>, name: &str) -> Option<Keypair> {
-    if let Some(value) = matches.value_of(name) {
-        if value == ASK_KEYWORD {
-            let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
-            keypair_from_seed_phrase(name, skip_validation, true).ok()
-        } else {
-            read_keypair_file(value).ok()
+// Return the keypair for a seed phrase keyword or URI, or a keypair file
+fn keypair_from_value(matches: &ArgMatches<'_>, name: &str, value: &str) -> Option<Keypair> {
+    let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
+    match parse_keypair_path(value) {
+        KeypairUrl::Ask => keypair_from_seed_phrase(name, skip_validation, true, None).ok(),
+        KeypairUrl::Prompt(uri) => {
+            let derivation_path = derivation_path_from_prompt_uri(&uri).ok()?;
+            keypair_from_seed_phrase(name, skip_validation, true, Some(&derivation_path)).ok()
         }
-    } else {
-        None
+        _ => read_keypair_file(value).ok(),
     }
 }
 
+// Return the keypair for an argument with filename `name` or None if not present.
+pub fn keypair_of(matches: &ArgMatches<'_>, name: &str) -> Option<Keypair> {
+    matches
+        .value_of(name)
+        .and_then(|value| keypair_from_value(matches, name, value))
+}
+
 pub fn keypairs_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Keypair>> {
     matches.values_of(name).map(|values| {
         values
-            .filter_map(|value| {
-                if value == ASK_KEYWORD {
-                    let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
-                    keypair_from_seed_phrase(name, skip_validation, true).ok()
-                } else {
-                    read_keypair_file(value).ok()
-                }
-            })
+            .filter_map(|value| keypair_from_value(matches, name, value))
             .collect()
     })
 }
@@ -129,6 +129,29 @@ pub fn signer_of(
     }
 }
 
+// Return the signers for an argument that accepts keypairs and `remote:` signers.  Unlike hardware
+// wallets, these signers may be shared with other threads
+pub fn keypairs_or_remote_signers_of(
+    matches: &ArgMatches<'_>,
+    name: &str,
+) -> Result<Option<Vec<Arc<dyn Signer + Send + Sync>>>, Box<dyn std::error::Error>> {
+    if let Some(values) = matches.values_of(name) {
+        let mut signers: Vec<Arc<dyn Signer + Send + Sync>> = vec![];
+        for value in values {
+            if let KeypairUrl::Remote(config_file) = parse_keypair_path(value) {
+                signers.push(Arc::new(RemoteSigner::from_config_file(&config_file)?));
+            } else {
+                let keypair = keypair_from_value(matches, name, value)
+                    .ok_or_else(|| format!("could not read keypair \"{}\"", value))?;
+                signers.push(Arc::new(keypair));
+            }
+        }
+        Ok(Some(signers))
+    } else {
+        Ok(None)
+    }
+}
+
 pub fn pubkey_of_signer(
     matches: &ArgMatches<'_>,
     name: &str,
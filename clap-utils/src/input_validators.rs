@@ -1,5 +1,8 @@
-use crate::keypair::{parse_keypair_path, KeypairUrl, ASK_KEYWORD};
+use crate::keypair::{
+    derivation_path_from_prompt_uri, parse_keypair_path, KeypairUrl, ASK_KEYWORD,
+};
 use chrono::DateTime;
+use solana_remote_wallet::remote_signer::RemoteSignerConfig;
 use solana_sdk::{
     clock::Slot,
     hash::Hash,
@@ -71,6 +74,20 @@ where
         .map_err(|err| format!("{}", err))
 }
 
+// Return an error if a keypair file cannot be parsed, or the config of a `remote:` signer
+// cannot be loaded
+pub fn is_keypair_or_ask_keyword_or_remote_signer<T>(string: T) -> Result<(), String>
+where
+    T: AsRef<str> + Display,
+{
+    match parse_keypair_path(string.as_ref()) {
+        KeypairUrl::Remote(config_file) => RemoteSignerConfig::load(&config_file)
+            .map(|_| ())
+            .map_err(|err| format!("{}", err)),
+        _ => is_keypair_or_ask_keyword(string),
+    }
+}
+
 // Return an error if string cannot be parsed as pubkey string or keypair file location
 pub fn is_pubkey_or_keypair<T>(string: T) -> Result<(), String>
 where
@@ -87,6 +104,12 @@ where
 {
     match parse_keypair_path(string.as_ref()) {
         KeypairUrl::Filepath(path) => is_keypair(path),
+        KeypairUrl::Prompt(uri) => derivation_path_from_prompt_uri(&uri)
+            .map(|_| ())
+            .map_err(|err| format!("{}", err)),
+        KeypairUrl::Remote(config_file) => RemoteSignerConfig::load(&config_file)
+            .map(|_| ())
+            .map_err(|err| format!("{}", err)),
         _ => Ok(()),
     }
 }
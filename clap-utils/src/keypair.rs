@@ -8,14 +8,15 @@ use clap::ArgMatches;
 use rpassword::prompt_password_stderr;
 use solana_remote_wallet::{
     remote_keypair::generate_remote_keypair,
-    remote_wallet::{maybe_wallet_manager, RemoteWalletError, RemoteWalletManager},
+    remote_signer::RemoteSigner,
+    remote_wallet::{maybe_wallet_manager, DerivationPath, RemoteWalletError, RemoteWalletManager},
 };
 use solana_sdk::{
     hash::Hash,
     pubkey::Pubkey,
     signature::{
-        keypair_from_seed, keypair_from_seed_phrase_and_passphrase, read_keypair,
-        read_keypair_file, Keypair, NullSigner, Presigner, Signature, Signer,
+        keypair_from_seed, keypair_from_seed_and_derivation_path, read_keypair, read_keypair_file,
+        seed_from_seed_phrase_and_passphrase, Keypair, NullSigner, Presigner, Signature, Signer,
     },
 };
 use std::{
@@ -25,6 +26,7 @@ use std::{
     str::FromStr,
     sync::Arc,
 };
+use url::Url;
 
 pub struct SignOnly {
     pub blockhash: Hash,
@@ -103,8 +105,10 @@ impl DefaultSigner {
 
 pub enum KeypairUrl {
     Ask,
+    Prompt(String),
     Filepath(String),
     Usb(String),
+    Remote(String),
     Stdin,
     Pubkey(Pubkey),
 }
@@ -114,8 +118,12 @@ pub fn parse_keypair_path(path: &str) -> KeypairUrl {
         KeypairUrl::Stdin
     } else if path == ASK_KEYWORD {
         KeypairUrl::Ask
+    } else if path.starts_with(PROMPT_URI_PREFIX) {
+        KeypairUrl::Prompt(path.to_string())
     } else if path.starts_with("usb://") {
         KeypairUrl::Usb(path.to_string())
+    } else if path.starts_with(REMOTE_SIGNER_PREFIX) {
+        KeypairUrl::Remote(path[REMOTE_SIGNER_PREFIX.len()..].to_string())
     } else if let Ok(pubkey) = Pubkey::from_str(path) {
         KeypairUrl::Pubkey(pubkey)
     } else {
@@ -123,6 +131,14 @@ pub fn parse_keypair_path(path: &str) -> KeypairUrl {
     }
 }
 
+/// Return the derivation path selected by the `key` query of a `prompt://` signer URI, which
+/// defaults to `m/44'/501'` like a hardware wallet
+pub fn derivation_path_from_prompt_uri(uri: &str) -> Result<DerivationPath, RemoteWalletError> {
+    let uri = Url::parse(uri)
+        .map_err(|e| RemoteWalletError::InvalidDerivationPath(format!("parse error: {:?}", e)))?;
+    DerivationPath::from_uri(&uri)
+}
+
 pub fn presigner_from_pubkey_sigs(
     pubkey: &Pubkey,
     signers: &[(Pubkey, Signature)],
@@ -149,6 +165,17 @@ pub fn signer_from_path(
                 keypair_name,
                 skip_validation,
                 false,
+                None,
+            )?))
+        }
+        KeypairUrl::Prompt(uri) => {
+            let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
+            let derivation_path = derivation_path_from_prompt_uri(&uri)?;
+            Ok(Box::new(keypair_from_seed_phrase(
+                keypair_name,
+                skip_validation,
+                false,
+                Some(&derivation_path),
             )?))
         }
         KeypairUrl::Filepath(path) => match read_keypair_file(&path) {
@@ -178,6 +205,9 @@ pub fn signer_from_path(
                 Err(RemoteWalletError::NoDeviceFound.into())
             }
         }
+        KeypairUrl::Remote(config_file) => Ok(Box::new(RemoteSigner::from_config_file(
+            &config_file,
+        )?)),
         KeypairUrl::Pubkey(pubkey) => {
             let presigner = pubkeys_sigs_of(matches, SIGNER_ARG.name)
                 .as_ref()
@@ -220,7 +250,18 @@ pub fn resolve_signer_from_path(
             let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
             // This method validates the seed phrase, but returns `None` because there is no path
             // on disk or to a device
-            keypair_from_seed_phrase(keypair_name, skip_validation, false).map(|_| None)
+            keypair_from_seed_phrase(keypair_name, skip_validation, false, None).map(|_| None)
+        }
+        KeypairUrl::Prompt(uri) => {
+            let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
+            let derivation_path = derivation_path_from_prompt_uri(&uri)?;
+            keypair_from_seed_phrase(
+                keypair_name,
+                skip_validation,
+                false,
+                Some(&derivation_path),
+            )
+            .map(|_| None)
         }
         KeypairUrl::Filepath(path) => match read_keypair_file(&path) {
             Err(e) => Err(std::io::Error::new(
@@ -253,6 +294,11 @@ pub fn resolve_signer_from_path(
                 Err(RemoteWalletError::NoDeviceFound.into())
             }
         }
+        KeypairUrl::Remote(config_file) => {
+            // Validate the config, but keep the full `remote:` path so it can be resolved again
+            RemoteSigner::from_config_file(&config_file)?;
+            Ok(Some(path.to_string()))
+        }
         _ => Ok(Some(path.to_string())),
     }
 }
@@ -260,6 +306,14 @@ pub fn resolve_signer_from_path(
 // Keyword used to indicate that the user should be asked for a keypair seed phrase
 pub const ASK_KEYWORD: &str = "ASK";
 
+// Prefix of a URI that asks the user for a seed phrase, and derives the keypair from it along the
+// path in its `key` query, e.g. `prompt://?key=0/1`
+pub const PROMPT_URI_PREFIX: &str = "prompt://";
+
+// Prefix of a signer that forwards messages to a remote signing service, followed by the path to
+// its config file, e.g. `remote:~/.config/solana/remote-signer.yml`
+pub const REMOTE_SIGNER_PREFIX: &str = "remote:";
+
 pub const SKIP_SEED_PHRASE_VALIDATION_ARG: ArgConstant<'static> = ArgConstant {
     long: "skip-seed-phrase-validation",
     name: "skip_seed_phrase_validation",
@@ -281,11 +335,43 @@ pub fn prompt_passphrase(prompt: &str) -> Result<String, Box<dyn error::Error>>
 /// Reads user input from stdin to retrieve a seed phrase and passphrase for keypair derivation
 /// Optionally skips validation of seed phrase
 /// Optionally confirms recovered public key
+/// Optionally derives the keypair along a BIP44 path instead of using the seed directly
 pub fn keypair_from_seed_phrase(
     keypair_name: &str,
     skip_validation: bool,
     confirm_pubkey: bool,
+    derivation_path: Option<&DerivationPath>,
 ) -> Result<Keypair, Box<dyn error::Error>> {
+    let seed = seed_from_seed_phrase(keypair_name, skip_validation)?;
+    let keypair = match derivation_path {
+        Some(derivation_path) => {
+            keypair_from_seed_and_derivation_path(&seed, &derivation_path.components())?
+        }
+        None => keypair_from_seed(&seed)?,
+    };
+
+    if confirm_pubkey {
+        let pubkey = keypair.pubkey();
+        print!("Recovered pubkey `{:?}`. Continue? (y/n): ", pubkey);
+        let _ignored = stdout().flush();
+        let mut input = String::new();
+        stdin().read_line(&mut input).expect("Unexpected input");
+        if input.to_lowercase().trim() != "y" {
+            println!("Exiting");
+            exit(1);
+        }
+    }
+
+    Ok(keypair)
+}
+
+/// Reads user input from stdin to retrieve a seed phrase and passphrase, and returns the BIP39
+/// seed they produce
+/// Optionally skips validation of seed phrase
+pub fn seed_from_seed_phrase(
+    keypair_name: &str,
+    skip_validation: bool,
+) -> Result<Vec<u8>, Box<dyn error::Error>> {
     let seed_phrase = prompt_password_stderr(&format!("[{}] seed phrase: ", keypair_name))?;
     let seed_phrase = seed_phrase.trim();
     let passphrase_prompt = format!(
@@ -293,9 +379,9 @@ pub fn keypair_from_seed_phrase(
         keypair_name,
     );
 
-    let keypair = if skip_validation {
+    let seed = if skip_validation {
         let passphrase = prompt_passphrase(&passphrase_prompt)?;
-        keypair_from_seed_phrase_and_passphrase(&seed_phrase, &passphrase)?
+        seed_from_seed_phrase_and_passphrase(&seed_phrase, &passphrase)
     } else {
         let sanitized = sanitize_seed_phrase(seed_phrase);
         let parse_language_fn = || {
@@ -317,23 +403,9 @@ pub fn keypair_from_seed_phrase(
         };
         let mnemonic = parse_language_fn()?;
         let passphrase = prompt_passphrase(&passphrase_prompt)?;
-        let seed = Seed::new(&mnemonic, &passphrase);
-        keypair_from_seed(seed.as_bytes())?
+        Seed::new(&mnemonic, &passphrase).as_bytes().to_vec()
     };
-
-    if confirm_pubkey {
-        let pubkey = keypair.pubkey();
-        print!("Recovered pubkey `{:?}`. Continue? (y/n): ", pubkey);
-        let _ignored = stdout().flush();
-        let mut input = String::new();
-        stdin().read_line(&mut input).expect("Unexpected input");
-        if input.to_lowercase().trim() != "y" {
-            println!("Exiting");
-            exit(1);
-        }
-    }
-
-    Ok(keypair)
+    Ok(seed)
 }
 
 fn sanitize_seed_phrase(seed_phrase: &str) -> String {
@@ -347,6 +419,35 @@ fn sanitize_seed_phrase(seed_phrase: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_prompt_uri() {
+        assert!(matches!(
+            parse_keypair_path("prompt://?key=0/1"),
+            KeypairUrl::Prompt(_)
+        ));
+        let derivation_path = derivation_path_from_prompt_uri("prompt://?key=0/1").unwrap();
+        assert_eq!(
+            derivation_path,
+            DerivationPath::from_key_str("0/1").unwrap()
+        );
+        assert_eq!(
+            derivation_path_from_prompt_uri("prompt://").unwrap(),
+            DerivationPath::default()
+        );
+        assert!(derivation_path_from_prompt_uri("prompt://?key=0/1/2").is_err());
+        assert!(derivation_path_from_prompt_uri("prompt://?account=0").is_err());
+    }
+
+    #[test]
+    fn test_parse_remote_signer_path() {
+        match parse_keypair_path("remote:/etc/solana/remote-signer.yml") {
+            KeypairUrl::Remote(config_file) => {
+                assert_eq!(config_file, "/etc/solana/remote-signer.yml")
+            }
+            _ => panic!("expected a remote signer"),
+        }
+    }
+
     #[test]
     fn test_sanitize_seed_phrase() {
         let seed_phrase = " Mary   had\ta\u{2009}little  \n\t lamb";
@@ -19,6 +19,12 @@ pub const SIGNER_ARG: ArgConstant<'static> = ArgConstant {
     help: "Provide a public-key/signature pair for the transaction",
 };
 
+pub const OUTPUT_TRANSACTION_ARG: ArgConstant<'static> = ArgConstant {
+    name: "output_transaction",
+    long: "output-transaction",
+    help: "Also write the partially signed transaction to this file, for use with `solana tx`",
+};
+
 pub fn blockhash_arg<'a, 'b>() -> Arg<'a, 'b> {
     Arg::with_name(BLOCKHASH_ARG.name)
         .long(BLOCKHASH_ARG.long)
@@ -47,6 +53,15 @@ fn signer_arg<'a, 'b>() -> Arg<'a, 'b> {
         .help(SIGNER_ARG.help)
 }
 
+pub fn output_transaction_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(OUTPUT_TRANSACTION_ARG.name)
+        .long(OUTPUT_TRANSACTION_ARG.long)
+        .takes_value(true)
+        .value_name("FILEPATH")
+        .requires(SIGN_ONLY_ARG.name)
+        .help(OUTPUT_TRANSACTION_ARG.help)
+}
+
 pub trait ArgsConfig {
     fn blockhash_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
         arg
@@ -57,6 +72,9 @@ pub trait ArgsConfig {
     fn signer_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
         arg
     }
+    fn output_transaction_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
+        arg
+    }
 }
 
 pub trait OfflineArgs {
@@ -69,6 +87,7 @@ impl OfflineArgs for App<'_, '_> {
         self.arg(config.blockhash_arg(blockhash_arg()))
             .arg(config.sign_only_arg(sign_only_arg()))
             .arg(config.signer_arg(signer_arg()))
+            .arg(config.output_transaction_arg(output_transaction_arg()))
     }
     fn offline_args(self) -> Self {
         struct NullArgsConfig {}
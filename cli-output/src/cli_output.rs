@@ -979,6 +979,7 @@ pub struct CliValidatorInfo {
     pub identity_pubkey: String,
     pub info_pubkey: String,
     pub info: Map<String, Value>,
+    pub verified: bool,
 }
 
 impl QuietDisplay for CliValidatorInfo {}
@@ -989,12 +990,28 @@ impl fmt::Display for CliValidatorInfo {
         writeln_name_value(f, "Validator Identity:", &self.identity_pubkey)?;
         writeln_name_value(f, "  Info Address:", &self.info_pubkey)?;
         for (key, value) in self.info.iter() {
-            writeln_name_value(
-                f,
-                &format!("  {}:", to_title_case(key)),
-                &value.as_str().unwrap_or("?"),
-            )?;
+            if let Some(fields) = value.as_object() {
+                writeln!(f, "  {}:", to_title_case(key))?;
+                for (key, value) in fields.iter() {
+                    writeln_name_value(
+                        f,
+                        &format!("    {}:", to_title_case(key)),
+                        &value.as_str().unwrap_or("?"),
+                    )?;
+                }
+            } else {
+                writeln_name_value(
+                    f,
+                    &format!("  {}:", to_title_case(key)),
+                    &value.as_str().unwrap_or("?"),
+                )?;
+            }
         }
+        writeln_name_value(
+            f,
+            "  Identity Proof Verified:",
+            if self.verified { "yes" } else { "no" },
+        )?;
         Ok(())
     }
 }
@@ -1194,6 +1211,74 @@ impl fmt::Display for CliSignature {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgram {
+    pub program_id: String,
+    pub owner: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub programdata_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_deploy_slot: Option<Slot>,
+    pub data_len: usize,
+}
+
+impl QuietDisplay for CliProgram {}
+impl VerboseDisplay for CliProgram {}
+
+impl fmt::Display for CliProgram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Program Id:", &self.program_id)?;
+        writeln_name_value(f, "Owner:", &self.owner)?;
+        if let Some(programdata_address) = &self.programdata_address {
+            writeln_name_value(f, "ProgramData Address:", programdata_address)?;
+            writeln_name_value(
+                f,
+                "Authority:",
+                self.authority.as_deref().unwrap_or("none (immutable)"),
+            )?;
+        }
+        if let Some(last_deploy_slot) = self.last_deploy_slot {
+            writeln_name_value(f, "Last Deployed In Slot:", &last_deploy_slot.to_string())?;
+        }
+        writeln_name_value(
+            f,
+            "Data Length:",
+            &format!("{:?} ({:#x?}) bytes", self.data_len, self.data_len),
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliUpgradeableBuffer {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+    pub data_len: usize,
+}
+
+impl QuietDisplay for CliUpgradeableBuffer {}
+impl VerboseDisplay for CliUpgradeableBuffer {}
+
+impl fmt::Display for CliUpgradeableBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Buffer Address:", &self.address)?;
+        writeln_name_value(f, "Authority:", self.authority.as_deref().unwrap_or("none"))?;
+        writeln_name_value(
+            f,
+            "Data Length:",
+            &format!("{:?} ({:#x?}) bytes", self.data_len, self.data_len),
+        )?;
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliAccountBalances {
@@ -0,0 +1,979 @@
+use crate::cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult};
+use chrono::{TimeZone, Utc};
+use clap::{value_t, App, Arg, ArgMatches, SubCommand};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_account_decoder::{parse_token::spl_token_id_v2_0, UiAccountData};
+use solana_clap_utils::{input_parsers::pubkey_of_signer, input_validators::is_parsable};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_request::TokenAccountsFilter,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_sdk::{
+    clock::{Slot, UnixTimestamp},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use solana_transaction_status::{
+    parse_instruction::ParsedInstruction, EncodedConfirmedTransaction, EncodedTransaction,
+    UiInstruction, UiMessage, UiParsedInstruction, UiTransactionEncoding,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+// Largest page `getConfirmedSignaturesForAddress2` returns
+const SIGNATURES_PAGE_SIZE: usize = 1000;
+
+const SOL_DECIMALS: u8 = 9;
+
+/// The mint of each token account whose history is exported along with the address, keyed by
+/// token account address
+type TokenAccounts = HashMap<String, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountHistoryFormat {
+    Csv,
+    Json,
+}
+
+/// One change to the balance of the address, or of a token account it owns, made by a
+/// transaction. A transaction that moves several assets produces one record per asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceChange {
+    pub signature: String,
+    pub slot: Slot,
+    /// RFC 3339 block time, when the cluster recorded one
+    pub timestamp: Option<String>,
+    /// Account whose balance changed: the address itself, or one of its token accounts
+    pub account: String,
+    /// `SOL`, or the mint of an SPL token. Empty for token transfers that do not name their mint,
+    /// from accounts the address does not own
+    pub asset: String,
+    /// Signed change in the asset's base units (lamports for SOL), excluding the fee
+    pub amount: i128,
+    pub decimals: Option<u8>,
+    pub counterparty: Option<String>,
+    /// Lamports the address paid in transaction fees
+    pub fee: u64,
+    pub memo: Option<String>,
+    pub failed: bool,
+}
+
+struct TransactionInfo<'a> {
+    signature: &'a str,
+    slot: Slot,
+    timestamp: Option<String>,
+    memo: Option<String>,
+    failed: bool,
+}
+
+impl TransactionInfo<'_> {
+    fn balance_change(
+        &self,
+        account: String,
+        asset: String,
+        amount: i128,
+        decimals: Option<u8>,
+        counterparty: Option<String>,
+    ) -> BalanceChange {
+        BalanceChange {
+            signature: self.signature.to_string(),
+            slot: self.slot,
+            timestamp: self.timestamp.clone(),
+            account,
+            asset,
+            amount,
+            decimals,
+            counterparty,
+            fee: 0,
+            memo: self.memo.clone(),
+            failed: self.failed,
+        }
+    }
+}
+
+/// Pairs of parsed instruction fields naming the account lamports move from, and the account
+/// they move to
+const LAMPORT_TRANSFER_FIELDS: &[(&str, &str)] = &[
+    ("source", "destination"),
+    ("source", "newAccount"),
+    ("nonceAccount", "destination"),
+    ("stakeAccount", "destination"),
+    ("stakeAccount", "newSplitAccount"),
+];
+
+fn info_str<'a>(info: &'a Value, field: &str) -> Option<&'a str> {
+    info.get(field).and_then(|value| value.as_str())
+}
+
+/// Every parsed instruction of the transaction, including those invoked by other programs
+fn parsed_instructions(transaction: &EncodedConfirmedTransaction) -> Vec<&ParsedInstruction> {
+    let mut instructions = vec![];
+    if let EncodedTransaction::Json(transaction) = &transaction.transaction.transaction {
+        if let UiMessage::Parsed(message) = &transaction.message {
+            instructions.extend(message.instructions.iter());
+        }
+    }
+    if let Some(inner_instructions) = transaction
+        .transaction
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.inner_instructions.as_ref())
+    {
+        for inner_instructions in inner_instructions {
+            instructions.extend(inner_instructions.instructions.iter());
+        }
+    }
+    instructions
+        .into_iter()
+        .filter_map(|instruction| match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(instruction)) => Some(instruction),
+            _ => None,
+        })
+        .collect()
+}
+
+fn account_keys(transaction: &EncodedConfirmedTransaction) -> Vec<String> {
+    match &transaction.transaction.transaction {
+        EncodedTransaction::Json(transaction) => match &transaction.message {
+            UiMessage::Parsed(message) => message
+                .account_keys
+                .iter()
+                .map(|account| account.pubkey.clone())
+                .collect(),
+            UiMessage::Raw(message) => message.account_keys.clone(),
+        },
+        _ => vec![],
+    }
+}
+
+/// The accounts on the other side of the lamport transfers the address takes part in
+fn lamport_counterparties(address: &str, instructions: &[&ParsedInstruction]) -> Option<String> {
+    let mut counterparties: Vec<&str> = vec![];
+    for instruction in instructions {
+        if !matches!(instruction.program.as_str(), "system" | "stake") {
+            continue;
+        }
+        let info = &instruction.parsed["info"];
+        for (from, to) in LAMPORT_TRANSFER_FIELDS {
+            let counterparty = match (info_str(info, from), info_str(info, to)) {
+                (Some(from), Some(to)) if from == address => to,
+                (Some(from), Some(to)) if to == address => from,
+                _ => continue,
+            };
+            if !counterparties.contains(&counterparty) {
+                counterparties.push(counterparty);
+            }
+        }
+    }
+    if counterparties.is_empty() {
+        None
+    } else {
+        Some(counterparties.join(";"))
+    }
+}
+
+/// The raw amount and decimals of a parsed token instruction
+fn token_amount(info: &Value) -> Option<(i128, Option<u8>)> {
+    if let Some(amount) = info_str(info, "amount") {
+        return amount.parse().ok().map(|amount| (amount, None));
+    }
+    let token_amount = info.get("tokenAmount")?;
+    let amount = info_str(token_amount, "amount")?.parse().ok()?;
+    let decimals = token_amount
+        .get("decimals")
+        .and_then(|decimals| decimals.as_u64())
+        .map(|decimals| decimals as u8);
+    Some((amount, decimals))
+}
+
+fn token_changes(
+    address: &str,
+    token_accounts: &TokenAccounts,
+    info: &TransactionInfo,
+    instructions: &[&ParsedInstruction],
+) -> Vec<BalanceChange> {
+    let mut changes = vec![];
+    for instruction in instructions {
+        if instruction.program != "spl-token" {
+            continue;
+        }
+        let instruction_type = instruction.parsed["type"].as_str().unwrap_or_default();
+        let parsed_info = &instruction.parsed["info"];
+        let (amount, decimals) = match token_amount(parsed_info) {
+            Some(amount) => amount,
+            None => continue,
+        };
+        let authority = info_str(parsed_info, "authority")
+            .or_else(|| info_str(parsed_info, "multisigAuthority"))
+            .or_else(|| info_str(parsed_info, "mintAuthority"));
+        let is_tracked = |account: &str| account == address || token_accounts.contains_key(account);
+        // Transfers only name their mint when checked
+        let mint_of = |account: &str| {
+            info_str(parsed_info, "mint")
+                .or_else(|| token_accounts.get(account).map(String::as_str))
+                .unwrap_or_default()
+                .to_string()
+        };
+        match instruction_type {
+            "transfer" | "transferChecked" => {
+                let source = info_str(parsed_info, "source").unwrap_or_default();
+                let destination = info_str(parsed_info, "destination").unwrap_or_default();
+                if is_tracked(source) || authority == Some(address) {
+                    changes.push(info.balance_change(
+                        source.to_string(),
+                        mint_of(source),
+                        -amount,
+                        decimals,
+                        Some(destination.to_string()),
+                    ));
+                }
+                if is_tracked(destination) {
+                    changes.push(info.balance_change(
+                        destination.to_string(),
+                        mint_of(destination),
+                        amount,
+                        decimals,
+                        Some(source.to_string()),
+                    ));
+                }
+            }
+            "mintTo" | "mintToChecked" => {
+                let account = info_str(parsed_info, "account").unwrap_or_default();
+                if is_tracked(account) {
+                    let mint = mint_of(account);
+                    changes.push(info.balance_change(
+                        account.to_string(),
+                        mint.clone(),
+                        amount,
+                        decimals,
+                        Some(mint),
+                    ));
+                }
+            }
+            "burn" | "burnChecked" => {
+                let account = info_str(parsed_info, "account").unwrap_or_default();
+                if !is_tracked(account) && authority != Some(address) {
+                    continue;
+                }
+                let mint = mint_of(account);
+                changes.push(info.balance_change(
+                    account.to_string(),
+                    mint.clone(),
+                    -amount,
+                    decimals,
+                    Some(mint),
+                ));
+            }
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// Derive the balance changes `transaction` made to `address` and its `token_accounts`, which
+/// must have been fetched with `jsonParsed` encoding
+fn balance_changes(
+    address: &Pubkey,
+    token_accounts: &TokenAccounts,
+    status: &RpcConfirmedTransactionStatusWithSignature,
+    block_time: Option<UnixTimestamp>,
+    transaction: &EncodedConfirmedTransaction,
+) -> Vec<BalanceChange> {
+    let meta = match &transaction.transaction.meta {
+        Some(meta) => meta,
+        None => return vec![],
+    };
+    let address = address.to_string();
+    let info = TransactionInfo {
+        signature: &status.signature,
+        slot: status.slot,
+        timestamp: block_time.map(|block_time| Utc.timestamp(block_time, 0).to_rfc3339()),
+        memo: status.memo.clone(),
+        failed: meta.err.is_some(),
+    };
+    let instructions = parsed_instructions(transaction);
+    let mut changes = vec![];
+
+    if let Some(index) = account_keys(transaction)
+        .iter()
+        .position(|key| *key == address)
+    {
+        if let (Some(pre_balance), Some(post_balance)) =
+            (meta.pre_balances.get(index), meta.post_balances.get(index))
+        {
+            // The first account pays the fee
+            let fee = if index == 0 { meta.fee } else { 0 };
+            let amount = *post_balance as i128 - *pre_balance as i128 + fee as i128;
+            if amount != 0 || fee != 0 {
+                let counterparty = lamport_counterparties(&address, &instructions);
+                let mut change = info.balance_change(
+                    address.clone(),
+                    "SOL".to_string(),
+                    amount,
+                    Some(SOL_DECIMALS),
+                    counterparty,
+                );
+                change.fee = fee;
+                changes.push(change);
+            }
+        }
+    }
+    // Failed transactions only ever charge a fee
+    if meta.err.is_none() {
+        changes.extend(token_changes(
+            &address,
+            token_accounts,
+            &info,
+            &instructions,
+        ));
+    }
+    changes
+}
+
+enum HistoryWriter {
+    Csv(csv::Writer<Box<dyn Write>>),
+    Json(Box<dyn Write>),
+}
+
+impl HistoryWriter {
+    fn new(format: AccountHistoryFormat, output: Box<dyn Write>, write_header: bool) -> Self {
+        match format {
+            AccountHistoryFormat::Csv => HistoryWriter::Csv(
+                csv::WriterBuilder::new()
+                    .has_headers(write_header)
+                    .from_writer(output),
+            ),
+            AccountHistoryFormat::Json => HistoryWriter::Json(output),
+        }
+    }
+
+    fn write(&mut self, changes: &[BalanceChange]) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            HistoryWriter::Csv(writer) => {
+                for change in changes {
+                    writer.serialize(change)?;
+                }
+                writer.flush()?;
+            }
+            HistoryWriter::Json(writer) => {
+                for change in changes {
+                    writeln!(writer, "{}", serde_json::to_string(change)?)?;
+                }
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The signature of the oldest transaction already recorded in an output file
+fn last_recorded_signature(
+    output_file: &Path,
+    format: AccountHistoryFormat,
+) -> Result<Option<Signature>, Box<dyn std::error::Error>> {
+    if !output_file.exists() {
+        return Ok(None);
+    }
+    let last_change: Option<BalanceChange> = match format {
+        AccountHistoryFormat::Csv => {
+            let mut reader = csv::Reader::from_path(output_file)?;
+            let mut last_change = None;
+            for change in reader.deserialize() {
+                last_change = Some(change?);
+            }
+            last_change
+        }
+        AccountHistoryFormat::Json => {
+            let mut last_change = None;
+            for line in BufReader::new(File::open(output_file)?).lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    last_change = Some(serde_json::from_str(&line)?);
+                }
+            }
+            last_change
+        }
+    };
+    match last_change {
+        Some(change) => Ok(Some(change.signature.parse().map_err(|err| {
+            CliError::BadParameter(format!(
+                "Invalid signature in {}: {}",
+                output_file.display(),
+                err
+            ))
+        })?)),
+        None => Ok(None),
+    }
+}
+
+/// The token accounts `address` owns, or `address` itself if it is a token account
+fn token_accounts(
+    rpc_client: &RpcClient,
+    address: &Pubkey,
+) -> Result<TokenAccounts, Box<dyn std::error::Error>> {
+    let mut token_accounts: TokenAccounts = rpc_client
+        .get_token_accounts_by_owner(address, TokenAccountsFilter::ProgramId(spl_token_id_v2_0()))?
+        .into_iter()
+        .filter_map(|keyed_account| match keyed_account.account.data {
+            UiAccountData::Json(account_data) => {
+                let mint = info_str(&account_data.parsed["info"], "mint")?.to_string();
+                Some((keyed_account.pubkey, mint))
+            }
+            _ => None,
+        })
+        .collect();
+    // Any other account fails to parse as a token account
+    if let Ok(Some(token_account)) = rpc_client.get_token_account(address) {
+        token_accounts.insert(address.to_string(), token_account.mint);
+    }
+    Ok(token_accounts)
+}
+
+/// The transaction history of one address, fetched a page at a time
+struct AddressHistory {
+    address: Pubkey,
+    statuses: VecDeque<RpcConfirmedTransactionStatusWithSignature>,
+    before: Option<Signature>,
+    exhausted: bool,
+}
+
+impl AddressHistory {
+    fn new(address: Pubkey, before: Option<Signature>) -> Self {
+        Self {
+            address,
+            statuses: VecDeque::new(),
+            before,
+            exhausted: false,
+        }
+    }
+
+    /// The newest transaction not yet taken, fetching up to `page_size` more when none are left
+    fn next(
+        &mut self,
+        rpc_client: &RpcClient,
+        until: Option<Signature>,
+        page_size: usize,
+    ) -> Result<Option<&RpcConfirmedTransactionStatusWithSignature>, Box<dyn std::error::Error>>
+    {
+        if self.statuses.is_empty() && !self.exhausted {
+            let statuses = rpc_client.get_confirmed_signatures_for_address2_with_config(
+                &self.address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before: self.before,
+                    until,
+                    limit: Some(page_size),
+                },
+            )?;
+            self.exhausted = statuses.len() < page_size;
+            if let Some(status) = statuses.last() {
+                self.before = Some(status.signature.parse()?);
+            }
+            self.statuses.extend(statuses);
+        }
+        Ok(self.statuses.front())
+    }
+}
+
+/// The index of the history whose next transaction is exported first: the one in the newest
+/// slot and, within a slot, the one with the greatest signature.  This is the order the RPC
+/// lists each history in, so `--resume` picks up every history right after the last
+/// transaction written
+fn newest_history(next_transactions: &[Option<(Slot, Signature)>]) -> Option<usize> {
+    next_transactions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, next_transaction)| next_transaction.map(|key| (key, i)))
+        .max()
+        .map(|(_, i)| i)
+}
+
+pub trait AccountHistorySubCommands {
+    fn account_history_subcommands(self) -> Self;
+}
+
+impl AccountHistorySubCommands for App<'_, '_> {
+    fn account_history_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("account-history")
+                .about("Export every balance change to the given address and the token accounts it owns, from newest to oldest")
+                .arg(
+                    pubkey!(Arg::with_name("address")
+                        .index(1)
+                        .value_name("ADDRESS")
+                        .required(true),
+                        "Account address. Token accounts the address has closed, or no longer owns, are not included"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(&["csv", "json"])
+                        .default_value("csv")
+                        .help("Write CSV, or JSON with one balance change per line"),
+                )
+                .arg(
+                    Arg::with_name("output_file")
+                        .long("output-file")
+                        .short("o")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .help("Write the balance changes to this file [default: stdout]"),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .long("resume")
+                        .takes_value(false)
+                        .requires("output_file")
+                        .conflicts_with("before")
+                        .help("Append to the output file, continuing after the oldest transaction it records"),
+                )
+                .arg(
+                    Arg::with_name("before")
+                        .long("before")
+                        .value_name("TRANSACTION_SIGNATURE")
+                        .takes_value(true)
+                        .help("Start with the first signature older than this one"),
+                )
+                .arg(
+                    Arg::with_name("until")
+                        .long("until")
+                        .value_name("TRANSACTION_SIGNATURE")
+                        .takes_value(true)
+                        .help("Stop at this signature, without including it"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .takes_value(true)
+                        .value_name("LIMIT")
+                        .validator(is_parsable::<usize>)
+                        .help("Maximum number of transactions to export [default: all]"),
+                ),
+        )
+    }
+}
+
+fn parse_signature(matches: &ArgMatches<'_>, name: &str) -> Result<Option<Signature>, CliError> {
+    match matches.value_of(name) {
+        Some(signature) => Ok(Some(signature.parse().map_err(|err| {
+            CliError::BadParameter(format!("Invalid signature: {}", err))
+        })?)),
+        None => Ok(None),
+    }
+}
+
+pub fn parse_account_history(
+    matches: &ArgMatches<'_>,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let address = pubkey_of_signer(matches, "address", wallet_manager)?.unwrap();
+    let format = match matches.value_of("format").unwrap() {
+        "json" => AccountHistoryFormat::Json,
+        _ => AccountHistoryFormat::Csv,
+    };
+    Ok(CliCommandInfo {
+        command: CliCommand::AccountHistory {
+            address,
+            format,
+            output_file: matches.value_of("output_file").map(PathBuf::from),
+            resume: matches.is_present("resume"),
+            before: parse_signature(matches, "before")?,
+            until: parse_signature(matches, "until")?,
+            limit: value_t!(matches, "limit", usize).ok(),
+        },
+        signers: vec![],
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_account_history(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    address: &Pubkey,
+    format: AccountHistoryFormat,
+    output_file: Option<&PathBuf>,
+    resume: bool,
+    before: Option<Signature>,
+    until: Option<Signature>,
+    limit: Option<usize>,
+) -> ProcessResult {
+    let mut before = before;
+    let (output, write_header): (Box<dyn Write>, bool) = match output_file {
+        Some(output_file) => {
+            if resume {
+                before = last_recorded_signature(output_file, format)?;
+            }
+            let append = resume && before.is_some();
+            let file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(append)
+                .truncate(!append)
+                .open(output_file)?;
+            (Box::new(file), !append)
+        }
+        None => (Box::new(io::stdout()), true),
+    };
+    let mut writer = HistoryWriter::new(format, output, write_header);
+
+    // A wallet's token balances change in transactions that may not mention the wallet, so
+    // the history of each of its token accounts is walked too, newest transaction first
+    let token_accounts = token_accounts(rpc_client, address)?;
+    let mut histories: Vec<AddressHistory> = std::iter::once(*address)
+        .chain(
+            token_accounts
+                .keys()
+                .filter_map(|token_account| token_account.parse().ok())
+                .filter(|token_account| token_account != address),
+        )
+        .map(|address| AddressHistory::new(address, before))
+        .collect();
+
+    let mut num_transactions = 0;
+    let mut num_changes = 0;
+    // A transaction in the history of several addresses is exported once
+    let mut slot_signatures: (Slot, HashSet<String>) = (0, HashSet::new());
+    loop {
+        let page_size = match limit {
+            Some(limit) if limit <= num_transactions => break,
+            Some(limit) => (limit - num_transactions).min(SIGNATURES_PAGE_SIZE),
+            None => SIGNATURES_PAGE_SIZE,
+        };
+        let mut next_transactions = vec![];
+        for history in histories.iter_mut() {
+            next_transactions.push(match history.next(rpc_client, until, page_size)? {
+                Some(status) => Some((status.slot, status.signature.parse()?)),
+                None => None,
+            });
+        }
+        let status = match newest_history(&next_transactions) {
+            Some(i) => histories[i].statuses.pop_front().unwrap(),
+            None => break,
+        };
+        if status.slot != slot_signatures.0 {
+            slot_signatures = (status.slot, HashSet::new());
+        }
+        if !slot_signatures.1.insert(status.signature.clone()) {
+            continue;
+        }
+
+        let signature: Signature = status.signature.parse()?;
+        let transaction =
+            rpc_client.get_confirmed_transaction(&signature, UiTransactionEncoding::JsonParsed)?;
+        let block_time = rpc_client.get_block_time(status.slot).ok();
+        let changes = balance_changes(address, &token_accounts, &status, block_time, &transaction);
+        // Each transaction's changes are written together, so an interrupted export can
+        // resume after the last one in the file
+        writer.write(&changes)?;
+        num_changes += changes.len();
+        num_transactions += 1;
+        if config.verbose && num_transactions % SIGNATURES_PAGE_SIZE == 0 {
+            eprintln!("{} transactions exported", num_transactions);
+        }
+    }
+
+    let summary = format!(
+        "{} balance changes found in {} transactions",
+        num_changes, num_transactions
+    );
+    if output_file.is_some() {
+        Ok(summary)
+    } else {
+        // Keep stdout to the export itself
+        eprintln!("{}", summary);
+        Ok("".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_transaction_status::UiTransactionStatusMeta;
+
+    fn confirmed_transaction(
+        account_keys: &[&str],
+        instructions: Value,
+        pre_balances: Vec<u64>,
+        post_balances: Vec<u64>,
+    ) -> EncodedConfirmedTransaction {
+        let account_keys: Vec<Value> = account_keys
+            .iter()
+            .map(|key| json!({"pubkey": key, "writable": true, "signer": false}))
+            .collect();
+        let transaction = json!({
+            "signatures": ["1111111111111111111111111111111111111111111111111111111111111111"],
+            "message": {
+                "accountKeys": account_keys,
+                "recentBlockhash": "11111111111111111111111111111111",
+                "instructions": instructions,
+            },
+        });
+        let meta = UiTransactionStatusMeta {
+            err: None,
+            status: Ok(()),
+            fee: 5000,
+            pre_balances,
+            post_balances,
+            inner_instructions: Some(vec![]),
+            log_messages: None,
+            return_data: None,
+            log_events: None,
+            loaded_addresses: None,
+        };
+        serde_json::from_value(json!({
+            "slot": 42,
+            "transaction": transaction,
+            "meta": meta,
+        }))
+        .unwrap()
+    }
+
+    fn status() -> RpcConfirmedTransactionStatusWithSignature {
+        RpcConfirmedTransactionStatusWithSignature {
+            signature: "1111111111111111111111111111111111111111111111111111111111111111"
+                .to_string(),
+            slot: 42,
+            err: None,
+            memo: Some("invoice 7".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_sol_balance_changes() {
+        let payer = solana_sdk::pubkey::new_rand();
+        let recipient = solana_sdk::pubkey::new_rand();
+        let (payer_str, recipient_str) = (payer.to_string(), recipient.to_string());
+        let transaction = confirmed_transaction(
+            &[
+                &payer_str,
+                &recipient_str,
+                "11111111111111111111111111111111",
+            ],
+            json!([{
+                "program": "system",
+                "programId": "11111111111111111111111111111111",
+                "parsed": {
+                    "type": "transfer",
+                    "info": {"source": payer_str, "destination": recipient_str, "lamports": 1000},
+                },
+            }]),
+            vec![10_000, 0, 1],
+            vec![4_000, 1_000, 1],
+        );
+
+        let changes = balance_changes(
+            &payer,
+            &TokenAccounts::new(),
+            &status(),
+            Some(0),
+            &transaction,
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].amount, -1000);
+        assert_eq!(changes[0].fee, 5000);
+        assert_eq!(changes[0].asset, "SOL");
+        assert_eq!(changes[0].counterparty, Some(recipient_str.clone()));
+        assert_eq!(changes[0].memo, Some("invoice 7".to_string()));
+        assert_eq!(
+            changes[0].timestamp,
+            Some("1970-01-01T00:00:00+00:00".to_string())
+        );
+
+        let changes = balance_changes(
+            &recipient,
+            &TokenAccounts::new(),
+            &status(),
+            None,
+            &transaction,
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].amount, 1000);
+        assert_eq!(changes[0].fee, 0);
+        assert_eq!(changes[0].counterparty, Some(payer_str));
+    }
+
+    #[test]
+    fn test_token_balance_changes() {
+        let owner = solana_sdk::pubkey::new_rand();
+        let (owner_str, source, destination, mint) = (
+            owner.to_string(),
+            solana_sdk::pubkey::new_rand().to_string(),
+            solana_sdk::pubkey::new_rand().to_string(),
+            solana_sdk::pubkey::new_rand().to_string(),
+        );
+        let transaction = confirmed_transaction(
+            &[&owner_str, &source, &destination],
+            json!([{
+                "program": "spl-token",
+                "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "parsed": {
+                    "type": "transferChecked",
+                    "info": {
+                        "source": source,
+                        "mint": mint,
+                        "destination": destination,
+                        "authority": owner_str,
+                        "tokenAmount": {"amount": "250", "decimals": 2, "uiAmount": 2.5},
+                    },
+                },
+            }]),
+            vec![10_000, 0, 0],
+            vec![5_000, 0, 0],
+        );
+
+        let changes = balance_changes(&owner, &TokenAccounts::new(), &status(), None, &transaction);
+        assert_eq!(changes.len(), 2);
+        // Fee only
+        assert_eq!(changes[0].amount, 0);
+        assert_eq!(changes[0].fee, 5000);
+        assert_eq!(changes[1].account, source);
+        assert_eq!(changes[1].asset, mint);
+        assert_eq!(changes[1].amount, -250);
+        assert_eq!(changes[1].decimals, Some(2));
+        assert_eq!(changes[1].counterparty, Some(destination.clone()));
+
+        let destination_pubkey: Pubkey = destination.parse().unwrap();
+        let changes = balance_changes(
+            &destination_pubkey,
+            &TokenAccounts::new(),
+            &status(),
+            None,
+            &transaction,
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].amount, 250);
+        assert_eq!(changes[0].counterparty, Some(source));
+    }
+
+    #[test]
+    fn test_owned_token_account_balance_changes() {
+        let owner = solana_sdk::pubkey::new_rand();
+        let (source, destination, mint) = (
+            solana_sdk::pubkey::new_rand().to_string(),
+            solana_sdk::pubkey::new_rand().to_string(),
+            solana_sdk::pubkey::new_rand().to_string(),
+        );
+        let transaction = confirmed_transaction(
+            &[&source, &destination],
+            json!([{
+                "program": "spl-token",
+                "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "parsed": {
+                    "type": "transfer",
+                    "info": {
+                        "source": source,
+                        "destination": destination,
+                        "authority": solana_sdk::pubkey::new_rand().to_string(),
+                        "amount": "42",
+                    },
+                },
+            }]),
+            vec![10_000, 0],
+            vec![5_000, 0],
+        );
+
+        // The owner is not in the transaction, but its token account is credited
+        let token_accounts: TokenAccounts = vec![(destination.clone(), mint.clone())]
+            .into_iter()
+            .collect();
+        let changes = balance_changes(&owner, &token_accounts, &status(), None, &transaction);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].account, destination);
+        assert_eq!(changes[0].asset, mint);
+        assert_eq!(changes[0].amount, 42);
+        assert_eq!(changes[0].fee, 0);
+        assert_eq!(changes[0].counterparty, Some(source));
+
+        let changes = balance_changes(&owner, &TokenAccounts::new(), &status(), None, &transaction);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_last_recorded_signature() {
+        let change = BalanceChange {
+            signature: Signature::new(&[1; 64]).to_string(),
+            slot: 1,
+            timestamp: None,
+            account: solana_sdk::pubkey::new_rand().to_string(),
+            asset: "SOL".to_string(),
+            amount: -1,
+            decimals: Some(SOL_DECIMALS),
+            counterparty: None,
+            fee: 0,
+            memo: None,
+            failed: false,
+        };
+        let last_change = BalanceChange {
+            signature: Signature::new(&[2; 64]).to_string(),
+            ..change.clone()
+        };
+        for format in &[AccountHistoryFormat::Csv, AccountHistoryFormat::Json] {
+            let file = tempfile::NamedTempFile::new().unwrap();
+            let path = file.path().to_path_buf();
+            assert_eq!(last_recorded_signature(&path, *format).unwrap(), None);
+
+            let output: Box<dyn Write> = Box::new(file.reopen().unwrap());
+            let mut writer = HistoryWriter::new(*format, output, true);
+            writer
+                .write(&[change.clone(), last_change.clone()])
+                .unwrap();
+            assert_eq!(
+                last_recorded_signature(&path, *format).unwrap(),
+                Some(Signature::new(&[2; 64]))
+            );
+        }
+    }
+
+    #[test]
+    fn test_newest_history_orders_within_a_slot() {
+        let signature = |i| Signature::new(&[i; 64]);
+        // Merge histories the way `process_account_history` does
+        let merge = |histories: Vec<Vec<(Slot, u8)>>| {
+            let mut histories: Vec<VecDeque<(Slot, Signature)>> = histories
+                .into_iter()
+                .map(|history| {
+                    history
+                        .into_iter()
+                        .map(|(slot, i)| (slot, signature(i)))
+                        .collect()
+                })
+                .collect();
+            let mut merged = vec![];
+            loop {
+                let next_transactions: Vec<_> = histories
+                    .iter()
+                    .map(|history| history.front().cloned())
+                    .collect();
+                match newest_history(&next_transactions) {
+                    Some(i) => merged.push(histories[i].pop_front().unwrap()),
+                    None => break merged,
+                }
+            }
+        };
+
+        // Two histories sharing slot 5, each listed newest first like the RPC does
+        let history_a = vec![(6, 3), (5, 4), (5, 1)];
+        let history_b = vec![(5, 5), (5, 2), (4, 6)];
+        let expected: Vec<_> = vec![(6, 3), (5, 5), (5, 4), (5, 2), (5, 1), (4, 6)]
+            .into_iter()
+            .map(|(slot, i)| (slot, signature(i)))
+            .collect();
+        assert_eq!(merge(vec![history_a, history_b]), expected);
+
+        // An export interrupted after (5, 4) resumes with everything older than it in each
+        // history, and picks up exactly where it stopped
+        let resumed = merge(vec![vec![(5, 1)], vec![(5, 2), (4, 6)]]);
+        assert_eq!(resumed, expected[3..].to_vec());
+    }
+}
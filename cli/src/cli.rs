@@ -1,8 +1,7 @@
 use crate::{
-    checks::*, cluster_query::*, feature::*, inflation::*, nonce::*, send_tpu::*, spend_utils::*,
-    stake::*, validator_info::*, vote::*,
+    account_history::*, checks::*, cluster_query::*, feature::*, inflation::*, nonce::*,
+    program::*, spend_utils::*, stake::*, tx::*, validator_info::*, vote::*,
 };
-use bincode::serialize;
 use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use clap::{value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand};
 use log::*;
@@ -24,7 +23,7 @@ use solana_cli_output::{
     display::{
         build_balance_message, new_spinner_progress_bar, println_name_value, println_transaction,
     },
-    return_signers, CliAccount, CliSignature, OutputFormat,
+    CliAccount, CliSignature, OutputFormat,
 };
 use solana_client::{
     blockhash_query::BlockhashQuery,
@@ -33,7 +32,8 @@ use solana_client::{
     rpc_client::RpcClient,
     rpc_config::{RpcLargestAccountsFilter, RpcSendTransactionConfig, RpcTransactionLogsFilter},
     rpc_request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
-    rpc_response::{RpcKeyedAccount, RpcLeaderSchedule},
+    rpc_response::RpcKeyedAccount,
+    tpu_client::{TpuClient, TpuClientConfig},
 };
 #[cfg(not(test))]
 use solana_faucet::faucet::request_airdrop_transaction;
@@ -65,13 +65,13 @@ use solana_stake_program::{
 use solana_transaction_status::{EncodedTransaction, UiTransactionEncoding};
 use solana_vote_program::vote_state::VoteAuthorize;
 use std::{
-    cmp::min,
     collections::HashMap,
     error,
     fmt::Write as FmtWrite,
     fs::File,
     io::{Read, Write},
-    net::{IpAddr, SocketAddr, UdpSocket},
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
     str::FromStr,
     sync::Arc,
     thread::sleep,
@@ -152,6 +152,15 @@ pub enum CliCommand {
         limit: usize,
         show_transactions: bool,
     },
+    AccountHistory {
+        address: Pubkey,
+        format: AccountHistoryFormat,
+        output_file: Option<PathBuf>,
+        resume: bool,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: Option<usize>,
+    },
     WaitForMaxStake {
         max_stake_percent: f32,
     },
@@ -189,6 +198,7 @@ pub enum CliCommand {
         use_deprecated_loader: bool,
         allow_excessive_balance: bool,
     },
+    Program(ProgramCliCommand),
     // Stake Commands
     CreateStakeAccount {
         stake_account: SignerIndex,
@@ -356,6 +366,7 @@ pub enum CliCommand {
         nonce_authority: SignerIndex,
         fee_payer: SignerIndex,
     },
+    Tx(TxCliCommand),
 }
 
 #[derive(Debug, PartialEq)]
@@ -422,6 +433,7 @@ pub struct CliConfig<'a> {
     pub commitment: CommitmentConfig,
     pub send_transaction_config: RpcSendTransactionConfig,
     pub address_labels: HashMap<String, String>,
+    pub output_transaction: Option<PathBuf>,
 }
 
 impl CliConfig<'_> {
@@ -528,6 +540,7 @@ impl Default for CliConfig<'_> {
             commitment: CommitmentConfig::default(),
             send_transaction_config: RpcSendTransactionConfig::default(),
             address_labels: HashMap::new(),
+            output_transaction: None,
         }
     }
 }
@@ -599,6 +612,7 @@ pub fn parse_command(
         ("transaction-history", Some(matches)) => {
             parse_transaction_history(matches, wallet_manager)
         }
+        ("account-history", Some(matches)) => parse_account_history(matches, wallet_manager),
         // Nonce Commands
         ("authorize-nonce-account", Some(matches)) => {
             parse_authorize_nonce_account(matches, default_signer, wallet_manager)
@@ -631,6 +645,9 @@ pub fn parse_command(
                 signers,
             })
         }
+        ("program", Some(matches)) => {
+            parse_program_subcommand(matches, default_signer, wallet_manager)
+        }
         ("wait-for-max-stake", Some(matches)) => {
             let max_stake_percent = value_t_or_exit!(matches, "max_percent", f32);
             Ok(CliCommandInfo {
@@ -835,6 +852,7 @@ pub fn parse_command(
                 signers: signer_info.signers,
             })
         }
+        ("tx", Some(matches)) => parse_tx_subcommand(matches, default_signer, wallet_manager),
         //
         ("", None) => {
             eprintln!("{}", matches.usage());
@@ -1038,7 +1056,7 @@ fn process_show_account(
     Ok(account_string)
 }
 
-fn send_and_confirm_transactions_with_spinner<T: Signers>(
+pub fn send_and_confirm_transactions_with_spinner<T: Signers>(
     rpc_client: &RpcClient,
     mut transactions: Vec<Transaction>,
     signer_keys: &T,
@@ -1047,36 +1065,19 @@ fn send_and_confirm_transactions_with_spinner<T: Signers>(
 ) -> Result<(), Box<dyn error::Error>> {
     let progress_bar = new_spinner_progress_bar();
     let mut send_retries = 5;
-    let mut leader_schedule: Option<RpcLeaderSchedule> = None;
-    let mut leader_schedule_epoch = 0;
-    let send_socket = UdpSocket::bind("0.0.0.0:0").unwrap();
-    let cluster_nodes = rpc_client.get_cluster_nodes().ok();
+
+    progress_bar.set_message("Finding leader nodes...");
+    let mut tpu_client = TpuClient::new(rpc_client, commitment, TpuClientConfig::default())?;
 
     loop {
         let mut status_retries = 15;
 
-        progress_bar.set_message("Finding leader node...");
-        let epoch_info = rpc_client.get_epoch_info_with_commitment(commitment)?;
-        if epoch_info.epoch > leader_schedule_epoch || leader_schedule.is_none() {
-            leader_schedule = rpc_client
-                .get_leader_schedule_with_commitment(Some(epoch_info.absolute_slot), commitment)?;
-            leader_schedule_epoch = epoch_info.epoch;
-        }
-        let tpu_address = get_leader_tpu(
-            min(epoch_info.slot_index + 1, epoch_info.slots_in_epoch),
-            leader_schedule.as_ref(),
-            cluster_nodes.as_ref(),
-        );
-
-        // Send all transactions
+        // Send all transactions to the upcoming leaders, falling back to RPC when none of their
+        // TPU addresses are known
         let mut pending_transactions = HashMap::new();
         let num_transactions = transactions.len();
         for transaction in transactions {
-            if let Some(tpu_address) = tpu_address {
-                let wire_transaction =
-                    serialize(&transaction).expect("serialization should succeed");
-                send_transaction_tpu(&send_socket, &tpu_address, &wire_transaction);
-            } else {
+            if !tpu_client.send_transaction(&transaction) {
                 let _result = rpc_client
                     .send_transaction_with_config(
                         &transaction,
@@ -1153,6 +1154,9 @@ fn send_and_confirm_transactions_with_spinner<T: Signers>(
         }
         send_retries -= 1;
 
+        progress_bar.set_message("Finding leader nodes...");
+        tpu_client.refresh_leaders()?;
+
         // Re-sign any failed transactions with a new blockhash and retry
         let (blockhash, _fee_calculator, new_last_valid_slot) = rpc_client
             .get_recent_blockhash_with_commitment(commitment)?
@@ -1481,7 +1485,7 @@ fn process_transfer(
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers(&tx, &config.output_format)
+        return_signers_with_config(&tx, config)
     } else {
         if let Some(nonce_account) = &nonce_account {
             let nonce_account = nonce_utils::get_account_with_commitment(
@@ -1609,6 +1613,25 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *limit,
             *show_transactions,
         ),
+        CliCommand::AccountHistory {
+            address,
+            format,
+            output_file,
+            resume,
+            before,
+            until,
+            limit,
+        } => process_account_history(
+            &rpc_client,
+            config,
+            address,
+            *format,
+            output_file.as_ref(),
+            *resume,
+            *before,
+            *until,
+            *limit,
+        ),
 
         // Nonce Commands
 
@@ -1688,6 +1711,9 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *use_deprecated_loader,
             *allow_excessive_balance,
         ),
+        CliCommand::Program(program_subcommand) => {
+            process_program_subcommand(&rpc_client, config, program_subcommand)
+        }
 
         // Stake Commands
 
@@ -2015,6 +2041,7 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
         // Confirm the last client transaction by signature
         CliCommand::Confirm(signature) => process_confirm(&rpc_client, config, signature),
         CliCommand::DecodeTransaction(transaction) => process_decode_transaction(transaction),
+        CliCommand::Tx(tx_subcommand) => process_tx_subcommand(&rpc_client, config, tx_subcommand),
         CliCommand::ResolveSigner(path) => {
             if let Some(path) = path {
                 Ok(path.to_string())
@@ -2169,11 +2196,14 @@ pub fn app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> App<'ab, '
                         .help("Confirm key on device; only relevant if using remote wallet"),
                 ),
         )
+        .account_history_subcommands()
         .cluster_query_subcommands()
         .feature_subcommands()
         .inflation_subcommands()
         .nonce_subcommands()
+        .program_subcommands()
         .stake_subcommands()
+        .tx_subcommands()
         .subcommand(
             SubCommand::with_name("airdrop")
                 .about("Request lamports")
@@ -20,15 +20,17 @@ macro_rules! pubkey {
 
 extern crate serde_derive;
 
+pub mod account_history;
 pub mod checks;
 pub mod cli;
 pub mod cluster_query;
 pub mod feature;
 pub mod inflation;
 pub mod nonce;
-pub mod send_tpu;
+pub mod program;
 pub mod spend_utils;
 pub mod stake;
 pub mod test_utils;
+pub mod tx;
 pub mod validator_info;
 pub mod vote;
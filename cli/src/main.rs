@@ -9,6 +9,7 @@ use solana_clap_utils::{
     input_parsers::commitment_of,
     input_validators::is_url,
     keypair::{CliSigners, DefaultSigner, SKIP_SEED_PHRASE_VALIDATION_ARG},
+    offline::OUTPUT_TRANSACTION_ARG,
     DisplayError,
 };
 use solana_cli::cli::{
@@ -183,6 +184,12 @@ pub fn parse_args<'a>(
         .and_then(|sub_matches| commitment_of(sub_matches, COMMITMENT_ARG.long))
         .unwrap_or_default();
 
+    let output_transaction = matches
+        .subcommand_name()
+        .and_then(|name| matches.subcommand_matches(name))
+        .and_then(|sub_matches| sub_matches.value_of(OUTPUT_TRANSACTION_ARG.name))
+        .map(PathBuf::from);
+
     let address_labels = if matches.is_present("no_address_labels") {
         HashMap::new()
     } else {
@@ -203,6 +210,7 @@ pub fn parse_args<'a>(
             commitment,
             send_transaction_config: RpcSendTransactionConfig::default(),
             address_labels,
+            output_transaction,
         },
         signers,
     ))
@@ -0,0 +1,1330 @@
+use crate::{
+    checks::*,
+    cli::{
+        log_instruction_custom_error, send_and_confirm_transactions_with_spinner, CliCommand,
+        CliCommandInfo, CliConfig, CliError, ProcessResult,
+    },
+};
+use bincode::serialized_size;
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use log::*;
+use serde_json::json;
+use solana_bpf_loader_program::{bpf_verifier, BPFError, ThisInstructionMeter};
+use solana_clap_utils::{input_parsers::*, input_validators::*, keypair::*};
+use solana_cli_output::{CliProgram, CliUpgradeableBuffer};
+use solana_client::rpc_client::RpcClient;
+use solana_rbpf::vm::{Config, Executable};
+use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_sdk::{
+    account::Account,
+    account_utils::StateMut,
+    bpf_loader, bpf_loader_deprecated,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{keypair_from_seed, Keypair, Signature, Signer},
+    system_instruction::SystemError,
+    transaction::Transaction,
+};
+use std::{
+    error,
+    fs::File,
+    io::{Read, Write},
+    sync::Arc,
+};
+
+/// Number of words in the seed phrase of an ephemeral buffer keypair
+const BUFFER_SEED_PHRASE_WORDS: usize = 12;
+
+#[derive(Debug, PartialEq)]
+pub enum ProgramCliCommand {
+    Deploy {
+        program_location: String,
+        program_signer_index: Option<SignerIndex>,
+        buffer_signer_index: Option<SignerIndex>,
+        upgrade_authority_signer_index: SignerIndex,
+        max_len: Option<usize>,
+    },
+    Upgrade {
+        program_location: String,
+        program_pubkey: Pubkey,
+        buffer_signer_index: Option<SignerIndex>,
+        upgrade_authority_signer_index: SignerIndex,
+    },
+    SetUpgradeAuthority {
+        program_pubkey: Pubkey,
+        upgrade_authority_signer_index: SignerIndex,
+        new_upgrade_authority: Option<Pubkey>,
+    },
+    Show {
+        account_pubkey: Pubkey,
+    },
+    Dump {
+        account_pubkey: Pubkey,
+        output_location: String,
+    },
+}
+
+fn buffer_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("buffer")
+        .long("buffer")
+        .value_name("BUFFER_SIGNER")
+        .takes_value(true)
+        .validator(is_valid_signer)
+        .help(
+            "Intermediate buffer account to write the program data to. \
+             If the buffer already holds part of the program, for example after \
+             an aborted deploy, only the missing data is written \
+             [default: new ephemeral buffer]",
+        )
+}
+
+pub trait ProgramSubCommands {
+    fn program_subcommands(self) -> Self;
+}
+
+impl ProgramSubCommands for App<'_, '_> {
+    fn program_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("program")
+                .about("Program management")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("deploy")
+                        .about("Deploy an upgradeable program")
+                        .arg(
+                            Arg::with_name("program_location")
+                                .index(1)
+                                .value_name("PROGRAM_FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("/path/to/program.so"),
+                        )
+                        .arg(
+                            Arg::with_name("program_id")
+                                .long("program-id")
+                                .value_name("PROGRAM_ID_SIGNER")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("The signer for the desired address of the program [default: new random address]"),
+                        )
+                        .arg(buffer_arg())
+                        .arg(
+                            Arg::with_name("upgrade_authority")
+                                .long("upgrade-authority")
+                                .value_name("UPGRADE_AUTHORITY_SIGNER")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("Upgrade authority [default: the default configured keypair]"),
+                        )
+                        .arg(
+                            Arg::with_name("max_len")
+                                .long("max-len")
+                                .value_name("max_len")
+                                .takes_value(true)
+                                .required(false)
+                                .validator(is_parsable::<usize>)
+                                .help("Maximum length of the upgradeable program \
+                                      [default: twice the length of the original deployed program]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("upgrade")
+                        .about("Upgrade an upgradeable program")
+                        .arg(
+                            Arg::with_name("program_location")
+                                .index(1)
+                                .value_name("PROGRAM_FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("/path/to/program.so"),
+                        )
+                        .arg(
+                            pubkey!(Arg::with_name("program_id")
+                                .index(2)
+                                .value_name("PROGRAM_ADDRESS")
+                                .required(true),
+                                "Address of the program to upgrade. "),
+                        )
+                        .arg(buffer_arg())
+                        .arg(
+                            Arg::with_name("upgrade_authority")
+                                .long("upgrade-authority")
+                                .value_name("UPGRADE_AUTHORITY_SIGNER")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("Upgrade authority [default: the default configured keypair]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-upgrade-authority")
+                        .about("Set a new program upgrade authority")
+                        .arg(
+                            pubkey!(Arg::with_name("program_id")
+                                .index(1)
+                                .value_name("PROGRAM_ADDRESS")
+                                .required(true),
+                                "Address of the program to change the upgrade authority of. "),
+                        )
+                        .arg(
+                            Arg::with_name("upgrade_authority")
+                                .long("upgrade-authority")
+                                .value_name("UPGRADE_AUTHORITY_SIGNER")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("Current upgrade authority [default: the default configured keypair]"),
+                        )
+                        .arg(
+                            pubkey!(Arg::with_name("new_upgrade_authority")
+                                .long("new-upgrade-authority")
+                                .required_unless("final")
+                                .value_name("NEW_UPGRADE_AUTHORITY"),
+                                "Address of the new upgrade authority. "),
+                        )
+                        .arg(
+                            Arg::with_name("final")
+                                .long("final")
+                                .conflicts_with("new_upgrade_authority")
+                                .help("The program will not be upgradeable"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Display information about a program or buffer account")
+                        .arg(
+                            pubkey!(Arg::with_name("account")
+                                .index(1)
+                                .value_name("ACCOUNT_ADDRESS")
+                                .required(true),
+                                "Address of the program or buffer account to show. "),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("dump")
+                        .about("Write the program data of a program or buffer account to a file")
+                        .arg(
+                            pubkey!(Arg::with_name("account")
+                                .index(1)
+                                .value_name("ACCOUNT_ADDRESS")
+                                .required(true),
+                                "Address of the program or buffer account to dump. "),
+                        )
+                        .arg(
+                            Arg::with_name("output_location")
+                                .index(2)
+                                .value_name("OUTPUT_FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("/path/to/program.so"),
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn parse_program_subcommand(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let response = match matches.subcommand() {
+        ("deploy", Some(matches)) => {
+            let (program_signer, program_pubkey) =
+                signer_of(matches, "program_id", wallet_manager)?;
+            let (buffer_signer, buffer_pubkey) = signer_of(matches, "buffer", wallet_manager)?;
+            let (upgrade_authority, upgrade_authority_pubkey) =
+                signer_of(matches, "upgrade_authority", wallet_manager)?;
+            let max_len = value_of(matches, "max_len");
+
+            let payer_provided = None;
+            let signer_info = default_signer.generate_unique_signers(
+                vec![
+                    payer_provided,
+                    upgrade_authority,
+                    program_signer,
+                    buffer_signer,
+                ],
+                matches,
+                wallet_manager,
+            )?;
+
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Deploy {
+                    program_location: matches.value_of("program_location").unwrap().to_string(),
+                    program_signer_index: program_pubkey
+                        .and_then(|pubkey| signer_info.index_of(Some(pubkey))),
+                    buffer_signer_index: buffer_pubkey
+                        .and_then(|pubkey| signer_info.index_of(Some(pubkey))),
+                    upgrade_authority_signer_index: signer_info
+                        .index_of(upgrade_authority_pubkey)
+                        .unwrap(),
+                    max_len,
+                }),
+                signers: signer_info.signers,
+            }
+        }
+        ("upgrade", Some(matches)) => {
+            let program_pubkey = pubkey_of_signer(matches, "program_id", wallet_manager)?.unwrap();
+            let (buffer_signer, buffer_pubkey) = signer_of(matches, "buffer", wallet_manager)?;
+            let (upgrade_authority, upgrade_authority_pubkey) =
+                signer_of(matches, "upgrade_authority", wallet_manager)?;
+
+            let payer_provided = None;
+            let signer_info = default_signer.generate_unique_signers(
+                vec![payer_provided, upgrade_authority, buffer_signer],
+                matches,
+                wallet_manager,
+            )?;
+
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Upgrade {
+                    program_location: matches.value_of("program_location").unwrap().to_string(),
+                    program_pubkey,
+                    buffer_signer_index: buffer_pubkey
+                        .and_then(|pubkey| signer_info.index_of(Some(pubkey))),
+                    upgrade_authority_signer_index: signer_info
+                        .index_of(upgrade_authority_pubkey)
+                        .unwrap(),
+                }),
+                signers: signer_info.signers,
+            }
+        }
+        ("set-upgrade-authority", Some(matches)) => {
+            let program_pubkey = pubkey_of_signer(matches, "program_id", wallet_manager)?.unwrap();
+            let (upgrade_authority, upgrade_authority_pubkey) =
+                signer_of(matches, "upgrade_authority", wallet_manager)?;
+            let new_upgrade_authority =
+                pubkey_of_signer(matches, "new_upgrade_authority", wallet_manager)?;
+
+            let payer_provided = None;
+            let signer_info = default_signer.generate_unique_signers(
+                vec![payer_provided, upgrade_authority],
+                matches,
+                wallet_manager,
+            )?;
+
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
+                    program_pubkey,
+                    upgrade_authority_signer_index: signer_info
+                        .index_of(upgrade_authority_pubkey)
+                        .unwrap(),
+                    new_upgrade_authority,
+                }),
+                signers: signer_info.signers,
+            }
+        }
+        ("show", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Program(ProgramCliCommand::Show {
+                account_pubkey: pubkey_of_signer(matches, "account", wallet_manager)?.unwrap(),
+            }),
+            signers: vec![],
+        },
+        ("dump", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Program(ProgramCliCommand::Dump {
+                account_pubkey: pubkey_of_signer(matches, "account", wallet_manager)?.unwrap(),
+                output_location: matches.value_of("output_location").unwrap().to_string(),
+            }),
+            signers: vec![],
+        },
+        _ => unreachable!(),
+    };
+    Ok(response)
+}
+
+pub fn process_program_subcommand(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_subcommand: &ProgramCliCommand,
+) -> ProcessResult {
+    match program_subcommand {
+        ProgramCliCommand::Deploy {
+            program_location,
+            program_signer_index,
+            buffer_signer_index,
+            upgrade_authority_signer_index,
+            max_len,
+        } => process_program_deploy(
+            rpc_client,
+            config,
+            program_location,
+            *program_signer_index,
+            *buffer_signer_index,
+            *upgrade_authority_signer_index,
+            *max_len,
+        ),
+        ProgramCliCommand::Upgrade {
+            program_location,
+            program_pubkey,
+            buffer_signer_index,
+            upgrade_authority_signer_index,
+        } => process_program_upgrade(
+            rpc_client,
+            config,
+            program_location,
+            program_pubkey,
+            *buffer_signer_index,
+            *upgrade_authority_signer_index,
+        ),
+        ProgramCliCommand::SetUpgradeAuthority {
+            program_pubkey,
+            upgrade_authority_signer_index,
+            new_upgrade_authority,
+        } => process_set_upgrade_authority(
+            rpc_client,
+            config,
+            program_pubkey,
+            *upgrade_authority_signer_index,
+            new_upgrade_authority.as_ref(),
+        ),
+        ProgramCliCommand::Show { account_pubkey } => {
+            process_program_show(rpc_client, config, account_pubkey)
+        }
+        ProgramCliCommand::Dump {
+            account_pubkey,
+            output_location,
+        } => process_program_dump(rpc_client, config, account_pubkey, output_location),
+    }
+}
+
+fn process_program_deploy(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_location: &str,
+    program_signer_index: Option<SignerIndex>,
+    buffer_signer_index: Option<SignerIndex>,
+    upgrade_authority_signer_index: SignerIndex,
+    max_len: Option<usize>,
+) -> ProcessResult {
+    let program_data = read_and_verify_elf(program_location)?;
+    let max_len = max_len.unwrap_or_else(|| program_data.len() * 2);
+    if max_len < program_data.len() {
+        return Err(CliError::BadParameter(format!(
+            "Maximum length {} is smaller than the program length {}",
+            max_len,
+            program_data.len()
+        ))
+        .into());
+    }
+
+    // Create ephemeral keypair to use for program address, if not provided
+    let new_program_keypair = Keypair::new();
+    let program_signer = if let Some(i) = program_signer_index {
+        config.signers[i]
+    } else {
+        &new_program_keypair
+    };
+    if rpc_client
+        .get_account_with_commitment(&program_signer.pubkey(), config.commitment)?
+        .value
+        .is_some()
+    {
+        return Err(CliError::DynamicProgramError(format!(
+            "Program account {} already exists",
+            program_signer.pubkey()
+        ))
+        .into());
+    }
+    let payer = config.signers[0];
+    let upgrade_authority = config.signers[upgrade_authority_signer_index];
+    let (buffer_mnemonic, ephemeral_buffer_keypair) = new_ephemeral_buffer_keypair()?;
+    let buffer_signer = if let Some(i) = buffer_signer_index {
+        config.signers[i]
+    } else {
+        &ephemeral_buffer_keypair
+    };
+
+    let program_lamports =
+        rpc_client.get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::program_len())?;
+    let programdata_lamports = rpc_client
+        .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::programdata_len(max_len))?;
+    let deploy_message = Message::new(
+        &bpf_loader_upgradeable::deploy_with_max_program_len(
+            &payer.pubkey(),
+            &program_signer.pubkey(),
+            &buffer_signer.pubkey(),
+            &upgrade_authority.pubkey(),
+            program_lamports,
+            max_len,
+        ),
+        Some(&payer.pubkey()),
+    );
+
+    let result = write_buffer_and_finalize(
+        rpc_client,
+        config,
+        &program_data,
+        buffer_signer,
+        upgrade_authority,
+        deploy_message,
+        &[payer, program_signer, upgrade_authority],
+        program_lamports + programdata_lamports,
+    );
+    if result.is_err() && buffer_signer_index.is_none() {
+        report_ephemeral_buffer(&buffer_mnemonic, "deploy");
+    }
+    result.map_err(|err| {
+        CliError::DynamicProgramError(format!("Deploying program failed: {}", err))
+    })?;
+
+    Ok(json!({
+        "programId": format!("{}", program_signer.pubkey()),
+    })
+    .to_string())
+}
+
+fn process_program_upgrade(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_location: &str,
+    program_pubkey: &Pubkey,
+    buffer_signer_index: Option<SignerIndex>,
+    upgrade_authority_signer_index: SignerIndex,
+) -> ProcessResult {
+    let program_data = read_and_verify_elf(program_location)?;
+
+    let program_account = rpc_client
+        .get_account_with_commitment(program_pubkey, config.commitment)?
+        .value
+        .ok_or_else(|| {
+            CliError::DynamicProgramError(format!("Program {} not found", program_pubkey))
+        })?;
+    if program_account.owner != bpf_loader_upgradeable::id() || !program_account.executable {
+        return Err(CliError::DynamicProgramError(format!(
+            "{} is not an upgradeable program",
+            program_pubkey
+        ))
+        .into());
+    }
+
+    let payer = config.signers[0];
+    let upgrade_authority = config.signers[upgrade_authority_signer_index];
+    let (buffer_mnemonic, ephemeral_buffer_keypair) = new_ephemeral_buffer_keypair()?;
+    let buffer_signer = if let Some(i) = buffer_signer_index {
+        config.signers[i]
+    } else {
+        &ephemeral_buffer_keypair
+    };
+    let upgrade_message = Message::new(
+        &[bpf_loader_upgradeable::upgrade(
+            program_pubkey,
+            &buffer_signer.pubkey(),
+            &upgrade_authority.pubkey(),
+            &payer.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+
+    let result = write_buffer_and_finalize(
+        rpc_client,
+        config,
+        &program_data,
+        buffer_signer,
+        upgrade_authority,
+        upgrade_message,
+        &[payer, upgrade_authority],
+        0,
+    );
+    if result.is_err() && buffer_signer_index.is_none() {
+        report_ephemeral_buffer(&buffer_mnemonic, "upgrade");
+    }
+    result.map_err(|err| {
+        CliError::DynamicProgramError(format!("Upgrading program failed: {}", err))
+    })?;
+
+    Ok(json!({
+        "programId": format!("{}", program_pubkey),
+    })
+    .to_string())
+}
+
+fn process_set_upgrade_authority(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: &Pubkey,
+    upgrade_authority_signer_index: SignerIndex,
+    new_upgrade_authority: Option<&Pubkey>,
+) -> ProcessResult {
+    let upgrade_authority = config.signers[upgrade_authority_signer_index];
+
+    let programdata_account = rpc_client
+        .get_account_with_commitment(
+            &bpf_loader_upgradeable::get_program_data_address(program_pubkey),
+            config.commitment,
+        )?
+        .value;
+    match programdata_account.map(|account| account.state()) {
+        Some(Ok(UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address: Some(upgrade_authority_address),
+            ..
+        })) => {
+            if upgrade_authority_address != upgrade_authority.pubkey() {
+                return Err(CliError::BadParameter(format!(
+                    "Upgrade authority of {} is {}",
+                    program_pubkey, upgrade_authority_address
+                ))
+                .into());
+            }
+        }
+        Some(Ok(UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address: None,
+            ..
+        })) => {
+            return Err(CliError::BadParameter(format!(
+                "Program {} is not upgradeable",
+                program_pubkey
+            ))
+            .into());
+        }
+        _ => {
+            return Err(CliError::DynamicProgramError(format!(
+                "{} is not an upgradeable program",
+                program_pubkey
+            ))
+            .into());
+        }
+    }
+
+    let (blockhash, fee_calculator, _) = rpc_client
+        .get_recent_blockhash_with_commitment(config.commitment)?
+        .value;
+    let message = Message::new(
+        &[bpf_loader_upgradeable::set_upgrade_authority(
+            program_pubkey,
+            &upgrade_authority.pubkey(),
+            new_upgrade_authority,
+        )],
+        Some(&config.signers[0].pubkey()),
+    );
+    let mut tx = Transaction::new_unsigned(message);
+    tx.try_sign(&[config.signers[0], upgrade_authority], blockhash)?;
+    check_account_for_fee_with_commitment(
+        rpc_client,
+        &config.signers[0].pubkey(),
+        &fee_calculator,
+        &tx.message,
+        config.commitment,
+    )?;
+    rpc_client
+        .send_and_confirm_transaction_with_spinner_and_config(
+            &tx,
+            config.commitment,
+            config.send_transaction_config,
+        )
+        .map_err(|e| {
+            CliError::DynamicProgramError(format!("Setting upgrade authority failed: {}", e))
+        })?;
+
+    Ok(json!({
+        "upgradeAuthority": new_upgrade_authority
+            .map(|pubkey| pubkey.to_string())
+            .unwrap_or_else(|| "None".to_string()),
+    })
+    .to_string())
+}
+
+fn process_program_show(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    account_pubkey: &Pubkey,
+) -> ProcessResult {
+    let account = get_account(rpc_client, config, account_pubkey)?;
+    if account.owner == bpf_loader::id() || account.owner == bpf_loader_deprecated::id() {
+        return Ok(config.output_format.formatted_string(&CliProgram {
+            program_id: account_pubkey.to_string(),
+            owner: account.owner.to_string(),
+            programdata_address: None,
+            authority: None,
+            last_deploy_slot: None,
+            data_len: account.data.len(),
+        }));
+    }
+    if account.owner != bpf_loader_upgradeable::id() {
+        return Err(CliError::DynamicProgramError(format!(
+            "{} is not owned by a BPF loader",
+            account_pubkey
+        ))
+        .into());
+    }
+
+    match account.state() {
+        Ok(UpgradeableLoaderState::Program {
+            programdata_address,
+        }) => {
+            let programdata_account = get_account(rpc_client, config, &programdata_address)?;
+            if let Ok(UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            }) = programdata_account.state()
+            {
+                Ok(config.output_format.formatted_string(&CliProgram {
+                    program_id: account_pubkey.to_string(),
+                    owner: account.owner.to_string(),
+                    programdata_address: Some(programdata_address.to_string()),
+                    authority: upgrade_authority_address.map(|pubkey| pubkey.to_string()),
+                    last_deploy_slot: Some(slot),
+                    data_len: programdata_account
+                        .data
+                        .len()
+                        .saturating_sub(UpgradeableLoaderState::programdata_data_offset()),
+                }))
+            } else {
+                Err(CliError::DynamicProgramError(format!(
+                    "Invalid ProgramData account {} for program {}",
+                    programdata_address, account_pubkey
+                ))
+                .into())
+            }
+        }
+        Ok(UpgradeableLoaderState::Buffer { authority_address }) => Ok(config
+            .output_format
+            .formatted_string(&CliUpgradeableBuffer {
+                address: account_pubkey.to_string(),
+                authority: authority_address.map(|pubkey| pubkey.to_string()),
+                data_len: account
+                    .data
+                    .len()
+                    .saturating_sub(UpgradeableLoaderState::buffer_data_offset()),
+            })),
+        _ => Err(CliError::DynamicProgramError(format!(
+            "{} is not a program or buffer account",
+            account_pubkey
+        ))
+        .into()),
+    }
+}
+
+fn process_program_dump(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    account_pubkey: &Pubkey,
+    output_location: &str,
+) -> ProcessResult {
+    let account = get_account(rpc_client, config, account_pubkey)?;
+    let program_data = if account.owner == bpf_loader::id()
+        || account.owner == bpf_loader_deprecated::id()
+    {
+        account.data
+    } else if account.owner == bpf_loader_upgradeable::id() {
+        match account.state() {
+            Ok(UpgradeableLoaderState::Program {
+                programdata_address,
+            }) => {
+                let programdata_account = get_account(rpc_client, config, &programdata_address)?;
+                if let Ok(UpgradeableLoaderState::ProgramData { .. }) = programdata_account.state()
+                {
+                    programdata_account.data[UpgradeableLoaderState::programdata_data_offset()..]
+                        .to_vec()
+                } else {
+                    return Err(CliError::DynamicProgramError(format!(
+                        "Invalid ProgramData account {} for program {}",
+                        programdata_address, account_pubkey
+                    ))
+                    .into());
+                }
+            }
+            Ok(UpgradeableLoaderState::Buffer { .. }) => {
+                account.data[UpgradeableLoaderState::buffer_data_offset()..].to_vec()
+            }
+            _ => {
+                return Err(CliError::DynamicProgramError(format!(
+                    "{} is not a program or buffer account",
+                    account_pubkey
+                ))
+                .into());
+            }
+        }
+    } else {
+        return Err(CliError::DynamicProgramError(format!(
+            "{} is not owned by a BPF loader",
+            account_pubkey
+        ))
+        .into());
+    };
+
+    let mut file = File::create(output_location).map_err(|err| {
+        CliError::DynamicProgramError(format!("Unable to create output file: {}", err))
+    })?;
+    file.write_all(&program_data).map_err(|err| {
+        CliError::DynamicProgramError(format!("Unable to write output file: {}", err))
+    })?;
+
+    Ok(format!(
+        "Wrote program to {} ({} bytes)",
+        output_location,
+        program_data.len()
+    ))
+}
+
+fn get_account(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    account_pubkey: &Pubkey,
+) -> Result<Account, Box<dyn error::Error>> {
+    rpc_client
+        .get_account_with_commitment(account_pubkey, config.commitment)?
+        .value
+        .ok_or_else(|| {
+            CliError::DynamicProgramError(format!("Account {} not found", account_pubkey)).into()
+        })
+}
+
+/// Generate the keypair of a new buffer account from a fresh seed phrase, so
+/// that a buffer left behind by an aborted deploy can be recovered
+fn new_ephemeral_buffer_keypair() -> Result<(Mnemonic, Keypair), Box<dyn error::Error>> {
+    let mnemonic = Mnemonic::new(
+        MnemonicType::for_word_count(BUFFER_SEED_PHRASE_WORDS)?,
+        Language::English,
+    );
+    let seed = Seed::new(&mnemonic, "");
+    let keypair = keypair_from_seed(seed.as_bytes())?;
+    Ok((mnemonic, keypair))
+}
+
+fn report_ephemeral_buffer(mnemonic: &Mnemonic, subcommand: &str) {
+    let phrase: &str = mnemonic.phrase();
+    let divider = String::from_utf8(vec![b'='; phrase.len()]).unwrap();
+    eprintln!(
+        "{}\nTo resume the {} without rewriting the data already in the buffer account,",
+        divider, subcommand
+    );
+    eprintln!(
+        "recover the ephemeral buffer keypair file with `solana-keygen recover` and the following"
+    );
+    eprintln!(
+        "{}-word seed phrase, then pass it as the --buffer argument to `solana program {} ...`\n{}\n{}\n{}",
+        BUFFER_SEED_PHRASE_WORDS, subcommand, divider, phrase, divider
+    );
+}
+
+/// Return the program data already staged in the buffer account, or `None` if
+/// the buffer has not been created yet.  An existing buffer must belong to
+/// `buffer_authority` and be sized for a program of `program_len` bytes.
+fn get_staged_program_data(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    buffer_pubkey: &Pubkey,
+    buffer_authority: &Pubkey,
+    program_len: usize,
+) -> Result<Option<Vec<u8>>, Box<dyn error::Error>> {
+    let account = match rpc_client
+        .get_account_with_commitment(buffer_pubkey, config.commitment)?
+        .value
+    {
+        Some(account) => account,
+        None => return Ok(None),
+    };
+    if account.owner != bpf_loader_upgradeable::id() {
+        return Err(CliError::BadParameter(format!(
+            "Buffer account {} is not owned by the upgradeable loader",
+            buffer_pubkey
+        ))
+        .into());
+    }
+    match account.state() {
+        Ok(UpgradeableLoaderState::Buffer { authority_address }) => {
+            if authority_address != Some(*buffer_authority) {
+                return Err(CliError::BadParameter(format!(
+                    "Buffer authority of {} is {}",
+                    buffer_pubkey,
+                    authority_address
+                        .map(|pubkey| pubkey.to_string())
+                        .unwrap_or_else(|| "None".to_string())
+                ))
+                .into());
+            }
+        }
+        _ => {
+            return Err(CliError::BadParameter(format!(
+                "{} is not a buffer account",
+                buffer_pubkey
+            ))
+            .into());
+        }
+    }
+    if account.data.len() != UpgradeableLoaderState::buffer_len(program_len) {
+        return Err(CliError::BadParameter(format!(
+            "Buffer account {} does not fit a program of {} bytes",
+            buffer_pubkey, program_len
+        ))
+        .into());
+    }
+    Ok(Some(
+        account.data[UpgradeableLoaderState::buffer_data_offset()..].to_vec(),
+    ))
+}
+
+fn read_and_verify_elf(program_location: &str) -> Result<Vec<u8>, Box<dyn error::Error>> {
+    let mut file = File::open(program_location).map_err(|err| {
+        CliError::DynamicProgramError(format!("Unable to open program file: {}", err))
+    })?;
+    let mut program_data = Vec::new();
+    file.read_to_end(&mut program_data).map_err(|err| {
+        CliError::DynamicProgramError(format!("Unable to read program file: {}", err))
+    })?;
+
+    Executable::<BPFError, ThisInstructionMeter>::from_elf(
+        &program_data,
+        Some(|x| bpf_verifier::check(x, false)),
+        Config::default(),
+    )
+    .map_err(|err| CliError::DynamicProgramError(format!("ELF error: {}", err)))?;
+
+    Ok(program_data)
+}
+
+/// Largest chunk of program data that keeps a transaction built by
+/// `create_msg` within a single packet
+fn calculate_max_chunk_size<F>(create_msg: &F) -> usize
+where
+    F: Fn(u32, Vec<u8>) -> Message,
+{
+    let baseline_msg = create_msg(0, Vec::new());
+    let tx_size = serialized_size(&Transaction {
+        signatures: vec![
+            Signature::default();
+            baseline_msg.header.num_required_signatures as usize
+        ],
+        message: baseline_msg,
+    })
+    .unwrap() as usize;
+    // Leave a byte for the growth of the data length's shortvec encoding
+    PACKET_DATA_SIZE.saturating_sub(tx_size).saturating_sub(1)
+}
+
+/// Stage `program_data` in a Buffer account, then send `final_message`, which
+/// consumes the buffer.  If the buffer already exists, only the chunks that
+/// differ from `program_data` are rewritten.
+#[allow(clippy::too_many_arguments)]
+fn write_buffer_and_finalize(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_data: &[u8],
+    buffer_signer: &dyn Signer,
+    buffer_authority: &dyn Signer,
+    final_message: Message,
+    final_signers: &[&dyn Signer],
+    additional_balance: u64,
+) -> Result<(), Box<dyn error::Error>> {
+    let payer = config.signers[0];
+    let buffer_lamports = rpc_client.get_minimum_balance_for_rent_exemption(
+        UpgradeableLoaderState::buffer_len(program_data.len()),
+    )?;
+
+    let staged_program_data = get_staged_program_data(
+        rpc_client,
+        config,
+        &buffer_signer.pubkey(),
+        &buffer_authority.pubkey(),
+        program_data.len(),
+    )?;
+    let create_buffer_message = if staged_program_data.is_none() {
+        Some(Message::new(
+            &bpf_loader_upgradeable::create_buffer(
+                &payer.pubkey(),
+                &buffer_signer.pubkey(),
+                &buffer_authority.pubkey(),
+                buffer_lamports,
+                program_data.len(),
+            ),
+            Some(&payer.pubkey()),
+        ))
+    } else {
+        None
+    };
+
+    let create_write_message = |offset: u32, bytes: Vec<u8>| {
+        Message::new(
+            &[bpf_loader_upgradeable::write(
+                &buffer_signer.pubkey(),
+                &buffer_authority.pubkey(),
+                offset,
+                bytes,
+            )],
+            Some(&payer.pubkey()),
+        )
+    };
+    let chunk_size = calculate_max_chunk_size(&create_write_message);
+    let write_messages = program_data
+        .chunks(chunk_size)
+        .enumerate()
+        .filter(|(i, chunk)| {
+            staged_program_data
+                .as_ref()
+                .map(|staged| {
+                    let offset = i * chunk_size;
+                    staged[offset..offset + chunk.len()] != **chunk
+                })
+                .unwrap_or(true)
+        })
+        .map(|(i, chunk)| create_write_message((i * chunk_size) as u32, chunk.to_vec()))
+        .collect::<Vec<_>>();
+
+    let mut messages = create_buffer_message.iter().collect::<Vec<_>>();
+    messages.extend(write_messages.iter());
+    messages.push(&final_message);
+
+    let (blockhash, fee_calculator, _) = rpc_client
+        .get_recent_blockhash_with_commitment(config.commitment)?
+        .value;
+    check_account_for_spend_multiple_fees_with_commitment(
+        rpc_client,
+        &payer.pubkey(),
+        if create_buffer_message.is_some() {
+            buffer_lamports
+        } else {
+            0
+        } + additional_balance,
+        &fee_calculator,
+        &messages,
+        config.commitment,
+    )?;
+
+    if let Some(create_buffer_message) = create_buffer_message {
+        trace!("Creating buffer account");
+        let mut create_buffer_tx = Transaction::new_unsigned(create_buffer_message);
+        create_buffer_tx.try_sign(&[payer, buffer_signer], blockhash)?;
+        let result = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
+            &create_buffer_tx,
+            config.commitment,
+            config.send_transaction_config,
+        );
+        log_instruction_custom_error::<SystemError>(result, &config).map_err(|err| {
+            CliError::DynamicProgramError(format!("Buffer account allocation failed: {}", err))
+        })?;
+    } else {
+        trace!(
+            "Resuming buffer account {}, {} chunks left to write",
+            buffer_signer.pubkey(),
+            write_messages.len()
+        );
+    }
+
+    let (blockhash, _, last_valid_slot) = rpc_client
+        .get_recent_blockhash_with_commitment(config.commitment)?
+        .value;
+    let write_signers = [payer, buffer_authority];
+    let mut write_transactions = vec![];
+    for message in write_messages.into_iter() {
+        let mut tx = Transaction::new_unsigned(message);
+        tx.try_sign(&write_signers, blockhash)?;
+        write_transactions.push(tx);
+    }
+
+    if !write_transactions.is_empty() {
+        trace!("Writing program data");
+        send_and_confirm_transactions_with_spinner(
+            &rpc_client,
+            write_transactions,
+            &write_signers,
+            config.commitment,
+            last_valid_slot,
+        )
+        .map_err(|err| {
+            CliError::DynamicProgramError(format!("Data writes to buffer account failed: {}", err))
+        })?;
+    }
+
+    let (blockhash, _, _) = rpc_client
+        .get_recent_blockhash_with_commitment(config.commitment)?
+        .value;
+    let mut final_tx = Transaction::new_unsigned(final_message);
+    final_tx.try_sign(&final_signers.to_vec(), blockhash)?;
+
+    trace!("Consuming buffer account");
+    rpc_client.send_and_confirm_transaction_with_spinner_and_config(
+        &final_tx,
+        config.commitment,
+        config.send_transaction_config,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{app, parse_command};
+    use solana_sdk::signature::{read_keypair_file, write_keypair};
+    use tempfile::NamedTempFile;
+
+    fn make_tmp_file() -> (String, NamedTempFile) {
+        let tmp_file = NamedTempFile::new().unwrap();
+        (String::from(tmp_file.path().to_str().unwrap()), tmp_file)
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let test_commands = app("test", "desc", "version");
+
+        let default_keypair = Keypair::new();
+        let (default_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&default_keypair, tmp_file.as_file_mut()).unwrap();
+        let default_signer = DefaultSigner {
+            path: default_keypair_file.clone(),
+            arg_name: String::new(),
+        };
+
+        let program_keypair = Keypair::new();
+        let (program_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&program_keypair, tmp_file.as_file_mut()).unwrap();
+        let authority_keypair = Keypair::new();
+        let (authority_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&authority_keypair, tmp_file.as_file_mut()).unwrap();
+        let buffer_keypair = Keypair::new();
+        let (buffer_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&buffer_keypair, tmp_file.as_file_mut()).unwrap();
+
+        // Test Deploy Subcommand
+        let test_deploy = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "deploy",
+            "/Users/test/program.so",
+        ]);
+        assert_eq!(
+            parse_command(&test_deploy, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Deploy {
+                    program_location: "/Users/test/program.so".to_string(),
+                    program_signer_index: None,
+                    buffer_signer_index: None,
+                    upgrade_authority_signer_index: 0,
+                    max_len: None,
+                }),
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
+            }
+        );
+
+        let test_deploy = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "deploy",
+            "/Users/test/program.so",
+            "--program-id",
+            &program_keypair_file,
+            "--upgrade-authority",
+            &authority_keypair_file,
+            "--max-len",
+            "42",
+        ]);
+        assert_eq!(
+            parse_command(&test_deploy, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Deploy {
+                    program_location: "/Users/test/program.so".to_string(),
+                    program_signer_index: Some(2),
+                    buffer_signer_index: None,
+                    upgrade_authority_signer_index: 1,
+                    max_len: Some(42),
+                }),
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into(),
+                    read_keypair_file(&program_keypair_file).unwrap().into(),
+                ],
+            }
+        );
+
+        let test_deploy = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "deploy",
+            "/Users/test/program.so",
+            "--buffer",
+            &buffer_keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_deploy, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Deploy {
+                    program_location: "/Users/test/program.so".to_string(),
+                    program_signer_index: None,
+                    buffer_signer_index: Some(1),
+                    upgrade_authority_signer_index: 0,
+                    max_len: None,
+                }),
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&buffer_keypair_file).unwrap().into(),
+                ],
+            }
+        );
+
+        // Test Upgrade Subcommand
+        let program_pubkey = program_keypair.pubkey();
+        let test_upgrade = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "upgrade",
+            "/Users/test/program.so",
+            &program_pubkey.to_string(),
+            "--upgrade-authority",
+            &authority_keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_upgrade, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Upgrade {
+                    program_location: "/Users/test/program.so".to_string(),
+                    program_pubkey,
+                    buffer_signer_index: None,
+                    upgrade_authority_signer_index: 1,
+                }),
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into(),
+                ],
+            }
+        );
+
+        let test_upgrade = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "upgrade",
+            "/Users/test/program.so",
+            &program_pubkey.to_string(),
+            "--upgrade-authority",
+            &authority_keypair_file,
+            "--buffer",
+            &buffer_keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_upgrade, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Upgrade {
+                    program_location: "/Users/test/program.so".to_string(),
+                    program_pubkey,
+                    buffer_signer_index: Some(2),
+                    upgrade_authority_signer_index: 1,
+                }),
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into(),
+                    read_keypair_file(&buffer_keypair_file).unwrap().into(),
+                ],
+            }
+        );
+
+        // Test SetUpgradeAuthority Subcommand
+        let new_authority_pubkey = Pubkey::new_unique();
+        let test_set_upgrade_authority = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "set-upgrade-authority",
+            &program_pubkey.to_string(),
+            "--new-upgrade-authority",
+            &new_authority_pubkey.to_string(),
+        ]);
+        assert_eq!(
+            parse_command(&test_set_upgrade_authority, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
+                    program_pubkey,
+                    upgrade_authority_signer_index: 0,
+                    new_upgrade_authority: Some(new_authority_pubkey),
+                }),
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
+            }
+        );
+
+        let test_set_upgrade_authority = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "set-upgrade-authority",
+            &program_pubkey.to_string(),
+            "--upgrade-authority",
+            &authority_keypair_file,
+            "--final",
+        ]);
+        assert_eq!(
+            parse_command(&test_set_upgrade_authority, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
+                    program_pubkey,
+                    upgrade_authority_signer_index: 1,
+                    new_upgrade_authority: None,
+                }),
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into(),
+                ],
+            }
+        );
+
+        // Test Show Subcommand
+        let test_show = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "show",
+            &program_pubkey.to_string(),
+        ]);
+        assert_eq!(
+            parse_command(&test_show, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Show {
+                    account_pubkey: program_pubkey,
+                }),
+                signers: vec![],
+            }
+        );
+
+        // Test Dump Subcommand
+        let test_dump = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "dump",
+            &program_pubkey.to_string(),
+            "/Users/test/dump.so",
+        ]);
+        assert_eq!(
+            parse_command(&test_dump, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Dump {
+                    account_pubkey: program_pubkey,
+                    output_location: "/Users/test/dump.so".to_string(),
+                }),
+                signers: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_ephemeral_buffer_keypair_recovery() {
+        let (mnemonic, keypair) = new_ephemeral_buffer_keypair().unwrap();
+        assert_eq!(
+            mnemonic.phrase().split_whitespace().count(),
+            BUFFER_SEED_PHRASE_WORDS
+        );
+        let recovered_mnemonic =
+            Mnemonic::from_phrase(mnemonic.phrase(), Language::English).unwrap();
+        let seed = Seed::new(&recovered_mnemonic, "");
+        assert_eq!(
+            keypair_from_seed(seed.as_bytes()).unwrap().pubkey(),
+            keypair.pubkey()
+        );
+    }
+
+    #[test]
+    fn test_calculate_max_chunk_size() {
+        let payer = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let create_msg = |offset: u32, bytes: Vec<u8>| {
+            Message::new(
+                &[bpf_loader_upgradeable::write(
+                    &buffer, &authority, offset, bytes,
+                )],
+                Some(&payer),
+            )
+        };
+        let chunk_size = calculate_max_chunk_size(&create_msg);
+        let message = create_msg(0, vec![0; chunk_size]);
+        let tx = Transaction {
+            signatures: vec![Signature::default(); message.header.num_required_signatures as usize],
+            message,
+        };
+        assert!(serialized_size(&tx).unwrap() as usize <= PACKET_DATA_SIZE);
+    }
+}
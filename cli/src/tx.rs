@@ -0,0 +1,380 @@
+use crate::cli::{
+    log_instruction_custom_error, CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult,
+};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use solana_clap_utils::keypair::DefaultSigner;
+use solana_cli_output::{display::println_transaction, return_signers, OutputFormat};
+use solana_client::{
+    partially_signed_transaction::PartiallySignedTransaction, rpc_client::RpcClient,
+};
+use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_sdk::{
+    signature::{Signature, Signer},
+    system_instruction::SystemError,
+    transaction::{uses_durable_nonce, Transaction},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum TxCliCommand {
+    Sign {
+        file: PathBuf,
+        output_file: Option<PathBuf>,
+    },
+    Combine {
+        files: Vec<PathBuf>,
+        output_file: PathBuf,
+    },
+    Inspect {
+        file: PathBuf,
+    },
+    Submit {
+        file: PathBuf,
+    },
+}
+
+/// Same as `return_signers`, but also writes the transaction to the `--output-transaction`
+/// file when one was given
+pub fn return_signers_with_config(tx: &Transaction, config: &CliConfig) -> ProcessResult {
+    if let Some(output_transaction) = &config.output_transaction {
+        PartiallySignedTransaction::from(tx).write(output_transaction)?;
+    }
+    return_signers(tx, &config.output_format)
+}
+
+pub trait TxSubCommands {
+    fn tx_subcommands(self) -> Self;
+}
+
+impl TxSubCommands for App<'_, '_> {
+    fn tx_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("tx")
+                .about("Partially signed transaction file management")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("sign")
+                        .about("Add the default signer's signature to a transaction file")
+                        .arg(
+                            Arg::with_name("file")
+                                .index(1)
+                                .value_name("FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Partially signed transaction file"),
+                        )
+                        .arg(
+                            Arg::with_name("output_file")
+                                .long("output")
+                                .short("o")
+                                .value_name("FILEPATH")
+                                .takes_value(true)
+                                .help("Write the signed transaction here [default: update FILEPATH in place]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("combine")
+                        .about("Combine the signatures of transaction files carrying the same message")
+                        .arg(
+                            Arg::with_name("files")
+                                .index(1)
+                                .value_name("FILEPATH")
+                                .takes_value(true)
+                                .multiple(true)
+                                .min_values(2)
+                                .required(true)
+                                .help("Partially signed transaction files"),
+                        )
+                        .arg(
+                            Arg::with_name("output_file")
+                                .long("output")
+                                .short("o")
+                                .value_name("FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Write the combined transaction here"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("inspect")
+                        .about("Display a transaction file and its signature status")
+                        .arg(
+                            Arg::with_name("file")
+                                .index(1)
+                                .value_name("FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Partially signed transaction file"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("submit")
+                        .about("Submit a fully signed transaction file to the cluster")
+                        .arg(
+                            Arg::with_name("file")
+                                .index(1)
+                                .value_name("FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Signed transaction file"),
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn parse_tx_subcommand(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let response = match matches.subcommand() {
+        ("sign", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Tx(TxCliCommand::Sign {
+                file: PathBuf::from(matches.value_of("file").unwrap()),
+                output_file: matches.value_of("output_file").map(PathBuf::from),
+            }),
+            signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+        },
+        ("combine", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Tx(TxCliCommand::Combine {
+                files: matches
+                    .values_of("files")
+                    .unwrap()
+                    .map(PathBuf::from)
+                    .collect(),
+                output_file: PathBuf::from(matches.value_of("output_file").unwrap()),
+            }),
+            signers: vec![],
+        },
+        ("inspect", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Tx(TxCliCommand::Inspect {
+                file: PathBuf::from(matches.value_of("file").unwrap()),
+            }),
+            signers: vec![],
+        },
+        ("submit", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Tx(TxCliCommand::Submit {
+                file: PathBuf::from(matches.value_of("file").unwrap()),
+            }),
+            signers: vec![],
+        },
+        _ => unreachable!(),
+    };
+    Ok(response)
+}
+
+pub fn process_tx_subcommand(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    tx_subcommand: &TxCliCommand,
+) -> ProcessResult {
+    match tx_subcommand {
+        TxCliCommand::Sign { file, output_file } => {
+            process_sign(config, file, output_file.as_ref().unwrap_or(file))
+        }
+        TxCliCommand::Combine { files, output_file } => process_combine(config, files, output_file),
+        TxCliCommand::Inspect { file } => process_inspect(config, file),
+        TxCliCommand::Submit { file } => process_submit(rpc_client, config, file),
+    }
+}
+
+fn process_sign(config: &CliConfig, file: &Path, output_file: &Path) -> ProcessResult {
+    let mut tx = PartiallySignedTransaction::read(file)?.to_transaction()?;
+    let recent_blockhash = tx.message.recent_blockhash;
+    tx.try_partial_sign(&config.signers, recent_blockhash)
+        .map_err(|err| {
+            CliError::BadParameter(format!(
+                "{} is not a signer of this transaction: {}",
+                config.signers[0].pubkey(),
+                err
+            ))
+        })?;
+    PartiallySignedTransaction::from(&tx).write(output_file)?;
+    return_signers(&tx, &config.output_format)
+}
+
+fn process_combine(config: &CliConfig, files: &[PathBuf], output_file: &Path) -> ProcessResult {
+    let mut combined = PartiallySignedTransaction::read(&files[0])?;
+    for file in &files[1..] {
+        combined.combine(&PartiallySignedTransaction::read(file)?)?;
+    }
+    let tx = combined.to_transaction()?;
+    combined.write(output_file)?;
+    return_signers(&tx, &config.output_format)
+}
+
+fn process_inspect(config: &CliConfig, file: &Path) -> ProcessResult {
+    // Signatures that do not verify are listed as bad rather than failing the whole inspection
+    let tx = PartiallySignedTransaction::read(file)?.to_unverified_transaction()?;
+    if config.output_format == OutputFormat::Display {
+        println_transaction(&tx, &None, "");
+        if uses_durable_nonce(&tx).is_some() {
+            println!("Uses a durable nonce; the blockhash above is the nonce value");
+        }
+    }
+    return_signers(&tx, &config.output_format)
+}
+
+fn process_submit(rpc_client: &RpcClient, config: &CliConfig, file: &Path) -> ProcessResult {
+    let tx = PartiallySignedTransaction::read(file)?.to_transaction()?;
+    let absent_signers: Vec<String> = tx
+        .signatures
+        .iter()
+        .zip(tx.message.account_keys.iter())
+        .filter(|(signature, _)| **signature == Signature::default())
+        .map(|(_, pubkey)| pubkey.to_string())
+        .collect();
+    if !absent_signers.is_empty() {
+        return Err(CliError::BadParameter(format!(
+            "Transaction is missing signatures from: {}",
+            absent_signers.join(", ")
+        ))
+        .into());
+    }
+    let result = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
+        &tx,
+        config.commitment,
+        config.send_transaction_config,
+    );
+    log_instruction_custom_error::<SystemError>(result, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{app, parse_command};
+    use solana_sdk::{
+        hash::Hash,
+        message::Message,
+        signature::{write_keypair, Keypair},
+        system_instruction,
+    };
+    use tempfile::NamedTempFile;
+
+    fn make_tmp_file() -> (String, NamedTempFile) {
+        let tmp_file = NamedTempFile::new().unwrap();
+        (String::from(tmp_file.path().to_str().unwrap()), tmp_file)
+    }
+
+    fn multisig_transaction(signers: &[Keypair]) -> Transaction {
+        let to = solana_sdk::pubkey::new_rand();
+        let instructions: Vec<_> = signers
+            .iter()
+            .map(|signer| system_instruction::transfer(&signer.pubkey(), &to, 1))
+            .collect();
+        let message = Message::new(&instructions, Some(&signers[0].pubkey()));
+        let mut tx = Transaction::new_unsigned(message);
+        tx.message.recent_blockhash = Hash::new_unique();
+        tx
+    }
+
+    #[test]
+    fn test_parse_tx_command() {
+        let test_commands = app("test", "desc", "version");
+        let default_keypair = Keypair::new();
+        let (default_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&default_keypair, tmp_file.as_file_mut()).unwrap();
+        let default_signer = DefaultSigner {
+            path: default_keypair_file,
+            arg_name: "".to_string(),
+        };
+
+        let test_sign = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "tx", "sign", "a"]);
+        let command_info = parse_command(&test_sign, &default_signer, &mut None).unwrap();
+        assert_eq!(
+            command_info.command,
+            CliCommand::Tx(TxCliCommand::Sign {
+                file: PathBuf::from("a"),
+                output_file: None,
+            })
+        );
+        assert_eq!(command_info.signers.len(), 1);
+        assert_eq!(command_info.signers[0].pubkey(), default_keypair.pubkey());
+
+        let test_combine = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "tx", "combine", "a", "b", "--output", "c"]);
+        assert_eq!(
+            parse_command(&test_combine, &default_signer, &mut None)
+                .unwrap()
+                .command,
+            CliCommand::Tx(TxCliCommand::Combine {
+                files: vec![PathBuf::from("a"), PathBuf::from("b")],
+                output_file: PathBuf::from("c"),
+            })
+        );
+
+        // A single file can't be combined
+        assert!(test_commands
+            .clone()
+            .get_matches_from_safe(vec!["test", "tx", "combine", "a", "--output", "c"])
+            .is_err());
+
+        let test_submit = test_commands.get_matches_from(vec!["test", "tx", "submit", "a"]);
+        assert_eq!(
+            parse_command(&test_submit, &default_signer, &mut None)
+                .unwrap()
+                .command,
+            CliCommand::Tx(TxCliCommand::Submit {
+                file: PathBuf::from("a"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_partially_signed_transaction_file() {
+        let signers = [Keypair::new(), Keypair::new()];
+        let mut tx = multisig_transaction(&signers);
+        let recent_blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[&signers[1]], recent_blockhash)
+            .unwrap();
+
+        let partial = PartiallySignedTransaction::from(&tx);
+        let (path, _tmp_file) = make_tmp_file();
+        let path = PathBuf::from(path);
+        partial.write(&path).unwrap();
+        assert_eq!(PartiallySignedTransaction::read(&path).unwrap(), partial);
+        assert_eq!(
+            PartiallySignedTransaction::read(&path)
+                .unwrap()
+                .to_transaction()
+                .unwrap(),
+            tx
+        );
+    }
+
+    #[test]
+    fn test_process_inspect_bad_signature() {
+        let signers = [Keypair::new(), Keypair::new()];
+        let mut tx = multisig_transaction(&signers);
+        let recent_blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[&signers[1]], recent_blockhash)
+            .unwrap();
+        let mut partial = PartiallySignedTransaction::from(&tx);
+        partial.signers[0].signature = partial.signers[1].signature.clone();
+        let (path, _tmp_file) = make_tmp_file();
+        let path = PathBuf::from(path);
+        partial.write(&path).unwrap();
+
+        let config = CliConfig {
+            output_format: OutputFormat::JsonCompact,
+            ..CliConfig::default()
+        };
+        let result: serde_json::Value =
+            serde_json::from_str(&process_inspect(&config, &path).unwrap()).unwrap();
+        assert_eq!(
+            result["badSig"],
+            serde_json::json!([signers[0].pubkey().to_string()])
+        );
+        assert_eq!(result["signers"].as_array().unwrap().len(), 1);
+        assert!(
+            process_submit(&RpcClient::new_mock("succeeds".to_string()), &config, &path).is_err()
+        );
+    }
+}
@@ -2,25 +2,31 @@ use crate::{
     cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
     spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
 };
-use bincode::deserialize;
+use bincode::{deserialize, serialized_size};
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use reqwest::blocking::Client;
 use serde_json::{Map, Value};
 use solana_account_decoder::validator_info::{
-    self, ValidatorInfo, MAX_LONG_FIELD_LENGTH, MAX_SHORT_FIELD_LENGTH,
+    self, IdentityProof, UiValidatorInfo, ValidatorInfo, ValidatorMetadata, MAX_LONG_FIELD_LENGTH,
+    MAX_SHORT_FIELD_LENGTH,
 };
 use solana_clap_utils::{
     input_parsers::pubkey_of,
-    input_validators::{is_pubkey, is_url},
-    keypair::DefaultSigner,
+    input_validators::{is_pubkey, is_url, is_valid_signer},
+    keypair::{signer_from_path, DefaultSigner},
 };
 use solana_cli_output::{CliValidatorInfo, CliValidatorInfoVec};
-use solana_client::rpc_client::RpcClient;
-use solana_config_program::{config_instruction, get_config_data, ConfigKeys, ConfigState};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+    rpc_request::RpcError,
+};
+use solana_config_program::{config_instruction, ConfigKeys, ConfigState};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     account::Account,
     commitment_config::CommitmentConfig,
+    instruction::Instruction,
     message::Message,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
@@ -28,6 +34,9 @@ use solana_sdk::{
 };
 use std::{error, sync::Arc};
 
+// JSON-RPC error code returned by a node that does not implement the requested method
+const JSON_RPC_METHOD_NOT_FOUND: i64 = -32601;
+
 // Return an error if a validator details are longer than the max length.
 pub fn check_details_length(string: String) -> Result<(), String> {
     if string.len() > MAX_LONG_FIELD_LENGTH {
@@ -113,21 +122,38 @@ fn parse_args(matches: &ArgMatches<'_>) -> Value {
 fn parse_validator_info(
     pubkey: &Pubkey,
     account: &Account,
-) -> Result<(Pubkey, Map<String, serde_json::value::Value>), Box<dyn error::Error>> {
+) -> Result<CliValidatorInfo, Box<dyn error::Error>> {
     if account.owner != solana_config_program::id() {
         return Err(format!("{} is not a validator info account", pubkey).into());
     }
-    let key_list: ConfigKeys = deserialize(&account.data)?;
-    if !key_list.keys.is_empty() {
-        let (validator_pubkey, _) = key_list.keys[1];
-        let validator_info_string: String = deserialize(&get_config_data(&account.data)?)?;
-        let validator_info: Map<_, _> = serde_json::from_str(&validator_info_string)?;
-        Ok((validator_pubkey, validator_info))
-    } else {
-        Err(format!("{} could not be parsed as a validator info account", pubkey).into())
+    let validator_info = validator_info::parse_validator_info(pubkey, account)
+        .map_err(|_| format!("{} could not be parsed as a validator info account", pubkey))?;
+    Ok(cli_validator_info(validator_info))
+}
+
+fn cli_validator_info(validator_info: UiValidatorInfo) -> CliValidatorInfo {
+    let info = match serde_json::to_value(validator_info.info) {
+        Ok(Value::Object(info)) => info,
+        _ => Map::new(),
+    };
+    CliValidatorInfo {
+        identity_pubkey: validator_info.identity_pubkey,
+        info_pubkey: validator_info.info_pubkey,
+        info,
+        verified: validator_info.verified,
     }
 }
 
+// Overwrite a validator info account so that it no longer lists as validator info
+fn retire_validator_info(info_pubkey: &Pubkey, identity: &Pubkey) -> Instruction {
+    config_instruction::store(
+        info_pubkey,
+        false,
+        vec![(*identity, true)],
+        &ValidatorInfo::default(),
+    )
+}
+
 pub trait ValidatorInfoSubCommands {
     fn validator_info_subcommands(self) -> Self;
 }
@@ -186,6 +212,24 @@ impl ValidatorInfoSubCommands for App<'_, '_> {
                                 .validator(check_details_length)
                                 .help("Validator description")
                         )
+                        .arg(
+                            Arg::with_name("proof_keypair")
+                                .long("proof-keypair")
+                                .value_name("KEYPAIR")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .requires("proof_url")
+                                .help("External keypair that signs the validator identity to prove ownership of it"),
+                        )
+                        .arg(
+                            Arg::with_name("proof_url")
+                                .long("proof-url")
+                                .value_name("URL")
+                                .takes_value(true)
+                                .validator(check_url)
+                                .requires("proof_keypair")
+                                .help("Url where the holder of the external keypair publishes the identity proof"),
+                        )
                         .arg(
                             Arg::with_name("force")
                                 .long("force")
@@ -216,15 +260,30 @@ pub fn parse_validator_info_command(
     wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
 ) -> Result<CliCommandInfo, CliError> {
     let info_pubkey = pubkey_of(matches, "info_pubkey");
+    let identity_signer = default_signer.signer_from_path(matches, wallet_manager)?;
     // Prepare validator info
-    let validator_info = parse_args(matches);
+    let mut validator_info = parse_args(matches);
+    if let Some(proof_url) = matches.value_of("proof_url") {
+        let proof_signer = signer_from_path(
+            matches,
+            matches.value_of("proof_keypair").unwrap(),
+            "proof_keypair",
+            wallet_manager,
+        )?;
+        let identity_proof = IdentityProof::new(
+            proof_signer.as_ref(),
+            &identity_signer.pubkey(),
+            proof_url.to_string(),
+        );
+        validator_info["identityProof"] = serde_json::to_value(identity_proof).unwrap();
+    }
     Ok(CliCommandInfo {
         command: CliCommand::SetValidatorInfo {
             validator_info,
             force_keybase: matches.is_present("force"),
             info_pubkey,
         },
-        signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+        signers: vec![identity_signer],
     })
 }
 
@@ -258,7 +317,12 @@ pub fn process_set_validator_info(
             }
         }
     }
-    let validator_string = serde_json::to_string(&validator_info).unwrap();
+    let metadata: ValidatorMetadata = serde_json::from_value(validator_info.clone())
+        .map_err(|err| CliError::BadParameter(format!("Invalid validator info: {}", err)))?;
+    metadata
+        .check()
+        .map_err(|err| CliError::BadParameter(format!("Invalid validator info: {:?}", err)))?;
+    let validator_string = serde_json::to_string(&metadata).unwrap();
     let validator_info = ValidatorInfo {
         info: validator_string,
     };
@@ -273,8 +337,11 @@ pub fn process_set_validator_info(
             },
         )
         .find(|(pubkey, account)| {
-            let (validator_pubkey, _) = parse_validator_info(&pubkey, &account).unwrap();
-            validator_pubkey == config.signers[0].pubkey()
+            parse_validator_info(&pubkey, &account)
+                .map(|validator_info| {
+                    validator_info.identity_pubkey == config.signers[0].pubkey().to_string()
+                })
+                .unwrap_or(false)
         });
 
     // Create validator-info keypair to use if info_pubkey not provided or does not exist
@@ -288,13 +355,36 @@ pub fn process_set_validator_info(
     };
 
     // Check existence of validator-info account
-    let balance = rpc_client
+    let mut balance = rpc_client
         .poll_get_balance_with_commitment(&info_pubkey, CommitmentConfig::default())
         .unwrap_or(0);
 
     let lamports =
         rpc_client.get_minimum_balance_for_rent_exemption(ValidatorInfo::max_space() as usize)?;
 
+    let keys = vec![
+        (validator_info::id(), false),
+        (config.signers[0].pubkey(), true),
+    ];
+    // Accounts created before identity proofs were added may be too small to hold one. The config
+    // program cannot grow them, so the info moves to a new account, and the old one is retired by
+    // dropping the validator info key from its key list
+    let mut retired_info_pubkey = None;
+    if balance != 0 {
+        let space = rpc_client.get_account(&info_pubkey)?.data.len() as u64;
+        let required_space =
+            ConfigKeys::serialized_size(keys.clone()) + serialized_size(&validator_info).unwrap();
+        if space < required_space {
+            println!(
+                "Validator info account {} has {} bytes of space, {} are required. Moving it to a new account...",
+                info_pubkey, space, required_space
+            );
+            retired_info_pubkey = Some(info_pubkey);
+            info_pubkey = info_keypair.pubkey();
+            balance = 0;
+        }
+    }
+
     let signers = if balance == 0 {
         if info_pubkey != info_keypair.pubkey() {
             println!(
@@ -309,10 +399,7 @@ pub fn process_set_validator_info(
     };
 
     let build_message = |lamports| {
-        let keys = vec![
-            (validator_info::id(), false),
-            (config.signers[0].pubkey(), true),
-        ];
+        let keys = keys.clone();
         if balance == 0 {
             println!(
                 "Publishing info for Validator {:?}",
@@ -330,6 +417,12 @@ pub fn process_set_validator_info(
                 keys,
                 &validator_info,
             )]);
+            if let Some(retired_info_pubkey) = retired_info_pubkey {
+                instructions.push(retire_validator_info(
+                    &retired_info_pubkey,
+                    &config.signers[0].pubkey(),
+                ));
+            }
             Message::new(&instructions, Some(&config.signers[0].pubkey()))
         } else {
             println!(
@@ -372,37 +465,38 @@ pub fn process_get_validator_info(
     config: &CliConfig,
     pubkey: Option<Pubkey>,
 ) -> ProcessResult {
-    let validator_info: Vec<(Pubkey, Account)> = if let Some(validator_info_pubkey) = pubkey {
-        vec![(
-            validator_info_pubkey,
-            rpc_client.get_account(&validator_info_pubkey)?,
-        )]
+    let validator_info_list: Vec<CliValidatorInfo> = if let Some(validator_info_pubkey) = pubkey {
+        vec![parse_validator_info(
+            &validator_info_pubkey,
+            &rpc_client.get_account(&validator_info_pubkey)?,
+        )?]
     } else {
-        let all_config = rpc_client.get_program_accounts(&solana_config_program::id())?;
-        all_config
-            .into_iter()
-            .filter(|(_, validator_info_account)| {
-                match deserialize::<ConfigKeys>(&validator_info_account.data) {
-                    Ok(key_list) => key_list.keys.contains(&(validator_info::id(), false)),
-                    Err(_) => false,
-                }
-            })
-            .collect()
+        match rpc_client.get_validator_info() {
+            Ok(validator_info_list) => validator_info_list
+                .into_iter()
+                .map(cli_validator_info)
+                .collect(),
+            Err(ClientError {
+                kind:
+                    ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                        code: JSON_RPC_METHOD_NOT_FOUND,
+                        ..
+                    }),
+                ..
+            }) => {
+                // The RPC node predates getValidatorInfo, so scan the config accounts instead
+                rpc_client
+                    .get_program_accounts(&solana_config_program::id())?
+                    .iter()
+                    .filter_map(|(pubkey, account)| parse_validator_info(pubkey, account).ok())
+                    .collect()
+            }
+            Err(err) => return Err(err.into()),
+        }
     };
-
-    let mut validator_info_list: Vec<CliValidatorInfo> = vec![];
-    if validator_info.is_empty() {
+    if validator_info_list.is_empty() {
         println!("No validator info accounts found");
     }
-    for (validator_info_pubkey, validator_info_account) in validator_info.iter() {
-        let (validator_pubkey, validator_info) =
-            parse_validator_info(&validator_info_pubkey, &validator_info_account)?;
-        validator_info_list.push(CliValidatorInfo {
-            identity_pubkey: validator_pubkey.to_string(),
-            info_pubkey: validator_info_pubkey.to_string(),
-            info: validator_info,
-        });
-    }
     Ok(config
         .output_format
         .formatted_string(&CliValidatorInfoVec::new(validator_info_list)))
@@ -412,7 +506,7 @@ pub fn process_get_validator_info(
 mod tests {
     use super::*;
     use crate::cli::app;
-    use bincode::{serialize, serialized_size};
+    use bincode::serialize;
     use serde_json::json;
 
     #[test]
@@ -496,18 +590,42 @@ mod tests {
         let validator_info = ValidatorInfo { info: info_string };
         let data = serialize(&(config, validator_info)).unwrap();
 
-        assert_eq!(
-            parse_validator_info(
-                &Pubkey::default(),
-                &Account {
-                    owner: solana_config_program::id(),
-                    data,
-                    ..Account::default()
-                }
-            )
-            .unwrap(),
-            (pubkey, info)
-        );
+        let validator_info = parse_validator_info(
+            &Pubkey::default(),
+            &Account {
+                owner: solana_config_program::id(),
+                data,
+                ..Account::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(validator_info.identity_pubkey, pubkey.to_string());
+        assert_eq!(validator_info.info, info);
+        assert!(!validator_info.verified);
+    }
+
+    #[test]
+    fn test_retire_validator_info() {
+        let info_pubkey = solana_sdk::pubkey::new_rand();
+        let identity = solana_sdk::pubkey::new_rand();
+        let keys = vec![(validator_info::id(), false), (identity, true)];
+        let validator_info = ValidatorInfo {
+            info: r#"{"name":"Alice"}"#.to_string(),
+        };
+        let mut data = serialize(&(ConfigKeys { keys }, validator_info)).unwrap();
+        let account = |data: Vec<u8>| Account {
+            owner: solana_config_program::id(),
+            data,
+            ..Account::default()
+        };
+        assert!(parse_validator_info(&info_pubkey, &account(data.clone())).is_ok());
+
+        let instruction = retire_validator_info(&info_pubkey, &identity);
+        assert_eq!(instruction.accounts[0].pubkey, info_pubkey);
+        assert_eq!(instruction.accounts[1].pubkey, identity);
+        assert!(instruction.accounts[1].is_signer);
+        data[..instruction.data.len()].copy_from_slice(&instruction.data);
+        assert!(parse_validator_info(&info_pubkey, &account(data)).is_err());
     }
 
     #[test]
@@ -525,9 +643,19 @@ mod tests {
         );
         info.insert(
             "keybaseUsername".to_string(),
-            Value::String(max_short_string),
+            Value::String(max_short_string.clone()),
         );
         info.insert("details".to_string(), Value::String(max_long_string));
+        let identity_proof = IdentityProof {
+            // Longest base58 encodings of a pubkey and a signature
+            external_key: "x".repeat(44),
+            signature: "x".repeat(88),
+            url: max_short_string,
+        };
+        info.insert(
+            "identityProof".to_string(),
+            serde_json::to_value(identity_proof).unwrap(),
+        );
         let info_string = serde_json::to_string(&Value::Object(info)).unwrap();
 
         let validator_info = ValidatorInfo { info: info_string };
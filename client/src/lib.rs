@@ -6,6 +6,7 @@ pub mod client_error;
 pub mod http_sender;
 pub mod mock_sender;
 pub mod nonce_utils;
+pub mod partially_signed_transaction;
 pub mod perf_utils;
 pub mod pubsub_client;
 pub mod rpc_client;
@@ -16,3 +17,4 @@ pub mod rpc_request;
 pub mod rpc_response;
 pub mod rpc_sender;
 pub mod thin_client;
+pub mod tpu_client;
@@ -1,7 +1,7 @@
 use crate::{
     client_error::Result,
     rpc_request::RpcRequest,
-    rpc_response::{Response, RpcResponseContext, RpcVersionInfo},
+    rpc_response::{Response, RpcContactInfo, RpcResponseContext, RpcVersionInfo},
     rpc_sender::RpcSender,
 };
 use serde_json::{json, Number, Value};
@@ -14,7 +14,7 @@ use solana_sdk::{
 };
 use solana_transaction_status::TransactionStatus;
 use solana_version::Version;
-use std::{collections::HashMap, sync::RwLock};
+use std::{collections::HashMap, net::SocketAddr, sync::RwLock};
 
 pub const PUBKEY: &str = "7RoSF9fUmdphVCpabEoefH81WwrW7orsWonXWqTXkKV8";
 pub const SIGNATURE: &str =
@@ -48,6 +48,14 @@ impl RpcSender for MockSender {
             return Ok(Value::Null);
         }
         let val = match request {
+            RpcRequest::GetClusterNodes => serde_json::to_value(vec![RpcContactInfo {
+                pubkey: PUBKEY.to_string(),
+                gossip: Some(SocketAddr::from(([10, 239, 6, 48], 8899))),
+                tpu: Some(SocketAddr::from(([10, 239, 6, 48], 8856))),
+                rpc: Some(SocketAddr::from(([10, 239, 6, 48], 8899))),
+                version: Some("1.0.0 c375ce1f".to_string()),
+                feature_set: None,
+            }])?,
             RpcRequest::GetBalance => serde_json::to_value(Response {
                 context: RpcResponseContext { slot: 1 },
                 value: Value::Number(Number::from(50)),
@@ -0,0 +1,256 @@
+//! A transaction that may still be missing signatures, in a form that can be passed between
+//! offline signers as a file
+
+use solana_sdk::{
+    message::Message, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use std::{fs::File, path::Path, str::FromStr};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum Error {
+    #[error("unable to decode message")]
+    InvalidMessage,
+    #[error("message requires {required} signers, {listed} listed")]
+    SignerCountMismatch { required: usize, listed: usize },
+    #[error("signer {listed} does not match message signer {required}")]
+    SignerMismatch { listed: String, required: Pubkey },
+    #[error("invalid signature for {0}")]
+    InvalidSignature(Pubkey),
+    #[error("bad signature for {0}")]
+    BadSignature(Pubkey),
+    #[error("transactions have different messages")]
+    MessageMismatch,
+    #[error("conflicting signatures for {0}")]
+    ConflictingSignatures(String),
+    #[error("{0}")]
+    Io(String),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartiallySignedTransaction {
+    /// Base64 encoding of the serialized message
+    pub message: String,
+    /// Every signer the message requires, in message order
+    pub signers: Vec<PartialSignature>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialSignature {
+    pub pubkey: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl From<&Transaction> for PartiallySignedTransaction {
+    fn from(tx: &Transaction) -> Self {
+        let signers = tx
+            .message
+            .account_keys
+            .iter()
+            .zip(tx.signatures.iter())
+            .map(|(pubkey, signature)| PartialSignature {
+                pubkey: pubkey.to_string(),
+                signature: if *signature == Signature::default() {
+                    None
+                } else {
+                    Some(signature.to_string())
+                },
+            })
+            .collect();
+        Self {
+            message: base64::encode(&tx.message_data()),
+            signers,
+        }
+    }
+}
+
+/// An unsigned transaction, awaiting every signature its message requires
+impl From<&Message> for PartiallySignedTransaction {
+    fn from(message: &Message) -> Self {
+        Self::from(&Transaction::new_unsigned(message.clone()))
+    }
+}
+
+impl PartiallySignedTransaction {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let file = File::open(path)
+            .map_err(|err| Error::Io(format!("unable to open {}: {}", path.display(), err)))?;
+        serde_json::from_reader(file).map_err(|err| {
+            Error::Io(format!(
+                "{} is not a partially signed transaction: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        let file = File::create(path)
+            .map_err(|err| Error::Io(format!("unable to create {}: {}", path.display(), err)))?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|err| Error::Io(format!("unable to write {}: {}", path.display(), err)))
+    }
+
+    /// Rebuild the transaction, rejecting any signer list that does not match the message or
+    /// signature that does not verify
+    pub fn to_transaction(&self) -> Result<Transaction, Error> {
+        self.build_transaction(true)
+    }
+
+    /// Rebuild the transaction like `to_transaction`, but keep signatures that do not verify so
+    /// that they can be reported
+    pub fn to_unverified_transaction(&self) -> Result<Transaction, Error> {
+        self.build_transaction(false)
+    }
+
+    fn build_transaction(&self, verify_signatures: bool) -> Result<Transaction, Error> {
+        let message_data = base64::decode(&self.message).map_err(|_| Error::InvalidMessage)?;
+        let message: Message =
+            bincode::deserialize(&message_data).map_err(|_| Error::InvalidMessage)?;
+        let num_required_signatures = message.header.num_required_signatures as usize;
+        if self.signers.len() != num_required_signatures {
+            return Err(Error::SignerCountMismatch {
+                required: num_required_signatures,
+                listed: self.signers.len(),
+            });
+        }
+        let mut signatures = vec![];
+        for (signer, pubkey) in self.signers.iter().zip(message.account_keys.iter()) {
+            if Pubkey::from_str(&signer.pubkey).ok().as_ref() != Some(pubkey) {
+                return Err(Error::SignerMismatch {
+                    listed: signer.pubkey.clone(),
+                    required: *pubkey,
+                });
+            }
+            let signature = match &signer.signature {
+                Some(signature) => {
+                    let signature = Signature::from_str(signature)
+                        .map_err(|_| Error::InvalidSignature(*pubkey))?;
+                    if verify_signatures && !signature.verify(pubkey.as_ref(), &message_data) {
+                        return Err(Error::BadSignature(*pubkey));
+                    }
+                    signature
+                }
+                None => Signature::default(),
+            };
+            signatures.push(signature);
+        }
+        Ok(Transaction {
+            signatures,
+            message,
+        })
+    }
+
+    /// Collect the signatures from `other`, which must carry the same message
+    pub fn combine(&mut self, other: &Self) -> Result<(), Error> {
+        if self.message != other.message || self.signers.len() != other.signers.len() {
+            return Err(Error::MessageMismatch);
+        }
+        for (signer, other_signer) in self.signers.iter_mut().zip(other.signers.iter()) {
+            match (&signer.signature, &other_signer.signature) {
+                (None, Some(_)) => signer.signature = other_signer.signature.clone(),
+                (Some(signature), Some(other_signature)) if signature != other_signature => {
+                    return Err(Error::ConflictingSignatures(signer.pubkey.clone()));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        hash::Hash,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+
+    fn multisig_transaction(signers: &[Keypair]) -> Transaction {
+        let to = solana_sdk::pubkey::new_rand();
+        let instructions: Vec<_> = signers
+            .iter()
+            .map(|signer| system_instruction::transfer(&signer.pubkey(), &to, 1))
+            .collect();
+        let message = Message::new(&instructions, Some(&signers[0].pubkey()));
+        let mut tx = Transaction::new_unsigned(message);
+        tx.message.recent_blockhash = Hash::new_unique();
+        tx
+    }
+
+    #[test]
+    fn test_partially_signed_transaction_roundtrip() {
+        let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+        let mut tx = multisig_transaction(&signers);
+        assert_eq!(
+            PartiallySignedTransaction::from(&tx.message),
+            PartiallySignedTransaction::from(&tx)
+        );
+        let recent_blockhash = tx.message.recent_blockhash;
+        tx.try_partial_sign(&[&signers[1]], recent_blockhash)
+            .unwrap();
+
+        let partial = PartiallySignedTransaction::from(&tx);
+        assert_eq!(partial.signers.len(), 3);
+        assert_eq!(partial.signers[0].signature, None);
+        assert!(partial.signers[1].signature.is_some());
+        assert_eq!(partial.to_transaction().unwrap(), tx);
+
+        // Signatures must verify and signers must match the message
+        let mut bad_signature = PartiallySignedTransaction::from(&tx);
+        bad_signature.signers[0].signature = bad_signature.signers[1].signature.clone();
+        assert_eq!(
+            bad_signature.to_transaction(),
+            Err(Error::BadSignature(signers[0].pubkey()))
+        );
+        let unverified = bad_signature.to_unverified_transaction().unwrap();
+        assert_eq!(unverified.verify_with_results(), vec![false, true, false]);
+        let mut bad_signer = PartiallySignedTransaction::from(&tx);
+        bad_signer.signers.swap(0, 2);
+        assert!(bad_signer.to_transaction().is_err());
+        let mut missing_signer = PartiallySignedTransaction::from(&tx);
+        missing_signer.signers.pop();
+        assert_eq!(
+            missing_signer.to_transaction(),
+            Err(Error::SignerCountMismatch {
+                required: 3,
+                listed: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_partially_signed_transaction_combine() {
+        let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+        let tx = multisig_transaction(&signers);
+        let recent_blockhash = tx.message.recent_blockhash;
+        let partials: Vec<_> = signers
+            .iter()
+            .map(|signer| {
+                let mut tx = tx.clone();
+                tx.try_partial_sign(&[signer], recent_blockhash).unwrap();
+                PartiallySignedTransaction::from(&tx)
+            })
+            .collect();
+
+        let mut combined = PartiallySignedTransaction::from(&tx);
+        for partial in &partials {
+            combined.combine(partial).unwrap();
+        }
+        let combined = combined.to_transaction().unwrap();
+        let mut expected = tx;
+        expected
+            .try_sign(&[&signers[0], &signers[1], &signers[2]], recent_blockhash)
+            .unwrap();
+        assert_eq!(combined, expected);
+        assert!(combined.verify().is_ok());
+
+        // Transactions with different messages can't be combined
+        let mut other = PartiallySignedTransaction::from(&multisig_transaction(&signers));
+        assert_eq!(other.combine(&partials[0]), Err(Error::MessageMismatch));
+    }
+}
@@ -18,6 +18,7 @@ use log::*;
 use serde_json::{json, Value};
 use solana_account_decoder::{
     parse_token::{TokenAccountType, UiTokenAccount, UiTokenAmount},
+    validator_info::UiValidatorInfo,
     UiAccount, UiAccountData, UiAccountEncoding,
 };
 use solana_sdk::{
@@ -384,6 +385,17 @@ impl RpcClient {
         self.send(RpcRequest::GetVoteAccounts, json!([commitment_config]))
     }
 
+    pub fn get_validator_info(&self) -> ClientResult<Vec<UiValidatorInfo>> {
+        self.get_validator_info_with_commitment(self.commitment_config)
+    }
+
+    pub fn get_validator_info_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Vec<UiValidatorInfo>> {
+        self.send(RpcRequest::GetValidatorInfo, json!([commitment_config]))
+    }
+
     pub fn wait_for_max_stake(
         &self,
         commitment: CommitmentConfig,
@@ -443,6 +455,14 @@ impl RpcClient {
         )
     }
 
+    pub fn get_block_stats(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+    ) -> ClientResult<Vec<RpcBlockStats>> {
+        self.send(RpcRequest::GetBlockStats, json!([start_slot, end_slot]))
+    }
+
     pub fn get_confirmed_blocks_with_limit(
         &self,
         start_slot: Slot,
@@ -10,6 +10,7 @@ pub enum RpcRequest {
     ValidatorExit,
     GetAccountInfo,
     GetBalance,
+    GetBlockStats,
     GetBlockTime,
     GetClusterNodes,
     GetConfirmedBlock,
@@ -48,6 +49,7 @@ pub enum RpcRequest {
     GetTokenSupply,
     GetTotalSupply,
     GetTransactionCount,
+    GetValidatorInfo,
     GetVersion,
     GetVoteAccounts,
     MinimumLedgerSlot,
@@ -65,6 +67,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::ValidatorExit => "validatorExit",
             RpcRequest::GetAccountInfo => "getAccountInfo",
             RpcRequest::GetBalance => "getBalance",
+            RpcRequest::GetBlockStats => "getBlockStats",
             RpcRequest::GetBlockTime => "getBlockTime",
             RpcRequest::GetClusterNodes => "getClusterNodes",
             RpcRequest::GetConfirmedBlock => "getConfirmedBlock",
@@ -103,6 +106,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetTokenSupply => "getTokenSupply",
             RpcRequest::GetTotalSupply => "getTotalSupply",
             RpcRequest::GetTransactionCount => "getTransactionCount",
+            RpcRequest::GetValidatorInfo => "getValidatorInfo",
             RpcRequest::GetVersion => "getVersion",
             RpcRequest::GetVoteAccounts => "getVoteAccounts",
             RpcRequest::MinimumLedgerSlot => "minimumLedgerSlot",
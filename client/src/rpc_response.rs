@@ -6,7 +6,7 @@ use solana_sdk::{
     inflation::Inflation,
     transaction::{Result, TransactionError},
 };
-use solana_transaction_status::ConfirmedTransactionStatusWithSignature;
+use solana_transaction_status::{ConfirmedTransactionStatusWithSignature, UiTransactionReturnData};
 use std::{collections::HashMap, fmt, net::SocketAddr};
 
 pub type RpcResult<T> = client_error::Result<Response<T>>;
@@ -229,6 +229,8 @@ pub struct RpcSignatureConfirmation {
 pub struct RpcSimulateTransactionResult {
     pub err: Option<TransactionError>,
     pub logs: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -297,6 +299,21 @@ pub struct RpcPerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockStats {
+    pub slot: Slot,
+    pub num_transactions: u64,
+    pub num_successful_transactions: u64,
+    pub num_failed_transactions: u64,
+    pub num_vote_transactions: u64,
+    pub num_non_vote_transactions: u64,
+    pub compute_units_consumed: u64,
+    pub fees: u64,
+    pub num_entries: u64,
+    pub num_ticks: u64,
+}
+
 impl From<ConfirmedTransactionStatusWithSignature> for RpcConfirmedTransactionStatusWithSignature {
     fn from(value: ConfirmedTransactionStatusWithSignature) -> Self {
         let ConfirmedTransactionStatusWithSignature {
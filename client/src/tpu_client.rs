@@ -0,0 +1,227 @@
+//! The `tpu_client` module sends transactions straight to the TPUs of the current and upcoming
+//! leaders over UDP.  RPC is only used to find out who those leaders are, confirming that the
+//! transactions landed is left to the caller.
+
+use crate::{
+    client_error::Result as ClientResult,
+    rpc_client::RpcClient,
+    rpc_response::{RpcContactInfo, RpcLeaderSchedule},
+};
+use bincode::serialize;
+use log::*;
+use solana_sdk::{
+    clock::Epoch, commitment_config::CommitmentConfig, pubkey::Pubkey, transaction::Transaction,
+};
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, UdpSocket},
+    str::FromStr,
+};
+
+/// Default number of upcoming slots whose leaders each transaction is sent to
+pub const DEFAULT_FANOUT_SLOTS: u64 = 12;
+
+/// Maximum number of upcoming slots whose leaders each transaction is sent to
+pub const MAX_FANOUT_SLOTS: u64 = 100;
+
+#[derive(Clone, Debug)]
+pub struct TpuClientConfig {
+    /// Number of upcoming slots whose leaders each transaction is sent to, between 1 and
+    /// `MAX_FANOUT_SLOTS`
+    pub fanout_slots: u64,
+}
+
+impl Default for TpuClientConfig {
+    fn default() -> Self {
+        Self {
+            fanout_slots: DEFAULT_FANOUT_SLOTS,
+        }
+    }
+}
+
+pub struct TpuClient<'a> {
+    rpc_client: &'a RpcClient,
+    commitment: CommitmentConfig,
+    send_socket: UdpSocket,
+    fanout_slots: u64,
+    leader_schedule_epoch: Option<Epoch>,
+    // Leader of each slot of `leader_schedule_epoch`, by slot index
+    slot_leaders: HashMap<u64, Pubkey>,
+    leader_tpu_map: HashMap<Pubkey, SocketAddr>,
+    leader_tpus: Vec<SocketAddr>,
+}
+
+impl<'a> TpuClient<'a> {
+    pub fn new(
+        rpc_client: &'a RpcClient,
+        commitment: CommitmentConfig,
+        config: TpuClientConfig,
+    ) -> ClientResult<Self> {
+        let mut tpu_client = Self {
+            rpc_client,
+            commitment,
+            send_socket: UdpSocket::bind("0.0.0.0:0")?,
+            fanout_slots: config.fanout_slots.max(1).min(MAX_FANOUT_SLOTS),
+            leader_schedule_epoch: None,
+            slot_leaders: HashMap::new(),
+            leader_tpu_map: HashMap::new(),
+            leader_tpus: vec![],
+        };
+        tpu_client.refresh_leaders()?;
+        Ok(tpu_client)
+    }
+
+    /// Look up the leaders of the current slot and the `fanout_slots` after it.  The leader
+    /// schedule and node addresses are only fetched again once the epoch changes
+    pub fn refresh_leaders(&mut self) -> ClientResult<()> {
+        let epoch_info = self
+            .rpc_client
+            .get_epoch_info_with_commitment(self.commitment)?;
+        if self.leader_schedule_epoch != Some(epoch_info.epoch) {
+            let leader_schedule = self
+                .rpc_client
+                .get_leader_schedule_with_commitment(
+                    Some(epoch_info.absolute_slot),
+                    self.commitment,
+                )?
+                .unwrap_or_default();
+            self.slot_leaders = slot_leaders(&leader_schedule);
+            self.leader_tpu_map = leader_tpu_map(&self.rpc_client.get_cluster_nodes()?);
+            self.leader_schedule_epoch = Some(epoch_info.epoch);
+        }
+
+        // Leaders of the next epoch aren't known yet, so the fanout stops at the epoch boundary
+        self.leader_tpus = leader_tpus(
+            epoch_info.slot_index
+                ..(epoch_info.slot_index + self.fanout_slots).min(epoch_info.slots_in_epoch),
+            &self.slot_leaders,
+            &self.leader_tpu_map,
+        );
+        if self.leader_tpus.is_empty() {
+            warn!(
+                "No TPU addresses found for the leaders of slots {}..{}",
+                epoch_info.absolute_slot,
+                epoch_info.absolute_slot + self.fanout_slots
+            );
+        }
+        Ok(())
+    }
+
+    /// Send a transaction to the TPUs of the upcoming leaders.  Returns whether it was sent to
+    /// at least one of them
+    pub fn send_transaction(&self, transaction: &Transaction) -> bool {
+        let wire_transaction = serialize(transaction).expect("serialization should succeed");
+        self.send_wire_transaction(&wire_transaction)
+    }
+
+    /// Send a serialized transaction to the TPUs of the upcoming leaders.  Returns whether it
+    /// was sent to at least one of them
+    pub fn send_wire_transaction(&self, wire_transaction: &[u8]) -> bool {
+        let mut sent = false;
+        for tpu_address in &self.leader_tpus {
+            match self.send_socket.send_to(wire_transaction, tpu_address) {
+                Ok(_) => sent = true,
+                Err(err) => warn!("Failed to send transaction to {}: {:?}", tpu_address, err),
+            }
+        }
+        sent
+    }
+}
+
+fn slot_leaders(leader_schedule: &RpcLeaderSchedule) -> HashMap<u64, Pubkey> {
+    leader_schedule
+        .iter()
+        .filter_map(|(pubkey, slot_indexes)| {
+            let pubkey = Pubkey::from_str(pubkey).ok()?;
+            Some(
+                slot_indexes
+                    .iter()
+                    .map(move |slot_index| (*slot_index as u64, pubkey)),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+fn leader_tpu_map(cluster_nodes: &[RpcContactInfo]) -> HashMap<Pubkey, SocketAddr> {
+    cluster_nodes
+        .iter()
+        .filter_map(|contact_info| {
+            Some((
+                Pubkey::from_str(&contact_info.pubkey).ok()?,
+                contact_info.tpu?,
+            ))
+        })
+        .collect()
+}
+
+// TPU addresses of the leaders of `slot_indexes`, in slot order and without duplicates, as a
+// leader is usually scheduled for several consecutive slots
+fn leader_tpus(
+    slot_indexes: std::ops::Range<u64>,
+    slot_leaders: &HashMap<u64, Pubkey>,
+    leader_tpu_map: &HashMap<Pubkey, SocketAddr>,
+) -> Vec<SocketAddr> {
+    let mut leader_tpus = vec![];
+    for slot_index in slot_indexes {
+        if let Some(tpu_address) = slot_leaders
+            .get(&slot_index)
+            .and_then(|leader| leader_tpu_map.get(leader))
+        {
+            if !leader_tpus.contains(tpu_address) {
+                leader_tpus.push(*tpu_address);
+            }
+        }
+    }
+    leader_tpus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn test_leader_tpus() {
+        let leaders: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let mut leader_schedule = RpcLeaderSchedule::new();
+        leader_schedule.insert(leaders[0].to_string(), vec![0, 1, 2, 3, 8, 9]);
+        leader_schedule.insert(leaders[1].to_string(), vec![4, 5, 6, 7]);
+        leader_schedule.insert(leaders[2].to_string(), vec![10, 11]);
+        leader_schedule.insert("not a pubkey".to_string(), vec![12]);
+        let slot_leaders = slot_leaders(&leader_schedule);
+        assert_eq!(slot_leaders.len(), 12);
+        assert_eq!(slot_leaders[&5], leaders[1]);
+
+        let tpu_address = |port| SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
+        // The last leader isn't advertising a TPU address
+        let cluster_nodes: Vec<_> = leaders
+            .iter()
+            .enumerate()
+            .map(|(i, leader)| RpcContactInfo {
+                pubkey: leader.to_string(),
+                gossip: None,
+                tpu: if i < 2 {
+                    Some(tpu_address(8000 + i as u16))
+                } else {
+                    None
+                },
+                rpc: None,
+                version: None,
+                feature_set: None,
+            })
+            .collect();
+        let leader_tpu_map = leader_tpu_map(&cluster_nodes);
+        assert_eq!(leader_tpu_map.len(), 2);
+
+        assert_eq!(
+            leader_tpus(2..10, &slot_leaders, &leader_tpu_map),
+            vec![tpu_address(8000), tpu_address(8001)]
+        );
+        assert_eq!(
+            leader_tpus(5..6, &slot_leaders, &leader_tpu_map),
+            vec![tpu_address(8001)]
+        );
+        assert!(leader_tpus(10..20, &slot_leaders, &leader_tpu_map).is_empty());
+    }
+}
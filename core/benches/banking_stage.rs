@@ -11,7 +11,7 @@ use solana_core::cluster_info::ClusterInfo;
 use solana_core::cluster_info::Node;
 use solana_core::poh_recorder::WorkingBankEntry;
 use solana_ledger::blockstore_processor::process_entries;
-use solana_ledger::entry::{next_hash, Entry};
+use solana_ledger::entry::Entry;
 use solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo};
 use solana_ledger::{blockstore::Blockstore, get_tmp_ledger_path};
 use solana_perf::packet::to_packets_chunked;
@@ -287,11 +287,7 @@ fn simulate_process_entries(
     }
 
     // Transfer lamports to each other
-    let entry = Entry {
-        num_hashes: 1,
-        hash: next_hash(&bank.last_blockhash(), 1, &tx_vector),
-        transactions: tx_vector,
-    };
+    let entry = Entry::new(&bank.last_blockhash(), 1, tx_vector);
     process_entries(&bank, &[entry], randomize_txs, None, None).unwrap();
 }
 
@@ -3,7 +3,7 @@
 extern crate solana_core;
 extern crate test;
 
-use solana_core::consensus::Tower;
+use solana_core::{consensus::Tower, tower_storage::FileTowerStorage};
 use solana_runtime::bank::Bank;
 use solana_runtime::bank_forks::BankForks;
 use solana_sdk::{
@@ -17,7 +17,7 @@ use test::Bencher;
 #[bench]
 fn bench_save_tower(bench: &mut Bencher) {
     let dir = TempDir::new().unwrap();
-    let path = dir.path();
+    let tower_storage = FileTowerStorage::new(dir.path().to_path_buf());
 
     let vote_account_pubkey = &Pubkey::default();
     let node_keypair = Arc::new(Keypair::new());
@@ -27,10 +27,9 @@ fn bench_save_tower(bench: &mut Bencher) {
         &vote_account_pubkey,
         0,
         &heaviest_bank,
-        &path,
     );
 
     bench.iter(move || {
-        tower.save(&node_keypair).unwrap();
+        tower.save(&tower_storage, &node_keypair).unwrap();
     });
 }
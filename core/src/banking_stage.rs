@@ -33,10 +33,11 @@ use solana_sdk::{
         Slot, DEFAULT_TICKS_PER_SLOT, MAX_PROCESSING_AGE, MAX_TRANSACTION_FORWARDING_DELAY,
         MAX_TRANSACTION_FORWARDING_DELAY_GPU,
     },
+    compute_budget, feature_set,
     poh_config::PohConfig,
     pubkey::Pubkey,
     timing::{duration_as_ms, timestamp},
-    transaction::{self, Transaction, TransactionError},
+    transaction::{self, Transaction, TransactionError, VersionedTransaction},
 };
 use std::{
     cmp, env,
@@ -449,7 +450,7 @@ impl BankingStage {
     }
 
     /// Convert the transactions from a blob of binary data to a vector of transactions
-    fn deserialize_transactions(p: &Packets) -> Vec<Option<Transaction>> {
+    fn deserialize_transactions(p: &Packets) -> Vec<Option<VersionedTransaction>> {
         p.packets
             .iter()
             .map(|x| limited_deserialize(&x.data[0..x.meta.size]).ok())
@@ -459,7 +460,7 @@ impl BankingStage {
     #[allow(clippy::match_wild_err_arm)]
     fn record_transactions(
         bank_slot: Slot,
-        txs: &[Transaction],
+        txs: &[VersionedTransaction],
         results: &[TransactionProcessResult],
         poh: &Arc<Mutex<PohRecorder>>,
     ) -> (Result<usize, PohRecorderError>, Vec<usize>) {
@@ -516,6 +517,7 @@ impl BankingStage {
         bank: &Arc<Bank>,
         poh: &Arc<Mutex<PohRecorder>>,
         batch: &TransactionBatch,
+        versioned_txs: &[VersionedTransaction],
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: &ReplayVoteSender,
     ) -> (Result<usize, PohRecorderError>, Vec<usize>) {
@@ -535,9 +537,12 @@ impl BankingStage {
             results,
             inner_instructions,
             transaction_logs,
+            transaction_log_events,
+            transaction_return_data,
             mut retryable_txs,
             tx_count,
             signature_count,
+            compute_units_consumed,
         ) = bank.load_and_execute_transactions(
             batch,
             MAX_PROCESSING_AGE,
@@ -550,7 +555,7 @@ impl BankingStage {
 
         let mut record_time = Measure::start("record_time");
         let (num_to_commit, retryable_record_txs) =
-            Self::record_transactions(bank.slot(), txs, &results, poh);
+            Self::record_transactions(bank.slot(), versioned_txs, &results, poh);
         retryable_txs.extend(retryable_record_txs);
         if num_to_commit.is_err() {
             return (num_to_commit, retryable_txs);
@@ -569,6 +574,7 @@ impl BankingStage {
                 &results,
                 tx_count,
                 signature_count,
+                compute_units_consumed,
             );
 
             bank_utils::find_and_send_votes(txs, &tx_results, Some(gossip_vote_sender));
@@ -577,11 +583,14 @@ impl BankingStage {
                 send_transaction_status_batch(
                     bank.clone(),
                     batch.transactions(),
+                    versioned_txs,
                     batch.iteration_order_vec(),
                     tx_results.processing_results,
                     TransactionBalancesSet::new(pre_balances, post_balances),
                     inner_instructions,
                     transaction_logs,
+                    transaction_log_events,
+                    transaction_return_data,
                     sender,
                 );
             }
@@ -605,6 +614,7 @@ impl BankingStage {
     pub fn process_and_record_transactions(
         bank: &Arc<Bank>,
         txs: &[Transaction],
+        versioned_txs: &[VersionedTransaction],
         poh: &Arc<Mutex<PohRecorder>>,
         chunk_offset: usize,
         transaction_status_sender: Option<TransactionStatusSender>,
@@ -620,6 +630,7 @@ impl BankingStage {
             bank,
             poh,
             &batch,
+            versioned_txs,
             transaction_status_sender,
             gossip_vote_sender,
         );
@@ -648,6 +659,7 @@ impl BankingStage {
     fn process_transactions(
         bank: &Arc<Bank>,
         transactions: &[Transaction],
+        versioned_transactions: &[VersionedTransaction],
         poh: &Arc<Mutex<PohRecorder>>,
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: &ReplayVoteSender,
@@ -655,14 +667,31 @@ impl BankingStage {
         let mut chunk_start = 0;
         let mut unprocessed_txs = vec![];
         while chunk_start != transactions.len() {
-            let chunk_end = std::cmp::min(
+            let mut chunk_end = std::cmp::min(
                 transactions.len(),
                 chunk_start + MAX_NUM_TRANSACTIONS_PER_BATCH,
             );
+            if bank
+                .feature_set
+                .is_active(&feature_set::transaction_wide_compute_cap::id())
+            {
+                chunk_end = chunk_start
+                    + Self::num_transactions_within_block_units(
+                        bank,
+                        &transactions[chunk_start..chunk_end],
+                    );
+                if chunk_start == chunk_end {
+                    // The block's compute budget is exhausted, hold the remaining transactions
+                    // for the next leader slot
+                    unprocessed_txs.extend(chunk_start..transactions.len());
+                    break;
+                }
+            }
 
             let (result, retryable_txs_in_chunk) = Self::process_and_record_transactions(
                 bank,
                 &transactions[chunk_start..chunk_end],
+                &versioned_transactions[chunk_start..chunk_end],
                 poh,
                 chunk_start,
                 transaction_status_sender.clone(),
@@ -692,12 +721,30 @@ impl BankingStage {
         (chunk_start, unprocessed_txs)
     }
 
+    // Returns how many of the leading `transactions` fit into the compute units left in the
+    // bank's block, assuming each consumes the full compute budget it requested
+    fn num_transactions_within_block_units(bank: &Bank, transactions: &[Transaction]) -> usize {
+        let mut block_units = bank.compute_units_consumed();
+        transactions
+            .iter()
+            .take_while(|tx| {
+                // Invalid requests fail at execution without consuming any units
+                let units = bank
+                    .bpf_compute_budget_for_message(tx.message())
+                    .map(|bpf_compute_budget| bpf_compute_budget.max_units)
+                    .unwrap_or(0);
+                block_units = block_units.saturating_add(units);
+                block_units <= compute_budget::MAX_BLOCK_UNITS
+            })
+            .count()
+    }
+
     // This function returns a vector of transactions that are not None. It also returns a vector
     // with position of the transaction in the input list
-    fn filter_transaction_indexes(
-        transactions: Vec<Option<Transaction>>,
+    fn filter_transaction_indexes<T>(
+        transactions: Vec<Option<T>>,
         indexes: &[usize],
-    ) -> (Vec<Transaction>, Vec<usize>) {
+    ) -> (Vec<T>, Vec<usize>) {
         transactions
             .into_iter()
             .zip(indexes)
@@ -734,12 +781,13 @@ impl BankingStage {
             .collect()
     }
 
-    // This function deserializes packets into transactions and returns non-None transactions
+    // This function deserializes packets into transactions and returns non-None transactions,
+    // both as received and resolved against the bank's address lookup tables
     fn transactions_from_packets(
         msgs: &Packets,
         transaction_indexes: &[usize],
-        secp256k1_program_enabled: bool,
-    ) -> (Vec<Transaction>, Vec<usize>) {
+        bank: &Bank,
+    ) -> (Vec<Transaction>, Vec<VersionedTransaction>, Vec<usize>) {
         let packets = Packets::new(
             transaction_indexes
                 .iter()
@@ -747,27 +795,24 @@ impl BankingStage {
                 .collect_vec(),
         );
 
-        let transactions = Self::deserialize_transactions(&packets);
-        let maybe_secp_verified_transactions: Vec<_> = if secp256k1_program_enabled {
-            transactions
-                .into_iter()
-                .map(|tx| {
-                    if let Some(tx) = tx {
-                        if tx.verify_precompiles().is_ok() {
-                            Some(tx)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        } else {
-            transactions
-        };
+        let secp256k1_program_enabled = bank.secp256k1_program_enabled();
+        let ed25519_program_enabled = bank.ed25519_program_enabled();
+        let resolved_transactions = Self::deserialize_transactions(&packets)
+            .into_iter()
+            .map(|tx| {
+                let tx = tx?;
+                tx.verify_precompiles(secp256k1_program_enabled, ed25519_program_enabled)
+                    .ok()?;
+                // Signatures were verified against the serialized message by sigverify
+                let resolved_tx = bank.resolve_versioned_transaction(tx.clone()).ok()?;
+                Some((resolved_tx, tx))
+            })
+            .collect();
 
-        Self::filter_transaction_indexes(maybe_secp_verified_transactions, &transaction_indexes)
+        let (transactions, transaction_indexes) =
+            Self::filter_transaction_indexes(resolved_transactions, &transaction_indexes);
+        let (transactions, versioned_transactions) = transactions.into_iter().unzip();
+        (transactions, versioned_transactions, transaction_indexes)
     }
 
     /// This function filters pending packets that are still valid
@@ -816,11 +861,8 @@ impl BankingStage {
         transaction_status_sender: Option<TransactionStatusSender>,
         gossip_vote_sender: &ReplayVoteSender,
     ) -> (usize, usize, Vec<usize>) {
-        let (transactions, transaction_to_packet_indexes) = Self::transactions_from_packets(
-            msgs,
-            &packet_indexes,
-            bank.secp256k1_program_enabled(),
-        );
+        let (transactions, versioned_transactions, transaction_to_packet_indexes) =
+            Self::transactions_from_packets(msgs, &packet_indexes, bank);
         debug!(
             "bank: {} filtered transactions {}",
             bank.slot(),
@@ -832,6 +874,7 @@ impl BankingStage {
         let (processed, unprocessed_tx_indexes) = Self::process_transactions(
             bank,
             &transactions,
+            &versioned_transactions,
             poh,
             transaction_status_sender,
             gossip_vote_sender,
@@ -869,11 +912,8 @@ impl BankingStage {
             }
         }
 
-        let (transactions, transaction_to_packet_indexes) = Self::transactions_from_packets(
-            msgs,
-            &transaction_indexes,
-            bank.secp256k1_program_enabled(),
-        );
+        let (transactions, _, transaction_to_packet_indexes) =
+            Self::transactions_from_packets(msgs, &transaction_indexes, bank);
 
         let tx_count = transaction_to_packet_indexes.len();
 
@@ -1095,15 +1135,23 @@ mod tests {
     use solana_perf::packet::to_packets;
     use solana_runtime::bank::HashAgeKind;
     use solana_sdk::{
+        ed25519_instruction::new_ed25519_instruction,
+        feature_set::FeatureSet,
+        hash::Hash,
         instruction::InstructionError,
+        message::{MessageV0, VersionedMessage},
         signature::{Keypair, Signer},
-        system_instruction::SystemError,
+        system_instruction::{self, SystemError},
         system_transaction,
         transaction::TransactionError,
     };
     use solana_transaction_status::TransactionWithStatusMeta;
     use std::{sync::atomic::Ordering, thread::sleep};
 
+    fn into_versioned(transactions: &[Transaction]) -> Vec<VersionedTransaction> {
+        transactions.iter().cloned().map(Into::into).collect()
+    }
+
     #[test]
     fn test_banking_stage_shutdown1() {
         let genesis_config = create_genesis_config(2).genesis_config;
@@ -1296,7 +1344,7 @@ mod tests {
                 if !entries.is_empty() {
                     blockhash = entries.last().unwrap().hash;
                     for entry in entries {
-                        bank.process_transactions(&entry.transactions)
+                        bank.process_versioned_transactions(entry.transactions)
                             .iter()
                             .for_each(|x| assert_eq!(*x, Ok(())));
                     }
@@ -1403,8 +1451,8 @@ mod tests {
                 .collect();
 
             let bank = Bank::new(&genesis_config);
-            for entry in &entries {
-                bank.process_transactions(&entry.transactions)
+            for entry in entries {
+                bank.process_versioned_transactions(entry.transactions)
                     .iter()
                     .for_each(|x| assert_eq!(*x, Ok(())));
             }
@@ -1452,9 +1500,10 @@ mod tests {
             let keypair2 = Keypair::new();
             let pubkey2 = solana_sdk::pubkey::new_rand();
 
-            let transactions = vec![
-                system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash()),
-                system_transaction::transfer(&keypair2, &pubkey2, 1, genesis_config.hash()),
+            let transactions: Vec<VersionedTransaction> = vec![
+                system_transaction::transfer(&mint_keypair, &pubkey, 1, genesis_config.hash())
+                    .into(),
+                system_transaction::transfer(&keypair2, &pubkey2, 1, genesis_config.hash()).into(),
             ];
 
             let mut results = vec![
@@ -1764,6 +1813,7 @@ mod tests {
             BankingStage::process_and_record_transactions(
                 &bank,
                 &transactions,
+                &into_versioned(&transactions),
                 &poh_recorder,
                 0,
                 None,
@@ -1801,6 +1851,7 @@ mod tests {
                 BankingStage::process_and_record_transactions(
                     &bank,
                     &transactions,
+                    &into_versioned(&transactions),
                     &poh_recorder,
                     0,
                     None,
@@ -1861,6 +1912,7 @@ mod tests {
             let (result, unprocessed) = BankingStage::process_and_record_transactions(
                 &bank,
                 &transactions,
+                &into_versioned(&transactions),
                 &poh_recorder,
                 0,
                 None,
@@ -1910,6 +1962,69 @@ mod tests {
             .collect_vec();
     }
 
+    #[test]
+    fn test_transactions_from_packets_ed25519_precompile() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let mut bank = Bank::new(&genesis_config);
+        let keypair = Keypair::new();
+        let mut instruction = new_ed25519_instruction(&keypair, b"hello");
+        let last = instruction.data.len() - 1;
+        instruction.data[last] ^= 0xff;
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            Hash::default(),
+        );
+        let packets = to_packets(&[tx]).pop().unwrap();
+
+        bank.feature_set = Arc::new(FeatureSet::default());
+        let (transactions, versioned_transactions, _) =
+            BankingStage::transactions_from_packets(&packets, &[0], &bank);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(versioned_transactions.len(), 1);
+        bank.feature_set = Arc::new(FeatureSet::all_enabled());
+        let (transactions, _, _) = BankingStage::transactions_from_packets(&packets, &[0], &bank);
+        assert!(transactions.is_empty());
+    }
+
+    #[test]
+    fn test_transactions_from_packets_versioned() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let mut bank = Bank::new(&genesis_config);
+        bank.feature_set = Arc::new(FeatureSet::all_enabled());
+
+        let to = solana_sdk::pubkey::new_rand();
+        let legacy_tx: VersionedTransaction =
+            system_transaction::transfer(&mint_keypair, &to, 1, genesis_config.hash()).into();
+        let mut message = MessageV0::new_with_lookup_tables(
+            &[system_instruction::transfer(&mint_keypair.pubkey(), &to, 1)],
+            Some(&mint_keypair.pubkey()),
+            &[(solana_sdk::pubkey::new_rand(), vec![to])],
+        );
+        message.recent_blockhash = genesis_config.hash();
+        let missing_table_tx =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&mint_keypair]).unwrap();
+
+        let packets = to_packets(&[missing_table_tx, legacy_tx.clone()])
+            .pop()
+            .unwrap();
+
+        // Transactions whose lookup tables can't be resolved are dropped
+        let (transactions, versioned_transactions, transaction_indexes) =
+            BankingStage::transactions_from_packets(&packets, &[0, 1], &bank);
+        assert_eq!(
+            transactions,
+            vec![legacy_tx.clone().into_legacy_transaction().unwrap()]
+        );
+        assert_eq!(versioned_transactions, vec![legacy_tx]);
+        assert_eq!(transaction_indexes, vec![1]);
+    }
+
     #[test]
     fn test_process_transactions_returns_unprocessed_txs() {
         solana_logger::setup();
@@ -1954,6 +2069,7 @@ mod tests {
                 BankingStage::process_transactions(
                     &bank,
                     &transactions,
+                    &into_versioned(&transactions),
                     &poh_recorder,
                     None,
                     &gossip_vote_sender,
@@ -2039,6 +2155,7 @@ mod tests {
             let _ = BankingStage::process_and_record_transactions(
                 &bank,
                 &transactions,
+                &into_versioned(&transactions),
                 &poh_recorder,
                 0,
                 Some(transaction_status_sender),
@@ -2072,4 +2189,35 @@ mod tests {
         }
         Blockstore::destroy(&ledger_path).unwrap();
     }
+
+    #[test]
+    fn test_num_transactions_within_block_units() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let mut bank = Bank::new(&genesis_config);
+        bank.feature_set = Arc::new(FeatureSet::all_enabled());
+
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                compute_budget::request_units(compute_budget::MAX_UNITS),
+                system_instruction::transfer(
+                    &mint_keypair.pubkey(),
+                    &solana_sdk::pubkey::new_rand(),
+                    1,
+                ),
+            ],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            genesis_config.hash(),
+        );
+        let transactions = vec![transaction; MAX_NUM_TRANSACTIONS_PER_BATCH];
+
+        assert_eq!(
+            BankingStage::num_transactions_within_block_units(&bank, &transactions),
+            (compute_budget::MAX_BLOCK_UNITS / compute_budget::MAX_UNITS) as usize
+        );
+    }
 }
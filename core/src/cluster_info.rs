@@ -22,6 +22,7 @@ use crate::{
         Version, Vote, MAX_WALLCLOCK,
     },
     data_budget::DataBudget,
+    duplicate_shred::{self, DuplicateShred, DuplicateShredIndex, LeaderScheduleFn},
     epoch_slots::EpochSlots,
     ping_pong::{self, PingCache, Pong},
     result::{Error, Result},
@@ -39,7 +40,7 @@ use itertools::Itertools;
 use rayon::prelude::*;
 use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde::ser::Serialize;
-use solana_ledger::staking_utils;
+use solana_ledger::{shred::Shred, staking_utils};
 use solana_measure::measure::Measure;
 use solana_measure::thread_mem_usage;
 use solana_metrics::{inc_new_counter_debug, inc_new_counter_error};
@@ -87,6 +88,10 @@ pub const GOSSIP_SLEEP_MILLIS: u64 = 100;
 /// The maximum size of a bloom filter
 pub const MAX_BLOOM_SIZE: usize = MAX_CRDS_OBJECT_SIZE;
 pub const MAX_CRDS_OBJECT_SIZE: usize = 928;
+/// Max size of serialized DuplicateShred so that its CrdsValue, i.e. the
+/// signature (64 bytes), CrdsData enum tag (4 bytes) and DuplicateShredIndex
+/// (2 bytes), fits in MAX_CRDS_OBJECT_SIZE.
+const DUPLICATE_SHRED_MAX_PAYLOAD_SIZE: usize = MAX_CRDS_OBJECT_SIZE - 70;
 /// A hard limit on incoming gossip messages
 /// Chosen to be able to handle 1Gbps of pure gossip traffic
 /// 128MB/PACKET_DATA_SIZE
@@ -237,6 +242,8 @@ struct GossipStats {
     push_vote_read: Counter,
     vote_process_push: Counter,
     get_votes: Counter,
+    push_duplicate_shred_read: Counter,
+    get_duplicate_shreds: Counter,
     get_accounts_hash: Counter,
     get_snapshot_hash: Counter,
     all_tvu_peers: Counter,
@@ -424,7 +431,7 @@ pub fn make_accounts_hashes_message(
 type Ping = ping_pong::Ping<[u8; GOSSIP_PING_TOKEN_SIZE]>;
 
 // TODO These messages should go through the gpu pipeline for spam filtering
-#[frozen_abi(digest = "3jHXixLRv6fuCykW47hBZSwFuwDjbZShR73GVQB6TjGr")]
+#[frozen_abi(digest = "3fVJMSv84en83RbK2g2dDPh8Km6mtFjfUfLxXAPkAyA7")]
 #[derive(Serialize, Deserialize, Debug, AbiEnumVisitor, AbiExample)]
 #[allow(clippy::large_enum_variant)]
 enum Protocol {
@@ -987,6 +994,52 @@ impl ClusterInfo {
             .push((entry, now));
     }
 
+    /// Pushes a proof that `shred` and `other_payload` are conflicting shreds
+    /// signed by the slot leader, split into DuplicateShred chunks. Indices
+    /// are allocated by overwriting the oldest duplicate shreds from this node.
+    pub fn push_duplicate_shred(
+        &self,
+        shred: &Shred,
+        other_payload: &[u8],
+        leader: impl LeaderScheduleFn,
+    ) -> Result<()> {
+        let now = timestamp();
+        let chunks = duplicate_shred::from_shred(
+            shred.clone(),
+            self.id(),
+            other_payload.to_vec(),
+            leader,
+            now,
+            DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+        )?;
+        let mut indices: Vec<DuplicateShredIndex> = {
+            let r_gossip = self.time_gossip_read_lock(
+                "gossip_read_push_duplicate_shred",
+                &self.stats.push_duplicate_shred_read,
+            );
+            let mut current: Vec<_> = (0..duplicate_shred::MAX_DUPLICATE_SHREDS)
+                .map(|ix| {
+                    let wallclock = r_gossip
+                        .crds
+                        .lookup(&CrdsValueLabel::DuplicateShred(ix, self.id()))
+                        .map(CrdsValue::wallclock);
+                    (wallclock, ix)
+                })
+                .collect();
+            // Vacant indices first, then the oldest ones.
+            current.sort_unstable();
+            current.into_iter().map(|(_, ix)| ix).collect()
+        };
+        indices.reverse();
+        let mut pending = self.local_message_pending_push_queue.write().unwrap();
+        for chunk in chunks {
+            let ix = indices.pop().expect("chunks exceed MAX_DUPLICATE_SHREDS");
+            let entry = CrdsValue::new_signed(CrdsData::DuplicateShred(ix, chunk), &self.keypair);
+            pending.push((entry, now));
+        }
+        Ok(())
+    }
+
     pub fn send_vote(&self, vote: &Transaction) -> Result<()> {
         let tpu = self.my_contact_info().tpu;
         let buf = serialize(vote)?;
@@ -1017,6 +1070,26 @@ impl ClusterInfo {
         (labels, txs, max_ts)
     }
 
+    /// Get duplicate shred chunks in the crds inserted after `since`.
+    ///
+    /// * return - The chunks, and the max insert timestamp from the new set.
+    pub fn get_duplicate_shreds(&self, since: u64) -> (Vec<DuplicateShred>, u64) {
+        let mut max_ts = since;
+        let chunks: Vec<_> = self
+            .time_gossip_read_lock("get_duplicate_shreds", &self.stats.get_duplicate_shreds)
+            .crds
+            .values()
+            .filter(|x| x.insert_timestamp > since)
+            .filter_map(|x| {
+                let chunk = x.value.duplicate_shred()?;
+                max_ts = std::cmp::max(x.insert_timestamp, max_ts);
+                Some(chunk.clone())
+            })
+            .collect();
+        inc_new_counter_info!("cluster_info-get_duplicate_shreds-count", chunks.len());
+        (chunks, max_ts)
+    }
+
     pub fn get_snapshot_hash(&self, slot: Slot) -> Vec<(Pubkey, Hash)> {
         self.time_gossip_read_lock("get_snapshot_hash", &self.stats.get_snapshot_hash)
             .crds
@@ -2622,6 +2695,16 @@ impl ClusterInfo {
                     i64
                 ),
                 ("get_votes", self.stats.get_votes.clear(), i64),
+                (
+                    "push_duplicate_shred_read",
+                    self.stats.push_duplicate_shred_read.clear(),
+                    i64
+                ),
+                (
+                    "get_duplicate_shreds",
+                    self.stats.get_duplicate_shreds.clear(),
+                    i64
+                ),
                 (
                     "get_accounts_hash",
                     self.stats.get_accounts_hash.clear(),
@@ -3791,6 +3874,44 @@ mod tests {
         assert_eq!(max_ts, new_max_ts);
     }
 
+    #[test]
+    fn test_push_duplicate_shred() {
+        let mut rng = rand::thread_rng();
+        let keys = Keypair::new();
+        let contact_info = ContactInfo::new_localhost(&keys.pubkey(), 0);
+        let cluster_info = ClusterInfo::new_with_invalid_keypair(contact_info);
+        let now = timestamp();
+        let (chunks, max_ts) = cluster_info.get_duplicate_shreds(now);
+        assert!(chunks.is_empty());
+        assert_eq!(max_ts, now);
+
+        let leader = Keypair::new();
+        let (slot, index) = (53084024, 5);
+        let shred1 = duplicate_shred::tests::new_rand_shred(&mut rng, slot, index, &leader);
+        let shred2 = duplicate_shred::tests::new_rand_shred(&mut rng, slot, index, &leader);
+        cluster_info
+            .push_duplicate_shred(&shred1, &shred2.payload, |_| Some(leader.pubkey()))
+            .unwrap();
+        cluster_info.flush_push_queue();
+        let (chunks, max_ts) = cluster_info.get_duplicate_shreds(now - 1);
+        assert!(max_ts >= now - 1);
+        for chunk in &chunks {
+            let value = CrdsValue::new_unsigned(CrdsData::DuplicateShred(0, chunk.clone()));
+            assert!(value.size() <= MAX_CRDS_OBJECT_SIZE as u64);
+            assert_eq!(chunk.from, keys.pubkey());
+        }
+        let (shred3, shred4) =
+            duplicate_shred::into_shreds(chunks, |_| Some(leader.pubkey())).unwrap();
+        assert_eq!(shred1, shred3);
+        assert_eq!(shred2, shred4);
+
+        // A proof not signed by the slot leader is not pushed.
+        let shred2 = duplicate_shred::tests::new_rand_shred(&mut rng, slot, index, &keys);
+        assert!(cluster_info
+            .push_duplicate_shred(&shred1, &shred2.payload, |_| Some(leader.pubkey()))
+            .is_err());
+    }
+
     #[test]
     fn test_push_epoch_slots() {
         let keys = Keypair::new();
@@ -1,6 +1,7 @@
 use crate::{
     progress_map::{LockoutIntervals, ProgressMap},
     pubkey_references::PubkeyReferences,
+    tower_storage::TowerStorage,
 };
 use chrono::prelude::*;
 use solana_ledger::{ancestor_iterator::AncestorIterator, blockstore::Blockstore, blockstore_db};
@@ -19,15 +20,12 @@ use solana_sdk::{
 };
 use solana_vote_program::{
     vote_instruction,
-    vote_state::{BlockTimestamp, Lockout, Vote, VoteState, MAX_LOCKOUT_HISTORY},
+    vote_state::{BlockTimestamp, Lockout, Vote, VoteState, VoteStateUpdate, MAX_LOCKOUT_HISTORY},
 };
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
-    fs::{self, File},
-    io::BufReader,
     ops::Bound::{Included, Unbounded},
-    path::{Path, PathBuf},
     sync::Arc,
 };
 use thiserror::Error;
@@ -67,6 +65,33 @@ impl SwitchForkDecision {
         }
     }
 
+    pub fn to_vote_state_update_instruction(
+        &self,
+        vote_state_update: VoteStateUpdate,
+        vote_account_pubkey: &Pubkey,
+        authorized_voter_pubkey: &Pubkey,
+    ) -> Option<Instruction> {
+        match self {
+            SwitchForkDecision::FailedSwitchThreshold(_, total_stake) => {
+                assert_ne!(*total_stake, 0);
+                None
+            }
+            SwitchForkDecision::SameFork => Some(vote_instruction::update_vote_state(
+                vote_account_pubkey,
+                authorized_voter_pubkey,
+                vote_state_update,
+            )),
+            SwitchForkDecision::SwitchProof(switch_proof_hash) => {
+                Some(vote_instruction::update_vote_state_switch(
+                    vote_account_pubkey,
+                    authorized_voter_pubkey,
+                    vote_state_update,
+                    *switch_proof_hash,
+                ))
+            }
+        }
+    }
+
     pub fn can_vote(&self) -> bool {
         !matches!(self, SwitchForkDecision::FailedSwitchThreshold(_, _))
     }
@@ -101,10 +126,6 @@ pub struct Tower {
     last_vote: Vote,
     last_timestamp: BlockTimestamp,
     #[serde(skip)]
-    path: PathBuf,
-    #[serde(skip)]
-    tmp_path: PathBuf, // used before atomic fs::rename()
-    #[serde(skip)]
     // Restored last voted slot which cannot be found in SlotHistory at replayed root
     // (This is a special field for slashing-free validator restart with edge cases).
     // This could be emptied after some time; but left intact indefinitely for easier
@@ -125,8 +146,6 @@ impl Default for Tower {
             lockouts: VoteState::default(),
             last_vote: Vote::default(),
             last_timestamp: BlockTimestamp::default(),
-            path: PathBuf::default(),
-            tmp_path: PathBuf::default(),
             stray_restored_slot: Option::default(),
             last_switch_threshold_check: Option::default(),
         };
@@ -142,14 +161,9 @@ impl Tower {
         vote_account_pubkey: &Pubkey,
         root: Slot,
         bank: &Bank,
-        path: &Path,
     ) -> Self {
-        let path = Self::get_filename(&path, node_pubkey);
-        let tmp_path = Self::get_tmp_filename(&path);
         let mut tower = Self {
             node_pubkey: *node_pubkey,
-            path,
-            tmp_path,
             ..Tower::default()
         };
         tower.initialize_lockouts_from_bank(vote_account_pubkey, root, bank);
@@ -176,7 +190,6 @@ impl Tower {
 
     pub fn new_from_bankforks(
         bank_forks: &BankForks,
-        ledger_path: &Path,
         my_pubkey: &Pubkey,
         vote_account: &Pubkey,
     ) -> Self {
@@ -199,13 +212,7 @@ impl Tower {
             Tower::find_heaviest_bank(&bank_forks, &my_pubkey).unwrap_or_else(|| root_bank.clone())
         };
 
-        Self::new(
-            &my_pubkey,
-            &vote_account,
-            root,
-            &heaviest_bank,
-            &ledger_path,
-        )
+        Self::new(&my_pubkey, &vote_account, root, &heaviest_bank)
     }
 
     pub(crate) fn collect_vote_lockouts<F>(
@@ -424,6 +431,17 @@ impl Tower {
         last_vote
     }
 
+    /// The local lockouts and root, with the hash and timestamp of `last_vote`, for syncing the
+    /// on-chain vote state to this tower
+    pub fn vote_state_update(&self, last_vote: &Vote) -> VoteStateUpdate {
+        VoteStateUpdate {
+            lockouts: self.lockouts.votes.clone(),
+            root: self.lockouts.root_slot,
+            hash: last_vote.hash,
+            timestamp: last_vote.timestamp,
+        }
+    }
+
     fn maybe_timestamp(&mut self, current_slot: Slot) -> Option<UnixTimestamp> {
         if current_slot > self.last_timestamp.slot
             || self.last_timestamp.slot == 0 && current_slot == self.last_timestamp.slot
@@ -1097,16 +1115,11 @@ impl Tower {
         self.lockouts.root_slot = Some(root);
     }
 
-    pub fn get_filename(path: &Path, node_pubkey: &Pubkey) -> PathBuf {
-        path.join(format!("tower-{}", node_pubkey))
-            .with_extension("bin")
-    }
-
-    pub fn get_tmp_filename(path: &Path) -> PathBuf {
-        path.with_extension("bin.new")
-    }
-
-    pub fn save(&self, node_keypair: &Arc<Keypair>) -> Result<()> {
+    pub fn save(
+        &self,
+        tower_storage: &dyn TowerStorage,
+        node_keypair: &Arc<Keypair>,
+    ) -> Result<()> {
         let mut measure = Measure::start("tower_save-ms");
 
         if self.node_pubkey != node_keypair.pubkey() {
@@ -1117,18 +1130,9 @@ impl Tower {
             )));
         }
 
-        let filename = &self.path;
-        let new_filename = &self.tmp_path;
-        {
-            // overwrite anything if exists
-            let mut file = File::create(&new_filename)?;
-            let saved_tower = SavedTower::new(self, node_keypair)?;
-            bincode::serialize_into(&mut file, &saved_tower)?;
-            // file.sync_all() hurts performance; pipeline sync-ing and submitting votes to the cluster!
-        }
+        let saved_tower = SavedTower::new(self, node_keypair)?;
+        tower_storage.store(&self.node_pubkey, &saved_tower)?;
         trace!("persisted votes: {:?}", self.voted_slots());
-        fs::rename(&new_filename, &filename)?;
-        // self.path.parent().sync_all() hurts performance same as the above sync
 
         measure.stop();
         inc_new_counter_info!("tower_save-ms", measure.as_ms() as usize);
@@ -1136,22 +1140,12 @@ impl Tower {
         Ok(())
     }
 
-    pub fn restore(path: &Path, node_pubkey: &Pubkey) -> Result<Self> {
-        let filename = Self::get_filename(path, node_pubkey);
-
-        // Ensure to create parent dir here, because restore() precedes save() always
-        fs::create_dir_all(&filename.parent().unwrap())?;
-
-        let file = File::open(&filename)?;
-        let mut stream = BufReader::new(file);
-
-        let saved_tower: SavedTower = bincode::deserialize_from(&mut stream)?;
+    pub fn restore(tower_storage: &dyn TowerStorage, node_pubkey: &Pubkey) -> Result<Self> {
+        let saved_tower = tower_storage.load(node_pubkey)?;
         if !saved_tower.verify(node_pubkey) {
             return Err(TowerError::InvalidSignature);
         }
-        let mut tower = saved_tower.deserialize()?;
-        tower.path = filename;
-        tower.tmp_path = Self::get_tmp_filename(&tower.path);
+        let tower = saved_tower.deserialize()?;
 
         // check that the tower actually belongs to this node
         if &tower.node_pubkey != node_pubkey {
@@ -1189,6 +1183,12 @@ pub enum TowerError {
 
     #[error("The tower is useless because of new hard fork: {0}")]
     HardFork(Slot),
+
+    #[error("The tower was saved by another validator instance: {0}")]
+    ConcurrentUpdate(String),
+
+    #[error("Tower storage error: {0}")]
+    StorageError(String),
 }
 
 impl TowerError {
@@ -1227,8 +1227,8 @@ impl SavedTower {
 // Given an untimely crash, tower may have roots that are not reflected in blockstore,
 // or the reverse of this.
 // That's because we don't impose any ordering guarantee or any kind of write barriers
-// between tower (plain old POSIX fs calls) and blockstore (through RocksDB), when
-// `ReplayState::handle_votable_bank()` saves tower before setting blockstore roots.
+// between tower (saved by `VotingService`) and blockstore (through RocksDB), which
+// `ReplayState::handle_votable_bank()` sets roots in without waiting for the tower save.
 pub fn reconcile_blockstore_roots_with_tower(
     tower: &Tower,
     blockstore: &Blockstore,
@@ -1280,6 +1280,7 @@ pub mod test {
         heaviest_subtree_fork_choice::HeaviestSubtreeForkChoice,
         progress_map::ForkProgress,
         replay_stage::{HeaviestForkFailures, ReplayStage},
+        tower_storage::FileTowerStorage,
     };
     use solana_ledger::{blockstore::make_slot_entries, get_tmp_ledger_path};
     use solana_runtime::{
@@ -1301,6 +1302,7 @@ pub mod test {
         collections::HashMap,
         fs::{remove_file, OpenOptions},
         io::{Read, Seek, SeekFrom, Write},
+        path::PathBuf,
         rc::Rc,
         sync::RwLock,
     };
@@ -1660,6 +1662,67 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn test_to_vote_state_update_instruction() {
+        let vote_state_update = VoteStateUpdate::default();
+        let mut decision = SwitchForkDecision::FailedSwitchThreshold(0, 1);
+        assert!(decision
+            .to_vote_state_update_instruction(
+                vote_state_update.clone(),
+                &Pubkey::default(),
+                &Pubkey::default()
+            )
+            .is_none());
+        decision = SwitchForkDecision::SameFork;
+        assert_eq!(
+            decision.to_vote_state_update_instruction(
+                vote_state_update.clone(),
+                &Pubkey::default(),
+                &Pubkey::default()
+            ),
+            Some(vote_instruction::update_vote_state(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                vote_state_update.clone(),
+            ))
+        );
+        decision = SwitchForkDecision::SwitchProof(Hash::default());
+        assert_eq!(
+            decision.to_vote_state_update_instruction(
+                vote_state_update.clone(),
+                &Pubkey::default(),
+                &Pubkey::default()
+            ),
+            Some(vote_instruction::update_vote_state_switch(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                vote_state_update,
+                Hash::default()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_vote_state_update() {
+        let mut tower = Tower::new_for_tests(0, 0.67);
+        for slot in 0..MAX_LOCKOUT_HISTORY as u64 {
+            tower.record_vote(slot, Hash::default());
+        }
+        let hash = Hash::new_unique();
+        tower.record_vote(MAX_LOCKOUT_HISTORY as u64, hash);
+
+        let last_vote = tower.last_vote_and_timestamp();
+        let vote_state_update = tower.vote_state_update(&last_vote);
+        assert_eq!(vote_state_update.lockouts, tower.lockouts.votes);
+        assert_eq!(vote_state_update.root, Some(0));
+        assert_eq!(
+            vote_state_update.last_voted_slot(),
+            Some(MAX_LOCKOUT_HISTORY as u64)
+        );
+        assert_eq!(vote_state_update.hash, hash);
+        assert_eq!(vote_state_update.timestamp, last_vote.timestamp);
+    }
+
     #[test]
     fn test_simple_votes() {
         // Init state
@@ -2465,14 +2528,13 @@ pub mod test {
 
         // Use values that will not match the default derived from BankForks
         let mut tower = Tower::new_for_tests(10, 0.9);
-        tower.path = Tower::get_filename(&dir.path().to_path_buf(), &identity_keypair.pubkey());
-        tower.tmp_path = Tower::get_tmp_filename(&tower.path);
+        let tower_storage = FileTowerStorage::new(dir.path().to_path_buf());
 
         modify_original(&mut tower, &identity_keypair.pubkey());
 
-        tower.save(&identity_keypair).unwrap();
-        modify_serialized(&tower.path);
-        let loaded = Tower::restore(&dir.path(), &identity_keypair.pubkey());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        modify_serialized(&tower_storage.filename(&identity_keypair.pubkey()));
+        let loaded = Tower::restore(&tower_storage, &identity_keypair.pubkey());
 
         (tower, loaded)
     }
@@ -2681,7 +2743,7 @@ pub mod test {
         let identity_keypair = Arc::new(Keypair::new());
         let tower = Tower::new_with_key(&Pubkey::default());
         assert_matches!(
-            tower.save(&identity_keypair),
+            tower.save(&FileTowerStorage::default(), &identity_keypair),
             Err(TowerError::WrongTower(_))
         )
     }
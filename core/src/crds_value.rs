@@ -1,6 +1,7 @@
 use crate::cluster_info::MAX_SNAPSHOT_HASHES;
 use crate::contact_info::ContactInfo;
 use crate::deprecated;
+use crate::duplicate_shred::{DuplicateShred, DuplicateShredIndex, MAX_DUPLICATE_SHREDS};
 use crate::epoch_slots::EpochSlots;
 use bincode::{serialize, serialized_size};
 use rand::Rng;
@@ -79,6 +80,7 @@ pub enum CrdsData {
     EpochSlots(EpochSlotsIndex, EpochSlots),
     LegacyVersion(LegacyVersion),
     Version(Version),
+    DuplicateShred(DuplicateShredIndex, DuplicateShred),
 }
 
 impl Sanitize for CrdsData {
@@ -107,6 +109,13 @@ impl Sanitize for CrdsData {
             }
             CrdsData::LegacyVersion(version) => version.sanitize(),
             CrdsData::Version(version) => version.sanitize(),
+            CrdsData::DuplicateShred(ix, shred) => {
+                if *ix >= MAX_DUPLICATE_SHREDS {
+                    Err(SanitizeError::ValueOutOfBounds)
+                } else {
+                    shred.sanitize()
+                }
+            }
         }
     }
 }
@@ -335,6 +344,7 @@ pub enum CrdsValueLabel {
     AccountsHashes(Pubkey),
     LegacyVersion(Pubkey),
     Version(Pubkey),
+    DuplicateShred(DuplicateShredIndex, Pubkey),
 }
 
 impl fmt::Display for CrdsValueLabel {
@@ -348,6 +358,9 @@ impl fmt::Display for CrdsValueLabel {
             CrdsValueLabel::AccountsHashes(_) => write!(f, "AccountsHashes({})", self.pubkey()),
             CrdsValueLabel::LegacyVersion(_) => write!(f, "LegacyVersion({})", self.pubkey()),
             CrdsValueLabel::Version(_) => write!(f, "Version({})", self.pubkey()),
+            CrdsValueLabel::DuplicateShred(ix, pubkey) => {
+                write!(f, "DuplicateShred({}, {})", ix, pubkey)
+            }
         }
     }
 }
@@ -363,6 +376,7 @@ impl CrdsValueLabel {
             CrdsValueLabel::AccountsHashes(p) => *p,
             CrdsValueLabel::LegacyVersion(p) => *p,
             CrdsValueLabel::Version(p) => *p,
+            CrdsValueLabel::DuplicateShred(_, p) => *p,
         }
     }
 }
@@ -409,6 +423,7 @@ impl CrdsValue {
             CrdsData::EpochSlots(_, p) => p.wallclock,
             CrdsData::LegacyVersion(version) => version.wallclock,
             CrdsData::Version(version) => version.wallclock,
+            CrdsData::DuplicateShred(_, shred) => shred.wallclock,
         }
     }
     pub fn pubkey(&self) -> Pubkey {
@@ -421,6 +436,7 @@ impl CrdsValue {
             CrdsData::EpochSlots(_, p) => p.from,
             CrdsData::LegacyVersion(version) => version.from,
             CrdsData::Version(version) => version.from,
+            CrdsData::DuplicateShred(_, shred) => shred.from,
         }
     }
     pub fn label(&self) -> CrdsValueLabel {
@@ -433,6 +449,7 @@ impl CrdsValue {
             CrdsData::EpochSlots(ix, _) => CrdsValueLabel::EpochSlots(*ix, self.pubkey()),
             CrdsData::LegacyVersion(_) => CrdsValueLabel::LegacyVersion(self.pubkey()),
             CrdsData::Version(_) => CrdsValueLabel::Version(self.pubkey()),
+            CrdsData::DuplicateShred(ix, _) => CrdsValueLabel::DuplicateShred(*ix, self.pubkey()),
         }
     }
     pub fn contact_info(&self) -> Option<&ContactInfo> {
@@ -497,6 +514,20 @@ impl CrdsValue {
         }
     }
 
+    pub fn duplicate_shred(&self) -> Option<&DuplicateShred> {
+        match &self.data {
+            CrdsData::DuplicateShred(_, shred) => Some(shred),
+            _ => None,
+        }
+    }
+
+    pub fn duplicate_shred_index(&self) -> Option<DuplicateShredIndex> {
+        match &self.data {
+            CrdsData::DuplicateShred(ix, _) => Some(*ix),
+            _ => None,
+        }
+    }
+
     /// Return all the possible labels for a record identified by Pubkey.
     pub fn record_labels(key: Pubkey) -> impl Iterator<Item = CrdsValueLabel> {
         const CRDS_VALUE_LABEL_STUBS: [fn(Pubkey) -> CrdsValueLabel; 6] = [
@@ -512,6 +543,7 @@ impl CrdsValue {
             .map(move |f| (f)(key))
             .chain((0..MAX_VOTES).map(move |ix| CrdsValueLabel::Vote(ix, key)))
             .chain((0..MAX_EPOCH_SLOTS).map(move |ix| CrdsValueLabel::EpochSlots(ix, key)))
+            .chain((0..MAX_DUPLICATE_SHREDS).map(move |ix| CrdsValueLabel::DuplicateShred(ix, key)))
     }
 
     /// Returns the size (in bytes) of a CrdsValue
@@ -584,7 +616,8 @@ mod test {
 
     #[test]
     fn test_labels() {
-        let mut hits = [false; 6 + MAX_VOTES as usize + MAX_EPOCH_SLOTS as usize];
+        let mut hits = [false;
+            6 + MAX_VOTES as usize + MAX_EPOCH_SLOTS as usize + MAX_DUPLICATE_SHREDS as usize];
         // this method should cover all the possible labels
         for v in CrdsValue::record_labels(Pubkey::default()) {
             match &v {
@@ -598,6 +631,9 @@ mod test {
                 CrdsValueLabel::EpochSlots(ix, _) => {
                     hits[*ix as usize + MAX_VOTES as usize + 6] = true
                 }
+                CrdsValueLabel::DuplicateShred(ix, _) => {
+                    hits[*ix as usize + MAX_VOTES as usize + MAX_EPOCH_SLOTS as usize + 6] = true
+                }
             }
         }
         assert!(hits.iter().all(|x| *x));
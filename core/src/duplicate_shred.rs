@@ -0,0 +1,426 @@
+use solana_ledger::{
+    blockstore_meta::DuplicateSlotProof,
+    shred::{Shred, ShredError, ShredType, SHRED_PAYLOAD_SIZE},
+};
+use solana_sdk::{
+    clock::Slot,
+    pubkey::Pubkey,
+    sanitize::{Sanitize, SanitizeError},
+};
+use std::{collections::HashMap, convert::TryFrom};
+use thiserror::Error;
+
+const DUPLICATE_SHRED_HEADER_SIZE: usize = 63;
+
+pub type DuplicateShredIndex = u16;
+pub const MAX_DUPLICATE_SHREDS: DuplicateShredIndex = 512;
+
+/// Function returning leader at a given slot.
+pub trait LeaderScheduleFn: FnOnce(Slot) -> Option<Pubkey> {}
+impl<F> LeaderScheduleFn for F where F: FnOnce(Slot) -> Option<Pubkey> {}
+
+/// A chunk of a serialized DuplicateSlotProof, i.e. two conflicting shreds
+/// for the same slot and index, each signed by the slot leader. The proof is
+/// split into chunks so that each one fits into a single gossip packet.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, AbiExample)]
+pub struct DuplicateShred {
+    pub from: Pubkey,
+    pub wallclock: u64,
+    pub slot: Slot,
+    shred_index: u32,
+    shred_type: u8,
+    // Serialized DuplicateSlotProof split into chunks.
+    num_chunks: u8,
+    chunk_index: u8,
+    chunk: Vec<u8>,
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("data chunk mismatch")]
+    DataChunkMismatch,
+    #[error("invalid chunk index")]
+    InvalidChunkIndex,
+    #[error("invalid duplicate shreds")]
+    InvalidDuplicateShreds,
+    #[error("invalid shred payload")]
+    InvalidShredPayload,
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("invalid size limit")]
+    InvalidSizeLimit,
+    #[error("missing data chunk")]
+    MissingDataChunk,
+    #[error("(de)serialization error: {0}")]
+    SerializationError(#[from] bincode::Error),
+    #[error("shred error: {0}")]
+    ShredError(#[from] ShredError),
+    #[error("shred index mismatch")]
+    ShredIndexMismatch,
+    #[error("shred type mismatch")]
+    ShredTypeMismatch,
+    #[error("slot mismatch")]
+    SlotMismatch,
+    #[error("type conversion error")]
+    TryFromIntError(#[from] std::num::TryFromIntError),
+    #[error("unknown slot leader")]
+    UnknownSlotLeader,
+}
+
+impl Sanitize for DuplicateShred {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        if self.wallclock >= crate::crds_value::MAX_WALLCLOCK {
+            return Err(SanitizeError::ValueOutOfBounds);
+        }
+        if self.slot >= crate::crds_value::MAX_SLOT {
+            return Err(SanitizeError::ValueOutOfBounds);
+        }
+        if self.chunk_index >= self.num_chunks {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        self.from.sanitize()
+    }
+}
+
+impl DuplicateShred {
+    pub fn num_chunks(&self) -> u8 {
+        self.num_chunks
+    }
+
+    pub fn chunk_index(&self) -> u8 {
+        self.chunk_index
+    }
+
+    pub fn shred_index(&self) -> u32 {
+        self.shred_index
+    }
+}
+
+// Parses a shred payload received from an untrusted source.
+fn deserialize_shred(payload: Vec<u8>) -> Result<Shred, Error> {
+    // Shred::new_from_serialized_shred asserts on the payload size.
+    if payload.len() < SHRED_PAYLOAD_SIZE {
+        return Err(Error::InvalidShredPayload);
+    }
+    Ok(Shred::new_from_serialized_shred(payload)?)
+}
+
+// Asserts that the two shreds can indicate duplicate proof for
+// the same triplet of (slot, shred-index, and shred-type), and
+// that they have valid signatures from the slot leader.
+fn check_shreds(
+    leader: impl LeaderScheduleFn,
+    shred1: &Shred,
+    shred2: &Shred,
+) -> Result<(), Error> {
+    if shred1.slot() != shred2.slot() {
+        Err(Error::SlotMismatch)
+    } else if shred1.index() != shred2.index() {
+        Err(Error::ShredIndexMismatch)
+    } else if shred1.common_header.shred_type != shred2.common_header.shred_type {
+        Err(Error::ShredTypeMismatch)
+    } else if shred1.payload == shred2.payload {
+        Err(Error::InvalidDuplicateShreds)
+    } else {
+        let slot_leader = leader(shred1.slot()).ok_or(Error::UnknownSlotLeader)?;
+        if !shred1.verify(&slot_leader) || !shred2.verify(&slot_leader) {
+            Err(Error::InvalidSignature)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Splits the proof that `shred` and `other_payload` conflict into
+/// DuplicateShred chunks, each of which serializes to at most `max_size`
+/// bytes. The proof is checked against the slot leader before being chunked
+/// so that invalid proofs are never propagated.
+pub fn from_shred(
+    shred: Shred,
+    self_pubkey: Pubkey, // Pubkey of my node broadcasting crds value.
+    other_payload: Vec<u8>,
+    leader: impl LeaderScheduleFn,
+    wallclock: u64,
+    max_size: usize, // Maximum serialized size of each DuplicateShred.
+) -> Result<impl Iterator<Item = DuplicateShred>, Error> {
+    let other_shred = deserialize_shred(other_payload)?;
+    check_shreds(leader, &shred, &other_shred)?;
+    let (slot, shred_index, shred_type) =
+        (shred.slot(), shred.index(), shred.common_header.shred_type);
+    let proof = DuplicateSlotProof {
+        shred1: shred.payload,
+        shred2: other_shred.payload,
+    };
+    let data = bincode::serialize(&proof)?;
+    let chunk_size = if DUPLICATE_SHRED_HEADER_SIZE < max_size {
+        max_size - DUPLICATE_SHRED_HEADER_SIZE
+    } else {
+        return Err(Error::InvalidSizeLimit);
+    };
+    let chunks: Vec<_> = data.chunks(chunk_size).map(Vec::from).collect();
+    let num_chunks = u8::try_from(chunks.len())?;
+    let chunks = chunks
+        .into_iter()
+        .enumerate()
+        .map(move |(i, chunk)| DuplicateShred {
+            from: self_pubkey,
+            wallclock,
+            slot,
+            shred_index,
+            shred_type: shred_type.0,
+            num_chunks,
+            chunk_index: i as u8,
+            chunk,
+        });
+    Ok(chunks)
+}
+
+// Returns a predicate checking if a duplicate-shred chunk matches
+// (slot, shred_index, shred_type) and has valid chunk_index.
+fn check_chunk(
+    slot: Slot,
+    shred_index: u32,
+    shred_type: u8,
+    num_chunks: u8,
+) -> impl Fn(&DuplicateShred) -> Result<(), Error> {
+    move |dup| {
+        if dup.slot != slot {
+            Err(Error::SlotMismatch)
+        } else if dup.shred_index != shred_index {
+            Err(Error::ShredIndexMismatch)
+        } else if dup.shred_type != shred_type {
+            Err(Error::ShredTypeMismatch)
+        } else if dup.num_chunks != num_chunks {
+            Err(Error::DataChunkMismatch)
+        } else if dup.chunk_index >= num_chunks {
+            Err(Error::InvalidChunkIndex)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reconstructs the duplicate shreds from chunks of DuplicateShred, and
+/// verifies that they are a valid proof signed by the slot leader.
+pub fn into_shreds(
+    chunks: impl IntoIterator<Item = DuplicateShred>,
+    leader: impl LeaderScheduleFn,
+) -> Result<(Shred, Shred), Error> {
+    let mut chunks = chunks.into_iter().peekable();
+    let (slot, shred_index, shred_type, num_chunks) = match chunks.peek() {
+        None => return Err(Error::InvalidDuplicateShreds),
+        Some(chunk) => (
+            chunk.slot,
+            chunk.shred_index,
+            chunk.shred_type,
+            chunk.num_chunks,
+        ),
+    };
+    let slot_leader = leader(slot).ok_or(Error::UnknownSlotLeader)?;
+    let check_chunk = check_chunk(slot, shred_index, shred_type, num_chunks);
+    let mut data = HashMap::new();
+    for chunk in chunks {
+        check_chunk(&chunk)?;
+        match data.get(&chunk.chunk_index) {
+            None => {
+                data.insert(chunk.chunk_index, chunk.chunk);
+            }
+            Some(data) => {
+                if data != &chunk.chunk {
+                    return Err(Error::DataChunkMismatch);
+                }
+            }
+        }
+    }
+    if data.len() != num_chunks as usize {
+        return Err(Error::MissingDataChunk);
+    }
+    let data: Vec<_> = (0..num_chunks).map(|k| data.remove(&k).unwrap()).collect();
+    let proof: DuplicateSlotProof = bincode::deserialize(&data.concat())?;
+    let shred1 = deserialize_shred(proof.shred1)?;
+    let shred2 = deserialize_shred(proof.shred2)?;
+    if shred1.slot() != slot || shred2.slot() != slot {
+        Err(Error::SlotMismatch)
+    } else if shred1.index() != shred_index || shred2.index() != shred_index {
+        Err(Error::ShredIndexMismatch)
+    } else if shred1.common_header.shred_type != ShredType(shred_type)
+        || shred2.common_header.shred_type != ShredType(shred_type)
+    {
+        Err(Error::ShredTypeMismatch)
+    } else if shred1.payload == shred2.payload {
+        Err(Error::InvalidDuplicateShreds)
+    } else if !shred1.verify(&slot_leader) || !shred2.verify(&slot_leader) {
+        Err(Error::InvalidSignature)
+    } else {
+        Ok((shred1, shred2))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use bincode::serialized_size;
+    use rand::Rng;
+    use solana_ledger::shred::Shredder;
+    use solana_sdk::{
+        signature::{Keypair, Signer},
+        timing::timestamp,
+    };
+
+    #[test]
+    fn test_duplicate_shred_header_size() {
+        let dup = DuplicateShred {
+            from: Pubkey::new_unique(),
+            wallclock: std::u64::MAX,
+            slot: std::u64::MAX,
+            shred_index: std::u32::MAX,
+            shred_type: ShredType::default().0,
+            num_chunks: std::u8::MAX,
+            chunk_index: std::u8::MAX,
+            chunk: Vec::default(),
+        };
+        assert_eq!(
+            serialized_size(&dup).unwrap() as usize,
+            DUPLICATE_SHRED_HEADER_SIZE
+        );
+    }
+
+    pub(crate) fn new_rand_shred<R: Rng>(
+        rng: &mut R,
+        slot: Slot,
+        index: u32,
+        keypair: &Keypair,
+    ) -> Shred {
+        let data: Vec<u8> = (0..64).map(|_| rng.gen()).collect();
+        let mut shred = Shred::new_from_data(
+            slot,
+            index,
+            1, // parent_offset
+            Some(&data[..]),
+            false, // is_last_in_fec_set
+            false, // is_last_in_slot
+            0,     // reference_tick
+            0,     // version
+            index, // fec_set_index
+        );
+        Shredder::sign_shred(keypair, &mut shred);
+        shred
+    }
+
+    #[test]
+    fn test_duplicate_shred_round_trip() {
+        let mut rng = rand::thread_rng();
+        let leader = Keypair::new();
+        let (slot, index) = (53084024, 5);
+        let shred1 = new_rand_shred(&mut rng, slot, index, &leader);
+        let shred2 = new_rand_shred(&mut rng, slot, index, &leader);
+        let leader_schedule = |s| {
+            if s == slot {
+                Some(leader.pubkey())
+            } else {
+                None
+            }
+        };
+        let chunks: Vec<_> = from_shred(
+            shred1.clone(),
+            Pubkey::new_unique(), // self_pubkey
+            shred2.payload.clone(),
+            leader_schedule,
+            timestamp(),
+            512, // max_size
+        )
+        .unwrap()
+        .collect();
+        assert!(chunks.len() > 4);
+        for chunk in &chunks {
+            assert!(serialized_size(chunk).unwrap() <= 512);
+            assert!(chunk.sanitize().is_ok());
+        }
+        let (shred3, shred4) = into_shreds(chunks.clone(), leader_schedule).unwrap();
+        assert_eq!(shred1, shred3);
+        assert_eq!(shred2, shred4);
+
+        // Chunks are reassembled regardless of the order they are received.
+        let (shred3, shred4) = into_shreds(chunks.iter().cloned().rev(), leader_schedule).unwrap();
+        assert_eq!(shred1, shred3);
+        assert_eq!(shred2, shred4);
+
+        // Missing chunks fail the reassembly.
+        assert!(matches!(
+            into_shreds(chunks.iter().skip(1).cloned(), leader_schedule),
+            Err(Error::MissingDataChunk)
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_shred_invalid_proofs() {
+        let mut rng = rand::thread_rng();
+        let leader = Keypair::new();
+        let (slot, index) = (53084024, 5);
+        let shred1 = new_rand_shred(&mut rng, slot, index, &leader);
+        let leader_schedule = |_| Some(leader.pubkey());
+
+        // Identical shreds are not a proof.
+        assert!(matches!(
+            from_shred(
+                shred1.clone(),
+                Pubkey::new_unique(),
+                shred1.payload.clone(),
+                leader_schedule,
+                timestamp(),
+                512,
+            ),
+            Err(Error::InvalidDuplicateShreds)
+        ));
+
+        // Shreds with different indices are not a proof.
+        let shred2 = new_rand_shred(&mut rng, slot, index + 1, &leader);
+        assert!(matches!(
+            from_shred(
+                shred1.clone(),
+                Pubkey::new_unique(),
+                shred2.payload,
+                leader_schedule,
+                timestamp(),
+                512,
+            ),
+            Err(Error::ShredIndexMismatch)
+        ));
+
+        // Shreds not signed by the slot leader are not a proof.
+        let shred2 = new_rand_shred(&mut rng, slot, index, &Keypair::new());
+        assert!(matches!(
+            from_shred(
+                shred1.clone(),
+                Pubkey::new_unique(),
+                shred2.payload.clone(),
+                leader_schedule,
+                timestamp(),
+                512,
+            ),
+            Err(Error::InvalidSignature)
+        ));
+
+        // A proof verified against one leader is rejected by another.
+        let shred2 = new_rand_shred(&mut rng, slot, index, &leader);
+        let chunks: Vec<_> = from_shred(
+            shred1,
+            Pubkey::new_unique(),
+            shred2.payload,
+            leader_schedule,
+            timestamp(),
+            512,
+        )
+        .unwrap()
+        .collect();
+        let other_leader = Keypair::new();
+        assert!(matches!(
+            into_shreds(chunks.clone(), |_| Some(other_leader.pubkey())),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(matches!(
+            into_shreds(chunks, |_| None),
+            Err(Error::UnknownSlotLeader)
+        ));
+    }
+}
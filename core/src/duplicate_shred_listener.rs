@@ -0,0 +1,345 @@
+//! The `duplicate_shred_listener` module reassembles duplicate shred proofs
+//! gossiped by other nodes, verifies them against the slot leader and notifies
+//! `ReplayStage` of slots for which the leader produced conflicting blocks.
+
+use crate::{
+    cluster_info::ClusterInfo,
+    duplicate_shred::{self, DuplicateShred},
+    result::Result,
+};
+use crossbeam_channel::{Receiver as CrossbeamReceiver, Sender as CrossbeamSender};
+use solana_ledger::{blockstore::Blockstore, leader_schedule_cache::LeaderScheduleCache};
+use solana_runtime::bank_forks::BankForks;
+use solana_sdk::{
+    clock::{Slot, DEFAULT_SLOTS_PER_EPOCH},
+    pubkey::Pubkey,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread::{self, sleep, Builder, JoinHandle},
+    time::Duration,
+};
+
+pub type GossipDuplicateSlotsSender = CrossbeamSender<Slot>;
+pub type GossipDuplicateSlotsReceiver = CrossbeamReceiver<Slot>;
+
+const LISTENER_SLEEP_MS: u64 = 200;
+// Chunks for slots further than this beyond the root are ignored, as the
+// leader schedule is unlikely to be known for them yet.
+const MAX_SLOT_DISTANCE_FROM_ROOT: Slot = DEFAULT_SLOTS_PER_EPOCH;
+// Incomplete proofs buffered for each node. Once exceeded, the proof with the
+// oldest wallclock is evicted, so a node can't grow the buffer without bound.
+const MAX_PENDING_PROOFS_PER_NODE: usize = 8;
+
+// Chunks of a single proof all share the pubkey of the node which pushed
+// them, the slot, and the wallclock at which they were generated.
+type ProofKey = (Pubkey, Slot, u64);
+
+pub struct DuplicateShredListener {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl DuplicateShredListener {
+    pub fn new(
+        exit: &Arc<AtomicBool>,
+        cluster_info: Arc<ClusterInfo>,
+        blockstore: Arc<Blockstore>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        leader_schedule_cache: Arc<LeaderScheduleCache>,
+        gossip_duplicate_slots_sender: GossipDuplicateSlotsSender,
+    ) -> Self {
+        let exit = exit.clone();
+        let thread_hdl = Builder::new()
+            .name("solana-duplicate-shred-listener".to_string())
+            .spawn(move || {
+                let mut handler = DuplicateShredHandler::new(
+                    blockstore,
+                    bank_forks,
+                    leader_schedule_cache,
+                    gossip_duplicate_slots_sender,
+                );
+                let mut last_ts = 0;
+                loop {
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let (chunks, new_ts) = cluster_info.get_duplicate_shreds(last_ts);
+                    last_ts = new_ts;
+                    if let Err(e) = handler.handle(chunks) {
+                        warn!("duplicate shred listener error: {:?}", e);
+                    }
+                    sleep(Duration::from_millis(LISTENER_SLEEP_MS));
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+struct DuplicateShredHandler {
+    blockstore: Arc<Blockstore>,
+    bank_forks: Arc<RwLock<BankForks>>,
+    leader_schedule_cache: Arc<LeaderScheduleCache>,
+    gossip_duplicate_slots_sender: GossipDuplicateSlotsSender,
+    // Chunks received so far for proofs which are not complete yet, keyed by
+    // the node which pushed them and then by slot and wallclock.
+    buffer: HashMap<Pubkey, HashMap<(Slot, u64), HashMap<u8, DuplicateShred>>>,
+    // Slots for which a verified proof has already been sent to ReplayStage.
+    notified_slots: BTreeSet<Slot>,
+}
+
+impl DuplicateShredHandler {
+    fn new(
+        blockstore: Arc<Blockstore>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        leader_schedule_cache: Arc<LeaderScheduleCache>,
+        gossip_duplicate_slots_sender: GossipDuplicateSlotsSender,
+    ) -> Self {
+        Self {
+            blockstore,
+            bank_forks,
+            leader_schedule_cache,
+            gossip_duplicate_slots_sender,
+            buffer: HashMap::new(),
+            notified_slots: BTreeSet::new(),
+        }
+    }
+
+    fn handle(&mut self, chunks: Vec<DuplicateShred>) -> Result<()> {
+        let root_bank = self.bank_forks.read().unwrap().root_bank().clone();
+        let root = root_bank.slot();
+        self.buffer.retain(|_, proofs| {
+            proofs.retain(|(slot, _), _| *slot > root);
+            !proofs.is_empty()
+        });
+        self.notified_slots = self.notified_slots.split_off(&(root + 1));
+
+        let mut completed: Vec<ProofKey> = vec![];
+        for chunk in chunks {
+            if chunk.slot <= root
+                || chunk.slot > root + MAX_SLOT_DISTANCE_FROM_ROOT
+                || self.notified_slots.contains(&chunk.slot)
+                || chunk.chunk_index() >= chunk.num_chunks()
+            {
+                continue;
+            }
+            let (from, slot, wallclock) = (chunk.from, chunk.slot, chunk.wallclock);
+            let proofs = self.buffer.entry(from).or_default();
+            if !proofs.contains_key(&(slot, wallclock))
+                && proofs.len() >= MAX_PENDING_PROOFS_PER_NODE
+            {
+                let oldest = *proofs
+                    .keys()
+                    .min_by_key(|(_, wallclock)| *wallclock)
+                    .unwrap();
+                if oldest.1 > wallclock {
+                    continue;
+                }
+                proofs.remove(&oldest);
+                inc_new_counter_info!("duplicate_shred_listener-evicted_proofs", 1);
+            }
+            let num_chunks = chunk.num_chunks() as usize;
+            let proof = proofs.entry((slot, wallclock)).or_default();
+            proof.insert(chunk.chunk_index(), chunk);
+            if proof.len() == num_chunks {
+                completed.push((from, slot, wallclock));
+            }
+        }
+
+        for (from, slot, wallclock) in completed {
+            let chunks = match self
+                .buffer
+                .get_mut(&from)
+                .and_then(|proofs| proofs.remove(&(slot, wallclock)))
+            {
+                Some(chunks) => chunks,
+                None => continue,
+            };
+            if self.notified_slots.contains(&slot) {
+                continue;
+            }
+            let leader_schedule_cache = &self.leader_schedule_cache;
+            let leader = |slot| leader_schedule_cache.slot_leader_at(slot, Some(&*root_bank));
+            match duplicate_shred::into_shreds(chunks.into_iter().map(|(_, chunk)| chunk), leader) {
+                Ok((shred1, shred2)) => {
+                    warn!(
+                        "received duplicate shred proof for slot {} from {}",
+                        slot, from
+                    );
+                    inc_new_counter_info!("duplicate_shred_listener-verified_proofs", 1);
+                    if !self.blockstore.has_duplicate_shreds_in_slot(slot) {
+                        self.blockstore.store_duplicate_slot(
+                            slot,
+                            shred1.payload,
+                            shred2.payload,
+                        )?;
+                    }
+                    self.notified_slots.insert(slot);
+                    self.gossip_duplicate_slots_sender.send(slot)?;
+                }
+                Err(err) => {
+                    inc_new_counter_info!("duplicate_shred_listener-invalid_proofs", 1);
+                    debug!(
+                        "invalid duplicate shred proof for slot {} from {}: {:?}",
+                        slot, from, err
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cluster_info::Node, duplicate_shred::tests::new_rand_shred};
+    use crossbeam_channel::unbounded;
+    use solana_ledger::{
+        genesis_utils::{create_genesis_config_with_leader, GenesisConfigInfo},
+        get_tmp_ledger_path,
+    };
+    use solana_runtime::bank::Bank;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_handle_duplicate_shreds() {
+        let mut rng = rand::thread_rng();
+        let leader_keypair = Arc::new(Keypair::new());
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &leader_keypair.pubkey(), 100);
+        let bank = Bank::new(&genesis_config);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(&bank));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        let (sender, receiver) = unbounded();
+        let mut handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            bank_forks,
+            leader_schedule_cache.clone(),
+            sender,
+        );
+
+        let node = Node::new_localhost_with_pubkey(&Keypair::new().pubkey());
+        let cluster_info = ClusterInfo::new_with_invalid_keypair(node.info);
+        let (slot, index) = (5, 3);
+        let leader = |slot| leader_schedule_cache.slot_leader_at(slot, None);
+        assert_eq!(leader(slot), Some(leader_keypair.pubkey()));
+        let shred1 = new_rand_shred(&mut rng, slot, index, &leader_keypair);
+        let shred2 = new_rand_shred(&mut rng, slot, index, &leader_keypair);
+        cluster_info
+            .push_duplicate_shred(&shred1, &shred2.payload, leader)
+            .unwrap();
+        cluster_info.flush_push_queue();
+        let (chunks, _) = cluster_info.get_duplicate_shreds(0);
+        assert!(chunks.len() > 1);
+
+        // Incomplete proofs are buffered until all chunks arrive.
+        let (head, tail) = chunks.split_at(1);
+        handler.handle(head.to_vec()).unwrap();
+        assert!(receiver.try_recv().is_err());
+        assert!(!blockstore.has_duplicate_shreds_in_slot(slot));
+        handler.handle(tail.to_vec()).unwrap();
+        assert_eq!(receiver.try_recv(), Ok(slot));
+        let proof = blockstore.get_duplicate_slot(slot).unwrap();
+        assert_eq!(proof.shred1, shred1.payload);
+        assert_eq!(proof.shred2, shred2.payload);
+
+        // The same slot is only reported once.
+        handler.handle(chunks).unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        // Proofs not signed by the slot leader are dropped.
+        let other_slot = slot + 1;
+        let keypair = Keypair::new();
+        let shred1 = new_rand_shred(&mut rng, other_slot, index, &keypair);
+        let shred2 = new_rand_shred(&mut rng, other_slot, index, &keypair);
+        let chunks: Vec<_> = duplicate_shred::from_shred(
+            shred1,
+            keypair.pubkey(),
+            shred2.payload,
+            |_| Some(keypair.pubkey()),
+            0, // wallclock
+            512,
+        )
+        .unwrap()
+        .collect();
+        handler.handle(chunks).unwrap();
+        assert!(receiver.try_recv().is_err());
+        assert!(!blockstore.has_duplicate_shreds_in_slot(other_slot));
+
+        drop(handler);
+        drop(blockstore);
+        Blockstore::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+
+    #[test]
+    fn test_max_pending_proofs_per_node() {
+        let mut rng = rand::thread_rng();
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &Pubkey::new_unique(), 100);
+        let bank = Bank::new(&genesis_config);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(&bank));
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
+        let (sender, receiver) = unbounded();
+        let mut handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            bank_forks,
+            leader_schedule_cache,
+            sender,
+        );
+
+        // Only the first chunk of each proof arrives, so none of them complete.
+        let keypair = Keypair::new();
+        let mut first_chunk = |wallclock| {
+            let shred1 = new_rand_shred(&mut rng, 5, 3, &keypair);
+            let shred2 = new_rand_shred(&mut rng, 5, 3, &keypair);
+            duplicate_shred::from_shred(
+                shred1,
+                keypair.pubkey(),
+                shred2.payload,
+                |_| Some(keypair.pubkey()),
+                wallclock,
+                512,
+            )
+            .unwrap()
+            .next()
+            .unwrap()
+        };
+        let num_proofs = MAX_PENDING_PROOFS_PER_NODE as u64 + 2;
+        // Received out of order, the oldest proofs are evicted regardless.
+        let mut chunks: Vec<_> = (0..num_proofs).map(&mut first_chunk).collect();
+        chunks.swap(0, num_proofs as usize - 1);
+        handler.handle(chunks).unwrap();
+        let mut wallclocks: Vec<_> = handler.buffer[&keypair.pubkey()]
+            .keys()
+            .map(|(_, wallclock)| *wallclock)
+            .collect();
+        wallclocks.sort_unstable();
+        assert_eq!(wallclocks, (2..num_proofs).collect::<Vec<_>>());
+
+        // A proof older than all of the pending ones is dropped.
+        handler.handle(vec![first_chunk(1)]).unwrap();
+        assert_eq!(
+            handler.buffer[&keypair.pubkey()].len(),
+            MAX_PENDING_PROOFS_PER_NODE
+        );
+        assert!(!handler.buffer[&keypair.pubkey()].contains_key(&(5, 1)));
+        assert!(receiver.try_recv().is_err());
+
+        drop(handler);
+        drop(blockstore);
+        Blockstore::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+}
@@ -56,6 +56,10 @@ struct ForkInfo {
     best_slot: Slot,
     parent: Option<Slot>,
     children: Vec<Slot>,
+    // False if a proof that the leader produced a duplicate version of
+    // this slot was received, in which case the subtree rooted at this
+    // slot is not considered when picking the best slot of its ancestors
+    is_candidate: bool,
 }
 
 pub struct HeaviestSubtreeForkChoice {
@@ -205,6 +209,7 @@ impl HeaviestSubtreeForkChoice {
             best_slot: root_info.best_slot,
             children: vec![self.root],
             parent: None,
+            is_candidate: true,
         };
         self.fork_infos.insert(root_parent, root_parent_info);
         self.root = root_parent;
@@ -226,6 +231,7 @@ impl HeaviestSubtreeForkChoice {
                 best_slot: slot,
                 children: vec![],
                 parent,
+                is_candidate: true,
             });
 
         if parent.is_none() {
@@ -256,6 +262,9 @@ impl HeaviestSubtreeForkChoice {
             return true;
         }
         for child in self.children(parent.unwrap()).unwrap() {
+            if !self.is_candidate_slot(*child).unwrap() {
+                continue;
+            }
             let child_weight = self
                 .stake_voted_subtree(*child)
                 .expect("child must exist in `self.fork_infos`");
@@ -268,6 +277,39 @@ impl HeaviestSubtreeForkChoice {
 
         true
     }
+
+    pub fn is_candidate_slot(&self, slot: Slot) -> Option<bool> {
+        self.fork_infos
+            .get(&slot)
+            .map(|fork_info| fork_info.is_candidate)
+    }
+
+    /// Excludes the subtree rooted at `slot` from the best slot of all of
+    /// its ancestors, e.g. because the leader of `slot` was shown to have
+    /// produced a duplicate block.
+    pub fn mark_fork_invalid_candidate(&mut self, slot: Slot) {
+        self.set_candidate(slot, false);
+    }
+
+    /// Undoes `mark_fork_invalid_candidate`, e.g. once the cluster has
+    /// confirmed one version of a duplicate slot.
+    pub fn mark_fork_valid_candidate(&mut self, slot: Slot) {
+        self.set_candidate(slot, true);
+    }
+
+    fn set_candidate(&mut self, slot: Slot, is_candidate: bool) {
+        match self.fork_infos.get_mut(&slot) {
+            Some(fork_info) if fork_info.is_candidate != is_candidate => {
+                fork_info.is_candidate = is_candidate;
+            }
+            _ => return,
+        }
+        let ancestors: Vec<_> = self.ancestor_iterator(slot).collect();
+        for ancestor in ancestors {
+            self.aggregate_slot(ancestor);
+        }
+    }
+
     pub fn all_slots_stake_voted_subtree(&self) -> Vec<(Slot, u64)> {
         self.fork_infos
             .iter()
@@ -376,6 +418,9 @@ impl HeaviestSubtreeForkChoice {
             for &child in &fork_info.children {
                 let child_stake_voted_subtree = self.stake_voted_subtree(child).unwrap();
                 stake_voted_subtree += child_stake_voted_subtree;
+                if !self.is_candidate_slot(child).unwrap() {
+                    continue;
+                }
                 if best_child_slot == slot ||
                 child_stake_voted_subtree > best_child_stake_voted_subtree ||
             // tiebreaker by slot height, prioritize earlier slot
@@ -728,6 +773,50 @@ mod test {
         assert_eq!(parents, vec![2]);
     }
 
+    #[test]
+    fn test_mark_fork_invalid_candidate() {
+        let mut heaviest_subtree_fork_choice = setup_forks();
+        let stake = 100;
+        let (bank, vote_pubkeys) = bank_utils::setup_bank_and_vote_pubkeys(1, stake);
+        heaviest_subtree_fork_choice.add_votes(
+            &[(vote_pubkeys[0], 6)],
+            bank.epoch_stakes_map(),
+            bank.epoch_schedule(),
+        );
+        assert_eq!(heaviest_subtree_fork_choice.best_overall_slot(), 6);
+
+        // Slot 3 is duplicate, so its subtree is no longer a candidate
+        // even though it has the most stake
+        heaviest_subtree_fork_choice.mark_fork_invalid_candidate(3);
+        assert_eq!(
+            heaviest_subtree_fork_choice.is_candidate_slot(3),
+            Some(false)
+        );
+        assert_eq!(heaviest_subtree_fork_choice.best_overall_slot(), 4);
+        assert_eq!(heaviest_subtree_fork_choice.best_slot(3).unwrap(), 6);
+        assert_eq!(
+            heaviest_subtree_fork_choice.stake_voted_subtree(1).unwrap(),
+            stake
+        );
+
+        // New leaves under the invalid fork are not propagated past it
+        heaviest_subtree_fork_choice.add_new_leaf_slot(7, Some(6));
+        assert_eq!(heaviest_subtree_fork_choice.best_slot(3).unwrap(), 7);
+        assert_eq!(heaviest_subtree_fork_choice.best_overall_slot(), 4);
+
+        // New leaves on the valid fork are propagated despite the heavier
+        // invalid sibling
+        heaviest_subtree_fork_choice.add_new_leaf_slot(8, Some(4));
+        assert_eq!(heaviest_subtree_fork_choice.best_overall_slot(), 8);
+
+        heaviest_subtree_fork_choice.mark_fork_valid_candidate(3);
+        assert_eq!(
+            heaviest_subtree_fork_choice.is_candidate_slot(3),
+            Some(true)
+        );
+        assert_eq!(heaviest_subtree_fork_choice.best_overall_slot(), 7);
+    }
+
     #[test]
     fn test_new_from_frozen_banks() {
         /*
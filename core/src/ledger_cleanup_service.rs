@@ -1,16 +1,18 @@
 //! The `ledger_cleanup_service` drops older ledger data to limit disk space usage
 
-use solana_ledger::blockstore::{Blockstore, PurgeType};
+use solana_ledger::blockstore::{Blockstore, ColumnGroup};
 use solana_ledger::blockstore_db::Result as BlockstoreResult;
 use solana_measure::measure::Measure;
-use solana_sdk::clock::{Slot, DEFAULT_TICKS_PER_SLOT, TICKS_PER_DAY};
+use solana_sdk::clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SLOT, TICKS_PER_DAY};
+use std::cmp::{max, min};
+use std::collections::HashMap;
 use std::string::ToString;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, RecvTimeoutError};
 use std::sync::Arc;
 use std::thread;
 use std::thread::{Builder, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // - To try and keep the RocksDB size under 400GB:
 //   Seeing about 1600b/shred, using 2000b/shred for margin, so 200m shreds can be stored in 400gb.
@@ -33,6 +35,66 @@ pub const DEFAULT_PURGE_SLOT_INTERVAL: u64 = 512;
 // Once a day should be ample
 const DEFAULT_COMPACTION_SLOT_INTERVAL: u64 = TICKS_PER_DAY / DEFAULT_TICKS_PER_SLOT;
 
+/// Limits on how much of a column group is retained. Slots are purged once either limit is
+/// exceeded; a policy without limits retains the column group indefinitely
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// Approximate on-disk size of the column group, in bytes
+    pub max_bytes: Option<u64>,
+    /// Age of the oldest block to retain, according to its cached block time.  Block times
+    /// are only cached by validators that record the RPC transaction history
+    pub max_age: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    pub fn is_limited(&self) -> bool {
+        self.max_bytes.is_some() || self.max_age.is_some()
+    }
+}
+
+/// Retention of each column group, in addition to the shred count limit of
+/// `--limit-ledger-size`. Transaction statuses and rewards are purged along with shreds unless
+/// they are given a policy of their own
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LedgerRetentionConfig {
+    pub shreds: RetentionPolicy,
+    pub transaction_status: Option<RetentionPolicy>,
+    pub rewards: Option<RetentionPolicy>,
+}
+
+impl LedgerRetentionConfig {
+    pub fn is_limited(&self) -> bool {
+        self.shreds.is_limited()
+            || self
+                .transaction_status
+                .map(|policy| policy.is_limited())
+                .unwrap_or(false)
+            || self
+                .rewards
+                .map(|policy| policy.is_limited())
+                .unwrap_or(false)
+    }
+}
+
+/// Cleanup progress of a column group, carried across `cleanup_ledger_with_retention` calls
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColumnGroupProgress {
+    /// Highest slot purged so far
+    pub lowest_cleanup_slot: Slot,
+    /// Highest slot compacted so far
+    pub last_compaction_slot: Slot,
+}
+
+pub type LedgerCleanupProgress = HashMap<ColumnGroup, ColumnGroupProgress>;
+
+// A purge of `column_group` over [from_slot, to_slot], compacting from `compact_from_slot` if set
+struct ColumnGroupPurge {
+    column_group: ColumnGroup,
+    from_slot: Slot,
+    to_slot: Slot,
+    compact_from_slot: Option<Slot>,
+}
+
 pub struct LedgerCleanupService {
     t_cleanup: JoinHandle<()>,
 }
@@ -43,14 +105,31 @@ impl LedgerCleanupService {
         blockstore: Arc<Blockstore>,
         max_ledger_shreds: u64,
         exit: &Arc<AtomicBool>,
+    ) -> Self {
+        Self::new_with_retention(
+            new_root_receiver,
+            blockstore,
+            Some(max_ledger_shreds),
+            LedgerRetentionConfig::default(),
+            exit,
+        )
+    }
+
+    pub fn new_with_retention(
+        new_root_receiver: Receiver<Slot>,
+        blockstore: Arc<Blockstore>,
+        max_ledger_shreds: Option<u64>,
+        retention_config: LedgerRetentionConfig,
+        exit: &Arc<AtomicBool>,
     ) -> Self {
         info!(
-            "LedgerCleanupService active. Max Ledger Slots {}",
-            max_ledger_shreds
+            "LedgerCleanupService active. Max Ledger Slots {:?}, retention {:?}",
+            max_ledger_shreds, retention_config
         );
         let exit = exit.clone();
+        let max_ledger_shreds = max_ledger_shreds.unwrap_or(std::u64::MAX);
         let mut last_purge_slot = 0;
-        let mut last_compaction_slot = 0;
+        let mut progress = LedgerCleanupProgress::default();
 
         let t_cleanup = Builder::new()
             .name("solana-ledger-cleanup".to_string())
@@ -58,13 +137,14 @@ impl LedgerCleanupService {
                 if exit.load(Ordering::Relaxed) {
                     break;
                 }
-                if let Err(e) = Self::cleanup_ledger(
+                if let Err(e) = Self::cleanup_ledger_with_retention(
                     &new_root_receiver,
                     &blockstore,
                     max_ledger_shreds,
+                    &retention_config,
                     &mut last_purge_slot,
                     DEFAULT_PURGE_SLOT_INTERVAL,
-                    &mut last_compaction_slot,
+                    &mut progress,
                     DEFAULT_COMPACTION_SLOT_INTERVAL,
                 ) {
                     match e {
@@ -81,6 +161,7 @@ impl LedgerCleanupService {
         blockstore: &Arc<Blockstore>,
         root: Slot,
         max_ledger_shreds: u64,
+        max_shreds_bytes: Option<u64>,
     ) -> (bool, Slot, Slot, u64) {
         let mut total_slots = Vec::new();
         let mut iterate_time = Measure::start("iterate_time");
@@ -99,6 +180,23 @@ impl LedgerCleanupService {
             }
         }
         iterate_time.stop();
+
+        // Translate the byte budget into a shred count using the average size of a stored shred
+        let max_ledger_shreds = match max_shreds_bytes {
+            Some(max_shreds_bytes) if total_shreds > 0 => {
+                match blockstore.column_group_storage_size(ColumnGroup::Shreds) {
+                    Ok(shreds_bytes) => {
+                        let bytes_per_shred = max(shreds_bytes / total_shreds, 1);
+                        min(max_ledger_shreds, max_shreds_bytes / bytes_per_shred)
+                    }
+                    Err(err) => {
+                        warn!("Unable to get the size of the shred columns: {:?}", err);
+                        max_ledger_shreds
+                    }
+                }
+            }
+            _ => max_ledger_shreds,
+        };
         info!(
             "first_slot={} total_slots={} total_shreds={} max_ledger_shreds={}, {}",
             first_slot,
@@ -123,6 +221,57 @@ impl LedgerCleanupService {
         (true, first_slot, lowest_cleanup_slot, total_shreds)
     }
 
+    /// Returns the highest slot older than `max_age`, as recorded by the `Blocktime` column
+    fn find_slot_older_than(blockstore: &Blockstore, max_age: Duration) -> Option<Slot> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+        let cutoff = now.checked_sub(max_age)?.as_secs() as UnixTimestamp;
+        blockstore
+            .get_last_slot_before_block_time(cutoff)
+            .unwrap_or_else(|err| {
+                warn!("Unable to map ledger age to a slot: {:?}", err);
+                None
+            })
+    }
+
+    /// Returns the highest slot to purge from `column_group` to honor `policy`. Slots are assumed
+    /// to take up the same amount of space, which is good enough for the slow moving columns this
+    /// applies to
+    fn find_column_group_cleanup_slot(
+        blockstore: &Blockstore,
+        column_group: ColumnGroup,
+        policy: &RetentionPolicy,
+        first_slot: Slot,
+        root: Slot,
+    ) -> Option<Slot> {
+        let bytes_cleanup_slot = policy.max_bytes.and_then(|max_bytes| {
+            let size = blockstore
+                .column_group_storage_size(column_group)
+                .map_err(|err| {
+                    warn!(
+                        "Unable to get the size of the {} columns: {:?}",
+                        column_group.name(),
+                        err
+                    )
+                })
+                .ok()?;
+            if size <= max_bytes {
+                return None;
+            }
+            let num_slots = root.saturating_sub(first_slot) as u128;
+            let excess_bytes = (size - max_bytes) as u128;
+            Some(first_slot + (num_slots * excess_bytes / size as u128) as Slot)
+        });
+        let age_cleanup_slot = policy
+            .max_age
+            .and_then(|max_age| Self::find_slot_older_than(blockstore, max_age));
+
+        let cleanup_slot = match (bytes_cleanup_slot, age_cleanup_slot) {
+            (Some(bytes_slot), Some(age_slot)) => Some(max(bytes_slot, age_slot)),
+            (bytes_slot, age_slot) => bytes_slot.or(age_slot),
+        };
+        cleanup_slot.map(|cleanup_slot| min(cleanup_slot, root))
+    }
+
     fn receive_new_roots(new_root_receiver: &Receiver<Slot>) -> Result<Slot, RecvTimeoutError> {
         let mut root = new_root_receiver.recv_timeout(Duration::from_secs(1))?;
         // Get the newest root
@@ -140,6 +289,44 @@ impl LedgerCleanupService {
         purge_interval: u64,
         last_compaction_slot: &mut u64,
         compaction_interval: u64,
+    ) -> Result<(), RecvTimeoutError> {
+        // Without retention policies all column groups are purged and compacted together
+        let mut progress: LedgerCleanupProgress = ColumnGroup::ALL
+            .iter()
+            .map(|column_group| {
+                (
+                    *column_group,
+                    ColumnGroupProgress {
+                        lowest_cleanup_slot: 0,
+                        last_compaction_slot: *last_compaction_slot,
+                    },
+                )
+            })
+            .collect();
+        let result = Self::cleanup_ledger_with_retention(
+            new_root_receiver,
+            blockstore,
+            max_ledger_shreds,
+            &LedgerRetentionConfig::default(),
+            last_purge_slot,
+            purge_interval,
+            &mut progress,
+            compaction_interval,
+        );
+        *last_compaction_slot = progress[&ColumnGroup::Shreds].last_compaction_slot;
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn cleanup_ledger_with_retention(
+        new_root_receiver: &Receiver<Slot>,
+        blockstore: &Arc<Blockstore>,
+        max_ledger_shreds: u64,
+        retention_config: &LedgerRetentionConfig,
+        last_purge_slot: &mut u64,
+        purge_interval: u64,
+        progress: &mut LedgerCleanupProgress,
+        compaction_interval: u64,
     ) -> Result<(), RecvTimeoutError> {
         let root = Self::receive_new_roots(new_root_receiver)?;
         if root - *last_purge_slot <= purge_interval {
@@ -148,59 +335,167 @@ impl LedgerCleanupService {
 
         let disk_utilization_pre = blockstore.storage_size();
         info!(
-            "purge: last_root={}, last_purge_slot={}, purge_interval={}, last_compaction_slot={}, disk_utilization={:?}",
-            root, last_purge_slot, purge_interval, last_compaction_slot, disk_utilization_pre
+            "purge: last_root={}, last_purge_slot={}, purge_interval={}, progress={:?}, disk_utilization={:?}",
+            root, last_purge_slot, purge_interval, progress, disk_utilization_pre
         );
 
         *last_purge_slot = root;
 
-        let (slots_to_clean, purge_first_slot, lowest_cleanup_slot, total_shreds) =
-            Self::find_slots_to_clean(&blockstore, root, max_ledger_shreds);
+        let (slots_to_clean, purge_first_slot, shreds_cleanup_slot, total_shreds) =
+            Self::find_slots_to_clean(
+                &blockstore,
+                root,
+                max_ledger_shreds,
+                retention_config.shreds.max_bytes,
+            );
+        let shreds_cleanup_slot = match (
+            slots_to_clean,
+            retention_config
+                .shreds
+                .max_age
+                .and_then(|max_age| Self::find_slot_older_than(blockstore, max_age)),
+        ) {
+            (true, Some(age_slot)) => Some(max(shreds_cleanup_slot, min(age_slot, root))),
+            (true, None) => Some(shreds_cleanup_slot),
+            (false, age_slot) => age_slot.map(|age_slot| min(age_slot, root)),
+        };
+
+        // Roots are retained until every other column group is purged, so the first root is the
+        // lowest slot any column group may still hold
+        let first_root = blockstore
+            .rooted_slot_iterator(0)
+            .ok()
+            .and_then(|mut roots| roots.next())
+            .unwrap_or_default();
+        let mut cleanup_slots: HashMap<ColumnGroup, Option<Slot>> = HashMap::new();
+        cleanup_slots.insert(ColumnGroup::Shreds, shreds_cleanup_slot);
+        for (column_group, policy) in &[
+            (
+                ColumnGroup::TransactionStatus,
+                retention_config.transaction_status,
+            ),
+            (ColumnGroup::Rewards, retention_config.rewards),
+        ] {
+            let cleanup_slot = match policy {
+                None => shreds_cleanup_slot,
+                Some(policy) => {
+                    let group_progress = progress.entry(*column_group).or_default();
+                    Self::find_column_group_cleanup_slot(
+                        blockstore,
+                        *column_group,
+                        policy,
+                        max(first_root, group_progress.lowest_cleanup_slot),
+                        root,
+                    )
+                }
+            };
+            cleanup_slots.insert(*column_group, cleanup_slot);
+        }
+        // Block metadata can only go once no other column group refers to the slot anymore
+        let block_metadata_cleanup_slot = [
+            ColumnGroup::Shreds,
+            ColumnGroup::TransactionStatus,
+            ColumnGroup::Rewards,
+        ]
+        .iter()
+        .map(|column_group| {
+            let lowest_cleanup_slot = progress
+                .get(column_group)
+                .map(|group_progress| group_progress.lowest_cleanup_slot)
+                .unwrap_or_default();
+            cleanup_slots[column_group].map_or(lowest_cleanup_slot, |cleanup_slot| {
+                max(cleanup_slot, lowest_cleanup_slot)
+            })
+        })
+        .min()
+        .unwrap();
+        cleanup_slots.insert(
+            ColumnGroup::BlockMetadata,
+            Some(block_metadata_cleanup_slot),
+        );
 
-        if slots_to_clean {
-            let mut compact_first_slot = std::u64::MAX;
-            if lowest_cleanup_slot.saturating_sub(*last_compaction_slot) > compaction_interval {
-                compact_first_slot = *last_compaction_slot;
-                *last_compaction_slot = lowest_cleanup_slot;
+        let mut purges = vec![];
+        for column_group in ColumnGroup::ALL.iter() {
+            let group_progress = progress.entry(*column_group).or_default();
+            let cleanup_slot = match cleanup_slots[column_group] {
+                Some(cleanup_slot) if cleanup_slot > group_progress.lowest_cleanup_slot => {
+                    cleanup_slot
+                }
+                _ => continue,
+            };
+            let from_slot = if *column_group == ColumnGroup::Shreds {
+                purge_first_slot
+            } else {
+                group_progress.lowest_cleanup_slot
+            };
+            let mut compact_from_slot = None;
+            if cleanup_slot.saturating_sub(group_progress.last_compaction_slot)
+                > compaction_interval
+            {
+                compact_from_slot = Some(group_progress.last_compaction_slot);
+                group_progress.last_compaction_slot = cleanup_slot;
             }
+            group_progress.lowest_cleanup_slot = cleanup_slot;
+            purges.push(ColumnGroupPurge {
+                column_group: *column_group,
+                from_slot,
+                to_slot: cleanup_slot,
+                compact_from_slot,
+            });
+        }
 
+        if !purges.is_empty() {
             let purge_complete = Arc::new(AtomicBool::new(false));
             let blockstore = blockstore.clone();
             let purge_complete1 = purge_complete.clone();
             let _t_purge = Builder::new()
                 .name("solana-ledger-purge".to_string())
                 .spawn(move || {
+                    // Readers stop using the slots of each column group before they are purged
                     let mut slot_update_time = Measure::start("slot_update");
-                    *blockstore.lowest_cleanup_slot.write().unwrap() = lowest_cleanup_slot;
+                    for purge in &purges {
+                        blockstore.set_column_group_cleanup_slot(purge.column_group, purge.to_slot);
+                    }
                     slot_update_time.stop();
 
-                    info!(
-                        "purging data from slots {} to {}",
-                        purge_first_slot, lowest_cleanup_slot
-                    );
-
-                    let mut purge_time = Measure::start("purge_slots");
-                    blockstore.purge_slots(
-                        purge_first_slot,
-                        lowest_cleanup_slot,
-                        PurgeType::PrimaryIndex,
-                    );
-                    purge_time.stop();
-                    info!("{}", purge_time);
-
-                    if compact_first_slot < lowest_cleanup_slot {
+                    for purge in purges {
                         info!(
-                            "compacting data from slots {} to {}",
-                            compact_first_slot, lowest_cleanup_slot
+                            "purging {} data from slots {} to {}",
+                            purge.column_group.name(),
+                            purge.from_slot,
+                            purge.to_slot
                         );
-                        if let Err(err) =
-                            blockstore.compact_storage(compact_first_slot, lowest_cleanup_slot)
-                        {
-                            // This error is not fatal and indicates an internal error?
-                            error!(
-                                "Error: {:?}; Couldn't compact storage from {:?} to {:?}",
-                                err, compact_first_slot, lowest_cleanup_slot
+
+                        let mut purge_time = Measure::start("purge_slots");
+                        blockstore.purge_column_group(
+                            purge.column_group,
+                            purge.from_slot,
+                            purge.to_slot,
+                        );
+                        purge_time.stop();
+                        info!("{}", purge_time);
+
+                        if let Some(compact_from_slot) = purge.compact_from_slot {
+                            info!(
+                                "compacting {} data from slots {} to {}",
+                                purge.column_group.name(),
+                                compact_from_slot,
+                                purge.to_slot
                             );
+                            if let Err(err) = blockstore.compact_column_group(
+                                purge.column_group,
+                                compact_from_slot,
+                                purge.to_slot,
+                            ) {
+                                // This error is not fatal and indicates an internal error?
+                                error!(
+                                    "Error: {:?}; Couldn't compact {} storage from {:?} to {:?}",
+                                    err,
+                                    purge.column_group.name(),
+                                    compact_from_slot,
+                                    purge.to_slot
+                                );
+                            }
                         }
                     }
 
@@ -285,6 +580,85 @@ mod tests {
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_cleanup_with_retention() {
+        solana_logger::setup();
+        let blockstore_path = get_tmp_ledger_path!();
+        let blockstore = Blockstore::open(&blockstore_path).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 50, 5);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        let roots: Vec<Slot> = (0..50).collect();
+        blockstore.set_roots(&roots).unwrap();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as UnixTimestamp;
+        for slot in 0..50 {
+            let block_time = if slot < 40 { now - 3600 } else { now };
+            blockstore.cache_block_time(slot, block_time).unwrap();
+            blockstore.write_rewards(slot, vec![]).unwrap();
+        }
+        let blockstore = Arc::new(blockstore);
+        let (sender, receiver) = channel();
+
+        // Shreds older than half an hour go, transaction statuses and rewards are kept longer
+        let mut retention_config = LedgerRetentionConfig {
+            shreds: RetentionPolicy {
+                max_bytes: None,
+                max_age: Some(Duration::from_secs(1800)),
+            },
+            transaction_status: Some(RetentionPolicy::default()),
+            rewards: Some(RetentionPolicy {
+                max_bytes: None,
+                max_age: Some(Duration::from_secs(7200)),
+            }),
+        };
+        let mut last_purge_slot = 0;
+        let mut progress = LedgerCleanupProgress::default();
+        sender.send(50).unwrap();
+        LedgerCleanupService::cleanup_ledger_with_retention(
+            &receiver,
+            &blockstore,
+            std::u64::MAX,
+            &retention_config,
+            &mut last_purge_slot,
+            10,
+            &mut progress,
+            10,
+        )
+        .unwrap();
+
+        blockstore
+            .slot_meta_iterator(0)
+            .unwrap()
+            .for_each(|(slot, _)| assert!(slot >= 40));
+        assert!(blockstore.read_rewards(10).unwrap().is_some());
+        assert!(blockstore.is_root(10));
+        assert_eq!(*blockstore.lowest_cleanup_slot.read().unwrap(), 39);
+
+        // Rewards now follow shreds, roots are still needed by transaction statuses
+        retention_config.rewards = None;
+        sender.send(61).unwrap();
+        LedgerCleanupService::cleanup_ledger_with_retention(
+            &receiver,
+            &blockstore,
+            std::u64::MAX,
+            &retention_config,
+            &mut last_purge_slot,
+            10,
+            &mut progress,
+            10,
+        )
+        .unwrap();
+
+        assert!(blockstore.read_rewards(10).unwrap().is_none());
+        assert!(blockstore.read_rewards(40).unwrap().is_some());
+        assert!(blockstore.is_root(10));
+
+        drop(blockstore);
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_cleanup_speed() {
         solana_logger::setup();
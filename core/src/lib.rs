@@ -32,6 +32,8 @@ pub mod crds_gossip_push;
 pub mod crds_shards;
 pub mod crds_value;
 pub mod data_budget;
+pub mod duplicate_shred;
+pub mod duplicate_shred_listener;
 pub mod epoch_slots;
 pub mod fetch_stage;
 pub mod fork_choice;
@@ -70,6 +72,7 @@ pub mod sigverify_shreds;
 pub mod sigverify_stage;
 pub mod snapshot_packager_service;
 pub mod test_validator;
+pub mod tower_storage;
 pub mod tpu;
 pub mod transaction_status_service;
 pub mod tree_diff;
@@ -77,6 +80,7 @@ pub mod tvu;
 pub mod validator;
 pub mod verified_vote_packets;
 pub mod vote_stake_tracker;
+pub mod voting_service;
 pub mod weighted_shuffle;
 pub mod window_service;
 
@@ -21,7 +21,7 @@ use solana_sdk::hash::Hash;
 use solana_sdk::poh_config::PohConfig;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::timing;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::VersionedTransaction;
 use std::cmp;
 use std::sync::mpsc::{channel, Receiver, SendError, Sender, SyncSender};
 use std::sync::{Arc, Mutex};
@@ -316,6 +316,7 @@ impl PohRecorder {
 
             for tick in &self.tick_cache[..entry_count] {
                 working_bank.bank.register_tick(&tick.0.hash);
+                working_bank.bank.inc_tick_entries_count(1);
                 send_result = self.sender.send((working_bank.bank.clone(), tick.clone()));
                 if send_result.is_err() {
                     break;
@@ -385,7 +386,7 @@ impl PohRecorder {
         &mut self,
         bank_slot: Slot,
         mixin: Hash,
-        transactions: Vec<Transaction>,
+        transactions: Vec<VersionedTransaction>,
     ) -> Result<()> {
         // Entries without transactions are used to track real-time passing in the ledger and
         // cannot be generated by `record()`
@@ -422,6 +423,7 @@ impl PohRecorder {
                     };
                     self.sender
                         .send((working_bank.bank.clone(), (entry, self.tick_height)))?;
+                    working_bank.bank.inc_transaction_entries_count(1);
                     return Ok(());
                 }
             }
@@ -754,7 +756,9 @@ mod tests {
             poh_recorder.tick();
             let tx = test_tx();
             let h1 = hash(b"hello world!");
-            assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_err());
+            assert!(poh_recorder
+                .record(bank.slot(), h1, vec![tx.into()])
+                .is_err());
             assert!(entry_receiver.try_recv().is_err());
         }
         Blockstore::destroy(&ledger_path).unwrap();
@@ -793,7 +797,7 @@ mod tests {
             let tx = test_tx();
             let h1 = hash(b"hello world!");
             assert_matches!(
-                poh_recorder.record(bank.slot() + 1, h1, vec![tx]),
+                poh_recorder.record(bank.slot() + 1, h1, vec![tx.into()]),
                 Err(PohRecorderError::MaxHeightReached)
             );
         }
@@ -832,7 +836,9 @@ mod tests {
             assert_eq!(poh_recorder.tick_height, 1);
             let tx = test_tx();
             let h1 = hash(b"hello world!");
-            assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_ok());
+            assert!(poh_recorder
+                .record(bank.slot(), h1, vec![tx.into()])
+                .is_ok());
             assert_eq!(poh_recorder.tick_cache.len(), 0);
 
             //tick in the cache + entry
@@ -876,7 +882,9 @@ mod tests {
             assert_eq!(poh_recorder.tick_height, 2);
             let tx = test_tx();
             let h1 = hash(b"hello world!");
-            assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_err());
+            assert!(poh_recorder
+                .record(bank.slot(), h1, vec![tx.into()])
+                .is_err());
 
             let (_bank, (entry, _tick_height)) = entry_receiver.recv().unwrap();
             assert!(entry.is_tick());
@@ -1111,7 +1119,9 @@ mod tests {
 
             let tx = test_tx();
             let h1 = hash(b"hello world!");
-            assert!(poh_recorder.record(bank.slot(), h1, vec![tx]).is_err());
+            assert!(poh_recorder
+                .record(bank.slot(), h1, vec![tx.into()])
+                .is_err());
             assert!(poh_recorder.working_bank.is_none());
             // Make sure the starting slot is updated
             assert_eq!(poh_recorder.start_slot, end_slot);
@@ -176,10 +176,11 @@ mod tests {
                             // send some data
                             let h1 = hash(b"hello world!");
                             let tx = test_tx();
-                            let _ = poh_recorder
-                                .lock()
-                                .unwrap()
-                                .record(bank.slot(), h1, vec![tx]);
+                            let _ = poh_recorder.lock().unwrap().record(
+                                bank.slot(),
+                                h1,
+                                vec![tx.into()],
+                            );
 
                             if exit.load(Ordering::Relaxed) {
                                 break;
@@ -9,6 +9,7 @@ use crate::{
     cluster_slots::ClusterSlots,
     commitment_service::{AggregateCommitmentService, CommitmentAggregationData},
     consensus::{ComputedBankState, Stake, SwitchForkDecision, Tower, VotedStakes},
+    duplicate_shred_listener::GossipDuplicateSlotsReceiver,
     fork_choice::{ForkChoice, SelectVoteAndResetForkResult},
     heaviest_subtree_fork_choice::HeaviestSubtreeForkChoice,
     optimistically_confirmed_bank_tracker::{BankNotification, BankNotificationSender},
@@ -19,10 +20,13 @@ use crate::{
     result::Result,
     rewards_recorder_service::RewardsRecorderSender,
     rpc_subscriptions::RpcSubscriptions,
+    tower_storage::TowerStorage,
+    voting_service::{VoteOp, VotingService},
 };
 use solana_ledger::{
     block_error::BlockError,
     blockstore::Blockstore,
+    blockstore_meta::BlockStats,
     blockstore_processor::{self, BlockstoreProcessorError, TransactionStatusSender},
     entry::VerifyRecyclers,
     leader_schedule_cache::LeaderScheduleCache,
@@ -35,16 +39,20 @@ use solana_runtime::{
 };
 use solana_sdk::{
     clock::{Slot, NUM_CONSECUTIVE_LEADER_SLOTS},
+    feature_set,
     genesis_config::ClusterType,
     hash::Hash,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::Signer,
     timing::timestamp,
     transaction::Transaction,
 };
-use solana_vote_program::{vote_instruction, vote_state::Vote};
+use solana_vote_program::{
+    vote_instruction,
+    vote_state::{Vote, VoteStateUpdate},
+};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     ops::Deref,
     result,
     sync::{
@@ -96,7 +104,7 @@ struct SkippedSlotsInfo {
 pub struct ReplayStageConfig {
     pub my_pubkey: Pubkey,
     pub vote_account: Pubkey,
-    pub authorized_voter_keypairs: Vec<Arc<Keypair>>,
+    pub authorized_voter_keypairs: Vec<Arc<dyn Signer + Send + Sync>>,
     pub exit: Arc<AtomicBool>,
     pub subscriptions: Arc<RpcSubscriptions>,
     pub leader_schedule_cache: Arc<LeaderScheduleCache>,
@@ -107,6 +115,7 @@ pub struct ReplayStageConfig {
     pub rewards_recorder_sender: Option<RewardsRecorderSender>,
     pub cache_block_time_sender: Option<CacheBlockTimeSender>,
     pub bank_notification_sender: Option<BankNotificationSender>,
+    pub tower_storage: Arc<dyn TowerStorage>,
 }
 
 #[derive(Default)]
@@ -207,6 +216,7 @@ impl ReplayTiming {
 pub struct ReplayStage {
     t_replay: JoinHandle<Result<()>>,
     commitment_service: AggregateCommitmentService,
+    voting_service: VotingService,
 }
 
 impl ReplayStage {
@@ -224,6 +234,7 @@ impl ReplayStage {
         retransmit_slots_sender: RetransmitSlotsSender,
         duplicate_slots_reset_receiver: DuplicateSlotsResetReceiver,
         replay_vote_sender: ReplayVoteSender,
+        gossip_duplicate_slots_receiver: GossipDuplicateSlotsReceiver,
     ) -> Self {
         let ReplayStageConfig {
             my_pubkey,
@@ -239,6 +250,7 @@ impl ReplayStage {
             rewards_recorder_sender,
             cache_block_time_sender,
             bank_notification_sender,
+            tower_storage,
         } = config;
 
         trace!("replay stage");
@@ -248,6 +260,8 @@ impl ReplayStage {
             block_commitment_cache.clone(),
             subscriptions.clone(),
         );
+        let (voting_sender, voting_service) =
+            VotingService::new(&exit, cluster_info.clone(), tower_storage);
 
         #[allow(clippy::cognitive_complexity)]
         let t_replay = Builder::new()
@@ -271,6 +285,7 @@ impl ReplayStage {
                 let mut partition_exists = false;
                 let mut skipped_slots_info = SkippedSlotsInfo::default();
                 let mut replay_timing = ReplayTiming::default();
+                let mut gossip_duplicate_slots = BTreeSet::new();
                 loop {
                     let allocated = thread_mem_usage::Allocatedp::default();
 
@@ -380,6 +395,16 @@ impl ReplayStage {
                     }
                     compute_slot_stats_time.stop();
 
+                    // Exclude slots for which the cluster gossiped a proof
+                    // that the leader produced a duplicate block
+                    Self::process_gossip_duplicate_slots(
+                        &gossip_duplicate_slots_receiver,
+                        &mut gossip_duplicate_slots,
+                        forks_root,
+                        &progress,
+                        &mut heaviest_subtree_fork_choice,
+                    );
+
                     let mut select_forks_time = Measure::start("select_forks_time");
                     let fork_choice: &mut dyn ForkChoice =
                         if forks_root > unlock_heaviest_subtree_fork_choice_slot {
@@ -463,6 +488,7 @@ impl ReplayStage {
                             &mut heaviest_subtree_fork_choice,
                             &cache_block_time_sender,
                             &bank_notification_sender,
+                            &voting_sender,
                         )?;
                     };
                     voting_time.stop();
@@ -601,6 +627,7 @@ impl ReplayStage {
         Self {
             t_replay,
             commitment_service,
+            voting_service,
         }
     }
 
@@ -704,6 +731,40 @@ impl ReplayStage {
         }
     }
 
+    fn process_gossip_duplicate_slots(
+        gossip_duplicate_slots_receiver: &GossipDuplicateSlotsReceiver,
+        gossip_duplicate_slots: &mut BTreeSet<Slot>,
+        root: Slot,
+        progress: &ProgressMap,
+        heaviest_subtree_fork_choice: &mut HeaviestSubtreeForkChoice,
+    ) {
+        gossip_duplicate_slots.extend(gossip_duplicate_slots_receiver.try_iter());
+        // The root has moved past the remaining slots, so they are either
+        // not on the rooted fork or the cluster has confirmed them
+        *gossip_duplicate_slots = gossip_duplicate_slots.split_off(&(root + 1));
+        for slot in gossip_duplicate_slots.iter() {
+            // The slot may not have been replayed yet, in which case it is
+            // marked once it is added to fork choice
+            let is_candidate = match heaviest_subtree_fork_choice.is_candidate_slot(*slot) {
+                Some(is_candidate) => is_candidate,
+                None => continue,
+            };
+            // If the cluster has confirmed this version of the slot anyway,
+            // it is safe to keep building on it
+            let is_confirmed = progress
+                .get_fork_stats(*slot)
+                .map(|fork_stats| fork_stats.confirmation_reported)
+                .unwrap_or(false);
+            if is_candidate && !is_confirmed {
+                warn!("excluding duplicate slot {} from fork choice", slot);
+                heaviest_subtree_fork_choice.mark_fork_invalid_candidate(*slot);
+            } else if !is_candidate && is_confirmed {
+                warn!("duplicate slot {} was confirmed by the cluster", slot);
+                heaviest_subtree_fork_choice.mark_fork_valid_candidate(*slot);
+            }
+        }
+    }
+
     fn purge_unconfirmed_duplicate_slot(
         duplicate_slot: Slot,
         ancestors: &mut HashMap<Slot, HashSet<Slot>>,
@@ -1018,7 +1079,7 @@ impl ReplayStage {
         tower: &mut Tower,
         progress: &mut ProgressMap,
         vote_account_pubkey: &Pubkey,
-        authorized_voter_keypairs: &[Arc<Keypair>],
+        authorized_voter_keypairs: &[Arc<dyn Signer + Send + Sync>],
         cluster_info: &Arc<ClusterInfo>,
         blockstore: &Arc<Blockstore>,
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
@@ -1031,6 +1092,7 @@ impl ReplayStage {
         heaviest_subtree_fork_choice: &mut HeaviestSubtreeForkChoice,
         cache_block_time_sender: &Option<CacheBlockTimeSender>,
         bank_notification_sender: &Option<BankNotificationSender>,
+        voting_sender: &Sender<VoteOp>,
     ) -> Result<()> {
         if bank.is_empty() {
             inc_new_counter_info!("replay_stage-voted_empty_bank", 1);
@@ -1039,11 +1101,7 @@ impl ReplayStage {
         let (vote, tower_index) = tower.new_vote_from_bank(bank, vote_account_pubkey);
         let new_root = tower.record_bank_vote(vote);
         let last_vote = tower.last_vote_and_timestamp();
-
-        if let Err(err) = tower.save(&cluster_info.keypair) {
-            error!("Unable to save tower: {:?}", err);
-            std::process::exit(1);
-        }
+        let vote_state_update = tower.vote_state_update(&last_vote);
 
         if let Some(new_root) = new_root {
             // get the root bank before squash
@@ -1105,29 +1163,33 @@ impl ReplayStage {
             progress.get_fork_stats(bank.slot()).unwrap().total_stake,
             lockouts_sender,
         );
-        Self::push_vote(
+        let vote_tx = Self::generate_vote_tx(
             cluster_info,
             bank,
             vote_account_pubkey,
             authorized_voter_keypairs,
             last_vote,
-            tower_index,
+            vote_state_update,
             switch_fork_decision,
         );
+        // The tower is persisted, and the vote signed and sent once it is, by the voting service
+        if let Err(e) = voting_sender.send(VoteOp::new(tower.clone(), vote_tx, tower_index)) {
+            trace!("voting_sender failed: {:?}", e);
+        }
         Ok(())
     }
 
-    fn push_vote(
+    fn generate_vote_tx(
         cluster_info: &ClusterInfo,
         bank: &Arc<Bank>,
         vote_account_pubkey: &Pubkey,
-        authorized_voter_keypairs: &[Arc<Keypair>],
+        authorized_voter_keypairs: &[Arc<dyn Signer + Send + Sync>],
         vote: Vote,
-        tower_index: usize,
+        vote_state_update: VoteStateUpdate,
         switch_fork_decision: &SwitchForkDecision,
-    ) {
+    ) -> Option<(Transaction, Arc<dyn Signer + Send + Sync>)> {
         if authorized_voter_keypairs.is_empty() {
-            return;
+            return None;
         }
         let vote_account = match bank.get_vote_account(vote_account_pubkey) {
             None => {
@@ -1135,7 +1197,7 @@ impl ReplayStage {
                     "Vote account {} does not exist.  Unable to vote",
                     vote_account_pubkey,
                 );
-                return;
+                return None;
             }
             Some((_stake, vote_account)) => vote_account,
         };
@@ -1146,7 +1208,7 @@ impl ReplayStage {
                     "Vote account {} is unreadable.  Unable to vote",
                     vote_account_pubkey,
                 );
-                return;
+                return None;
             }
             Ok(vote_state) => vote_state,
         };
@@ -1159,7 +1221,7 @@ impl ReplayStage {
                     vote_account_pubkey,
                     bank.epoch()
                 );
-                return;
+                return None;
             };
 
         let authorized_voter_keypair = match authorized_voter_keypairs
@@ -1169,14 +1231,27 @@ impl ReplayStage {
             None => {
                 warn!("The authorized keypair {} for vote account {} is not available.  Unable to vote",
                       authorized_voter_pubkey, vote_account_pubkey);
-                return;
+                return None;
             }
             Some(authorized_voter_keypair) => authorized_voter_keypair,
         };
         let node_keypair = cluster_info.keypair.clone();
 
-        // Send our last few votes along with the new one
-        let vote_ix = if bank.slot() > Self::get_unlock_switch_vote_slot(bank.cluster_type()) {
+        let vote_ix = if bank
+            .feature_set
+            .is_active(&feature_set::vote_state_update::id())
+        {
+            // Send our whole tower, so the vote account's lockouts track it even if some of our
+            // earlier votes were dropped
+            switch_fork_decision
+                .to_vote_state_update_instruction(
+                    vote_state_update,
+                    &vote_account_pubkey,
+                    &authorized_voter_keypair.pubkey(),
+                )
+                .expect("Switch threshold failure should not lead to voting")
+        } else if bank.slot() > Self::get_unlock_switch_vote_slot(bank.cluster_type()) {
+            // Send our last few votes along with the new one
             switch_fork_decision
                 .to_vote_instruction(
                     vote,
@@ -1196,9 +1271,9 @@ impl ReplayStage {
 
         let blockhash = bank.last_blockhash();
         vote_tx.partial_sign(&[node_keypair.as_ref()], blockhash);
-        vote_tx.partial_sign(&[authorized_voter_keypair.as_ref()], blockhash);
-        let _ = cluster_info.send_vote(&vote_tx);
-        cluster_info.push_vote(tower_index, vote_tx);
+        // The authorized voter may be a remote signer, so leave its signature to the voting
+        // service
+        Some((vote_tx, authorized_voter_keypair.clone()))
     }
 
     fn update_commitment_cache(
@@ -1338,6 +1413,7 @@ impl ReplayStage {
                 }
 
                 Self::record_rewards(&bank, &rewards_recorder_sender);
+                Self::record_block_stats(&bank, &blockstore);
             } else {
                 trace!(
                     "bank {} not completed tick_height: {}, max_tick_height: {}",
@@ -1904,6 +1980,21 @@ impl ReplayStage {
         }
     }
 
+    fn record_block_stats(bank: &Bank, blockstore: &Blockstore) {
+        let block_stats = BlockStats {
+            num_transactions: bank.executed_transaction_count(),
+            num_failed_transactions: bank.transaction_error_count(),
+            num_vote_transactions: bank.vote_transaction_count(),
+            compute_units_consumed: bank.compute_units_consumed(),
+            fees: bank.collector_fees(),
+            num_entries: bank.transaction_entries_count(),
+            num_ticks: bank.tick_entries_count(),
+        };
+        blockstore
+            .write_block_stats(bank.slot(), &block_stats)
+            .unwrap_or_else(|err| warn!("write_block_stats failed: {:?}", err));
+    }
+
     fn cache_block_times(
         blockstore: &Arc<Blockstore>,
         bank_forks: &Arc<RwLock<BankForks>>,
@@ -1958,6 +2049,7 @@ impl ReplayStage {
 
     pub fn join(self) -> thread::Result<()> {
         self.commitment_service.join()?;
+        self.voting_service.join()?;
         self.t_replay.join().map(|_| ())
     }
 }
@@ -1,6 +1,7 @@
 //! The `result` module exposes a Result type that propagates one of many different Error types.
 
 use crate::cluster_info;
+use crate::duplicate_shred;
 use crate::poh_recorder;
 use solana_ledger::block_error;
 use solana_ledger::blockstore;
@@ -31,6 +32,7 @@ pub enum Error {
     BlockstoreError(blockstore::BlockstoreError),
     FsExtra(fs_extra::error::Error),
     SnapshotError(snapshot_utils::SnapshotError),
+    DuplicateShredError(duplicate_shred::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -143,6 +145,11 @@ impl std::convert::From<snapshot_utils::SnapshotError> for Error {
         Error::SnapshotError(e)
     }
 }
+impl std::convert::From<duplicate_shred::Error> for Error {
+    fn from(e: duplicate_shred::Error) -> Error {
+        Error::DuplicateShredError(e)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -18,6 +18,7 @@ use solana_account_decoder::{
         get_token_account_mint, spl_token_id_v2_0, spl_token_v2_0_native_mint,
         token_amount_to_ui_amount, UiTokenAmount,
     },
+    validator_info::{parse_validator_info, UiValidatorInfo},
     UiAccount, UiAccountData, UiAccountEncoding, UiDataSliceConfig,
 };
 use solana_client::{
@@ -56,7 +57,7 @@ use solana_sdk::{
     stake_history::StakeHistory,
     system_instruction,
     sysvar::stake_history,
-    transaction::{self, Transaction},
+    transaction::{self, VersionedTransaction},
 };
 use solana_stake_program::stake_state::StakeState;
 use solana_transaction_status::{
@@ -71,6 +72,7 @@ use std::{
     cmp::{max, min},
     collections::{HashMap, HashSet},
     net::SocketAddr,
+    path::PathBuf,
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -108,6 +110,7 @@ pub struct JsonRpcConfig {
     pub health_check_slot_distance: u64,
     pub enable_bigtable_ledger_storage: bool,
     pub enable_bigtable_ledger_upload: bool,
+    pub event_layouts_path: Option<PathBuf>,
 }
 
 #[derive(Clone)]
@@ -582,6 +585,14 @@ impl JsonRpcRequestProcessor {
         })
     }
 
+    fn get_validator_info(&self, commitment: Option<CommitmentConfig>) -> Vec<UiValidatorInfo> {
+        let bank = self.bank(commitment);
+        bank.get_program_accounts(&solana_config_program::id())
+            .into_iter()
+            .filter_map(|(pubkey, account)| parse_validator_info(&pubkey, &account).ok())
+            .collect()
+    }
+
     pub fn set_log_filter(&self, filter: String) {
         if self.config.enable_set_log_filter {
             solana_logger::setup_with(&filter);
@@ -780,6 +791,51 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    pub fn get_block_stats(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+    ) -> Result<Vec<RpcBlockStats>> {
+        let end_slot = min(
+            end_slot.unwrap_or(std::u64::MAX),
+            self.block_commitment_cache
+                .read()
+                .unwrap()
+                .highest_confirmed_root(),
+        );
+        if end_slot < start_slot {
+            return Ok(vec![]);
+        }
+        if end_slot - start_slot > MAX_GET_CONFIRMED_BLOCKS_RANGE {
+            return Err(Error::invalid_params(format!(
+                "Slot range too large; max {}",
+                MAX_GET_CONFIRMED_BLOCKS_RANGE
+            )));
+        }
+
+        let result = self.blockstore.get_block_stats_range(start_slot, end_slot);
+        self.check_slot_cleaned_up(&result, start_slot)?;
+
+        // Statistics are recorded for every frozen bank, only report those of rooted blocks
+        Ok(result
+            .map_err(|_| Error::internal_error())?
+            .into_iter()
+            .filter(|(slot, _)| self.blockstore.is_root(*slot))
+            .map(|(slot, block_stats)| RpcBlockStats {
+                slot,
+                num_transactions: block_stats.num_transactions,
+                num_successful_transactions: block_stats.num_successful_transactions(),
+                num_failed_transactions: block_stats.num_failed_transactions,
+                num_vote_transactions: block_stats.num_vote_transactions,
+                num_non_vote_transactions: block_stats.num_non_vote_transactions(),
+                compute_units_consumed: block_stats.compute_units_consumed,
+                fees: block_stats.fees,
+                num_entries: block_stats.num_entries,
+                num_ticks: block_stats.num_ticks,
+            })
+            .collect())
+    }
+
     pub fn get_signature_confirmation_status(
         &self,
         signature: Signature,
@@ -1293,12 +1349,15 @@ impl JsonRpcRequestProcessor {
     }
 }
 
-fn verify_transaction(transaction: &Transaction) -> Result<()> {
+fn verify_transaction(transaction: &VersionedTransaction, bank: &Bank) -> Result<()> {
     if transaction.verify().is_err() {
         return Err(RpcCustomError::TransactionSignatureVerificationFailure.into());
     }
 
-    if let Err(e) = transaction.verify_precompiles() {
+    if let Err(e) = transaction.verify_precompiles(
+        bank.secp256k1_program_enabled(),
+        bank.ed25519_program_enabled(),
+    ) {
         return Err(RpcCustomError::TransactionPrecompileVerificationFailure(e).into());
     }
 
@@ -1740,6 +1799,13 @@ pub trait RpcSol {
         commitment: Option<CommitmentConfig>,
     ) -> Result<RpcVoteAccountStatus>;
 
+    #[rpc(meta, name = "getValidatorInfo")]
+    fn get_validator_info(
+        &self,
+        meta: Self::Metadata,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Vec<UiValidatorInfo>>;
+
     #[rpc(meta, name = "validatorExit")]
     fn validator_exit(&self, meta: Self::Metadata) -> Result<bool>;
 
@@ -1763,6 +1829,14 @@ pub trait RpcSol {
     #[rpc(meta, name = "getBlockTime")]
     fn get_block_time(&self, meta: Self::Metadata, slot: Slot) -> Result<Option<UnixTimestamp>>;
 
+    #[rpc(meta, name = "getBlockStats")]
+    fn get_block_stats(
+        &self,
+        meta: Self::Metadata,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+    ) -> Result<Vec<RpcBlockStats>>;
+
     #[rpc(meta, name = "getConfirmedBlocks")]
     fn get_confirmed_blocks(
         &self,
@@ -1864,7 +1938,7 @@ pub trait RpcSol {
 
 fn _send_transaction(
     meta: JsonRpcRequestProcessor,
-    transaction: Transaction,
+    transaction: VersionedTransaction,
     wire_transaction: Vec<u8>,
     last_valid_slot: Slot,
 ) -> Result<String> {
@@ -2296,7 +2370,7 @@ impl RpcSol for RpcSolImpl {
             Error::internal_error()
         })?;
 
-        _send_transaction(meta, transaction, wire_transaction, last_valid_slot)
+        _send_transaction(meta, transaction.into(), wire_transaction, last_valid_slot)
     }
 
     fn send_transaction(
@@ -2311,11 +2385,11 @@ impl RpcSol for RpcSolImpl {
         let (wire_transaction, transaction) = deserialize_transaction(data, encoding)?;
         let bank = &*meta.bank(None);
         let last_valid_slot = bank
-            .get_blockhash_last_valid_slot(&transaction.message.recent_blockhash)
+            .get_blockhash_last_valid_slot(transaction.message.recent_blockhash())
             .unwrap_or(0);
 
         if !config.skip_preflight {
-            if let Err(e) = verify_transaction(&transaction) {
+            if let Err(e) = verify_transaction(&transaction, bank) {
                 return Err(e);
             }
 
@@ -2327,12 +2401,25 @@ impl RpcSol for RpcSolImpl {
                 .preflight_commitment
                 .map(|commitment| CommitmentConfig { commitment });
             let preflight_bank = &*meta.bank(preflight_commitment);
-            if let (Err(err), logs) = preflight_bank.simulate_transaction(transaction.clone()) {
+            let resolved_transaction = preflight_bank
+                .resolve_versioned_transaction(transaction.clone())
+                .map_err(|err| RpcCustomError::SendTransactionPreflightFailure {
+                    message: format!("Transaction resolution failed: {}", err),
+                    result: RpcSimulateTransactionResult {
+                        err: Some(err),
+                        logs: None,
+                        return_data: None,
+                    },
+                })?;
+            if let (Err(err), logs, return_data) =
+                preflight_bank.simulate_transaction(resolved_transaction)
+            {
                 return Err(RpcCustomError::SendTransactionPreflightFailure {
                     message: format!("Transaction simulation failed: {}", err),
                     result: RpcSimulateTransactionResult {
                         err: Some(err),
                         logs: Some(logs),
+                        return_data: return_data.map(|return_data| return_data.into()),
                     },
                 }
                 .into());
@@ -2353,20 +2440,34 @@ impl RpcSol for RpcSolImpl {
         let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base58);
         let (_, transaction) = deserialize_transaction(data, encoding)?;
 
+        let bank = &*meta.bank(config.commitment);
         if config.sig_verify {
-            if let Err(e) = verify_transaction(&transaction) {
+            if let Err(e) = verify_transaction(&transaction, bank) {
                 return Err(e);
             }
         }
 
-        let bank = &*meta.bank(config.commitment);
-        let (result, logs) = bank.simulate_transaction(transaction);
+        let transaction = match bank.resolve_versioned_transaction(transaction) {
+            Ok(transaction) => transaction,
+            Err(err) => {
+                return Ok(new_response(
+                    &bank,
+                    RpcSimulateTransactionResult {
+                        err: Some(err),
+                        logs: None,
+                        return_data: None,
+                    },
+                ))
+            }
+        };
+        let (result, logs, return_data) = bank.simulate_transaction(transaction);
 
         Ok(new_response(
             &bank,
             RpcSimulateTransactionResult {
                 err: result.err(),
                 logs: Some(logs),
+                return_data: return_data.map(|return_data| return_data.into()),
             },
         ))
     }
@@ -2394,6 +2495,15 @@ impl RpcSol for RpcSolImpl {
         meta.get_vote_accounts(commitment)
     }
 
+    fn get_validator_info(
+        &self,
+        meta: Self::Metadata,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Vec<UiValidatorInfo>> {
+        debug!("get_validator_info rpc request received");
+        Ok(meta.get_validator_info(commitment))
+    }
+
     fn validator_exit(&self, meta: Self::Metadata) -> Result<bool> {
         debug!("validator_exit rpc request received");
         Ok(meta.validator_exit())
@@ -2461,6 +2571,19 @@ impl RpcSol for RpcSolImpl {
         meta.get_block_time(slot)
     }
 
+    fn get_block_stats(
+        &self,
+        meta: Self::Metadata,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+    ) -> Result<Vec<RpcBlockStats>> {
+        debug!(
+            "get_block_stats rpc request received: {}-{:?}",
+            start_slot, end_slot
+        );
+        meta.get_block_stats(start_slot, end_slot)
+    }
+
     fn get_confirmed_transaction(
         &self,
         meta: Self::Metadata,
@@ -2635,7 +2758,7 @@ const WORST_CASE_BASE64_TX: usize = 1644; // Golden, bump if PACKET_DATA_SIZE ch
 fn deserialize_transaction(
     encoded_transaction: String,
     encoding: UiTransactionEncoding,
-) -> Result<(Vec<u8>, Transaction)> {
+) -> Result<(Vec<u8>, VersionedTransaction)> {
     let wire_transaction = match encoding {
         UiTransactionEncoding::Base58 => {
             inc_new_counter_info!("rpc-base58_encoded_tx", 1);
@@ -2716,9 +2839,14 @@ pub mod tests {
         futures::future::Future, ErrorCode, MetaIoHandler, Output, Response, Value,
     };
     use jsonrpc_core_client::transports::local;
-    use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes};
+    use solana_account_decoder::validator_info::{IdentityProof, ValidatorInfo, ValidatorMetadata};
+    use solana_client::{
+        rpc_custom_error::JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP,
+        rpc_filter::{Memcmp, MemcmpEncodedBytes},
+    };
     use solana_ledger::{
-        blockstore_meta::PerfSample,
+        blockstore::ColumnGroup,
+        blockstore_meta::{BlockStats, PerfSample},
         blockstore_processor::fill_blockstore_slot_with_ticks,
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
     };
@@ -2733,7 +2861,7 @@ pub mod tests {
         signature::{Keypair, Signer},
         system_program, system_transaction,
         timing::slot_duration_from_slots_per_year,
-        transaction::{self, TransactionError},
+        transaction::{self, Transaction, TransactionError},
     };
     use solana_transaction_status::{
         EncodedTransaction, EncodedTransactionWithStatusMeta, UiMessage,
@@ -3584,6 +3712,57 @@ pub mod tests {
         result["error"].as_object().unwrap();
     }
 
+    #[test]
+    fn test_rpc_get_validator_info() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler { io, meta, bank, .. } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let identity = solana_sdk::pubkey::new_rand();
+        let info_pubkey = solana_sdk::pubkey::new_rand();
+        let metadata = ValidatorMetadata {
+            name: "Alice".to_string(),
+            identity_proof: Some(IdentityProof::new(
+                &Keypair::new(),
+                &identity,
+                "https://example.com/proof".to_string(),
+            )),
+            ..ValidatorMetadata::default()
+        };
+        let validator_info = ValidatorInfo {
+            info: serde_json::to_string(&metadata).unwrap(),
+        };
+        let account = solana_config_program::create_config_account(
+            vec![
+                (solana_account_decoder::validator_info::id(), false),
+                (identity, true),
+            ],
+            &validator_info,
+            42,
+        );
+        bank.store_account(&info_pubkey, &account);
+
+        // Config accounts that are not validator info are skipped
+        let other_pubkey = solana_sdk::pubkey::new_rand();
+        let account = solana_config_program::create_config_account(vec![], &validator_info, 42);
+        bank.store_account(&other_pubkey, &account);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getValidatorInfo"}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let validator_infos: Vec<UiValidatorInfo> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(
+            validator_infos,
+            vec![UiValidatorInfo {
+                info_pubkey: info_pubkey.to_string(),
+                identity_pubkey: identity.to_string(),
+                info: metadata,
+                verified: true,
+            }]
+        );
+    }
+
     #[test]
     fn test_rpc_get_program_accounts() {
         let bob = Keypair::new();
@@ -4987,6 +5166,127 @@ pub mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_get_block_stats() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            block_commitment_cache,
+            ..
+        } = start_rpc_handler_with_tx_and_blockstore(&bob_pubkey, vec![1, 2, 3, 4, 5, 6, 7]);
+        block_commitment_cache
+            .write()
+            .unwrap()
+            .set_highest_confirmed_root(6);
+        // Slot 8 is frozen but not rooted
+        for slot in 1..=8 {
+            meta.blockstore
+                .write_block_stats(
+                    slot,
+                    &BlockStats {
+                        num_transactions: 10 + slot,
+                        num_failed_transactions: 1,
+                        num_vote_transactions: 5,
+                        compute_units_consumed: 100 * slot,
+                        fees: 5000 * slot,
+                        num_entries: 3,
+                        num_ticks: 64,
+                    },
+                )
+                .unwrap();
+        }
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlockStats","params":[4]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let block_stats: Vec<RpcBlockStats> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(
+            block_stats,
+            vec![
+                RpcBlockStats {
+                    slot: 4,
+                    num_transactions: 14,
+                    num_successful_transactions: 13,
+                    num_failed_transactions: 1,
+                    num_vote_transactions: 5,
+                    num_non_vote_transactions: 9,
+                    compute_units_consumed: 400,
+                    fees: 20000,
+                    num_entries: 3,
+                    num_ticks: 64,
+                },
+                RpcBlockStats {
+                    slot: 5,
+                    num_transactions: 15,
+                    num_successful_transactions: 14,
+                    num_failed_transactions: 1,
+                    num_vote_transactions: 5,
+                    num_non_vote_transactions: 10,
+                    compute_units_consumed: 500,
+                    fees: 25000,
+                    num_entries: 3,
+                    num_ticks: 64,
+                },
+                RpcBlockStats {
+                    slot: 6,
+                    num_transactions: 16,
+                    num_successful_transactions: 15,
+                    num_failed_transactions: 1,
+                    num_vote_transactions: 5,
+                    num_non_vote_transactions: 11,
+                    compute_units_consumed: 600,
+                    fees: 30000,
+                    num_entries: 3,
+                    num_ticks: 64,
+                },
+            ]
+        );
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlockStats","params":[2, 3]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let block_stats: Vec<RpcBlockStats> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        let slots: Vec<Slot> = block_stats.iter().map(|stats| stats.slot).collect();
+        assert_eq!(slots, vec![2, 3]);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlockStats","params":[7]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let block_stats: Vec<RpcBlockStats> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(block_stats, vec![]);
+
+        meta.blockstore
+            .set_column_group_cleanup_slot(ColumnGroup::BlockMetadata, 2);
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getBlockStats","params":[2, 3]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["error"]["code"],
+            JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP
+        );
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getBlockStats","params":[0, {}]}}"#,
+            MAX_GET_CONFIRMED_BLOCKS_RANGE + 1
+        );
+        block_commitment_cache
+            .write()
+            .unwrap()
+            .set_highest_confirmed_root(std::u64::MAX);
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["error"]["code"], -32602);
+    }
+
     fn advance_block_commitment_cache(
         block_commitment_cache: &Arc<RwLock<BlockCommitmentCache>>,
         bank_forks: &Arc<RwLock<BankForks>>,
@@ -25,6 +25,7 @@ use solana_runtime::{
 use solana_sdk::{hash::Hash, native_token::lamports_to_sol, pubkey::Pubkey};
 use std::{
     collections::HashSet,
+    io::SeekFrom,
     net::SocketAddr,
     path::{Path, PathBuf},
     sync::atomic::{AtomicBool, Ordering},
@@ -105,7 +106,18 @@ impl RpcRequestMiddleware {
         }
     }
 
-    fn process_file_get(&self, path: &str) -> RequestMiddlewareAction {
+    fn range_not_satisfiable(file_size: u64) -> hyper::Response<hyper::Body> {
+        hyper::Response::builder()
+            .status(hyper::StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(
+                hyper::header::CONTENT_RANGE,
+                format!("bytes */{}", file_size),
+            )
+            .body(hyper::Body::empty())
+            .unwrap()
+    }
+
+    fn process_file_get(&self, path: &str, range: Option<&str>) -> RequestMiddlewareAction {
         // Stuck on tokio 0.1 until the jsonrpc-http-server crate upgrades to tokio 0.2
         use tokio_01::prelude::*;
 
@@ -122,16 +134,55 @@ impl RpcRequestMiddleware {
             }
         };
 
-        info!("get {} -> {:?}", path, filename);
+        info!("get {} -> {:?} (range: {:?})", path, filename, range);
 
+        let range = range.map(str::to_string);
         RequestMiddlewareAction::Respond {
             should_validate_hosts: true,
             response: Box::new(
                 tokio_fs_01::file::File::open(filename)
-                    .and_then(|file| {
-                        let buf: Vec<u8> = Vec::new();
-                        tokio_io_01::io::read_to_end(file, buf)
-                            .and_then(|item| Ok(hyper::Response::new(item.1.into())))
+                    .and_then(move |file| {
+                        file.metadata()
+                            .and_then(move |(file, metadata)| {
+                                let file_size = metadata.len();
+                                let (start, end, partial) =
+                                    match parse_byte_range(range.as_deref(), file_size) {
+                                        ByteRange::Full => (0, file_size, false),
+                                        ByteRange::Partial(first, last) => (first, last + 1, true),
+                                        ByteRange::Unsatisfiable => {
+                                            return future::Either::A(future::ok(
+                                                RpcRequestMiddleware::range_not_satisfiable(
+                                                    file_size,
+                                                ),
+                                            ));
+                                        }
+                                    };
+                                future::Either::B(
+                                    file.seek(SeekFrom::Start(start))
+                                        .and_then(move |(file, _)| {
+                                            let buf = vec![0; (end - start) as usize];
+                                            tokio_io_01::io::read_exact(file, buf)
+                                        })
+                                        .map(move |(_, buf)| {
+                                            let mut response = hyper::Response::builder();
+                                            response.header(hyper::header::ACCEPT_RANGES, "bytes");
+                                            if partial {
+                                                response
+                                                    .status(hyper::StatusCode::PARTIAL_CONTENT)
+                                                    .header(
+                                                        hyper::header::CONTENT_RANGE,
+                                                        format!(
+                                                            "bytes {}-{}/{}",
+                                                            start,
+                                                            end - 1,
+                                                            file_size
+                                                        ),
+                                                    );
+                                            }
+                                            response.body(buf.into()).unwrap()
+                                        }),
+                                )
+                            })
                             .or_else(|_| Ok(RpcRequestMiddleware::internal_server_error()))
                     })
                     .or_else(|_| Ok(RpcRequestMiddleware::not_found())),
@@ -191,7 +242,11 @@ impl RequestMiddleware for RpcRequestMiddleware {
                 )),
             }
         } else if self.is_file_get_path(request.uri().path()) {
-            self.process_file_get(request.uri().path())
+            let range = request
+                .headers()
+                .get(hyper::header::RANGE)
+                .and_then(|range| range.to_str().ok());
+            self.process_file_get(request.uri().path(), range)
         } else if request.uri().path() == "/health" {
             RequestMiddlewareAction::Respond {
                 should_validate_hosts: true,
@@ -234,6 +289,58 @@ fn process_rest(bank_forks: &Arc<RwLock<BankForks>>, path: &str) -> Option<Strin
     }
 }
 
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    /// No usable `Range` header, serve the whole file
+    Full,
+    /// Inclusive first and last byte offsets to serve
+    Partial(u64, u64),
+    /// The requested range lies entirely beyond the end of the file
+    Unsatisfiable,
+}
+
+/// Interprets a single `bytes=` range from an HTTP `Range` header.  Malformed or multi-range
+/// headers are ignored, as RFC 7233 permits, and the whole file is served instead
+fn parse_byte_range(range: Option<&str>, file_size: u64) -> ByteRange {
+    let range = match range.and_then(|range| range.trim().strip_prefix("bytes=")) {
+        Some(range) if !range.contains(',') => range,
+        _ => return ByteRange::Full,
+    };
+    let (first, last) = match range.find('-') {
+        Some(i) => (range[..i].trim(), range[i + 1..].trim()),
+        None => return ByteRange::Full,
+    };
+
+    if first.is_empty() {
+        // Suffix range, the last `n` bytes of the file
+        return match last.parse::<u64>() {
+            Ok(suffix_length) if suffix_length > 0 && file_size > 0 => {
+                ByteRange::Partial(file_size.saturating_sub(suffix_length), file_size - 1)
+            }
+            Ok(_) => ByteRange::Unsatisfiable,
+            Err(_) => ByteRange::Full,
+        };
+    }
+
+    let first = match first.parse::<u64>() {
+        Ok(first) => first,
+        Err(_) => return ByteRange::Full,
+    };
+    let last = if last.is_empty() {
+        u64::MAX
+    } else {
+        match last.parse::<u64>() {
+            Ok(last) if last >= first => last,
+            _ => return ByteRange::Full,
+        }
+    };
+    if first >= file_size {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(first, last.min(file_size - 1))
+    }
+}
+
 impl JsonRpcService {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -534,6 +641,132 @@ mod tests {
         assert!(!rrm.is_file_get_path("🎣"));
     }
 
+    #[test]
+    fn test_parse_byte_range() {
+        assert_eq!(parse_byte_range(None, 100), ByteRange::Full);
+        assert_eq!(
+            parse_byte_range(Some("bytes=0-0"), 100),
+            ByteRange::Partial(0, 0)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=10-19"), 100),
+            ByteRange::Partial(10, 19)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=90-"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=90-200"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=-10"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=-200"), 100),
+            ByteRange::Partial(0, 99)
+        );
+
+        assert_eq!(
+            parse_byte_range(Some("bytes=100-"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=-0"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            parse_byte_range(Some("bytes=0-0"), 0),
+            ByteRange::Unsatisfiable
+        );
+
+        assert_eq!(parse_byte_range(Some("bytes=19-10"), 100), ByteRange::Full);
+        assert_eq!(
+            parse_byte_range(Some("bytes=0-1,5-6"), 100),
+            ByteRange::Full
+        );
+        assert_eq!(parse_byte_range(Some("items=0-1"), 100), ByteRange::Full);
+        assert_eq!(parse_byte_range(Some("bytes=a-b"), 100), ByteRange::Full);
+    }
+
+    #[test]
+    fn test_process_file_get_range() {
+        use tokio_01::prelude::*;
+
+        let snapshot_package_output_path = tempfile::TempDir::new().unwrap();
+        let path = "/snapshot-100-AvFf9oS8A8U78HdjT9YG2sTTThLHJZmhaMn2g8vkWYnr.tar.bz2";
+        let contents: Vec<u8> = (0..=255).collect();
+        std::fs::write(
+            snapshot_package_output_path.path().join(&path[1..]),
+            &contents,
+        )
+        .unwrap();
+        let rrm = RpcRequestMiddleware::new(
+            PathBuf::from("/"),
+            Some(SnapshotConfig {
+                snapshot_interval_slots: 0,
+                snapshot_package_output_path: snapshot_package_output_path.path().to_path_buf(),
+                snapshot_path: PathBuf::from("/"),
+                compression: CompressionType::Bzip2,
+                snapshot_version: SnapshotVersion::default(),
+            }),
+            create_bank_forks(),
+            RpcHealth::stub(),
+        );
+
+        let mut runtime = tokio_01::runtime::Runtime::new().unwrap();
+        let mut get = |range: Option<&str>| {
+            let mut request = hyper::Request::get(path);
+            if let Some(range) = range {
+                request.header(hyper::header::RANGE, range);
+            }
+            let response = match rrm.on_request(request.body(hyper::Body::empty()).unwrap()) {
+                RequestMiddlewareAction::Respond { response, .. } => {
+                    runtime.block_on(response).unwrap()
+                }
+                RequestMiddlewareAction::Proceed { .. } => panic!("file get not handled"),
+            };
+            let status = response.status();
+            let content_range = response
+                .headers()
+                .get(hyper::header::CONTENT_RANGE)
+                .map(|content_range| content_range.to_str().unwrap().to_string());
+            let body = runtime
+                .block_on(response.into_body().concat2())
+                .unwrap()
+                .to_vec();
+            (status, content_range, body)
+        };
+
+        assert_eq!(
+            get(Some("bytes=100-149")),
+            (
+                hyper::StatusCode::PARTIAL_CONTENT,
+                Some("bytes 100-149/256".to_string()),
+                contents[100..150].to_vec()
+            )
+        );
+        assert_eq!(
+            get(Some("bytes=0-0")),
+            (
+                hyper::StatusCode::PARTIAL_CONTENT,
+                Some("bytes 0-0/256".to_string()),
+                vec![0]
+            )
+        );
+        assert_eq!(
+            get(Some("bytes=256-")),
+            (
+                hyper::StatusCode::RANGE_NOT_SATISFIABLE,
+                Some("bytes */256".to_string()),
+                vec![]
+            )
+        );
+        assert_eq!(get(None), (hyper::StatusCode::OK, None, contents));
+    }
+
     #[test]
     fn test_health_check_with_no_trusted_validators() {
         let rm = RpcRequestMiddleware::new(
@@ -0,0 +1,439 @@
+//! The `tower_storage` module persists the signed vote tower of a validator.
+//!
+//! The default backend keeps the tower in a local file.  The remote backend
+//! keeps it in a key-value store supporting compare-and-swap, such as etcd,
+//! so that when a primary and a hot-spare validator share the same identity,
+//! a save by an instance holding a stale copy of the tower fails instead of
+//! silently overwriting the votes of the other instance.
+
+use crate::consensus::{Result, SavedTower, TowerError};
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+pub trait TowerStorage: fmt::Debug + Sync + Send {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<SavedTower>;
+    fn store(&self, node_pubkey: &Pubkey, saved_tower: &SavedTower) -> Result<()>;
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileTowerStorage {
+    pub tower_path: PathBuf,
+}
+
+impl FileTowerStorage {
+    pub fn new(tower_path: PathBuf) -> Self {
+        Self { tower_path }
+    }
+
+    pub fn filename(&self, node_pubkey: &Pubkey) -> PathBuf {
+        self.tower_path
+            .join(format!("tower-{}", node_pubkey))
+            .with_extension("bin")
+    }
+}
+
+impl TowerStorage for FileTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<SavedTower> {
+        let filename = self.filename(node_pubkey);
+        trace!("load {}", filename.display());
+
+        // Ensure to create parent dir here, because restore() precedes save() always
+        fs::create_dir_all(&filename.parent().unwrap())?;
+
+        let file = File::open(&filename)?;
+        let mut stream = BufReader::new(file);
+        bincode::deserialize_from(&mut stream).map_err(|e| e.into())
+    }
+
+    fn store(&self, node_pubkey: &Pubkey, saved_tower: &SavedTower) -> Result<()> {
+        let filename = self.filename(node_pubkey);
+        trace!("store: {}", filename.display());
+        let new_filename = filename.with_extension("bin.new");
+
+        {
+            // overwrite anything if exists
+            let mut file = File::create(&new_filename)?;
+            bincode::serialize_into(&mut file, saved_tower)?;
+            // file.sync_all() hurts performance; pipeline sync-ing and submitting votes to the cluster!
+        }
+        fs::rename(&new_filename, &filename)?;
+        // self.path.parent().sync_all() hurts performance same as the above sync
+        Ok(())
+    }
+}
+
+/// Revision of a key in a `CasKeyValueStore`, changing on every update
+pub type Revision = u64;
+
+/// A key-value store which can atomically update a key only if it has not
+/// been modified since it was last read, e.g. etcd
+pub trait CasKeyValueStore: fmt::Debug + Sync + Send {
+    /// Returns the current revision and value of `key`, if any
+    fn get(&self, key: &str) -> Result<Option<(Revision, Vec<u8>)>>;
+
+    /// Sets `key` to `value` if its current revision is `expected_revision`,
+    /// where `None` means that `key` must not exist yet.  Returns the new
+    /// revision of `key`, or `TowerError::ConcurrentUpdate` if the revision
+    /// did not match.
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        expected_revision: Option<Revision>,
+        value: &[u8],
+    ) -> Result<Revision>;
+}
+
+/// In-process `CasKeyValueStore`, standing in for a remote store in tests
+/// and local clusters
+#[derive(Debug, Default)]
+pub struct InMemoryKeyValueStore {
+    // (latest revision of the whole store, entries keyed by key)
+    inner: Mutex<(Revision, HashMap<String, (Revision, Vec<u8>)>)>,
+}
+
+impl CasKeyValueStore for InMemoryKeyValueStore {
+    fn get(&self, key: &str) -> Result<Option<(Revision, Vec<u8>)>> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.1.get(key).cloned())
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        expected_revision: Option<Revision>,
+        value: &[u8],
+    ) -> Result<Revision> {
+        let mut inner = self.inner.lock().unwrap();
+        let current_revision = inner.1.get(key).map(|(revision, _)| *revision);
+        if current_revision != expected_revision {
+            return Err(TowerError::ConcurrentUpdate(format!(
+                "{} is at revision {:?}, expected {:?}",
+                key, current_revision, expected_revision
+            )));
+        }
+        inner.0 += 1;
+        let revision = inner.0;
+        inner.1.insert(key.to_string(), (revision, value.to_vec()));
+        Ok(revision)
+    }
+}
+
+const ETCD_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `CasKeyValueStore` backed by etcd, through the JSON gateway of its v3 API
+#[derive(Debug)]
+pub struct EtcdKeyValueStore {
+    endpoint: String,
+    client: reqwest::blocking::Client,
+}
+
+impl EtcdKeyValueStore {
+    /// `endpoint` is the client URL of an etcd member, e.g. `http://127.0.0.1:2379`
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            client: reqwest::blocking::Client::builder()
+                .timeout(ETCD_REQUEST_TIMEOUT)
+                .build()
+                .expect("build etcd client"),
+        }
+    }
+
+    fn post(&self, path: &str, request: serde_json::Value) -> Result<serde_json::Value> {
+        self.client
+            .post(&format!("{}{}", self.endpoint, path))
+            .json(&request)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(|err| TowerError::StorageError(format!("etcd {}: {}", path, err)))
+    }
+}
+
+// etcd encodes int64 fields as JSON strings, and omits fields set to zero
+fn etcd_revision(value: &serde_json::Value) -> Result<Revision> {
+    match value {
+        serde_json::Value::Null => Ok(0),
+        serde_json::Value::String(s) => s.parse().map_err(|_| {
+            TowerError::StorageError(format!("etcd returned invalid revision: {}", s))
+        }),
+        value => value.as_u64().ok_or_else(|| {
+            TowerError::StorageError(format!("etcd returned invalid revision: {}", value))
+        }),
+    }
+}
+
+impl CasKeyValueStore for EtcdKeyValueStore {
+    fn get(&self, key: &str) -> Result<Option<(Revision, Vec<u8>)>> {
+        let response = self.post("/v3/kv/range", json!({ "key": base64::encode(key) }))?;
+        let kv = match response["kvs"].get(0) {
+            Some(kv) => kv,
+            None => return Ok(None),
+        };
+        let revision = etcd_revision(&kv["mod_revision"])?;
+        let value = base64::decode(kv["value"].as_str().unwrap_or_default()).map_err(|err| {
+            TowerError::StorageError(format!("etcd returned invalid value: {}", err))
+        })?;
+        Ok(Some((revision, value)))
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        expected_revision: Option<Revision>,
+        value: &[u8],
+    ) -> Result<Revision> {
+        let key = base64::encode(key);
+        let compare = match expected_revision {
+            Some(revision) => json!({
+                "key": key,
+                "result": "EQUAL",
+                "target": "MOD",
+                "mod_revision": revision.to_string(),
+            }),
+            None => json!({
+                "key": key,
+                "result": "EQUAL",
+                "target": "CREATE",
+                "create_revision": "0",
+            }),
+        };
+        let response = self.post(
+            "/v3/kv/txn",
+            json!({
+                "compare": [compare],
+                "success": [{ "request_put": { "key": key, "value": base64::encode(value) } }],
+            }),
+        )?;
+        if response["succeeded"].as_bool() != Some(true) {
+            return Err(TowerError::ConcurrentUpdate(format!(
+                "etcd key was modified since revision {:?}",
+                expected_revision
+            )));
+        }
+        etcd_revision(&response["header"]["revision"])
+    }
+}
+
+/// Stores the signed tower in a `CasKeyValueStore`.  Every save is
+/// conditional on the tower not having been saved by anyone else since this
+/// instance last loaded or saved it, so only one instance can vote at a time.
+#[derive(Debug)]
+pub struct RemoteTowerStorage {
+    store: Arc<dyn CasKeyValueStore>,
+    key_prefix: String,
+    // Revision of each tower as last loaded or stored by this instance
+    revisions: Mutex<HashMap<Pubkey, Option<Revision>>>,
+}
+
+impl RemoteTowerStorage {
+    pub fn new(store: Arc<dyn CasKeyValueStore>, key_prefix: &str) -> Self {
+        Self {
+            store,
+            key_prefix: key_prefix.to_string(),
+            revisions: Mutex::default(),
+        }
+    }
+
+    pub fn key(&self, node_pubkey: &Pubkey) -> String {
+        format!("{}/tower-{}", self.key_prefix, node_pubkey)
+    }
+}
+
+impl TowerStorage for RemoteTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<SavedTower> {
+        let key = self.key(node_pubkey);
+        trace!("load {}", key);
+        let mut revisions = self.revisions.lock().unwrap();
+        match self.store.get(&key)? {
+            None => {
+                revisions.insert(*node_pubkey, None);
+                Err(io::Error::new(io::ErrorKind::NotFound, format!("{} not found", key)).into())
+            }
+            Some((revision, value)) => {
+                let saved_tower = bincode::deserialize(&value)?;
+                revisions.insert(*node_pubkey, Some(revision));
+                Ok(saved_tower)
+            }
+        }
+    }
+
+    fn store(&self, node_pubkey: &Pubkey, saved_tower: &SavedTower) -> Result<()> {
+        let key = self.key(node_pubkey);
+        trace!("store: {}", key);
+        let value = bincode::serialize(saved_tower)?;
+        let mut revisions = self.revisions.lock().unwrap();
+        // A tower which was never loaded must not exist in the store yet
+        let expected_revision = revisions.get(node_pubkey).cloned().flatten();
+        let revision = match self.store.compare_and_swap(&key, expected_revision, &value) {
+            Ok(revision) => revision,
+            Err(err @ TowerError::StorageError(_)) => {
+                // The update may have been applied even though its response was lost, in which
+                // case the store now holds exactly this tower.  Adopt its revision, otherwise
+                // every later save would fail with `ConcurrentUpdate`
+                match self.store.get(&key) {
+                    Ok(Some((revision, stored_value))) if stored_value == value => {
+                        warn!("Recovered from {} as {} was saved anyway", err, key);
+                        revision
+                    }
+                    _ => return Err(err),
+                }
+            }
+            Err(err) => return Err(err),
+        };
+        revisions.insert(*node_pubkey, Some(revision));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::Tower;
+    use solana_sdk::{
+        hash::Hash,
+        signature::{Keypair, Signer},
+    };
+    use tempfile::TempDir;
+
+    fn new_saved_tower(keypair: &Arc<Keypair>) -> SavedTower {
+        let tower = Tower::new_with_key(&keypair.pubkey());
+        SavedTower::new(&tower, keypair).unwrap()
+    }
+
+    #[test]
+    fn test_file_tower_storage() {
+        let dir = TempDir::new().unwrap();
+        let storage = FileTowerStorage::new(dir.path().to_path_buf());
+        let keypair = Arc::new(Keypair::new());
+        assert!(storage
+            .load(&keypair.pubkey())
+            .unwrap_err()
+            .is_file_missing());
+
+        let saved_tower = new_saved_tower(&keypair);
+        storage.store(&keypair.pubkey(), &saved_tower).unwrap();
+        assert!(storage.filename(&keypair.pubkey()).exists());
+        assert_eq!(storage.load(&keypair.pubkey()).unwrap(), saved_tower);
+    }
+
+    #[test]
+    fn test_remote_tower_storage_single_writer() {
+        let store: Arc<dyn CasKeyValueStore> = Arc::new(InMemoryKeyValueStore::default());
+        let primary = RemoteTowerStorage::new(store.clone(), "/solana");
+        let standby = RemoteTowerStorage::new(store, "/solana");
+        let keypair = Arc::new(Keypair::new());
+        let pubkey = keypair.pubkey();
+
+        // Both instances start without a tower
+        assert!(primary.load(&pubkey).unwrap_err().is_file_missing());
+        assert!(standby.load(&pubkey).unwrap_err().is_file_missing());
+
+        // The first save wins, the stale instance can no longer save
+        let saved_tower = new_saved_tower(&keypair);
+        primary.store(&pubkey, &saved_tower).unwrap();
+        primary.store(&pubkey, &saved_tower).unwrap();
+        assert!(matches!(
+            standby.store(&pubkey, &saved_tower),
+            Err(TowerError::ConcurrentUpdate(_))
+        ));
+
+        // Once the standby takes over with the latest tower, the primary
+        // can no longer save
+        assert_eq!(standby.load(&pubkey).unwrap(), saved_tower);
+        standby.store(&pubkey, &saved_tower).unwrap();
+        assert!(matches!(
+            primary.store(&pubkey, &saved_tower),
+            Err(TowerError::ConcurrentUpdate(_))
+        ));
+    }
+
+    #[test]
+    fn test_remote_tower_storage_without_load() {
+        let store: Arc<dyn CasKeyValueStore> = Arc::new(InMemoryKeyValueStore::default());
+        let keypair = Arc::new(Keypair::new());
+        let saved_tower = new_saved_tower(&keypair);
+        RemoteTowerStorage::new(store.clone(), "/solana")
+            .store(&keypair.pubkey(), &saved_tower)
+            .unwrap();
+        // An instance which never loaded the tower can't overwrite it
+        assert!(matches!(
+            RemoteTowerStorage::new(store.clone(), "/solana")
+                .store(&keypair.pubkey(), &saved_tower),
+            Err(TowerError::ConcurrentUpdate(_))
+        ));
+        // Towers are keyed by prefix
+        RemoteTowerStorage::new(store, "/other")
+            .store(&keypair.pubkey(), &saved_tower)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_remote_tower_storage_lost_response() {
+        // Fails the next `failures` updates without applying them, and loses the response of
+        // the next `lost_responses` updates that are applied
+        #[derive(Debug, Default)]
+        struct LossyKeyValueStore {
+            store: InMemoryKeyValueStore,
+            failures: Mutex<u32>,
+            lost_responses: Mutex<u32>,
+        }
+        impl CasKeyValueStore for LossyKeyValueStore {
+            fn get(&self, key: &str) -> Result<Option<(Revision, Vec<u8>)>> {
+                self.store.get(key)
+            }
+            fn compare_and_swap(
+                &self,
+                key: &str,
+                expected_revision: Option<Revision>,
+                value: &[u8],
+            ) -> Result<Revision> {
+                let mut failures = self.failures.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err(TowerError::StorageError("unavailable".to_string()));
+                }
+                let revision = self.store.compare_and_swap(key, expected_revision, value)?;
+                let mut lost_responses = self.lost_responses.lock().unwrap();
+                if *lost_responses > 0 {
+                    *lost_responses -= 1;
+                    return Err(TowerError::StorageError("timed out".to_string()));
+                }
+                Ok(revision)
+            }
+        }
+
+        let store = Arc::new(LossyKeyValueStore::default());
+        let storage = RemoteTowerStorage::new(store.clone(), "/solana");
+        let keypair = Arc::new(Keypair::new());
+        let pubkey = keypair.pubkey();
+        assert!(storage.load(&pubkey).unwrap_err().is_file_missing());
+
+        // The tower was saved, so the lost response is not an error and later saves succeed
+        *store.lost_responses.lock().unwrap() = 1;
+        let saved_tower = new_saved_tower(&keypair);
+        storage.store(&pubkey, &saved_tower).unwrap();
+        storage.store(&pubkey, &saved_tower).unwrap();
+
+        // An update that wasn't applied is still an error, and doesn't disturb the revision
+        *store.failures.lock().unwrap() = 1;
+        let mut tower = Tower::new_with_key(&pubkey);
+        tower.record_vote(1, Hash::default());
+        let new_saved_tower = SavedTower::new(&tower, &keypair).unwrap();
+        assert!(matches!(
+            storage.store(&pubkey, &new_saved_tower),
+            Err(TowerError::StorageError(_))
+        ));
+        storage.store(&pubkey, &new_saved_tower).unwrap();
+        assert_eq!(storage.load(&pubkey).unwrap(), new_saved_tower);
+    }
+}
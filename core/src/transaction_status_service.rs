@@ -48,28 +48,37 @@ impl TransactionStatusService {
         let TransactionStatusBatch {
             bank,
             transactions,
+            versioned_transactions,
             iteration_order,
             statuses,
             balances,
             inner_instructions,
             transaction_logs,
+            transaction_log_events,
+            transaction_return_data,
         } = write_transaction_status_receiver.recv_timeout(Duration::from_secs(1))?;
 
         let slot = bank.slot();
         for (
             (_, transaction),
+            (_, versioned_transaction),
             (status, hash_age_kind),
             pre_balances,
             post_balances,
             inner_instructions,
             log_messages,
+            log_events,
+            return_data,
         ) in izip!(
             OrderedIterator::new(&transactions, iteration_order.as_deref()),
+            OrderedIterator::new(&versioned_transactions, iteration_order.as_deref()),
             statuses,
             balances.pre_balances,
             balances.post_balances,
             inner_instructions,
-            transaction_logs
+            transaction_logs,
+            transaction_log_events,
+            transaction_return_data
         ) {
             if Bank::can_commit(&status) && !transaction.signatures.is_empty() {
                 let fee_calculator = hash_age_kind
@@ -95,6 +104,10 @@ impl TransactionStatusService {
                 });
 
                 let log_messages = Some(log_messages);
+                let log_events = Some(log_events);
+                let loaded_addresses = versioned_transaction
+                    .message
+                    .loaded_addresses(&transaction.message);
 
                 blockstore
                     .write_transaction_status(
@@ -109,9 +122,15 @@ impl TransactionStatusService {
                             post_balances,
                             inner_instructions,
                             log_messages,
+                            return_data,
+                            log_events,
+                            loaded_addresses,
                         },
                     )
                     .expect("Expect database write to succeed");
+                blockstore
+                    .write_transaction(slot, versioned_transaction)
+                    .expect("Expect database write to succeed");
             }
         }
         Ok(())
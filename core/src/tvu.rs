@@ -10,7 +10,8 @@ use crate::{
     cluster_slots::ClusterSlots,
     completed_data_sets_service::CompletedDataSetsSender,
     consensus::Tower,
-    ledger_cleanup_service::LedgerCleanupService,
+    duplicate_shred_listener::DuplicateShredListener,
+    ledger_cleanup_service::{LedgerCleanupService, LedgerRetentionConfig},
     optimistically_confirmed_bank_tracker::BankNotificationSender,
     poh_recorder::PohRecorder,
     replay_stage::{ReplayStage, ReplayStageConfig},
@@ -20,6 +21,7 @@ use crate::{
     shred_fetch_stage::ShredFetchStage,
     sigverify_shreds::ShredSigVerifier,
     sigverify_stage::SigVerifyStage,
+    tower_storage::TowerStorage,
 };
 use crossbeam_channel::unbounded;
 use solana_ledger::{
@@ -57,6 +59,7 @@ pub struct Tvu {
     ledger_cleanup_service: Option<LedgerCleanupService>,
     accounts_background_service: AccountsBackgroundService,
     accounts_hash_verifier: AccountsHashVerifier,
+    duplicate_shred_listener: DuplicateShredListener,
 }
 
 pub struct Sockets {
@@ -69,6 +72,7 @@ pub struct Sockets {
 #[derive(Default)]
 pub struct TvuConfig {
     pub max_ledger_shreds: Option<u64>,
+    pub ledger_retention: LedgerRetentionConfig,
     pub shred_version: u16,
     pub halt_on_trusted_validators_accounts_hash_mismatch: bool,
     pub trusted_validators: Option<HashSet<Pubkey>>,
@@ -86,7 +90,7 @@ impl Tvu {
     #[allow(clippy::new_ret_no_self, clippy::too_many_arguments)]
     pub fn new(
         vote_account: &Pubkey,
-        authorized_voter_keypairs: Vec<Arc<Keypair>>,
+        authorized_voter_keypairs: Vec<Arc<dyn Signer + Send + Sync>>,
         bank_forks: &Arc<RwLock<BankForks>>,
         cluster_info: &Arc<ClusterInfo>,
         sockets: Sockets,
@@ -95,6 +99,7 @@ impl Tvu {
         subscriptions: &Arc<RpcSubscriptions>,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
         tower: Tower,
+        tower_storage: Arc<dyn TowerStorage>,
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
         exit: &Arc<AtomicBool>,
         completed_slots_receiver: CompletedSlotsReceiver,
@@ -145,6 +150,15 @@ impl Tvu {
 
         let cluster_slots = Arc::new(ClusterSlots::default());
         let (duplicate_slots_reset_sender, duplicate_slots_reset_receiver) = unbounded();
+        let (gossip_duplicate_slots_sender, gossip_duplicate_slots_receiver) = unbounded();
+        let duplicate_shred_listener = DuplicateShredListener::new(
+            exit,
+            cluster_info.clone(),
+            blockstore.clone(),
+            bank_forks.clone(),
+            leader_schedule_cache.clone(),
+            gossip_duplicate_slots_sender,
+        );
         let retransmit_stage = RetransmitStage::new(
             bank_forks.clone(),
             leader_schedule_cache,
@@ -222,6 +236,7 @@ impl Tvu {
             rewards_recorder_sender,
             cache_block_time_sender,
             bank_notification_sender,
+            tower_storage,
         };
 
         let replay_stage = ReplayStage::new(
@@ -237,16 +252,21 @@ impl Tvu {
             retransmit_slots_sender,
             duplicate_slots_reset_receiver,
             replay_vote_sender,
+            gossip_duplicate_slots_receiver,
         );
 
-        let ledger_cleanup_service = tvu_config.max_ledger_shreds.map(|max_ledger_shreds| {
-            LedgerCleanupService::new(
-                ledger_cleanup_slot_receiver,
-                blockstore.clone(),
-                max_ledger_shreds,
-                &exit,
-            )
-        });
+        let ledger_cleanup_service =
+            if tvu_config.max_ledger_shreds.is_some() || tvu_config.ledger_retention.is_limited() {
+                Some(LedgerCleanupService::new_with_retention(
+                    ledger_cleanup_slot_receiver,
+                    blockstore.clone(),
+                    tvu_config.max_ledger_shreds,
+                    tvu_config.ledger_retention,
+                    &exit,
+                ))
+            } else {
+                None
+            };
 
         let accounts_background_service =
             AccountsBackgroundService::new(bank_forks.clone(), &exit, snapshot_request_handler);
@@ -259,6 +279,7 @@ impl Tvu {
             ledger_cleanup_service,
             accounts_background_service,
             accounts_hash_verifier,
+            duplicate_shred_listener,
         }
     }
 
@@ -272,6 +293,7 @@ impl Tvu {
         self.accounts_background_service.join()?;
         self.replay_stage.join()?;
         self.accounts_hash_verifier.join()?;
+        self.duplicate_shred_listener.join()?;
         Ok(())
     }
 }
@@ -283,6 +305,7 @@ pub mod tests {
         banking_stage::create_test_recorder,
         cluster_info::{ClusterInfo, Node},
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        tower_storage::FileTowerStorage,
     };
     use serial_test_derive::serial;
     use solana_ledger::{
@@ -356,6 +379,7 @@ pub mod tests {
             )),
             &poh_recorder,
             tower,
+            Arc::new(FileTowerStorage::default()),
             &leader_schedule_cache,
             &exit,
             completed_slots_receiver,
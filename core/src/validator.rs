@@ -9,6 +9,7 @@ use crate::{
     consensus::{reconcile_blockstore_roots_with_tower, Tower},
     contact_info::ContactInfo,
     gossip_service::GossipService,
+    ledger_cleanup_service::LedgerRetentionConfig,
     optimistically_confirmed_bank_tracker::{
         OptimisticallyConfirmedBank, OptimisticallyConfirmedBankTracker,
     },
@@ -24,6 +25,7 @@ use crate::{
     serve_repair_service::ServeRepairService,
     sigverify,
     snapshot_packager_service::SnapshotPackagerService,
+    tower_storage::{FileTowerStorage, TowerStorage},
     tpu::Tpu,
     transaction_status_service::TransactionStatusService,
     tvu::{Sockets, Tvu, TvuConfig},
@@ -57,6 +59,7 @@ use solana_sdk::{
     signature::{Keypair, Signer},
     timing::timestamp,
 };
+use solana_transaction_status::parse_instruction::register_event_layouts_from_file;
 use solana_vote_program::vote_state::VoteState;
 use std::time::Instant;
 use std::{
@@ -86,6 +89,7 @@ pub struct ValidatorConfig {
     pub pubsub_config: PubSubConfig,
     pub snapshot_config: Option<SnapshotConfig>,
     pub max_ledger_shreds: Option<u64>,
+    pub ledger_retention: LedgerRetentionConfig,
     pub broadcast_stage_type: BroadcastStageType,
     pub enable_partition: Option<Arc<AtomicBool>>,
     pub fixed_leader_schedule: Option<FixedSchedule>,
@@ -104,6 +108,7 @@ pub struct ValidatorConfig {
     pub poh_verify: bool, // Perform PoH verification during blockstore processing at boo
     pub cuda: bool,
     pub require_tower: bool,
+    pub tower_storage: Option<Arc<dyn TowerStorage>>, // None = tower file in the ledger directory
     pub debug_keys: Option<Arc<HashSet<Pubkey>>>,
     pub contact_debug_interval: u64,
 }
@@ -117,6 +122,7 @@ impl Default for ValidatorConfig {
             expected_shred_version: None,
             voting_disabled: false,
             max_ledger_shreds: None,
+            ledger_retention: LedgerRetentionConfig::default(),
             account_paths: Vec::new(),
             rpc_config: JsonRpcConfig::default(),
             rpc_addrs: None,
@@ -140,6 +146,7 @@ impl Default for ValidatorConfig {
             poh_verify: true,
             cuda: false,
             require_tower: false,
+            tower_storage: None,
             debug_keys: None,
             contact_debug_interval: DEFAULT_CONTACT_DEBUG_INTERVAL,
         }
@@ -205,7 +212,7 @@ impl Validator {
         identity_keypair: &Arc<Keypair>,
         ledger_path: &Path,
         vote_account: &Pubkey,
-        mut authorized_voter_keypairs: Vec<Arc<Keypair>>,
+        mut authorized_voter_keypairs: Vec<Arc<dyn Signer + Send + Sync>>,
         cluster_entrypoint: Option<&ContactInfo>,
         config: &ValidatorConfig,
     ) -> Self {
@@ -267,6 +274,11 @@ impl Validator {
         validator_exit.register_exit(Box::new(move || exit_.store(true, Ordering::Relaxed)));
         let validator_exit = Arc::new(RwLock::new(Some(validator_exit)));
 
+        let tower_storage = config
+            .tower_storage
+            .clone()
+            .unwrap_or_else(|| Arc::new(FileTowerStorage::new(ledger_path.to_path_buf())));
+
         let (replay_vote_sender, replay_vote_receiver) = unbounded();
         let (
             genesis_config,
@@ -290,6 +302,7 @@ impl Validator {
             vote_account,
             config,
             ledger_path,
+            tower_storage.as_ref(),
             config.poh_verify,
             &exit,
         );
@@ -405,6 +418,12 @@ impl Validator {
                     assert!(!ContactInfo::is_valid_address(&node.info.rpc_pubsub));
                     assert!(!ContactInfo::is_valid_address(&node.info.rpc_banks));
                 }
+                if let Some(event_layouts_path) = &config.rpc_config.event_layouts_path {
+                    register_event_layouts_from_file(event_layouts_path).unwrap_or_else(|err| {
+                        error!("Failed to load event layouts: {}", err);
+                        process::exit(1);
+                    });
+                }
                 let tpu_address = cluster_info.my_contact_info().tpu;
                 let (bank_notification_sender, bank_notification_receiver) = unbounded();
                 (
@@ -554,6 +573,7 @@ impl Validator {
             &subscriptions,
             &poh_recorder,
             tower,
+            tower_storage,
             &leader_schedule_cache,
             &exit,
             completed_slots_receiver,
@@ -571,6 +591,7 @@ impl Validator {
             bank_notification_sender.clone(),
             TvuConfig {
                 max_ledger_shreds: config.max_ledger_shreds,
+                ledger_retention: config.ledger_retention.clone(),
                 halt_on_trusted_validators_accounts_hash_mismatch: config
                     .halt_on_trusted_validators_accounts_hash_mismatch,
                 shred_version: node.info.shred_version,
@@ -720,7 +741,6 @@ fn post_process_restored_tower(
     validator_identity: &Pubkey,
     vote_account: &Pubkey,
     config: &ValidatorConfig,
-    ledger_path: &Path,
     bank_forks: &BankForks,
 ) -> Tower {
     let mut should_require_tower = config.require_tower;
@@ -792,7 +812,6 @@ fn post_process_restored_tower(
 
             Tower::new_from_bankforks(
                 &bank_forks,
-                &ledger_path,
                 &validator_identity,
                 &vote_account,
             )
@@ -805,6 +824,7 @@ fn new_banks_from_ledger(
     vote_account: &Pubkey,
     config: &ValidatorConfig,
     ledger_path: &Path,
+    tower_storage: &dyn TowerStorage,
     poh_verify: bool,
     exit: &Arc<AtomicBool>,
 ) -> (
@@ -847,8 +867,9 @@ fn new_banks_from_ledger(
     } = Blockstore::open_with_signal(ledger_path, config.wal_recovery_mode.clone())
         .expect("Failed to open ledger database");
     blockstore.set_no_compaction(config.no_rocksdb_compaction);
+    blockstore.set_retain_transactions(config.ledger_retention.transaction_status.is_some());
 
-    let restored_tower = Tower::restore(ledger_path, &validator_identity);
+    let restored_tower = Tower::restore(tower_storage, &validator_identity);
     if let Ok(tower) = &restored_tower {
         reconcile_blockstore_roots_with_tower(&tower, &blockstore).unwrap_or_else(|err| {
             error!("Failed to reconcile blockstore with tower: {:?}", err);
@@ -893,7 +914,6 @@ fn new_banks_from_ledger(
         &validator_identity,
         &vote_account,
         &config,
-        &ledger_path,
         &bank_forks,
     );
 
@@ -0,0 +1,224 @@
+//! The `voting_service` persists the tower and signs and sends the replay stage's votes.  All of
+//! these may block on the network, so they're kept off the replay thread.
+
+use crate::{
+    cluster_info::ClusterInfo,
+    consensus::{Tower, TowerError},
+    tower_storage::TowerStorage,
+};
+use solana_metrics::inc_new_counter_info;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+    sync::Arc,
+    thread::{self, Builder, JoinHandle},
+    time::Duration,
+};
+
+const MAX_TOWER_SAVE_ATTEMPTS: u32 = 5;
+const TOWER_SAVE_RETRY_DELAY_MS: u64 = 50;
+
+pub struct VoteOp {
+    tower: Tower,
+    // The vote transaction, signed by the node but still missing the signature of the
+    // authorized voter
+    vote_tx: Option<(Transaction, Arc<dyn Signer + Send + Sync>)>,
+    tower_index: usize,
+}
+
+impl VoteOp {
+    pub fn new(
+        tower: Tower,
+        vote_tx: Option<(Transaction, Arc<dyn Signer + Send + Sync>)>,
+        tower_index: usize,
+    ) -> Self {
+        Self {
+            tower,
+            vote_tx,
+            tower_index,
+        }
+    }
+}
+
+pub struct VotingService {
+    t_voting: JoinHandle<()>,
+}
+
+impl VotingService {
+    pub fn new(
+        exit: &Arc<AtomicBool>,
+        cluster_info: Arc<ClusterInfo>,
+        tower_storage: Arc<dyn TowerStorage>,
+    ) -> (Sender<VoteOp>, Self) {
+        let (sender, receiver): (Sender<VoteOp>, Receiver<VoteOp>) = channel();
+        let exit_ = exit.clone();
+        (
+            sender,
+            Self {
+                t_voting: Builder::new()
+                    .name("solana-voting".to_string())
+                    .spawn(move || loop {
+                        if exit_.load(Ordering::Relaxed) {
+                            break;
+                        }
+
+                        if let Err(RecvTimeoutError::Disconnected) =
+                            Self::run(&receiver, &cluster_info, tower_storage.as_ref())
+                        {
+                            break;
+                        }
+                    })
+                    .unwrap(),
+            },
+        )
+    }
+
+    fn run(
+        receiver: &Receiver<VoteOp>,
+        cluster_info: &ClusterInfo,
+        tower_storage: &dyn TowerStorage,
+    ) -> Result<(), RecvTimeoutError> {
+        let mut vote_op = receiver.recv_timeout(Duration::from_secs(1))?;
+
+        // Each tower contains all of the votes of the ones before it, so if we've fallen behind
+        // only the latest needs to be saved and sent
+        while let Ok(new_vote_op) = receiver.try_recv() {
+            vote_op = new_vote_op;
+        }
+
+        let VoteOp {
+            tower,
+            vote_tx,
+            tower_index,
+        } = vote_op;
+        if !Self::save_tower(&tower, tower_storage, &cluster_info.keypair) {
+            // Never vote on a tower that isn't persisted
+            inc_new_counter_info!("voting_service-tower_save_failed", 1);
+            return Ok(());
+        }
+
+        if let Some((mut vote_tx, authorized_voter)) = vote_tx {
+            // The authorized voter may be a remote signer, which can fail to sign
+            let blockhash = vote_tx.message.recent_blockhash;
+            if let Err(err) =
+                vote_tx.try_partial_sign(&[authorized_voter.as_ref() as &dyn Signer], blockhash)
+            {
+                warn!(
+                    "The authorized voter {} failed to sign: {}.  Unable to vote",
+                    authorized_voter.pubkey(),
+                    err
+                );
+                return Ok(());
+            }
+            let _ = cluster_info.send_vote(&vote_tx);
+            cluster_info.push_vote(tower_index, vote_tx);
+        }
+        Ok(())
+    }
+
+    /// Persist `tower`, retrying with exponential backoff in case the storage is temporarily
+    /// unavailable.  Returns whether the tower was saved
+    fn save_tower(
+        tower: &Tower,
+        tower_storage: &dyn TowerStorage,
+        node_keypair: &Arc<Keypair>,
+    ) -> bool {
+        for attempt in 0..MAX_TOWER_SAVE_ATTEMPTS {
+            match tower.save(tower_storage, node_keypair) {
+                Ok(()) => return true,
+                Err(err @ TowerError::ConcurrentUpdate(_))
+                | Err(err @ TowerError::WrongTower(_)) => {
+                    // Retrying won't help, another instance owns the tower
+                    error!("Unable to save tower, refusing to vote: {}", err);
+                    return false;
+                }
+                Err(err) => {
+                    warn!(
+                        "Unable to save tower (attempt {}/{}): {}",
+                        attempt + 1,
+                        MAX_TOWER_SAVE_ATTEMPTS,
+                        err
+                    );
+                    if attempt + 1 < MAX_TOWER_SAVE_ATTEMPTS {
+                        thread::sleep(Duration::from_millis(TOWER_SAVE_RETRY_DELAY_MS << attempt));
+                    }
+                }
+            }
+        }
+        error!(
+            "Unable to save tower after {} attempts, refusing to vote",
+            MAX_TOWER_SAVE_ATTEMPTS
+        );
+        false
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.t_voting.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::SavedTower;
+    use solana_sdk::pubkey::Pubkey;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_save_tower_retries() {
+        // Fails the next `failures` saves with `error`
+        #[derive(Debug)]
+        struct FlakyTowerStorage {
+            failures: Mutex<u32>,
+            error: fn() -> TowerError,
+        }
+        impl TowerStorage for FlakyTowerStorage {
+            fn load(&self, _node_pubkey: &Pubkey) -> crate::consensus::Result<SavedTower> {
+                Err(TowerError::StorageError("load not supported".to_string()))
+            }
+            fn store(
+                &self,
+                _node_pubkey: &Pubkey,
+                _saved_tower: &SavedTower,
+            ) -> crate::consensus::Result<()> {
+                let mut failures = self.failures.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err((self.error)());
+                }
+                Ok(())
+            }
+        }
+        let storage_error = || TowerError::StorageError("unavailable".to_string());
+
+        let keypair = Arc::new(Keypair::new());
+        let tower = Tower::new_with_key(&keypair.pubkey());
+
+        // Transient failures are retried
+        let tower_storage = FlakyTowerStorage {
+            failures: Mutex::new(MAX_TOWER_SAVE_ATTEMPTS - 1),
+            error: storage_error,
+        };
+        assert!(VotingService::save_tower(&tower, &tower_storage, &keypair));
+        assert_eq!(*tower_storage.failures.lock().unwrap(), 0);
+
+        // ...but not forever
+        let tower_storage = FlakyTowerStorage {
+            failures: Mutex::new(MAX_TOWER_SAVE_ATTEMPTS),
+            error: storage_error,
+        };
+        assert!(!VotingService::save_tower(&tower, &tower_storage, &keypair));
+
+        // A tower saved by another instance is not retried
+        let tower_storage = FlakyTowerStorage {
+            failures: Mutex::new(1),
+            error: || TowerError::ConcurrentUpdate("conflict".to_string()),
+        };
+        assert!(!VotingService::save_tower(&tower, &tower_storage, &keypair));
+        assert_eq!(*tower_storage.failures.lock().unwrap(), 0);
+    }
+}
@@ -83,7 +83,9 @@ pub fn should_retransmit_and_persist(
 }
 
 fn run_check_duplicate(
-    blockstore: &Arc<Blockstore>,
+    cluster_info: &ClusterInfo,
+    blockstore: &Blockstore,
+    leader_schedule_cache: &LeaderScheduleCache,
     shred_receiver: &CrossbeamReceiver<Shred>,
 ) -> Result<()> {
     let check_duplicate = |shred: Shred| -> Result<()> {
@@ -91,6 +93,19 @@ fn run_check_duplicate(
             if let Some(existing_shred_payload) =
                 blockstore.is_shred_duplicate(shred.slot(), shred.index(), &shred.payload)
             {
+                // Gossip the proof so that the rest of the cluster can stop
+                // building on top of this slot as well.
+                if let Err(err) =
+                    cluster_info.push_duplicate_shred(&shred, &existing_shred_payload, |slot| {
+                        leader_schedule_cache.slot_leader_at(slot, None)
+                    })
+                {
+                    warn!(
+                        "unable to push duplicate shred proof for slot {}: {:?}",
+                        shred.slot(),
+                        err
+                    );
+                }
                 blockstore.store_duplicate_slot(
                     shred.slot(),
                     existing_shred_payload,
@@ -336,8 +351,13 @@ impl WindowService {
         let (insert_sender, insert_receiver) = unbounded();
         let (duplicate_sender, duplicate_receiver) = unbounded();
 
-        let t_check_duplicate =
-            Self::start_check_duplicate_thread(exit, &blockstore, duplicate_receiver);
+        let t_check_duplicate = Self::start_check_duplicate_thread(
+            cluster_info.clone(),
+            exit,
+            &blockstore,
+            leader_schedule_cache,
+            duplicate_receiver,
+        );
 
         let t_insert = Self::start_window_insert_thread(
             exit,
@@ -368,12 +388,15 @@ impl WindowService {
     }
 
     fn start_check_duplicate_thread(
+        cluster_info: Arc<ClusterInfo>,
         exit: &Arc<AtomicBool>,
         blockstore: &Arc<Blockstore>,
+        leader_schedule_cache: &Arc<LeaderScheduleCache>,
         duplicate_receiver: CrossbeamReceiver<Shred>,
     ) -> JoinHandle<()> {
         let exit = exit.clone();
         let blockstore = blockstore.clone();
+        let leader_schedule_cache = leader_schedule_cache.clone();
         let handle_error = || {
             inc_new_counter_error!("solana-check-duplicate-error", 1, 1);
         };
@@ -385,7 +408,12 @@ impl WindowService {
                 }
 
                 let mut noop = || {};
-                if let Err(e) = run_check_duplicate(&blockstore, &duplicate_receiver) {
+                if let Err(e) = run_check_duplicate(
+                    &cluster_info,
+                    &blockstore,
+                    &leader_schedule_cache,
+                    &duplicate_receiver,
+                ) {
                     if Self::should_exit_on_error(e, &mut noop, &handle_error) {
                         break;
                     }
@@ -548,6 +576,7 @@ impl WindowService {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::contact_info::ContactInfo;
     use solana_ledger::{
         blockstore::{make_many_slot_entries, Blockstore},
         entry::{create_ticks, Entry},
@@ -560,6 +589,7 @@ mod test {
         epoch_schedule::MINIMUM_SLOTS_PER_EPOCH,
         hash::Hash,
         signature::{Keypair, Signer},
+        timing::timestamp,
     };
     use std::sync::Arc;
 
@@ -678,7 +708,17 @@ mod test {
         let duplicate_shred_slot = duplicate_shred.slot();
         sender.send(duplicate_shred).unwrap();
         assert!(!blockstore.has_duplicate_shreds_in_slot(duplicate_shred_slot));
-        run_check_duplicate(&blockstore, &receiver).unwrap();
+        let keypair = Keypair::new();
+        let contact_info = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
+        let cluster_info = ClusterInfo::new(contact_info, Arc::new(keypair));
+        let leader_schedule_cache = LeaderScheduleCache::default();
+        run_check_duplicate(
+            &cluster_info,
+            &blockstore,
+            &leader_schedule_cache,
+            &receiver,
+        )
+        .unwrap();
         assert!(blockstore.has_duplicate_shreds_in_slot(duplicate_shred_slot));
     }
 }
@@ -4,16 +4,28 @@ use log::*;
 use solana_runtime::{bank_forks::CompressionType, snapshot_utils};
 use solana_sdk::clock::Slot;
 use solana_sdk::hash::Hash;
-use std::fs::{self, File};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::io::Read;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, Builder};
+use std::time::{Duration, Instant};
 
 static TRUCK: Emoji = Emoji("🚚 ", "");
 static SPARKLE: Emoji = Emoji("✨ ", "");
 
+/// Default size of each byte range fetched when downloading a file from multiple peers at once
+pub const DOWNLOAD_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Number of times a single chunk is retried, across all peers, before giving up
+const MAX_CHUNK_DOWNLOAD_ATTEMPTS: usize = 5;
+
+/// Number of consecutive chunk failures after which a peer is no longer used
+const MAX_PEER_CONSECUTIVE_FAILURES: usize = 3;
+
 /// Creates a new process bar for processing that will take an unknown amount of time
 fn new_spinner_progress_bar() -> ProgressBar {
     let progress_bar = ProgressBar::new(42);
@@ -35,33 +47,61 @@ pub fn download_file(
 
     fs::create_dir_all(destination_file.parent().unwrap()).map_err(|err| err.to_string())?;
 
-    let temp_destination_file = destination_file.with_extension(".tmp");
+    let temp_destination_file = temp_file_of(destination_file);
 
     let progress_bar = new_spinner_progress_bar();
     if use_progress_bar {
         progress_bar.set_message(&format!("{}Downloading {}...", TRUCK, url));
     }
 
-    let response = reqwest::blocking::Client::new()
-        .get(url)
-        .send()
-        .and_then(|response| response.error_for_status())
-        .map_err(|err| {
+    // Pick up where a previously interrupted download of this file left off
+    let mut resume_offset = fs::metadata(&temp_destination_file)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+
+    let client = reqwest::blocking::Client::new();
+    let response = loop {
+        let mut request = client.get(url);
+        if resume_offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_offset));
+        }
+        let response = request.send().map_err(|err| {
+            progress_bar.finish_and_clear();
+            err.to_string()
+        })?;
+        if resume_offset > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            // The partial download is at least as large as the file being served, so it
+            // isn't a prefix of it
+            warn!(
+                "Discarding partial download {:?} of {}",
+                temp_destination_file, url
+            );
+            fs::remove_file(&temp_destination_file).map_err(|err| {
+                progress_bar.finish_and_clear();
+                format!("Unable to remove {:?}: {:?}", temp_destination_file, err)
+            })?;
+            resume_offset = 0;
+            continue;
+        }
+        break response.error_for_status().map_err(|err| {
             progress_bar.finish_and_clear();
             err.to_string()
         })?;
+    };
 
-    let download_size = {
-        response
-            .headers()
-            .get(reqwest::header::CONTENT_LENGTH)
-            .and_then(|content_length| content_length.to_str().ok())
-            .and_then(|content_length| content_length.parse().ok())
-            .unwrap_or(0)
+    // A server that ignores the range request sends the whole file again
+    let resume_offset = if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+        info!("Resuming download of {} at byte {}", url, resume_offset);
+        resume_offset
+    } else {
+        0
     };
 
+    let download_size = resume_offset + content_length(&response).unwrap_or(0);
+
     if use_progress_bar {
         progress_bar.set_length(download_size);
+        progress_bar.set_position(resume_offset);
         progress_bar.set_style(
             ProgressStyle::default_bar()
                 .template(&format!(
@@ -113,12 +153,17 @@ pub fn download_file(
         progress_bar,
         response,
         last_print: Instant::now(),
-        current_bytes: 0,
+        current_bytes: resume_offset as usize,
         download_size: (download_size as f32).max(1f32),
         use_progress_bar,
     };
 
-    File::create(&temp_destination_file)
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resume_offset > 0)
+        .truncate(resume_offset == 0)
+        .open(&temp_destination_file)
         .and_then(|mut file| std::io::copy(&mut source, &mut file))
         .map_err(|err| format!("Unable to write {:?}: {:?}", temp_destination_file, err))?;
 
@@ -140,6 +185,371 @@ pub fn download_file(
     Ok(())
 }
 
+fn content_length(response: &reqwest::blocking::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|content_length| content_length.to_str().ok())
+        .and_then(|content_length| content_length.parse().ok())
+}
+
+/// Returns the total size of the file at `url` if the server supports range requests for it
+fn probe_range_support(client: &reqwest::blocking::Client, url: &str) -> Option<u64> {
+    let response = client
+        .get(url)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .send()
+        .and_then(|response| response.error_for_status())
+        .ok()?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return None;
+    }
+
+    // Content-Range: bytes 0-0/<total size>
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|content_range| content_range.to_str().ok())
+        .and_then(|content_range| content_range.rsplit('/').next())
+        .and_then(|total_size| total_size.parse().ok())
+}
+
+fn temp_file_of(destination_file: &Path) -> PathBuf {
+    let mut temp_file = destination_file.as_os_str().to_owned();
+    temp_file.push(".tmp");
+    PathBuf::from(temp_file)
+}
+
+fn chunks_dir_of(destination_file: &Path) -> PathBuf {
+    let mut chunks_dir = destination_file.as_os_str().to_owned();
+    chunks_dir.push(".chunks");
+    PathBuf::from(chunks_dir)
+}
+
+fn chunk_path(chunks_dir: &Path, chunk: u64) -> PathBuf {
+    chunks_dir.join(format!("chunk-{}", chunk))
+}
+
+fn chunks_manifest_path(chunks_dir: &Path) -> PathBuf {
+    chunks_dir.join("manifest")
+}
+
+// Identifies the download that the chunks in a chunks directory belong to
+fn chunks_manifest(chunk_size: u64, download_size: u64, file_hash: &Hash) -> String {
+    format!("{} {} {}\n", chunk_size, download_size, file_hash)
+}
+
+// Chunks shared by the peer download threads of `download_file_from_peers`
+struct ChunkQueue {
+    // Chunks waiting for a peer, with the number of failed attempts at each
+    pending: VecDeque<(u64, usize)>,
+    // Chunks currently being fetched, any of which may still fail and return to `pending`
+    in_flight: usize,
+    failed: Option<String>,
+}
+
+/// Fetch the byte range of `chunk` into its own file under `chunks_dir`, resuming from
+/// whatever a previous attempt already wrote
+fn download_chunk(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    chunks_dir: &Path,
+    chunk: u64,
+    chunk_size: u64,
+    download_size: u64,
+    progress_bar: &ProgressBar,
+) -> Result<(), String> {
+    let start = chunk * chunk_size;
+    let end = (start + chunk_size).min(download_size);
+    let chunk_file = chunk_path(chunks_dir, chunk);
+    let mut downloaded = fs::metadata(&chunk_file)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    if downloaded > end - start {
+        // Stitching this in would shift every later byte of the file, so fetch it again
+        warn!(
+            "Discarding chunk {} of {} bytes, larger than its {} byte range",
+            chunk,
+            downloaded,
+            end - start
+        );
+        fs::remove_file(&chunk_file)
+            .map_err(|err| format!("Unable to remove {:?}: {:?}", chunk_file, err))?;
+        progress_bar.set_position(progress_bar.position().saturating_sub(downloaded));
+        downloaded = 0;
+    }
+    if start + downloaded == end {
+        return Ok(());
+    }
+
+    let mut response = client
+        .get(url)
+        .header(
+            reqwest::header::RANGE,
+            format!("bytes={}-{}", start + downloaded, end - 1),
+        )
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(format!("{} does not support range requests", url));
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&chunk_file)
+        .map_err(|err| format!("Unable to open {:?}: {:?}", chunk_file, err))?;
+    let written = io::copy(&mut response, &mut file)
+        .map_err(|err| format!("Unable to write {:?}: {:?}", chunk_file, err))?;
+    progress_bar.inc(written);
+
+    if start + downloaded + written != end {
+        return Err(format!(
+            "Short read of chunk {} from {}: expected {} bytes, received {}",
+            chunk,
+            url,
+            end - start - downloaded,
+            written
+        ));
+    }
+    Ok(())
+}
+
+/// Download a file that is served identically by every url in `urls`.
+///
+/// The file is split into `chunk_size` byte ranges which are fetched in parallel,
+/// one thread per peer.  A failed chunk is handed to another peer, and completed chunks are
+/// kept on disk so an interrupted download resumes where it left off.  A peer is dropped
+/// after `MAX_PEER_CONSECUTIVE_FAILURES` failed chunks in a row.  Peers that don't
+/// support range requests, or that disagree on the file size, are not used.  If no peer
+/// supports range requests the file is fetched with `download_file()` from the first url
+/// that works.
+///
+/// `file_hash` identifies the file being downloaded.  Chunks left behind by an earlier
+/// download are only reused if they were fetched for the same `file_hash` and file size, with
+/// the same `chunk_size`.
+pub fn download_file_from_peers(
+    urls: &[String],
+    destination_file: &Path,
+    file_hash: &Hash,
+    chunk_size: u64,
+    use_progress_bar: bool,
+) -> Result<(), String> {
+    if destination_file.is_file() {
+        return Err(format!("{:?} already exists", destination_file));
+    }
+    let download_start = Instant::now();
+
+    let client = reqwest::blocking::Client::new();
+    let mut peers_by_size: HashMap<u64, Vec<String>> = HashMap::new();
+    for url in urls {
+        if let Some(download_size) = probe_range_support(&client, url) {
+            peers_by_size
+                .entry(download_size)
+                .or_default()
+                .push(url.clone());
+        }
+    }
+    let (download_size, peers) = match peers_by_size
+        .into_iter()
+        .max_by_key(|(_download_size, peers)| peers.len())
+    {
+        Some((download_size, peers)) if download_size > 0 => (download_size, peers),
+        _ => {
+            let mut result = Err("No peers to download from".to_string());
+            for url in urls {
+                result = download_file(url, destination_file, use_progress_bar);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            return result;
+        }
+    };
+
+    fs::create_dir_all(destination_file.parent().unwrap()).map_err(|err| err.to_string())?;
+    let chunks_dir = chunks_dir_of(destination_file);
+    let manifest = chunks_manifest(chunk_size, download_size, file_hash);
+    if chunks_dir.exists()
+        && fs::read_to_string(chunks_manifest_path(&chunks_dir)).ok() != Some(manifest.clone())
+    {
+        // Stitching these chunks together with new ones would silently corrupt the file
+        info!(
+            "Discarding partial download {:?} of a different file or chunk size",
+            chunks_dir
+        );
+        fs::remove_dir_all(&chunks_dir).map_err(|err| err.to_string())?;
+    }
+    fs::create_dir_all(&chunks_dir).map_err(|err| err.to_string())?;
+    fs::write(chunks_manifest_path(&chunks_dir), &manifest).map_err(|err| err.to_string())?;
+
+    let num_chunks = (download_size + chunk_size - 1) / chunk_size;
+    let already_downloaded: u64 = (0..num_chunks)
+        .filter_map(|chunk| fs::metadata(chunk_path(&chunks_dir, chunk)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    let progress_bar = if use_progress_bar {
+        let progress_bar = ProgressBar::new(download_size);
+        progress_bar.set_position(already_downloaded);
+        progress_bar.set_style(
+            ProgressStyle::default_bar()
+                .template(&format!(
+                    "{}{}Downloading {:?} from {} peers {}",
+                    "{spinner:.green} ",
+                    TRUCK,
+                    destination_file.file_name().unwrap(),
+                    peers.len(),
+                    "[{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})"
+                ))
+                .progress_chars("=> "),
+        );
+        progress_bar.enable_steady_tick(100);
+        progress_bar
+    } else {
+        info!(
+            "Downloading {} bytes in {} chunks from {} peers: {:?}",
+            download_size,
+            num_chunks,
+            peers.len(),
+            peers
+        );
+        ProgressBar::hidden()
+    };
+
+    let chunk_queue = Arc::new((
+        Mutex::new(ChunkQueue {
+            pending: (0..num_chunks).map(|chunk| (chunk, 0)).collect(),
+            in_flight: 0,
+            failed: None,
+        }),
+        Condvar::new(),
+    ));
+
+    let threads: Vec<_> = peers
+        .iter()
+        .enumerate()
+        .map(|(i, url)| {
+            let client = client.clone();
+            let url = url.clone();
+            let chunks_dir = chunks_dir.clone();
+            let chunk_queue = chunk_queue.clone();
+            let progress_bar = progress_bar.clone();
+            Builder::new()
+                .name(format!("solDownload{:02}", i))
+                .spawn(move || {
+                    let (queue, chunk_done) = &*chunk_queue;
+                    let mut consecutive_failures = 0;
+                    loop {
+                        let (chunk, attempts) = {
+                            let mut queue = queue.lock().unwrap();
+                            loop {
+                                if queue.failed.is_some() {
+                                    return;
+                                }
+                                if let Some(pending_chunk) = queue.pending.pop_front() {
+                                    queue.in_flight += 1;
+                                    break pending_chunk;
+                                }
+                                if queue.in_flight == 0 {
+                                    return;
+                                }
+                                // Another peer may yet fail and hand its chunk back
+                                queue = chunk_done.wait(queue).unwrap();
+                            }
+                        };
+                        let result = download_chunk(
+                            &client,
+                            &url,
+                            &chunks_dir,
+                            chunk,
+                            chunk_size,
+                            download_size,
+                            &progress_bar,
+                        );
+
+                        let mut queue = queue.lock().unwrap();
+                        queue.in_flight -= 1;
+                        chunk_done.notify_all();
+                        if let Err(err) = result {
+                            warn!("Failed to download chunk {} from {}: {}", chunk, url, err);
+                            if attempts + 1 >= MAX_CHUNK_DOWNLOAD_ATTEMPTS {
+                                queue.failed = Some(err);
+                                return;
+                            }
+                            // Put the chunk back for whichever peer is free next
+                            queue.pending.push_back((chunk, attempts + 1));
+                            drop(queue);
+
+                            consecutive_failures += 1;
+                            if consecutive_failures >= MAX_PEER_CONSECUTIVE_FAILURES {
+                                warn!(
+                                    "Giving up on {} after {} failures",
+                                    url, consecutive_failures
+                                );
+                                return;
+                            }
+                            thread::sleep(Duration::from_secs(consecutive_failures as u64));
+                            continue;
+                        }
+                        consecutive_failures = 0;
+                        if progress_bar.is_hidden() {
+                            info!("Downloaded chunk {}/{} from {}", chunk + 1, num_chunks, url);
+                        }
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    progress_bar.finish_and_clear();
+
+    let mut queue = chunk_queue.0.lock().unwrap();
+    if let Some(err) = queue.failed.take() {
+        return Err(err);
+    }
+    if !queue.pending.is_empty() {
+        return Err(format!(
+            "Unable to download {:?}: all peers failed",
+            destination_file
+        ));
+    }
+    drop(queue);
+
+    // Stitch the chunks back together
+    let temp_destination_file = temp_file_of(destination_file);
+    File::create(&temp_destination_file)
+        .and_then(|mut file| {
+            for chunk in 0..num_chunks {
+                let mut chunk_file = File::open(chunk_path(&chunks_dir, chunk))?;
+                io::copy(&mut chunk_file, &mut file)?;
+            }
+            Ok(())
+        })
+        .map_err(|err| format!("Unable to write {:?}: {:?}", temp_destination_file, err))?;
+
+    info!(
+        "  {}{}",
+        SPARKLE,
+        format!(
+            "Downloaded {:?} ({} bytes) from {} peers in {:?}",
+            destination_file,
+            download_size,
+            peers.len(),
+            Instant::now().duration_since(download_start),
+        )
+    );
+
+    std::fs::rename(temp_destination_file, destination_file)
+        .map_err(|err| format!("Unable to rename: {:?}", err))?;
+    let _ignored = fs::remove_dir_all(&chunks_dir);
+
+    Ok(())
+}
+
 pub fn download_genesis_if_missing(
     rpc_addr: &SocketAddr,
     genesis_package: &Path,
@@ -162,8 +572,33 @@ pub fn download_genesis_if_missing(
     }
 }
 
+/// Remove the temporary files and chunk directories of interrupted snapshot downloads, other
+/// than those of snapshots whose file names start with `keep_prefix`
+fn remove_partial_snapshot_downloads(ledger_path: &Path, keep_prefix: Option<&str>) {
+    if let Ok(entries) = fs::read_dir(ledger_path) {
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+            if !file_name.starts_with("snapshot-")
+                || keep_prefix.map_or(false, |prefix| file_name.starts_with(prefix))
+            {
+                continue;
+            }
+            if path.is_dir() && file_name.ends_with(".chunks") {
+                info!("Removing partial snapshot download: {:?}", path);
+                let _ignored = fs::remove_dir_all(&path);
+            } else if path.is_file() && file_name.ends_with(".tmp") {
+                info!("Removing partial snapshot download: {:?}", path);
+                let _ignored = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+/// Download the snapshot archive for `desired_snapshot_hash` from the given RPC peers, all
+/// of which are expected to be advertising that snapshot hash in gossip.  The downloaded
+/// archive is verified against `desired_snapshot_hash` and removed if it doesn't match.
 pub fn download_snapshot(
-    rpc_addr: &SocketAddr,
+    rpc_addrs: &[SocketAddr],
     ledger_path: &Path,
     desired_snapshot_hash: (Slot, Hash),
     use_progress_bar: bool,
@@ -182,7 +617,15 @@ pub fn download_snapshot(
         }
     }
 
+    // Remove partial downloads of any other snapshot
+    let desired_snapshot_prefix = format!(
+        "snapshot-{}-{}.",
+        desired_snapshot_hash.0, desired_snapshot_hash.1
+    );
+    remove_partial_snapshot_downloads(ledger_path, Some(&desired_snapshot_prefix));
+
     if found_package {
+        remove_partial_snapshot_downloads(ledger_path, None);
         Ok(())
     } else {
         for compression in &[
@@ -195,25 +638,348 @@ pub fn download_snapshot(
                 &desired_snapshot_hash,
                 compression,
             );
+            let urls: Vec<_> = rpc_addrs
+                .iter()
+                .map(|rpc_addr| {
+                    format!(
+                        "http://{}/{}",
+                        rpc_addr,
+                        desired_snapshot_package
+                            .file_name()
+                            .unwrap()
+                            .to_str()
+                            .unwrap()
+                    )
+                })
+                .collect();
 
-            if download_file(
-                &format!(
-                    "http://{}/{}",
-                    rpc_addr,
-                    desired_snapshot_package
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap()
-                ),
+            if download_file_from_peers(
+                &urls,
                 &desired_snapshot_package,
+                &desired_snapshot_hash.1,
+                DOWNLOAD_CHUNK_SIZE,
                 use_progress_bar,
             )
             .is_ok()
             {
-                return Ok(());
+                // Partial downloads in the other compression formats are no longer needed
+                remove_partial_snapshot_downloads(ledger_path, None);
+
+                // Check the archive really is the snapshot the peers advertised before it's
+                // ever unpacked
+                return snapshot_utils::verify_snapshot_archive_hash(
+                    &desired_snapshot_package,
+                    compression.clone(),
+                    &desired_snapshot_hash,
+                )
+                .map_err(|err| {
+                    let _ignored = fs::remove_file(&desired_snapshot_package);
+                    format!(
+                        "Downloaded snapshot {:?} failed to verify: {}",
+                        desired_snapshot_package, err
+                    )
+                });
             }
         }
         Err("Snapshot couldn't be downloaded".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn serve(data: Arc<Vec<u8>>, support_ranges: bool) -> String {
+        serve_flaky(data, support_ranges, 0)
+    }
+
+    // Minimal HTTP/1.1 file server honoring single `Range: bytes=a-b` requests.  The first
+    // `failures` range requests, other than the `bytes=0-0` probe, fail with a 503 after a short
+    // delay
+    fn serve_flaky(data: Arc<Vec<u8>>, support_ranges: bool, mut failures: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut range = None;
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(bytes) = line.strip_prefix("range: bytes=") {
+                        let mut bounds = bytes.split('-');
+                        let start: usize = bounds.next().unwrap().parse().unwrap();
+                        let end = bounds
+                            .next()
+                            .and_then(|end| end.parse::<usize>().ok())
+                            .unwrap_or(data.len() - 1);
+                        range = Some((start, end));
+                    }
+                }
+                let (status, body, content_range) = match range {
+                    Some((_start, end)) if support_ranges && failures > 0 && end > 0 => {
+                        failures -= 1;
+                        thread::sleep(Duration::from_millis(200));
+                        ("503 Service Unavailable", &data[..0], String::new())
+                    }
+                    Some((start, _end)) if support_ranges && start >= data.len() => (
+                        "416 Range Not Satisfiable",
+                        &data[..0],
+                        format!("Content-Range: bytes */{}\r\n", data.len()),
+                    ),
+                    Some((start, end)) if support_ranges => (
+                        "206 Partial Content",
+                        &data[start..=end],
+                        format!("Content-Range: bytes {}-{}/{}\r\n", start, end, data.len()),
+                    ),
+                    _ => ("200 OK", &data[..], String::new()),
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+                    status,
+                    body.len(),
+                    content_range
+                );
+                let _ = stream.write_all(body);
+            }
+        });
+        format!("http://{}/file", addr)
+    }
+
+    const TEST_CHUNK_SIZE: u64 = 1024;
+
+    fn test_data() -> Arc<Vec<u8>> {
+        let len = TEST_CHUNK_SIZE as usize * 2 + 123;
+        Arc::new((0..len).map(|i| (i % 251) as u8).collect())
+    }
+
+    #[test]
+    fn test_download_file_from_peers() {
+        let data = test_data();
+        let urls = vec![serve(data.clone(), true), serve(data.clone(), true)];
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file");
+
+        download_file_from_peers(
+            &urls,
+            &destination_file,
+            &Hash::default(),
+            TEST_CHUNK_SIZE,
+            false,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), *data);
+        assert!(!chunks_dir_of(&destination_file).exists());
+    }
+
+    #[test]
+    fn test_download_file_from_peers_resume() {
+        let data = test_data();
+        let urls = vec![serve(data.clone(), true)];
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file");
+
+        // Leave behind a complete first chunk and a partial second chunk
+        let chunks_dir = chunks_dir_of(&destination_file);
+        fs::create_dir_all(&chunks_dir).unwrap();
+        fs::write(
+            chunks_manifest_path(&chunks_dir),
+            chunks_manifest(TEST_CHUNK_SIZE, data.len() as u64, &Hash::default()),
+        )
+        .unwrap();
+        let chunk_size = TEST_CHUNK_SIZE as usize;
+        fs::write(chunk_path(&chunks_dir, 0), &data[..chunk_size]).unwrap();
+        fs::write(
+            chunk_path(&chunks_dir, 1),
+            &data[chunk_size..chunk_size + 42],
+        )
+        .unwrap();
+
+        download_file_from_peers(
+            &urls,
+            &destination_file,
+            &Hash::default(),
+            TEST_CHUNK_SIZE,
+            false,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), *data);
+    }
+
+    #[test]
+    fn test_download_file_from_peers_refetches_oversized_chunk() {
+        let data = test_data();
+        let urls = vec![serve(data.clone(), true)];
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file");
+
+        // A chunk that somehow grew past the end of its byte range
+        let chunks_dir = chunks_dir_of(&destination_file);
+        fs::create_dir_all(&chunks_dir).unwrap();
+        fs::write(
+            chunks_manifest_path(&chunks_dir),
+            chunks_manifest(TEST_CHUNK_SIZE, data.len() as u64, &Hash::default()),
+        )
+        .unwrap();
+        fs::write(
+            chunk_path(&chunks_dir, 0),
+            vec![0u8; TEST_CHUNK_SIZE as usize + 1],
+        )
+        .unwrap();
+
+        download_file_from_peers(
+            &urls,
+            &destination_file,
+            &Hash::default(),
+            TEST_CHUNK_SIZE,
+            false,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), *data);
+    }
+
+    #[test]
+    fn test_download_file_from_peers_discards_mismatched_chunks() {
+        let data = test_data();
+        let urls = vec![serve(data.clone(), true)];
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file");
+        let chunks_dir = chunks_dir_of(&destination_file);
+
+        let leave_garbage_chunks = |manifest: Option<String>| {
+            fs::create_dir_all(&chunks_dir).unwrap();
+            if let Some(manifest) = manifest {
+                fs::write(chunks_manifest_path(&chunks_dir), manifest).unwrap();
+            }
+            for chunk in 0..2 {
+                fs::write(chunk_path(&chunks_dir, chunk), vec![0u8; 512]).unwrap();
+            }
+        };
+        let data_size = data.len() as u64;
+        for manifest in &[
+            None,
+            Some(chunks_manifest(512, data_size, &Hash::default())),
+            Some(chunks_manifest(
+                TEST_CHUNK_SIZE,
+                data_size + 1,
+                &Hash::default(),
+            )),
+            Some(chunks_manifest(
+                TEST_CHUNK_SIZE,
+                data_size,
+                &solana_sdk::hash::hash(&[1]),
+            )),
+        ] {
+            leave_garbage_chunks(manifest.clone());
+            download_file_from_peers(
+                &urls,
+                &destination_file,
+                &Hash::default(),
+                TEST_CHUNK_SIZE,
+                false,
+            )
+            .unwrap();
+            assert_eq!(fs::read(&destination_file).unwrap(), *data);
+            fs::remove_file(&destination_file).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_download_file_from_flaky_peer() {
+        let data = test_data();
+        let urls = vec![serve_flaky(
+            data.clone(),
+            true,
+            MAX_PEER_CONSECUTIVE_FAILURES - 1,
+        )];
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file");
+
+        download_file_from_peers(
+            &urls,
+            &destination_file,
+            &Hash::default(),
+            TEST_CHUNK_SIZE,
+            false,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), *data);
+    }
+
+    #[test]
+    fn test_download_file_from_peers_after_other_peer_gives_up() {
+        let data = test_data();
+        // The flaky peer gives up on its chunk only after the good peer has run out of work
+        let urls = vec![
+            serve(data.clone(), true),
+            serve_flaky(data.clone(), true, MAX_PEER_CONSECUTIVE_FAILURES),
+        ];
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file");
+
+        download_file_from_peers(
+            &urls,
+            &destination_file,
+            &Hash::default(),
+            TEST_CHUNK_SIZE,
+            false,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), *data);
+    }
+
+    #[test]
+    fn test_download_file_discards_unusable_partial_download() {
+        let data = Arc::new(vec![42u8; 4096]);
+        let url = serve(data.clone(), true);
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file.tar.zst");
+
+        // A leftover partial download that is longer than the file being served
+        fs::write(temp_file_of(&destination_file), vec![0u8; 8192]).unwrap();
+        // Partial downloads of differently compressed archives don't share a temporary file
+        assert_ne!(
+            temp_file_of(&destination_file),
+            temp_file_of(&temp_dir.path().join("file.tar.bz2"))
+        );
+
+        download_file(&url, &destination_file, false).unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), *data);
+        assert!(!temp_file_of(&destination_file).exists());
+    }
+
+    #[test]
+    fn test_download_file_without_range_support() {
+        let data = Arc::new(vec![42u8; 4096]);
+        let urls = vec![serve(data.clone(), false)];
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let destination_file = temp_dir.path().join("file");
+
+        download_file_from_peers(
+            &urls,
+            &destination_file,
+            &Hash::default(),
+            TEST_CHUNK_SIZE,
+            false,
+        )
+        .unwrap();
+        assert_eq!(fs::read(&destination_file).unwrap(), *data);
+        assert!(download_file_from_peers(
+            &urls,
+            &destination_file,
+            &Hash::default(),
+            TEST_CHUNK_SIZE,
+            false,
+        )
+        .is_err());
+    }
+}
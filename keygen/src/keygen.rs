@@ -1,22 +1,28 @@
 use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use clap::{
-    crate_description, crate_name, value_t, values_t_or_exit, App, AppSettings, Arg, ArgMatches,
-    SubCommand,
+    crate_description, crate_name, value_t, value_t_or_exit, values_t_or_exit, App, AppSettings,
+    Arg, ArgMatches, SubCommand,
 };
 use solana_clap_utils::{
+    input_validators::{is_derivation, is_parsable},
     keypair::{
-        keypair_from_seed_phrase, prompt_passphrase, signer_from_path,
+        keypair_from_seed_phrase, prompt_passphrase, seed_from_seed_phrase, signer_from_path,
         SKIP_SEED_PHRASE_VALIDATION_ARG,
     },
     DisplayError,
 };
 use solana_cli_config::{Config, CONFIG_FILE};
-use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_remote_wallet::remote_wallet::{
+    DerivationPath, DerivationPathComponent, RemoteWalletManager,
+};
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     message::Message,
     pubkey::{write_pubkey_file, Pubkey},
-    signature::{keypair_from_seed, write_keypair, write_keypair_file, Keypair, Signer},
+    signature::{
+        keypair_from_seed, keypair_from_seed_and_derivation_path, write_keypair,
+        write_keypair_file, Keypair, Signer,
+    },
 };
 use std::{
     collections::HashSet,
@@ -79,6 +85,43 @@ fn output_keypair(
     Ok(())
 }
 
+fn derivation_path_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("derivation_path")
+        .long("derivation-path")
+        .value_name("ACCOUNT[/CHANGE]")
+        .takes_value(true)
+        .validator(is_derivation)
+        .help("Derive the keypair at m/44'/501'/ACCOUNT'/CHANGE', as hardware wallets do, instead of using the seed directly")
+}
+
+fn derivation_path_of(
+    matches: &ArgMatches,
+) -> Result<Option<DerivationPath>, Box<dyn error::Error>> {
+    match matches.value_of("derivation_path") {
+        Some(key_path) => Ok(Some(DerivationPath::from_key_str(key_path)?)),
+        None => Ok(None),
+    }
+}
+
+// Return the derivation paths of `num_accounts` accounts starting at `start_account`, each at
+// `change` if given
+fn derivation_paths(
+    start_account: u32,
+    num_accounts: u32,
+    change: Option<u32>,
+) -> Result<Vec<DerivationPath>, Box<dyn error::Error>> {
+    let end_account = start_account
+        .checked_add(num_accounts)
+        .filter(|end_account| *end_account <= DerivationPathComponent::HARDENED_BIT)
+        .ok_or("Account indexes must be below 2^31")?;
+    Ok((start_account..end_account)
+        .map(|account| DerivationPath {
+            account: Some(account.into()),
+            change: change.map(|change| change.into()),
+        })
+        .collect())
+}
+
 fn grind_validator_starts_with(v: String) -> Result<(), String> {
     if v.matches(':').count() != 1 || (v.starts_with(':') || v.ends_with(':')) {
         return Err(String::from("Expected : between PREFIX and COUNT"));
@@ -296,6 +339,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                         .long("silent")
                         .help("Do not display seed phrase. Useful when piping output to other programs that prompt for user input, like gpg"),
                 )
+                .arg(derivation_path_arg())
         )
         .subcommand(
             SubCommand::with_name("grind")
@@ -390,9 +434,46 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     Arg::with_name(SKIP_SEED_PHRASE_VALIDATION_ARG.name)
                         .long(SKIP_SEED_PHRASE_VALIDATION_ARG.long)
                         .help(SKIP_SEED_PHRASE_VALIDATION_ARG.help),
-                ),
+                )
+                .arg(derivation_path_arg()),
 
         )
+        .subcommand(
+            SubCommand::with_name("derive")
+                .about("List the pubkeys derived from a seed phrase and passphrase at consecutive accounts")
+                .setting(AppSettings::DisableVersion)
+                .arg(
+                    Arg::with_name("start_account")
+                        .long("start-account")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .default_value("0")
+                        .validator(is_parsable::<u32>)
+                        .help("First account to derive"),
+                )
+                .arg(
+                    Arg::with_name("num_accounts")
+                        .long("num-accounts")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .default_value("10")
+                        .validator(is_parsable::<u32>)
+                        .help("Number of accounts to derive"),
+                )
+                .arg(
+                    Arg::with_name("change")
+                        .long("change")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .validator(is_parsable::<u32>)
+                        .help("Change to derive at every account. Without it, pubkeys are derived at m/44'/501'/ACCOUNT'"),
+                )
+                .arg(
+                    Arg::with_name(SKIP_SEED_PHRASE_VALIDATION_ARG.name)
+                        .long(SKIP_SEED_PHRASE_VALIDATION_ARG.long)
+                        .help(SKIP_SEED_PHRASE_VALIDATION_ARG.help),
+                ),
+        )
         .get_matches();
 
     do_main(&matches).map_err(|err| DisplayError::new_as_boxed(err).into())
@@ -460,7 +541,13 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
                 )?
             };
             let seed = Seed::new(&mnemonic, &passphrase);
-            let keypair = keypair_from_seed(seed.as_bytes())?;
+            let keypair = match derivation_path_of(matches)? {
+                Some(derivation_path) => keypair_from_seed_and_derivation_path(
+                    seed.as_bytes(),
+                    &derivation_path.components(),
+                )?,
+                None => keypair_from_seed(seed.as_bytes())?,
+            };
 
             if let Some(outfile) = outfile {
                 output_keypair(&keypair, &outfile, "new")
@@ -491,9 +578,29 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
             }
 
             let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
-            let keypair = keypair_from_seed_phrase("recover", skip_validation, true)?;
+            let derivation_path = derivation_path_of(matches)?;
+            let keypair = keypair_from_seed_phrase(
+                "recover",
+                skip_validation,
+                true,
+                derivation_path.as_ref(),
+            )?;
             output_keypair(&keypair, &outfile, "recovered")?;
         }
+        ("derive", Some(matches)) => {
+            let derivation_paths = derivation_paths(
+                value_t_or_exit!(matches, "start_account", u32),
+                value_t_or_exit!(matches, "num_accounts", u32),
+                value_t!(matches, "change", u32).ok(),
+            )?;
+            let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
+            let seed = seed_from_seed_phrase("derive", skip_validation)?;
+            for derivation_path in derivation_paths {
+                let keypair =
+                    keypair_from_seed_and_derivation_path(&seed, &derivation_path.components())?;
+                println!("{:?} {}", derivation_path, keypair.pubkey());
+            }
+        }
         ("grind", Some(matches)) => {
             let ignore_case = matches.is_present("ignore_case");
 
@@ -66,7 +66,7 @@ async fn block(slot: Slot) -> Result<(), Box<dyn std::error::Error>> {
     for (index, transaction_with_meta) in block.transactions.into_iter().enumerate() {
         println!("Transaction {}:", index);
         println_transaction(
-            &transaction_with_meta.transaction,
+            &transaction_with_meta.resolved_transaction(),
             &transaction_with_meta.meta.map(|meta| meta.into()),
             "  ",
         );
@@ -101,7 +101,7 @@ async fn confirm(signature: &Signature, verbose: bool) -> Result<(), Box<dyn std
                     confirmed_transaction.slot
                 );
                 println_transaction(
-                    &confirmed_transaction.transaction.transaction,
+                    &confirmed_transaction.transaction.resolved_transaction(),
                     &confirmed_transaction.transaction.meta.map(|m| m.into()),
                     "  ",
                 );
@@ -179,7 +179,7 @@ pub async fn transaction_history(
                                 }
                                 Some(transaction_with_meta) => {
                                     println_transaction(
-                                        &transaction_with_meta.transaction,
+                                        &transaction_with_meta.resolved_transaction(),
                                         &transaction_with_meta.meta.clone().map(|m| m.into()),
                                         "  ",
                                     );
@@ -122,11 +122,14 @@ fn output_entry(
                     })
                     .map(|transaction_status| transaction_status.into());
 
-                solana_cli_output::display::println_transaction(
-                    &transaction,
-                    &transaction_status,
-                    "      ",
-                );
+                match transaction.clone().into_legacy_transaction() {
+                    Some(transaction) => solana_cli_output::display::println_transaction(
+                        &transaction,
+                        &transaction_status,
+                        "      ",
+                    ),
+                    None => println!("      {:?}", transaction),
+                }
             }
         }
         LedgerOutputMethod::Json => {
@@ -187,9 +190,10 @@ fn output_slot(
             transactions += entry.transactions.len();
             hashes += entry.num_hashes;
             for transaction in &entry.transactions {
-                for instruction in &transaction.message().instructions {
-                    let program_id =
-                        transaction.message().account_keys[instruction.program_id_index as usize];
+                for instruction in transaction.message.instructions() {
+                    // Program ids are never loaded from address lookup tables
+                    let program_id = transaction.message.static_account_keys()
+                        [instruction.program_id_index as usize];
                     *program_ids.entry(program_id).or_insert(0) += 1;
                 }
             }
@@ -901,6 +905,19 @@ fn main() {
             .arg(&starting_slot_arg)
             .about("Print all of dead slots")
         )
+        .subcommand(
+            SubCommand::with_name("block-stats")
+            .about("Print the recorded statistics of blocks")
+            .arg(&starting_slot_arg)
+            .arg(
+                Arg::with_name("ending_slot")
+                    .long("ending-slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .help("Stop at this slot [default: last available slot]"),
+            )
+        )
         .subcommand(
             SubCommand::with_name("set-dead-slot")
             .about("Mark one or more slots dead")
@@ -1498,6 +1515,38 @@ fn main() {
                 println!("{}", slot);
             }
         }
+        ("block-stats", Some(arg_matches)) => {
+            let blockstore = open_blockstore(
+                &ledger_path,
+                AccessType::TryPrimaryThenSecondary,
+                wal_recovery_mode,
+            );
+            let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+            let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(std::u64::MAX);
+            match blockstore.get_block_stats_range(starting_slot, ending_slot) {
+                Ok(block_stats) => {
+                    for (slot, stats) in block_stats {
+                        println!(
+                            "Slot {}: transactions={} (successful={} failed={} vote={} non-vote={}) compute_units={} fees={} entries={} ticks={}",
+                            slot,
+                            stats.num_transactions,
+                            stats.num_successful_transactions(),
+                            stats.num_failed_transactions,
+                            stats.num_vote_transactions,
+                            stats.num_non_vote_transactions(),
+                            stats.compute_units_consumed,
+                            stats.fees,
+                            stats.num_entries,
+                            stats.num_ticks,
+                        );
+                    }
+                }
+                Err(err) => {
+                    eprintln!("Unable to read block stats: {:?}", err);
+                    exit(1);
+                }
+            }
+        }
         ("set-dead-slot", Some(arg_matches)) => {
             let slots = values_t_or_exit!(arg_matches, "slots", Slot);
             let blockstore =
@@ -39,7 +39,7 @@ use solana_sdk::{
         calculate_stake_weighted_timestamp, EstimateType, TIMESTAMP_SLOT_RANGE,
     },
     timing::timestamp,
-    transaction::Transaction,
+    transaction::VersionedTransaction,
 };
 use solana_storage_proto::StoredExtendedRewards;
 use solana_transaction_status::{
@@ -98,6 +98,38 @@ pub enum PurgeType {
     PrimaryIndex,
 }
 
+/// Sets of column families that can be retained for different periods of time
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColumnGroup {
+    /// Shreds and the metadata needed to assemble them into blocks
+    Shreds,
+    /// `TransactionStatus`, `AddressSignatures` and `Transactions`
+    TransactionStatus,
+    /// `Rewards`
+    Rewards,
+    /// Slot-keyed metadata the other groups rely on: `Root`, `Blocktime`, `PerfSamples` and
+    /// `BlockStats`
+    BlockMetadata,
+}
+
+impl ColumnGroup {
+    pub const ALL: [ColumnGroup; 4] = [
+        ColumnGroup::Shreds,
+        ColumnGroup::TransactionStatus,
+        ColumnGroup::Rewards,
+        ColumnGroup::BlockMetadata,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColumnGroup::Shreds => "shreds",
+            ColumnGroup::TransactionStatus => "transaction_status",
+            ColumnGroup::Rewards => "rewards",
+            ColumnGroup::BlockMetadata => "block_metadata",
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum InsertDataShredError {
     Exists,
@@ -142,12 +174,17 @@ pub struct Blockstore {
     rewards_cf: LedgerColumn<cf::Rewards>,
     blocktime_cf: LedgerColumn<cf::Blocktime>,
     perf_samples_cf: LedgerColumn<cf::PerfSamples>,
+    block_stats_cf: LedgerColumn<cf::BlockStats>,
+    transactions_cf: LedgerColumn<cf::Transactions>,
     last_root: Arc<RwLock<Slot>>,
     insert_shreds_lock: Arc<Mutex<()>>,
     pub new_shreds_signals: Vec<SyncSender<bool>>,
     pub completed_slots_senders: Vec<SyncSender<Vec<Slot>>>,
     pub lowest_cleanup_slot: Arc<RwLock<u64>>,
+    /// Highest slot purged from each column group retained apart from shreds
+    column_group_cleanup_slots: RwLock<HashMap<ColumnGroup, Slot>>,
     no_compaction: bool,
+    retain_transactions: bool,
 }
 
 pub struct IndexMetaWorkingSetEntry {
@@ -301,6 +338,8 @@ impl Blockstore {
         let rewards_cf = db.column();
         let blocktime_cf = db.column();
         let perf_samples_cf = db.column();
+        let block_stats_cf = db.column();
+        let transactions_cf = db.column();
 
         let db = Arc::new(db);
 
@@ -347,12 +386,16 @@ impl Blockstore {
             rewards_cf,
             blocktime_cf,
             perf_samples_cf,
+            block_stats_cf,
+            transactions_cf,
             new_shreds_signals: vec![],
             completed_slots_senders: vec![],
             insert_shreds_lock: Arc::new(Mutex::new(())),
             last_root,
             lowest_cleanup_slot: Arc::new(RwLock::new(0)),
+            column_group_cleanup_slots: RwLock::new(HashMap::new()),
             no_compaction: false,
+            retain_transactions: false,
         };
         if initialize_transaction_status_index {
             blockstore.initialize_transaction_status_index()?;
@@ -429,6 +472,38 @@ impl Blockstore {
         self.no_compaction = no_compaction;
     }
 
+    /// Keep a copy of each transaction whose status is written, so that it can still be served
+    /// once the shreds of its slot are purged
+    pub fn set_retain_transactions(&mut self, retain_transactions: bool) {
+        self.retain_transactions = retain_transactions;
+    }
+
+    /// Returns the highest slot purged from `column_group` by the LedgerCleanupService
+    pub fn column_group_cleanup_slot(&self, column_group: ColumnGroup) -> Slot {
+        match column_group {
+            ColumnGroup::Shreds => *self.lowest_cleanup_slot.read().unwrap(),
+            _ => self
+                .column_group_cleanup_slots
+                .read()
+                .unwrap()
+                .get(&column_group)
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn set_column_group_cleanup_slot(&self, column_group: ColumnGroup, slot: Slot) {
+        match column_group {
+            ColumnGroup::Shreds => *self.lowest_cleanup_slot.write().unwrap() = slot,
+            _ => {
+                self.column_group_cleanup_slots
+                    .write()
+                    .unwrap()
+                    .insert(column_group, slot);
+            }
+        }
+    }
+
     pub fn destroy(ledger_path: &Path) -> Result<()> {
         // Database::destroy() fails if the path doesn't exist
         fs::create_dir_all(ledger_path)?;
@@ -1581,15 +1656,53 @@ impl Blockstore {
             "blockstore-rpc-api",
             ("method", "get_block_time".to_string(), String)
         );
-        let lowest_cleanup_slot = self.lowest_cleanup_slot.read().unwrap();
-        // lowest_cleanup_slot is the last slot that was not cleaned up by
-        // LedgerCleanupService
-        if *lowest_cleanup_slot > 0 && *lowest_cleanup_slot >= slot {
+        // Block times can be retained for longer than shreds
+        let lowest_cleanup_slot = self.column_group_cleanup_slot(ColumnGroup::BlockMetadata);
+        if lowest_cleanup_slot > 0 && lowest_cleanup_slot >= slot {
             return Err(BlockstoreError::SlotCleanedUp);
         }
         self.blocktime_cf.get(slot)
     }
 
+    /// Returns the highest slot whose cached block time is earlier than `timestamp`
+    pub fn get_last_slot_before_block_time(
+        &self,
+        timestamp: UnixTimestamp,
+    ) -> Result<Option<Slot>> {
+        // Block times increase with slot, so binary search the column by seeking
+        let first_slot = match self.blocktime_cf.iter(IteratorMode::Start)?.next() {
+            Some((slot, _)) => slot,
+            None => return Ok(None),
+        };
+        let last_slot = match self.blocktime_cf.iter(IteratorMode::End)?.next() {
+            Some((slot, _)) => slot,
+            None => return Ok(None),
+        };
+        let block_time_from = |slot: Slot| -> Result<Option<(Slot, UnixTimestamp)>> {
+            match self
+                .blocktime_cf
+                .iter(IteratorMode::From(slot, IteratorDirection::Forward))?
+                .next()
+            {
+                Some((slot, data)) => Ok(Some((slot, deserialize(&data)?))),
+                None => Ok(None),
+            }
+        };
+        let (mut low, mut high) = (first_slot, last_slot.saturating_add(1));
+        let mut found = None;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match block_time_from(mid)? {
+                Some((slot, block_time)) if block_time < timestamp => {
+                    found = Some(slot);
+                    low = slot + 1;
+                }
+                _ => high = mid,
+            }
+        }
+        Ok(found)
+    }
+
     fn get_timestamp_slots(&self, slot: Slot, timestamp_sample_range: usize) -> Vec<Slot> {
         let root_iterator = self
             .db
@@ -1674,6 +1787,17 @@ impl Blockstore {
         Ok(root_iterator.next().unwrap_or_default())
     }
 
+    /// Returns the first root whose transaction statuses are still available, which may be
+    /// older than the first available block when transactions are retained apart from shreds
+    fn get_first_available_transaction_status_slot(&self) -> Result<Slot> {
+        if !self.retain_transactions {
+            return self.get_first_available_block();
+        }
+        let lowest_cleanup_slot = self.column_group_cleanup_slot(ColumnGroup::TransactionStatus);
+        let mut root_iterator = self.rooted_slot_iterator(lowest_cleanup_slot.saturating_add(1))?;
+        Ok(root_iterator.next().unwrap_or_default())
+    }
+
     pub fn get_confirmed_block(&self, slot: Slot) -> Result<ConfirmedBlock> {
         datapoint_info!(
             "blockstore-rpc-api",
@@ -1697,7 +1821,7 @@ impl Blockstore {
 
             let slot_entries = self.get_slot_entries(slot, 0)?;
             if !slot_entries.is_empty() {
-                let slot_transaction_iterator = slot_entries
+                let slot_transactions = slot_entries
                     .iter()
                     .cloned()
                     .flat_map(|entry| entry.transactions);
@@ -1724,8 +1848,7 @@ impl Blockstore {
                     previous_blockhash: previous_blockhash.to_string(),
                     blockhash: blockhash.to_string(),
                     parent_slot: slot_meta.parent_slot,
-                    transactions: self
-                        .map_transactions_to_statuses(slot, slot_transaction_iterator),
+                    transactions: self.map_transactions_to_statuses(slot, slot_transactions),
                     rewards,
                     block_time,
                 };
@@ -1738,7 +1861,7 @@ impl Blockstore {
     fn map_transactions_to_statuses<'a>(
         &self,
         slot: Slot,
-        iterator: impl Iterator<Item = Transaction> + 'a,
+        iterator: impl Iterator<Item = VersionedTransaction> + 'a,
     ) -> Vec<TransactionWithStatusMeta> {
         iterator
             .map(|transaction| {
@@ -1880,6 +2003,15 @@ impl Blockstore {
         Ok(())
     }
 
+    /// Keeps a copy of a transaction alongside its status, if transactions are retained
+    pub fn write_transaction(&self, slot: Slot, transaction: &VersionedTransaction) -> Result<()> {
+        if !self.retain_transactions {
+            return Ok(());
+        }
+        self.transactions_cf
+            .put((slot, transaction.signatures[0]), transaction)
+    }
+
     // Returns a transaction status if it was processed in a root, as well as a loop counter for
     // unit testing
     fn get_transaction_status_with_counter(
@@ -1930,7 +2062,7 @@ impl Blockstore {
         );
         if let Some((slot, status)) = self.get_transaction_status(signature)? {
             let transaction = self
-                .find_transaction_in_slot(slot, signature)?
+                .get_transaction_in_slot(slot, signature)?
                 .ok_or(BlockstoreError::TransactionStatusSlotMismatch)?; // Should not happen
             Ok(Some(ConfirmedTransaction {
                 slot,
@@ -1944,11 +2076,57 @@ impl Blockstore {
         }
     }
 
+    // Returns the retained copy of a transaction, falling back to the entries of its slot
+    fn get_transaction_in_slot(
+        &self,
+        slot: Slot,
+        signature: Signature,
+    ) -> Result<Option<VersionedTransaction>> {
+        if let Some(transaction) = self.transactions_cf.get((slot, signature))? {
+            return Ok(Some(transaction));
+        }
+        self.find_transaction_in_slot(slot, signature)
+    }
+
+    // Returns the first signature of every transaction in a rooted slot, from the retained
+    // transactions if there are any, or the entries of the slot
+    fn get_block_signatures(&self, slot: Slot) -> Result<Vec<Signature>> {
+        let signatures: Vec<_> = self
+            .transactions_cf
+            .iter(IteratorMode::From(
+                (slot, Signature::default()),
+                IteratorDirection::Forward,
+            ))?
+            .take_while(|((transaction_slot, _), _)| *transaction_slot == slot)
+            .map(|((_, signature), _)| signature)
+            .collect();
+        if !signatures.is_empty() {
+            return Ok(signatures);
+        }
+        let confirmed_block = self.get_confirmed_block(slot).map_err(|err| {
+            BlockstoreError::IO(IOError::new(
+                ErrorKind::Other,
+                format!("Unable to get confirmed block: {}", err),
+            ))
+        })?;
+        Ok(confirmed_block
+            .transactions
+            .into_iter()
+            .filter_map(|transaction_with_meta| {
+                transaction_with_meta
+                    .transaction
+                    .signatures
+                    .into_iter()
+                    .next()
+            })
+            .collect())
+    }
+
     fn find_transaction_in_slot(
         &self,
         slot: Slot,
         signature: Signature,
-    ) -> Result<Option<Transaction>> {
+    ) -> Result<Option<VersionedTransaction>> {
         let slot_entries = self.get_slot_entries(slot, 0)?;
         Ok(slot_entries
             .iter()
@@ -2037,25 +2215,8 @@ impl Blockstore {
                 match transaction_status {
                     None => return Ok(vec![]),
                     Some((slot, _)) => {
-                        let confirmed_block = self.get_confirmed_block(slot).map_err(|err| {
-                            BlockstoreError::IO(IOError::new(
-                                ErrorKind::Other,
-                                format!("Unable to get confirmed block: {}", err),
-                            ))
-                        })?;
-
                         // Load all signatures for the block
-                        let mut slot_signatures: Vec<_> = confirmed_block
-                            .transactions
-                            .into_iter()
-                            .filter_map(|transaction_with_meta| {
-                                transaction_with_meta
-                                    .transaction
-                                    .signatures
-                                    .into_iter()
-                                    .next()
-                            })
-                            .collect();
+                        let mut slot_signatures = self.get_block_signatures(slot)?;
 
                         // Sort signatures as a way to entire a stable ordering within a slot, as
                         // the AddressSignatures column is ordered by signatures within a slot,
@@ -2087,25 +2248,8 @@ impl Blockstore {
                 match transaction_status {
                     None => (0, HashSet::new()),
                     Some((slot, _)) => {
-                        let confirmed_block = self.get_confirmed_block(slot).map_err(|err| {
-                            BlockstoreError::IO(IOError::new(
-                                ErrorKind::Other,
-                                format!("Unable to get confirmed block: {}", err),
-                            ))
-                        })?;
-
                         // Load all signatures for the block
-                        let mut slot_signatures: Vec<_> = confirmed_block
-                            .transactions
-                            .into_iter()
-                            .filter_map(|transaction_with_meta| {
-                                transaction_with_meta
-                                    .transaction
-                                    .signatures
-                                    .into_iter()
-                                    .next()
-                            })
-                            .collect();
+                        let mut slot_signatures = self.get_block_signatures(slot)?;
 
                         // Sort signatures as a way to entire a stable ordering within a slot, as
                         // the AddressSignatures column is ordered by signatures within a slot,
@@ -2125,7 +2269,7 @@ impl Blockstore {
         get_until_slot_timer.stop();
 
         // Fetch the list of signatures that affect the given address
-        let first_available_block = self.get_first_available_block()?;
+        let first_available_block = self.get_first_available_transaction_status_slot()?;
         let mut address_signatures = vec![];
 
         // Get signatures in `slot`
@@ -2297,18 +2441,29 @@ impl Blockstore {
             .flat_map(|entry| entry.transactions)
             .flat_map(|transaction| {
                 let mut timestamps: Vec<(Pubkey, (Slot, UnixTimestamp))> = Vec::new();
-                for instruction in transaction.message.instructions {
-                    let program_id = instruction.program_id(&transaction.message.account_keys);
+                let account_keys = transaction.message.static_account_keys();
+                for instruction in transaction.message.instructions() {
+                    let program_id = instruction.program_id(account_keys);
                     if program_id == &solana_vote_program::id() {
-                        if let Ok(VoteInstruction::Vote(vote)) =
-                            limited_deserialize(&instruction.data)
-                        {
+                        let vote = match limited_deserialize(&instruction.data) {
+                            Ok(VoteInstruction::Vote(vote)) => Some(vote),
+                            Ok(VoteInstruction::UpdateVoteState(vote_state_update)) => {
+                                Some(vote_state_update.into())
+                            }
+                            _ => None,
+                        };
+                        if let Some(vote) = vote {
                             if let Some(timestamp) = vote.timestamp {
                                 let timestamp_slot = vote.slots.iter().max();
-                                if let Some(timestamp_slot) = timestamp_slot {
-                                    let vote_pubkey = transaction.message.account_keys
-                                        [instruction.accounts[0] as usize];
-                                    timestamps.push((vote_pubkey, (*timestamp_slot, timestamp)));
+                                // Vote accounts loaded from address lookup tables are skipped
+                                let vote_pubkey = instruction
+                                    .accounts
+                                    .get(0)
+                                    .and_then(|index| account_keys.get(*index as usize));
+                                if let (Some(timestamp_slot), Some(vote_pubkey)) =
+                                    (timestamp_slot, vote_pubkey)
+                                {
+                                    timestamps.push((*vote_pubkey, (*timestamp_slot, timestamp)));
                                 }
                             }
                         }
@@ -2335,6 +2490,46 @@ impl Blockstore {
         self.perf_samples_cf.put(index, perf_sample)
     }
 
+    pub fn write_block_stats(&self, slot: Slot, block_stats: &BlockStats) -> Result<()> {
+        self.block_stats_cf.put(slot, block_stats)
+    }
+
+    pub fn get_block_stats(&self, slot: Slot) -> Result<Option<BlockStats>> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            ("method", "get_block_stats".to_string(), String)
+        );
+        // Block statistics can be retained for longer than shreds
+        let lowest_cleanup_slot = self.column_group_cleanup_slot(ColumnGroup::BlockMetadata);
+        if lowest_cleanup_slot > 0 && lowest_cleanup_slot >= slot {
+            return Err(BlockstoreError::SlotCleanedUp);
+        }
+        self.block_stats_cf.get(slot)
+    }
+
+    /// Returns the statistics recorded for every slot in `[start_slot, end_slot]`, in slot order.
+    /// Slots without recorded statistics (skipped, not yet frozen, or frozen on an abandoned
+    /// fork and since purged) are omitted
+    pub fn get_block_stats_range(
+        &self,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> Result<Vec<(Slot, BlockStats)>> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            ("method", "get_block_stats_range".to_string(), String)
+        );
+        let lowest_cleanup_slot = self.column_group_cleanup_slot(ColumnGroup::BlockMetadata);
+        if lowest_cleanup_slot > 0 && lowest_cleanup_slot >= start_slot {
+            return Err(BlockstoreError::SlotCleanedUp);
+        }
+        self.db
+            .iter::<cf::BlockStats>(IteratorMode::From(start_slot, IteratorDirection::Forward))?
+            .take_while(|(slot, _)| *slot <= end_slot)
+            .map(|(slot, data)| Ok((slot, deserialize(&data)?)))
+            .collect()
+    }
+
     /// Returns the entry vector for the slot starting with `shred_start_index`
     pub fn get_slot_entries(&self, slot: Slot, shred_start_index: u64) -> Result<Vec<Entry>> {
         self.get_slot_entries_with_shred_info(slot, shred_start_index, false)
@@ -3464,7 +3659,7 @@ pub mod tests {
     use super::*;
     use crate::{
         blockstore_processor::fill_blockstore_slot_with_ticks,
-        entry::{next_entry, next_entry_mut},
+        entry::{next_entry, next_entry_mut, next_hash},
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
         leader_schedule::{FixedSchedule, LeaderSchedule},
         shred::{max_ticks_per_n_shreds, DataShredHeader},
@@ -3477,14 +3672,17 @@ pub mod tests {
     use solana_sdk::{
         hash::{self, hash, Hash},
         instruction::CompiledInstruction,
-        message::Message,
+        message::{LoadedAddresses, Message, MessageV0, VersionedMessage},
         packet::PACKET_DATA_SIZE,
         pubkey::Pubkey,
         signature::Signature,
-        transaction::TransactionError,
+        system_instruction, system_transaction,
+        transaction::{Transaction, TransactionError},
     };
     use solana_storage_proto::convert::generated;
-    use solana_transaction_status::{InnerInstructions, Reward, Rewards};
+    use solana_transaction_status::{
+        InnerInstructions, Reward, Rewards, TransactionLogEvent, TransactionReturnData,
+    };
     use solana_vote_program::{vote_instruction, vote_state::Vote};
     use std::{iter::FromIterator, time::Duration};
 
@@ -5671,7 +5869,8 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                let num_keys = transaction.message.static_account_keys().len();
+                for i in 0..num_keys {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -5687,6 +5886,9 @@ pub mod tests {
                             post_balances: post_balances.clone(),
                             inner_instructions: Some(vec![]),
                             log_messages: Some(vec![]),
+                            return_data: None,
+                            log_events: None,
+                            loaded_addresses: LoadedAddresses::default(),
                         },
                     )
                     .unwrap();
@@ -5701,6 +5903,9 @@ pub mod tests {
                             post_balances: post_balances.clone(),
                             inner_instructions: Some(vec![]),
                             log_messages: Some(vec![]),
+                            return_data: None,
+                            log_events: None,
+                            loaded_addresses: LoadedAddresses::default(),
                         },
                     )
                     .unwrap();
@@ -5713,6 +5918,9 @@ pub mod tests {
                         post_balances,
                         inner_instructions: Some(vec![]),
                         log_messages: Some(vec![]),
+                        return_data: None,
+                        log_events: None,
+                        loaded_addresses: LoadedAddresses::default(),
                     }),
                 }
             })
@@ -5765,6 +5973,81 @@ pub mod tests {
         Blockstore::destroy(&ledger_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_get_confirmed_block_with_versioned_transactions() {
+        let slot = 10;
+        let keypair = Keypair::new();
+        let to = Pubkey::new_unique();
+        let legacy_transaction = system_transaction::transfer(&keypair, &to, 1, Hash::default());
+        let message = MessageV0::new_with_lookup_tables(
+            &[system_instruction::transfer(&keypair.pubkey(), &to, 2)],
+            Some(&keypair.pubkey()),
+            &[(Pubkey::new_unique(), vec![to])],
+        );
+        let versioned_transaction =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&keypair]).unwrap();
+        let transactions = vec![legacy_transaction.into(), versioned_transaction];
+        let entries = vec![Entry {
+            num_hashes: 1,
+            hash: next_hash(&Hash::default(), 1, &transactions),
+            transactions: transactions.clone(),
+        }];
+        let blockhash = get_last_hash(entries.iter()).unwrap();
+        let shreds = entries_to_test_shreds(entries, slot, slot - 1, true, 0);
+        let ledger_path = get_tmp_ledger_path!();
+        let blockstore = Blockstore::open(&ledger_path).unwrap();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots(&[slot - 1, slot]).unwrap();
+
+        let expected_transactions: Vec<TransactionWithStatusMeta> = transactions
+            .into_iter()
+            .map(|transaction| {
+                let loaded_addresses = match transaction.message {
+                    VersionedMessage::Legacy(_) => LoadedAddresses::default(),
+                    VersionedMessage::V0(_) => LoadedAddresses {
+                        writable: vec![to],
+                        readonly: vec![],
+                    },
+                };
+                let status = TransactionStatusMeta {
+                    fee: 42,
+                    loaded_addresses,
+                    ..TransactionStatusMeta::default()
+                };
+                blockstore
+                    .transaction_status_cf
+                    .put((0, transaction.signatures[0], slot), &status)
+                    .unwrap();
+                TransactionWithStatusMeta {
+                    transaction,
+                    meta: Some(status),
+                }
+            })
+            .collect();
+
+        // A slot mixing legacy and v0 transactions is served in full
+        let confirmed_block = blockstore.get_confirmed_block(slot).unwrap();
+        assert_eq!(confirmed_block.blockhash, blockhash.to_string());
+        assert_eq!(confirmed_block.transactions, expected_transactions);
+
+        let signatures: Vec<_> = expected_transactions
+            .iter()
+            .map(|transaction_with_meta| transaction_with_meta.transaction.signatures[0])
+            .collect();
+        assert_eq!(blockstore.get_block_signatures(slot).unwrap(), signatures);
+
+        for transaction in expected_transactions {
+            let signature = transaction.transaction.signatures[0];
+            assert_eq!(
+                blockstore.get_confirmed_transaction(signature).unwrap(),
+                Some(ConfirmedTransaction { slot, transaction })
+            );
+        }
+
+        drop(blockstore);
+        Blockstore::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_get_block_timestamps() {
         let vote_keypairs: Vec<Keypair> = (0..6).map(|_| Keypair::new()).collect();
@@ -5903,6 +6186,18 @@ pub mod tests {
                 instructions: vec![CompiledInstruction::new(1, &(), vec![0])],
             }];
             let log_messages_vec = vec![String::from("Test message\n")];
+            let return_data_value = TransactionReturnData {
+                program_id: Pubkey::new_unique(),
+                data: vec![1, 2, 3],
+            };
+            let log_events_value = vec![TransactionLogEvent {
+                program_id: Pubkey::new_unique(),
+                data: vec![vec![4, 5], vec![6]],
+            }];
+            let loaded_addresses_value = LoadedAddresses {
+                writable: vec![Pubkey::new_unique()],
+                readonly: vec![Pubkey::new_unique()],
+            };
 
             // result not found
             assert!(transaction_status_cf
@@ -5923,6 +6218,9 @@ pub mod tests {
                         post_balances: post_balances_vec.clone(),
                         inner_instructions: Some(inner_instructions_vec.clone()),
                         log_messages: Some(log_messages_vec.clone()),
+                        return_data: Some(return_data_value.clone()),
+                        log_events: Some(log_events_value.clone()),
+                        loaded_addresses: loaded_addresses_value.clone(),
                     },
                 )
                 .is_ok());
@@ -5935,6 +6233,9 @@ pub mod tests {
                 post_balances,
                 inner_instructions,
                 log_messages,
+                return_data,
+                log_events,
+                loaded_addresses,
             } = transaction_status_cf
                 .get((0, Signature::default(), 0))
                 .unwrap()
@@ -5945,6 +6246,9 @@ pub mod tests {
             assert_eq!(post_balances, post_balances_vec);
             assert_eq!(inner_instructions.unwrap(), inner_instructions_vec);
             assert_eq!(log_messages.unwrap(), log_messages_vec);
+            assert_eq!(return_data.unwrap(), return_data_value);
+            assert_eq!(log_events.unwrap(), log_events_value);
+            assert_eq!(loaded_addresses, loaded_addresses_value);
 
             // insert value
             assert!(transaction_status_cf
@@ -5957,6 +6261,9 @@ pub mod tests {
                         post_balances: post_balances_vec.clone(),
                         inner_instructions: Some(inner_instructions_vec.clone()),
                         log_messages: Some(log_messages_vec.clone()),
+                        return_data: Some(return_data_value.clone()),
+                        log_events: Some(log_events_value.clone()),
+                        loaded_addresses: loaded_addresses_value.clone(),
                     },
                 )
                 .is_ok());
@@ -5969,6 +6276,9 @@ pub mod tests {
                 post_balances,
                 inner_instructions,
                 log_messages,
+                return_data,
+                log_events,
+                loaded_addresses,
             } = transaction_status_cf
                 .get((0, Signature::new(&[2u8; 64]), 9))
                 .unwrap()
@@ -5981,6 +6291,9 @@ pub mod tests {
             assert_eq!(post_balances, post_balances_vec);
             assert_eq!(inner_instructions.unwrap(), inner_instructions_vec);
             assert_eq!(log_messages.unwrap(), log_messages_vec);
+            assert_eq!(return_data.unwrap(), return_data_value);
+            assert_eq!(log_events.unwrap(), log_events_value);
+            assert_eq!(loaded_addresses, loaded_addresses_value);
         }
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
@@ -6209,6 +6522,9 @@ pub mod tests {
                 post_balances: post_balances_vec,
                 inner_instructions: Some(vec![]),
                 log_messages: Some(vec![]),
+                return_data: None,
+                log_events: None,
+                loaded_addresses: LoadedAddresses::default(),
             };
 
             let signature1 = Signature::new(&[1u8; 64]);
@@ -6334,7 +6650,8 @@ pub mod tests {
             .map(|transaction| {
                 let mut pre_balances: Vec<u64> = vec![];
                 let mut post_balances: Vec<u64> = vec![];
-                for (i, _account_key) in transaction.message.account_keys.iter().enumerate() {
+                let num_keys = transaction.message.static_account_keys().len();
+                for i in 0..num_keys {
                     pre_balances.push(i as u64 * 10);
                     post_balances.push(i as u64 * 11);
                 }
@@ -6355,6 +6672,9 @@ pub mod tests {
                             post_balances: post_balances.clone(),
                             inner_instructions: inner_instructions.clone(),
                             log_messages: log_messages.clone(),
+                            return_data: None,
+                            log_events: None,
+                            loaded_addresses: LoadedAddresses::default(),
                         },
                     )
                     .unwrap();
@@ -6367,6 +6687,9 @@ pub mod tests {
                         post_balances,
                         inner_instructions,
                         log_messages,
+                        return_data: None,
+                        log_events: None,
+                        loaded_addresses: LoadedAddresses::default(),
                     }),
                 }
             })
@@ -6391,6 +6714,131 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_get_retained_transaction_after_shreds_purged() {
+        let slot = 2;
+        let entries = make_slot_entries_with_transactions(5);
+        let shreds = entries_to_test_shreds(entries.clone(), slot, slot - 1, true, 0);
+        let ledger_path = get_tmp_ledger_path!();
+        let mut blockstore = Blockstore::open(&ledger_path).unwrap();
+        blockstore.set_retain_transactions(true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots(&[slot - 1, slot]).unwrap();
+        blockstore.cache_block_time(slot, 42).unwrap();
+
+        let transactions: Vec<Transaction> = entries
+            .into_iter()
+            .flat_map(|entry| entry.transactions)
+            .map(|transaction| transaction.into_legacy_transaction().unwrap())
+            .collect();
+        let status = TransactionStatusMeta {
+            status: Ok(()),
+            fee: 42,
+            pre_balances: vec![],
+            post_balances: vec![],
+            inner_instructions: None,
+            log_messages: None,
+            return_data: None,
+            log_events: None,
+            loaded_addresses: LoadedAddresses::default(),
+        };
+        for transaction in &transactions {
+            let (writable_keys, readonly_keys) =
+                transaction.message.get_account_keys_by_lock_type();
+            blockstore
+                .write_transaction_status(
+                    slot,
+                    transaction.signatures[0],
+                    writable_keys,
+                    readonly_keys,
+                    &status,
+                )
+                .unwrap();
+            blockstore
+                .write_transaction(slot, &transaction.clone().into())
+                .unwrap();
+        }
+
+        // Purge the shreds, keeping transaction statuses and block metadata
+        blockstore.set_column_group_cleanup_slot(ColumnGroup::Shreds, slot);
+        blockstore.purge_column_group(ColumnGroup::Shreds, 0, slot);
+        assert!(blockstore.get_confirmed_block(slot).is_err());
+        for transaction in &transactions {
+            assert_eq!(
+                blockstore
+                    .get_confirmed_transaction(transaction.signatures[0])
+                    .unwrap(),
+                Some(ConfirmedTransaction {
+                    slot,
+                    transaction: TransactionWithStatusMeta {
+                        transaction: transaction.clone().into(),
+                        meta: Some(status.clone()),
+                    },
+                })
+            );
+        }
+        assert_eq!(blockstore.get_block_time(slot).unwrap(), Some(42));
+
+        // Signatures before a transaction are found without the block's entries
+        let address = transactions[0].message.account_keys[0];
+        let signatures = blockstore
+            .get_confirmed_signatures_for_address2(
+                address,
+                slot,
+                Some(transactions[0].signatures[0]),
+                None,
+                usize::MAX,
+            )
+            .unwrap();
+        assert!(signatures.is_empty());
+
+        blockstore.set_column_group_cleanup_slot(ColumnGroup::BlockMetadata, slot);
+        assert_matches!(
+            blockstore.get_block_time(slot),
+            Err(BlockstoreError::SlotCleanedUp)
+        );
+    }
+
+    #[test]
+    fn test_get_last_slot_before_block_time() {
+        let blockstore_path = get_tmp_ledger_path!();
+        let blockstore = Blockstore::open(&blockstore_path).unwrap();
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(10).unwrap(),
+            None
+        );
+
+        // Skipped slots leave gaps in the column
+        for slot in (5..100).step_by(3) {
+            blockstore
+                .cache_block_time(slot, slot as UnixTimestamp * 10)
+                .unwrap();
+        }
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(50).unwrap(),
+            None
+        );
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(51).unwrap(),
+            Some(5)
+        );
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(500).unwrap(),
+            Some(47)
+        );
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(501).unwrap(),
+            Some(50)
+        );
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(10_000).unwrap(),
+            Some(98)
+        );
+
+        drop(blockstore);
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_empty_transaction_status() {
         let blockstore_path = get_tmp_ledger_path!();
@@ -6588,7 +7036,7 @@ pub mod tests {
                             .write_transaction_status(
                                 slot,
                                 transaction.signatures[0],
-                                transaction.message.account_keys.iter().collect(),
+                                transaction.message.static_account_keys().iter().collect(),
                                 vec![],
                                 &TransactionStatusMeta::default(),
                             )
@@ -6808,6 +7256,9 @@ pub mod tests {
                             post_balances: vec![],
                             inner_instructions: Some(vec![]),
                             log_messages: Some(vec![]),
+                            return_data: None,
+                            log_events: None,
+                            loaded_addresses: LoadedAddresses::default(),
                         },
                     )
                     .unwrap();
@@ -6864,6 +7315,62 @@ pub mod tests {
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_write_get_block_stats() {
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let block_stats: Vec<(Slot, BlockStats)> = (1..=10)
+                .map(|slot| {
+                    (
+                        slot,
+                        BlockStats {
+                            num_transactions: 100 + slot,
+                            num_failed_transactions: slot,
+                            num_vote_transactions: 50,
+                            compute_units_consumed: 1000 * slot,
+                            fees: 5000 * slot,
+                            num_entries: 10,
+                            num_ticks: 64,
+                        },
+                    )
+                })
+                .collect();
+            for (slot, stats) in block_stats.iter() {
+                blockstore.write_block_stats(*slot, stats).unwrap();
+            }
+
+            assert_eq!(blockstore.get_block_stats(0).unwrap(), None);
+            assert_eq!(
+                blockstore.get_block_stats(3).unwrap(),
+                Some(block_stats[2].1)
+            );
+            assert_eq!(
+                blockstore.get_block_stats_range(3, 6).unwrap(),
+                block_stats[2..6].to_vec()
+            );
+            assert_eq!(
+                blockstore.get_block_stats_range(8, std::u64::MAX).unwrap(),
+                block_stats[7..].to_vec()
+            );
+
+            blockstore.set_column_group_cleanup_slot(ColumnGroup::BlockMetadata, 2);
+            assert_matches!(
+                blockstore.get_block_stats_range(2, 6),
+                Err(BlockstoreError::SlotCleanedUp)
+            );
+            assert_eq!(
+                blockstore.get_block_stats_range(3, 6).unwrap(),
+                block_stats[2..6].to_vec()
+            );
+            assert_eq!(
+                block_stats[2].1.num_successful_transactions(),
+                block_stats[2].1.num_transactions - 3
+            );
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_lowest_slot() {
         let blockstore_path = get_tmp_ledger_path!();
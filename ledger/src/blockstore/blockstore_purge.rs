@@ -145,6 +145,14 @@ impl Blockstore {
             & self
                 .db
                 .delete_range_cf::<cf::PerfSamples>(&mut write_batch, from_slot, to_slot)
+                .is_ok()
+            & self
+                .db
+                .delete_range_cf::<cf::BlockStats>(&mut write_batch, from_slot, to_slot)
+                .is_ok()
+            & self
+                .db
+                .delete_range_cf::<cf::Transactions>(&mut write_batch, from_slot, to_slot)
                 .is_ok();
         let mut w_active_transaction_status_index =
             self.active_transaction_status_index.write().unwrap();
@@ -243,6 +251,14 @@ impl Blockstore {
             && self
                 .perf_samples_cf
                 .compact_range(from_slot, to_slot)
+                .unwrap_or(false)
+            && self
+                .block_stats_cf
+                .compact_range(from_slot, to_slot)
+                .unwrap_or(false)
+            && self
+                .transactions_cf
+                .compact_range(from_slot, to_slot)
                 .unwrap_or(false);
         compact_timer.stop();
         if !result {
@@ -255,6 +271,276 @@ impl Blockstore {
         Ok(result)
     }
 
+    /// Deletes the column families of `column_group` in the range [from_slot,to_slot], leaving
+    /// the other column families untouched so that they can be retained for longer.
+    /// `ColumnGroup::TransactionStatus` is purged by primary index.
+    /// Dangerous; Use with care:
+    /// Does not check for integrity and does not update slot metas that refer to deleted slots
+    pub fn purge_column_group(&self, column_group: ColumnGroup, from_slot: Slot, to_slot: Slot) {
+        let mut purge_stats = PurgeStats::default();
+        let purge_result = self.run_purge_column_group_with_stats(
+            column_group,
+            from_slot,
+            to_slot,
+            &mut purge_stats,
+        );
+
+        datapoint_info!(
+            "blockstore-purge-column-group",
+            ("column_group", column_group.name().to_string(), String),
+            ("from_slot", from_slot as i64, i64),
+            ("to_slot", to_slot as i64, i64),
+            ("delete_range_us", purge_stats.delete_range as i64, i64),
+            ("write_batch_us", purge_stats.write_batch as i64, i64)
+        );
+        if let Err(e) = purge_result {
+            error!(
+                "Error: {:?}; Purge of {} failed in range {:?} to {:?}",
+                e,
+                column_group.name(),
+                from_slot,
+                to_slot
+            );
+        }
+    }
+
+    // Returns whether or not all columns of the group successfully purged the slot range
+    pub(crate) fn run_purge_column_group_with_stats(
+        &self,
+        column_group: ColumnGroup,
+        from_slot: Slot,
+        to_slot: Slot,
+        purge_stats: &mut PurgeStats,
+    ) -> Result<bool> {
+        let mut write_batch = self
+            .db
+            .batch()
+            .expect("Database Error: Failed to get write batch");
+        // delete range cf is not inclusive
+        let to_slot = to_slot.checked_add(1).unwrap_or_else(|| std::u64::MAX);
+
+        let mut delete_range_timer = Measure::start("delete_range");
+        let mut w_active_transaction_status_index =
+            self.active_transaction_status_index.write().unwrap();
+        let columns_purged = match column_group {
+            ColumnGroup::Shreds => {
+                self.db
+                    .delete_range_cf::<cf::SlotMeta>(&mut write_batch, from_slot, to_slot)
+                    .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::ShredData>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::ShredCode>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::DeadSlots>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::DuplicateSlots>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::ErasureMeta>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::Orphans>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::Index>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+            }
+            ColumnGroup::TransactionStatus => {
+                let mut columns_purged = self
+                    .db
+                    .delete_range_cf::<cf::Transactions>(&mut write_batch, from_slot, to_slot)
+                    .is_ok();
+                self.purge_special_columns_with_primary_index(
+                    &mut write_batch,
+                    &mut columns_purged,
+                    &mut w_active_transaction_status_index,
+                    to_slot,
+                )?;
+                columns_purged
+            }
+            ColumnGroup::Rewards => self
+                .db
+                .delete_range_cf::<cf::Rewards>(&mut write_batch, from_slot, to_slot)
+                .is_ok(),
+            ColumnGroup::BlockMetadata => {
+                self.db
+                    .delete_range_cf::<cf::Root>(&mut write_batch, from_slot, to_slot)
+                    .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::Blocktime>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::PerfSamples>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+                    & self
+                        .db
+                        .delete_range_cf::<cf::BlockStats>(&mut write_batch, from_slot, to_slot)
+                        .is_ok()
+            }
+        };
+        delete_range_timer.stop();
+        let mut write_timer = Measure::start("write_batch");
+        if let Err(e) = self.db.write(write_batch) {
+            error!(
+                "Error: {:?} while submitting write batch for {} at slot {:?}",
+                e,
+                column_group.name(),
+                from_slot
+            );
+            return Err(e);
+        }
+        write_timer.stop();
+        purge_stats.delete_range += delete_range_timer.as_us();
+        purge_stats.write_batch += write_timer.as_us();
+        Ok(columns_purged)
+    }
+
+    pub fn compact_column_group(
+        &self,
+        column_group: ColumnGroup,
+        from_slot: Slot,
+        to_slot: Slot,
+    ) -> Result<bool> {
+        if self.no_compaction {
+            info!("compact_column_group: compaction disabled");
+            return Ok(false);
+        }
+        info!(
+            "compact_column_group: {} from {} to {}",
+            column_group.name(),
+            from_slot,
+            to_slot
+        );
+        let mut compact_timer = Measure::start("compact_range");
+        let result = match column_group {
+            ColumnGroup::Shreds => {
+                self.meta_cf
+                    .compact_range(from_slot, to_slot)
+                    .unwrap_or(false)
+                    && self
+                        .data_shred_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .code_shred_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .dead_slots_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .duplicate_slots_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .erasure_meta_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .orphans_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .index_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+            }
+            ColumnGroup::TransactionStatus => {
+                self.transaction_status_cf
+                    .compact_range(0, 2)
+                    .unwrap_or(false)
+                    && self
+                        .address_signatures_cf
+                        .compact_range(0, 2)
+                        .unwrap_or(false)
+                    && self
+                        .transaction_status_index_cf
+                        .compact_range(0, 2)
+                        .unwrap_or(false)
+                    && self
+                        .transactions_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+            }
+            ColumnGroup::Rewards => self
+                .rewards_cf
+                .compact_range(from_slot, to_slot)
+                .unwrap_or(false),
+            ColumnGroup::BlockMetadata => {
+                self.db
+                    .column::<cf::Root>()
+                    .compact_range(from_slot, to_slot)
+                    .unwrap_or(false)
+                    && self
+                        .blocktime_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .perf_samples_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+                    && self
+                        .block_stats_cf
+                        .compact_range(from_slot, to_slot)
+                        .unwrap_or(false)
+            }
+        };
+        compact_timer.stop();
+        if !result {
+            info!("compact_column_group incomplete");
+        }
+        datapoint_info!(
+            "blockstore-compact-column-group",
+            ("column_group", column_group.name().to_string(), String),
+            ("compact_range_us", compact_timer.as_us() as i64, i64),
+        );
+        Ok(result)
+    }
+
+    /// Returns the on-disk size of the column families of `column_group`, in bytes
+    pub fn column_group_storage_size(&self, column_group: ColumnGroup) -> Result<u64> {
+        let sizes = match column_group {
+            ColumnGroup::Shreds => vec![
+                self.meta_cf.storage_size()?,
+                self.data_shred_cf.storage_size()?,
+                self.code_shred_cf.storage_size()?,
+                self.dead_slots_cf.storage_size()?,
+                self.duplicate_slots_cf.storage_size()?,
+                self.erasure_meta_cf.storage_size()?,
+                self.orphans_cf.storage_size()?,
+                self.index_cf.storage_size()?,
+            ],
+            ColumnGroup::TransactionStatus => vec![
+                self.transaction_status_cf.storage_size()?,
+                self.address_signatures_cf.storage_size()?,
+                self.transaction_status_index_cf.storage_size()?,
+                self.transactions_cf.storage_size()?,
+            ],
+            ColumnGroup::Rewards => vec![self.rewards_cf.storage_size()?],
+            ColumnGroup::BlockMetadata => vec![
+                self.db.column::<cf::Root>().storage_size()?,
+                self.blocktime_cf.storage_size()?,
+                self.perf_samples_cf.storage_size()?,
+                self.block_stats_cf.storage_size()?,
+            ],
+        };
+        Ok(sizes.into_iter().sum())
+    }
+
     /// Purges special columns (using a non-Slot primary-index) exactly, by deserializing each slot
     /// being purged and iterating through all transactions to determine the keys of individual
     /// records. **This method is very slow.**
@@ -275,16 +561,18 @@ impl Blockstore {
             {
                 batch.delete::<cf::TransactionStatus>((0, transaction.signatures[0], slot))?;
                 batch.delete::<cf::TransactionStatus>((1, transaction.signatures[0], slot))?;
-                for pubkey in transaction.message.account_keys {
+                // Records of addresses loaded from lookup tables are only removed once their
+                // primary index is purged
+                for pubkey in transaction.message.static_account_keys() {
                     batch.delete::<cf::AddressSignatures>((
                         0,
-                        pubkey,
+                        *pubkey,
                         slot,
                         transaction.signatures[0],
                     ))?;
                     batch.delete::<cf::AddressSignatures>((
                         1,
-                        pubkey,
+                        *pubkey,
                         slot,
                         transaction.signatures[0],
                     ))?;
@@ -455,6 +743,49 @@ pub mod tests {
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_purge_column_group() {
+        let blockstore_path = get_tmp_ledger_path!();
+        let blockstore = Blockstore::open(&blockstore_path).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 20, 5);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        let roots: Vec<Slot> = (0..20).collect();
+        blockstore.set_roots(&roots).unwrap();
+        for slot in 0..20 {
+            blockstore.write_rewards(slot, vec![]).unwrap();
+            blockstore
+                .cache_block_time(slot, slot as UnixTimestamp)
+                .unwrap();
+        }
+
+        blockstore.purge_column_group(ColumnGroup::Shreds, 0, 9);
+        assert!(blockstore
+            .slot_meta_iterator(0)
+            .unwrap()
+            .all(|(slot, _)| slot >= 10));
+        assert!(blockstore.read_rewards(5).unwrap().is_some());
+        assert!(blockstore.is_root(5));
+
+        blockstore.purge_column_group(ColumnGroup::Rewards, 0, 14);
+        assert!(blockstore.read_rewards(14).unwrap().is_none());
+        assert!(blockstore.read_rewards(15).unwrap().is_some());
+        assert!(blockstore.is_root(14));
+
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(12).unwrap(),
+            Some(11)
+        );
+        blockstore.purge_column_group(ColumnGroup::BlockMetadata, 0, 9);
+        test_all_empty_or_min(&blockstore, 10);
+        assert_eq!(
+            blockstore.get_last_slot_before_block_time(10).unwrap(),
+            None
+        );
+
+        drop(blockstore);
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_purge_huge() {
         let blockstore_path = get_tmp_ledger_path!();
@@ -15,6 +15,7 @@ use solana_sdk::{
     clock::{Slot, UnixTimestamp},
     pubkey::Pubkey,
     signature::Signature,
+    transaction::VersionedTransaction,
 };
 use solana_storage_proto::convert::generated;
 use solana_transaction_status::TransactionStatusMeta;
@@ -56,6 +57,10 @@ const REWARDS_CF: &str = "rewards";
 const BLOCKTIME_CF: &str = "blocktime";
 /// Column family for Performance Samples
 const PERF_SAMPLES_CF: &str = "perf_samples";
+/// Column family for Block Statistics
+const BLOCK_STATS_CF: &str = "block_stats";
+/// Column family for Transactions, kept so they outlive the shreds they were read from
+const TRANSACTIONS_CF: &str = "transactions";
 
 #[derive(Error, Debug)]
 pub enum BlockstoreError {
@@ -150,6 +155,14 @@ pub mod columns {
     #[derive(Debug)]
     /// The performance samples column
     pub struct PerfSamples;
+
+    #[derive(Debug)]
+    /// The block statistics column
+    pub struct BlockStats;
+
+    #[derive(Debug)]
+    /// The transactions column
+    pub struct Transactions;
 }
 
 pub enum AccessType {
@@ -210,9 +223,9 @@ impl Rocks {
         recovery_mode: Option<BlockstoreRecoveryMode>,
     ) -> Result<Rocks> {
         use columns::{
-            AddressSignatures, Blocktime, DeadSlots, DuplicateSlots, ErasureMeta, Index, Orphans,
-            PerfSamples, Rewards, Root, ShredCode, ShredData, SlotMeta, TransactionStatus,
-            TransactionStatusIndex,
+            AddressSignatures, BlockStats, Blocktime, DeadSlots, DuplicateSlots, ErasureMeta,
+            Index, Orphans, PerfSamples, Rewards, Root, ShredCode, ShredData, SlotMeta,
+            TransactionStatus, TransactionStatusIndex, Transactions,
         };
 
         fs::create_dir_all(&path)?;
@@ -257,6 +270,10 @@ impl Rocks {
             ColumnFamilyDescriptor::new(Blocktime::NAME, get_cf_options(&access_type));
         let perf_samples_cf_descriptor =
             ColumnFamilyDescriptor::new(PerfSamples::NAME, get_cf_options(&access_type));
+        let block_stats_cf_descriptor =
+            ColumnFamilyDescriptor::new(BlockStats::NAME, get_cf_options(&access_type));
+        let transactions_cf_descriptor =
+            ColumnFamilyDescriptor::new(Transactions::NAME, get_cf_options(&access_type));
 
         let cfs = vec![
             (SlotMeta::NAME, meta_cf_descriptor),
@@ -277,6 +294,8 @@ impl Rocks {
             (Rewards::NAME, rewards_cf_descriptor),
             (Blocktime::NAME, blocktime_cf_descriptor),
             (PerfSamples::NAME, perf_samples_cf_descriptor),
+            (BlockStats::NAME, block_stats_cf_descriptor),
+            (Transactions::NAME, transactions_cf_descriptor),
         ];
 
         // Open the database
@@ -314,9 +333,9 @@ impl Rocks {
 
     fn columns(&self) -> Vec<&'static str> {
         use columns::{
-            AddressSignatures, Blocktime, DeadSlots, DuplicateSlots, ErasureMeta, Index, Orphans,
-            PerfSamples, Rewards, Root, ShredCode, ShredData, SlotMeta, TransactionStatus,
-            TransactionStatusIndex,
+            AddressSignatures, BlockStats, Blocktime, DeadSlots, DuplicateSlots, ErasureMeta,
+            Index, Orphans, PerfSamples, Rewards, Root, ShredCode, ShredData, SlotMeta,
+            TransactionStatus, TransactionStatusIndex, Transactions,
         };
 
         vec![
@@ -335,6 +354,8 @@ impl Rocks {
             Rewards::NAME,
             Blocktime::NAME,
             PerfSamples::NAME,
+            BlockStats::NAME,
+            Transactions::NAME,
         ]
     }
 
@@ -399,6 +420,10 @@ impl Rocks {
     fn is_primary_access(&self) -> bool {
         self.1 == ActualAccessType::Primary
     }
+
+    fn property_int_value_cf(&self, cf: &ColumnFamily, name: &str) -> Result<Option<u64>> {
+        Ok(self.0.property_int_value_cf(cf, name)?)
+    }
 }
 
 pub trait Column {
@@ -580,6 +605,46 @@ impl TypedColumn for columns::PerfSamples {
     type Type = blockstore_meta::PerfSample;
 }
 
+impl SlotColumn for columns::BlockStats {}
+impl ColumnName for columns::BlockStats {
+    const NAME: &'static str = BLOCK_STATS_CF;
+}
+impl TypedColumn for columns::BlockStats {
+    type Type = blockstore_meta::BlockStats;
+}
+
+impl Column for columns::Transactions {
+    type Index = (Slot, Signature);
+
+    fn key((slot, signature): (Slot, Signature)) -> Vec<u8> {
+        let mut key = vec![0; 8 + 64]; // size_of Slot + size_of Signature
+        BigEndian::write_u64(&mut key[0..8], slot);
+        key[8..72].clone_from_slice(&signature.as_ref()[0..64]);
+        key
+    }
+
+    fn index(key: &[u8]) -> (Slot, Signature) {
+        let slot = BigEndian::read_u64(&key[0..8]);
+        let signature = Signature::new(&key[8..72]);
+        (slot, signature)
+    }
+
+    fn primary_index(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, Signature::default())
+    }
+}
+
+impl ColumnName for columns::Transactions {
+    const NAME: &'static str = TRANSACTIONS_CF;
+}
+impl TypedColumn for columns::Transactions {
+    type Type = VersionedTransaction;
+}
+
 impl Column for columns::ShredCode {
     type Index = (u64, u64);
 
@@ -888,6 +953,15 @@ where
         Ok(end)
     }
 
+    /// Returns the total size of the column family's SST files, in bytes. Data still held in
+    /// memtables is not accounted for
+    pub fn storage_size(&self) -> Result<u64> {
+        Ok(self
+            .backend
+            .property_int_value_cf(self.handle(), "rocksdb.total-sst-files-size")?
+            .unwrap_or_default())
+    }
+
     pub fn compact_range(&self, from: Slot, to: Slot) -> Result<bool>
     where
         C::Index: PartialOrd + Copy,
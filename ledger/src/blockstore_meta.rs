@@ -250,6 +250,37 @@ pub struct PerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// Per-block statistics, recorded once the block's bank is frozen
+pub struct BlockStats {
+    /// Number of transactions committed to the block, including failed ones
+    pub num_transactions: u64,
+    /// Number of committed transactions whose execution failed
+    pub num_failed_transactions: u64,
+    /// Number of committed simple vote transactions
+    pub num_vote_transactions: u64,
+    /// Compute units consumed by every transaction in the block
+    pub compute_units_consumed: u64,
+    /// Transaction fees collected, before burning
+    pub fees: u64,
+    /// Number of entries that carry transactions
+    pub num_entries: u64,
+    /// Number of tick entries
+    pub num_ticks: u64,
+}
+
+impl BlockStats {
+    pub fn num_successful_transactions(&self) -> u64 {
+        self.num_transactions
+            .saturating_sub(self.num_failed_transactions)
+    }
+
+    pub fn num_non_vote_transactions(&self) -> u64 {
+        self.num_transactions
+            .saturating_sub(self.num_vote_transactions)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -16,8 +16,9 @@ use solana_metrics::{datapoint_error, inc_new_counter_debug};
 use solana_rayon_threadlimit::get_thread_count;
 use solana_runtime::{
     bank::{
-        Bank, InnerInstructionsList, TransactionBalancesSet, TransactionLogMessages,
-        TransactionProcessResult, TransactionResults,
+        Bank, InnerInstructionsList, TransactionBalancesSet, TransactionLogEvents,
+        TransactionLogMessages, TransactionProcessResult, TransactionResults,
+        TransactionReturnData,
     },
     bank_forks::BankForks,
     bank_utils,
@@ -34,7 +35,7 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     timing::duration_as_ms,
-    transaction::{Result, Transaction, TransactionError},
+    transaction::{Result, Transaction, TransactionError, VersionedTransaction},
 };
 use std::{
     cell::RefCell,
@@ -98,18 +99,25 @@ fn get_first_error(
 
 fn execute_batch(
     batch: &TransactionBatch,
+    versioned_transactions: &[VersionedTransaction],
     bank: &Arc<Bank>,
     transaction_status_sender: Option<TransactionStatusSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
 ) -> Result<()> {
-    let (tx_results, balances, inner_instructions, transaction_logs) =
-        batch.bank().load_execute_and_commit_transactions(
-            batch,
-            MAX_PROCESSING_AGE,
-            transaction_status_sender.is_some(),
-            transaction_status_sender.is_some(),
-            transaction_status_sender.is_some(),
-        );
+    let (
+        tx_results,
+        balances,
+        inner_instructions,
+        transaction_logs,
+        transaction_log_events,
+        transaction_return_data,
+    ) = batch.bank().load_execute_and_commit_transactions(
+        batch,
+        MAX_PROCESSING_AGE,
+        transaction_status_sender.is_some(),
+        transaction_status_sender.is_some(),
+        transaction_status_sender.is_some(),
+    );
 
     bank_utils::find_and_send_votes(batch.transactions(), &tx_results, replay_vote_sender);
 
@@ -123,11 +131,14 @@ fn execute_batch(
         send_transaction_status_batch(
             bank.clone(),
             batch.transactions(),
+            versioned_transactions,
             batch.iteration_order_vec(),
             processing_results,
             balances,
             inner_instructions,
             transaction_logs,
+            transaction_log_events,
+            transaction_return_data,
             sender,
         );
     }
@@ -138,7 +149,7 @@ fn execute_batch(
 
 fn execute_batches(
     bank: &Arc<Bank>,
-    batches: &[TransactionBatch],
+    batches: &[(TransactionBatch, &[VersionedTransaction])],
     entry_callback: Option<&ProcessCallback>,
     transaction_status_sender: Option<TransactionStatusSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
@@ -148,13 +159,22 @@ fn execute_batches(
         thread_pool.borrow().install(|| {
             batches
                 .into_par_iter()
-                .map_with(transaction_status_sender, |sender, batch| {
-                    let result = execute_batch(batch, bank, sender.clone(), replay_vote_sender);
-                    if let Some(entry_callback) = entry_callback {
-                        entry_callback(bank);
-                    }
-                    result
-                })
+                .map_with(
+                    transaction_status_sender,
+                    |sender, (batch, versioned_transactions)| {
+                        let result = execute_batch(
+                            batch,
+                            versioned_transactions,
+                            bank,
+                            sender.clone(),
+                            replay_vote_sender,
+                        );
+                        if let Some(entry_callback) = entry_callback {
+                            entry_callback(bank);
+                        }
+                        result
+                    },
+                )
                 .collect()
         })
     });
@@ -192,11 +212,26 @@ fn process_entries_with_callback(
     transaction_status_sender: Option<TransactionStatusSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
 ) -> Result<()> {
+    // Resolve the address table lookups of every transaction up front, as the batches below
+    // borrow the resolved transactions until they are executed.  Lookup tables are read as of
+    // the parent bank, so executing the earlier entries doesn't change how later ones resolve.
+    let resolved_transactions = entries
+        .iter()
+        .map(|entry| {
+            entry
+                .transactions
+                .iter()
+                .map(|transaction| bank.resolve_versioned_transaction(transaction.clone()))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
     // accumulator for entries that can be processed in parallel
     let mut batches = vec![];
     let mut tick_hashes = vec![];
-    for entry in entries {
+    for (entry, transactions) in entries.iter().zip(&resolved_transactions) {
         if entry.is_tick() {
+            bank.inc_tick_entries_count(1);
             // If it's a tick, save it for later
             tick_hashes.push(entry.hash);
             if bank.is_block_boundary(bank.tick_height() + tick_hashes.len() as u64) {
@@ -217,10 +252,11 @@ fn process_entries_with_callback(
             }
             continue;
         }
+        bank.inc_transaction_entries_count(1);
         // else loop on processing the entry
         loop {
             let iteration_order = if randomize {
-                let mut iteration_order: Vec<usize> = (0..entry.transactions.len()).collect();
+                let mut iteration_order: Vec<usize> = (0..transactions.len()).collect();
                 iteration_order.shuffle(&mut thread_rng());
                 Some(iteration_order)
             } else {
@@ -228,13 +264,13 @@ fn process_entries_with_callback(
             };
 
             // try to lock the accounts
-            let batch = bank.prepare_batch(&entry.transactions, iteration_order);
+            let batch = bank.prepare_batch(transactions, iteration_order);
 
             let first_lock_err = first_err(batch.lock_results());
 
             // if locking worked
             if first_lock_err.is_ok() {
-                batches.push(batch);
+                batches.push((batch, &entry.transactions[..]));
                 // done with this entry
                 break;
             }
@@ -659,6 +695,7 @@ pub fn confirm_slot(
             &progress.last_entry,
             recyclers.clone(),
             bank.secp256k1_program_enabled(),
+            bank.ed25519_program_enabled(),
         );
         if entry_state.status() == EntryVerificationStatus::Failure {
             warn!("Ledger proof of history failed at slot: {}", slot);
@@ -1027,12 +1064,17 @@ fn process_single_slot(
 
 pub struct TransactionStatusBatch {
     pub bank: Arc<Bank>,
+    /// The transactions with the address table lookups of their messages resolved
     pub transactions: Vec<Transaction>,
+    /// The same transactions as they were signed and recorded in the ledger
+    pub versioned_transactions: Vec<VersionedTransaction>,
     pub iteration_order: Option<Vec<usize>>,
     pub statuses: Vec<TransactionProcessResult>,
     pub balances: TransactionBalancesSet,
     pub inner_instructions: Vec<Option<InnerInstructionsList>>,
     pub transaction_logs: Vec<TransactionLogMessages>,
+    pub transaction_log_events: Vec<TransactionLogEvents>,
+    pub transaction_return_data: Vec<Option<TransactionReturnData>>,
 }
 
 pub type TransactionStatusSender = Sender<TransactionStatusBatch>;
@@ -1040,22 +1082,28 @@ pub type TransactionStatusSender = Sender<TransactionStatusBatch>;
 pub fn send_transaction_status_batch(
     bank: Arc<Bank>,
     transactions: &[Transaction],
+    versioned_transactions: &[VersionedTransaction],
     iteration_order: Option<Vec<usize>>,
     statuses: Vec<TransactionProcessResult>,
     balances: TransactionBalancesSet,
     inner_instructions: Vec<Option<InnerInstructionsList>>,
     transaction_logs: Vec<TransactionLogMessages>,
+    transaction_log_events: Vec<TransactionLogEvents>,
+    transaction_return_data: Vec<Option<TransactionReturnData>>,
     transaction_status_sender: TransactionStatusSender,
 ) {
     let slot = bank.slot();
     if let Err(e) = transaction_status_sender.send(TransactionStatusBatch {
         bank,
         transactions: transactions.to_vec(),
+        versioned_transactions: versioned_transactions.to_vec(),
         iteration_order,
         statuses,
         balances,
         inner_instructions,
         transaction_logs,
+        transaction_log_events,
+        transaction_return_data,
     }) {
         trace!(
             "Slot {} transaction_status send batch failed: {:?}",
@@ -1100,7 +1148,7 @@ pub fn fill_blockstore_slot_with_ticks(
 pub mod tests {
     use super::*;
     use crate::{
-        entry::{create_ticks, next_entry, next_entry_mut},
+        entry::{create_ticks, next_entry, next_entry_mut, next_hash},
         genesis_utils::{
             create_genesis_config, create_genesis_config_with_leader, GenesisConfigInfo,
         },
@@ -1117,7 +1165,7 @@ pub mod tests {
         hash::Hash,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
-        system_instruction::SystemError,
+        system_instruction::{self, SystemError},
         system_transaction,
         transaction::{Transaction, TransactionError},
     };
@@ -2006,6 +2054,8 @@ pub mod tests {
         let tick = next_entry(&genesis_config.hash(), 1, vec![]);
         assert_eq!(process_entries(&bank, &[tick], true, None, None), Ok(()));
         assert_eq!(bank.tick_height(), 1);
+        assert_eq!(bank.tick_entries_count(), 1);
+        assert_eq!(bank.transaction_entries_count(), 0);
     }
 
     #[test]
@@ -2179,8 +2229,15 @@ pub mod tests {
         assert_eq!(bank.get_balance(&keypair2.pubkey()), 4);
 
         // Check all accounts are unlocked
-        let txs1 = &entry_1_to_mint.transactions[..];
-        let txs2 = &entry_2_to_3_mint_to_1.transactions[..];
+        let legacy_transactions = |entry: Entry| -> Vec<Transaction> {
+            entry
+                .transactions
+                .into_iter()
+                .map(|tx| tx.into_legacy_transaction().unwrap())
+                .collect()
+        };
+        let txs1 = &legacy_transactions(entry_1_to_mint)[..];
+        let txs2 = &legacy_transactions(entry_2_to_3_mint_to_1)[..];
         let batch1 = bank.prepare_batch(txs1, None);
         for result in batch1.lock_results() {
             assert!(result.is_ok());
@@ -2338,6 +2395,60 @@ pub mod tests {
         assert_eq!(bank.last_blockhash(), blockhash);
     }
 
+    #[test]
+    fn test_process_entries_versioned_transaction() {
+        use solana_address_lookup_table_program::lookup_table_state::{
+            AddressLookupTable, LookupTableState,
+        };
+        use solana_sdk::{
+            account_utils::StateMut,
+            message::{MessageV0, VersionedMessage},
+        };
+
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1000);
+        let parent = Arc::new(Bank::new(&genesis_config));
+
+        let to = solana_sdk::pubkey::new_rand();
+        let table_key = solana_sdk::pubkey::new_rand();
+        let mut table_account = Account::new(
+            1,
+            AddressLookupTable::space(1) as usize,
+            &solana_address_lookup_table_program::id(),
+        );
+        table_account
+            .set_state(&LookupTableState::Initialized(AddressLookupTable {
+                authority: None,
+                addresses: vec![to],
+            }))
+            .unwrap();
+        parent.store_account(&table_key, &table_account);
+        let bank = Arc::new(Bank::new_from_parent(&parent, &Pubkey::default(), 1));
+
+        let blockhash = bank.last_blockhash();
+        let mut message = MessageV0::new_with_lookup_tables(
+            &[system_instruction::transfer(&mint_keypair.pubkey(), &to, 1)],
+            Some(&mint_keypair.pubkey()),
+            &[(table_key, vec![to])],
+        );
+        message.recent_blockhash = blockhash;
+        let tx =
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&mint_keypair]).unwrap();
+        let transactions = vec![tx.clone()];
+        let entry = Entry {
+            num_hashes: 1,
+            hash: next_hash(&blockhash, 1, &transactions),
+            transactions,
+        };
+
+        assert_eq!(process_entries(&bank, &[entry], true, None, None), Ok(()));
+        assert_eq!(bank.get_balance(&to), 1);
+        assert_eq!(bank.get_signature_status(&tx.signatures[0]), Some(Ok(())));
+    }
+
     #[test]
     fn test_process_entry_tx_random_execution_with_error() {
         let GenesisConfigInfo {
@@ -2513,6 +2624,9 @@ pub mod tests {
         );
         assert_eq!(bank.get_balance(&keypair3.pubkey()), 1);
         assert_eq!(bank.get_balance(&keypair4.pubkey()), 1);
+        // Ticks registered directly on the bank are not entries
+        assert_eq!(bank.tick_entries_count(), 1);
+        assert_eq!(bank.transaction_entries_count(), 2);
 
         // ensure that an error is returned for an empty account (keypair2)
         let tx =
@@ -2901,6 +3015,8 @@ pub mod tests {
             _balances,
             _inner_instructions,
             _log_messages,
+            _log_events,
+            _return_data,
         ) = batch.bank().load_execute_and_commit_transactions(
             &batch,
             MAX_PROCESSING_AGE,
@@ -19,7 +19,7 @@ use solana_perf::recycler::Recycler;
 use solana_rayon_threadlimit::get_thread_count;
 use solana_sdk::hash::Hash;
 use solana_sdk::timing;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
 use std::cell::RefCell;
 use std::ffi::OsStr;
 use std::sync::mpsc::{Receiver, Sender};
@@ -114,12 +114,13 @@ pub struct Entry {
     /// An unordered list of transactions that were observed before the Entry ID was
     /// generated. They may have been observed before a previous Entry ID but were
     /// pushed back into this list to ensure deterministic interpretation of the ledger.
-    pub transactions: Vec<Transaction>,
+    pub transactions: Vec<VersionedTransaction>,
 }
 
 impl Entry {
     /// Creates the next Entry `num_hashes` after `start_hash`.
     pub fn new(prev_hash: &Hash, mut num_hashes: u64, transactions: Vec<Transaction>) -> Self {
+        let transactions: Vec<_> = transactions.into_iter().map(Into::into).collect();
         // If you passed in transactions, but passed in num_hashes == 0, then
         // next_hash will generate the next hash and set num_hashes == 1
         if num_hashes == 0 && !transactions.is_empty() {
@@ -174,7 +175,7 @@ impl Entry {
     }
 }
 
-pub fn hash_transactions(transactions: &[Transaction]) -> Hash {
+pub fn hash_transactions(transactions: &[VersionedTransaction]) -> Hash {
     // a hash of a slice of transactions only needs to hash the signatures
     let signatures: Vec<_> = transactions
         .iter()
@@ -192,7 +193,11 @@ pub fn hash_transactions(transactions: &[Transaction]) -> Hash {
 /// a signature, the final hash will be a hash of both the previous ID and
 /// the signature.  If num_hashes is zero and there's no transaction data,
 ///  start_hash is returned.
-pub fn next_hash(start_hash: &Hash, num_hashes: u64, transactions: &[Transaction]) -> Hash {
+pub fn next_hash(
+    start_hash: &Hash,
+    num_hashes: u64,
+    transactions: &[VersionedTransaction],
+) -> Hash {
     if num_hashes == 0 && transactions.is_empty() {
         return *start_hash;
     }
@@ -328,6 +333,7 @@ pub trait EntrySlice {
         start_hash: &Hash,
         recyclers: VerifyRecyclers,
         secp256k1_program_enabled: bool,
+        ed25519_program_enabled: bool,
     ) -> EntryVerificationState;
     fn verify(&self, start_hash: &Hash) -> bool;
     /// Checks that each entry tick has the correct number of hashes. Entry slices do not
@@ -336,12 +342,16 @@ pub trait EntrySlice {
     fn verify_tick_hash_count(&self, tick_hash_count: &mut u64, hashes_per_tick: u64) -> bool;
     /// Counts tick entries
     fn tick_count(&self) -> u64;
-    fn verify_transaction_signatures(&self, secp256k1_program_enabled: bool) -> bool;
+    fn verify_transaction_signatures(
+        &self,
+        secp256k1_program_enabled: bool,
+        ed25519_program_enabled: bool,
+    ) -> bool;
 }
 
 impl EntrySlice for [Entry] {
     fn verify(&self, start_hash: &Hash) -> bool {
-        self.start_verify(start_hash, VerifyRecyclers::default(), true)
+        self.start_verify(start_hash, VerifyRecyclers::default(), true, true)
             .finish_verify(self)
     }
 
@@ -489,15 +499,24 @@ impl EntrySlice for [Entry] {
         }
     }
 
-    fn verify_transaction_signatures(&self, secp256k1_program_enabled: bool) -> bool {
+    fn verify_transaction_signatures(
+        &self,
+        secp256k1_program_enabled: bool,
+        ed25519_program_enabled: bool,
+    ) -> bool {
         PAR_THREAD_POOL.with(|thread_pool| {
             thread_pool.borrow().install(|| {
                 self.par_iter().all(|e| {
                     e.transactions.par_iter().all(|transaction| {
                         let sig_verify = transaction.verify().is_ok();
                         if sig_verify
-                            && secp256k1_program_enabled
-                            && transaction.verify_precompiles().is_err()
+                            && (secp256k1_program_enabled || ed25519_program_enabled)
+                            && transaction
+                                .verify_precompiles(
+                                    secp256k1_program_enabled,
+                                    ed25519_program_enabled,
+                                )
+                                .is_err()
                         {
                             return false;
                         }
@@ -513,9 +532,11 @@ impl EntrySlice for [Entry] {
         start_hash: &Hash,
         recyclers: VerifyRecyclers,
         secp256k1_program_enabled: bool,
+        ed25519_program_enabled: bool,
     ) -> EntryVerificationState {
         let start = Instant::now();
-        let res = self.verify_transaction_signatures(secp256k1_program_enabled);
+        let res =
+            self.verify_transaction_signatures(secp256k1_program_enabled, ed25519_program_enabled);
         let transaction_duration_us = timing::duration_as_us(&start.elapsed());
         if !res {
             return EntryVerificationState {
@@ -676,6 +697,7 @@ pub fn create_random_ticks(num_ticks: u64, max_hashes_per_tick: u64, mut hash: H
 /// Creates the next Tick or Transaction Entry `num_hashes` after `start_hash`.
 pub fn next_entry(prev_hash: &Hash, num_hashes: u64, transactions: Vec<Transaction>) -> Entry {
     assert!(num_hashes > 0 || transactions.is_empty());
+    let transactions: Vec<_> = transactions.into_iter().map(Into::into).collect();
     Entry {
         num_hashes,
         hash: next_hash(prev_hash, num_hashes, &transactions),
@@ -742,8 +764,8 @@ mod tests {
         assert!(e0.verify(&zero));
 
         // Next, swap two transactions and ensure verification fails.
-        e0.transactions[0] = tx1; // <-- attack
-        e0.transactions[1] = tx0;
+        e0.transactions[0] = tx1.into(); // <-- attack
+        e0.transactions[1] = tx0.into();
         assert!(!e0.verify(&zero));
     }
 
@@ -781,6 +803,60 @@ mod tests {
         assert!(e0.verify(&zero));
     }
 
+    #[test]
+    fn test_transaction_ed25519_precompile() {
+        use solana_sdk::ed25519_instruction::new_ed25519_instruction;
+        let zero = Hash::default();
+
+        let keypair = Keypair::new();
+        let mut instruction = new_ed25519_instruction(&keypair, b"hello");
+        // Point the signature at a message other than the one signed
+        let last = instruction.data.len() - 1;
+        instruction.data[last] ^= 0xff;
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            zero,
+        );
+        let entries = vec![Entry::new(&zero, 0, vec![tx])];
+        assert!(entries.verify_transaction_signatures(false, false));
+        // Only the ed25519 program is enabled
+        assert!(!entries.verify_transaction_signatures(false, true));
+        assert!(!entries.verify_transaction_signatures(true, true));
+    }
+
+    #[test]
+    fn test_versioned_transaction_signing() {
+        use solana_sdk::{
+            message::{MessageV0, VersionedMessage},
+            pubkey::Pubkey,
+            system_instruction,
+        };
+        let zero = Hash::default();
+
+        let keypair = Keypair::new();
+        let to = Pubkey::new_unique();
+        let message = MessageV0::new_with_lookup_tables(
+            &[system_instruction::transfer(&keypair.pubkey(), &to, 1)],
+            Some(&keypair.pubkey()),
+            &[(Pubkey::new_unique(), vec![to])],
+        );
+        let tx = VersionedTransaction::try_new(VersionedMessage::V0(message), &[&keypair]).unwrap();
+        let mut e0 = vec![Entry {
+            num_hashes: 1,
+            hash: next_hash(&zero, 1, &[tx.clone()]),
+            transactions: vec![tx],
+        }];
+        assert!(e0.verify(&zero));
+
+        // The signature covers the address table lookups of the versioned message
+        if let VersionedMessage::V0(message) = &mut e0[0].transactions[0].message {
+            message.address_table_lookups[0].account_key = Pubkey::new_unique();
+        }
+        assert!(!e0.verify_transaction_signatures(false, false));
+    }
+
     #[test]
     fn test_witness_reorder_attack() {
         let zero = Hash::default();
@@ -793,8 +869,8 @@ mod tests {
         assert!(e0.verify(&zero));
 
         // Next, swap two witness transactions and ensure verification fails.
-        e0.transactions[0] = tx1; // <-- attack
-        e0.transactions[1] = tx0;
+        e0.transactions[0] = tx1.into(); // <-- attack
+        e0.transactions[1] = tx0.into();
         assert!(!e0.verify(&zero));
     }
 
@@ -813,7 +889,7 @@ mod tests {
         let tx0 = create_sample_timestamp(&keypair, zero);
         let entry0 = next_entry(&zero, 1, vec![tx0.clone()]);
         assert_eq!(entry0.num_hashes, 1);
-        assert_eq!(entry0.hash, next_hash(&zero, 1, &[tx0]));
+        assert_eq!(entry0.hash, next_hash(&zero, 1, &[tx0.into()]));
     }
 
     #[test]
@@ -902,26 +978,26 @@ mod tests {
         let tx = Transaction::default();
 
         let no_hash_tx_entry = Entry {
-            transactions: vec![tx.clone()],
+            transactions: vec![tx.clone().into()],
             ..Entry::default()
         };
         let single_hash_tx_entry = Entry {
-            transactions: vec![tx.clone()],
+            transactions: vec![tx.clone().into()],
             num_hashes: 1,
             ..Entry::default()
         };
         let partial_tx_entry = Entry {
             num_hashes: hashes_per_tick - 1,
-            transactions: vec![tx.clone()],
+            transactions: vec![tx.clone().into()],
             ..Entry::default()
         };
         let full_tx_entry = Entry {
             num_hashes: hashes_per_tick,
-            transactions: vec![tx.clone()],
+            transactions: vec![tx.clone().into()],
             ..Entry::default()
         };
         let max_hash_tx_entry = Entry {
-            transactions: vec![tx],
+            transactions: vec![tx.into()],
             num_hashes: u64::MAX,
             ..Entry::default()
         };
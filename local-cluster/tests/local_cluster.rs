@@ -16,6 +16,7 @@ use solana_core::{
     consensus::{Tower, SWITCH_FORK_THRESHOLD, VOTE_THRESHOLD_DEPTH},
     gossip_service::discover_cluster,
     optimistic_confirmation_verifier::OptimisticConfirmationVerifier,
+    tower_storage::FileTowerStorage,
     validator::ValidatorConfig,
 };
 use solana_download_utils::download_snapshot;
@@ -1049,7 +1050,7 @@ fn test_snapshot_download() {
 
     // Download the snapshot, then boot a validator from it.
     download_snapshot(
-        &cluster.entry_point_info.rpc,
+        &[cluster.entry_point_info.rpc],
         &validator_archive_path,
         archive_snapshot_hash,
         false,
@@ -1545,6 +1546,7 @@ fn test_validator_saves_tower() {
         .info
         .ledger_path
         .clone();
+    let tower_storage = FileTowerStorage::new(ledger_path.clone());
 
     // Wait for some votes to be generated
     let mut last_replayed_root;
@@ -1562,7 +1564,7 @@ fn test_validator_saves_tower() {
 
     // Stop validator and check saved tower
     let validator_info = cluster.exit_node(&validator_id);
-    let tower1 = Tower::restore(&ledger_path, &validator_id).unwrap();
+    let tower1 = Tower::restore(&tower_storage, &validator_id).unwrap();
     trace!("tower1: {:?}", tower1);
     assert_eq!(tower1.root(), 0);
 
@@ -1587,14 +1589,16 @@ fn test_validator_saves_tower() {
         .get_slot_with_commitment(CommitmentConfig::recent())
         .unwrap();
     let validator_info = cluster.exit_node(&validator_id);
-    let tower2 = Tower::restore(&ledger_path, &validator_id).unwrap();
+    let tower2 = Tower::restore(&tower_storage, &validator_id).unwrap();
     trace!("tower2: {:?}", tower2);
     assert_eq!(tower2.root(), last_replayed_root);
     last_replayed_root = recent_slot;
 
     // Rollback saved tower to `tower1` to simulate a validator starting from a newer snapshot
     // without having to wait for that snapshot to be generated in this test
-    tower1.save(&validator_identity_keypair).unwrap();
+    tower1
+        .save(&tower_storage, &validator_identity_keypair)
+        .unwrap();
 
     cluster.restart_node(&validator_id, validator_info);
     let validator_client = cluster.get_validator_client(&validator_id).unwrap();
@@ -1616,7 +1620,7 @@ fn test_validator_saves_tower() {
 
     // Check the new root is reflected in the saved tower state
     let mut validator_info = cluster.exit_node(&validator_id);
-    let tower3 = Tower::restore(&ledger_path, &validator_id).unwrap();
+    let tower3 = Tower::restore(&tower_storage, &validator_id).unwrap();
     trace!("tower3: {:?}", tower3);
     assert!(tower3.root() > last_replayed_root);
 
@@ -1644,7 +1648,7 @@ fn test_validator_saves_tower() {
 
     cluster.close_preserve_ledgers();
 
-    let tower4 = Tower::restore(&ledger_path, &validator_id).unwrap();
+    let tower4 = Tower::restore(&tower_storage, &validator_id).unwrap();
     trace!("tower4: {:?}", tower4);
     // should tower4 advance 1 slot compared to tower3????
     assert_eq!(tower4.root(), tower3.root() + 1);
@@ -1664,7 +1668,8 @@ fn purge_slots(blockstore: &Blockstore, start_slot: Slot, slot_count: Slot) {
 }
 
 fn restore_tower(ledger_path: &Path, node_pubkey: &Pubkey) -> Option<Tower> {
-    let tower = Tower::restore(&ledger_path, &node_pubkey);
+    let tower_storage = FileTowerStorage::new(ledger_path.to_path_buf());
+    let tower = Tower::restore(&tower_storage, &node_pubkey);
     if let Err(tower_err) = tower {
         if tower_err.is_file_missing() {
             return None;
@@ -1673,7 +1678,7 @@ fn restore_tower(ledger_path: &Path, node_pubkey: &Pubkey) -> Option<Tower> {
         }
     }
     // actually saved tower must have at least one vote.
-    Tower::restore(&ledger_path, &node_pubkey).ok()
+    Tower::restore(&tower_storage, &node_pubkey).ok()
 }
 
 fn last_vote_in_tower(ledger_path: &Path, node_pubkey: &Pubkey) -> Option<Slot> {
@@ -1685,7 +1690,8 @@ fn root_in_tower(ledger_path: &Path, node_pubkey: &Pubkey) -> Option<Slot> {
 }
 
 fn remove_tower(ledger_path: &Path, node_pubkey: &Pubkey) {
-    fs::remove_file(Tower::get_filename(&ledger_path, &node_pubkey)).unwrap();
+    let tower_storage = FileTowerStorage::new(ledger_path.to_path_buf());
+    fs::remove_file(tower_storage.filename(&node_pubkey)).unwrap();
 }
 
 // A bit convoluted test case; but this roughly follows this test theoretical scenario:
@@ -17,10 +17,233 @@
 /// ```bash
 /// export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'
 /// ```
+///
+/// To post every notification as JSON to a generic webhook, define the URL and optionally a
+/// template for the body. String values of the template may contain `{{id}}`, `{{severity}}`,
+/// `{{title}}`, `{{details}}`, `{{text}}` and `{{status}}` (`triggered` or `resolved`), and a
+/// value of exactly `"{{resolved}}"` is replaced by a boolean:
+/// ```bash
+/// export WEBHOOK_URL=...
+/// export WEBHOOK_TEMPLATE='{"summary": "{{title}}", "resolved": "{{resolved}}"}'
+/// ```
+///
+/// To open and resolve incidents in PagerDuty or Opsgenie for warning and critical notifications
+/// sent with `Notifier::notify`, define either or both of:
+/// ```bash
+/// export PAGERDUTY_ROUTING_KEY=...
+/// export OPSGENIE_API_KEY=...
+/// ```
 use log::*;
-use reqwest::{blocking::Client, StatusCode};
-use serde_json::json;
-use std::{env, thread::sleep, time::Duration};
+use reqwest::{blocking::Client, StatusCode, Url};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{env, fmt, thread::sleep, time::Duration};
+
+const DEFAULT_WEBHOOK_TEMPLATE: &str = r#"{
+    "id": "{{id}}",
+    "severity": "{{severity}}",
+    "title": "{{title}}",
+    "details": "{{details}}",
+    "resolved": "{{resolved}}"
+}"#;
+const DEFAULT_PAGERDUTY_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+const DEFAULT_OPSGENIE_URL: &str = "https://api.opsgenie.com";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Severity::Warning
+    }
+}
+
+impl Severity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str().to_uppercase())
+    }
+}
+
+/// A notification about an incident. A later notification with the same `id` updates the
+/// incident, or resolves it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub id: String,
+    pub severity: Severity,
+    pub title: String,
+    pub details: String,
+    pub resolved: bool,
+}
+
+impl Notification {
+    /// The notification as a plain message, for sinks without structure
+    pub fn text(&self) -> String {
+        if self.details.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{}\n{}", self.title, self.details)
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        if self.resolved {
+            "resolved"
+        } else {
+            "triggered"
+        }
+    }
+}
+
+/// Fill the placeholders of a webhook body template with the fields of `notification`
+fn render_template(template: &Value, notification: &Notification) -> Value {
+    match template {
+        Value::String(value) if value == "{{resolved}}" => Value::Bool(notification.resolved),
+        Value::String(value) => Value::String(
+            value
+                .replace("{{id}}", &notification.id)
+                .replace("{{severity}}", notification.severity.as_str())
+                .replace("{{title}}", &notification.title)
+                .replace("{{details}}", &notification.details)
+                .replace("{{text}}", &notification.text())
+                .replace("{{status}}", notification.status())
+                .replace("{{resolved}}", &notification.resolved.to_string()),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| render_template(value, notification))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render_template(value, notification)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+struct Webhook {
+    url: String,
+    template: Value,
+}
+
+fn get_webhook_config(env_prefix: &str) -> Result<Option<Webhook>, String> {
+    let url = match env::var(format!("{}WEBHOOK_URL", env_prefix)) {
+        Ok(url) => url,
+        Err(_) => {
+            info!("Webhook notifications disabled");
+            return Ok(None);
+        }
+    };
+    let template = env::var(format!("{}WEBHOOK_TEMPLATE", env_prefix))
+        .unwrap_or_else(|_| DEFAULT_WEBHOOK_TEMPLATE.to_string());
+    let template = serde_json::from_str(&template)
+        .map_err(|err| format!("WEBHOOK_TEMPLATE is not JSON: {}", err))?;
+    Ok(Some(Webhook { url, template }))
+}
+
+enum IncidentApi {
+    PagerDuty { url: String, routing_key: String },
+    Opsgenie { url: String, api_key: String },
+}
+
+impl IncidentApi {
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let request = match self {
+            IncidentApi::PagerDuty { url, routing_key } => {
+                let data = if notification.resolved {
+                    json!({
+                        "routing_key": routing_key,
+                        "event_action": "resolve",
+                        "dedup_key": notification.id,
+                    })
+                } else {
+                    json!({
+                        "routing_key": routing_key,
+                        "event_action": "trigger",
+                        "dedup_key": notification.id,
+                        "payload": {
+                            "summary": notification.title,
+                            "source": "solana",
+                            "severity": notification.severity.as_str(),
+                            "custom_details": notification.details,
+                        },
+                    })
+                };
+                client.post(url).json(&data)
+            }
+            IncidentApi::Opsgenie { url, api_key } => {
+                let mut url = Url::parse(url).map_err(|err| err.to_string())?;
+                let request = if notification.resolved {
+                    url.path_segments_mut()
+                        .map_err(|_| "invalid Opsgenie url".to_string())?
+                        .pop_if_empty()
+                        .extend(&["v2", "alerts", &notification.id, "close"]);
+                    url.query_pairs_mut().append_pair("identifierType", "alias");
+                    client.post(url).json(&json!({}))
+                } else {
+                    url.path_segments_mut()
+                        .map_err(|_| "invalid Opsgenie url".to_string())?
+                        .pop_if_empty()
+                        .extend(&["v2", "alerts"]);
+                    let priority = match notification.severity {
+                        Severity::Info => "P5",
+                        Severity::Warning => "P3",
+                        Severity::Critical => "P1",
+                    };
+                    client.post(url).json(&json!({
+                        "message": notification.title,
+                        "alias": notification.id,
+                        "description": notification.details,
+                        "priority": priority,
+                    }))
+                };
+                request.header("Authorization", format!("GenieKey {}", api_key))
+            }
+        };
+        let response = request.send().map_err(|err| err.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("response status: {}", response.status()))
+        }
+    }
+}
+
+fn get_incident_apis(env_prefix: &str) -> Vec<IncidentApi> {
+    let mut incident_apis = vec![];
+    if let Ok(routing_key) = env::var(format!("{}PAGERDUTY_ROUTING_KEY", env_prefix)) {
+        let url = env::var(format!("{}PAGERDUTY_URL", env_prefix))
+            .unwrap_or_else(|_| DEFAULT_PAGERDUTY_URL.to_string());
+        incident_apis.push(IncidentApi::PagerDuty { url, routing_key });
+    } else {
+        info!("PagerDuty notifications disabled");
+    }
+    if let Ok(api_key) = env::var(format!("{}OPSGENIE_API_KEY", env_prefix)) {
+        let url = env::var(format!("{}OPSGENIE_URL", env_prefix))
+            .unwrap_or_else(|_| DEFAULT_OPSGENIE_URL.to_string());
+        incident_apis.push(IncidentApi::Opsgenie { url, api_key });
+    } else {
+        info!("Opsgenie notifications disabled");
+    }
+    incident_apis
+}
 
 struct TelegramWebHook {
     bot_token: String,
@@ -81,6 +304,8 @@ pub struct Notifier {
     slack_webhook: Option<String>,
     telegram_webhook: Option<TelegramWebHook>,
     twilio_webhook: Option<TwilioWebHook>,
+    webhook: Option<Webhook>,
+    incident_apis: Vec<IncidentApi>,
 }
 
 impl Notifier {
@@ -114,6 +339,10 @@ impl Notifier {
         let twilio_webhook = get_twilio_config()
             .map_err(|err| panic!("Twilio config error: {}", err))
             .unwrap();
+        let webhook = get_webhook_config(env_prefix)
+            .map_err(|err| panic!("Webhook config error: {}", err))
+            .unwrap();
+        let incident_apis = get_incident_apis(env_prefix);
 
         Notifier {
             client: Client::new(),
@@ -121,10 +350,60 @@ impl Notifier {
             slack_webhook,
             telegram_webhook,
             twilio_webhook,
+            webhook,
+            incident_apis,
         }
     }
 
+    /// Send a plain message. The webhook receives it as an info notification with an empty id,
+    /// and incident APIs do not receive it
     pub fn send(&self, msg: &str) {
+        self.send_message(msg);
+        self.send_webhook(&Notification {
+            id: String::new(),
+            severity: Severity::Info,
+            title: msg.to_string(),
+            details: String::new(),
+            resolved: false,
+        });
+    }
+
+    /// Send a notification to every sink. Warning and critical notifications also trigger or
+    /// resolve their incident in the incident APIs
+    pub fn notify(&self, notification: &Notification) {
+        self.notify_event(notification);
+        if notification.severity == Severity::Info {
+            return;
+        }
+        for incident_api in &self.incident_apis {
+            if let Err(err) = incident_api.send(&self.client, notification) {
+                warn!("Failed to send incident {}: {}", notification.id, err);
+            }
+        }
+    }
+
+    /// Send a notification of a one-off event, which has no incident to resolve, to every sink
+    /// but the incident APIs
+    pub fn notify_event(&self, notification: &Notification) {
+        self.send_message(&notification.text());
+        self.send_webhook(notification);
+    }
+
+    fn send_webhook(&self, notification: &Notification) {
+        if let Some(Webhook { url, template }) = &self.webhook {
+            let data = render_template(template, notification);
+            match self.client.post(url).json(&data).send() {
+                Ok(response) if !response.status().is_success() => warn!(
+                    "Failed to send webhook notification: response status: {}",
+                    response.status()
+                ),
+                Ok(_) => {}
+                Err(err) => warn!("Failed to send webhook notification: {:?}", err),
+            }
+        }
+    }
+
+    fn send_message(&self, msg: &str) {
         if let Some(webhook) = &self.discord_webhook {
             for line in msg.split('\n') {
                 // Discord rate limiting is aggressive, limit to 1 message a second
@@ -187,3 +466,209 @@ impl Notifier {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc::{channel, Receiver},
+        thread,
+    };
+
+    struct Request {
+        path: String,
+        headers: HashMap<String, String>,
+        body: Value,
+    }
+
+    // Answer every request to a local port with an empty JSON object, passing the requests on
+    fn start_stand_in() -> (String, Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line.split_whitespace().nth(1).unwrap().to_string();
+                let mut headers = HashMap::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let mut header = line.splitn(2, ':');
+                    headers.insert(
+                        header.next().unwrap().to_ascii_lowercase(),
+                        header.next().unwrap_or_default().trim().to_string(),
+                    );
+                }
+                let content_length = headers
+                    .get("content-length")
+                    .map(|length| length.parse().unwrap())
+                    .unwrap_or(0);
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{{}}"
+                )
+                .unwrap();
+                let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+                if sender
+                    .send(Request {
+                        path,
+                        headers,
+                        body,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        (url, receiver)
+    }
+
+    fn notifier(webhook: Option<Webhook>, incident_apis: Vec<IncidentApi>) -> Notifier {
+        Notifier {
+            client: Client::new(),
+            discord_webhook: None,
+            slack_webhook: None,
+            telegram_webhook: None,
+            twilio_webhook: None,
+            webhook,
+            incident_apis,
+        }
+    }
+
+    fn notification(resolved: bool) -> Notification {
+        Notification {
+            id: "watchtower/vote-lag".to_string(),
+            severity: Severity::Critical,
+            title: "Validator is \"behind\"".to_string(),
+            details: "200 slots".to_string(),
+            resolved,
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let template = serde_json::from_str(DEFAULT_WEBHOOK_TEMPLATE).unwrap();
+        assert_eq!(
+            render_template(&template, &notification(false)),
+            json!({
+                "id": "watchtower/vote-lag",
+                "severity": "critical",
+                "title": "Validator is \"behind\"",
+                "details": "200 slots",
+                "resolved": false,
+            })
+        );
+
+        let template = json!({
+            "text": "[{{status}}] {{text}}",
+            "tags": ["{{severity}}", 42],
+            "closed": "{{resolved}}",
+        });
+        assert_eq!(
+            render_template(&template, &notification(true)),
+            json!({
+                "text": "[resolved] Validator is \"behind\"\n200 slots",
+                "tags": ["critical", 42],
+                "closed": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_webhook() {
+        let (url, requests) = start_stand_in();
+        let template = json!({ "summary": "{{title}}", "resolved": "{{resolved}}" });
+        let notifier = notifier(Some(Webhook { url, template }), vec![]);
+
+        notifier.notify(&notification(false));
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/");
+        assert_eq!(
+            request.body,
+            json!({ "summary": "Validator is \"behind\"", "resolved": false })
+        );
+
+        notifier.send("hello");
+        let request = requests.recv().unwrap();
+        assert_eq!(
+            request.body,
+            json!({ "summary": "hello", "resolved": false })
+        );
+    }
+
+    #[test]
+    fn test_pagerduty() {
+        let (url, requests) = start_stand_in();
+        let incident_api = IncidentApi::PagerDuty {
+            url: format!("{}/v2/enqueue", url),
+            routing_key: "key".to_string(),
+        };
+        let notifier = notifier(None, vec![incident_api]);
+
+        notifier.notify(&notification(false));
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/v2/enqueue");
+        assert_eq!(request.body["event_action"], "trigger");
+        assert_eq!(request.body["dedup_key"], "watchtower/vote-lag");
+        assert_eq!(request.body["payload"]["severity"], "critical");
+
+        notifier.notify(&notification(true));
+        let request = requests.recv().unwrap();
+        assert_eq!(
+            request.body,
+            json!({
+                "routing_key": "key",
+                "event_action": "resolve",
+                "dedup_key": "watchtower/vote-lag",
+            })
+        );
+
+        // Plain messages, events and info notifications do not open incidents
+        notifier.send("hello");
+        notifier.notify_event(&notification(false));
+        notifier.notify(&Notification {
+            severity: Severity::Info,
+            ..notification(false)
+        });
+        notifier.notify(&notification(true));
+        assert_eq!(requests.recv().unwrap().body["event_action"], "resolve");
+    }
+
+    #[test]
+    fn test_opsgenie() {
+        let (url, requests) = start_stand_in();
+        let incident_api = IncidentApi::Opsgenie {
+            url,
+            api_key: "key".to_string(),
+        };
+        let notifier = notifier(None, vec![incident_api]);
+
+        notifier.notify(&notification(false));
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/v2/alerts");
+        assert_eq!(request.headers["authorization"], "GenieKey key");
+        assert_eq!(request.body["alias"], "watchtower/vote-lag");
+        assert_eq!(request.body["priority"], "P1");
+
+        notifier.notify(&notification(true));
+        let request = requests.recv().unwrap();
+        assert_eq!(
+            request.path,
+            "/v2/alerts/watchtower%2Fvote-lag/close?identifierType=alias"
+        );
+    }
+}
@@ -12,7 +12,7 @@ use bincode::serialized_size;
 use rayon::ThreadPool;
 use solana_metrics::inc_new_counter_debug;
 use solana_rayon_threadlimit::get_thread_count;
-use solana_sdk::message::MessageHeader;
+use solana_sdk::message::{MessageHeader, MESSAGE_VERSION_PREFIX};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::short_vec::decode_len;
 use solana_sdk::signature::Signature;
@@ -59,6 +59,7 @@ pub enum PacketError {
     InvalidSignatureLen,
     MismatchSignatureLen,
     PayerNotWritable,
+    UnsupportedVersion,
 }
 
 impl std::convert::From<std::boxed::Box<bincode::ErrorKind>> for PacketError {
@@ -138,21 +139,36 @@ fn do_get_packet_offsets(
     // Ultimately, the actual sigverify will determine the uncertainty.
     let msg_start_offset = sig_size + sig_len_untrusted * size_of::<Signature>();
 
+    // A versioned message starts with a byte holding its version ahead of the MessageHeader,
+    // the signatures still cover the whole message including that byte
+    if msg_start_offset >= packet.meta.size {
+        return Err(PacketError::InvalidSignatureLen);
+    }
+    let message_prefix = packet.data[msg_start_offset];
+    let msg_header_offset = if message_prefix & MESSAGE_VERSION_PREFIX != 0 {
+        if message_prefix & !MESSAGE_VERSION_PREFIX != 0 {
+            return Err(PacketError::UnsupportedVersion);
+        }
+        msg_start_offset + 1
+    } else {
+        msg_start_offset
+    };
+
     // Packet should have data at least for signatures, MessageHeader, 1 byte for Message.account_keys.len
-    if (msg_start_offset + message_header_size + 1) > packet.meta.size {
+    if (msg_header_offset + message_header_size + 1) > packet.meta.size {
         return Err(PacketError::InvalidSignatureLen);
     }
 
     // read MessageHeader.num_required_signatures (serialized with u8)
-    let sig_len_maybe_trusted = packet.data[msg_start_offset] as usize;
+    let sig_len_maybe_trusted = packet.data[msg_header_offset] as usize;
 
-    let message_account_keys_len_offset = msg_start_offset + message_header_size;
+    let message_account_keys_len_offset = msg_header_offset + message_header_size;
 
     // This reads and compares the MessageHeader num_required_signatures and
     // num_readonly_signed_accounts bytes. If num_required_signatures is not larger than
     // num_readonly_signed_accounts, the first account is not debitable, and cannot be charged
     // required transaction fees.
-    if packet.data[msg_start_offset] <= packet.data[msg_start_offset + 1] {
+    if packet.data[msg_header_offset] <= packet.data[msg_header_offset + 1] {
         return Err(PacketError::PayerNotWritable);
     }
 
@@ -168,7 +184,7 @@ fn do_get_packet_offsets(
 
     let sig_start = current_offset as usize + sig_size;
     let msg_start = current_offset as usize + msg_start_offset;
-    let pubkey_start = msg_start + message_header_size + pubkey_len_size;
+    let pubkey_start = current_offset as usize + message_account_keys_len_offset + pubkey_len_size;
 
     if sig_len_maybe_trusted != sig_len_untrusted {
         return Err(PacketError::MismatchSignatureLen);
@@ -511,9 +527,10 @@ mod tests {
 
         let unsanitized_packet_offsets = sigverify::do_get_packet_offsets(&packet, 0);
 
+        // A first message byte with the high bit set is read as a version prefix
         assert_eq!(
             unsanitized_packet_offsets,
-            Err(PacketError::MismatchSignatureLen)
+            Err(PacketError::UnsupportedVersion)
         );
     }
 
@@ -750,6 +767,63 @@ mod tests {
         assert_eq!(ans, ref_vec);
     }
 
+    #[test]
+    fn test_verify_versioned_transaction() {
+        use solana_sdk::{
+            message::{MessageV0, VersionedMessage},
+            pubkey::Pubkey,
+            signature::{Keypair, Signer},
+            system_instruction,
+            transaction::VersionedTransaction,
+        };
+
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let message = MessageV0::new_with_lookup_tables(
+            &[system_instruction::transfer(&payer.pubkey(), &to, 1)],
+            Some(&payer.pubkey()),
+            &[(Pubkey::new_unique(), vec![to])],
+        );
+        let tx = VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer]).unwrap();
+        let tx_bytes = serialize(&tx).unwrap();
+        let mut packet = Packet::default();
+        packet.meta.size = tx_bytes.len();
+        packet.data[..packet.meta.size].copy_from_slice(&tx_bytes);
+
+        // The signed message starts at the version prefix, the header follows it
+        let packet_offsets = sigverify::do_get_packet_offsets(&packet, 0).unwrap();
+        assert_eq!(
+            memfind(&tx_bytes, &tx.message.serialize()),
+            Some(packet_offsets.msg_start as usize)
+        );
+        assert_eq!(packet.data[packet_offsets.msg_start as usize], 0x80);
+        assert_eq!(
+            memfind(&tx_bytes, payer.pubkey().as_ref()),
+            Some(packet_offsets.pubkey_start as usize)
+        );
+
+        let recycler = Recycler::default();
+        let recycler_out = Recycler::default();
+        let batches = generate_packet_vec(&packet, 1, 1);
+        let ans = sigverify::ed25519_verify(&batches, &recycler, &recycler_out);
+        assert_eq!(ans, vec![vec![1u8]]);
+
+        // The address table lookups are covered by the signature
+        let mut tampered_packet = packet.clone();
+        let last = tampered_packet.meta.size - 1;
+        tampered_packet.data[last] = tampered_packet.data[last].wrapping_add(1);
+        let batches = generate_packet_vec(&tampered_packet, 1, 1);
+        let ans = sigverify::ed25519_verify(&batches, &recycler, &recycler_out);
+        assert_eq!(ans, vec![vec![0u8]]);
+
+        let mut unsupported_packet = packet;
+        unsupported_packet.data[packet_offsets.msg_start as usize] = 0x81;
+        assert_eq!(
+            sigverify::do_get_packet_offsets(&unsupported_packet, 0),
+            Err(PacketError::UnsupportedVersion)
+        );
+    }
+
     #[test]
     fn test_verify_fuzz() {
         use rand::{thread_rng, Rng};
@@ -7,10 +7,22 @@ use {
     solana_banks_client::start_client,
     solana_banks_server::banks_server::start_local_server,
     solana_program::{
-        account_info::AccountInfo, entrypoint::ProgramResult, fee_calculator::FeeCalculator,
-        hash::Hash, instruction::Instruction, instruction::InstructionError, message::Message,
-        native_token::sol_to_lamports, program_error::ProgramError, program_stubs, pubkey::Pubkey,
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::{ProgramResult, SUCCESS},
+        epoch_schedule::EpochSchedule,
+        fee_calculator::FeeCalculator,
+        hash::Hash,
+        instruction::Instruction,
+        instruction::InstructionError,
+        message::Message,
+        native_token::sol_to_lamports,
+        program::MAX_RETURN_DATA,
+        program_error::{ProgramError, INVALID_ARGUMENT},
+        program_stubs,
+        pubkey::Pubkey,
         rent::Rent,
+        sysvar::{self, fees::Fees, Sysvar},
     },
     solana_runtime::{
         bank::{Bank, Builtin},
@@ -18,10 +30,12 @@ use {
         genesis_utils::create_genesis_config_with_leader,
     },
     solana_sdk::{
-        account::Account,
+        account::{from_account, Account},
         keyed_account::KeyedAccount,
         process_instruction::BpfComputeBudget,
-        process_instruction::{InvokeContext, MockInvokeContext, ProcessInstructionWithContext},
+        process_instruction::{
+            stable_log, InvokeContext, MockInvokeContext, ProcessInstructionWithContext,
+        },
         signature::{Keypair, Signer},
     },
     std::{
@@ -76,6 +90,19 @@ pub fn builtin_process_instruction(
     let mut mock_invoke_context = MockInvokeContext::default();
     mock_invoke_context.programs = invoke_context.get_programs().to_vec();
     mock_invoke_context.key = *program_id;
+    mock_invoke_context.sysvars = [
+        sysvar::clock::id(),
+        sysvar::epoch_schedule::id(),
+        sysvar::fees::id(),
+        sysvar::rent::id(),
+    ]
+    .iter()
+    .filter_map(|id| {
+        invoke_context
+            .get_sysvar_data(id)
+            .map(|data| (*id, data.to_vec()))
+    })
+    .collect();
     // TODO: Populate MockInvokeContext more, or rework to avoid MockInvokeContext entirely.
     //       The context being passed into the program is incomplete...
     let local_invoke_context = RefCell::new(Rc::new(mock_invoke_context));
@@ -148,6 +175,13 @@ pub fn builtin_process_instruction(
         }
     }
 
+    // Propagate return data back to caller's invoke context, if any was set
+    let local_invoke_context = local_invoke_context.borrow();
+    let (return_data_program_id, return_data) = local_invoke_context.get_return_data();
+    if return_data_program_id != Pubkey::default() {
+        invoke_context.set_return_data(return_data.to_vec())?;
+    }
+
     result
 }
 
@@ -179,6 +213,38 @@ pub fn swap_invoke_context(other_invoke_context: &RefCell<Rc<MockInvokeContext>>
     });
 }
 
+fn set_return_data(return_data: (Pubkey, Vec<u8>)) {
+    INVOKE_CONTEXT.with(|invoke_context| {
+        let mut invoke_context = invoke_context.borrow_mut();
+        Rc::get_mut(&mut invoke_context)
+            .expect("get_mut")
+            .return_data = return_data;
+    });
+}
+
+/// Write the sysvar `id` of the current invocation into `var_addr`, as the `sol_get_*_sysvar`
+/// syscalls do
+fn get_sysvar<T: Sysvar>(id: &Pubkey, var_addr: *mut u8) -> u64 {
+    INVOKE_CONTEXT.with(|invoke_context| {
+        let invoke_context = invoke_context.borrow();
+        let sysvar = invoke_context.get_sysvar_data(id).and_then(|data| {
+            from_account::<T>(&Account {
+                data: data.to_vec(),
+                ..Account::default()
+            })
+        });
+        match sysvar {
+            Some(sysvar) => {
+                unsafe {
+                    *(var_addr as *mut T) = sysvar;
+                }
+                SUCCESS
+            }
+            None => INVALID_ARGUMENT,
+        }
+    })
+}
+
 struct SyscallStubs {}
 impl program_stubs::SyscallStubs for SyscallStubs {
     fn sol_log(&self, message: &str) {
@@ -193,6 +259,13 @@ impl program_stubs::SyscallStubs for SyscallStubs {
         });
     }
 
+    fn sol_log_data(&self, data: &[&[u8]]) {
+        INVOKE_CONTEXT.with(|invoke_context| {
+            let invoke_context = invoke_context.borrow_mut();
+            stable_log::program_data(&invoke_context.get_logger(), &invoke_context.key, data);
+        });
+    }
+
     fn sol_invoke_signed(
         &self,
         instruction: &Instruction,
@@ -218,6 +291,9 @@ impl program_stubs::SyscallStubs for SyscallStubs {
             //       The context being passed into the program is incomplete...
         });
 
+        // Return data is cleared so the caller only sees what the callee sets
+        set_return_data((Pubkey::default(), vec![]));
+
         if instruction.accounts.len() + 1 != account_infos.len() {
             panic!(
                 "Instruction accounts mismatch.  Instruction contains {} accounts, with {}
@@ -230,6 +306,7 @@ impl program_stubs::SyscallStubs for SyscallStubs {
 
         let program_id_index = message.instructions[0].program_id_index as usize;
         let program_id = message.account_keys[program_id_index];
+        mock_invoke_context.key = program_id;
 
         let program_account_info = &account_infos[program_id_index];
         if !program_account_info.executable {
@@ -285,6 +362,8 @@ impl program_stubs::SyscallStubs for SyscallStubs {
         )
         .map_err(|err| ProgramError::try_from(err).unwrap_or_else(|err| panic!("{}", err)))?;
 
+        set_return_data(mock_invoke_context.return_data.clone());
+
         // Propagate logs back to caller's invoke context
         // (TODO: This goes away if MockInvokeContext usage can be removed)
         INVOKE_CONTEXT.with(|invoke_context| {
@@ -335,6 +414,46 @@ impl program_stubs::SyscallStubs for SyscallStubs {
 
         Ok(())
     }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        get_sysvar::<Clock>(&sysvar::clock::id(), var_addr)
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        get_sysvar::<EpochSchedule>(&sysvar::epoch_schedule::id(), var_addr)
+    }
+
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        get_sysvar::<Fees>(&sysvar::fees::id(), var_addr)
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        get_sysvar::<Rent>(&sysvar::rent::id(), var_addr)
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        if data.len() > MAX_RETURN_DATA {
+            panic!(
+                "Return data too large ({} > {})",
+                data.len(),
+                MAX_RETURN_DATA
+            );
+        }
+        let program_id = INVOKE_CONTEXT.with(|invoke_context| invoke_context.borrow().key);
+        set_return_data((program_id, data.to_vec()));
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        INVOKE_CONTEXT.with(|invoke_context| {
+            let invoke_context = invoke_context.borrow();
+            let (program_id, data) = invoke_context.get_return_data();
+            if data.is_empty() {
+                None
+            } else {
+                Some((program_id, data.to_vec()))
+            }
+        })
+    }
 }
 
 fn find_file(filename: &str, search_path: &[PathBuf]) -> Option<PathBuf> {
@@ -609,6 +728,7 @@ impl ProgramTest {
         for loader in &[
             solana_bpf_loader_deprecated_program!(),
             solana_bpf_loader_program!(),
+            solana_bpf_loader_upgradeable_program!(),
         ] {
             bank.add_builtin(&loader.0, loader.1, loader.2);
         }
@@ -654,7 +774,7 @@ impl ProgramTest {
         // `bank.commit_transactions()` so that the fee calculator in the child bank will be
         // initialized with a non-zero fee.
         assert_eq!(bank.signature_count(), 0);
-        bank.commit_transactions(&[], None, &mut [], &[], 0, 1);
+        bank.commit_transactions(&[], None, &mut [], &[], 0, 1, 0);
         assert_eq!(bank.signature_count(), 1);
 
         // Advance beyond slot 0 for a slightly more realistic test environment
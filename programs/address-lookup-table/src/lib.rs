@@ -0,0 +1,5 @@
+pub mod lookup_table_instruction;
+pub mod lookup_table_processor;
+pub mod lookup_table_state;
+
+solana_sdk::declare_id!("AddressLookupTab1e1111111111111111111111111");
@@ -0,0 +1,103 @@
+use crate::{id, lookup_table_state::AddressLookupTable};
+use num_derive::{FromPrimitive, ToPrimitive};
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    decode_error::DecodeError,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_instruction,
+};
+use thiserror::Error;
+
+/// Reasons the lookup table program might have had an error
+#[derive(Error, Debug, Clone, PartialEq, FromPrimitive, ToPrimitive)]
+pub enum LookupTableError {
+    #[error("lookup table is frozen")]
+    Frozen,
+
+    #[error("lookup table cannot hold any more addresses")]
+    TableFull,
+}
+
+impl<E> DecodeError<E> for LookupTableError {
+    fn type_of() -> &'static str {
+        "LookupTableError"
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum LookupTableInstruction {
+    /// Initialize an empty address lookup table
+    ///
+    /// # Account references
+    ///   0. [WRITE, SIGNER] Uninitialized lookup table account
+    ///   1. [SIGNER] Authority allowed to extend and freeze the table
+    InitializeLookupTable,
+
+    /// Append addresses to a lookup table
+    ///
+    /// # Account references
+    ///   0. [WRITE] Lookup table account
+    ///   1. [SIGNER] Current authority
+    ExtendLookupTable { new_addresses: Vec<Pubkey> },
+
+    /// Permanently freeze a lookup table, no more addresses can be added
+    ///
+    /// # Account references
+    ///   0. [WRITE] Lookup table account
+    ///   1. [SIGNER] Current authority
+    FreezeLookupTable,
+}
+
+/// Create and initialize a lookup table account able to hold `max_addresses` addresses
+pub fn create_lookup_table(
+    from_pubkey: &Pubkey,
+    lookup_table_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    lamports: u64,
+    max_addresses: usize,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::create_account(
+            from_pubkey,
+            lookup_table_pubkey,
+            lamports,
+            AddressLookupTable::space(max_addresses),
+            &id(),
+        ),
+        Instruction::new(
+            id(),
+            &LookupTableInstruction::InitializeLookupTable,
+            vec![
+                AccountMeta::new(*lookup_table_pubkey, true),
+                AccountMeta::new_readonly(*authority_pubkey, true),
+            ],
+        ),
+    ]
+}
+
+pub fn extend_lookup_table(
+    lookup_table_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    new_addresses: Vec<Pubkey>,
+) -> Instruction {
+    Instruction::new(
+        id(),
+        &LookupTableInstruction::ExtendLookupTable { new_addresses },
+        vec![
+            AccountMeta::new(*lookup_table_pubkey, false),
+            AccountMeta::new_readonly(*authority_pubkey, true),
+        ],
+    )
+}
+
+pub fn freeze_lookup_table(lookup_table_pubkey: &Pubkey, authority_pubkey: &Pubkey) -> Instruction {
+    Instruction::new(
+        id(),
+        &LookupTableInstruction::FreezeLookupTable,
+        vec![
+            AccountMeta::new(*lookup_table_pubkey, false),
+            AccountMeta::new_readonly(*authority_pubkey, true),
+        ],
+    )
+}
@@ -0,0 +1,217 @@
+//! Address lookup table program
+
+use crate::{
+    lookup_table_instruction::{LookupTableError, LookupTableInstruction},
+    lookup_table_state::{AddressLookupTable, LookupTableState, LOOKUP_TABLE_MAX_ADDRESSES},
+};
+use log::*;
+use solana_sdk::{
+    account_utils::State,
+    instruction::InstructionError,
+    keyed_account::{next_keyed_account, KeyedAccount},
+    process_instruction::InvokeContext,
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+};
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+    data: &[u8],
+    _invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    let keyed_accounts_iter = &mut keyed_accounts.iter();
+    let lookup_table_account = next_keyed_account(keyed_accounts_iter)?;
+    let authority_account = next_keyed_account(keyed_accounts_iter)?;
+
+    match limited_deserialize(data)? {
+        LookupTableInstruction::InitializeLookupTable => {
+            // Without both signatures anyone could claim a table that was funded but not yet
+            // initialized, or name someone else as its authority
+            if lookup_table_account.signer_key().is_none()
+                || authority_account.signer_key().is_none()
+            {
+                return Err(InstructionError::MissingRequiredSignature);
+            }
+            let state: LookupTableState = lookup_table_account.state()?;
+            if let LookupTableState::Initialized(_) = state {
+                return Err(InstructionError::AccountAlreadyInitialized);
+            }
+            lookup_table_account.set_state(&LookupTableState::Initialized(AddressLookupTable {
+                authority: Some(*authority_account.unsigned_key()),
+                addresses: vec![],
+            }))
+        }
+        LookupTableInstruction::ExtendLookupTable { new_addresses } => {
+            let mut table = authorized_table(lookup_table_account, authority_account)?;
+            if table.addresses.len() + new_addresses.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+                return Err(LookupTableError::TableFull.into());
+            }
+            table.addresses.extend(new_addresses);
+            lookup_table_account.set_state(&LookupTableState::Initialized(table))
+        }
+        LookupTableInstruction::FreezeLookupTable => {
+            let mut table = authorized_table(lookup_table_account, authority_account)?;
+            table.authority = None;
+            lookup_table_account.set_state(&LookupTableState::Initialized(table))
+        }
+    }
+}
+
+/// Deserialize the lookup table, checking that it may still be modified by `authority_account`
+fn authorized_table(
+    lookup_table_account: &KeyedAccount,
+    authority_account: &KeyedAccount,
+) -> Result<AddressLookupTable, InstructionError> {
+    let state: LookupTableState = lookup_table_account.state()?;
+    let table = match state {
+        LookupTableState::Initialized(table) => table,
+        LookupTableState::Uninitialized => return Err(InstructionError::UninitializedAccount),
+    };
+    match table.authority {
+        None => Err(LookupTableError::Frozen.into()),
+        Some(authority) if authority_account.signer_key() != Some(&authority) => {
+            debug!("lookup table authority {} did not sign", authority);
+            Err(InstructionError::MissingRequiredSignature)
+        }
+        Some(_) => Ok(table),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id;
+    use solana_sdk::{
+        account::Account, keyed_account::create_keyed_is_signer_accounts,
+        process_instruction::MockInvokeContext,
+    };
+    use std::cell::RefCell;
+
+    fn process(
+        lookup_table_account: &RefCell<Account>,
+        lookup_table_signed: bool,
+        authority_pubkey: &Pubkey,
+        authority_signed: bool,
+        instruction: &LookupTableInstruction,
+    ) -> Result<(), InstructionError> {
+        let lookup_table_pubkey = Pubkey::new_unique();
+        let authority_account = RefCell::new(Account::default());
+        let accounts = vec![
+            (
+                &lookup_table_pubkey,
+                lookup_table_signed,
+                lookup_table_account,
+            ),
+            (authority_pubkey, authority_signed, &authority_account),
+        ];
+        let keyed_accounts = create_keyed_is_signer_accounts(&accounts);
+        process_instruction(
+            &id(),
+            &keyed_accounts,
+            &bincode::serialize(instruction).unwrap(),
+            &mut MockInvokeContext::default(),
+        )
+    }
+
+    #[test]
+    fn test_lookup_table_lifecycle() {
+        let authority = Pubkey::new_unique();
+        let lookup_table_account = RefCell::new(Account::new(
+            1,
+            AddressLookupTable::space(2) as usize,
+            &id(),
+        ));
+
+        let new_addresses = vec![Pubkey::new_unique()];
+        let extend = LookupTableInstruction::ExtendLookupTable {
+            new_addresses: new_addresses.clone(),
+        };
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, true, &extend),
+            Err(InstructionError::UninitializedAccount)
+        );
+
+        // Both the table and its authority must sign to initialize
+        let initialize = LookupTableInstruction::InitializeLookupTable;
+        assert_eq!(
+            process(&lookup_table_account, false, &authority, true, &initialize),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, false, &initialize),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            LookupTableState::from_account(&lookup_table_account.borrow()),
+            None
+        );
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, true, &initialize),
+            Ok(())
+        );
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, true, &initialize),
+            Err(InstructionError::AccountAlreadyInitialized)
+        );
+
+        // Only the authority may extend the table
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, false, &extend),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            process(
+                &lookup_table_account,
+                true,
+                &Pubkey::new_unique(),
+                true,
+                &extend
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, true, &extend),
+            Ok(())
+        );
+        assert_eq!(
+            LookupTableState::from_account(&lookup_table_account.borrow()),
+            Some(AddressLookupTable {
+                authority: Some(authority),
+                addresses: new_addresses.clone(),
+            })
+        );
+
+        // Extending beyond the allocated space fails
+        let extend_too_far = LookupTableInstruction::ExtendLookupTable {
+            new_addresses: vec![Pubkey::new_unique(); 2],
+        };
+        assert_eq!(
+            process(
+                &lookup_table_account,
+                true,
+                &authority,
+                true,
+                &extend_too_far
+            ),
+            Err(InstructionError::AccountDataTooSmall)
+        );
+
+        let freeze = LookupTableInstruction::FreezeLookupTable;
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, true, &freeze),
+            Ok(())
+        );
+        assert_eq!(
+            process(&lookup_table_account, true, &authority, true, &extend),
+            Err(LookupTableError::Frozen.into())
+        );
+        assert_eq!(
+            LookupTableState::from_account(&lookup_table_account.borrow()),
+            Some(AddressLookupTable {
+                authority: None,
+                addresses: new_addresses,
+            })
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Address lookup table account state
+
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{account::Account, account_utils::StateMut, pubkey::Pubkey};
+
+/// Maximum number of addresses a lookup table can hold, so that every address stays
+/// addressable by a `u8` index
+pub const LOOKUP_TABLE_MAX_ADDRESSES: usize = 256;
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct AddressLookupTable {
+    /// Key allowed to extend or freeze the table, `None` once the table is frozen
+    pub authority: Option<Pubkey>,
+    /// Addresses stored in the table, in the order they were added
+    pub addresses: Vec<Pubkey>,
+}
+
+impl AddressLookupTable {
+    /// Number of bytes needed to store a table of up to `max_addresses` addresses
+    pub fn space(max_addresses: usize) -> u64 {
+        bincode::serialized_size(&LookupTableState::Initialized(AddressLookupTable {
+            authority: Some(Pubkey::default()),
+            addresses: vec![Pubkey::default(); max_addresses],
+        }))
+        .unwrap()
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.authority.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum LookupTableState {
+    Uninitialized,
+    Initialized(AddressLookupTable),
+}
+
+impl Default for LookupTableState {
+    fn default() -> Self {
+        LookupTableState::Uninitialized
+    }
+}
+
+impl LookupTableState {
+    /// Deserialize the lookup table stored in `account`, if any
+    pub fn from_account(account: &Account) -> Option<AddressLookupTable> {
+        match account.state() {
+            Ok(LookupTableState::Initialized(table)) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_table_space() {
+        let mut account = Account::new(1, AddressLookupTable::space(2) as usize, &crate::id());
+        assert_eq!(LookupTableState::from_account(&account), None);
+
+        let table = AddressLookupTable {
+            authority: Some(Pubkey::new_unique()),
+            addresses: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        };
+        account
+            .set_state(&LookupTableState::Initialized(table.clone()))
+            .unwrap();
+        assert_eq!(
+            LookupTableState::from_account(&account),
+            Some(table.clone())
+        );
+
+        // Frozen tables take less space than their capacity
+        let table = AddressLookupTable {
+            authority: None,
+            ..table
+        };
+        account
+            .set_state(&LookupTableState::Initialized(table.clone()))
+            .unwrap();
+        assert_eq!(LookupTableState::from_account(&account), Some(table));
+
+        let table = AddressLookupTable {
+            authority: Some(Pubkey::new_unique()),
+            addresses: vec![Pubkey::new_unique(); 3],
+        };
+        assert!(account
+            .set_state(&LookupTableState::Initialized(table))
+            .is_err());
+    }
+}
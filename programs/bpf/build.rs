@@ -77,6 +77,7 @@ fn main() {
             "panic",
             "param_passing",
             "rand",
+            "return_data",
             "ristretto",
             "sanity",
             "sha256",
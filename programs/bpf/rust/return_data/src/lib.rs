@@ -0,0 +1,47 @@
+//! @brief Example Rust-based BPF program that sets and gets return data
+
+extern crate solana_program;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint,
+    entrypoint::ProgramResult,
+    info,
+    instruction::Instruction,
+    program::{get_return_data, invoke, set_return_data},
+    pubkey::Pubkey,
+};
+
+pub const SET: u8 = 0;
+pub const INVOKE_SET: u8 = 1;
+pub const NONE: u8 = 2;
+
+pub const RETURN_DATA: &[u8] = b"return data";
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match instruction_data[0] {
+        SET => {
+            info!("set return data");
+            set_return_data(b"replaced");
+            set_return_data(RETURN_DATA);
+            assert_eq!(get_return_data(), Some((*program_id, RETURN_DATA.to_vec())));
+        }
+        INVOKE_SET => {
+            info!("invoke a program that sets return data");
+            set_return_data(b"cleared by invoke");
+            invoke(&Instruction::new(*program_id, &SET, vec![]), accounts)?;
+            assert_eq!(get_return_data(), Some((*program_id, RETURN_DATA.to_vec())));
+        }
+        NONE => {
+            info!("set no return data");
+            assert_eq!(get_return_data(), None);
+        }
+        _ => panic!(),
+    }
+
+    Ok(())
+}
@@ -11,7 +11,7 @@ use solana_bpf_loader_program::{
 };
 use solana_rbpf::vm::{Config, Executable, Tracer};
 use solana_runtime::{
-    bank::Bank,
+    bank::{Bank, TransactionReturnData},
     bank_client::BankClient,
     genesis_utils::{create_genesis_config, GenesisConfigInfo},
     loader_utils::load_program,
@@ -151,13 +151,8 @@ fn process_transaction_and_record_inner(
     let signature = tx.signatures.get(0).unwrap().clone();
     let txs = vec![tx];
     let tx_batch = bank.prepare_batch(&txs, None);
-    let (mut results, _, mut inner, _transaction_logs) = bank.load_execute_and_commit_transactions(
-        &tx_batch,
-        MAX_PROCESSING_AGE,
-        false,
-        true,
-        false,
-    );
+    let (mut results, _, mut inner, _transaction_logs, _log_events, _return_data) = bank
+        .load_execute_and_commit_transactions(&tx_batch, MAX_PROCESSING_AGE, false, true, false);
     let inner_instructions = inner.swap_remove(0);
     let result = results
         .fee_collection_results
@@ -950,3 +945,62 @@ fn test_program_bpf_instruction_introspection() {
         .get_account(&solana_sdk::sysvar::instructions::id())
         .is_none());
 }
+
+#[cfg(feature = "bpf_rust")]
+#[test]
+fn test_program_bpf_return_data() {
+    solana_logger::setup();
+
+    let GenesisConfigInfo {
+        genesis_config,
+        mint_keypair,
+        ..
+    } = create_genesis_config(50_000);
+    let mut bank = Bank::new(&genesis_config);
+    let (name, id, entrypoint) = solana_bpf_loader_program!();
+    bank.add_builtin(&name, id, entrypoint);
+    let bank = Arc::new(bank);
+    let bank_client = BankClient::new_shared(&bank);
+
+    let program_id = load_bpf_program(
+        &bank_client,
+        &bpf_loader::id(),
+        &mint_keypair,
+        "solana_bpf_rust_return_data",
+    );
+    bank.freeze();
+
+    const SET: u8 = 0;
+    const INVOKE_SET: u8 = 1;
+    const NONE: u8 = 2;
+    let expected = Some(TransactionReturnData {
+        program_id,
+        data: b"return data".to_vec(),
+    });
+
+    for (instructions, expected) in [
+        (vec![SET], expected.clone()),
+        (vec![INVOKE_SET], expected.clone()),
+        // The most recent return data is kept by later instructions that set none
+        (vec![SET, NONE], expected),
+        (vec![NONE], None),
+    ]
+    .iter()
+    {
+        let instructions: Vec<_> = instructions
+            .iter()
+            .map(|instruction| {
+                Instruction::new(
+                    program_id,
+                    instruction,
+                    vec![AccountMeta::new_readonly(program_id, false)],
+                )
+            })
+            .collect();
+        let message = Message::new(&instructions, Some(&mint_keypair.pubkey()));
+        let transaction = Transaction::new(&[&mint_keypair], message, bank.last_blockhash());
+        let (result, _log_messages, return_data) = bank.simulate_transaction(transaction);
+        assert_eq!(result, Ok(()));
+        assert_eq!(&return_data, expected);
+    }
+}
@@ -4,6 +4,7 @@ pub mod bpf_verifier;
 pub mod deprecated;
 pub mod serialization;
 pub mod syscalls;
+pub mod upgradeable;
 
 use crate::{
     bpf_verifier::VerifierError,
@@ -17,17 +18,24 @@ use solana_rbpf::{
     memory_region::MemoryRegion,
     vm::{Config, EbpfVm, Executable, InstructionMeter},
 };
+use solana_runtime::message_processor::MessageProcessor;
 use solana_sdk::{
+    account_utils::State,
     bpf_loader, bpf_loader_deprecated,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
     decode_error::DecodeError,
     entrypoint::SUCCESS,
     feature_set::bpf_compute_budget_balancing,
     instruction::InstructionError,
-    keyed_account::{is_executable, next_keyed_account, KeyedAccount},
+    keyed_account::{from_keyed_account, is_executable, next_keyed_account, KeyedAccount},
     loader_instruction::LoaderInstruction,
-    process_instruction::{stable_log, ComputeMeter, Executor, InvokeContext},
+    loader_upgradeable_instruction::UpgradeableLoaderInstruction,
+    process_instruction::{stable_log, ComputeMeter, Executor, InvokeContext, Logger},
     program_utils::limited_deserialize,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
 };
 use std::{cell::RefCell, fmt::Debug, rc::Rc, sync::Arc};
 use thiserror::Error;
@@ -94,12 +102,13 @@ fn map_ebpf_error(
 const IS_JIT_ENABLED: bool = false;
 
 pub fn create_and_cache_executor(
-    program: &KeyedAccount,
+    key: &Pubkey,
+    data: &[u8],
     invoke_context: &mut dyn InvokeContext,
 ) -> Result<Arc<BPFExecutor>, InstructionError> {
     let bpf_compute_budget = invoke_context.get_bpf_compute_budget();
     let mut executable = Executable::<BPFError, ThisInstructionMeter>::from_elf(
-        &program.try_account_ref()?.data,
+        data,
         None,
         Config {
             max_call_depth: bpf_compute_budget.max_call_depth,
@@ -124,7 +133,7 @@ pub fn create_and_cache_executor(
         return Err(BPFLoaderError::JustInTimeCompilationFailed.into());
     }
     let executor = Arc::new(BPFExecutor { executable });
-    invoke_context.add_executor(program.unsigned_key(), executor.clone());
+    invoke_context.add_executor(key, executor.clone());
     Ok(executor)
 }
 
@@ -159,7 +168,11 @@ pub fn process_instruction(
     instruction_data: &[u8],
     invoke_context: &mut dyn InvokeContext,
 ) -> Result<(), InstructionError> {
-    debug_assert!(bpf_loader::check_id(program_id) || bpf_loader_deprecated::check_id(program_id));
+    debug_assert!(
+        bpf_loader::check_id(program_id)
+            || bpf_loader_deprecated::check_id(program_id)
+            || bpf_loader_upgradeable::check_id(program_id)
+    );
 
     let logger = invoke_context.get_logger();
 
@@ -172,48 +185,425 @@ pub fn process_instruction(
     if is_executable(keyed_accounts)? {
         let executor = match invoke_context.get_executor(program.unsigned_key()) {
             Some(executor) => executor,
-            None => create_and_cache_executor(program, invoke_context)?,
+            None if bpf_loader_upgradeable::check_id(program_id) => {
+                let programdata = keyed_accounts
+                    .get(1)
+                    .ok_or(InstructionError::NotEnoughAccountKeys)?;
+                if let UpgradeableLoaderState::Program {
+                    programdata_address,
+                } = program.state()?
+                {
+                    if programdata_address != *programdata.unsigned_key() {
+                        log!(logger, "Wrong ProgramData account for this Program account");
+                        return Err(InstructionError::InvalidArgument);
+                    }
+                } else {
+                    log!(logger, "Invalid Program account");
+                    return Err(InstructionError::InvalidAccountData);
+                }
+                let offset = UpgradeableLoaderState::programdata_data_offset();
+                if programdata.data_len()? < offset {
+                    log!(logger, "ProgramData account too small");
+                    return Err(InstructionError::InvalidAccountData);
+                }
+                create_and_cache_executor(
+                    program.unsigned_key(),
+                    &programdata.try_account_ref()?.data[offset..],
+                    invoke_context,
+                )?
+            }
+            None => create_and_cache_executor(
+                program.unsigned_key(),
+                &program.try_account_ref()?.data,
+                invoke_context,
+            )?,
         };
         executor.execute(program_id, keyed_accounts, instruction_data, invoke_context)?
-    } else if !keyed_accounts.is_empty() {
-        match limited_deserialize(instruction_data)? {
-            LoaderInstruction::Write { offset, bytes } => {
-                if program.signer_key().is_none() {
-                    log!(logger, "key[0] did not sign the transaction");
-                    return Err(InstructionError::MissingRequiredSignature);
+    } else if bpf_loader_upgradeable::check_id(program_id) {
+        process_loader_upgradeable_instruction(
+            program_id,
+            keyed_accounts,
+            instruction_data,
+            invoke_context,
+        )?;
+    } else {
+        process_loader_instruction(keyed_accounts, instruction_data, invoke_context)?;
+    }
+    Ok(())
+}
+
+fn process_loader_upgradeable_instruction(
+    program_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+    instruction_data: &[u8],
+    invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    let logger = invoke_context.get_logger();
+    let account_iter = &mut keyed_accounts.iter();
+
+    match limited_deserialize(instruction_data)? {
+        UpgradeableLoaderInstruction::InitializeBuffer => {
+            let buffer = next_keyed_account(account_iter)?;
+            let authority = next_keyed_account(account_iter)?;
+
+            if UpgradeableLoaderState::Uninitialized != buffer.state()? {
+                log!(logger, "Buffer account already initialized");
+                return Err(InstructionError::AccountAlreadyInitialized);
+            }
+            buffer.set_state(&UpgradeableLoaderState::Buffer {
+                authority_address: Some(*authority.unsigned_key()),
+            })?;
+        }
+        UpgradeableLoaderInstruction::Write { offset, bytes } => {
+            let buffer = next_keyed_account(account_iter)?;
+            let authority = next_keyed_account(account_iter)?;
+
+            if let UpgradeableLoaderState::Buffer { authority_address } = buffer.state()? {
+                check_authority(&logger, authority_address, authority)?;
+            } else {
+                log!(logger, "Invalid Buffer account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+            write_program_data(
+                &logger,
+                buffer,
+                UpgradeableLoaderState::buffer_data_offset() + offset as usize,
+                &bytes,
+            )?;
+        }
+        UpgradeableLoaderInstruction::DeployWithMaxDataLen { max_data_len } => {
+            let payer = next_keyed_account(account_iter)?;
+            let programdata = next_keyed_account(account_iter)?;
+            let program = next_keyed_account(account_iter)?;
+            let buffer = next_keyed_account(account_iter)?;
+            let rent = from_keyed_account::<Rent>(next_keyed_account(account_iter)?)?;
+            let clock = from_keyed_account::<Clock>(next_keyed_account(account_iter)?)?;
+            let system = next_keyed_account(account_iter)?;
+            let authority = next_keyed_account(account_iter)?;
+
+            // Verify Program account
+
+            if UpgradeableLoaderState::Uninitialized != program.state()? {
+                log!(logger, "Program account already initialized");
+                return Err(InstructionError::AccountAlreadyInitialized);
+            }
+            if program.data_len()? < UpgradeableLoaderState::program_len() {
+                log!(logger, "Program account too small");
+                return Err(InstructionError::AccountDataTooSmall);
+            }
+            if program.lamports()? < rent.minimum_balance(program.data_len()?) {
+                log!(logger, "Program account not rent-exempt");
+                return Err(InstructionError::ExecutableAccountNotRentExempt);
+            }
+
+            // Verify Buffer account
+
+            if let UpgradeableLoaderState::Buffer { authority_address } = buffer.state()? {
+                check_authority(&logger, authority_address, authority)?;
+            } else {
+                log!(logger, "Invalid Buffer account");
+                return Err(InstructionError::InvalidArgument);
+            }
+            let buffer_data_offset = UpgradeableLoaderState::buffer_data_offset();
+            let buffer_data_len = buffer.data_len()?.saturating_sub(buffer_data_offset);
+            if buffer_data_len == 0 {
+                log!(logger, "Buffer account too small");
+                return Err(InstructionError::InvalidAccountData);
+            }
+            if max_data_len < buffer_data_len {
+                log!(logger, "Max data length is too small to hold Buffer data");
+                return Err(InstructionError::AccountDataTooSmall);
+            }
+
+            // Create the ProgramData account, signing for its derived address
+
+            let (derived_address, bump_seed) =
+                Pubkey::find_program_address(&[program.unsigned_key().as_ref()], program_id);
+            if derived_address != *programdata.unsigned_key() {
+                log!(logger, "ProgramData address is not derived");
+                return Err(InstructionError::InvalidArgument);
+            }
+            let programdata_len = UpgradeableLoaderState::programdata_len(max_data_len);
+            MessageProcessor::native_invoke(
+                invoke_context,
+                system_instruction::create_account(
+                    payer.unsigned_key(),
+                    programdata.unsigned_key(),
+                    1.max(rent.minimum_balance(programdata_len)),
+                    programdata_len as u64,
+                    program_id,
+                ),
+                &[payer, programdata, system],
+                &[&[program.unsigned_key().as_ref(), &[bump_seed]]],
+            )?;
+
+            // Load and verify the program bits
+
+            let _ = create_and_cache_executor(
+                program.unsigned_key(),
+                &buffer.try_account_ref()?.data[buffer_data_offset..],
+                invoke_context,
+            )?;
+
+            // Update the ProgramData account and record the program bits
+
+            programdata.set_state(&UpgradeableLoaderState::ProgramData {
+                slot: clock.slot,
+                upgrade_authority_address: Some(*authority.unsigned_key()),
+            })?;
+            let programdata_data_offset = UpgradeableLoaderState::programdata_data_offset();
+            programdata.try_account_ref_mut()?.data
+                [programdata_data_offset..programdata_data_offset + buffer_data_len]
+                .copy_from_slice(&buffer.try_account_ref()?.data[buffer_data_offset..]);
+
+            // Update the Program account
+
+            program.set_state(&UpgradeableLoaderState::Program {
+                programdata_address: *programdata.unsigned_key(),
+            })?;
+            program.try_account_ref_mut()?.executable = true;
+
+            // Drain the Buffer account back to the payer
+
+            payer.try_account_ref_mut()?.lamports += buffer.lamports()?;
+            buffer.try_account_ref_mut()?.lamports = 0;
+
+            log!(logger, "Deployed program {:?}", program.unsigned_key());
+        }
+        UpgradeableLoaderInstruction::Upgrade => {
+            let programdata = next_keyed_account(account_iter)?;
+            let program = next_keyed_account(account_iter)?;
+            let buffer = next_keyed_account(account_iter)?;
+            let spill = next_keyed_account(account_iter)?;
+            let rent = from_keyed_account::<Rent>(next_keyed_account(account_iter)?)?;
+            let clock = from_keyed_account::<Clock>(next_keyed_account(account_iter)?)?;
+            let authority = next_keyed_account(account_iter)?;
+
+            // Verify Program account
+
+            if !program.executable()? {
+                log!(logger, "Program account not executable");
+                return Err(InstructionError::AccountNotExecutable);
+            }
+            // Transactions invoking the program read-lock its account, but
+            // load its ProgramData account without a lock.  Write-locking the
+            // Program account keeps them from running alongside the upgrade
+            if !program.is_writable() {
+                log!(logger, "Program account not writeable");
+                return Err(InstructionError::InvalidArgument);
+            }
+            if program.owner()? != *program_id {
+                log!(logger, "Program account not owned by loader");
+                return Err(InstructionError::IncorrectProgramId);
+            }
+            if let UpgradeableLoaderState::Program {
+                programdata_address,
+            } = program.state()?
+            {
+                if programdata_address != *programdata.unsigned_key() {
+                    log!(logger, "Program and ProgramData account mismatch");
+                    return Err(InstructionError::InvalidArgument);
                 }
-                let offset = offset as usize;
-                let len = bytes.len();
-                if program.data_len()? < offset + len {
-                    log!(
-                        logger,
-                        "Write overflow: {} < {}",
-                        program.data_len()?,
-                        offset + len
-                    );
-                    return Err(InstructionError::AccountDataTooSmall);
+            } else {
+                log!(logger, "Invalid Program account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+
+            // Verify Buffer account
+
+            if let UpgradeableLoaderState::Buffer { authority_address } = buffer.state()? {
+                if authority_address != Some(*authority.unsigned_key()) {
+                    log!(logger, "Buffer and upgrade authority don't match");
+                    return Err(InstructionError::InvalidArgument);
                 }
-                program.try_account_ref_mut()?.data[offset..offset + len].copy_from_slice(&bytes);
+            } else {
+                log!(logger, "Invalid Buffer account");
+                return Err(InstructionError::InvalidArgument);
+            }
+            let buffer_data_offset = UpgradeableLoaderState::buffer_data_offset();
+            let buffer_data_len = buffer.data_len()?.saturating_sub(buffer_data_offset);
+            if buffer_data_len == 0 {
+                log!(logger, "Buffer account too small");
+                return Err(InstructionError::InvalidAccountData);
+            }
+
+            // Verify ProgramData account
+
+            let programdata_data_offset = UpgradeableLoaderState::programdata_data_offset();
+            let programdata_balance_required = 1.max(rent.minimum_balance(programdata.data_len()?));
+            if programdata.data_len()? < UpgradeableLoaderState::programdata_len(buffer_data_len) {
+                log!(logger, "ProgramData account not large enough");
+                return Err(InstructionError::AccountDataTooSmall);
+            }
+            if programdata.lamports()? + buffer.lamports()? < programdata_balance_required {
+                log!(logger, "Buffer account balance too low to fund upgrade");
+                return Err(InstructionError::InsufficientFunds);
+            }
+            if let UpgradeableLoaderState::ProgramData {
+                slot: _,
+                upgrade_authority_address,
+            } = programdata.state()?
+            {
+                check_authority(&logger, upgrade_authority_address, authority)?;
+            } else {
+                log!(logger, "Invalid ProgramData account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+
+            // Load and verify the program bits, replacing the cached executor
+
+            let _ = create_and_cache_executor(
+                program.unsigned_key(),
+                &buffer.try_account_ref()?.data[buffer_data_offset..],
+                invoke_context,
+            )?;
+
+            // Update the ProgramData account, record the upgraded data, and
+            // zero the rest
+
+            programdata.set_state(&UpgradeableLoaderState::ProgramData {
+                slot: clock.slot,
+                upgrade_authority_address: Some(*authority.unsigned_key()),
+            })?;
+            {
+                let mut programdata_account = programdata.try_account_ref_mut()?;
+                let (program_bytes, remainder) = programdata_account.data
+                    [programdata_data_offset..]
+                    .split_at_mut(buffer_data_len);
+                program_bytes
+                    .copy_from_slice(&buffer.try_account_ref()?.data[buffer_data_offset..]);
+                remainder.iter_mut().for_each(|byte| *byte = 0);
             }
-            LoaderInstruction::Finalize => {
-                if program.signer_key().is_none() {
-                    log!(logger, "key[0] did not sign the transaction");
-                    return Err(InstructionError::MissingRequiredSignature);
+
+            // Fund the ProgramData account to rent-exemption, spill the rest
+
+            spill.try_account_ref_mut()?.lamports +=
+                programdata.lamports()? + buffer.lamports()? - programdata_balance_required;
+            buffer.try_account_ref_mut()?.lamports = 0;
+            programdata.try_account_ref_mut()?.lamports = programdata_balance_required;
+
+            log!(logger, "Upgraded program {:?}", program.unsigned_key());
+        }
+        UpgradeableLoaderInstruction::SetAuthority => {
+            let account = next_keyed_account(account_iter)?;
+            let present_authority = next_keyed_account(account_iter)?;
+            let new_authority = next_keyed_account(account_iter)
+                .ok()
+                .map(|account| *account.unsigned_key());
+
+            match account.state()? {
+                UpgradeableLoaderState::Buffer { authority_address } => {
+                    if new_authority.is_none() {
+                        log!(logger, "Buffer authority is not optional");
+                        return Err(InstructionError::InvalidArgument);
+                    }
+                    check_authority(&logger, authority_address, present_authority)?;
+                    account.set_state(&UpgradeableLoaderState::Buffer {
+                        authority_address: new_authority,
+                    })?;
+                }
+                UpgradeableLoaderState::ProgramData {
+                    slot,
+                    upgrade_authority_address,
+                } => {
+                    check_authority(&logger, upgrade_authority_address, present_authority)?;
+                    account.set_state(&UpgradeableLoaderState::ProgramData {
+                        slot,
+                        upgrade_authority_address: new_authority,
+                    })?;
                 }
+                _ => {
+                    log!(logger, "Account does not support authorities");
+                    return Err(InstructionError::InvalidAccountData);
+                }
+            }
 
-                let _ = create_and_cache_executor(program, invoke_context)?;
-                program.try_account_ref_mut()?.executable = true;
-                log!(
-                    logger,
-                    "Finalized account {:?}",
-                    program.signer_key().unwrap()
-                );
+            log!(logger, "New authority {:?}", new_authority);
+        }
+    }
+
+    Ok(())
+}
+
+fn process_loader_instruction(
+    keyed_accounts: &[KeyedAccount],
+    instruction_data: &[u8],
+    invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    let logger = invoke_context.get_logger();
+    let program = next_keyed_account(&mut keyed_accounts.iter())?;
+    match limited_deserialize(instruction_data)? {
+        LoaderInstruction::Write { offset, bytes } => {
+            if program.signer_key().is_none() {
+                log!(logger, "key[0] did not sign the transaction");
+                return Err(InstructionError::MissingRequiredSignature);
             }
+            write_program_data(&logger, program, offset as usize, &bytes)?;
+        }
+        LoaderInstruction::Finalize => {
+            if program.signer_key().is_none() {
+                log!(logger, "key[0] did not sign the transaction");
+                return Err(InstructionError::MissingRequiredSignature);
+            }
+
+            let _ = create_and_cache_executor(
+                program.unsigned_key(),
+                &program.try_account_ref()?.data,
+                invoke_context,
+            )?;
+            program.try_account_ref_mut()?.executable = true;
+            log!(
+                logger,
+                "Finalized account {:?}",
+                program.signer_key().unwrap()
+            );
         }
     }
     Ok(())
 }
 
+fn check_authority(
+    logger: &Rc<RefCell<dyn Logger>>,
+    authority_address: Option<Pubkey>,
+    authority: &KeyedAccount,
+) -> Result<(), InstructionError> {
+    match authority_address {
+        None => {
+            log!(logger, "Account is immutable");
+            Err(InstructionError::InvalidArgument)
+        }
+        Some(address) if address != *authority.unsigned_key() => {
+            log!(logger, "Incorrect authority provided");
+            Err(InstructionError::InvalidArgument)
+        }
+        Some(_) if authority.signer_key().is_none() => {
+            log!(logger, "Authority did not sign");
+            Err(InstructionError::MissingRequiredSignature)
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn write_program_data(
+    logger: &Rc<RefCell<dyn Logger>>,
+    account: &KeyedAccount,
+    offset: usize,
+    bytes: &[u8],
+) -> Result<(), InstructionError> {
+    let len = bytes.len();
+    if account.data_len()? < offset + len {
+        log!(
+            logger,
+            "Write overflow: {} < {}",
+            account.data_len()?,
+            offset + len
+        );
+        return Err(InstructionError::AccountDataTooSmall);
+    }
+    account.try_account_ref_mut()?.data[offset..offset + len].copy_from_slice(&bytes);
+    Ok(())
+}
+
 /// Passed to the VM to enforce the compute budget
 pub struct ThisInstructionMeter {
     pub compute_meter: Rc<RefCell<dyn ComputeMeter>>,
@@ -259,6 +649,10 @@ impl Executor for BPFExecutor {
 
         let mut keyed_accounts_iter = keyed_accounts.iter();
         let program = next_keyed_account(&mut keyed_accounts_iter)?;
+        if bpf_loader_upgradeable::check_id(program_id) {
+            // The program's ProgramData account is not passed to the program
+            next_keyed_account(&mut keyed_accounts_iter)?;
+        }
 
         let parameter_accounts = keyed_accounts_iter.as_slice();
         let mut parameter_bytes = serialize_parameters(
@@ -336,14 +730,24 @@ impl Executor for BPFExecutor {
 mod tests {
     use super::*;
     use rand::Rng;
-    use solana_runtime::message_processor::{Executors, ThisInvokeContext};
+    use solana_runtime::{
+        bank::Bank,
+        bank_client::BankClient,
+        genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        message_processor::{Executors, ThisComputeMeter, ThisInvokeContext},
+    };
     use solana_sdk::{
         account::Account,
+        account_utils::StateMut,
+        client::SyncClient,
         feature_set::FeatureSet,
-        instruction::InstructionError,
+        instruction::{Instruction, InstructionError},
+        message::Message,
         process_instruction::{BpfComputeBudget, MockInvokeContext},
         pubkey::Pubkey,
         rent::Rent,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
     };
     use std::{cell::RefCell, fs::File, io::Read, ops::Range, rc::Rc};
 
@@ -588,10 +992,15 @@ mod tests {
                 max_call_depth: 20,
                 stack_frame_size: 4096,
                 log_pubkey_units: 100,
+                sysvar_base_cost: 100,
+                return_data_base_cost: 100,
+                log_data_byte_cost: 1,
             },
+            ThisComputeMeter::new_ref(1),
             Rc::new(RefCell::new(Executors::default())),
             None,
             Arc::new(FeatureSet::default()),
+            &[],
         );
         assert_eq!(
             Err(InstructionError::Custom(194969602)),
@@ -703,6 +1112,471 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bpf_loader_upgradeable_initialize_buffer() {
+        let buffer_address = Pubkey::new_unique();
+        let buffer_account = Account::new_ref(
+            1,
+            UpgradeableLoaderState::buffer_len(9),
+            &bpf_loader_upgradeable::id(),
+        );
+        let authority_address = Pubkey::new_unique();
+        let authority_account = Account::new_ref(1, 0, &Pubkey::new_unique());
+        let instruction_data =
+            bincode::serialize(&UpgradeableLoaderInstruction::InitializeBuffer).unwrap();
+
+        // Case: Success
+        let keyed_accounts = vec![
+            KeyedAccount::new(&buffer_address, false, &buffer_account),
+            KeyedAccount::new_readonly(&authority_address, false, &authority_account),
+        ];
+        assert_eq!(
+            Ok(()),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+        let state: UpgradeableLoaderState = buffer_account.borrow().state().unwrap();
+        assert_eq!(
+            state,
+            UpgradeableLoaderState::Buffer {
+                authority_address: Some(authority_address)
+            }
+        );
+
+        // Case: Already initialized
+        assert_eq!(
+            Err(InstructionError::AccountAlreadyInitialized),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_bpf_loader_upgradeable_write() {
+        let buffer_address = Pubkey::new_unique();
+        let buffer_account = Account::new_ref(
+            1,
+            UpgradeableLoaderState::buffer_len(9),
+            &bpf_loader_upgradeable::id(),
+        );
+        let authority_address = Pubkey::new_unique();
+        let authority_account = Account::new_ref(1, 0, &Pubkey::new_unique());
+        buffer_account
+            .borrow_mut()
+            .set_state(&UpgradeableLoaderState::Buffer {
+                authority_address: Some(authority_address),
+            })
+            .unwrap();
+        let write = |offset, bytes: &[u8]| {
+            bincode::serialize(&UpgradeableLoaderInstruction::Write {
+                offset,
+                bytes: bytes.to_vec(),
+            })
+            .unwrap()
+        };
+
+        // Case: Not signed
+        let keyed_accounts = vec![
+            KeyedAccount::new(&buffer_address, false, &buffer_account),
+            KeyedAccount::new_readonly(&authority_address, false, &authority_account),
+        ];
+        assert_eq!(
+            Err(InstructionError::MissingRequiredSignature),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &write(0, &[42; 9]),
+                &mut MockInvokeContext::default()
+            )
+        );
+
+        // Case: Wrong authority
+        let wrong_authority_address = Pubkey::new_unique();
+        let keyed_accounts = vec![
+            KeyedAccount::new(&buffer_address, false, &buffer_account),
+            KeyedAccount::new_readonly(&wrong_authority_address, true, &authority_account),
+        ];
+        assert_eq!(
+            Err(InstructionError::InvalidArgument),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &write(0, &[42; 9]),
+                &mut MockInvokeContext::default()
+            )
+        );
+
+        // Case: Write past the end of the buffer
+        let keyed_accounts = vec![
+            KeyedAccount::new(&buffer_address, false, &buffer_account),
+            KeyedAccount::new_readonly(&authority_address, true, &authority_account),
+        ];
+        assert_eq!(
+            Err(InstructionError::AccountDataTooSmall),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &write(1, &[42; 9]),
+                &mut MockInvokeContext::default()
+            )
+        );
+
+        // Case: Success
+        assert_eq!(
+            Ok(()),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &write(0, &[42; 9]),
+                &mut MockInvokeContext::default()
+            )
+        );
+        assert_eq!(
+            &buffer_account.borrow().data[UpgradeableLoaderState::buffer_data_offset()..],
+            &[42; 9]
+        );
+    }
+
+    #[test]
+    fn test_bpf_loader_upgradeable_set_authority() {
+        let programdata_address = Pubkey::new_unique();
+        let programdata_account = Account::new_ref(
+            1,
+            UpgradeableLoaderState::programdata_len(0),
+            &bpf_loader_upgradeable::id(),
+        );
+        let authority_address = Pubkey::new_unique();
+        let authority_account = Account::new_ref(1, 0, &Pubkey::new_unique());
+        let new_authority_address = Pubkey::new_unique();
+        programdata_account
+            .borrow_mut()
+            .set_state(&UpgradeableLoaderState::ProgramData {
+                slot: 42,
+                upgrade_authority_address: Some(authority_address),
+            })
+            .unwrap();
+        let instruction_data =
+            bincode::serialize(&UpgradeableLoaderInstruction::SetAuthority).unwrap();
+
+        // Case: Not signed
+        let keyed_accounts = vec![
+            KeyedAccount::new(&programdata_address, false, &programdata_account),
+            KeyedAccount::new_readonly(&authority_address, false, &authority_account),
+            KeyedAccount::new_readonly(&new_authority_address, false, &authority_account),
+        ];
+        assert_eq!(
+            Err(InstructionError::MissingRequiredSignature),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+
+        // Case: New authority
+        let keyed_accounts = vec![
+            KeyedAccount::new(&programdata_address, false, &programdata_account),
+            KeyedAccount::new_readonly(&authority_address, true, &authority_account),
+            KeyedAccount::new_readonly(&new_authority_address, false, &authority_account),
+        ];
+        assert_eq!(
+            Ok(()),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+        let state: UpgradeableLoaderState = programdata_account.borrow().state().unwrap();
+        assert_eq!(
+            state,
+            UpgradeableLoaderState::ProgramData {
+                slot: 42,
+                upgrade_authority_address: Some(new_authority_address),
+            }
+        );
+
+        // Case: Old authority no longer valid
+        assert_eq!(
+            Err(InstructionError::InvalidArgument),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+
+        // Case: Make immutable
+        let keyed_accounts = vec![
+            KeyedAccount::new(&programdata_address, false, &programdata_account),
+            KeyedAccount::new_readonly(&new_authority_address, true, &authority_account),
+        ];
+        assert_eq!(
+            Ok(()),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+        let state: UpgradeableLoaderState = programdata_account.borrow().state().unwrap();
+        assert_eq!(
+            state,
+            UpgradeableLoaderState::ProgramData {
+                slot: 42,
+                upgrade_authority_address: None,
+            }
+        );
+
+        // Case: Immutable
+        assert_eq!(
+            Err(InstructionError::InvalidArgument),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+
+        // Case: Buffer authority is not optional
+        let buffer_account = Account::new_ref(
+            1,
+            UpgradeableLoaderState::buffer_len(0),
+            &bpf_loader_upgradeable::id(),
+        );
+        buffer_account
+            .borrow_mut()
+            .set_state(&UpgradeableLoaderState::Buffer {
+                authority_address: Some(authority_address),
+            })
+            .unwrap();
+        let keyed_accounts = vec![
+            KeyedAccount::new(&programdata_address, false, &buffer_account),
+            KeyedAccount::new_readonly(&authority_address, true, &authority_account),
+        ];
+        assert_eq!(
+            Err(InstructionError::InvalidArgument),
+            process_instruction(
+                &bpf_loader_upgradeable::id(),
+                &keyed_accounts,
+                &instruction_data,
+                &mut MockInvokeContext::default()
+            )
+        );
+    }
+
+    fn write_upgradeable_buffer(
+        bank_client: &BankClient,
+        payer_keypair: &Keypair,
+        buffer_keypair: &Keypair,
+        elf: &[u8],
+    ) {
+        let chunk_size = 1024;
+        let message = Message::new(
+            &bpf_loader_upgradeable::create_buffer(
+                &payer_keypair.pubkey(),
+                &buffer_keypair.pubkey(),
+                &payer_keypair.pubkey(),
+                Rent::default().minimum_balance(UpgradeableLoaderState::buffer_len(elf.len())),
+                elf.len(),
+            ),
+            Some(&payer_keypair.pubkey()),
+        );
+        bank_client
+            .send_and_confirm_message(&[payer_keypair, buffer_keypair], message)
+            .unwrap();
+        for (i, chunk) in elf.chunks(chunk_size).enumerate() {
+            bank_client
+                .send_and_confirm_instruction(
+                    payer_keypair,
+                    bpf_loader_upgradeable::write(
+                        &buffer_keypair.pubkey(),
+                        &payer_keypair.pubkey(),
+                        (i * chunk_size) as u32,
+                        chunk.to_vec(),
+                    ),
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_bpf_loader_upgradeable_deploy_and_upgrade() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1_000_000_000);
+        let mut bank = Bank::new(&genesis_config);
+        bank.add_builtin(
+            "solana_bpf_loader_upgradeable_program",
+            bpf_loader_upgradeable::id(),
+            process_instruction,
+        );
+        let bank = Arc::new(bank);
+        let bank_client = BankClient::new_shared(&bank);
+
+        let mut file = File::open("test_elfs/noop_aligned.so").expect("file open failed");
+        let mut elf = Vec::new();
+        file.read_to_end(&mut elf).unwrap();
+
+        // Deploy
+        let buffer_keypair = Keypair::new();
+        let program_keypair = Keypair::new();
+        write_upgradeable_buffer(&bank_client, &mint_keypair, &buffer_keypair, &elf);
+        let message = Message::new(
+            &bpf_loader_upgradeable::deploy_with_max_program_len(
+                &mint_keypair.pubkey(),
+                &program_keypair.pubkey(),
+                &buffer_keypair.pubkey(),
+                &mint_keypair.pubkey(),
+                Rent::default().minimum_balance(UpgradeableLoaderState::program_len()),
+                elf.len() * 2,
+            ),
+            Some(&mint_keypair.pubkey()),
+        );
+        bank_client
+            .send_and_confirm_message(&[&mint_keypair, &program_keypair], message)
+            .unwrap();
+        assert!(bank.get_account(&buffer_keypair.pubkey()).is_none());
+
+        let programdata_address =
+            bpf_loader_upgradeable::get_program_data_address(&program_keypair.pubkey());
+        let program_account = bank.get_account(&program_keypair.pubkey()).unwrap();
+        assert!(program_account.executable);
+        assert_eq!(
+            program_account.state(),
+            Ok(UpgradeableLoaderState::Program {
+                programdata_address
+            })
+        );
+        let programdata_account = bank.get_account(&programdata_address).unwrap();
+        assert_eq!(
+            programdata_account.data.len(),
+            UpgradeableLoaderState::programdata_len(elf.len() * 2)
+        );
+        assert_eq!(
+            programdata_account.state(),
+            Ok(UpgradeableLoaderState::ProgramData {
+                slot: bank.slot(),
+                upgrade_authority_address: Some(mint_keypair.pubkey()),
+            })
+        );
+        let offset = UpgradeableLoaderState::programdata_data_offset();
+        assert_eq!(
+            &programdata_account.data[offset..offset + elf.len()],
+            &elf[..]
+        );
+
+        // Invoke
+        let instruction = Instruction::new(program_keypair.pubkey(), &(), vec![]);
+        bank_client
+            .send_and_confirm_instruction(&mint_keypair, instruction.clone())
+            .unwrap();
+
+        // Upgrade requires the upgrade authority
+        let buffer_keypair = Keypair::new();
+        write_upgradeable_buffer(&bank_client, &mint_keypair, &buffer_keypair, &elf);
+        let wrong_authority_keypair = Keypair::new();
+        let message = Message::new(
+            &[bpf_loader_upgradeable::upgrade(
+                &program_keypair.pubkey(),
+                &buffer_keypair.pubkey(),
+                &wrong_authority_keypair.pubkey(),
+                &mint_keypair.pubkey(),
+            )],
+            Some(&mint_keypair.pubkey()),
+        );
+        assert!(bank_client
+            .send_and_confirm_message(&[&mint_keypair, &wrong_authority_keypair], message)
+            .is_err());
+
+        // Upgrade requires a write lock on the Program account
+        let mut upgrade_instruction = bpf_loader_upgradeable::upgrade(
+            &program_keypair.pubkey(),
+            &buffer_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            &Pubkey::new_unique(),
+        );
+        upgrade_instruction.accounts[1].is_writable = false;
+        assert_eq!(
+            bank_client
+                .send_and_confirm_instruction(&mint_keypair, upgrade_instruction)
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+        );
+
+        // So transactions invoking the program can't be batched with an upgrade
+        let invoker_keypair = Keypair::new();
+        bank_client
+            .transfer_and_confirm(1_000_000, &mint_keypair, &invoker_keypair.pubkey())
+            .unwrap();
+        let upgrade_instruction = bpf_loader_upgradeable::upgrade(
+            &program_keypair.pubkey(),
+            &buffer_keypair.pubkey(),
+            &mint_keypair.pubkey(),
+            &Pubkey::new_unique(),
+        );
+        let txs = vec![
+            Transaction::new_signed_with_payer(
+                &[instruction.clone()],
+                Some(&invoker_keypair.pubkey()),
+                &[&invoker_keypair],
+                bank.last_blockhash(),
+            ),
+            Transaction::new_signed_with_payer(
+                &[upgrade_instruction],
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair],
+                bank.last_blockhash(),
+            ),
+        ];
+        let batch = bank.prepare_batch(&txs, None);
+        assert_eq!(
+            batch.lock_results(),
+            &vec![Ok(()), Err(TransactionError::AccountInUse)]
+        );
+        drop(batch);
+
+        // Upgrade
+        let spill_address = Pubkey::new_unique();
+        bank_client
+            .send_and_confirm_instruction(
+                &mint_keypair,
+                bpf_loader_upgradeable::upgrade(
+                    &program_keypair.pubkey(),
+                    &buffer_keypair.pubkey(),
+                    &mint_keypair.pubkey(),
+                    &spill_address,
+                ),
+            )
+            .unwrap();
+        assert!(bank.get_account(&buffer_keypair.pubkey()).is_none());
+        assert!(bank.get_balance(&spill_address) > 0);
+        let programdata_account = bank.get_account(&programdata_address).unwrap();
+        assert_eq!(
+            &programdata_account.data[offset..offset + elf.len()],
+            &elf[..]
+        );
+        bank_client
+            .send_and_confirm_instruction(&mint_keypair, instruction)
+            .unwrap();
+    }
+
     /// fuzzing utility function
     fn fuzz<F>(
         bytes: &[u8],
@@ -12,19 +12,24 @@ use solana_runtime::message_processor::MessageProcessor;
 use solana_sdk::{
     account::Account,
     account_info::AccountInfo,
+    account_utils::StateMut,
     bpf_loader_deprecated,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS},
     feature_set::{
-        pubkey_log_syscall_enabled, ristretto_mul_syscall_enabled, sha256_syscall_enabled,
-        sol_log_compute_units_syscall,
+        log_data_syscall_enabled, pubkey_log_syscall_enabled, return_data_syscall_enabled,
+        ristretto_mul_syscall_enabled, sha256_syscall_enabled, sol_log_compute_units_syscall,
+        sysvar_syscalls,
     },
     hash::{Hasher, HASH_BYTES},
     instruction::{AccountMeta, Instruction, InstructionError},
     keyed_account::KeyedAccount,
     message::Message,
     process_instruction::{stable_log, ComputeMeter, InvokeContext, Logger},
+    program::MAX_RETURN_DATA,
     program_error::ProgramError,
     pubkey::{Pubkey, PubkeyError},
+    sysvar::{self, clock::Clock, epoch_schedule::EpochSchedule, fees::Fees, rent::Rent, Sysvar},
 };
 use std::{
     alloc::Layout,
@@ -60,6 +65,10 @@ pub enum SyscallError {
     PrivilegeEscalation,
     #[error("Unaligned pointer")]
     UnalignedPointer,
+    #[error("Sysvar {0} is not available")]
+    UnsupportedSysvar(Pubkey),
+    #[error("Return data too large ({0} > {1})")]
+    ReturnDataTooLarge(u64, u64),
 }
 impl From<SyscallError> for EbpfError<BPFError> {
     fn from(error: SyscallError) -> Self {
@@ -107,6 +116,10 @@ pub fn register_syscalls(
         syscall_registry.register_syscall_by_name(b"sol_log_pubkey", SyscallLogPubkey::call)?;
     }
 
+    if invoke_context.is_feature_active(&log_data_syscall_enabled::id()) {
+        syscall_registry.register_syscall_by_name(b"sol_log_data_", SyscallLogData::call)?;
+    }
+
     if invoke_context.is_feature_active(&sha256_syscall_enabled::id()) {
         syscall_registry.register_syscall_by_name(b"sol_sha256", SyscallSha256::call)?;
     }
@@ -126,6 +139,26 @@ pub fn register_syscalls(
         .register_syscall_by_name(b"sol_invoke_signed_rust", SyscallInvokeSignedRust::call)?;
     syscall_registry.register_syscall_by_name(b"sol_alloc_free_", SyscallAllocFree::call)?;
 
+    if invoke_context.is_feature_active(&sysvar_syscalls::id()) {
+        syscall_registry
+            .register_syscall_by_name(b"sol_get_clock_sysvar", SyscallGetClockSysvar::call)?;
+        syscall_registry.register_syscall_by_name(
+            b"sol_get_epoch_schedule_sysvar",
+            SyscallGetEpochScheduleSysvar::call,
+        )?;
+        syscall_registry
+            .register_syscall_by_name(b"sol_get_fees_sysvar", SyscallGetFeesSysvar::call)?;
+        syscall_registry
+            .register_syscall_by_name(b"sol_get_rent_sysvar", SyscallGetRentSysvar::call)?;
+    }
+
+    if invoke_context.is_feature_active(&return_data_syscall_enabled::id()) {
+        syscall_registry
+            .register_syscall_by_name(b"sol_set_return_data", SyscallSetReturnData::call)?;
+        syscall_registry
+            .register_syscall_by_name(b"sol_get_return_data", SyscallGetReturnData::call)?;
+    }
+
     Ok(syscall_registry)
 }
 
@@ -181,6 +214,21 @@ pub fn bind_syscall_context_objects<'a>(
             None,
         )?;
     }
+    if invoke_context.is_feature_active(&log_data_syscall_enabled::id()) {
+        vm.bind_syscall_context_object(
+            Box::new(SyscallLogData {
+                cost: bpf_compute_budget.log_units,
+                byte_cost: bpf_compute_budget.log_data_byte_cost,
+                compute_meter: invoke_context.get_compute_meter(),
+                logger: invoke_context.get_logger(),
+                program_id: *invoke_context
+                    .get_caller()
+                    .map_err(SyscallError::InstructionError)?,
+                loader_id,
+            }),
+            None,
+        )?;
+    }
 
     if invoke_context.is_feature_active(&sha256_syscall_enabled::id()) {
         vm.bind_syscall_context_object(
@@ -234,6 +282,64 @@ pub fn bind_syscall_context_objects<'a>(
         None,
     )?;
 
+    // Sysvar syscalls
+
+    let is_sysvar_syscalls_active = invoke_context
+        .borrow()
+        .is_feature_active(&sysvar_syscalls::id());
+    if is_sysvar_syscalls_active {
+        vm.bind_syscall_context_object(
+            Box::new(SyscallGetClockSysvar {
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            }),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            Box::new(SyscallGetEpochScheduleSysvar {
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            }),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            Box::new(SyscallGetFeesSysvar {
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            }),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            Box::new(SyscallGetRentSysvar {
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            }),
+            None,
+        )?;
+    }
+
+    // Return data
+
+    let is_return_data_syscall_active = invoke_context
+        .borrow()
+        .is_feature_active(&return_data_syscall_enabled::id());
+    if is_return_data_syscall_active {
+        vm.bind_syscall_context_object(
+            Box::new(SyscallSetReturnData {
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            }),
+            None,
+        )?;
+        vm.bind_syscall_context_object(
+            Box::new(SyscallGetReturnData {
+                invoke_context: invoke_context.clone(),
+                loader_id,
+            }),
+            None,
+        )?;
+    }
+
     // Memory allocator
 
     vm.bind_syscall_context_object(
@@ -537,6 +643,65 @@ impl<'a> SyscallObject<BPFError> for SyscallLogPubkey<'a> {
     }
 }
 
+/// Log a list of byte slices as a single structured event
+pub struct SyscallLogData<'a> {
+    cost: u64,
+    byte_cost: u64,
+    compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+    logger: Rc<RefCell<dyn Logger>>,
+    program_id: Pubkey,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallLogData<'a> {
+    fn call(
+        &mut self,
+        vals_addr: u64,
+        vals_len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        question_mark!(
+            self.compute_meter
+                .consume(self.cost.saturating_add(self.cost.saturating_mul(vals_len))),
+            result
+        );
+        let vals = question_mark!(
+            translate_slice::<&[u8]>(
+                memory_mapping,
+                AccessType::Load,
+                vals_addr,
+                vals_len,
+                self.loader_id
+            ),
+            result
+        );
+        let mut fields = Vec::with_capacity(vals.len());
+        for val in vals.iter() {
+            question_mark!(
+                self.compute_meter
+                    .consume(self.byte_cost.saturating_mul(val.len() as u64)),
+                result
+            );
+            let bytes = question_mark!(
+                translate_slice::<u8>(
+                    memory_mapping,
+                    AccessType::Load,
+                    val.as_ptr() as u64,
+                    val.len() as u64,
+                    self.loader_id
+                ),
+                result
+            );
+            fields.push(bytes);
+        }
+        stable_log::program_data(&self.logger, &self.program_id, &fields);
+        *result = Ok(0);
+    }
+}
+
 /// Dynamic memory allocation syscall called when the BPF program calls
 /// `sol_alloc_free_()`.  The allocator is expected to allocate/free
 /// from/to a given chunk of memory and enforce size restrictions.  The
@@ -777,6 +942,243 @@ impl<'a> SyscallObject<BPFError> for SyscallRistrettoMul<'a> {
     }
 }
 
+fn get_sysvar<T: Sysvar>(
+    id: &Pubkey,
+    var_addr: u64,
+    loader_id: &Pubkey,
+    memory_mapping: &MemoryMapping,
+    invoke_context: &Rc<RefCell<&mut dyn InvokeContext>>,
+) -> Result<u64, EbpfError<BPFError>> {
+    let invoke_context = invoke_context
+        .try_borrow()
+        .map_err(|_| SyscallError::InvokeContextBorrowFailed)?;
+    invoke_context.get_compute_meter().consume(
+        invoke_context.get_bpf_compute_budget().sysvar_base_cost + size_of::<T>() as u64,
+    )?;
+
+    let var = translate_type_mut::<T>(memory_mapping, AccessType::Store, var_addr, loader_id)?;
+    let sysvar_data = invoke_context
+        .get_sysvar_data(id)
+        .ok_or(SyscallError::UnsupportedSysvar(*id))?;
+    *var = bincode::deserialize(sysvar_data)
+        .map_err(|_| SyscallError::InstructionError(InstructionError::InvalidAccountData))?;
+
+    Ok(SUCCESS)
+}
+
+/// Get a Clock sysvar
+struct SyscallGetClockSysvar<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetClockSysvar<'a> {
+    fn call(
+        &mut self,
+        var_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = get_sysvar::<Clock>(
+            &sysvar::clock::id(),
+            var_addr,
+            self.loader_id,
+            memory_mapping,
+            &self.invoke_context,
+        );
+    }
+}
+/// Get a EpochSchedule sysvar
+struct SyscallGetEpochScheduleSysvar<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetEpochScheduleSysvar<'a> {
+    fn call(
+        &mut self,
+        var_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = get_sysvar::<EpochSchedule>(
+            &sysvar::epoch_schedule::id(),
+            var_addr,
+            self.loader_id,
+            memory_mapping,
+            &self.invoke_context,
+        );
+    }
+}
+/// Get a Fees sysvar
+struct SyscallGetFeesSysvar<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetFeesSysvar<'a> {
+    fn call(
+        &mut self,
+        var_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = get_sysvar::<Fees>(
+            &sysvar::fees::id(),
+            var_addr,
+            self.loader_id,
+            memory_mapping,
+            &self.invoke_context,
+        );
+    }
+}
+/// Get a Rent sysvar
+struct SyscallGetRentSysvar<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetRentSysvar<'a> {
+    fn call(
+        &mut self,
+        var_addr: u64,
+        _arg2: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        *result = get_sysvar::<Rent>(
+            &sysvar::rent::id(),
+            var_addr,
+            self.loader_id,
+            memory_mapping,
+            &self.invoke_context,
+        );
+    }
+}
+
+/// Set the return data of the running program
+struct SyscallSetReturnData<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallSetReturnData<'a> {
+    fn call(
+        &mut self,
+        addr: u64,
+        len: u64,
+        _arg3: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        let mut invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow_mut()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+        if len > MAX_RETURN_DATA as u64 {
+            *result = Err(SyscallError::ReturnDataTooLarge(len, MAX_RETURN_DATA as u64).into());
+            return;
+        }
+        let cost = invoke_context
+            .get_bpf_compute_budget()
+            .return_data_base_cost
+            .saturating_add(len);
+        question_mark!(invoke_context.get_compute_meter().consume(cost), result);
+
+        let return_data = if len == 0 {
+            Vec::new()
+        } else {
+            question_mark!(
+                translate_slice::<u8>(memory_mapping, AccessType::Load, addr, len, self.loader_id),
+                result
+            )
+            .to_vec()
+        };
+        question_mark!(
+            invoke_context
+                .set_return_data(return_data)
+                .map_err(SyscallError::InstructionError),
+            result
+        );
+
+        *result = Ok(0);
+    }
+}
+
+/// Get the return data set by the most recently invoked program
+struct SyscallGetReturnData<'a> {
+    invoke_context: Rc<RefCell<&'a mut dyn InvokeContext>>,
+    loader_id: &'a Pubkey,
+}
+impl<'a> SyscallObject<BPFError> for SyscallGetReturnData<'a> {
+    fn call(
+        &mut self,
+        return_data_addr: u64,
+        len: u64,
+        program_id_addr: u64,
+        _arg4: u64,
+        _arg5: u64,
+        memory_mapping: &MemoryMapping,
+        result: &mut Result<u64, EbpfError<BPFError>>,
+    ) {
+        let invoke_context = question_mark!(
+            self.invoke_context
+                .try_borrow()
+                .map_err(|_| SyscallError::InvokeContextBorrowFailed),
+            result
+        );
+        let (program_id, return_data) = invoke_context.get_return_data();
+        let length = len.min(return_data.len() as u64);
+        let cost = invoke_context
+            .get_bpf_compute_budget()
+            .return_data_base_cost
+            .saturating_add(length);
+        question_mark!(invoke_context.get_compute_meter().consume(cost), result);
+
+        if length != 0 {
+            let return_data_result = question_mark!(
+                translate_slice_mut::<u8>(
+                    memory_mapping,
+                    AccessType::Store,
+                    return_data_addr,
+                    length,
+                    self.loader_id,
+                ),
+                result
+            );
+            return_data_result.copy_from_slice(&return_data[..length as usize]);
+
+            let program_id_result = question_mark!(
+                translate_type_mut::<Pubkey>(
+                    memory_mapping,
+                    AccessType::Store,
+                    program_id_addr,
+                    self.loader_id,
+                ),
+                result
+            );
+            *program_id_result = program_id;
+        }
+
+        // Return the actual length, so the caller can tell if the data was truncated
+        *result = Ok(return_data.len() as u64);
+    }
+}
+
 // Cross-program invocation syscalls
 
 struct AccountReferences<'a> {
@@ -1339,6 +1741,36 @@ fn verify_instruction<'a>(
     Ok(())
 }
 
+/// An upgradeable callee also needs its ProgramData account, which the caller
+/// must have been given
+fn get_upgradeable_executable(
+    program_account: &RefCell<Account>,
+    keyed_accounts: &[KeyedAccount],
+) -> Result<Option<(Pubkey, RefCell<Account>)>, EbpfError<BPFError>> {
+    let program_account = program_account.borrow();
+    if program_account.owner != bpf_loader_upgradeable::id() {
+        return Ok(None);
+    }
+    if let UpgradeableLoaderState::Program {
+        programdata_address,
+    } = program_account
+        .state()
+        .map_err(SyscallError::InstructionError)?
+    {
+        if let Some(keyed_account) = keyed_accounts
+            .iter()
+            .find(|keyed_account| programdata_address == *keyed_account.unsigned_key())
+        {
+            let programdata = keyed_account
+                .try_account_ref()
+                .map_err(SyscallError::InstructionError)?
+                .clone();
+            return Ok(Some((programdata_address, RefCell::new(programdata))));
+        }
+    }
+    Err(SyscallError::InstructionError(InstructionError::MissingAccount).into())
+}
+
 /// Call process instruction, common to both Rust and C
 fn call<'a>(
     syscall: &mut dyn SyscallInvokeSigned<'a>,
@@ -1379,13 +1811,25 @@ fn call<'a>(
 
     invoke_context.record_instruction(&instruction);
 
+    // Return data is cleared so the caller only sees what the callee sets
+    if invoke_context.is_feature_active(&return_data_syscall_enabled::id()) {
+        invoke_context
+            .set_return_data(Vec::new())
+            .map_err(SyscallError::InstructionError)?;
+    }
+
     // Process instruction
 
     let program_account = (*accounts[callee_program_id_index]).clone();
     if !program_account.borrow().executable {
         return Err(SyscallError::InstructionError(InstructionError::AccountNotExecutable).into());
     }
-    let executable_accounts = vec![(callee_program_id, program_account)];
+    let programdata_executable =
+        get_upgradeable_executable(&program_account, syscall.get_callers_keyed_accounts())?;
+    let mut executable_accounts = vec![(callee_program_id, program_account)];
+    if let Some(executable) = programdata_executable {
+        executable_accounts.push(executable);
+    }
 
     #[allow(clippy::deref_addrof)]
     match MessageProcessor::process_cross_program_instruction(
@@ -1440,7 +1884,7 @@ mod tests {
     use solana_sdk::{
         bpf_loader,
         hash::hashv,
-        process_instruction::{MockComputeMeter, MockLogger},
+        process_instruction::{MockComputeMeter, MockInvokeContext, MockLogger},
     };
     use std::str::FromStr;
 
@@ -1789,6 +2233,140 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_syscall_sol_log_data() {
+        let bytes1 = "Gaggablaghblagh!";
+        let bytes2 = "flurbos";
+
+        struct MockSlice {
+            pub addr: u64,
+            pub len: usize,
+        }
+        let mock_slice1 = MockSlice {
+            addr: 4096,
+            len: bytes1.len(),
+        };
+        let mock_slice2 = MockSlice {
+            addr: 8192,
+            len: bytes2.len(),
+        };
+        let bytes_to_log = [mock_slice1, mock_slice2];
+        let ro_len = bytes_to_log.len() as u64;
+        let ro_va = 96;
+        let memory_mapping = MemoryMapping::new_from_regions(vec![
+            MemoryRegion {
+                host_addr: bytes1.as_ptr() as *const _ as u64,
+                vm_addr: 4096,
+                len: bytes1.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            },
+            MemoryRegion {
+                host_addr: bytes2.as_ptr() as *const _ as u64,
+                vm_addr: 8192,
+                len: bytes2.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            },
+            MemoryRegion {
+                host_addr: bytes_to_log.as_ptr() as *const _ as u64,
+                vm_addr: 96,
+                len: 32,
+                vm_gap_shift: 63,
+                is_writable: false,
+            },
+        ]);
+        let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
+            Rc::new(RefCell::new(MockComputeMeter {
+                // base and per-slice cost of both successful and failed calls, plus per-byte cost
+                remaining: 2 * (1 + ro_len) + (bytes1.len() + bytes2.len()) as u64,
+            }));
+        struct EventLogger {
+            log: Rc<RefCell<Vec<String>>>,
+            events: Rc<RefCell<Vec<(Pubkey, Vec<Vec<u8>>)>>>,
+        }
+        impl Logger for EventLogger {
+            fn log_enabled(&self) -> bool {
+                true
+            }
+            fn log(&self, message: &str) {
+                self.log.borrow_mut().push(message.to_string());
+            }
+            fn log_event(&self, program_id: &Pubkey, data: &[&[u8]]) {
+                self.events.borrow_mut().push((
+                    *program_id,
+                    data.iter().map(|bytes| bytes.to_vec()).collect(),
+                ));
+            }
+        }
+        let log = Rc::new(RefCell::new(vec![]));
+        let events = Rc::new(RefCell::new(vec![]));
+        let logger: Rc<RefCell<dyn Logger>> = Rc::new(RefCell::new(EventLogger {
+            log: log.clone(),
+            events: events.clone(),
+        }));
+        let program_id = solana_sdk::pubkey::new_rand();
+        let mut syscall = SyscallLogData {
+            cost: 1,
+            byte_cost: 1,
+            compute_meter,
+            logger,
+            program_id,
+            loader_id: &bpf_loader_deprecated::id(),
+        };
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(ro_va, ro_len, 0, 0, 0, &memory_mapping, &mut result);
+        result.unwrap();
+        assert_eq!(log.borrow().len(), 1);
+        assert_eq!(
+            log.borrow()[0],
+            "Program data: R2FnZ2FibGFnaGJsYWdoIQ== Zmx1cmJvcw=="
+        );
+        assert_eq!(
+            *events.borrow(),
+            vec![(
+                program_id,
+                vec![bytes1.as_bytes().to_vec(), bytes2.as_bytes().to_vec()]
+            )]
+        );
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(
+            ro_va - 1, // AccessViolation
+            ro_len,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_access_violation!(result, ro_va - 1, ro_len);
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        syscall.call(ro_va, ro_len, 0, 0, 0, &memory_mapping, &mut result);
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::InstructionError(InstructionError::ComputationalBudgetExceeded)
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn test_syscall_sol_log_data_feature_gate() {
+        let hash = solana_rbpf::ebpf::hash_symbol_name(b"sol_log_data_");
+
+        let mut invoke_context = MockInvokeContext::default();
+        let syscall_registry = register_syscalls(&mut invoke_context).unwrap();
+        assert!(syscall_registry.lookup_syscall(hash).is_some());
+
+        invoke_context
+            .disabled_features
+            .insert(log_data_syscall_enabled::id());
+        let syscall_registry = register_syscalls(&mut invoke_context).unwrap();
+        assert!(syscall_registry.lookup_syscall(hash).is_none());
+    }
+
     #[test]
     fn test_syscall_sol_log_u64() {
         let compute_meter: Rc<RefCell<dyn ComputeMeter>> =
@@ -1974,6 +2552,199 @@ mod tests {
         check_alignment::<u128>();
     }
 
+    #[test]
+    fn test_syscall_get_sysvar() {
+        // Test clock sysvar
+        {
+            let got_clock = Clock::default();
+            let got_clock_va = 2048;
+
+            let memory_mapping = MemoryMapping::new_from_regions(vec![MemoryRegion {
+                host_addr: &got_clock as *const _ as u64,
+                vm_addr: got_clock_va,
+                len: size_of::<Clock>() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            }]);
+
+            let src_clock = Clock {
+                slot: 1,
+                epoch_start_timestamp: 2,
+                epoch: 3,
+                leader_schedule_epoch: 4,
+                unix_timestamp: 5,
+            };
+            let mut invoke_context = MockInvokeContext::default();
+            invoke_context.sysvars =
+                vec![(sysvar::clock::id(), bincode::serialize(&src_clock).unwrap())];
+            let invoke_context: &mut dyn InvokeContext = &mut invoke_context;
+            let mut syscall = SyscallGetClockSysvar {
+                invoke_context: Rc::new(RefCell::new(invoke_context)),
+                loader_id: &bpf_loader::id(),
+            };
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+
+            syscall.call(got_clock_va, 0, 0, 0, 0, &memory_mapping, &mut result);
+            result.unwrap();
+            assert_eq!(got_clock, src_clock);
+        }
+
+        // Test rent sysvar
+        {
+            let got_rent = Rent::default();
+            let got_rent_va = 2048;
+
+            let memory_mapping = MemoryMapping::new_from_regions(vec![MemoryRegion {
+                host_addr: &got_rent as *const _ as u64,
+                vm_addr: got_rent_va,
+                len: size_of::<Rent>() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            }]);
+
+            let src_rent = Rent {
+                lamports_per_byte_year: 1,
+                exemption_threshold: 2.0,
+                burn_percent: 3,
+            };
+            let mut invoke_context = MockInvokeContext::default();
+            invoke_context.sysvars =
+                vec![(sysvar::rent::id(), bincode::serialize(&src_rent).unwrap())];
+            let invoke_context: &mut dyn InvokeContext = &mut invoke_context;
+            let mut syscall = SyscallGetRentSysvar {
+                invoke_context: Rc::new(RefCell::new(invoke_context)),
+                loader_id: &bpf_loader::id(),
+            };
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+
+            syscall.call(got_rent_va, 0, 0, 0, 0, &memory_mapping, &mut result);
+            result.unwrap();
+            assert_eq!(got_rent, src_rent);
+
+            // Sysvar not provided by the runtime
+            let invoke_context: &mut dyn InvokeContext = &mut MockInvokeContext::default();
+            let mut syscall = SyscallGetRentSysvar {
+                invoke_context: Rc::new(RefCell::new(invoke_context)),
+                loader_id: &bpf_loader::id(),
+            };
+            let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+            syscall.call(got_rent_va, 0, 0, 0, 0, &memory_mapping, &mut result);
+            assert_eq!(
+                Err(EbpfError::UserError(BPFError::SyscallError(
+                    SyscallError::UnsupportedSysvar(sysvar::rent::id())
+                ))),
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_syscall_return_data() {
+        let data = [1u8, 2, 3, 4];
+        let data_va = 4096;
+        let got_data = [0u8; 8];
+        let got_data_va = 8192;
+        let got_program_id = Pubkey::default();
+        let got_program_id_va = 12288;
+        let memory_mapping = MemoryMapping::new_from_regions(vec![
+            MemoryRegion {
+                host_addr: data.as_ptr() as *const _ as u64,
+                vm_addr: data_va,
+                len: data.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: false,
+            },
+            MemoryRegion {
+                host_addr: got_data.as_ptr() as *const _ as u64,
+                vm_addr: got_data_va,
+                len: got_data.len() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            },
+            MemoryRegion {
+                host_addr: &got_program_id as *const _ as u64,
+                vm_addr: got_program_id_va,
+                len: size_of::<Pubkey>() as u64,
+                vm_gap_shift: 63,
+                is_writable: true,
+            },
+        ]);
+
+        let program_id = Pubkey::new_unique();
+        let mut invoke_context = MockInvokeContext::default();
+        invoke_context.key = program_id;
+        let invoke_context: &mut dyn InvokeContext = &mut invoke_context;
+        let invoke_context = Rc::new(RefCell::new(invoke_context));
+        let mut set_syscall = SyscallSetReturnData {
+            invoke_context: invoke_context.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+        let mut get_syscall = SyscallGetReturnData {
+            invoke_context: invoke_context.clone(),
+            loader_id: &bpf_loader::id(),
+        };
+
+        // Nothing set yet
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        get_syscall.call(
+            got_data_va,
+            got_data.len() as u64,
+            got_program_id_va,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), 0);
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        set_syscall.call(
+            data_va,
+            data.len() as u64,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        result.unwrap();
+
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        get_syscall.call(
+            got_data_va,
+            got_data.len() as u64,
+            got_program_id_va,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(result.unwrap(), data.len() as u64);
+        assert_eq!(got_data[..data.len()], data);
+        assert_eq!(got_program_id, program_id);
+
+        // Too large
+        let mut result: Result<u64, EbpfError<BPFError>> = Ok(0);
+        set_syscall.call(
+            data_va,
+            MAX_RETURN_DATA as u64 + 1,
+            0,
+            0,
+            0,
+            &memory_mapping,
+            &mut result,
+        );
+        assert_eq!(
+            Err(EbpfError::UserError(BPFError::SyscallError(
+                SyscallError::ReturnDataTooLarge(
+                    MAX_RETURN_DATA as u64 + 1,
+                    MAX_RETURN_DATA as u64
+                )
+            ))),
+            result
+        );
+    }
+
     #[test]
     fn test_syscall_sha256() {
         let bytes1 = "Gaggablaghblagh!";
@@ -0,0 +1,14 @@
+use solana_sdk::{
+    instruction::InstructionError, keyed_account::KeyedAccount, process_instruction::InvokeContext,
+    pubkey::Pubkey,
+};
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _keyed_accounts: &[KeyedAccount],
+    _data: &[u8],
+    _invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    // Requested units are applied by the runtime before the transaction executes
+    Ok(())
+}
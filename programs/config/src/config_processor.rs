@@ -1,9 +1,10 @@
 //! Config program
 
-use crate::ConfigKeys;
+use crate::{validator_info, ConfigKeys};
 use bincode::deserialize;
 use log::*;
 use solana_sdk::{
+    feature_set::validator_info_schema,
     instruction::InstructionError,
     keyed_account::{next_keyed_account, KeyedAccount},
     process_instruction::InvokeContext,
@@ -15,7 +16,7 @@ pub fn process_instruction(
     _program_id: &Pubkey,
     keyed_accounts: &[KeyedAccount],
     data: &[u8],
-    _invoke_context: &mut dyn InvokeContext,
+    invoke_context: &mut dyn InvokeContext,
 ) -> Result<(), InstructionError> {
     let key_list: ConfigKeys = limited_deserialize(data)?;
     let keyed_accounts_iter = &mut keyed_accounts.iter();
@@ -95,6 +96,15 @@ pub fn process_instruction(
         return Err(InstructionError::MissingRequiredSignature);
     }
 
+    if validator_info::is_validator_info(&key_list.keys)
+        && invoke_context.is_feature_active(&validator_info_schema::id())
+    {
+        if let Err(err) = validator_info::parse_validator_info(data) {
+            error!("invalid validator info: {:?}", err);
+            return Err(InstructionError::InvalidInstructionData);
+        }
+    }
+
     if config_keyed_account.data_len()? < data.len() {
         error!("instruction data too large");
         return Err(InstructionError::InvalidInstructionData);
@@ -107,7 +117,11 @@ pub fn process_instruction(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{config_instruction, get_config_data, id, ConfigKeys, ConfigState};
+    use crate::{
+        config_instruction, get_config_data, id,
+        validator_info::{IdentityProof, ValidatorInfo, ValidatorMetadata, MAX_SHORT_FIELD_LENGTH},
+        ConfigKeys, ConfigState,
+    };
     use bincode::serialized_size;
     use serde_derive::{Deserialize, Serialize};
     use solana_sdk::{
@@ -558,6 +572,79 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_process_store_validator_info() {
+        solana_logger::setup();
+        let identity_keypair = Keypair::new();
+        let identity = identity_keypair.pubkey();
+        let config_keypair = Keypair::new();
+        let config_pubkey = config_keypair.pubkey();
+        let keys = vec![(validator_info::id(), false), (identity, true)];
+        let config_account = RefCell::new(Account {
+            data: vec![0; ValidatorInfo::max_space() as usize + 100],
+            ..Account::default()
+        });
+        let identity_account = RefCell::new(Account::default());
+        let accounts = vec![
+            (&config_pubkey, true, &config_account),
+            (&identity, true, &identity_account),
+        ];
+        let keyed_accounts = create_keyed_is_signer_accounts(&accounts);
+        let store = |metadata: &ValidatorMetadata| {
+            let validator_info = ValidatorInfo {
+                info: serde_json::to_string(metadata).unwrap(),
+            };
+            let instruction =
+                config_instruction::store(&config_pubkey, true, keys.clone(), &validator_info);
+            process_instruction(
+                &id(),
+                &keyed_accounts,
+                &instruction.data,
+                &mut MockInvokeContext::default(),
+            )
+        };
+
+        let mut metadata = ValidatorMetadata {
+            name: "Alice".to_string(),
+            identity_proof: Some(IdentityProof::new(
+                &Keypair::new(),
+                &identity,
+                "https://example.com/proof".to_string(),
+            )),
+            ..ValidatorMetadata::default()
+        };
+        assert_eq!(store(&metadata), Ok(()));
+
+        // Proof signed over some other identity
+        metadata.identity_proof = Some(IdentityProof::new(
+            &Keypair::new(),
+            &solana_sdk::pubkey::new_rand(),
+            "https://example.com/proof".to_string(),
+        ));
+        assert_eq!(
+            store(&metadata),
+            Err(InstructionError::InvalidInstructionData)
+        );
+
+        // Field over its size limit
+        metadata.identity_proof = None;
+        metadata.name = "x".repeat(MAX_SHORT_FIELD_LENGTH + 1);
+        assert_eq!(
+            store(&metadata),
+            Err(InstructionError::InvalidInstructionData)
+        );
+
+        // Field outside the schema
+        metadata.name = "Alice".to_string();
+        metadata
+            .legacy_fields
+            .insert("twitter".to_string(), "alice".into());
+        assert_eq!(
+            store(&metadata),
+            Err(InstructionError::InvalidInstructionData)
+        );
+    }
+
     #[test]
     fn test_config_initialize_no_panic() {
         let from_pubkey = solana_sdk::pubkey::new_rand();
@@ -1,6 +1,7 @@
 pub mod config_instruction;
 pub mod config_processor;
 pub mod date_instruction;
+pub mod validator_info;
 
 use bincode::{deserialize, serialize, serialized_size};
 use serde_derive::{Deserialize, Serialize};
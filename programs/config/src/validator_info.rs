@@ -0,0 +1,338 @@
+//! Validator info schema
+//!
+//! Validator info is stored in a config account whose key list starts with `id()` followed by the
+//! validator identity as a signer.  The config data is a JSON string matching `ValidatorMetadata`.
+
+use crate::{get_config_data, ConfigKeys, ConfigState};
+use bincode::deserialize;
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use std::str::FromStr;
+
+pub const MAX_SHORT_FIELD_LENGTH: usize = 70;
+pub const MAX_LONG_FIELD_LENGTH: usize = 300;
+pub const MAX_VALIDATOR_INFO: u64 = 837;
+
+solana_sdk::declare_id!("Va1idator1nfo111111111111111111111111111111");
+
+#[derive(Debug, Deserialize, PartialEq, Serialize, Default)]
+pub struct ValidatorInfo {
+    pub info: String,
+}
+
+impl ConfigState for ValidatorInfo {
+    fn max_space() -> u64 {
+        MAX_VALIDATOR_INFO
+    }
+}
+
+impl ValidatorInfo {
+    pub fn metadata(&self) -> Result<ValidatorMetadata, ValidatorInfoError> {
+        let metadata: ValidatorMetadata =
+            serde_json::from_str(&self.info).map_err(|_| ValidatorInfoError::InvalidData)?;
+        metadata.check()?;
+        Ok(metadata)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ValidatorInfoError {
+    /// Config data is not a JSON string matching `ValidatorMetadata`
+    InvalidData,
+    /// The named field exceeds its size limit
+    FieldTooLong(&'static str),
+    /// The key list does not name the validator identity as a signer
+    MissingIdentity,
+    /// The identity proof does not carry a valid external pubkey or signature
+    InvalidProof,
+    /// The identity proof signature does not match the validator identity
+    UnverifiedProof,
+    /// The metadata carries fields outside the schema
+    UnknownFields,
+}
+
+/// Proof that the holder of `external_key` vouches for a validator identity.  `signature` is the
+/// external key's signature over the identity pubkey bytes, and `url` is where the holder
+/// publishes that signature so it can be checked off-chain.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IdentityProof {
+    pub external_key: String,
+    pub signature: String,
+    pub url: String,
+}
+
+impl IdentityProof {
+    pub fn new(external_signer: &dyn Signer, identity: &Pubkey, url: String) -> Self {
+        Self {
+            external_key: external_signer.pubkey().to_string(),
+            signature: external_signer.sign_message(identity.as_ref()).to_string(),
+            url,
+        }
+    }
+
+    fn parse(&self) -> Result<(Pubkey, Signature), ValidatorInfoError> {
+        let external_key =
+            Pubkey::from_str(&self.external_key).map_err(|_| ValidatorInfoError::InvalidProof)?;
+        let signature =
+            Signature::from_str(&self.signature).map_err(|_| ValidatorInfoError::InvalidProof)?;
+        Ok((external_key, signature))
+    }
+
+    pub fn verify(&self, identity: &Pubkey) -> bool {
+        self.parse()
+            .map(|(external_key, signature)| {
+                signature.verify(external_key.as_ref(), identity.as_ref())
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorMetadata {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keybase_username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_proof: Option<IdentityProof>,
+    /// Fields outside the schema, which validator info published before it may carry.  They are
+    /// kept so that such info still parses, but new info may not add any
+    #[serde(flatten)]
+    pub legacy_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ValidatorMetadata {
+    /// Check field size limits and that any identity proof is well formed
+    pub fn check(&self) -> Result<(), ValidatorInfoError> {
+        let check_length = |field: &'static str, value: Option<&String>, max_length: usize| {
+            if value.map(|value| value.len() > max_length).unwrap_or(false) {
+                Err(ValidatorInfoError::FieldTooLong(field))
+            } else {
+                Ok(())
+            }
+        };
+        check_length("name", Some(&self.name), MAX_SHORT_FIELD_LENGTH)?;
+        check_length("website", self.website.as_ref(), MAX_SHORT_FIELD_LENGTH)?;
+        check_length("details", self.details.as_ref(), MAX_LONG_FIELD_LENGTH)?;
+        check_length(
+            "keybaseUsername",
+            self.keybase_username.as_ref(),
+            MAX_SHORT_FIELD_LENGTH,
+        )?;
+        if let Some(identity_proof) = &self.identity_proof {
+            check_length("url", Some(&identity_proof.url), MAX_SHORT_FIELD_LENGTH)?;
+            identity_proof.parse()?;
+        }
+        Ok(())
+    }
+
+    /// Whether the metadata carries an identity proof signed over `identity`
+    pub fn verify(&self, identity: &Pubkey) -> bool {
+        self.identity_proof
+            .as_ref()
+            .map(|identity_proof| identity_proof.verify(identity))
+            .unwrap_or(false)
+    }
+}
+
+/// Return the validator identity named by a validator info key list
+pub fn get_identity(keys: &[(Pubkey, bool)]) -> Result<Pubkey, ValidatorInfoError> {
+    match keys {
+        [_, (identity, true)] => Ok(*identity),
+        _ => Err(ValidatorInfoError::MissingIdentity),
+    }
+}
+
+/// Whether a config key list describes a validator info account
+pub fn is_validator_info(keys: &[(Pubkey, bool)]) -> bool {
+    keys.first() == Some(&(id(), false))
+}
+
+/// Parse validator info config `data` being stored, returning the validator identity and its
+/// metadata.  Any identity proof present must verify against the identity, and only legacy info
+/// already on chain may carry fields outside the schema.
+pub fn parse_validator_info(
+    data: &[u8],
+) -> Result<(Pubkey, ValidatorMetadata), ValidatorInfoError> {
+    let key_list: ConfigKeys = deserialize(data).map_err(|_| ValidatorInfoError::InvalidData)?;
+    let identity = get_identity(&key_list.keys)?;
+    let validator_info = get_config_data(data)
+        .ok()
+        .and_then(|data| deserialize::<ValidatorInfo>(data).ok())
+        .ok_or(ValidatorInfoError::InvalidData)?;
+    let metadata = validator_info.metadata()?;
+    if !metadata.legacy_fields.is_empty() {
+        return Err(ValidatorInfoError::UnknownFields);
+    }
+    if metadata.identity_proof.is_some() && !metadata.verify(&identity) {
+        return Err(ValidatorInfoError::UnverifiedProof);
+    }
+    Ok((identity, metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode::{serialize, serialized_size};
+    use solana_sdk::signature::Keypair;
+
+    fn validator_info_data(identity: &Pubkey, metadata: &ValidatorMetadata) -> Vec<u8> {
+        let keys = vec![(id(), false), (*identity, true)];
+        let validator_info = ValidatorInfo {
+            info: serde_json::to_string(metadata).unwrap(),
+        };
+        serialize(&(ConfigKeys { keys }, validator_info)).unwrap()
+    }
+
+    #[test]
+    fn test_validator_metadata_legacy_json() {
+        let validator_info = ValidatorInfo {
+            info: r#"{"keybaseUsername":"alice_keybase","name":"Alice"}"#.to_string(),
+        };
+        assert_eq!(
+            validator_info.metadata().unwrap(),
+            ValidatorMetadata {
+                name: "Alice".to_string(),
+                keybase_username: Some("alice_keybase".to_string()),
+                ..ValidatorMetadata::default()
+            }
+        );
+
+        // Fields outside the schema are kept rather than failing the whole entry
+        let validator_info = ValidatorInfo {
+            info: r#"{"name":"Alice","twitter":"alice"}"#.to_string(),
+        };
+        let metadata = validator_info.metadata().unwrap();
+        assert_eq!(metadata.name, "Alice");
+        assert_eq!(
+            metadata.legacy_fields.get("twitter"),
+            Some(&serde_json::Value::String("alice".to_string()))
+        );
+        assert_eq!(
+            serde_json::to_string(&metadata).unwrap(),
+            r#"{"name":"Alice","twitter":"alice"}"#
+        );
+
+        // but may not be stored anew
+        let data = serialize(&(
+            ConfigKeys {
+                keys: vec![(id(), false), (solana_sdk::pubkey::new_rand(), true)],
+            },
+            validator_info,
+        ))
+        .unwrap();
+        assert_eq!(
+            parse_validator_info(&data),
+            Err(ValidatorInfoError::UnknownFields)
+        );
+    }
+
+    #[test]
+    fn test_validator_metadata_check() {
+        let metadata = ValidatorMetadata {
+            name: "Alice".to_string(),
+            details: Some("x".repeat(MAX_LONG_FIELD_LENGTH)),
+            ..ValidatorMetadata::default()
+        };
+        assert_eq!(metadata.check(), Ok(()));
+
+        let metadata = ValidatorMetadata {
+            name: "x".repeat(MAX_SHORT_FIELD_LENGTH + 1),
+            ..ValidatorMetadata::default()
+        };
+        assert_eq!(
+            metadata.check(),
+            Err(ValidatorInfoError::FieldTooLong("name"))
+        );
+
+        let metadata = ValidatorMetadata {
+            name: "Alice".to_string(),
+            identity_proof: Some(IdentityProof {
+                external_key: "not a pubkey".to_string(),
+                ..IdentityProof::default()
+            }),
+            ..ValidatorMetadata::default()
+        };
+        assert_eq!(metadata.check(), Err(ValidatorInfoError::InvalidProof));
+    }
+
+    #[test]
+    fn test_identity_proof() {
+        let identity = solana_sdk::pubkey::new_rand();
+        let external_keypair = Keypair::new();
+        let identity_proof = IdentityProof::new(
+            &external_keypair,
+            &identity,
+            "https://example.com/proof".to_string(),
+        );
+        assert!(identity_proof.verify(&identity));
+        assert!(!identity_proof.verify(&solana_sdk::pubkey::new_rand()));
+
+        let metadata = ValidatorMetadata {
+            name: "Alice".to_string(),
+            identity_proof: Some(identity_proof),
+            ..ValidatorMetadata::default()
+        };
+        let data = validator_info_data(&identity, &metadata);
+        assert_eq!(
+            parse_validator_info(&data),
+            Ok((identity, metadata.clone()))
+        );
+
+        let data = validator_info_data(&solana_sdk::pubkey::new_rand(), &metadata);
+        assert_eq!(
+            parse_validator_info(&data),
+            Err(ValidatorInfoError::UnverifiedProof)
+        );
+    }
+
+    #[test]
+    fn test_get_identity() {
+        let identity = solana_sdk::pubkey::new_rand();
+        assert_eq!(
+            get_identity(&[(id(), false), (identity, true)]),
+            Ok(identity)
+        );
+        assert_eq!(
+            get_identity(&[(id(), false), (identity, false)]),
+            Err(ValidatorInfoError::MissingIdentity)
+        );
+        assert_eq!(
+            get_identity(&[(id(), false)]),
+            Err(ValidatorInfoError::MissingIdentity)
+        );
+    }
+
+    #[test]
+    fn test_validator_info_max_space() {
+        let max_short_string = "x".repeat(MAX_SHORT_FIELD_LENGTH);
+        let metadata = ValidatorMetadata {
+            name: max_short_string.clone(),
+            website: Some(max_short_string.clone()),
+            details: Some("x".repeat(MAX_LONG_FIELD_LENGTH)),
+            keybase_username: Some(max_short_string.clone()),
+            identity_proof: Some(IdentityProof {
+                // Longest base58 encodings of a pubkey and a signature
+                external_key: "x".repeat(44),
+                signature: "x".repeat(88),
+                url: max_short_string,
+            }),
+            ..ValidatorMetadata::default()
+        };
+        let validator_info = ValidatorInfo {
+            info: serde_json::to_string(&metadata).unwrap(),
+        };
+        assert_eq!(
+            serialized_size(&validator_info).unwrap(),
+            ValidatorInfo::max_space()
+        );
+    }
+}
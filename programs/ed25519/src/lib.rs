@@ -0,0 +1,63 @@
+use solana_sdk::{
+    instruction::InstructionError, keyed_account::KeyedAccount, process_instruction::InvokeContext,
+    pubkey::Pubkey,
+};
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _keyed_accounts: &[KeyedAccount],
+    _data: &[u8],
+    _invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    // Should be already checked by now.
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod test {
+    use rand::{thread_rng, Rng};
+    use solana_sdk::{
+        ed25519_instruction::{
+            new_ed25519_instruction, Ed25519SignatureOffsets, SIGNATURE_OFFSETS_SERIALIZED_SIZE,
+        },
+        hash::Hash,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    };
+
+    #[test]
+    fn test_ed25519() {
+        solana_logger::setup();
+        let offsets = Ed25519SignatureOffsets::default();
+        assert_eq!(
+            bincode::serialized_size(&offsets).unwrap() as usize,
+            SIGNATURE_OFFSETS_SERIALIZED_SIZE
+        );
+
+        let privkey = Keypair::new();
+        let message_arr = b"hello";
+        let mut instruction = new_ed25519_instruction(&privkey, message_arr);
+        let mint_keypair = Keypair::new();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction.clone()],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            Hash::default(),
+        );
+
+        assert!(tx.verify_precompiles(false, true).is_ok());
+
+        let index = thread_rng().gen_range(0, instruction.data.len());
+        instruction.data[index] = instruction.data[index].wrapping_add(12);
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            Hash::default(),
+        );
+        assert!(tx.verify_precompiles(false, true).is_err());
+        // Not checked until the program is enabled
+        assert!(tx.verify_precompiles(true, false).is_ok());
+    }
+}
@@ -84,5 +84,6 @@ pub fn process_instruction(
         ),
 
         StakeInstruction::SetLockup(lockup) => me.set_lockup(&lockup, &signers),
+        StakeInstruction::Redelegate => Err(InstructionError::InvalidInstructionData),
     }
 }
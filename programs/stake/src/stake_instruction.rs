@@ -8,6 +8,7 @@ use serde_derive::{Deserialize, Serialize};
 use solana_sdk::{
     clock::{Epoch, UnixTimestamp},
     decode_error::DecodeError,
+    feature_set,
     instruction::{AccountMeta, Instruction, InstructionError},
     keyed_account::{from_keyed_account, get_signers, next_keyed_account, KeyedAccount},
     process_instruction::InvokeContext,
@@ -41,6 +42,12 @@ pub enum StakeError {
 
     #[error("stake account merge failed due to different authority, lockups or state")]
     MergeMismatch,
+
+    #[error("stake redelegation requires fully active stake")]
+    RedelegateTransientOrInactiveStake,
+
+    #[error("stake redelegation to the same vote account is not permitted")]
+    RedelegateToSameVoteAccount,
 }
 
 impl<E> DecodeError<E> for StakeError {
@@ -139,6 +146,24 @@ pub enum StakeInstruction {
     ///   0. [WRITE] Stake account to be updated
     ///   1. [SIGNER] Base key of stake or withdraw authority
     AuthorizeWithSeed(AuthorizeWithSeedArgs),
+
+    /// Redelegate fully activated stake to another vote account without waiting for it to cool
+    /// down first
+    ///
+    /// The delegated stake account is scheduled for deactivation and its effective stake is
+    /// moved into the uninitialized stake account, which is delegated to the new vote account
+    /// and starts warming up in the current epoch.  The delegated stake account keeps its rent
+    /// exempt reserve and any lamports that were not part of the delegation.
+    ///
+    /// # Account references
+    ///   0. [WRITE] Fully activated stake account to be redelegated
+    ///   1. [WRITE] Uninitialized stake account that will hold the redelegated stake
+    ///   2. [] Vote account to which the stake will be redelegated
+    ///   3. [] Clock sysvar
+    ///   4. [] Stake history sysvar that carries stake warmup/cooldown history
+    ///   5. [] Address of config account that carries stake config
+    ///   6. [SIGNER] Stake authority
+    Redelegate,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
@@ -294,6 +319,34 @@ pub fn merge(
     )]
 }
 
+pub fn redelegate(
+    stake_pubkey: &Pubkey,
+    authorized_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    uninitialized_stake_pubkey: &Pubkey,
+) -> Vec<Instruction> {
+    let account_metas = vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new(*uninitialized_stake_pubkey, false),
+        AccountMeta::new_readonly(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(crate::config::id(), false),
+        AccountMeta::new_readonly(*authorized_pubkey, true),
+    ];
+
+    vec![
+        system_instruction::create_account(
+            authorized_pubkey, // Sending 0, so any signer will suffice
+            uninitialized_stake_pubkey,
+            0,
+            std::mem::size_of::<StakeState>() as u64,
+            &id(),
+        ),
+        Instruction::new(id(), &StakeInstruction::Redelegate, account_metas),
+    ]
+}
+
 pub fn create_account_and_delegate_stake(
     from_pubkey: &Pubkey,
     stake_pubkey: &Pubkey,
@@ -447,7 +500,7 @@ pub fn process_instruction(
     _program_id: &Pubkey,
     keyed_accounts: &[KeyedAccount],
     data: &[u8],
-    _invoke_context: &mut dyn InvokeContext,
+    invoke_context: &mut dyn InvokeContext,
 ) -> Result<(), InstructionError> {
     trace!("process_instruction: {:?}", data);
     trace!("keyed_accounts: {:?}", keyed_accounts);
@@ -518,6 +571,22 @@ pub fn process_instruction(
         ),
 
         StakeInstruction::SetLockup(lockup) => me.set_lockup(&lockup, &signers),
+        StakeInstruction::Redelegate => {
+            if !invoke_context.is_feature_active(&feature_set::stake_redelegate_instruction::id()) {
+                return Err(InstructionError::InvalidInstructionData);
+            }
+            let uninitialized_stake = &next_keyed_account(keyed_accounts)?;
+            let vote = next_keyed_account(keyed_accounts)?;
+
+            me.redelegate(
+                uninitialized_stake,
+                &vote,
+                &from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?,
+                &from_keyed_account::<StakeHistory>(next_keyed_account(keyed_accounts)?)?,
+                &config::from_keyed_account(next_keyed_account(keyed_accounts)?)?,
+                &signers,
+            )
+        }
     }
 }
 
@@ -674,6 +743,17 @@ mod tests {
             )),
             Err(InstructionError::InvalidAccountData),
         );
+        assert_eq!(
+            process_instruction(
+                &redelegate(
+                    &Pubkey::default(),
+                    &Pubkey::default(),
+                    &invalid_vote_state_pubkey(),
+                    &invalid_stake_state_pubkey(),
+                )[1]
+            ),
+            Err(InstructionError::InvalidAccountData),
+        );
     }
 
     #[test]
@@ -806,6 +806,15 @@ pub trait StakeAccount {
         withdraw_authority: &KeyedAccount,
         custodian: Option<&KeyedAccount>,
     ) -> Result<(), InstructionError>;
+    fn redelegate(
+        &self,
+        uninitialized_stake: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        signers: &HashSet<Pubkey>,
+    ) -> Result<(), InstructionError>;
 }
 
 impl<'a> StakeAccount for KeyedAccount<'a> {
@@ -1170,6 +1179,81 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
         to.try_account_ref_mut()?.lamports += lamports;
         Ok(())
     }
+
+    fn redelegate(
+        &self,
+        uninitialized_stake: &KeyedAccount,
+        vote_account: &KeyedAccount,
+        clock: &Clock,
+        stake_history: &StakeHistory,
+        config: &Config,
+        signers: &HashSet<Pubkey>,
+    ) -> Result<(), InstructionError> {
+        if uninitialized_stake.owner()? != id() {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        if vote_account.owner()? != solana_vote_program::id() {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        if uninitialized_stake.unsigned_key() == self.unsigned_key() {
+            return Err(InstructionError::InvalidArgument);
+        }
+        if !matches!(uninitialized_stake.state()?, StakeState::Uninitialized) {
+            return Err(InstructionError::InvalidAccountData);
+        }
+
+        let (meta, mut stake) = match self.state()? {
+            StakeState::Stake(meta, stake) => (meta, stake),
+            _ => return Err(InstructionError::InvalidAccountData),
+        };
+        meta.authorized.check(signers, StakeAuthorize::Staker)?;
+
+        // only fully active stake can be redelegated, so that the stake leaving this account
+        //  cools down in step with the stake warming up in the new account
+        let (effective_stake, activating_stake, deactivating_stake) = stake
+            .delegation
+            .stake_activating_and_deactivating(clock.epoch, Some(stake_history), true);
+        if effective_stake == 0 || activating_stake != 0 || deactivating_stake != 0 {
+            return Err(StakeError::RedelegateTransientOrInactiveStake.into());
+        }
+        if stake.delegation.voter_pubkey == *vote_account.unsigned_key() {
+            return Err(StakeError::RedelegateToSameVoteAccount.into());
+        }
+        let remaining_lamports = self
+            .lamports()?
+            .checked_sub(effective_stake)
+            .ok_or(InstructionError::InsufficientFunds)?;
+
+        let mut redelegated_meta = meta;
+        redelegated_meta.rent_exempt_reserve = calculate_split_rent_exempt_reserve(
+            meta.rent_exempt_reserve,
+            self.data_len()? as u64,
+            uninitialized_stake.data_len()? as u64,
+        );
+        // the new account's rent exempt reserve comes out of the redelegated stake, unless the
+        //  account was already funded
+        let redelegated_lamports = uninitialized_stake.lamports()? + effective_stake;
+        let redelegated_stake_amount =
+            redelegated_lamports.saturating_sub(redelegated_meta.rent_exempt_reserve);
+        if redelegated_stake_amount == 0 {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        let redelegated_stake = Stake::new(
+            redelegated_stake_amount,
+            vote_account.unsigned_key(),
+            &State::<VoteStateVersions>::state(vote_account)?.convert_to_current(),
+            clock.epoch,
+            config,
+        );
+
+        stake.deactivate(clock.epoch)?;
+        self.set_state(&StakeState::Stake(meta, stake))?;
+        uninitialized_stake.set_state(&StakeState::Stake(redelegated_meta, redelegated_stake))?;
+
+        self.try_account_ref_mut()?.lamports = remaining_lamports;
+        uninitialized_stake.try_account_ref_mut()?.lamports = redelegated_lamports;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -5571,6 +5655,188 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_redelegate() {
+        let initial_lamports = 4242424242;
+        let rent = Rent::default();
+        let rent_exempt_reserve = rent.minimum_balance(std::mem::size_of::<StakeState>());
+        let staker_pubkey = Pubkey::new_unique();
+        let stake_pubkey = Pubkey::new_unique();
+
+        let vote_pubkey = Pubkey::new_unique();
+        let vote_account = RefCell::new(vote_state::create_account(
+            &vote_pubkey,
+            &Pubkey::new_unique(),
+            0,
+            100,
+        ));
+        let vote_keyed_account = KeyedAccount::new(&vote_pubkey, false, &vote_account);
+        let new_vote_pubkey = Pubkey::new_unique();
+        let new_vote_account = RefCell::new(vote_state::create_account(
+            &new_vote_pubkey,
+            &Pubkey::new_unique(),
+            0,
+            100,
+        ));
+        let new_vote_keyed_account = KeyedAccount::new(&new_vote_pubkey, false, &new_vote_account);
+
+        let meta = Meta {
+            rent_exempt_reserve,
+            ..Meta::auto(&staker_pubkey)
+        };
+        let stake = Stake {
+            delegation: Delegation {
+                voter_pubkey: vote_pubkey,
+                stake: initial_lamports,
+                activation_epoch: std::u64::MAX,
+                ..Delegation::default()
+            },
+            ..Stake::default()
+        };
+        let stake_account = Account::new_ref_data_with_space(
+            rent_exempt_reserve + initial_lamports,
+            &StakeState::Stake(meta, stake),
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("stake_account");
+        let stake_keyed_account = KeyedAccount::new(&stake_pubkey, false, &stake_account);
+
+        let new_stake_pubkey = Pubkey::new_unique();
+        let new_stake_account = Account::new_ref_data_with_space(
+            0,
+            &StakeState::Uninitialized,
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("new_stake_account");
+        let new_stake_keyed_account =
+            KeyedAccount::new(&new_stake_pubkey, false, &new_stake_account);
+
+        let signers = HashSet::from_iter(vec![staker_pubkey]);
+        let config = Config::default();
+        let stake_history = StakeHistory::default();
+        let clock = Clock {
+            epoch: 10,
+            ..Clock::default()
+        };
+
+        // signers must include the staker
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &new_stake_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &HashSet::default(),
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+
+        // the vote account must change
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &new_stake_keyed_account,
+                &vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(StakeError::RedelegateToSameVoteAccount.into())
+        );
+
+        stake_keyed_account
+            .redelegate(
+                &new_stake_keyed_account,
+                &new_vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            )
+            .unwrap();
+
+        // the original stake is deactivating, leaving only the rent exempt reserve behind
+        assert_eq!(stake_keyed_account.lamports().unwrap(), rent_exempt_reserve);
+        let stake = StakeState::stake_from(&stake_account.borrow()).unwrap();
+        assert_eq!(stake.delegation.deactivation_epoch, clock.epoch);
+        assert_eq!(
+            stake.delegation.stake_activating_and_deactivating(
+                clock.epoch,
+                Some(&stake_history),
+                true
+            ),
+            (initial_lamports, 0, initial_lamports)
+        );
+
+        // the redelegated stake is warming up to the new vote account
+        assert_eq!(
+            new_stake_keyed_account.lamports().unwrap(),
+            initial_lamports
+        );
+        let (new_meta, new_stake) = match StakeState::from(&new_stake_account.borrow()).unwrap() {
+            StakeState::Stake(meta, stake) => (meta, stake),
+            state => panic!("unexpected state: {:?}", state),
+        };
+        assert_eq!(new_meta.authorized, meta.authorized);
+        assert_eq!(new_meta.rent_exempt_reserve, rent_exempt_reserve);
+        assert_eq!(new_stake.delegation.voter_pubkey, new_vote_pubkey);
+        assert_eq!(
+            new_stake.delegation.stake,
+            initial_lamports - rent_exempt_reserve
+        );
+        assert_eq!(new_stake.delegation.activation_epoch, clock.epoch);
+
+        // neither deactivating nor activating stake can be redelegated
+        let other_stake_pubkey = Pubkey::new_unique();
+        let other_stake_account = Account::new_ref_data_with_space(
+            0,
+            &StakeState::Uninitialized,
+            std::mem::size_of::<StakeState>(),
+            &id(),
+        )
+        .expect("other_stake_account");
+        let other_stake_keyed_account =
+            KeyedAccount::new(&other_stake_pubkey, false, &other_stake_account);
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &other_stake_keyed_account,
+                &vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(StakeError::RedelegateTransientOrInactiveStake.into())
+        );
+        assert_eq!(
+            new_stake_keyed_account.redelegate(
+                &other_stake_keyed_account,
+                &vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(StakeError::RedelegateTransientOrInactiveStake.into())
+        );
+
+        // the new stake account must be uninitialized
+        assert_eq!(
+            stake_keyed_account.redelegate(
+                &new_stake_keyed_account,
+                &vote_keyed_account,
+                &clock,
+                &stake_history,
+                &config,
+                &signers,
+            ),
+            Err(InstructionError::InvalidAccountData)
+        );
+    }
+
     #[test]
     fn test_meta_rewrite_rent_exempt_reserve() {
         let right_data_len = std::mem::size_of::<StakeState>() as u64;
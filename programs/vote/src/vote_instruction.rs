@@ -3,7 +3,10 @@
 
 use crate::{
     id,
-    vote_state::{self, Vote, VoteAuthorize, VoteInit, VoteState},
+    vote_state::{
+        self, serde_compact_vote_state_update, Vote, VoteAuthorize, VoteInit, VoteState,
+        VoteStateUpdate,
+    },
 };
 use log::*;
 use num_derive::{FromPrimitive, ToPrimitive};
@@ -11,6 +14,7 @@ use serde_derive::{Deserialize, Serialize};
 use solana_metrics::inc_new_counter_info;
 use solana_sdk::{
     decode_error::DecodeError,
+    feature_set,
     hash::Hash,
     instruction::{AccountMeta, Instruction, InstructionError},
     keyed_account::{from_keyed_account, get_signers, next_keyed_account, KeyedAccount},
@@ -43,6 +47,33 @@ pub enum VoteError {
 
     #[error("authorized voter has already been changed this epoch")]
     TooSoonToReauthorize,
+
+    #[error("vote state update has more lockouts than the maximum tower height")]
+    TooManyVotes,
+
+    #[error("vote state update slots are not ordered")]
+    SlotsNotOrdered,
+
+    #[error("vote state update confirmations are not ordered")]
+    ConfirmationsNotOrdered,
+
+    #[error("vote state update has a lockout with zero confirmations")]
+    ZeroConfirmations,
+
+    #[error("vote state update has a lockout with too many confirmations")]
+    ConfirmationTooLarge,
+
+    #[error("vote state update root rolls back the current root")]
+    RootRollBack,
+
+    #[error("vote state update rolls back a lockout's confirmation count")]
+    ConfirmationRollBack,
+
+    #[error("vote state update conflicts with a lockout in the current vote state")]
+    LockoutConflict,
+
+    #[error("vote state update root is neither a current vote nor in the bank's slot hashes")]
+    RootNotFound,
 }
 
 impl<E> DecodeError<E> for VoteError {
@@ -110,6 +141,27 @@ pub enum VoteInstruction {
     ///   2. [] Clock sysvar
     ///   3. [SIGNER] Vote authority
     VoteSwitch(Vote, Hash),
+
+    /// Replace the vote account's lockouts and root with the voter's local tower
+    ///
+    /// # Account references
+    ///   0. [WRITE] Vote account to vote with
+    ///   1. [] Slot hashes sysvar
+    ///   2. [] Clock sysvar
+    ///   3. [SIGNER] Vote authority
+    UpdateVoteState(#[serde(with = "serde_compact_vote_state_update")] VoteStateUpdate),
+
+    /// Replace the vote account's lockouts and root with the voter's local tower, switching forks
+    ///
+    /// # Account references
+    ///   0. [WRITE] Vote account to vote with
+    ///   1. [] Slot hashes sysvar
+    ///   2. [] Clock sysvar
+    ///   3. [SIGNER] Vote authority
+    UpdateVoteStateSwitch(
+        #[serde(with = "serde_compact_vote_state_update")] VoteStateUpdate,
+        Hash,
+    ),
 }
 
 fn initialize_account(vote_pubkey: &Pubkey, vote_init: &VoteInit) -> Instruction {
@@ -247,6 +299,45 @@ pub fn vote_switch(
     )
 }
 
+pub fn update_vote_state(
+    vote_pubkey: &Pubkey,
+    authorized_voter_pubkey: &Pubkey,
+    vote_state_update: VoteStateUpdate,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*authorized_voter_pubkey, true),
+    ];
+
+    Instruction::new(
+        id(),
+        &VoteInstruction::UpdateVoteState(vote_state_update),
+        account_metas,
+    )
+}
+
+pub fn update_vote_state_switch(
+    vote_pubkey: &Pubkey,
+    authorized_voter_pubkey: &Pubkey,
+    vote_state_update: VoteStateUpdate,
+    proof_hash: Hash,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*authorized_voter_pubkey, true),
+    ];
+
+    Instruction::new(
+        id(),
+        &VoteInstruction::UpdateVoteStateSwitch(vote_state_update, proof_hash),
+        account_metas,
+    )
+}
+
 pub fn withdraw(
     vote_pubkey: &Pubkey,
     authorized_withdrawer_pubkey: &Pubkey,
@@ -278,7 +369,7 @@ pub fn process_instruction(
     _program_id: &Pubkey,
     keyed_accounts: &[KeyedAccount],
     data: &[u8],
-    _invoke_context: &mut dyn InvokeContext,
+    invoke_context: &mut dyn InvokeContext,
 ) -> Result<(), InstructionError> {
     trace!("process_instruction: {:?}", data);
     trace!("keyed_accounts: {:?}", keyed_accounts);
@@ -323,6 +414,20 @@ pub fn process_instruction(
                 &signers,
             )
         }
+        VoteInstruction::UpdateVoteState(vote_state_update)
+        | VoteInstruction::UpdateVoteStateSwitch(vote_state_update, _) => {
+            if !invoke_context.is_feature_active(&feature_set::vote_state_update::id()) {
+                return Err(InstructionError::InvalidInstructionData);
+            }
+            inc_new_counter_info!("vote-state-update-native", 1);
+            vote_state::process_vote_state_update(
+                me,
+                &from_keyed_account::<SlotHashes>(next_keyed_account(keyed_accounts)?)?,
+                &from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?,
+                &vote_state_update,
+                &signers,
+            )
+        }
         VoteInstruction::Withdraw(lamports) => {
             let to = next_keyed_account(keyed_accounts)?;
             vote_state::withdraw(me, lamports, to, &signers)
@@ -420,6 +525,23 @@ mod tests {
             )),
             Err(InstructionError::InvalidAccountData),
         );
+        assert_eq!(
+            process_instruction(&update_vote_state(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                VoteStateUpdate::default(),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
+        assert_eq!(
+            process_instruction(&update_vote_state_switch(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                VoteStateUpdate::default(),
+                Hash::default(),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
         assert_eq!(
             process_instruction(&authorize(
                 &Pubkey::default(),
@@ -465,6 +587,40 @@ mod tests {
         assert!(minimum_balance as f64 / 10f64.powf(9.0) < 0.04)
     }
 
+    #[test]
+    fn test_update_vote_state_compact_serialization() {
+        use crate::vote_state::{Lockout, MAX_LOCKOUT_HISTORY};
+        use solana_sdk::clock::Slot;
+
+        let lockouts = (0..MAX_LOCKOUT_HISTORY)
+            .map(|i| Lockout {
+                slot: 1_000_000 + i as Slot,
+                confirmation_count: (MAX_LOCKOUT_HISTORY - i) as u32,
+            })
+            .collect();
+        let mut vote_state_update =
+            VoteStateUpdate::new(lockouts, Some(999_990), Hash::new_unique());
+        vote_state_update.timestamp = Some(1_606_000_000);
+
+        for instruction in &[
+            VoteInstruction::UpdateVoteState(vote_state_update.clone()),
+            VoteInstruction::UpdateVoteStateSwitch(vote_state_update.clone(), Hash::new_unique()),
+            VoteInstruction::UpdateVoteState(VoteStateUpdate::default()),
+        ] {
+            let bytes = bincode::serialize(instruction).unwrap();
+            assert_eq!(
+                &limited_deserialize::<VoteInstruction>(&bytes).unwrap(),
+                instruction
+            );
+        }
+
+        // slots are stored as small offsets, and confirmation counts as single bytes
+        let bytes =
+            bincode::serialize(&VoteInstruction::UpdateVoteState(vote_state_update.clone()))
+                .unwrap();
+        assert!(bytes.len() * 3 < bincode::serialize(&vote_state_update).unwrap().len());
+    }
+
     #[test]
     fn test_custom_error_decode() {
         use num_traits::FromPrimitive;
@@ -20,6 +20,7 @@ use solana_sdk::{
 };
 use std::boxed::Box;
 use std::collections::{HashSet, VecDeque};
+use std::convert::TryFrom;
 
 mod vote_state_0_23_5;
 pub mod vote_state_versions;
@@ -57,6 +58,47 @@ impl Vote {
     }
 }
 
+#[derive(Serialize, Default, Deserialize, Debug, PartialEq, Eq, Clone, AbiExample)]
+pub struct VoteStateUpdate {
+    /// The proposed tower, starting with the oldest vote
+    pub lockouts: VecDeque<Lockout>,
+    /// The proposed root
+    pub root: Option<Slot>,
+    /// signature of the bank's state at the last slot
+    pub hash: Hash,
+    /// processing timestamp of last slot
+    pub timestamp: Option<UnixTimestamp>,
+}
+
+impl VoteStateUpdate {
+    pub fn new(lockouts: VecDeque<Lockout>, root: Option<Slot>, hash: Hash) -> Self {
+        Self {
+            lockouts,
+            root,
+            hash,
+            timestamp: None,
+        }
+    }
+
+    pub fn slots(&self) -> Vec<Slot> {
+        self.lockouts.iter().map(|lockout| lockout.slot).collect()
+    }
+
+    pub fn last_voted_slot(&self) -> Option<Slot> {
+        self.lockouts.back().map(|lockout| lockout.slot)
+    }
+}
+
+impl From<VoteStateUpdate> for Vote {
+    fn from(vote_state_update: VoteStateUpdate) -> Self {
+        Self {
+            slots: vote_state_update.slots(),
+            hash: vote_state_update.hash,
+            timestamp: vote_state_update.timestamp,
+        }
+    }
+}
+
 #[derive(Serialize, Default, Deserialize, Debug, PartialEq, Eq, Clone, AbiExample)]
 pub struct Lockout {
     pub slot: Slot,
@@ -348,6 +390,151 @@ impl VoteState {
         Ok(())
     }
 
+    fn check_vote_state_update_is_valid(
+        &self,
+        vote_state_update: &VoteStateUpdate,
+        slot_hashes: &[(Slot, Hash)],
+    ) -> Result<(), VoteError> {
+        let new_lockouts = &vote_state_update.lockouts;
+        let new_root = vote_state_update.root;
+        let last_lockout = new_lockouts.back().ok_or(VoteError::EmptySlots)?;
+        if new_lockouts.len() > MAX_LOCKOUT_HISTORY {
+            return Err(VoteError::TooManyVotes);
+        }
+
+        match (new_root, self.root_slot) {
+            (Some(new_root), Some(current_root)) if new_root < current_root => {
+                return Err(VoteError::RootRollBack)
+            }
+            (None, Some(_)) => return Err(VoteError::RootRollBack),
+            _ => (),
+        }
+
+        // A new root must be a slot this account voted on, or an ancestor of the bank
+        if let Some(new_root) = new_root {
+            if self.root_slot != Some(new_root)
+                && !self.votes.iter().any(|vote| vote.slot == new_root)
+                && slot_hashes
+                    .binary_search_by(|(slot, _)| new_root.cmp(slot))
+                    .is_err()
+            {
+                return Err(VoteError::RootNotFound);
+            }
+        }
+
+        // The proposed tower must be a well formed tower: strictly increasing slots above the
+        // root, strictly decreasing confirmation counts, and no vote expired by a later one
+        let mut previous_lockout: Option<&Lockout> = None;
+        for lockout in new_lockouts {
+            if lockout.confirmation_count == 0 {
+                return Err(VoteError::ZeroConfirmations);
+            }
+            if lockout.confirmation_count > MAX_LOCKOUT_HISTORY as u32 {
+                return Err(VoteError::ConfirmationTooLarge);
+            }
+            if new_root.map_or(false, |new_root| lockout.slot <= new_root) {
+                return Err(VoteError::SlotsNotOrdered);
+            }
+            if let Some(previous_lockout) = previous_lockout {
+                if previous_lockout.slot >= lockout.slot {
+                    return Err(VoteError::SlotsNotOrdered);
+                }
+                if previous_lockout.confirmation_count <= lockout.confirmation_count {
+                    return Err(VoteError::ConfirmationsNotOrdered);
+                }
+                if previous_lockout.is_expired(lockout.slot) {
+                    return Err(VoteError::LockoutConflict);
+                }
+            }
+            previous_lockout = Some(lockout);
+        }
+
+        if self.last_voted_slot().map_or(false, |last_voted_slot| {
+            last_lockout.slot <= last_voted_slot
+        }) {
+            return Err(VoteError::VoteTooOld);
+        }
+        if slot_hashes
+            .last()
+            .map_or(true, |(oldest_slot, _)| last_lockout.slot < *oldest_slot)
+        {
+            return Err(VoteError::VoteTooOld);
+        }
+
+        // Every slot must either already be in the current tower, or be a slot this bank has
+        // frozen, and the hash of the last slot must match the bank's
+        for lockout in new_lockouts {
+            if let Some(current_lockout) = self.votes.iter().find(|v| v.slot == lockout.slot) {
+                if current_lockout.confirmation_count > lockout.confirmation_count {
+                    return Err(VoteError::ConfirmationRollBack);
+                }
+            } else if slot_hashes
+                .binary_search_by(|(slot, _)| lockout.slot.cmp(slot))
+                .is_err()
+            {
+                info!(
+                    "{} dropped vote state update {:?} failed to match slot: {:?}",
+                    self.node_pubkey, vote_state_update, slot_hashes,
+                );
+                inc_new_counter_info!("dropped-vote-state-update-slot", 1);
+                return Err(VoteError::SlotsMismatch);
+            }
+        }
+        let last_slot_hash = slot_hashes
+            .binary_search_by(|(slot, _)| last_lockout.slot.cmp(slot))
+            .map(|i| slot_hashes[i].1)
+            .map_err(|_| VoteError::SlotsMismatch)?;
+        if last_slot_hash != vote_state_update.hash {
+            warn!(
+                "{} dropped vote state update {:?} failed to match hash {} {}",
+                self.node_pubkey, vote_state_update, vote_state_update.hash, last_slot_hash
+            );
+            inc_new_counter_info!("dropped-vote-state-update-hash", 1);
+            return Err(VoteError::SlotHashMismatch);
+        }
+
+        // Votes that are still locked out at the new last slot can't be dropped, unless they are
+        // now rooted
+        for current_lockout in &self.votes {
+            if new_root.map_or(false, |new_root| current_lockout.slot <= new_root) {
+                continue;
+            }
+            if !current_lockout.is_expired(last_lockout.slot)
+                && !new_lockouts.iter().any(|v| v.slot == current_lockout.slot)
+            {
+                return Err(VoteError::LockoutConflict);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the vote state's tower with the proposed one, after checking that it is a valid
+    /// successor of the current tower
+    pub fn process_vote_state_update(
+        &mut self,
+        vote_state_update: &VoteStateUpdate,
+        slot_hashes: &[SlotHash],
+        epoch: Epoch,
+    ) -> Result<(), VoteError> {
+        self.check_vote_state_update_is_valid(vote_state_update, slot_hashes)?;
+
+        if let Some(new_root) = vote_state_update.root {
+            // Award one credit for every vote of the current tower that the new root finalizes,
+            // as popping them off one vote at a time would have
+            let finalized_votes = self
+                .votes
+                .iter()
+                .filter(|lockout| lockout.slot <= new_root)
+                .count();
+            for _ in 0..finalized_votes {
+                self.increment_credits(epoch);
+            }
+        }
+        self.votes = vote_state_update.lockouts.clone();
+        self.root_slot = vote_state_update.root;
+        Ok(())
+    }
+
     pub fn process_slot(&mut self, slot: Slot, epoch: Epoch) {
         // Ignore votes for slots earlier than we already have votes for
         if self
@@ -718,6 +905,126 @@ pub fn process_vote<S: std::hash::BuildHasher>(
     vote_account.set_state(&VoteStateVersions::Current(Box::new(vote_state)))
 }
 
+pub fn process_vote_state_update<S: std::hash::BuildHasher>(
+    vote_account: &KeyedAccount,
+    slot_hashes: &[SlotHash],
+    clock: &Clock,
+    vote_state_update: &VoteStateUpdate,
+    signers: &HashSet<Pubkey, S>,
+) -> Result<(), InstructionError> {
+    let versioned = State::<VoteStateVersions>::state(vote_account)?;
+
+    if versioned.is_uninitialized() {
+        return Err(InstructionError::UninitializedAccount);
+    }
+
+    let mut vote_state = versioned.convert_to_current();
+    let authorized_voter = vote_state
+        .get_and_update_authorized_voter(clock.epoch)
+        .expect("the clock epoch is monotonically increasing, so authorized voter must be known");
+    verify_authorized_signer(&authorized_voter, signers)?;
+
+    vote_state.process_vote_state_update(vote_state_update, slot_hashes, clock.epoch)?;
+    if let Some(timestamp) = vote_state_update.timestamp {
+        vote_state_update
+            .last_voted_slot()
+            .ok_or(VoteError::EmptySlots)
+            .and_then(|slot| vote_state.process_timestamp(slot, timestamp))?;
+    }
+    vote_account.set_state(&VoteStateVersions::Current(Box::new(vote_state)))
+}
+
+/// Compact serialization of a `VoteStateUpdate` for the vote instructions: each slot is stored as
+/// a varint offset from the previous slot (or from the root), and each confirmation count as a
+/// single byte
+pub mod serde_compact_vote_state_update {
+    use super::*;
+    use serde::{
+        de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer,
+    };
+    use solana_sdk::{serde_varint, short_vec};
+
+    #[derive(Serialize, Deserialize)]
+    struct LockoutOffset {
+        #[serde(with = "serde_varint")]
+        offset: Slot,
+        confirmation_count: u8,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct CompactVoteStateUpdate {
+        root: Slot,
+        #[serde(with = "short_vec")]
+        lockout_offsets: Vec<LockoutOffset>,
+        hash: Hash,
+        timestamp: Option<UnixTimestamp>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        vote_state_update: &VoteStateUpdate,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let root = vote_state_update.root.unwrap_or(std::u64::MAX);
+        let mut previous_slot = vote_state_update.root.unwrap_or_default();
+        let lockout_offsets = vote_state_update
+            .lockouts
+            .iter()
+            .map(|lockout| {
+                let offset = lockout
+                    .slot
+                    .checked_sub(previous_slot)
+                    .ok_or_else(|| S::Error::custom("invalid vote state update"))?;
+                let confirmation_count = u8::try_from(lockout.confirmation_count)
+                    .map_err(|_| S::Error::custom("invalid confirmation count"))?;
+                previous_slot = lockout.slot;
+                Ok(LockoutOffset {
+                    offset,
+                    confirmation_count,
+                })
+            })
+            .collect::<Result<Vec<_>, S::Error>>()?;
+        CompactVoteStateUpdate {
+            root,
+            lockout_offsets,
+            hash: vote_state_update.hash,
+            timestamp: vote_state_update.timestamp,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<VoteStateUpdate, D::Error> {
+        let compact = CompactVoteStateUpdate::deserialize(deserializer)?;
+        let root = if compact.root == std::u64::MAX {
+            None
+        } else {
+            Some(compact.root)
+        };
+        let mut previous_slot = root.unwrap_or_default();
+        let lockouts = compact
+            .lockout_offsets
+            .iter()
+            .map(|lockout_offset| {
+                let slot = previous_slot
+                    .checked_add(lockout_offset.offset)
+                    .ok_or_else(|| D::Error::custom("invalid lockout offset"))?;
+                previous_slot = slot;
+                Ok(Lockout {
+                    slot,
+                    confirmation_count: u32::from(lockout_offset.confirmation_count),
+                })
+            })
+            .collect::<Result<VecDeque<_>, D::Error>>()?;
+        Ok(VoteStateUpdate {
+            lockouts,
+            root,
+            hash: compact.hash,
+            timestamp: compact.timestamp,
+        })
+    }
+}
+
 pub fn create_account_with_authorized(
     node_pubkey: &Pubkey,
     authorized_voter: &Pubkey,
@@ -1565,6 +1872,220 @@ mod tests {
         );
     }
 
+    fn lockouts(slots_and_confirmations: &[(Slot, u32)]) -> VecDeque<Lockout> {
+        slots_and_confirmations
+            .iter()
+            .map(|(slot, confirmation_count)| Lockout {
+                slot: *slot,
+                confirmation_count: *confirmation_count,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_process_vote_state_update() {
+        let slot_hashes: Vec<_> = (0..=10)
+            .rev()
+            .map(|slot| (slot, hash(&[slot as u8])))
+            .collect();
+        let slot_hash = |slot: Slot| hash(&[slot as u8]);
+        let mut vote_state = VoteState::default();
+
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(1, 3), (2, 2), (3, 1)]), None, slot_hash(3));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Ok(())
+        );
+        assert_eq!(vote_state.votes, vote_state_update.lockouts);
+        assert_eq!(vote_state.root_slot, None);
+        assert_eq!(vote_state.credits(), 0);
+
+        // the last slot is already voted on
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::VoteTooOld)
+        );
+
+        // the hash must match the bank's hash for the last slot
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(1, 4), (2, 3), (3, 2), (4, 1)]),
+            None,
+            slot_hash(3),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::SlotHashMismatch)
+        );
+
+        // every new slot must be in the bank's slot hashes
+        let missing_slot_hashes: Vec<_> = slot_hashes
+            .iter()
+            .filter(|(slot, _)| *slot != 4)
+            .cloned()
+            .collect();
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(1, 4), (2, 3), (3, 2), (4, 1)]),
+            None,
+            slot_hash(4),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &missing_slot_hashes, 0),
+            Err(VoteError::SlotsMismatch)
+        );
+
+        // the proposed tower must be well formed
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(1, 3), (2, 3), (4, 1)]), None, slot_hash(4));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::ConfirmationsNotOrdered)
+        );
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(2, 3), (1, 2), (4, 1)]), None, slot_hash(4));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::SlotsNotOrdered)
+        );
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(1, 4), (2, 3), (3, 1), (4, 0)]),
+            None,
+            slot_hash(4),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::ZeroConfirmations)
+        );
+
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(1, 4), (2, 2), (3, 2), (4, 1)]),
+            None,
+            slot_hash(4),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::ConfirmationsNotOrdered)
+        );
+
+        // confirmation counts of existing votes can't go down
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(1, 2), (4, 1)]), None, slot_hash(4));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::ConfirmationRollBack)
+        );
+
+        // rooting slot 1 earns a credit
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(2, 4), (3, 3), (4, 2), (5, 1)]),
+            Some(1),
+            slot_hash(5),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 1),
+            Ok(())
+        );
+        assert_eq!(vote_state.root_slot, Some(1));
+        assert_eq!(vote_state.credits(), 1);
+
+        // the root can't roll back
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(2, 4), (3, 3), (4, 2), (6, 1)]),
+            None,
+            slot_hash(6),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 1),
+            Err(VoteError::RootRollBack)
+        );
+
+        // votes still locked out at the new last slot can't be dropped
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(2, 4), (6, 1)]), Some(1), slot_hash(6));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 1),
+            Err(VoteError::LockoutConflict)
+        );
+
+        // rooting slots 2 and 3 at once earns a credit for each
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(4, 3), (5, 2), (6, 1)]), Some(3), slot_hash(6));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 1),
+            Ok(())
+        );
+        assert_eq!(vote_state.root_slot, Some(3));
+        assert_eq!(vote_state.credits(), 3);
+    }
+
+    #[test]
+    fn test_process_vote_state_update_root() {
+        let slot_hashes: Vec<_> = (0..=20)
+            .rev()
+            .map(|slot| (slot, hash(&[slot as u8])))
+            .collect();
+        let slot_hash = |slot: Slot| hash(&[slot as u8]);
+        let mut vote_state = VoteState::default();
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(2, 3), (4, 2), (5, 1)]), None, slot_hash(5));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Ok(())
+        );
+
+        // A root that was never voted on only earns credits for the votes it finalizes
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(4, 3), (5, 2), (6, 1)]), Some(3), slot_hash(6));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Ok(())
+        );
+        assert_eq!(vote_state.root_slot, Some(3));
+        assert_eq!(vote_state.credits(), 1);
+
+        // Re-sending the same root earns nothing
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(4, 4), (5, 3), (6, 2), (7, 1)]),
+            Some(3),
+            slot_hash(7),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Ok(())
+        );
+        assert_eq!(vote_state.credits(), 1);
+
+        // The root can't go backwards
+        let vote_state_update = VoteStateUpdate::new(
+            lockouts(&[(4, 5), (5, 4), (6, 3), (7, 2), (8, 1)]),
+            Some(2),
+            slot_hash(8),
+        );
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &slot_hashes, 0),
+            Err(VoteError::RootRollBack)
+        );
+
+        // The root must be a current vote or an ancestor in the slot hashes
+        let missing_slot_hashes: Vec<_> = slot_hashes
+            .iter()
+            .filter(|(slot, _)| *slot != 9 && *slot != 10)
+            .cloned()
+            .collect();
+        let vote_state_update = VoteStateUpdate::new(lockouts(&[(11, 1)]), Some(10), slot_hash(11));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &missing_slot_hashes, 0),
+            Err(VoteError::RootNotFound)
+        );
+        let vote_state_update =
+            VoteStateUpdate::new(lockouts(&[(6, 3), (7, 2), (11, 1)]), Some(5), slot_hash(11));
+        assert_eq!(
+            vote_state.process_vote_state_update(&vote_state_update, &missing_slot_hashes, 0),
+            Ok(())
+        );
+        assert_eq!(vote_state.credits(), 3);
+    }
+
     #[test]
     fn test_vote_state_commission_split() {
         let vote_state = VoteState::default();
@@ -41,6 +41,12 @@ pub fn parse_vote_transaction(tx: &Transaction) -> Option<(Pubkey, Vote, Option<
                             VoteInstruction::VoteSwitch(vote, hash) => {
                                 Some((*key, vote, Some(hash)))
                             }
+                            VoteInstruction::UpdateVoteState(vote_state_update) => {
+                                Some((*key, vote_state_update.into(), None))
+                            }
+                            VoteInstruction::UpdateVoteStateSwitch(vote_state_update, hash) => {
+                                Some((*key, vote_state_update.into(), Some(hash)))
+                            }
                             _ => None,
                         })
                     })
@@ -120,4 +126,37 @@ mod test {
         run_test_parse_vote_transaction(None);
         run_test_parse_vote_transaction(Some(hash(&[42u8])));
     }
+
+    #[test]
+    fn test_parse_vote_state_update_transaction() {
+        use crate::vote_state::{Lockout, VoteStateUpdate};
+
+        let node_keypair = Keypair::new();
+        let vote_keypair = Keypair::new();
+        let auth_voter_keypair = Keypair::new();
+        let bank_hash = hash(&[42u8]);
+        let vote_state_update = VoteStateUpdate::new(
+            vec![
+                Lockout {
+                    slot: 41,
+                    confirmation_count: 2,
+                },
+                Lockout::new(42),
+            ]
+            .into_iter()
+            .collect(),
+            Some(40),
+            bank_hash,
+        );
+        let vote_ix = vote_instruction::update_vote_state(
+            &vote_keypair.pubkey(),
+            &auth_voter_keypair.pubkey(),
+            vote_state_update,
+        );
+        let vote_tx = Transaction::new_with_payer(&[vote_ix], Some(&node_keypair.pubkey()));
+        let (key, vote, hash) = parse_vote_transaction(&vote_tx).unwrap();
+        assert_eq!(hash, None);
+        assert_eq!(vote, Vote::new(vec![41, 42], bank_hash));
+        assert_eq!(key, vote_keypair.pubkey());
+    }
 }
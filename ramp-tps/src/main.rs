@@ -198,7 +198,7 @@ fn main() {
     let _ = fs::remove_dir_all(&tmp_ledger_path);
     fs::create_dir_all(&tmp_ledger_path).expect("failed to create temp ledger path");
 
-    notifier.send("Hi!");
+    utils::announce_start(&notifier);
     datapoint_info!("ramp-tps", ("event", "boot", String),);
 
     let entrypoint_str = matches.value_of("entrypoint").unwrap();
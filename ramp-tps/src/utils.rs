@@ -2,7 +2,7 @@ use bzip2::bufread::BzDecoder;
 use log::*;
 use solana_client::rpc_client::RpcClient;
 use solana_net_utils::parse_host;
-use solana_notifier::Notifier;
+use solana_notifier::{Notification, Notifier, Severity};
 use solana_sdk::{
     clock::{Epoch, Slot},
     genesis_config::GenesisConfig,
@@ -89,8 +89,27 @@ pub fn is_host(string: String) -> Result<(), String> {
     Ok(())
 }
 
+// Bailing out opens an incident shared by every run, which stays open until ramp-tps starts
+// again
+const BAIL_NOTIFICATION_ID: &str = "solana-ramp-tps/bail";
+
+fn bail_notification(title: &str, resolved: bool) -> Notification {
+    Notification {
+        id: BAIL_NOTIFICATION_ID.to_string(),
+        severity: Severity::Critical,
+        title: title.to_string(),
+        details: String::new(),
+        resolved,
+    }
+}
+
+/// Announce that ramp-tps has started, resolving the incident of the run that last bailed
+pub fn announce_start(notifier: &Notifier) {
+    notifier.notify(&bail_notification("Hi!", true));
+}
+
 pub fn bail(notifier: &Notifier, msg: &str) -> ! {
-    notifier.send(msg);
+    notifier.notify(&bail_notification(msg, false));
     sleep(Duration::from_secs(30)); // Wait for notifications to send
     std::process::exit(1);
 }
@@ -1,4 +1,5 @@
 pub mod ledger;
 pub mod ledger_error;
 pub mod remote_keypair;
+pub mod remote_signer;
 pub mod remote_wallet;
@@ -0,0 +1,610 @@
+//! A `Signer` that forwards messages to an external signing service, such as one backed by an
+//! HSM, authenticating with a client certificate.
+//!
+//! The service implements a single endpoint, `POST <url>/v1/sign`, which takes a JSON
+//! `{"pubkey": <base58>, "message": <base64>}` request and answers with a JSON
+//! `{"signature": <base58>}` response.
+
+use crate::remote_wallet::RemoteWalletError;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    signature::{Signature, Signer, SignerError},
+};
+use std::{collections::HashSet, fs, fs::File, str::FromStr, time::Duration};
+use url::{Host, Url};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+/// Expand a leading `~/` to the home directory, as a shell would have done for a plain path
+fn expand_home_dir(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(relative_path), Ok(home)) => format!("{}/{}", home, relative_path),
+        _ => path.to_string(),
+    }
+}
+
+/// Configuration of a remote signer, read from the YAML file named by a `remote:` signer URI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSignerConfig {
+    /// Base URL of the signing service. Must be `https`, unless the service is on localhost
+    pub url: String,
+    /// Pubkey the service signs for
+    pub pubkey: String,
+    /// PEM file holding the client certificate and private key presented to the service
+    #[serde(default)]
+    pub identity: Option<String>,
+    /// PEM file holding the CA certificate the service's certificate must chain to
+    #[serde(default)]
+    pub ca_certificate: Option<String>,
+    /// Programs the signer may sign for. A message that invokes any other program is refused
+    /// without being sent to the service
+    pub allowed_programs: Vec<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl RemoteSignerConfig {
+    pub fn load(config_file: &str) -> Result<Self, RemoteWalletError> {
+        let config_file = &expand_home_dir(config_file);
+        let file = File::open(config_file).map_err(|err| {
+            RemoteWalletError::InvalidInput(format!("unable to open {}: {}", config_file, err))
+        })?;
+        serde_yaml::from_reader(file).map_err(|err| {
+            RemoteWalletError::InvalidInput(format!(
+                "{} is not a remote signer config: {}",
+                config_file, err
+            ))
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SignRequest {
+    pubkey: String,
+    message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+pub struct RemoteSigner {
+    pubkey: Pubkey,
+    sign_url: String,
+    allowed_programs: HashSet<Pubkey>,
+    client: reqwest::blocking::Client,
+}
+
+impl RemoteSigner {
+    pub fn new(config: &RemoteSignerConfig) -> Result<Self, RemoteWalletError> {
+        let invalid_input = |msg: String| RemoteWalletError::InvalidInput(msg);
+        let pubkey = Pubkey::from_str(&config.pubkey)
+            .map_err(|_| invalid_input(format!("invalid pubkey: {}", config.pubkey)))?;
+        let allowed_programs = config
+            .allowed_programs
+            .iter()
+            .map(|program_id| {
+                Pubkey::from_str(program_id)
+                    .map_err(|_| invalid_input(format!("invalid program id: {}", program_id)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let url = Url::parse(&config.url)
+            .map_err(|err| invalid_input(format!("invalid url {}: {}", config.url, err)))?;
+        let is_localhost = match url.host() {
+            Some(Host::Domain(domain)) => domain == "localhost",
+            Some(Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        };
+        match url.scheme() {
+            "https" if config.identity.is_none() => {
+                return Err(invalid_input(
+                    "an identity is required to authenticate to the remote signer".to_string(),
+                ));
+            }
+            "https" => {}
+            "http" if is_localhost => {}
+            _ => {
+                return Err(invalid_input(format!(
+                    "remote signer url must be https: {}",
+                    config.url
+                )));
+            }
+        }
+
+        let read_pem = |path: &str| {
+            let path = &expand_home_dir(path);
+            fs::read(path).map_err(|err| invalid_input(format!("unable to read {}: {}", path, err)))
+        };
+        let mut builder = reqwest::blocking::Client::builder()
+            .use_rustls_tls()
+            .timeout(Duration::from_secs(config.timeout_secs));
+        if let Some(identity) = &config.identity {
+            let identity = reqwest::Identity::from_pem(&read_pem(identity)?)
+                .map_err(|err| invalid_input(format!("invalid identity: {}", err)))?;
+            builder = builder.identity(identity);
+        }
+        if let Some(ca_certificate) = &config.ca_certificate {
+            let ca_certificate = reqwest::Certificate::from_pem(&read_pem(ca_certificate)?)
+                .map_err(|err| invalid_input(format!("invalid CA certificate: {}", err)))?;
+            builder = builder.add_root_certificate(ca_certificate);
+        }
+        let client = builder
+            .build()
+            .map_err(|err| invalid_input(format!("unable to build client: {}", err)))?;
+
+        Ok(Self {
+            pubkey,
+            sign_url: format!("{}/v1/sign", config.url.trim_end_matches('/')),
+            allowed_programs,
+            client,
+        })
+    }
+
+    pub fn from_config_file(config_file: &str) -> Result<Self, RemoteWalletError> {
+        Self::new(&RemoteSignerConfig::load(config_file)?)
+    }
+
+    /// Refuse anything that is not exactly one legacy message, or that invokes a program outside
+    /// the allow-list
+    fn check_message(&self, message: &[u8]) -> Result<(), SignerError> {
+        let message: VersionedMessage = bincode::options()
+            .with_fixint_encoding()
+            .reject_trailing_bytes()
+            .deserialize(message)
+            .map_err(|_| {
+                SignerError::InvalidInput(
+                    "remote signer only signs transaction messages".to_string(),
+                )
+            })?;
+        // The accounts of a versioned message may be loaded from lookup tables, which can't be
+        // checked against the allow-list without reading them from the cluster
+        let message = match message {
+            VersionedMessage::Legacy(message) => message,
+            VersionedMessage::V0(_) => {
+                return Err(SignerError::InvalidInput(
+                    "remote signer does not sign versioned messages".to_string(),
+                ));
+            }
+        };
+        for instruction in &message.instructions {
+            let program_id = message
+                .account_keys
+                .get(instruction.program_id_index as usize)
+                .ok_or_else(|| SignerError::InvalidInput("invalid program index".to_string()))?;
+            if !self.allowed_programs.contains(program_id) {
+                return Err(SignerError::InvalidInput(format!(
+                    "remote signer is not allowed to sign for program {}",
+                    program_id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        self.check_message(message)?;
+        let request = SignRequest {
+            pubkey: self.pubkey.to_string(),
+            message: base64::encode(message),
+        };
+        let response = self
+            .client
+            .post(&self.sign_url)
+            .json(&request)
+            .send()
+            .map_err(|err| SignerError::Connection(err.to_string()))?;
+        if !response.status().is_success() {
+            return Err(SignerError::Protocol(format!(
+                "remote signer refused to sign: {}",
+                response.status()
+            )));
+        }
+        let response: SignResponse = response
+            .json()
+            .map_err(|err| SignerError::Protocol(format!("invalid response: {}", err)))?;
+        let signature = Signature::from_str(&response.signature)
+            .map_err(|_| SignerError::Protocol("invalid signature".to_string()))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(SignerError::Protocol(
+                "remote signer returned a signature that does not verify".to_string(),
+            ));
+        }
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::Session;
+    use solana_sdk::{
+        message::{Message, MessageV0},
+        signature::Keypair,
+        system_instruction, system_program,
+        transaction::Transaction,
+    };
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    // Read one signing request from `stream`, counting it, and answer it with a signature by
+    // `keypair`
+    fn serve_sign_request<S: Read + Write>(
+        stream: &mut S,
+        keypair: &Keypair,
+        requests: &AtomicUsize,
+    ) {
+        let mut reader = BufReader::new(&mut *stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let header = line.to_ascii_lowercase();
+            if let Some(value) = header.strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        drop(reader);
+        requests.fetch_add(1, Ordering::Relaxed);
+
+        let request: SignRequest = serde_json::from_slice(&body).unwrap();
+        let message = base64::decode(&request.message).unwrap();
+        let response = serde_json::to_string(&SignResponse {
+            signature: keypair.sign_message(&message).to_string(),
+        })
+        .unwrap();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.len(),
+            response
+        )
+        .unwrap();
+        stream.flush().unwrap();
+    }
+
+    // Serve the signing endpoint on a local port, signing every request with `keypair`
+    fn start_mock_signer(keypair: Keypair, requests: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                serve_sign_request(&mut stream.unwrap(), &keypair, &requests);
+            }
+        });
+        url
+    }
+
+    fn config(url: String, pubkey: &Pubkey, allowed_programs: &[Pubkey]) -> RemoteSignerConfig {
+        RemoteSignerConfig {
+            url,
+            pubkey: pubkey.to_string(),
+            identity: None,
+            ca_certificate: None,
+            allowed_programs: allowed_programs.iter().map(|id| id.to_string()).collect(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+
+    fn transfer_message(from: &Pubkey) -> Message {
+        let instruction = system_instruction::transfer(from, &solana_sdk::pubkey::new_rand(), 42);
+        Message::new(&[instruction], Some(from))
+    }
+
+    #[test]
+    fn test_remote_signer_sign() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = start_mock_signer(keypair, requests.clone());
+        let signer = RemoteSigner::new(&config(url, &pubkey, &[system_program::id()])).unwrap();
+        assert_eq!(signer.pubkey(), pubkey);
+
+        let mut transaction = Transaction::new_unsigned(transfer_message(&pubkey));
+        transaction
+            .try_sign(&[&signer], transaction.message.recent_blockhash)
+            .unwrap();
+        assert!(transaction.verify().is_ok());
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_remote_signer_allowed_programs() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = start_mock_signer(keypair, requests.clone());
+        let other_program = solana_sdk::pubkey::new_rand();
+        let signer = RemoteSigner::new(&config(url, &pubkey, &[other_program])).unwrap();
+
+        let message = transfer_message(&pubkey).serialize();
+        assert!(matches!(
+            signer.try_sign_message(&message),
+            Err(SignerError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            signer.try_sign_message(b"not a message"),
+            Err(SignerError::InvalidInput(_))
+        ));
+        assert_eq!(requests.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_remote_signer_refuses_versioned_and_padded_messages() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = start_mock_signer(keypair, requests.clone());
+        let signer = RemoteSigner::new(&config(url, &pubkey, &[system_program::id()])).unwrap();
+
+        // Only invokes an allowed program, but its accounts may come from a lookup table
+        let instruction =
+            system_instruction::transfer(&pubkey, &solana_sdk::pubkey::new_rand(), 42);
+        let message = MessageV0::new_with_lookup_tables(&[instruction], Some(&pubkey), &[]);
+        let message = VersionedMessage::V0(message).serialize();
+        assert!(matches!(
+            signer.try_sign_message(&message),
+            Err(SignerError::InvalidInput(_))
+        ));
+
+        let mut message = transfer_message(&pubkey).serialize();
+        message.push(0);
+        assert!(matches!(
+            signer.try_sign_message(&message),
+            Err(SignerError::InvalidInput(_))
+        ));
+        assert_eq!(requests.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_remote_signer_bad_signature() {
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = start_mock_signer(Keypair::new(), requests);
+        let signer = RemoteSigner::new(&config(url, &pubkey, &[system_program::id()])).unwrap();
+
+        let message = transfer_message(&pubkey).serialize();
+        assert!(matches!(
+            signer.try_sign_message(&message),
+            Err(SignerError::Protocol(_))
+        ));
+    }
+
+    // Accept connections, counting them, and reject every TLS handshake with a fatal
+    // handshake_failure alert as soon as the client hello arrives
+    fn start_handshake_rejecting_signer(connections: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        // rustls only verifies certificates issued to DNS names, not to IP addresses
+        let url = format!(
+            "https://localhost:{}",
+            listener.local_addr().unwrap().port()
+        );
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                connections.fetch_add(1, Ordering::Relaxed);
+                let mut client_hello = [0; 1024];
+                let _ = stream.read(&mut client_hello);
+                let _ = stream.write_all(&[0x15, 0x03, 0x03, 0x00, 0x02, 0x02, 0x28]);
+            }
+        });
+        url
+    }
+
+    fn new_ca() -> rcgen::Certificate {
+        let mut ca_params = rcgen::CertificateParams::new(vec![]);
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        rcgen::Certificate::from_params(ca_params).unwrap()
+    }
+
+    // Write the `ca` certificate, and a freshly generated client identity it issued, to `dir`,
+    // returning their paths
+    fn write_mtls_files(dir: &std::path::Path, ca: &rcgen::Certificate) -> (String, String) {
+        let client = rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec![
+            "remote-signer-client".to_string(),
+        ]))
+        .unwrap();
+
+        let ca_certificate = dir.join("ca.pem");
+        fs::write(&ca_certificate, ca.serialize_pem().unwrap()).unwrap();
+        let identity = dir.join("client.pem");
+        fs::write(
+            &identity,
+            client.serialize_pem_with_signer(ca).unwrap() + &client.serialize_private_key_pem(),
+        )
+        .unwrap();
+        (
+            identity.to_str().unwrap().to_string(),
+            ca_certificate.to_str().unwrap().to_string(),
+        )
+    }
+
+    // Serve the signing endpoint over TLS on a local port, with a server certificate issued by
+    // `ca`, only accepting clients that present a certificate issued by `ca` as well
+    fn start_mtls_mock_signer(
+        keypair: Keypair,
+        ca: &rcgen::Certificate,
+        requests: Arc<AtomicUsize>,
+    ) -> String {
+        let server = rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec![
+            "localhost".to_string(),
+        ]))
+        .unwrap();
+        let mut roots = rustls::RootCertStore::empty();
+        roots
+            .add(&rustls::Certificate(ca.serialize_der().unwrap()))
+            .unwrap();
+        let mut server_config =
+            rustls::ServerConfig::new(rustls::AllowAnyAuthenticatedClient::new(roots));
+        server_config
+            .set_single_cert(
+                vec![rustls::Certificate(
+                    server.serialize_der_with_signer(ca).unwrap(),
+                )],
+                rustls::PrivateKey(server.serialize_private_key_der()),
+            )
+            .unwrap();
+        let server_config = Arc::new(server_config);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "https://localhost:{}",
+            listener.local_addr().unwrap().port()
+        );
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let session = rustls::ServerSession::new(&server_config);
+                let mut stream = rustls::StreamOwned::new(session, stream.unwrap());
+                serve_sign_request(&mut stream, &keypair, &requests);
+                stream.sess.send_close_notify();
+                let _ = stream.flush();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_remote_signer_sign_mtls() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let ca = new_ca();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = start_mtls_mock_signer(keypair, &ca, requests.clone());
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (identity, ca_certificate) = write_mtls_files(temp_dir.path(), &ca);
+        let signer = RemoteSigner::new(&RemoteSignerConfig {
+            identity: Some(identity),
+            ca_certificate: Some(ca_certificate),
+            ..config(url, &pubkey, &[system_program::id()])
+        })
+        .unwrap();
+
+        let mut transaction = Transaction::new_unsigned(transfer_message(&pubkey));
+        transaction
+            .try_sign(&[&signer], transaction.message.recent_blockhash)
+            .unwrap();
+        assert!(transaction.verify().is_ok());
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_remote_signer_mtls() {
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let url = start_handshake_rejecting_signer(connections.clone());
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (identity, ca_certificate) = write_mtls_files(temp_dir.path(), &new_ca());
+        let mtls_config = RemoteSignerConfig {
+            identity: Some(identity),
+            ca_certificate: Some(ca_certificate.clone()),
+            ..config(url, &pubkey, &[system_program::id()])
+        };
+        let signer = RemoteSigner::new(&mtls_config).unwrap();
+
+        // The message is never sent when the service rejects the handshake
+        let message = transfer_message(&pubkey).serialize();
+        match signer.try_sign_message(&message) {
+            Err(SignerError::Connection(err)) => {
+                assert!(err.contains("HandshakeFailure"), "{}", err)
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(connections.load(Ordering::Relaxed), 1);
+
+        // The identity must hold a private key as well as the certificate
+        let no_private_key = RemoteSignerConfig {
+            identity: Some(ca_certificate),
+            ..mtls_config.clone()
+        };
+        assert!(RemoteSigner::new(&no_private_key).is_err());
+        let missing = |name: &str| Some(temp_dir.path().join(name).to_str().unwrap().to_string());
+        let bad_ca_certificate = RemoteSignerConfig {
+            ca_certificate: missing("missing-ca.pem"),
+            ..mtls_config.clone()
+        };
+        assert!(RemoteSigner::new(&bad_ca_certificate).is_err());
+        let bad_identity = RemoteSignerConfig {
+            identity: missing("missing-client.pem"),
+            ..mtls_config
+        };
+        assert!(RemoteSigner::new(&bad_identity).is_err());
+    }
+
+    #[test]
+    fn test_expand_home_dir() {
+        assert_eq!(
+            expand_home_dir("/etc/solana/signer.yml"),
+            "/etc/solana/signer.yml"
+        );
+        assert_eq!(expand_home_dir("signer~/signer.yml"), "signer~/signer.yml");
+        if let Ok(home) = std::env::var("HOME") {
+            assert_eq!(
+                expand_home_dir("~/.config/solana/signer.yml"),
+                format!("{}/.config/solana/signer.yml", home)
+            );
+        }
+    }
+
+    #[test]
+    fn test_remote_signer_config() {
+        let pubkey = solana_sdk::pubkey::new_rand();
+        let yaml = format!(
+            "url: https://signer.example.com:8443\npubkey: {}\nidentity: /etc/solana/client.pem\nallowed_programs:\n  - {}\n",
+            pubkey,
+            system_program::id()
+        );
+        let parsed: RemoteSignerConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.identity, Some("/etc/solana/client.pem".to_string()));
+        assert_eq!(parsed.ca_certificate, None);
+        assert_eq!(parsed.timeout_secs, DEFAULT_TIMEOUT_SECS);
+
+        let allowed_programs = [system_program::id()];
+        // Plain http is only allowed to localhost
+        let remote_http = config(
+            "http://signer.example.com".to_string(),
+            &pubkey,
+            &allowed_programs,
+        );
+        assert!(RemoteSigner::new(&remote_http).is_err());
+        // https requires a client identity
+        let no_identity = config(
+            "https://signer.example.com".to_string(),
+            &pubkey,
+            &allowed_programs,
+        );
+        assert!(RemoteSigner::new(&no_identity).is_err());
+        let bad_program = RemoteSignerConfig {
+            allowed_programs: vec!["system".to_string()],
+            ..config("http://localhost:8080".to_string(), &pubkey, &[])
+        };
+        assert!(RemoteSigner::new(&bad_program).is_err());
+    }
+}
@@ -274,46 +274,7 @@ impl RemoteWalletInfo {
             }
         }
 
-        let mut derivation_path = DerivationPath::default();
-        let mut query_pairs = wallet_path.query_pairs();
-        if query_pairs.count() > 0 {
-            for _ in 0..query_pairs.count() {
-                if let Some(mut pair) = query_pairs.next() {
-                    if pair.0 == "key" {
-                        let key_path = pair.1.to_mut();
-                        let _key_path = key_path.clone();
-                        if key_path.ends_with('/') {
-                            key_path.pop();
-                        }
-                        let mut parts = key_path.split('/');
-                        if let Some(account) = parts.next() {
-                            derivation_path.account =
-                                Some(DerivationPathComponent::from_str(account)?);
-                        }
-                        if let Some(change) = parts.next() {
-                            derivation_path.change =
-                                Some(DerivationPathComponent::from_str(change)?);
-                        }
-                        if parts.next().is_some() {
-                            return Err(RemoteWalletError::InvalidDerivationPath(format!(
-                                "key path `{}` too deep, only <account>/<change> supported",
-                                _key_path
-                            )));
-                        }
-                    } else {
-                        return Err(RemoteWalletError::InvalidDerivationPath(format!(
-                            "invalid query string `{}={}`, only `key` supported",
-                            pair.0, pair.1
-                        )));
-                    }
-                }
-                if query_pairs.next().is_some() {
-                    return Err(RemoteWalletError::InvalidDerivationPath(
-                        "invalid query string, extra fields not supported".to_string(),
-                    ));
-                }
-            }
-        }
+        let derivation_path = DerivationPath::from_uri(&wallet_path)?;
         Ok((wallet_info, derivation_path))
     }
 
@@ -406,6 +367,62 @@ impl fmt::Debug for DerivationPath {
 }
 
 impl DerivationPath {
+    /// Parse an `<account>/<change>` key path, as found in the `key` query of a signer URI
+    pub fn from_key_str(key_path: &str) -> Result<Self, RemoteWalletError> {
+        let mut derivation_path = DerivationPath::default();
+        let mut parts = key_path.strip_suffix('/').unwrap_or(key_path).split('/');
+        if let Some(account) = parts.next() {
+            derivation_path.account = Some(DerivationPathComponent::from_str(account)?);
+        }
+        if let Some(change) = parts.next() {
+            derivation_path.change = Some(DerivationPathComponent::from_str(change)?);
+        }
+        if parts.next().is_some() {
+            return Err(RemoteWalletError::InvalidDerivationPath(format!(
+                "key path `{}` too deep, only <account>/<change> supported",
+                key_path
+            )));
+        }
+        Ok(derivation_path)
+    }
+
+    /// Parse the derivation path from the `key` query of a signer URI
+    pub fn from_uri(uri: &Url) -> Result<Self, RemoteWalletError> {
+        let mut derivation_path = DerivationPath::default();
+        let mut query_pairs = uri.query_pairs();
+        if let Some(pair) = query_pairs.next() {
+            if pair.0 == "key" {
+                derivation_path = Self::from_key_str(&pair.1)?;
+            } else {
+                return Err(RemoteWalletError::InvalidDerivationPath(format!(
+                    "invalid query string `{}={}`, only `key` supported",
+                    pair.0, pair.1
+                )));
+            }
+        }
+        if query_pairs.next().is_some() {
+            return Err(RemoteWalletError::InvalidDerivationPath(
+                "invalid query string, extra fields not supported".to_string(),
+            ));
+        }
+        Ok(derivation_path)
+    }
+
+    /// Every index of the full path, starting with the `44'/501'` purpose and coin type
+    pub fn components(&self) -> Vec<u32> {
+        let mut components = vec![
+            DerivationPathComponent::from(44).as_u32(),
+            DerivationPathComponent::from(501).as_u32(),
+        ];
+        if let Some(account) = &self.account {
+            components.push(account.as_u32());
+            if let Some(change) = &self.change {
+                components.push(change.as_u32());
+            }
+        }
+        components
+    }
+
     pub fn get_query(&self) -> String {
         if let Some(account) = &self.account {
             if let Some(change) = &self.change {
@@ -674,6 +691,27 @@ mod tests {
         assert_eq!(format!("{:?}", path), "m/44'/501'/1'/2'".to_string());
     }
 
+    #[test]
+    fn test_derivation_path_components() {
+        let hardened = |index: u32| index | DerivationPathComponent::HARDENED_BIT;
+        let path = DerivationPath::from_key_str("1/2").unwrap();
+        assert_eq!(
+            path.components(),
+            vec![hardened(44), hardened(501), hardened(1), hardened(2)]
+        );
+        let path = DerivationPath::from_key_str("1'").unwrap();
+        assert_eq!(
+            path.components(),
+            vec![hardened(44), hardened(501), hardened(1)]
+        );
+        assert_eq!(
+            DerivationPath::default().components(),
+            vec![hardened(44), hardened(501)]
+        );
+        assert!(DerivationPath::from_key_str("1/2/3").is_err());
+        assert!(DerivationPath::from_key_str("one").is_err());
+    }
+
     #[test]
     fn test_derivation_path_component() {
         let f = DerivationPathComponent::from(1);
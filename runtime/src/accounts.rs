@@ -13,17 +13,20 @@ use crate::{
 use log::*;
 use rand::{thread_rng, Rng};
 use rayon::slice::ParallelSliceMut;
+use solana_address_lookup_table_program::lookup_table_state::LookupTableState;
 use solana_sdk::{
     account::Account,
     account_utils::StateMut,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     clock::{Epoch, Slot},
     feature_set::{self, FeatureSet},
     fee_calculator::{FeeCalculator, FeeConfig},
     genesis_config::ClusterType,
     hash::Hash,
-    message::Message,
+    message::{LoadedAddresses, Message, MessageAddressTableLookup, VersionedMessage},
     native_loader, nonce,
     pubkey::Pubkey,
+    sanitize::Sanitize,
     transaction::Result,
     transaction::{Transaction, TransactionError},
 };
@@ -260,6 +263,28 @@ impl Accounts {
 
             // add loader to chain
             let program_owner = program.owner;
+
+            if bpf_loader_upgradeable::check_id(&program_owner) {
+                // The upgradeable loader requires the derived ProgramData account
+                if let Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) = program.state()
+                {
+                    match AccountsDB::load(storage, ancestors, accounts_index, &programdata_address)
+                        .map(|(account, _)| account)
+                    {
+                        Some(programdata) => accounts.insert(0, (programdata_address, programdata)),
+                        None => {
+                            error_counters.account_not_found += 1;
+                            return Err(TransactionError::ProgramAccountNotFound);
+                        }
+                    }
+                } else {
+                    error_counters.invalid_program_for_execution += 1;
+                    return Err(TransactionError::InvalidProgramForExecution);
+                }
+            }
+
             accounts.insert(0, (program_id, program));
             program_id = program_owner;
         }
@@ -311,6 +336,8 @@ impl Accounts {
         let fee_config = FeeConfig {
             secp256k1_program_enabled: feature_set
                 .is_active(&feature_set::secp256k1_program_enabled::id()),
+            ed25519_program_enabled: feature_set
+                .is_active(&feature_set::ed25519_program_enabled::id()),
         };
         OrderedIterator::new(txs, txs_iteration_order)
             .zip(lock_results.into_iter())
@@ -419,6 +446,62 @@ impl Accounts {
         }
     }
 
+    /// Load the addresses an address table lookup references from its lookup table account
+    pub fn load_lookup_table_addresses(
+        &self,
+        ancestors: &Ancestors,
+        address_table_lookup: &MessageAddressTableLookup,
+    ) -> Result<LoadedAddresses> {
+        let (table_account, _slot) = self
+            .load_slow(ancestors, &address_table_lookup.account_key)
+            .ok_or(TransactionError::AddressLookupTableNotFound)?;
+        if table_account.owner != solana_address_lookup_table_program::id() {
+            return Err(TransactionError::InvalidAddressLookupTableOwner);
+        }
+        let table = LookupTableState::from_account(&table_account)
+            .ok_or(TransactionError::InvalidAddressLookupTableData)?;
+
+        let lookup_addresses = |indexes: &[u8]| -> Result<Vec<Pubkey>> {
+            indexes
+                .iter()
+                .map(|index| {
+                    table
+                        .addresses
+                        .get(*index as usize)
+                        .cloned()
+                        .ok_or(TransactionError::InvalidAddressLookupTableIndex)
+                })
+                .collect()
+        };
+        Ok(LoadedAddresses {
+            writable: lookup_addresses(&address_table_lookup.writable_indexes)?,
+            readonly: lookup_addresses(&address_table_lookup.readonly_indexes)?,
+        })
+    }
+
+    /// Sanitize a versioned message and convert it into the equivalent legacy message, loading
+    /// the accounts it references from address lookup tables
+    pub fn resolve_versioned_message(
+        &self,
+        ancestors: &Ancestors,
+        message: VersionedMessage,
+    ) -> Result<Message> {
+        message.sanitize()?;
+        match message {
+            VersionedMessage::Legacy(message) => Ok(message),
+            VersionedMessage::V0(message) => {
+                let mut loaded_addresses = LoadedAddresses::default();
+                for address_table_lookup in &message.address_table_lookups {
+                    let LoadedAddresses { writable, readonly } =
+                        self.load_lookup_table_addresses(ancestors, address_table_lookup)?;
+                    loaded_addresses.writable.extend(writable);
+                    loaded_addresses.readonly.extend(readonly);
+                }
+                Ok(message.into_legacy_message(&loaded_addresses))
+            }
+        }
+    }
+
     /// scans underlying accounts_db for this delta (slot) with a map function
     ///   from StoredAccount to B
     /// returns only the latest/current version of B for this slot
@@ -1566,6 +1649,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_accounts_upgradeable_program() {
+        let mut accounts: Vec<(Pubkey, Account)> = Vec::new();
+        let mut error_counters = ErrorCounters::default();
+
+        let keypair = Keypair::new();
+        let key0 = keypair.pubkey();
+        let program_key = Pubkey::new(&[5u8; 32]);
+        let programdata_key = Pubkey::new(&[6u8; 32]);
+
+        let mut account = Account::new(1, 0, &Pubkey::default());
+        account.rent_epoch = 1;
+        accounts.push((key0, account));
+
+        let mut account = Account::new(40, 1, &native_loader::id());
+        account.executable = true;
+        account.rent_epoch = 1;
+        accounts.push((bpf_loader_upgradeable::id(), account));
+
+        let mut account = Account::new(
+            41,
+            UpgradeableLoaderState::program_len(),
+            &bpf_loader_upgradeable::id(),
+        );
+        account
+            .set_state(&UpgradeableLoaderState::Program {
+                programdata_address: programdata_key,
+            })
+            .unwrap();
+        account.executable = true;
+        account.rent_epoch = 1;
+        accounts.push((program_key, account));
+
+        let instructions = vec![CompiledInstruction::new(1, &(), vec![0])];
+        let tx = Transaction::new_with_compiled_instructions(
+            &[&keypair],
+            &[],
+            Hash::default(),
+            vec![program_key],
+            instructions,
+        );
+
+        // The ProgramData account is missing
+        let loaded_accounts = load_accounts(tx.clone(), &accounts, &mut error_counters);
+        assert_eq!(error_counters.account_not_found, 1);
+        assert_eq!(
+            loaded_accounts[0],
+            (
+                Err(TransactionError::ProgramAccountNotFound),
+                Some(HashAgeKind::Extant)
+            )
+        );
+
+        let mut account = Account::new(
+            42,
+            UpgradeableLoaderState::programdata_len(1),
+            &bpf_loader_upgradeable::id(),
+        );
+        account
+            .set_state(&UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(key0),
+            })
+            .unwrap();
+        account.rent_epoch = 1;
+        accounts.push((programdata_key, account));
+
+        let mut error_counters = ErrorCounters::default();
+        let loaded_accounts = load_accounts(tx, &accounts, &mut error_counters);
+        assert_eq!(error_counters.account_not_found, 0);
+        match &loaded_accounts[0] {
+            (
+                Ok((_transaction_accounts, transaction_loaders, _transaction_rents)),
+                _hash_age_kind,
+            ) => {
+                // The ProgramData account follows the Program account
+                assert_eq!(transaction_loaders.len(), 1);
+                assert_eq!(
+                    transaction_loaders[0],
+                    vec![
+                        accounts[1].clone(),
+                        accounts[2].clone(),
+                        accounts[3].clone()
+                    ]
+                );
+            }
+            (Err(e), _hash_age_kind) => Err(e).unwrap(),
+        }
+    }
+
     #[test]
     fn test_load_by_program_slot() {
         let accounts = Accounts::new(Vec::new(), &ClusterType::Development);
@@ -2358,4 +2531,66 @@ mod tests {
             &next_blockhash
         ));
     }
+
+    #[test]
+    fn test_load_lookup_table_addresses() {
+        use solana_address_lookup_table_program::lookup_table_state::AddressLookupTable;
+
+        let accounts = Accounts::new(Vec::new(), &ClusterType::Development);
+        let ancestors = vec![(0, 0)].into_iter().collect();
+
+        let table_key = Pubkey::new_unique();
+        let table_addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let lookup = MessageAddressTableLookup {
+            account_key: table_key,
+            writable_indexes: vec![1],
+            readonly_indexes: vec![0],
+        };
+        assert_eq!(
+            accounts.load_lookup_table_addresses(&ancestors, &lookup),
+            Err(TransactionError::AddressLookupTableNotFound)
+        );
+
+        let mut table_account = Account::new(
+            1,
+            AddressLookupTable::space(table_addresses.len()) as usize,
+            &Pubkey::new_unique(),
+        );
+        accounts.store_slow(0, &table_key, &table_account);
+        assert_eq!(
+            accounts.load_lookup_table_addresses(&ancestors, &lookup),
+            Err(TransactionError::InvalidAddressLookupTableOwner)
+        );
+
+        table_account.owner = solana_address_lookup_table_program::id();
+        accounts.store_slow(0, &table_key, &table_account);
+        assert_eq!(
+            accounts.load_lookup_table_addresses(&ancestors, &lookup),
+            Err(TransactionError::InvalidAddressLookupTableData)
+        );
+
+        table_account
+            .set_state(&LookupTableState::Initialized(AddressLookupTable {
+                authority: None,
+                addresses: table_addresses.clone(),
+            }))
+            .unwrap();
+        accounts.store_slow(0, &table_key, &table_account);
+        assert_eq!(
+            accounts.load_lookup_table_addresses(&ancestors, &lookup),
+            Ok(LoadedAddresses {
+                writable: vec![table_addresses[1]],
+                readonly: vec![table_addresses[0]],
+            })
+        );
+
+        let bad_lookup = MessageAddressTableLookup {
+            readonly_indexes: vec![2],
+            ..lookup
+        };
+        assert_eq!(
+            accounts.load_lookup_table_addresses(&ancestors, &bad_lookup),
+            Err(TransactionError::InvalidAddressLookupTableIndex)
+        );
+    }
 }
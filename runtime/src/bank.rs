@@ -35,6 +35,7 @@ use solana_sdk::{
         MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES, MAX_TRANSACTION_FORWARDING_DELAY,
         SECONDS_PER_DAY,
     },
+    compute_budget,
     epoch_info::EpochInfo,
     epoch_schedule::EpochSchedule,
     feature,
@@ -46,7 +47,7 @@ use solana_sdk::{
     incinerator,
     inflation::Inflation,
     instruction::CompiledInstruction,
-    message::Message,
+    message::{Message, VersionedMessage},
     native_loader,
     native_token::sol_to_lamports,
     nonce, nonce_account,
@@ -64,7 +65,7 @@ use solana_sdk::{
     system_transaction,
     sysvar::{self},
     timing::years_as_slots,
-    transaction::{self, Result, Transaction, TransactionError},
+    transaction::{self, Result, Transaction, TransactionError, VersionedTransaction},
 };
 use solana_stake_program::stake_state::{
     self, Delegation, InflationPointCalculationEvent, PointValue,
@@ -114,7 +115,7 @@ pub const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
 pub const MAX_LEADER_SCHEDULE_STAKES: Epoch = 5;
 
 type BankStatusCache = StatusCache<Result<()>>;
-#[frozen_abi(digest = "4nZ6EdivqQPcnrnXisbjuTjpcUBoHLDEQWvbZQDCoQQR")]
+#[frozen_abi(digest = "3bVUWzUsFaNaJ1jYxHKfwa6WVnW71o4jymzuGxUF9H1x")]
 pub type BankSlotDelta = SlotDelta<Result<()>>;
 type TransactionAccountRefCells = Vec<Rc<RefCell<Account>>>;
 type TransactionLoaderRefCells = Vec<Vec<(Pubkey, RefCell<Account>)>>;
@@ -273,7 +274,10 @@ impl CachedExecutors {
         })
     }
     fn put(&mut self, pubkey: &Pubkey, executor: Arc<dyn Executor>) {
-        if !self.executors.contains_key(pubkey) {
+        if let Some((_, cached_executor)) = self.executors.get_mut(pubkey) {
+            // The program was upgraded, replace the stale executor
+            *cached_executor = executor;
+        } else {
             if self.executors.len() >= self.max {
                 let mut least = u64::MAX;
                 let default_key = Pubkey::default();
@@ -405,6 +409,24 @@ pub type InnerInstructionsList = Vec<InnerInstructions>;
 /// A list of log messages emitted during a transaction
 pub type TransactionLogMessages = Vec<String>;
 
+/// Return data set by the last program to call `sol_set_return_data` during a
+/// transaction
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TransactionReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// An event emitted by a program through `sol_log_data`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TransactionLogEvent {
+    pub program_id: Pubkey,
+    pub data: Vec<Vec<u8>>,
+}
+
+/// A list of events emitted during a transaction
+pub type TransactionLogEvents = Vec<TransactionLogEvent>;
+
 #[derive(Serialize, Deserialize, AbiExample, AbiEnumVisitor, Debug, PartialEq)]
 pub enum TransactionLogCollectorFilter {
     All,
@@ -772,6 +794,24 @@ pub struct Bank {
     pub transaction_log_collector: Arc<RwLock<TransactionLogCollector>>,
 
     pub feature_set: Arc<FeatureSet>,
+
+    /// The number of transactions committed in this slot, with or without error
+    executed_transaction_count: AtomicU64,
+
+    /// The number of transactions committed in this slot that failed to execute
+    transaction_error_count: AtomicU64,
+
+    /// The number of simple vote transactions committed in this slot
+    vote_transaction_count: AtomicU64,
+
+    /// Compute units consumed by the transactions committed in this slot
+    compute_units_consumed: AtomicU64,
+
+    /// The number of entries with transactions recorded into this slot
+    transaction_entries_count: AtomicU64,
+
+    /// The number of tick entries recorded into this slot
+    tick_entries_count: AtomicU64,
 }
 
 impl Default for BlockhashQueue {
@@ -918,6 +958,12 @@ impl Bank {
             transaction_log_collector_config: parent.transaction_log_collector_config.clone(),
             transaction_log_collector: Arc::new(RwLock::new(TransactionLogCollector::default())),
             feature_set: parent.feature_set.clone(),
+            executed_transaction_count: AtomicU64::new(0),
+            transaction_error_count: AtomicU64::new(0),
+            vote_transaction_count: AtomicU64::new(0),
+            compute_units_consumed: AtomicU64::new(0),
+            transaction_entries_count: AtomicU64::new(0),
+            tick_entries_count: AtomicU64::new(0),
         };
 
         datapoint_info!(
@@ -1036,6 +1082,12 @@ impl Bank {
             transaction_log_collector_config: new(),
             transaction_log_collector: new(),
             feature_set: new(),
+            executed_transaction_count: new(),
+            transaction_error_count: new(),
+            vote_transaction_count: new(),
+            compute_units_consumed: new(),
+            transaction_entries_count: new(),
+            tick_entries_count: new(),
         };
         bank.finish_init(genesis_config, additional_builtins);
 
@@ -2145,6 +2197,72 @@ impl Bank {
             .map_or(Ok(()), |sig| self.get_signature_status(sig).unwrap())
     }
 
+    /// Convert a versioned transaction into the equivalent legacy transaction, so that the
+    /// accounts its message loads from address lookup tables are locked and loaded like any
+    /// other account of the batch.  The signatures aren't checked: they cover the versioned
+    /// message, so callers verify them on `tx` before it is resolved.
+    pub fn resolve_versioned_transaction(&self, tx: VersionedTransaction) -> Result<Transaction> {
+        tx.sanitize()?;
+        if tx.message.address_table_lookups().is_some()
+            && !self
+                .feature_set
+                .is_active(&feature_set::address_lookup_table_program::id())
+        {
+            return Err(TransactionError::SanitizeFailure);
+        }
+
+        let VersionedTransaction {
+            signatures,
+            message,
+        } = tx;
+        Ok(Transaction {
+            signatures,
+            message: self.resolve_versioned_message(message)?,
+        })
+    }
+
+    /// Verify the signatures and precompiles of a versioned transaction and resolve it, see
+    /// Bank::resolve_versioned_transaction
+    pub fn verify_and_resolve_versioned_transaction(
+        &self,
+        tx: VersionedTransaction,
+    ) -> Result<Transaction> {
+        tx.sanitize()?;
+        tx.verify()?;
+        tx.verify_precompiles(
+            self.secp256k1_program_enabled(),
+            self.ed25519_program_enabled(),
+        )?;
+        self.resolve_versioned_transaction(tx)
+    }
+
+    /// Process versioned transactions, resolving their address table lookups against this bank
+    pub fn process_versioned_transactions(
+        &self,
+        txs: Vec<VersionedTransaction>,
+    ) -> Vec<Result<()>> {
+        let resolved_txs: Vec<_> = txs
+            .into_iter()
+            .map(|tx| self.verify_and_resolve_versioned_transaction(tx))
+            .collect();
+        let legacy_txs: Vec<_> = resolved_txs
+            .iter()
+            .filter_map(|tx| tx.as_ref().ok().cloned())
+            .collect();
+        let mut results = self.process_transactions(&legacy_txs).into_iter();
+        resolved_txs
+            .into_iter()
+            .map(|tx| tx.and_then(|_| results.next().unwrap()))
+            .collect()
+    }
+
+    /// Process a versioned Transaction, see Bank::process_transaction
+    pub fn process_versioned_transaction(&self, tx: VersionedTransaction) -> Result<()> {
+        let signature = tx.signatures.get(0).cloned();
+        self.process_versioned_transactions(vec![tx])[0].clone()?;
+        signature.map_or(Ok(()), |sig| self.get_signature_status(&sig).unwrap())
+    }
+
     pub fn prepare_batch<'a, 'b>(
         &'a self,
         txs: &'b [Transaction],
@@ -2174,7 +2292,11 @@ impl Bank {
     pub fn simulate_transaction(
         &self,
         transaction: Transaction,
-    ) -> (Result<()>, TransactionLogMessages) {
+    ) -> (
+        Result<()>,
+        TransactionLogMessages,
+        Option<TransactionReturnData>,
+    ) {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
         let txs = &[transaction];
@@ -2185,9 +2307,12 @@ impl Bank {
             executed,
             _inner_instructions,
             log_messages,
+            _log_events,
+            return_data,
             _retryable_transactions,
             _transaction_count,
             _signature_count,
+            _compute_units_consumed,
         ) = self.load_and_execute_transactions(
             &batch,
             // After simulation, transactions will need to be forwarded to the leader
@@ -2202,8 +2327,9 @@ impl Bank {
         let log_messages = log_messages
             .get(0)
             .map_or(vec![], |messages| messages.to_vec());
+        let return_data = return_data.into_iter().next().flatten();
 
-        (transaction_result, log_messages)
+        (transaction_result, log_messages, return_data)
     }
 
     pub fn unlock_accounts(&self, batch: &mut TransactionBatch) {
@@ -2537,6 +2663,26 @@ impl Bank {
         }));
     }
 
+    /// Collect the sysvars that programs may fetch through syscalls rather than
+    /// as instruction accounts
+    fn get_syscall_sysvars(&self) -> Vec<(Pubkey, Vec<u8>)> {
+        if !self
+            .feature_set
+            .is_active(&feature_set::sysvar_syscalls::id())
+        {
+            return vec![];
+        }
+        [
+            sysvar::clock::id(),
+            sysvar::epoch_schedule::id(),
+            sysvar::fees::id(),
+            sysvar::rent::id(),
+        ]
+        .iter()
+        .filter_map(|id| self.get_account(id).map(|account| (*id, account.data)))
+        .collect()
+    }
+
     /// Get any cached executors needed by the transaction
     fn get_executors(
         &self,
@@ -2601,9 +2747,12 @@ impl Bank {
         Vec<TransactionProcessResult>,
         Vec<Option<InnerInstructionsList>>,
         Vec<TransactionLogMessages>,
+        Vec<TransactionLogEvents>,
+        Vec<Option<TransactionReturnData>>,
         Vec<usize>,
         u64,
         u64,
+        u64,
     ) {
         let txs = batch.transactions();
         debug!("processing transactions: {}", txs.len());
@@ -2641,18 +2790,22 @@ impl Bank {
 
         let mut execution_time = Measure::start("execution_time");
         let mut signature_count: u64 = 0;
+        let mut compute_units_consumed: u64 = 0;
         let mut inner_instructions: Vec<Option<InnerInstructionsList>> =
             Vec::with_capacity(txs.len());
         let mut transaction_log_messages = Vec::with_capacity(txs.len());
-        let bpf_compute_budget = self
-            .bpf_compute_budget
-            .unwrap_or_else(|| BpfComputeBudget::new(&self.feature_set));
+        let mut transaction_log_events = Vec::with_capacity(txs.len());
+        let mut transaction_return_data = Vec::with_capacity(txs.len());
+        let sysvars = self.get_syscall_sysvars();
 
         let executed: Vec<TransactionProcessResult> = loaded_accounts
             .iter_mut()
             .zip(OrderedIterator::new(txs, batch.iteration_order()))
             .map(|(accs, (_, tx))| match accs {
-                (Err(e), hash_age_kind) => (Err(e.clone()), hash_age_kind.clone()),
+                (Err(e), hash_age_kind) => {
+                    transaction_return_data.push(None);
+                    (Err(e.clone()), hash_age_kind.clone())
+                }
                 (Ok((accounts, loaders, _rents)), hash_age_kind) => {
                     signature_count += u64::from(tx.message().header.num_required_signatures);
 
@@ -2676,25 +2829,35 @@ impl Bank {
                         None
                     };
 
-                    let process_result = self.message_processor.process_message(
-                        tx.message(),
-                        &loader_refcells,
-                        &account_refcells,
-                        &self.rent_collector,
-                        log_collector.clone(),
-                        executors.clone(),
-                        instruction_recorders.as_deref(),
-                        self.feature_set.clone(),
-                        bpf_compute_budget,
-                    );
+                    let mut return_data = None;
+                    let process_result = self
+                        .bpf_compute_budget_for_message(tx.message())
+                        .and_then(|bpf_compute_budget| {
+                            self.message_processor.process_message(
+                                tx.message(),
+                                &loader_refcells,
+                                &account_refcells,
+                                &self.rent_collector,
+                                log_collector.clone(),
+                                executors.clone(),
+                                instruction_recorders.as_deref(),
+                                self.feature_set.clone(),
+                                &sysvars,
+                                bpf_compute_budget,
+                                &mut compute_units_consumed,
+                                &mut return_data,
+                            )
+                        });
+                    transaction_return_data.push(return_data);
 
                     if enable_log_recording {
-                        let log_messages: TransactionLogMessages =
+                        let (log_messages, log_events) =
                             Rc::try_unwrap(log_collector.unwrap_or_default())
                                 .unwrap_or_default()
-                                .into();
+                                .into_messages_and_events();
 
                         transaction_log_messages.push(log_messages);
+                        transaction_log_events.push(log_events);
                     }
 
                     Self::compile_recorded_instructions(
@@ -2710,7 +2873,11 @@ impl Bank {
                         loader_refcells,
                     );
 
-                    self.update_executors(executors);
+                    // Executors of a failed transaction may belong to program
+                    // upgrades which were rolled back
+                    if process_result.is_ok() {
+                        self.update_executors(executors);
+                    }
 
                     if let Err(TransactionError::InstructionError(_, _)) = &process_result {
                         error_counters.instruction_error += 1;
@@ -2809,9 +2976,12 @@ impl Bank {
             executed,
             inner_instructions,
             transaction_log_messages,
+            transaction_log_events,
+            transaction_return_data,
             retryable_txs,
             tx_count,
             signature_count,
+            compute_units_consumed,
         )
     }
 
@@ -2826,6 +2996,7 @@ impl Bank {
 
         let fee_config = FeeConfig {
             secp256k1_program_enabled: self.secp256k1_program_enabled(),
+            ed25519_program_enabled: self.ed25519_program_enabled(),
         };
 
         let results = OrderedIterator::new(txs, iteration_order)
@@ -2875,6 +3046,7 @@ impl Bank {
         results
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn commit_transactions(
         &self,
         txs: &[Transaction],
@@ -2883,6 +3055,7 @@ impl Bank {
         executed: &[TransactionProcessResult],
         tx_count: u64,
         signature_count: u64,
+        compute_units_consumed: u64,
     ) -> TransactionResults {
         assert!(
             !self.is_frozen(),
@@ -2891,6 +3064,9 @@ impl Bank {
 
         self.increment_transaction_count(tx_count);
         self.increment_signature_count(signature_count);
+        self.compute_units_consumed
+            .fetch_add(compute_units_consumed, Relaxed);
+        self.update_executed_transaction_counts(txs, iteration_order, executed);
 
         inc_new_counter_info!("bank-process_transactions-txs", tx_count as usize);
         inc_new_counter_info!("bank-process_transactions-sigs", signature_count as usize);
@@ -3510,6 +3686,8 @@ impl Bank {
         TransactionBalancesSet,
         Vec<Option<InnerInstructionsList>>,
         Vec<TransactionLogMessages>,
+        Vec<TransactionLogEvents>,
+        Vec<Option<TransactionReturnData>>,
     ) {
         let pre_balances = if collect_balances {
             self.collect_balances(batch)
@@ -3522,9 +3700,12 @@ impl Bank {
             executed,
             inner_instructions,
             transaction_logs,
+            transaction_log_events,
+            transaction_return_data,
             _,
             tx_count,
             signature_count,
+            compute_units_consumed,
         ) = self.load_and_execute_transactions(
             batch,
             max_age,
@@ -3539,6 +3720,7 @@ impl Bank {
             &executed,
             tx_count,
             signature_count,
+            compute_units_consumed,
         );
         let post_balances = if collect_balances {
             self.collect_balances(batch)
@@ -3550,6 +3732,8 @@ impl Bank {
             TransactionBalancesSet::new(pre_balances, post_balances),
             inner_instructions,
             transaction_logs,
+            transaction_log_events,
+            transaction_return_data,
         )
     }
 
@@ -3716,6 +3900,47 @@ impl Bank {
         self.bpf_compute_budget = bpf_compute_budget;
     }
 
+    /// The compute budget `message` executes under, honoring any compute-budget program
+    /// request once the transaction-wide compute cap is active.  Without a request, the
+    /// instructions share the units each of them used to get on its own, up to `MAX_UNITS`
+    pub fn bpf_compute_budget_for_message(&self, message: &Message) -> Result<BpfComputeBudget> {
+        let mut bpf_compute_budget = self
+            .bpf_compute_budget
+            .unwrap_or_else(|| BpfComputeBudget::new(&self.feature_set));
+        if self
+            .feature_set
+            .is_active(&feature_set::transaction_wide_compute_cap::id())
+        {
+            bpf_compute_budget.max_units = match compute_budget::get_requested_units(message)? {
+                Some(units) => units,
+                None => bpf_compute_budget
+                    .max_units
+                    .saturating_mul(message.instructions.len() as u64)
+                    .min(compute_budget::MAX_UNITS),
+            };
+        }
+        Ok(bpf_compute_budget)
+    }
+
+    /// Convert a versioned message into the equivalent legacy message, loading the accounts
+    /// it references from address lookup tables as of the parent bank.  Addresses added to a
+    /// table in this slot are only usable from the next one, so the leader and the validators
+    /// replaying its block resolve every message of the slot the same way, no matter how far
+    /// the slot was processed when they did.
+    pub fn resolve_versioned_message(&self, message: VersionedMessage) -> Result<Message> {
+        if message.address_table_lookups().is_none() {
+            return self
+                .rc
+                .accounts
+                .resolve_versioned_message(&self.ancestors, message);
+        }
+        let mut ancestors = self.ancestors.clone();
+        ancestors.remove(&self.slot());
+        self.rc
+            .accounts
+            .resolve_versioned_message(&ancestors, message)
+    }
+
     pub fn hard_forks(&self) -> Arc<RwLock<HardForks>> {
         self.hard_forks.clone()
     }
@@ -3825,6 +4050,81 @@ impl Bank {
         self.signature_count.fetch_add(signature_count, Relaxed);
     }
 
+    fn update_executed_transaction_counts(
+        &self,
+        txs: &[Transaction],
+        iteration_order: Option<&[usize]>,
+        executed: &[TransactionProcessResult],
+    ) {
+        let mut executed_transaction_count = 0;
+        let mut transaction_error_count = 0;
+        let mut vote_transaction_count = 0;
+        for ((_, tx), (res, _hash_age_kind)) in
+            OrderedIterator::new(txs, iteration_order).zip(executed.iter())
+        {
+            if !Self::can_commit(res) {
+                continue;
+            }
+            executed_transaction_count += 1;
+            if res.is_err() {
+                transaction_error_count += 1;
+            }
+            if is_simple_vote_transaction(tx) {
+                vote_transaction_count += 1;
+            }
+        }
+        self.executed_transaction_count
+            .fetch_add(executed_transaction_count, Relaxed);
+        self.transaction_error_count
+            .fetch_add(transaction_error_count, Relaxed);
+        self.vote_transaction_count
+            .fetch_add(vote_transaction_count, Relaxed);
+    }
+
+    /// The number of transactions committed in this slot, with or without error
+    pub fn executed_transaction_count(&self) -> u64 {
+        self.executed_transaction_count.load(Relaxed)
+    }
+
+    /// The number of transactions committed in this slot that failed to execute
+    pub fn transaction_error_count(&self) -> u64 {
+        self.transaction_error_count.load(Relaxed)
+    }
+
+    /// The number of simple vote transactions committed in this slot
+    pub fn vote_transaction_count(&self) -> u64 {
+        self.vote_transaction_count.load(Relaxed)
+    }
+
+    /// Compute units consumed by the transactions committed in this slot
+    pub fn compute_units_consumed(&self) -> u64 {
+        self.compute_units_consumed.load(Relaxed)
+    }
+
+    /// The number of entries with transactions recorded into this slot
+    pub fn transaction_entries_count(&self) -> u64 {
+        self.transaction_entries_count.load(Relaxed)
+    }
+
+    pub fn inc_transaction_entries_count(&self, entries_count: u64) {
+        self.transaction_entries_count
+            .fetch_add(entries_count, Relaxed);
+    }
+
+    /// The number of tick entries recorded into this slot
+    pub fn tick_entries_count(&self) -> u64 {
+        self.tick_entries_count.load(Relaxed)
+    }
+
+    pub fn inc_tick_entries_count(&self, entries_count: u64) {
+        self.tick_entries_count.fetch_add(entries_count, Relaxed);
+    }
+
+    /// Transaction fees collected in this slot, before any are burned
+    pub fn collector_fees(&self) -> u64 {
+        self.collector_fees.load(Relaxed)
+    }
+
     pub fn get_signature_status_processed_since_parent(
         &self,
         signature: &Signature,
@@ -4272,6 +4572,11 @@ impl Bank {
             .is_active(&feature_set::secp256k1_program_enabled::id())
     }
 
+    pub fn ed25519_program_enabled(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::ed25519_program_enabled::id())
+    }
+
     pub fn no_overflow_rent_distribution_enabled(&self) -> bool {
         self.feature_set
             .is_active(&feature_set::no_overflow_rent_distribution::id())
@@ -4514,7 +4819,7 @@ fn is_simple_vote_transaction(transaction: &Transaction) -> bool {
         if program_pubkey == solana_vote_program::id() {
             if let Ok(vote_instruction) = limited_deserialize::<VoteInstruction>(&instruction.data)
             {
-                return matches!(vote_instruction, VoteInstruction::Vote(_) | VoteInstruction::VoteSwitch(_, _));
+                return matches!(vote_instruction, VoteInstruction::Vote(_) | VoteInstruction::VoteSwitch(_, _) | VoteInstruction::UpdateVoteState(_) | VoteInstruction::UpdateVoteStateSwitch(_, _));
             }
         }
     }
@@ -8996,17 +9301,25 @@ pub(crate) mod tests {
         let txs = vec![tx0, tx1, tx2];
 
         let lock_result = bank0.prepare_batch(&txs, None);
-        let (transaction_results, transaction_balances_set, inner_instructions, transaction_logs) =
-            bank0.load_execute_and_commit_transactions(
-                &lock_result,
-                MAX_PROCESSING_AGE,
-                true,
-                false,
-                false,
-            );
+        let (
+            transaction_results,
+            transaction_balances_set,
+            inner_instructions,
+            transaction_logs,
+            transaction_log_events,
+            transaction_return_data,
+        ) = bank0.load_execute_and_commit_transactions(
+            &lock_result,
+            MAX_PROCESSING_AGE,
+            true,
+            false,
+            false,
+        );
 
         assert!(inner_instructions[0].iter().all(|ix| ix.is_empty()));
         assert_eq!(transaction_logs.len(), 0);
+        assert_eq!(transaction_log_events.len(), 0);
+        assert_eq!(transaction_return_data, vec![None, None, None]);
 
         assert_eq!(transaction_balances_set.pre_balances.len(), 3);
         assert_eq!(transaction_balances_set.post_balances.len(), 3);
@@ -10031,6 +10344,13 @@ pub(crate) mod tests {
         assert!(cache.get(&key2).is_none());
         assert!(cache.get(&key3).is_some());
         assert!(cache.get(&key4).is_some());
+
+        // An upgraded program's executor replaces the cached one
+        let upgraded_executor: Arc<dyn Executor> = Arc::new(TestExecutor {});
+        cache.put(&key1, upgraded_executor.clone());
+        assert!(Arc::ptr_eq(&cache.get(&key1).unwrap(), &upgraded_executor));
+        assert!(cache.get(&key3).is_some());
+        assert!(cache.get(&key4).is_some());
     }
 
     #[test]
@@ -11130,4 +11450,163 @@ pub(crate) mod tests {
         }
         assert_eq!(bank.get_inflation_num_slots(), 2 * slots_per_epoch);
     }
+
+    #[test]
+    fn test_bpf_compute_budget_for_message() {
+        let (genesis_config, mint_keypair) = create_genesis_config(100_000);
+        let mut bank = Bank::new(&genesis_config);
+        let default_units = BpfComputeBudget::new(&bank.feature_set).max_units;
+
+        let transfer = system_instruction::transfer(
+            &mint_keypair.pubkey(),
+            &solana_sdk::pubkey::new_rand(),
+            1,
+        );
+        let message = Message::new(
+            &[compute_budget::request_units(500_000), transfer],
+            Some(&mint_keypair.pubkey()),
+        );
+
+        // Requests are ignored until the feature is active
+        bank.feature_set = Arc::new(FeatureSet::default());
+        assert_eq!(
+            bank.bpf_compute_budget_for_message(&message)
+                .unwrap()
+                .max_units,
+            default_units
+        );
+
+        bank.feature_set = Arc::new(FeatureSet::all_enabled());
+        assert_eq!(
+            bank.bpf_compute_budget_for_message(&message)
+                .unwrap()
+                .max_units,
+            500_000
+        );
+
+        let message = Message::new(
+            &[compute_budget::request_units(compute_budget::MAX_UNITS + 1)],
+            Some(&mint_keypair.pubkey()),
+        );
+        assert_eq!(
+            bank.bpf_compute_budget_for_message(&message).unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_bpf_compute_budget_for_message_without_request() {
+        let (genesis_config, mint_keypair) = create_genesis_config(100_000);
+        let mut bank = Bank::new(&genesis_config);
+        bank.feature_set = Arc::new(FeatureSet::all_enabled());
+        let default_units = BpfComputeBudget::new(&bank.feature_set).max_units;
+        let transfers = |num_instructions| {
+            let instructions: Vec<_> = (0..num_instructions)
+                .map(|_| {
+                    system_instruction::transfer(
+                        &mint_keypair.pubkey(),
+                        &solana_sdk::pubkey::new_rand(),
+                        1,
+                    )
+                })
+                .collect();
+            Message::new(&instructions, Some(&mint_keypair.pubkey()))
+        };
+
+        // Each instruction keeps the budget it had before the cap was shared
+        let num_instructions = 3;
+        assert!(default_units * num_instructions < compute_budget::MAX_UNITS);
+        assert_eq!(
+            bank.bpf_compute_budget_for_message(&transfers(num_instructions))
+                .unwrap()
+                .max_units,
+            default_units * num_instructions
+        );
+
+        // Up to the most a transaction can request
+        let num_instructions = compute_budget::MAX_UNITS / default_units + 1;
+        assert_eq!(
+            bank.bpf_compute_budget_for_message(&transfers(num_instructions))
+                .unwrap()
+                .max_units,
+            compute_budget::MAX_UNITS
+        );
+    }
+
+    #[test]
+    fn test_process_versioned_transaction() {
+        use solana_address_lookup_table_program::lookup_table_state::{
+            AddressLookupTable, LookupTableState,
+        };
+        use solana_sdk::message::{MessageV0, VersionedMessage};
+
+        let (genesis_config, mint_keypair) = create_genesis_config(100_000);
+        let parent = Arc::new(Bank::new(&genesis_config));
+
+        let to = solana_sdk::pubkey::new_rand();
+        let table_key = solana_sdk::pubkey::new_rand();
+        let mut table_account = Account::new(
+            1,
+            AddressLookupTable::space(1) as usize,
+            &solana_address_lookup_table_program::id(),
+        );
+        table_account
+            .set_state(&LookupTableState::Initialized(AddressLookupTable {
+                authority: None,
+                addresses: vec![to],
+            }))
+            .unwrap();
+        parent.store_account(&table_key, &table_account);
+        let mut bank = Bank::new_from_parent(&parent, &Pubkey::default(), 1);
+
+        let recent_blockhash = bank.last_blockhash();
+        let new_versioned_transaction = |table_key: Pubkey| {
+            let mut message = MessageV0::new_with_lookup_tables(
+                &[system_instruction::transfer(&mint_keypair.pubkey(), &to, 1)],
+                Some(&mint_keypair.pubkey()),
+                &[(table_key, vec![to])],
+            );
+            message.recent_blockhash = recent_blockhash;
+            VersionedTransaction::try_new(VersionedMessage::V0(message), &[&mint_keypair]).unwrap()
+        };
+        let tx = new_versioned_transaction(table_key);
+        assert!(!tx.message.static_account_keys().contains(&to));
+
+        // Versioned messages are rejected until lookup tables are enabled
+        bank.feature_set = Arc::new(FeatureSet::default());
+        assert_eq!(
+            bank.process_versioned_transaction(tx.clone()),
+            Err(TransactionError::SanitizeFailure)
+        );
+
+        bank.feature_set = Arc::new(FeatureSet::all_enabled());
+        let mut bad_tx = tx.clone();
+        bad_tx.signatures[0] = Signature::default();
+        assert_eq!(
+            bank.process_versioned_transaction(bad_tx),
+            Err(TransactionError::SignatureFailure)
+        );
+        assert_eq!(
+            bank.process_versioned_transaction(new_versioned_transaction(
+                solana_sdk::pubkey::new_rand()
+            )),
+            Err(TransactionError::AddressLookupTableNotFound)
+        );
+
+        // Tables are read as of the parent bank, so one stored in this slot isn't usable yet
+        let late_table_key = solana_sdk::pubkey::new_rand();
+        bank.store_account(&late_table_key, &table_account);
+        assert_eq!(
+            bank.process_versioned_transaction(new_versioned_transaction(late_table_key)),
+            Err(TransactionError::AddressLookupTableNotFound)
+        );
+
+        assert_eq!(bank.process_versioned_transaction(tx.clone()), Ok(()));
+        assert_eq!(bank.get_balance(&to), 1);
+        assert_eq!(bank.get_signature_status(&tx.signatures[0]), Some(Ok(())));
+        assert_eq!(
+            bank.process_versioned_transaction(tx),
+            Err(TransactionError::DuplicateSignature)
+        );
+    }
 }
@@ -102,6 +102,15 @@ fn feature_builtins() -> Vec<(Builtin, Pubkey, ActivationType)> {
             feature_set::secp256k1_program_enabled::id(),
             ActivationType::NewProgram,
         ),
+        (
+            Builtin::new(
+                "ed25519_program",
+                solana_sdk::ed25519_program::id(),
+                solana_ed25519_program::process_instruction,
+            ),
+            feature_set::ed25519_program_enabled::id(),
+            ActivationType::NewProgram,
+        ),
         (
             Builtin::new(
                 "stake_program_v2",
@@ -111,6 +120,24 @@ fn feature_builtins() -> Vec<(Builtin, Pubkey, ActivationType)> {
             feature_set::stake_program_v2::id(),
             ActivationType::NewVersion,
         ),
+        (
+            Builtin::new(
+                "compute_budget_program",
+                solana_sdk::compute_budget::id(),
+                solana_compute_budget_program::process_instruction,
+            ),
+            feature_set::transaction_wide_compute_cap::id(),
+            ActivationType::NewProgram,
+        ),
+        (
+            Builtin::new(
+                "address_lookup_table_program",
+                solana_address_lookup_table_program::id(),
+                solana_address_lookup_table_program::lookup_table_processor::process_instruction,
+            ),
+            feature_set::address_lookup_table_program::id(),
+            ActivationType::NewProgram,
+        ),
     ]
 }
 
@@ -1,12 +1,17 @@
+use crate::bank::{TransactionLogEvent, TransactionLogEvents, TransactionLogMessages};
 use std::cell::RefCell;
 
 const LOG_MESSAGES_BYTES_LIMIT: usize = 10 * 1000;
+const LOG_EVENTS_BYTES_LIMIT: usize = 10 * 1000;
 
 #[derive(Default)]
 struct LogCollectorInner {
     messages: Vec<String>,
     bytes_written: usize,
     limit_warning: bool,
+    events: Vec<TransactionLogEvent>,
+    events_bytes_written: usize,
+    events_limit_warning: bool,
 }
 
 #[derive(Default)]
@@ -28,6 +33,28 @@ impl LogCollector {
             inner.messages.push(message.to_string());
         }
     }
+
+    /// Record a structured event. Events are kept when the log messages are truncated, but have
+    /// a size limit of their own, counted in serialized bytes
+    pub fn log_event(&self, event: TransactionLogEvent) {
+        let mut inner = self.inner.borrow_mut();
+        let event_len = bincode::serialized_size(&event).unwrap_or(u64::MAX) as usize;
+
+        if inner.events_bytes_written.saturating_add(event_len) >= LOG_EVENTS_BYTES_LIMIT {
+            if !inner.events_limit_warning {
+                inner.events_limit_warning = true;
+                inner.messages.push(String::from("Log events truncated"));
+            }
+        } else {
+            inner.events_bytes_written += event_len;
+            inner.events.push(event);
+        }
+    }
+
+    pub fn into_messages_and_events(self) -> (TransactionLogMessages, TransactionLogEvents) {
+        let inner = self.inner.into_inner();
+        (inner.messages, inner.events)
+    }
 }
 
 impl Into<Vec<String>> for LogCollector {
@@ -55,4 +82,31 @@ pub(crate) mod tests {
         }
         assert_eq!(logs.last(), Some(&"Log truncated".to_string()));
     }
+
+    #[test]
+    fn test_log_events_bytes_limit() {
+        let lc = LogCollector::default();
+        let event = TransactionLogEvent {
+            program_id: solana_sdk::pubkey::new_rand(),
+            data: vec![vec![1; 100], vec![2; 50]],
+        };
+        let event_len = bincode::serialized_size(&event).unwrap() as usize;
+
+        // Events outlive the truncation of the log messages...
+        for _i in 0..LOG_MESSAGES_BYTES_LIMIT * 2 {
+            lc.log("x");
+        }
+        // ...but are truncated themselves once they exceed their own limit
+        for _i in 0..LOG_EVENTS_BYTES_LIMIT {
+            lc.log_event(event.clone());
+        }
+
+        let (messages, events) = lc.into_messages_and_events();
+        assert_eq!(events.len(), (LOG_EVENTS_BYTES_LIMIT - 1) / event_len);
+        assert!(events.iter().all(|logged_event| *logged_event == event));
+        assert_eq!(
+            messages[messages.len() - 2..],
+            ["Log truncated", "Log events truncated"]
+        );
+    }
 }
@@ -1,13 +1,16 @@
 use crate::{
-    instruction_recorder::InstructionRecorder, log_collector::LogCollector,
-    native_loader::NativeLoader, rent_collector::RentCollector,
+    bank::{TransactionLogEvent, TransactionReturnData},
+    instruction_recorder::InstructionRecorder,
+    log_collector::LogCollector,
+    native_loader::NativeLoader,
+    rent_collector::RentCollector,
 };
 use log::*;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
     account::Account,
     clock::Epoch,
-    feature_set::{instructions_sysvar_enabled, FeatureSet},
+    feature_set::{instructions_sysvar_enabled, transaction_wide_compute_cap, FeatureSet},
     instruction::{CompiledInstruction, Instruction, InstructionError},
     keyed_account::{create_keyed_readonly_accounts, KeyedAccount},
     message::Message,
@@ -186,6 +189,11 @@ impl PreAccount {
 pub struct ThisComputeMeter {
     remaining: u64,
 }
+impl ThisComputeMeter {
+    pub fn new_ref(remaining: u64) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self { remaining }))
+    }
+}
 impl ComputeMeter for ThisComputeMeter {
     fn consume(&mut self, amount: u64) -> Result<(), InstructionError> {
         let exceeded = self.remaining < amount;
@@ -210,6 +218,8 @@ pub struct ThisInvokeContext<'a> {
     executors: Rc<RefCell<Executors>>,
     instruction_recorder: Option<InstructionRecorder>,
     feature_set: Arc<FeatureSet>,
+    sysvars: &'a [(Pubkey, Vec<u8>)],
+    return_data: Option<(Pubkey, Vec<u8>)>,
 }
 impl<'a> ThisInvokeContext<'a> {
     pub fn new(
@@ -219,9 +229,11 @@ impl<'a> ThisInvokeContext<'a> {
         programs: &'a [(Pubkey, ProcessInstructionWithContext)],
         log_collector: Option<Rc<LogCollector>>,
         bpf_compute_budget: BpfComputeBudget,
+        compute_meter: Rc<RefCell<dyn ComputeMeter>>,
         executors: Rc<RefCell<Executors>>,
         instruction_recorder: Option<InstructionRecorder>,
         feature_set: Arc<FeatureSet>,
+        sysvars: &'a [(Pubkey, Vec<u8>)],
     ) -> Self {
         let mut program_ids = Vec::with_capacity(bpf_compute_budget.max_invoke_depth);
         program_ids.push(*program_id);
@@ -232,12 +244,12 @@ impl<'a> ThisInvokeContext<'a> {
             programs,
             logger: Rc::new(RefCell::new(ThisLogger { log_collector })),
             bpf_compute_budget,
-            compute_meter: Rc::new(RefCell::new(ThisComputeMeter {
-                remaining: bpf_compute_budget.max_units,
-            })),
+            compute_meter,
             executors,
             instruction_recorder,
             feature_set,
+            sysvars,
+            return_data: None,
         }
     }
 }
@@ -308,6 +320,22 @@ impl<'a> InvokeContext for ThisInvokeContext<'a> {
     fn is_feature_active(&self, feature_id: &Pubkey) -> bool {
         self.feature_set.is_active(feature_id)
     }
+    fn get_sysvar_data(&self, id: &Pubkey) -> Option<&[u8]> {
+        self.sysvars
+            .iter()
+            .find(|(key, _)| key == id)
+            .map(|(_, data)| data.as_slice())
+    }
+    fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError> {
+        self.return_data = Some((*self.get_caller()?, data));
+        Ok(())
+    }
+    fn get_return_data(&self) -> (Pubkey, &[u8]) {
+        match &self.return_data {
+            Some((program_id, data)) => (*program_id, data),
+            None => (Pubkey::default(), &[]),
+        }
+    }
 }
 pub struct ThisLogger {
     log_collector: Option<Rc<LogCollector>>,
@@ -322,6 +350,14 @@ impl Logger for ThisLogger {
             log_collector.log(message);
         }
     }
+    fn log_event(&self, program_id: &Pubkey, data: &[&[u8]]) {
+        if let Some(log_collector) = &self.log_collector {
+            log_collector.log_event(TransactionLogEvent {
+                program_id: *program_id,
+                data: data.iter().map(|bytes| bytes.to_vec()).collect(),
+            });
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -530,6 +566,91 @@ impl MessageProcessor {
         }
     }
 
+    /// Process a cross-program instruction issued by a builtin program, e.g.
+    /// a loader creating an account through the system program.  Signers may
+    /// include program addresses derived from the caller's program id.
+    pub fn native_invoke(
+        invoke_context: &mut dyn InvokeContext,
+        instruction: Instruction,
+        keyed_accounts: &[&KeyedAccount],
+        signers_seeds: &[&[&[u8]]],
+    ) -> Result<(), InstructionError> {
+        let caller_program_id = *invoke_context.get_caller()?;
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller_program_id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| InstructionError::InvalidSeeds)?;
+
+        // Check for privilege escalation
+        for account in instruction.accounts.iter() {
+            let keyed_account = keyed_accounts
+                .iter()
+                .find(|keyed_account| &account.pubkey == keyed_account.unsigned_key())
+                .ok_or(InstructionError::MissingAccount)?;
+            // Readonly account cannot become writable
+            if account.is_writable && !keyed_account.is_writable() {
+                return Err(InstructionError::InvalidArgument);
+            }
+            // Signer must have signed the caller's instruction or be a derived signer
+            if account.is_signer
+                && keyed_account.signer_key().is_none()
+                && !signers.contains(&account.pubkey)
+            {
+                return Err(InstructionError::MissingRequiredSignature);
+            }
+        }
+
+        let message = Message::new(&[instruction.clone()], None);
+        let callee_program_id = instruction.program_id;
+        let mut accounts = Vec::with_capacity(message.account_keys.len());
+        let mut account_refs = Vec::with_capacity(message.account_keys.len());
+        for account_key in message.account_keys.iter() {
+            let keyed_account = keyed_accounts
+                .iter()
+                .find(|keyed_account| account_key == keyed_account.unsigned_key())
+                .ok_or(InstructionError::MissingAccount)?;
+            accounts.push(Rc::new(RefCell::new(
+                keyed_account.try_account_ref()?.clone(),
+            )));
+            account_refs.push(keyed_account);
+        }
+
+        invoke_context.record_instruction(&instruction);
+
+        // Process instruction
+        let program_account = keyed_accounts
+            .iter()
+            .find(|keyed_account| &callee_program_id == keyed_account.unsigned_key())
+            .ok_or(InstructionError::MissingAccount)?;
+        if !program_account.executable()? {
+            return Err(InstructionError::AccountNotExecutable);
+        }
+        let executable_accounts = vec![(
+            callee_program_id,
+            RefCell::new(program_account.try_account_ref()?.clone()),
+        )];
+        Self::process_cross_program_instruction(
+            &message,
+            &executable_accounts,
+            &accounts,
+            invoke_context,
+        )?;
+
+        // Copy results back to the caller's accounts
+        for (i, (account, keyed_account)) in accounts.iter().zip(account_refs).enumerate() {
+            let account = account.borrow();
+            if message.is_writable(i) && !account.executable {
+                let mut keyed_account = keyed_account.try_account_ref_mut()?;
+                keyed_account.lamports = account.lamports;
+                keyed_account.owner = account.owner;
+                keyed_account.data.clone_from(&account.data);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Record the initial state of the accounts so that they can be compared
     /// after the instruction is processed
     pub fn create_pre_accounts(
@@ -662,7 +783,11 @@ impl MessageProcessor {
         instruction_recorder: Option<InstructionRecorder>,
         instruction_index: usize,
         feature_set: Arc<FeatureSet>,
+        sysvars: &[(Pubkey, Vec<u8>)],
         bpf_compute_budget: BpfComputeBudget,
+        compute_meter: Rc<RefCell<dyn ComputeMeter>>,
+        compute_units_consumed: &mut u64,
+        return_data: &mut Option<TransactionReturnData>,
     ) -> Result<(), InstructionError> {
         // Fixup the special instructions key if present
         // before the account pre-values are taken care of
@@ -680,6 +805,7 @@ impl MessageProcessor {
         }
 
         let pre_accounts = Self::create_pre_accounts(message, instruction, accounts);
+        let pre_remaining_units = compute_meter.borrow().get_remaining();
         let mut invoke_context = ThisInvokeContext::new(
             instruction.program_id(&message.account_keys),
             rent_collector.rent,
@@ -687,13 +813,27 @@ impl MessageProcessor {
             &self.programs,
             log_collector,
             bpf_compute_budget,
+            compute_meter.clone(),
             executors,
             instruction_recorder,
             feature_set,
+            sysvars,
         );
         let keyed_accounts =
             Self::create_keyed_accounts(message, instruction, executable_accounts, accounts)?;
-        self.process_instruction(&keyed_accounts, &instruction.data, &mut invoke_context)?;
+        let process_result =
+            self.process_instruction(&keyed_accounts, &instruction.data, &mut invoke_context);
+        *compute_units_consumed +=
+            pre_remaining_units.saturating_sub(compute_meter.borrow().get_remaining());
+        // Only an instruction that set return data replaces that of the ones before it
+        if let Some((program_id, data)) = invoke_context.return_data.take() {
+            *return_data = if data.is_empty() {
+                None
+            } else {
+                Some(TransactionReturnData { program_id, data })
+            };
+        }
+        process_result?;
         Self::verify(
             message,
             instruction,
@@ -708,6 +848,10 @@ impl MessageProcessor {
     /// Process a message.
     /// This method calls each instruction in the message over the set of loaded Accounts
     /// The accounts are committed back to the bank only if every instruction succeeds
+    /// The compute units used by every instruction, successful or not, are added to
+    /// `compute_units_consumed`.  Once the transaction-wide compute cap is active all
+    /// instructions draw from a single meter of `bpf_compute_budget.max_units`, otherwise each
+    /// instruction gets a fresh meter
     #[allow(clippy::too_many_arguments)]
     pub fn process_message(
         &self,
@@ -719,12 +863,23 @@ impl MessageProcessor {
         executors: Rc<RefCell<Executors>>,
         instruction_recorders: Option<&[InstructionRecorder]>,
         feature_set: Arc<FeatureSet>,
+        sysvars: &[(Pubkey, Vec<u8>)],
         bpf_compute_budget: BpfComputeBudget,
+        compute_units_consumed: &mut u64,
+        return_data: &mut Option<TransactionReturnData>,
     ) -> Result<(), TransactionError> {
+        let transaction_wide_compute_cap =
+            feature_set.is_active(&transaction_wide_compute_cap::id());
+        let transaction_compute_meter = ThisComputeMeter::new_ref(bpf_compute_budget.max_units);
         for (instruction_index, instruction) in message.instructions.iter().enumerate() {
             let instruction_recorder = instruction_recorders
                 .as_ref()
                 .map(|recorders| recorders[instruction_index].clone());
+            let compute_meter = if transaction_wide_compute_cap {
+                transaction_compute_meter.clone()
+            } else {
+                ThisComputeMeter::new_ref(bpf_compute_budget.max_units)
+            };
             self.execute_instruction(
                 message,
                 instruction,
@@ -736,7 +891,11 @@ impl MessageProcessor {
                 instruction_recorder,
                 instruction_index,
                 feature_set.clone(),
+                sysvars,
                 bpf_compute_budget,
+                compute_meter,
+                compute_units_consumed,
+                return_data,
             )
             .map_err(|err| TransactionError::InstructionError(instruction_index as u8, err))?;
         }
@@ -788,9 +947,11 @@ mod tests {
             &[],
             None,
             BpfComputeBudget::default(),
+            ThisComputeMeter::new_ref(BpfComputeBudget::default().max_units),
             Rc::new(RefCell::new(Executors::default())),
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
         );
 
         // Check call depth increases and has a limit
@@ -1335,7 +1496,10 @@ mod tests {
             executors.clone(),
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
             BpfComputeBudget::new(&FeatureSet::all_enabled()),
+            &mut 0,
+            &mut None,
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].borrow().lamports, 100);
@@ -1359,7 +1523,10 @@ mod tests {
             executors.clone(),
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
             BpfComputeBudget::new(&FeatureSet::all_enabled()),
+            &mut 0,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -1387,7 +1554,10 @@ mod tests {
             executors,
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
             BpfComputeBudget::new(&FeatureSet::all_enabled()),
+            &mut 0,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -1398,6 +1568,160 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_process_message_transaction_wide_compute_meter() {
+        fn mock_process_instruction(
+            _program_id: &Pubkey,
+            _keyed_accounts: &[KeyedAccount],
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            let units: u64 = bincode::deserialize(data).unwrap();
+            invoke_context
+                .get_compute_meter()
+                .borrow_mut()
+                .consume(units)
+        }
+
+        let mock_program_id = Pubkey::new(&[2u8; 32]);
+        let rent_collector = RentCollector::default();
+        let mut message_processor = MessageProcessor::default();
+        message_processor.add_program(mock_program_id, mock_process_instruction);
+
+        let accounts = vec![Account::new_ref(100, 1, &mock_program_id)];
+        let loaders: Vec<Vec<(Pubkey, RefCell<Account>)>> = (0..2)
+            .map(|_| {
+                let account = RefCell::new(create_loadable_account("mock_program"));
+                vec![(mock_program_id, account)]
+            })
+            .collect();
+
+        let from_pubkey = solana_sdk::pubkey::new_rand();
+        let account_metas = vec![AccountMeta::new(from_pubkey, true)];
+        let message = Message::new(
+            &[
+                Instruction::new(mock_program_id, &60_000u64, account_metas.clone()),
+                Instruction::new(mock_program_id, &60_000u64, account_metas),
+            ],
+            Some(&from_pubkey),
+        );
+        let bpf_compute_budget = BpfComputeBudget {
+            max_units: 100_000,
+            ..BpfComputeBudget::default()
+        };
+
+        // Each instruction gets its own meter
+        let mut compute_units_consumed = 0;
+        let result = message_processor.process_message(
+            &message,
+            &loaders,
+            &accounts,
+            &rent_collector,
+            None,
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::default()),
+            &[],
+            bpf_compute_budget,
+            &mut compute_units_consumed,
+            &mut None,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(compute_units_consumed, 120_000);
+
+        // All instructions share the transaction's meter
+        let mut compute_units_consumed = 0;
+        let result = message_processor.process_message(
+            &message,
+            &loaders,
+            &accounts,
+            &rent_collector,
+            None,
+            Rc::new(RefCell::new(Executors::default())),
+            None,
+            Arc::new(FeatureSet::all_enabled()),
+            &[],
+            bpf_compute_budget,
+            &mut compute_units_consumed,
+            &mut None,
+        );
+        assert_eq!(
+            result,
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::ComputationalBudgetExceeded
+            ))
+        );
+        assert_eq!(compute_units_consumed, 100_000);
+    }
+
+    #[test]
+    fn test_process_message_return_data() {
+        fn mock_process_instruction(
+            _program_id: &Pubkey,
+            _keyed_accounts: &[KeyedAccount],
+            data: &[u8],
+            invoke_context: &mut dyn InvokeContext,
+        ) -> Result<(), InstructionError> {
+            let return_data: Option<Vec<u8>> = bincode::deserialize(data).unwrap();
+            if let Some(return_data) = return_data {
+                invoke_context.set_return_data(return_data)?;
+            }
+            Ok(())
+        }
+
+        let mock_program_id = Pubkey::new(&[2u8; 32]);
+        let rent_collector = RentCollector::default();
+        let mut message_processor = MessageProcessor::default();
+        message_processor.add_program(mock_program_id, mock_process_instruction);
+
+        let accounts = vec![Account::new_ref(100, 1, &mock_program_id)];
+        let loaders: Vec<Vec<(Pubkey, RefCell<Account>)>> = (0..2)
+            .map(|_| {
+                let account = RefCell::new(create_loadable_account("mock_program"));
+                vec![(mock_program_id, account)]
+            })
+            .collect();
+
+        let from_pubkey = solana_sdk::pubkey::new_rand();
+        let account_metas = vec![AccountMeta::new(from_pubkey, true)];
+        let process_message = |return_data: &[Option<Vec<u8>>]| {
+            let instructions: Vec<_> = return_data
+                .iter()
+                .map(|data| Instruction::new(mock_program_id, data, account_metas.clone()))
+                .collect();
+            let message = Message::new(&instructions, Some(&from_pubkey));
+            let mut return_data = None;
+            let result = message_processor.process_message(
+                &message,
+                &loaders,
+                &accounts,
+                &rent_collector,
+                None,
+                Rc::new(RefCell::new(Executors::default())),
+                None,
+                Arc::new(FeatureSet::all_enabled()),
+                &[],
+                BpfComputeBudget::default(),
+                &mut 0,
+                &mut return_data,
+            );
+            assert_eq!(result, Ok(()));
+            return_data
+        };
+        let expected = Some(TransactionReturnData {
+            program_id: mock_program_id,
+            data: vec![1, 2, 3],
+        });
+
+        // An instruction that sets no return data keeps that of the one before it
+        assert_eq!(process_message(&[Some(vec![1, 2, 3]), None]), expected);
+        assert_eq!(process_message(&[None, Some(vec![1, 2, 3])]), expected);
+
+        // Setting empty return data clears it
+        assert_eq!(process_message(&[Some(vec![1, 2, 3]), Some(vec![])]), None);
+    }
+
     #[test]
     fn test_process_message_duplicate_accounts() {
         #[derive(Serialize, Deserialize)]
@@ -1499,7 +1823,10 @@ mod tests {
             executors.clone(),
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
             BpfComputeBudget::new(&FeatureSet::all_enabled()),
+            &mut 0,
+            &mut None,
         );
         assert_eq!(
             result,
@@ -1527,7 +1854,10 @@ mod tests {
             executors.clone(),
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
             BpfComputeBudget::new(&FeatureSet::all_enabled()),
+            &mut 0,
+            &mut None,
         );
         assert_eq!(result, Ok(()));
 
@@ -1552,7 +1882,10 @@ mod tests {
             executors,
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
             BpfComputeBudget::new(&FeatureSet::all_enabled()),
+            &mut 0,
+            &mut None,
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].borrow().lamports, 80);
@@ -1635,9 +1968,11 @@ mod tests {
             programs.as_slice(),
             None,
             BpfComputeBudget::default(),
+            ThisComputeMeter::new_ref(BpfComputeBudget::default().max_units),
             Rc::new(RefCell::new(Executors::default())),
             None,
             Arc::new(FeatureSet::all_enabled()),
+            &[],
         );
         let metas = vec![
             AccountMeta::new(owned_key, false),
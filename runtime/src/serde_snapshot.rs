@@ -157,6 +157,31 @@ where
     })
 }
 
+// Read just enough of a serialized bank to recover the slot and accounts hash it was
+// snapshotted at, without touching any of the account storages
+pub(crate) fn snapshot_hash_from_stream<R>(
+    serde_style: SerdeStyle,
+    stream: &mut BufReader<R>,
+) -> std::result::Result<(Slot, Hash), Error>
+where
+    R: Read,
+{
+    macro_rules! INTO {
+        ($x:ident) => {{
+            let (bank_fields, accounts_db_fields) = $x::deserialize_bank_fields(stream)?;
+            let AccountsDbFields(_storage, _version, _slot, bank_hash_info) = accounts_db_fields;
+            Ok((bank_fields.slot, bank_hash_info.snapshot_hash))
+        }};
+    }
+    match serde_style {
+        SerdeStyle::NEWER => INTO!(TypeContextFuture),
+    }
+    .map_err(|err: Error| {
+        warn!("snapshot_hash_from_stream error: {:?}", err);
+        err
+    })
+}
+
 pub(crate) fn bank_to_stream<W>(
     serde_style: SerdeStyle,
     stream: &mut BufWriter<W>,
@@ -3,7 +3,8 @@ use crate::{
     bank_forks::CompressionType,
     hardened_unpack::{unpack_snapshot, UnpackError},
     serde_snapshot::{
-        bank_from_stream, bank_to_stream, SerdeStyle, SnapshotStorage, SnapshotStorages,
+        bank_from_stream, bank_to_stream, snapshot_hash_from_stream, SerdeStyle, SnapshotStorage,
+        SnapshotStorages,
     },
     snapshot_package::{AccountsPackage, AccountsPackageSendError, AccountsPackageSender},
     status_cache::MAX_CACHE_ENTRIES,
@@ -128,6 +129,12 @@ pub enum SnapshotError {
 
     #[error("accounts package send error")]
     AccountsPackageSendError(#[from] AccountsPackageSendError),
+
+    #[error("no bank snapshot found in archive")]
+    NoBankSnapshotInArchive,
+
+    #[error("snapshot hash mismatch: expected {0:?}, archive contains {1:?}")]
+    MismatchedSnapshotHash((Slot, Hash), (Slot, Hash)),
 }
 pub type Result<T> = std::result::Result<T, SnapshotError>;
 
@@ -753,6 +760,92 @@ pub fn untar_snapshot_in<P: AsRef<Path>, Q: AsRef<Path>>(
     Ok(())
 }
 
+/// Scan a snapshot archive for its bank snapshot and return the slot and accounts hash
+/// recorded in it.  Only the bank snapshot file is deserialized, the account storages are
+/// skipped over, so this is considerably cheaper than `bank_from_archive()`
+pub fn snapshot_hash_of_archive<P: AsRef<Path>>(
+    snapshot_tar: P,
+    compression: CompressionType,
+) -> Result<(Slot, Hash)> {
+    let tar_name = File::open(&snapshot_tar)?;
+    let tar: Box<dyn Read> = match compression {
+        CompressionType::Bzip2 => Box::new(BzDecoder::new(BufReader::new(tar_name))),
+        CompressionType::Gzip => Box::new(GzDecoder::new(BufReader::new(tar_name))),
+        CompressionType::Zstd => {
+            Box::new(zstd::stream::read::Decoder::new(BufReader::new(tar_name))?)
+        }
+        CompressionType::NoCompression => Box::new(BufReader::new(tar_name)),
+    };
+    let mut archive = Archive::new(tar);
+
+    let mut snapshot_version = None;
+    let mut bank_snapshot = None;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let parts: Vec<_> = path
+            .iter()
+            .map(|p| p.to_str().unwrap_or_default())
+            .collect();
+        match parts.as_slice() {
+            [TAR_VERSION_FILE] => {
+                let mut version = String::new();
+                entry.read_to_string(&mut version)?;
+                snapshot_version = Some(version);
+            }
+            [TAR_SNAPSHOTS_DIR, dir, file] if dir == file && file.parse::<Slot>().is_ok() => {
+                // The version file is archived last, so hold onto the bank snapshot until the
+                // serialization format is known
+                let mut data = vec![];
+                entry
+                    .take(MAX_SNAPSHOT_DATA_FILE_SIZE)
+                    .read_to_end(&mut data)?;
+                bank_snapshot = Some(data);
+            }
+            _ => {}
+        }
+    }
+
+    let snapshot_version = snapshot_version
+        .ok_or_else(|| get_io_error("No version file found in snapshot archive"))?;
+    let snapshot_version_enum = SnapshotVersion::maybe_from_string(snapshot_version.trim())
+        .ok_or_else(|| {
+            get_io_error(&format!(
+                "unsupported snapshot version: {}",
+                snapshot_version
+            ))
+        })?;
+    let bank_snapshot = bank_snapshot.ok_or(SnapshotError::NoBankSnapshotInArchive)?;
+    let serde_style = match snapshot_version_enum {
+        SnapshotVersion::V1_2_0 => SerdeStyle::NEWER,
+    };
+    Ok(snapshot_hash_from_stream(
+        serde_style,
+        &mut BufReader::new(bank_snapshot.as_slice()),
+    )?)
+}
+
+/// Check that a snapshot archive contains the bank snapshot for `expected_snapshot_hash`,
+/// typically the `(slot, hash)` pair advertised by a peer in gossip
+pub fn verify_snapshot_archive_hash<P: AsRef<Path>>(
+    snapshot_tar: P,
+    compression: CompressionType,
+    expected_snapshot_hash: &(Slot, Hash),
+) -> Result<()> {
+    let mut measure = Measure::start("snapshot archive hash verify");
+    let snapshot_hash = snapshot_hash_of_archive(snapshot_tar, compression)?;
+    measure.stop();
+    info!("{}", measure);
+
+    if snapshot_hash != *expected_snapshot_hash {
+        return Err(SnapshotError::MismatchedSnapshotHash(
+            *expected_snapshot_hash,
+            snapshot_hash,
+        ));
+    }
+    Ok(())
+}
+
 fn rebuild_bank_from_snapshots<P>(
     snapshot_version: &str,
     account_paths: &[PathBuf],
@@ -1032,4 +1125,55 @@ mod tests {
 
         assert!(snapshot_hash_of("invalid").is_none());
     }
+
+    #[test]
+    fn test_verify_snapshot_archive_hash() {
+        let genesis_config = crate::genesis_utils::create_genesis_config(500).genesis_config;
+        let bank = Bank::new(&genesis_config);
+        bank.squash();
+        bank.update_accounts_hash();
+
+        let snapshot_dir = tempfile::TempDir::new().unwrap();
+        let output_dir = tempfile::TempDir::new().unwrap();
+        let storages = bank.get_snapshot_storages();
+        let slot_snapshot_paths = add_snapshot(
+            snapshot_dir.path(),
+            &bank,
+            &storages,
+            SnapshotVersion::default(),
+        )
+        .unwrap();
+        let package = package_snapshot(
+            &bank,
+            &slot_snapshot_paths,
+            snapshot_dir.path(),
+            vec![],
+            output_dir.path(),
+            storages,
+            CompressionType::Gzip,
+            SnapshotVersion::default(),
+        )
+        .unwrap();
+        archive_snapshot_package(&package).unwrap();
+
+        let snapshot_hash = (bank.slot(), bank.get_accounts_hash());
+        assert_eq!(
+            snapshot_hash_of_archive(&package.tar_output_file, CompressionType::Gzip).unwrap(),
+            snapshot_hash
+        );
+        assert!(verify_snapshot_archive_hash(
+            &package.tar_output_file,
+            CompressionType::Gzip,
+            &snapshot_hash
+        )
+        .is_ok());
+        assert_matches!(
+            verify_snapshot_archive_hash(
+                &package.tar_output_file,
+                CompressionType::Gzip,
+                &(bank.slot(), Hash::default())
+            ),
+            Err(SnapshotError::MismatchedSnapshotHash(_, _))
+        );
+    }
 }
@@ -419,6 +419,15 @@ typedef struct {
   uint64_t len; /** number of bytes*/
 } SolBytes;
 
+/**
+ * Logs a list of byte arrays as a single structured event
+ *
+ * @param bytes Array of byte arrays
+ * @param bytes_len Number of byte arrays
+ */
+void sol_log_data_(const SolBytes *bytes, uint64_t bytes_len);
+#define sol_log_data sol_log_data_
+
 /**
  * Length of a sha256 hash result
  */
@@ -639,6 +648,16 @@ void sol_log_pubkey(const SolPubkey *pubkey) {
 void sol_log_compute_units_() {
   printf("Program consumption: __ units remaining\n");
 }
+void sol_log_data_(const SolBytes *bytes, uint64_t bytes_len) {
+  printf("Program data:");
+  for (uint64_t i = 0; i < bytes_len; i++) {
+    printf(" ");
+    for (uint64_t j = 0; j < bytes[i].len; j++) {
+      printf("%02x", bytes[i].addr[j]);
+    }
+  }
+  printf("\n");
+}
 void sol_panic_(const char *file, uint64_t len, uint64_t line, uint64_t column) {
   printf("Panic in %s at %d:%d\n", file, line, column);
   abort();
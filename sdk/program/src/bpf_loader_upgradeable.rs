@@ -0,0 +1,268 @@
+//! @brief An Upgradeable Solana BPF loader.
+//!
+//! The upgradeable BPF loader is responsible for deploying, upgrading, and
+//! executing BPF programs.  The upgradeable loader allows a program's authority
+//! to update the program at any time.  Unlike with the other loaders, a
+//! program deployed here is not immutable once it is on-chain, so care should
+//! be taken before executing upgradeable programs which still have an
+//! upgrade authority.  For more information refer to
+//! `loader_upgradeable_instruction.rs`
+
+use crate::{
+    instruction::{AccountMeta, Instruction},
+    loader_upgradeable_instruction::UpgradeableLoaderInstruction,
+    pubkey::Pubkey,
+    system_instruction, system_program, sysvar,
+};
+
+crate::declare_id!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+/// Upgradeable loader account states
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum UpgradeableLoaderState {
+    /// Account is not initialized.
+    Uninitialized,
+    /// A Buffer account.
+    Buffer {
+        /// Authority address
+        authority_address: Option<Pubkey>,
+        // The raw program data follows this serialized structure in the
+        // account's data.
+    },
+    /// A Program account.
+    Program {
+        /// Address of the ProgramData account.
+        programdata_address: Pubkey,
+    },
+    /// A ProgramData account.
+    ProgramData {
+        /// Slot that the program was last modified.
+        slot: u64,
+        /// Address of the Program's upgrade authority.
+        upgrade_authority_address: Option<Pubkey>,
+        // The raw program data follows this serialized structure in the
+        // account's data.
+    },
+}
+impl UpgradeableLoaderState {
+    /// Length of an uninitialized account
+    pub fn uninitialized_len() -> usize {
+        bincode::serialized_size(&Self::Uninitialized).unwrap() as usize
+    }
+
+    /// Length of a Buffer account's metadata, which precedes the program data
+    pub fn buffer_data_offset() -> usize {
+        bincode::serialized_size(&Self::Buffer {
+            authority_address: Some(Pubkey::default()),
+        })
+        .unwrap() as usize
+    }
+
+    /// Length of a Buffer account holding `program_len` bytes of program data
+    pub fn buffer_len(program_len: usize) -> usize {
+        Self::buffer_data_offset().saturating_add(program_len)
+    }
+
+    /// Length of a Program account
+    pub fn program_len() -> usize {
+        bincode::serialized_size(&Self::Program {
+            programdata_address: Pubkey::default(),
+        })
+        .unwrap() as usize
+    }
+
+    /// Length of a ProgramData account's metadata, which precedes the program data
+    pub fn programdata_data_offset() -> usize {
+        bincode::serialized_size(&Self::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(Pubkey::default()),
+        })
+        .unwrap() as usize
+    }
+
+    /// Length of a ProgramData account holding up to `program_len` bytes of
+    /// program data
+    pub fn programdata_len(program_len: usize) -> usize {
+        Self::programdata_data_offset().saturating_add(program_len)
+    }
+}
+
+/// Returns the address of the ProgramData account of a program
+pub fn get_program_data_address(program_address: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_address.as_ref()], &id()).0
+}
+
+/// Returns the instructions required to initialize a Buffer account.
+pub fn create_buffer(
+    payer_address: &Pubkey,
+    buffer_address: &Pubkey,
+    authority_address: &Pubkey,
+    lamports: u64,
+    program_len: usize,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::create_account(
+            payer_address,
+            buffer_address,
+            lamports,
+            UpgradeableLoaderState::buffer_len(program_len) as u64,
+            &id(),
+        ),
+        Instruction::new(
+            id(),
+            &UpgradeableLoaderInstruction::InitializeBuffer,
+            vec![
+                AccountMeta::new(*buffer_address, false),
+                AccountMeta::new_readonly(*authority_address, false),
+            ],
+        ),
+    ]
+}
+
+/// Returns the instructions required to write a chunk of program data to a
+/// buffer account.
+pub fn write(
+    buffer_address: &Pubkey,
+    authority_address: &Pubkey,
+    offset: u32,
+    bytes: Vec<u8>,
+) -> Instruction {
+    Instruction::new(
+        id(),
+        &UpgradeableLoaderInstruction::Write { offset, bytes },
+        vec![
+            AccountMeta::new(*buffer_address, false),
+            AccountMeta::new_readonly(*authority_address, true),
+        ],
+    )
+}
+
+/// Returns the instructions required to deploy a program with a specified
+/// maximum program length.  The maximum length must be large enough to
+/// accommodate any future upgrades.
+pub fn deploy_with_max_program_len(
+    payer_address: &Pubkey,
+    program_address: &Pubkey,
+    buffer_address: &Pubkey,
+    upgrade_authority_address: &Pubkey,
+    program_lamports: u64,
+    max_data_len: usize,
+) -> Vec<Instruction> {
+    let programdata_address = get_program_data_address(program_address);
+    vec![
+        system_instruction::create_account(
+            payer_address,
+            program_address,
+            program_lamports,
+            UpgradeableLoaderState::program_len() as u64,
+            &id(),
+        ),
+        Instruction::new(
+            id(),
+            &UpgradeableLoaderInstruction::DeployWithMaxDataLen { max_data_len },
+            vec![
+                AccountMeta::new(*payer_address, true),
+                AccountMeta::new(programdata_address, false),
+                AccountMeta::new(*program_address, false),
+                AccountMeta::new(*buffer_address, false),
+                AccountMeta::new_readonly(sysvar::rent::id(), false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(*upgrade_authority_address, true),
+            ],
+        ),
+    ]
+}
+
+/// Returns the instruction required to upgrade a program.
+pub fn upgrade(
+    program_address: &Pubkey,
+    buffer_address: &Pubkey,
+    authority_address: &Pubkey,
+    spill_address: &Pubkey,
+) -> Instruction {
+    let programdata_address = get_program_data_address(program_address);
+    Instruction::new(
+        id(),
+        &UpgradeableLoaderInstruction::Upgrade,
+        vec![
+            AccountMeta::new(programdata_address, false),
+            AccountMeta::new(*program_address, false),
+            AccountMeta::new(*buffer_address, false),
+            AccountMeta::new(*spill_address, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*authority_address, true),
+        ],
+    )
+}
+
+/// Returns the instruction required to set a buffer's authority.
+pub fn set_buffer_authority(
+    buffer_address: &Pubkey,
+    current_authority_address: &Pubkey,
+    new_authority_address: &Pubkey,
+) -> Instruction {
+    Instruction::new(
+        id(),
+        &UpgradeableLoaderInstruction::SetAuthority,
+        vec![
+            AccountMeta::new(*buffer_address, false),
+            AccountMeta::new_readonly(*current_authority_address, true),
+            AccountMeta::new_readonly(*new_authority_address, false),
+        ],
+    )
+}
+
+/// Returns the instruction required to set a program's upgrade authority, or
+/// to make the program immutable if `new_authority_address` is `None`.
+pub fn set_upgrade_authority(
+    program_address: &Pubkey,
+    current_authority_address: &Pubkey,
+    new_authority_address: Option<&Pubkey>,
+) -> Instruction {
+    let programdata_address = get_program_data_address(program_address);
+
+    let mut metas = vec![
+        AccountMeta::new(programdata_address, false),
+        AccountMeta::new_readonly(*current_authority_address, true),
+    ];
+    if let Some(address) = new_authority_address {
+        metas.push(AccountMeta::new_readonly(*address, false));
+    }
+    Instruction::new(id(), &UpgradeableLoaderInstruction::SetAuthority, metas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_lengths() {
+        assert_eq!(4, UpgradeableLoaderState::uninitialized_len());
+        assert_eq!(36, UpgradeableLoaderState::program_len());
+        assert_eq!(37, UpgradeableLoaderState::buffer_data_offset());
+        assert_eq!(37 + 42, UpgradeableLoaderState::buffer_len(42));
+        assert_eq!(45, UpgradeableLoaderState::programdata_data_offset());
+        assert_eq!(45 + 42, UpgradeableLoaderState::programdata_len(42));
+    }
+
+    #[test]
+    fn test_program_data_address() {
+        let program_address = Pubkey::new_unique();
+        let programdata_address = get_program_data_address(&program_address);
+        assert_ne!(programdata_address, program_address);
+        assert_eq!(
+            get_program_data_address(&program_address),
+            programdata_address
+        );
+
+        let instruction = upgrade(
+            &program_address,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        );
+        assert_eq!(instruction.accounts[0].pubkey, programdata_address);
+    }
+}
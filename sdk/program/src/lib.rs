@@ -7,8 +7,10 @@ extern crate self as solana_program;
 pub mod account_info;
 pub mod bpf_loader;
 pub mod bpf_loader_deprecated;
+pub mod bpf_loader_upgradeable;
 pub mod clock;
 pub mod decode_error;
+pub mod ed25519_program;
 pub mod entrypoint;
 pub mod entrypoint_deprecated;
 pub mod epoch_schedule;
@@ -18,6 +20,7 @@ pub mod hash;
 pub mod incinerator;
 pub mod instruction;
 pub mod loader_instruction;
+pub mod loader_upgradeable_instruction;
 pub mod log;
 pub mod message;
 pub mod native_token;
@@ -31,6 +34,7 @@ pub mod pubkey;
 pub mod rent;
 pub mod sanitize;
 pub mod secp256k1_program;
+pub mod serde_varint;
 pub mod serialize_utils;
 pub mod short_vec;
 pub mod slot_hashes;
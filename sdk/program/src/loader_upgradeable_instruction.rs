@@ -0,0 +1,120 @@
+//! Instructions for the upgradeable BPF loader
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum UpgradeableLoaderInstruction {
+    /// Initialize a Buffer account.
+    ///
+    /// A Buffer account is an intermediary that once fully populated is used
+    /// with the `DeployWithMaxDataLen` instruction to populate the program's
+    /// ProgramData account, or with the `Upgrade` instruction to replace it.
+    ///
+    /// The `InitializeBuffer` instruction requires no signers and MUST be
+    /// included within the same Transaction as the system program's
+    /// `CreateAccount` instruction that creates the account being initialized.
+    /// Otherwise another party may initialize the account.
+    ///
+    /// # Account references
+    ///   0. [WRITE] Buffer account to initialize.
+    ///   1. [] Buffer authority
+    InitializeBuffer,
+
+    /// Write program data into a Buffer account.
+    ///
+    /// # Account references
+    ///   0. [WRITE] Buffer account to write program data to.
+    ///   1. [SIGNER] Buffer authority
+    Write {
+        /// Offset at which to write the given bytes.
+        offset: u32,
+        /// Serialized program data
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+    },
+
+    /// Deploy an executable program.
+    ///
+    /// A program consists of a Program and ProgramData account pair.
+    ///   - The Program account's address will serve as the program id for any
+    ///     instructions that execute this program.
+    ///   - The ProgramData account will remain mutable by the loader only and
+    ///     holds the program data and authority information.  The ProgramData
+    ///     account's address is derived from the Program account's address and
+    ///     created by the DeployWithMaxDataLen instruction.
+    ///
+    /// The ProgramData address is derived from the Program account's address
+    /// as follows:
+    ///
+    /// ```
+    /// # use solana_program::pubkey::Pubkey;
+    /// # use solana_program::bpf_loader_upgradeable;
+    /// # let program_address = Pubkey::default();
+    /// let (program_data_address, _) = Pubkey::find_program_address(
+    ///     &[program_address.as_ref()],
+    ///     &bpf_loader_upgradeable::id(),
+    /// );
+    /// ```
+    ///
+    /// The `DeployWithMaxDataLen` instruction does not require the Program
+    /// account to be a signer and therefore MUST be included within the same
+    /// Transaction as the system program's `CreateAccount` instruction that
+    /// creates the Program account.  Otherwise another party may initialize the
+    /// account.
+    ///
+    /// The Buffer account's lamports are drained to the payer, closing the
+    /// Buffer account.  The signing buffer authority becomes the program's
+    /// upgrade authority.
+    ///
+    /// # Account references
+    ///   0. [SIGNER, WRITE] The payer account that will pay to create the
+    ///      ProgramData account.
+    ///   1. [WRITE] The uninitialized ProgramData account.
+    ///   2. [WRITE] The uninitialized Program account.
+    ///   3. [WRITE] The Buffer account where the program data has been
+    ///      written.
+    ///   4. [] Rent sysvar.
+    ///   5. [] Clock sysvar.
+    ///   6. [] System program (`solana_sdk::system_program::id()`).
+    ///   7. [SIGNER] The buffer authority, which becomes the upgrade authority.
+    DeployWithMaxDataLen {
+        /// Maximum length that the program can be upgraded to.
+        max_data_len: usize,
+    },
+
+    /// Upgrade a program.
+    ///
+    /// A program can be updated as long as the program's authority has not
+    /// been set to `None`.
+    ///
+    /// The program data is replaced by the contents of the Buffer account,
+    /// which must fit within the ProgramData account's maximum data length.
+    /// The Buffer account's lamports, less any needed to keep the ProgramData
+    /// account rent-exempt, are transferred to the spill account, leaving the
+    /// Buffer account balance at zero.
+    ///
+    /// The Program account is write-locked so that no transaction invoking
+    /// the program runs alongside the upgrade of its ProgramData account.
+    ///
+    /// # Account references
+    ///   0. [WRITE] The ProgramData account.
+    ///   1. [WRITE] The Program account.
+    ///   2. [WRITE] The Buffer account where the program data has been
+    ///      written.  The buffer account's authority must match the program's
+    ///      authority
+    ///   3. [WRITE] The spill account.
+    ///   4. [] Rent sysvar.
+    ///   5. [] Clock sysvar.
+    ///   6. [SIGNER] The program's authority.
+    Upgrade,
+
+    /// Set a new authority that is allowed to write the buffer or upgrade the
+    /// program.  To permanently disable program updates omit the new
+    /// authority.  A Buffer account always requires a new authority.
+    ///
+    /// # Account references
+    ///   0. `[WRITE]` The Buffer or ProgramData account to change the
+    ///      authority of.
+    ///   1. `[SIGNER]` The current authority.
+    ///   2. `[]` The new authority, optional for a ProgramData account, if
+    ///      omitted then the program will not be upgradeable.
+    SetAuthority,
+}
@@ -63,6 +63,28 @@ extern "C" {
     fn sol_log_64_(arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64);
 }
 
+/// Logs a list of byte slices as a single structured event
+///
+/// Indexers receive the event decoded from the transaction status rather than having to parse
+/// free-form log messages.
+///
+/// @param data - The slices to log
+#[inline]
+pub fn sol_log_data(data: &[&[u8]]) {
+    #[cfg(target_arch = "bpf")]
+    unsafe {
+        sol_log_data_(data as *const _ as *const u8, data.len() as u64);
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_log_data(data);
+}
+
+#[cfg(target_arch = "bpf")]
+extern "C" {
+    fn sol_log_data_(data: *const u8, data_len: u64);
+}
+
 /// Prints the hexadecimal representation of a slice
 ///
 /// @param slice - The array to print
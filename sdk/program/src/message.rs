@@ -423,6 +423,494 @@ impl Message {
     }
 }
 
+/// Bit set in the first byte of a serialized message to mark it as versioned.  Legacy messages
+/// start with `num_required_signatures`, which can never reach this value.
+pub const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+/// Maximum number of accounts a transaction may reference, including the ones loaded from
+/// address lookup tables, so that every account stays addressable by a `u8` index
+pub const MAX_ACCOUNTS: usize = 256;
+
+/// Reference to accounts stored in an on-chain address lookup table
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageAddressTableLookup {
+    /// Address of the lookup table account
+    pub account_key: Pubkey,
+    /// Indexes of the table's addresses to load as writable accounts
+    #[serde(with = "short_vec")]
+    pub writable_indexes: Vec<u8>,
+    /// Indexes of the table's addresses to load as read-only accounts
+    #[serde(with = "short_vec")]
+    pub readonly_indexes: Vec<u8>,
+}
+
+/// Addresses loaded from the lookup tables of a `MessageV0`, in the order the message
+/// references them
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct LoadedAddresses {
+    pub writable: Vec<Pubkey>,
+    pub readonly: Vec<Pubkey>,
+}
+
+impl LoadedAddresses {
+    pub fn len(&self) -> usize {
+        self.writable.len() + self.readonly.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Version 0 of the message format.  Beyond the `account_keys` stored in the message itself,
+/// instructions may reference accounts loaded from address lookup tables.  Loaded accounts are
+/// indexed after `account_keys`: first every writable loaded address, then every read-only one,
+/// each in the order of `address_table_lookups`.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageV0 {
+    /// The message header, identifying signed and read-only `account_keys`
+    pub header: MessageHeader,
+
+    /// The account keys stored in the message itself
+    #[serde(with = "short_vec")]
+    pub account_keys: Vec<Pubkey>,
+
+    /// The id of a recent ledger entry.
+    pub recent_blockhash: Hash,
+
+    /// Programs that will be executed in sequence and committed in one atomic transaction if all
+    /// succeed.
+    #[serde(with = "short_vec")]
+    pub instructions: Vec<CompiledInstruction>,
+
+    /// Accounts to load from address lookup tables
+    #[serde(with = "short_vec")]
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
+}
+
+impl Sanitize for MessageV0 {
+    fn sanitize(&self) -> std::result::Result<(), SanitizeError> {
+        let num_static_keys = self.account_keys.len();
+
+        // signing area and read-only non-signing area should not overlap
+        if self.header.num_required_signatures as usize
+            + self.header.num_readonly_unsigned_accounts as usize
+            > num_static_keys
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        // there should be at least 1 RW fee-payer account.
+        if self.header.num_readonly_signed_accounts >= self.header.num_required_signatures {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        let mut num_loaded_keys = 0;
+        for lookup in &self.address_table_lookups {
+            let num_lookup_keys = lookup.writable_indexes.len() + lookup.readonly_indexes.len();
+            // each lookup table must be used to load at least one account
+            if num_lookup_keys == 0 {
+                return Err(SanitizeError::InvalidValue);
+            }
+            num_loaded_keys += num_lookup_keys;
+        }
+        let num_keys = num_static_keys + num_loaded_keys;
+        if num_keys > MAX_ACCOUNTS {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        for ci in &self.instructions {
+            // Programs must be stored in the message itself
+            if ci.program_id_index as usize >= num_static_keys {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            // A program cannot be a payer.
+            if ci.program_id_index == 0 {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            for ai in &ci.accounts {
+                if *ai as usize >= num_keys {
+                    return Err(SanitizeError::IndexOutOfBounds);
+                }
+            }
+        }
+        self.account_keys.sanitize()?;
+        self.recent_blockhash.sanitize()?;
+        self.instructions.sanitize()?;
+        Ok(())
+    }
+}
+
+impl MessageV0 {
+    /// Create a message which loads every unsigned, non-program account it can from
+    /// `address_lookup_tables`, given as pairs of table address and table contents
+    pub fn new_with_lookup_tables(
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        address_lookup_tables: &[(Pubkey, Vec<Pubkey>)],
+    ) -> Self {
+        let message = Message::new(instructions, payer);
+        let program_ids = message.program_ids();
+        let num_signed_keys = message.header.num_required_signatures as usize;
+
+        let mut account_keys = vec![];
+        let mut num_readonly_unsigned_accounts = 0;
+        let mut address_table_lookups: Vec<MessageAddressTableLookup> = vec![];
+        for (i, key) in message.account_keys.iter().enumerate() {
+            let table = if i < num_signed_keys || program_ids.contains(&key) {
+                None
+            } else {
+                address_lookup_tables
+                    .iter()
+                    .find_map(|(table_key, addresses)| {
+                        addresses
+                            .iter()
+                            .position(|address| address == key)
+                            .map(|index| (table_key, index as u8))
+                    })
+            };
+            match table {
+                Some((table_key, index)) => {
+                    let lookup = match address_table_lookups
+                        .iter_mut()
+                        .position(|lookup| lookup.account_key == *table_key)
+                    {
+                        Some(position) => &mut address_table_lookups[position],
+                        None => {
+                            address_table_lookups.push(MessageAddressTableLookup {
+                                account_key: *table_key,
+                                ..MessageAddressTableLookup::default()
+                            });
+                            address_table_lookups.last_mut().unwrap()
+                        }
+                    };
+                    if message.is_writable(i) {
+                        lookup.writable_indexes.push(index);
+                    } else {
+                        lookup.readonly_indexes.push(index);
+                    }
+                }
+                None => {
+                    if i >= num_signed_keys && !message.is_writable(i) {
+                        num_readonly_unsigned_accounts += 1;
+                    }
+                    account_keys.push(*key);
+                }
+            }
+        }
+
+        let loaded_addresses = LoadedAddresses {
+            writable: address_table_lookups
+                .iter()
+                .flat_map(|lookup| {
+                    let table = Self::lookup_table(address_lookup_tables, &lookup.account_key);
+                    lookup
+                        .writable_indexes
+                        .iter()
+                        .map(move |i| table[*i as usize])
+                })
+                .collect(),
+            readonly: address_table_lookups
+                .iter()
+                .flat_map(|lookup| {
+                    let table = Self::lookup_table(address_lookup_tables, &lookup.account_key);
+                    lookup
+                        .readonly_indexes
+                        .iter()
+                        .map(move |i| table[*i as usize])
+                })
+                .collect(),
+        };
+        let keys: Vec<_> = account_keys
+            .iter()
+            .chain(loaded_addresses.writable.iter())
+            .chain(loaded_addresses.readonly.iter())
+            .cloned()
+            .collect();
+        let instructions = message
+            .instructions
+            .iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: position(
+                    &keys,
+                    &message.account_keys[instruction.program_id_index as usize],
+                ),
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|i| position(&keys, &message.account_keys[*i as usize]))
+                    .collect(),
+                data: instruction.data.clone(),
+            })
+            .collect();
+
+        Self {
+            header: MessageHeader {
+                num_readonly_unsigned_accounts,
+                ..message.header
+            },
+            account_keys,
+            recent_blockhash: message.recent_blockhash,
+            instructions,
+            address_table_lookups,
+        }
+    }
+
+    fn lookup_table<'a>(
+        address_lookup_tables: &'a [(Pubkey, Vec<Pubkey>)],
+        table_key: &Pubkey,
+    ) -> &'a [Pubkey] {
+        address_lookup_tables
+            .iter()
+            .find(|(key, _)| key == table_key)
+            .map(|(_, addresses)| addresses.as_slice())
+            .unwrap()
+    }
+
+    /// Convert into a legacy message which lists every account, including the ones loaded
+    /// from address lookup tables, in its `account_keys`.  Loaded writable accounts are placed
+    /// before the message's own read-only unsigned accounts so that the legacy header still
+    /// describes which accounts are writable.
+    pub fn into_legacy_message(self, loaded_addresses: &LoadedAddresses) -> Message {
+        let num_static_keys = self.account_keys.len();
+        let num_readonly_static_keys = self.header.num_readonly_unsigned_accounts as usize;
+        let num_writable_static_keys = num_static_keys - num_readonly_static_keys;
+        let num_writable_loaded_keys = loaded_addresses.writable.len();
+        let map_index = |index: u8| -> u8 {
+            let index = index as usize;
+            let mapped = if index < num_writable_static_keys {
+                index
+            } else if index < num_static_keys {
+                // read-only unsigned static keys move behind the writable loaded keys
+                index + num_writable_loaded_keys
+            } else if index < num_static_keys + num_writable_loaded_keys {
+                // writable loaded keys move in front of the read-only unsigned static keys
+                index - num_readonly_static_keys
+            } else {
+                index
+            };
+            mapped as u8
+        };
+
+        let mut account_keys = Vec::with_capacity(num_static_keys + loaded_addresses.len());
+        account_keys.extend_from_slice(&self.account_keys[..num_writable_static_keys]);
+        account_keys.extend_from_slice(&loaded_addresses.writable);
+        account_keys.extend_from_slice(&self.account_keys[num_writable_static_keys..]);
+        account_keys.extend_from_slice(&loaded_addresses.readonly);
+
+        let instructions = self
+            .instructions
+            .into_iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: map_index(instruction.program_id_index),
+                accounts: instruction.accounts.into_iter().map(map_index).collect(),
+                data: instruction.data,
+            })
+            .collect();
+
+        Message {
+            header: MessageHeader {
+                num_readonly_unsigned_accounts: self.header.num_readonly_unsigned_accounts
+                    + loaded_addresses.readonly.len() as u8,
+                ..self.header
+            },
+            account_keys,
+            recent_blockhash: self.recent_blockhash,
+            instructions,
+        }
+    }
+}
+
+/// A message in either the legacy or a versioned format.  Versioned messages are serialized
+/// with a leading byte of `MESSAGE_VERSION_PREFIX | version`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VersionedMessage {
+    Legacy(Message),
+    V0(MessageV0),
+}
+
+impl Default for VersionedMessage {
+    fn default() -> Self {
+        Self::Legacy(Message::default())
+    }
+}
+
+impl From<Message> for VersionedMessage {
+    fn from(message: Message) -> Self {
+        Self::Legacy(message)
+    }
+}
+
+impl Sanitize for VersionedMessage {
+    fn sanitize(&self) -> std::result::Result<(), SanitizeError> {
+        match self {
+            Self::Legacy(message) => message.sanitize(),
+            Self::V0(message) => message.sanitize(),
+        }
+    }
+}
+
+impl VersionedMessage {
+    pub fn header(&self) -> &MessageHeader {
+        match self {
+            Self::Legacy(message) => &message.header,
+            Self::V0(message) => &message.header,
+        }
+    }
+
+    /// The account keys stored in the message itself
+    pub fn static_account_keys(&self) -> &[Pubkey] {
+        match self {
+            Self::Legacy(message) => &message.account_keys,
+            Self::V0(message) => &message.account_keys,
+        }
+    }
+
+    pub fn recent_blockhash(&self) -> &Hash {
+        match self {
+            Self::Legacy(message) => &message.recent_blockhash,
+            Self::V0(message) => &message.recent_blockhash,
+        }
+    }
+
+    pub fn instructions(&self) -> &[CompiledInstruction] {
+        match self {
+            Self::Legacy(message) => &message.instructions,
+            Self::V0(message) => &message.instructions,
+        }
+    }
+
+    pub fn address_table_lookups(&self) -> Option<&[MessageAddressTableLookup]> {
+        match self {
+            Self::Legacy(_) => None,
+            Self::V0(message) => Some(&message.address_table_lookups),
+        }
+    }
+
+    /// The addresses that resolving this message into `resolved_message` loaded from address
+    /// lookup tables, undoing `MessageV0::into_legacy_message`.  Legacy messages load none.
+    pub fn loaded_addresses(&self, resolved_message: &Message) -> LoadedAddresses {
+        let message = match self {
+            Self::Legacy(_) => return LoadedAddresses::default(),
+            Self::V0(message) => message,
+        };
+        let num_static_keys = message.account_keys.len();
+        let num_readonly_static_keys = message.header.num_readonly_unsigned_accounts as usize;
+        let num_writable_static_keys = num_static_keys - num_readonly_static_keys;
+        let num_readonly_loaded_keys = resolved_message.header.num_readonly_unsigned_accounts
+            as usize
+            - num_readonly_static_keys;
+        let num_writable_loaded_keys =
+            resolved_message.account_keys.len() - num_static_keys - num_readonly_loaded_keys;
+
+        let account_keys = &resolved_message.account_keys;
+        LoadedAddresses {
+            writable: account_keys
+                [num_writable_static_keys..num_writable_static_keys + num_writable_loaded_keys]
+                .to_vec(),
+            readonly: account_keys[num_static_keys + num_writable_loaded_keys..].to_vec(),
+        }
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+}
+
+impl serde::Serialize for VersionedMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeTuple;
+
+        match self {
+            Self::Legacy(message) => {
+                let mut seq = serializer.serialize_tuple(1)?;
+                seq.serialize_element(message)?;
+                seq.end()
+            }
+            Self::V0(message) => {
+                let mut seq = serializer.serialize_tuple(2)?;
+                seq.serialize_element(&MESSAGE_VERSION_PREFIX)?;
+                seq.serialize_element(message)?;
+                seq.end()
+            }
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for VersionedMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::{self, SeqAccess, Unexpected, Visitor};
+        use std::fmt;
+
+        // The remainder of a legacy message once its first byte, `num_required_signatures`,
+        // has been read
+        #[derive(Deserialize)]
+        struct RemainingLegacyMessage {
+            num_readonly_signed_accounts: u8,
+            num_readonly_unsigned_accounts: u8,
+            #[serde(with = "short_vec")]
+            account_keys: Vec<Pubkey>,
+            recent_blockhash: Hash,
+            #[serde(with = "short_vec")]
+            instructions: Vec<CompiledInstruction>,
+        }
+
+        struct MessageVisitor;
+
+        impl<'de> Visitor<'de> for MessageVisitor {
+            type Value = VersionedMessage;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a legacy or versioned message")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<VersionedMessage, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let prefix: u8 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                if prefix & MESSAGE_VERSION_PREFIX == 0 {
+                    let message: RemainingLegacyMessage = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                    return Ok(VersionedMessage::Legacy(Message {
+                        header: MessageHeader {
+                            num_required_signatures: prefix,
+                            num_readonly_signed_accounts: message.num_readonly_signed_accounts,
+                            num_readonly_unsigned_accounts: message.num_readonly_unsigned_accounts,
+                        },
+                        account_keys: message.account_keys,
+                        recent_blockhash: message.recent_blockhash,
+                        instructions: message.instructions,
+                    }));
+                }
+                match prefix & !MESSAGE_VERSION_PREFIX {
+                    0 => Ok(VersionedMessage::V0(
+                        seq.next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    )),
+                    version => Err(de::Error::invalid_value(
+                        Unexpected::Unsigned(version as u64),
+                        &"a supported message version",
+                    )),
+                }
+            }
+        }
+
+        deserializer.deserialize_tuple(2, MessageVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -794,4 +1282,146 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_versioned_message_serialization() {
+        let payer = Pubkey::new_unique();
+        let message = Message::new(
+            &[Instruction::new(
+                Pubkey::new_unique(),
+                &0,
+                vec![AccountMeta::new(Pubkey::new_unique(), false)],
+            )],
+            Some(&payer),
+        );
+
+        // Legacy messages serialize exactly as before
+        let versioned_message = VersionedMessage::from(message.clone());
+        assert_eq!(versioned_message.serialize(), message.serialize());
+        assert_eq!(
+            bincode::deserialize::<VersionedMessage>(&message.serialize()).unwrap(),
+            versioned_message
+        );
+
+        let versioned_message = VersionedMessage::V0(MessageV0 {
+            header: message.header.clone(),
+            account_keys: message.account_keys.clone(),
+            recent_blockhash: message.recent_blockhash,
+            instructions: message.instructions.clone(),
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![1],
+                readonly_indexes: vec![0, 2],
+            }],
+        });
+        let serialized = versioned_message.serialize();
+        assert_eq!(serialized[0], MESSAGE_VERSION_PREFIX);
+        assert_eq!(
+            bincode::deserialize::<VersionedMessage>(&serialized).unwrap(),
+            versioned_message
+        );
+
+        // Unknown versions are rejected
+        let mut serialized = serialized;
+        serialized[0] = MESSAGE_VERSION_PREFIX | 1;
+        assert!(bincode::deserialize::<VersionedMessage>(&serialized).is_err());
+    }
+
+    #[test]
+    fn test_message_v0_sanitize() {
+        let message = MessageV0 {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            recent_blockhash: Hash::default(),
+            instructions: vec![CompiledInstruction::new(1, &(), vec![0, 2])],
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        };
+        assert_eq!(message.sanitize(), Ok(()));
+
+        // Loaded accounts cannot be programs
+        let mut bad_message = message.clone();
+        bad_message.instructions[0].program_id_index = 2;
+        assert_eq!(bad_message.sanitize(), Err(SanitizeError::IndexOutOfBounds));
+
+        // Instructions cannot reference accounts beyond the loaded ones
+        let mut bad_message = message.clone();
+        bad_message.instructions[0].accounts = vec![3];
+        assert_eq!(bad_message.sanitize(), Err(SanitizeError::IndexOutOfBounds));
+
+        // Lookups must load at least one account
+        let mut bad_message = message;
+        bad_message.address_table_lookups[0]
+            .writable_indexes
+            .clear();
+        assert_eq!(bad_message.sanitize(), Err(SanitizeError::InvalidValue));
+    }
+
+    #[test]
+    fn test_message_v0_into_legacy_message() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let id0 = Pubkey::new_unique();
+        let id1 = Pubkey::new_unique();
+        let id2 = Pubkey::new_unique();
+        let id3 = Pubkey::new_unique();
+        let instructions = vec![
+            Instruction::new(
+                program_id,
+                &0,
+                vec![
+                    AccountMeta::new(id0, false),
+                    AccountMeta::new_readonly(id1, false),
+                    AccountMeta::new(id2, false),
+                    AccountMeta::new_readonly(id3, false),
+                ],
+            ),
+            Instruction::new(program_id, &1, vec![AccountMeta::new(id2, false)]),
+        ];
+        let table_key = Pubkey::new_unique();
+        let table = vec![id3, Pubkey::new_unique(), id2];
+
+        let message =
+            MessageV0::new_with_lookup_tables(&instructions, Some(&payer), &[(table_key, table)]);
+        assert_eq!(message.sanitize(), Ok(()));
+        assert_eq!(message.account_keys, vec![payer, id0, id1, program_id]);
+        assert_eq!(message.header.num_readonly_unsigned_accounts, 2);
+        assert_eq!(
+            message.address_table_lookups,
+            vec![MessageAddressTableLookup {
+                account_key: table_key,
+                writable_indexes: vec![2],
+                readonly_indexes: vec![0],
+            }]
+        );
+
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![id2],
+            readonly: vec![id3],
+        };
+        let legacy_message = message.clone().into_legacy_message(&loaded_addresses);
+        assert_eq!(legacy_message.sanitize(), Ok(()));
+        assert_eq!(
+            VersionedMessage::V0(message).loaded_addresses(&legacy_message),
+            loaded_addresses
+        );
+        assert_eq!(
+            VersionedMessage::Legacy(legacy_message.clone()).loaded_addresses(&legacy_message),
+            LoadedAddresses::default()
+        );
+        let resolved_instructions: Vec<_> = (0..instructions.len())
+            .map(|i| {
+                Message::deserialize_instruction(i, &legacy_message.serialize_instructions())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(resolved_instructions, instructions);
+    }
 }
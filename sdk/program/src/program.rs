@@ -1,4 +1,9 @@
-use crate::{account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction};
+use crate::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction, pubkey::Pubkey,
+};
+
+/// Maximum size of the return data a program may set
+pub const MAX_RETURN_DATA: usize = 1024;
 
 /// Invoke a cross-program instruction
 ///
@@ -54,6 +59,49 @@ pub fn invoke_signed(
     crate::program_stubs::sol_invoke_signed(instruction, account_infos, signers_seeds)
 }
 
+/// Set the running program's return data
+///
+/// The return data is a byte buffer of at most `MAX_RETURN_DATA` bytes that
+/// the caller of this program can retrieve with `get_return_data()` once the
+/// invocation completes.  Setting it again replaces the previous contents.
+pub fn set_return_data(data: &[u8]) {
+    #[cfg(target_arch = "bpf")]
+    unsafe {
+        sol_set_return_data(data.as_ptr(), data.len() as u64)
+    };
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_set_return_data(data)
+}
+
+/// Get the return data set by the most recently invoked program, along with
+/// the id of the program that set it
+///
+/// Return data is cleared at the start of every cross-program invocation, so
+/// `None` is returned if the last invoked program did not set any.
+pub fn get_return_data() -> Option<(Pubkey, Vec<u8>)> {
+    #[cfg(target_arch = "bpf")]
+    {
+        use std::cmp::min;
+
+        let mut buf = [0u8; MAX_RETURN_DATA];
+        let mut program_id = Pubkey::default();
+
+        let size =
+            unsafe { sol_get_return_data(buf.as_mut_ptr(), buf.len() as u64, &mut program_id) };
+
+        if size == 0 {
+            None
+        } else {
+            let size = min(size as usize, MAX_RETURN_DATA);
+            Some((program_id, buf[..size].to_vec()))
+        }
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    crate::program_stubs::sol_get_return_data()
+}
+
 #[cfg(target_arch = "bpf")]
 extern "C" {
     fn sol_invoke_signed_rust(
@@ -63,4 +111,8 @@ extern "C" {
         signers_seeds_addr: *const u8,
         signers_seeds_len: u64,
     ) -> u64;
+
+    fn sol_set_return_data(data: *const u8, length: u64);
+
+    fn sol_get_return_data(data: *mut u8, length: u64, program_id: *mut Pubkey) -> u64;
 }
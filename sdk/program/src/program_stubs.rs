@@ -2,7 +2,10 @@
 
 #![cfg(not(target_arch = "bpf"))]
 
-use crate::{account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction};
+use crate::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction,
+    program_error::INVALID_ARGUMENT, pubkey::Pubkey,
+};
 use std::sync::{Arc, RwLock};
 
 lazy_static::lazy_static! {
@@ -22,6 +25,9 @@ pub trait SyscallStubs: Sync + Send {
     fn sol_log_compute_units(&self) {
         sol_log("SyscallStubs: sol_log_compute_units() not available");
     }
+    fn sol_log_data(&self, _data: &[&[u8]]) {
+        sol_log("SyscallStubs: sol_log_data() not available");
+    }
     fn sol_invoke_signed(
         &self,
         _instruction: &Instruction,
@@ -31,6 +37,24 @@ pub trait SyscallStubs: Sync + Send {
         sol_log("SyscallStubs: sol_invoke_signed() not available");
         Ok(())
     }
+    fn sol_get_clock_sysvar(&self, _var_addr: *mut u8) -> u64 {
+        INVALID_ARGUMENT
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, _var_addr: *mut u8) -> u64 {
+        INVALID_ARGUMENT
+    }
+    fn sol_get_fees_sysvar(&self, _var_addr: *mut u8) -> u64 {
+        INVALID_ARGUMENT
+    }
+    fn sol_get_rent_sysvar(&self, _var_addr: *mut u8) -> u64 {
+        INVALID_ARGUMENT
+    }
+    fn sol_set_return_data(&self, _data: &[u8]) {
+        sol_log("SyscallStubs: sol_set_return_data() not available");
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        None
+    }
 }
 
 struct DefaultSyscallStubs {}
@@ -51,6 +75,10 @@ pub(crate) fn sol_log_compute_units() {
     SYSCALL_STUBS.read().unwrap().sol_log_compute_units();
 }
 
+pub(crate) fn sol_log_data(data: &[&[u8]]) {
+    SYSCALL_STUBS.read().unwrap().sol_log_data(data)
+}
+
 pub(crate) fn sol_invoke_signed(
     instruction: &Instruction,
     account_infos: &[AccountInfo],
@@ -61,3 +89,30 @@ pub(crate) fn sol_invoke_signed(
         .unwrap()
         .sol_invoke_signed(instruction, account_infos, signers_seeds)
 }
+
+pub(crate) fn sol_get_clock_sysvar(var_addr: *mut u8) -> u64 {
+    SYSCALL_STUBS.read().unwrap().sol_get_clock_sysvar(var_addr)
+}
+
+pub(crate) fn sol_get_epoch_schedule_sysvar(var_addr: *mut u8) -> u64 {
+    SYSCALL_STUBS
+        .read()
+        .unwrap()
+        .sol_get_epoch_schedule_sysvar(var_addr)
+}
+
+pub(crate) fn sol_get_fees_sysvar(var_addr: *mut u8) -> u64 {
+    SYSCALL_STUBS.read().unwrap().sol_get_fees_sysvar(var_addr)
+}
+
+pub(crate) fn sol_get_rent_sysvar(var_addr: *mut u8) -> u64 {
+    SYSCALL_STUBS.read().unwrap().sol_get_rent_sysvar(var_addr)
+}
+
+pub(crate) fn sol_set_return_data(data: &[u8]) {
+    SYSCALL_STUBS.read().unwrap().sol_set_return_data(data)
+}
+
+pub(crate) fn sol_get_return_data() -> Option<(Pubkey, Vec<u8>)> {
+    SYSCALL_STUBS.read().unwrap().sol_get_return_data()
+}
@@ -0,0 +1,122 @@
+//! Compact serde encoding of `u64` values, using 7 bits of each byte for the
+//! value and the high bit to flag that more bytes follow.
+//!
+//! Use it on a field with the following annotation:
+//!
+//! #[serde(with = "serde_varint")]
+//!
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserializer, Serializer,
+};
+use std::fmt;
+
+// A u64 needs at most ceil(64 / 7) bytes
+const MAX_ENCODING_LENGTH: usize = 10;
+
+pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    // Pass a non-zero value to serialize_tuple() so that serde_json will
+    // generate an open bracket.
+    let mut seq = serializer.serialize_tuple(1)?;
+
+    let mut rem = *value;
+    loop {
+        let mut elem = (rem & 0x7f) as u8;
+        rem >>= 7;
+        if rem == 0 {
+            seq.serialize_element(&elem)?;
+            break;
+        } else {
+            elem |= 0x80;
+            seq.serialize_element(&elem)?;
+        }
+    }
+    seq.end()
+}
+
+struct VarIntVisitor;
+
+impl<'de> Visitor<'de> for VarIntVisitor {
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a varint encoded u64")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<u64, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut value: u64 = 0;
+        for size in 0..MAX_ENCODING_LENGTH {
+            let elem: u8 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(size, &self))?;
+            let bits = u64::from(elem & 0x7f);
+            let shift = size as u32 * 7;
+            // Reject encodings that overflow a u64, or that carry redundant
+            // trailing zero bytes, so that every value has exactly one encoding
+            if bits.checked_shl(shift).map_or(true, |v| v >> shift != bits)
+                || (size > 0 && elem == 0)
+            {
+                return Err(de::Error::custom("invalid varint encoding"));
+            }
+            value |= bits << shift;
+            if elem & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(de::Error::invalid_length(MAX_ENCODING_LENGTH + 1, &self))
+    }
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_tuple(MAX_ENCODING_LENGTH, VarIntVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Value {
+        #[serde(with = "super")]
+        value: u64,
+    }
+
+    #[test]
+    fn test_serde_varint() {
+        for (value, len) in &[
+            (0, 1),
+            (0x7f, 1),
+            (0x80, 2),
+            (0x3fff, 2),
+            (0x4000, 3),
+            (std::u64::MAX, 10),
+        ] {
+            let value = Value { value: *value };
+            let bytes = bincode::serialize(&value).unwrap();
+            assert_eq!(bytes.len(), *len);
+            assert_eq!(bincode::deserialize::<Value>(&bytes).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_serde_varint_invalid() {
+        // trailing zero byte
+        assert!(bincode::deserialize::<Value>(&[0x80, 0x00]).is_err());
+        // overflows a u64
+        assert!(bincode::deserialize::<Value>(
+            &[0xff; 9].iter().chain(&[0x02]).copied().collect::<Vec<_>>()
+        )
+        .is_err());
+        // too long
+        assert!(bincode::deserialize::<Value>(&[0xff; 11]).is_err());
+        // truncated
+        assert!(bincode::deserialize::<Value>(&[0x80]).is_err());
+    }
+}
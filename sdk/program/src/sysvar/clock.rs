@@ -2,8 +2,10 @@
 //!
 pub use crate::clock::Clock;
 
-use crate::sysvar::Sysvar;
+use crate::{impl_sysvar_get, program_error::ProgramError, sysvar::Sysvar};
 
 crate::declare_sysvar_id!("SysvarC1ock11111111111111111111111111111111", Clock);
 
-impl Sysvar for Clock {}
+impl Sysvar for Clock {
+    impl_sysvar_get!(sol_get_clock_sysvar);
+}
@@ -70,6 +70,38 @@ pub trait Sysvar:
     fn to_account_info(&self, account_info: &mut AccountInfo) -> Option<()> {
         bincode::serialize_into(&mut account_info.data.borrow_mut()[..], self).ok()
     }
+    /// Load the sysvar directly from the runtime, without needing the sysvar
+    /// account to be passed to the program
+    fn get() -> Result<Self, ProgramError> {
+        Err(ProgramError::InvalidArgument)
+    }
+}
+
+/// Implements the `Sysvar::get()` method for a sysvar served by the given
+/// syscall
+#[macro_export]
+macro_rules! impl_sysvar_get {
+    ($syscall_name:ident) => {
+        fn get() -> Result<Self, ProgramError> {
+            let mut var = Self::default();
+            let var_addr = &mut var as *mut _ as *mut u8;
+
+            #[cfg(target_arch = "bpf")]
+            let result = unsafe {
+                extern "C" {
+                    fn $syscall_name(var_addr: *mut u8) -> u64;
+                }
+                $syscall_name(var_addr)
+            };
+            #[cfg(not(target_arch = "bpf"))]
+            let result = crate::program_stubs::$syscall_name(var_addr);
+
+            match result {
+                crate::entrypoint::SUCCESS => Ok(var),
+                e => Err(e.into()),
+            }
+        }
+    };
 }
 
 #[cfg(test)]
@@ -0,0 +1,97 @@
+//! The compute budget native program lets a transaction request the total number of compute
+//! units shared by all of its instructions
+
+#![cfg(feature = "full")]
+
+use crate::{
+    instruction::{Instruction, InstructionError},
+    message::Message,
+    program_utils::limited_deserialize,
+    transaction::TransactionError,
+};
+use serde_derive::{Deserialize, Serialize};
+
+crate::declare_id!("ComputeBudget111111111111111111111111111111");
+
+/// Maximum number of compute units a transaction may request
+pub const MAX_UNITS: u64 = 1_400_000;
+
+/// Maximum number of compute units a leader will pack into a single block
+pub const MAX_BLOCK_UNITS: u64 = 100_000_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ComputeBudgetInstruction {
+    /// Request a limit on the total number of compute units consumed by all the
+    /// instructions of the transaction, up to `MAX_UNITS`
+    ///
+    /// No accounts are required
+    RequestUnits(u64),
+}
+
+/// Create a `ComputeBudgetInstruction::RequestUnits` instruction
+pub fn request_units(units: u64) -> Instruction {
+    Instruction::new(id(), &ComputeBudgetInstruction::RequestUnits(units), vec![])
+}
+
+/// Return the number of compute units requested by the message, if any
+///
+/// A message may contain at most one request, and the request may not exceed `MAX_UNITS`
+pub fn get_requested_units(message: &Message) -> Result<Option<u64>, TransactionError> {
+    let mut requested_units = None;
+    for (i, instruction) in message.instructions.iter().enumerate() {
+        if !check_id(instruction.program_id(&message.account_keys)) {
+            continue;
+        }
+        let invalid_instruction_data =
+            TransactionError::InstructionError(i as u8, InstructionError::InvalidInstructionData);
+        let ComputeBudgetInstruction::RequestUnits(units) =
+            limited_deserialize(&instruction.data).map_err(|_| invalid_instruction_data.clone())?;
+        if requested_units.is_some() || units > MAX_UNITS {
+            return Err(invalid_instruction_data);
+        }
+        requested_units = Some(units);
+    }
+    Ok(requested_units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pubkey::Pubkey, system_instruction};
+
+    #[test]
+    fn test_get_requested_units() {
+        let payer = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+
+        let message = Message::new(&[transfer.clone()], Some(&payer));
+        assert_eq!(get_requested_units(&message), Ok(None));
+
+        let message = Message::new(&[request_units(500_000), transfer.clone()], Some(&payer));
+        assert_eq!(get_requested_units(&message), Ok(Some(500_000)));
+
+        let message = Message::new(
+            &[transfer.clone(), request_units(MAX_UNITS + 1)],
+            Some(&payer),
+        );
+        assert_eq!(
+            get_requested_units(&message),
+            Err(TransactionError::InstructionError(
+                1,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+
+        let message = Message::new(
+            &[request_units(1), transfer, request_units(2)],
+            Some(&payer),
+        );
+        assert_eq!(
+            get_requested_units(&message),
+            Err(TransactionError::InstructionError(
+                2,
+                InstructionError::InvalidInstructionData
+            ))
+        );
+    }
+}
@@ -0,0 +1,290 @@
+#![cfg(feature = "full")]
+
+use crate::{
+    instruction::Instruction,
+    signature::{Keypair, Signature, Signer},
+};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq)]
+pub enum Ed25519Error {
+    InvalidSignature,
+    InvalidDataOffsets,
+    InvalidInstructionDataSize,
+}
+
+pub const PUBKEY_SERIALIZED_SIZE: usize = 32;
+pub const SIGNATURE_SERIALIZED_SIZE: usize = 64;
+pub const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 11;
+
+#[derive(Default, Serialize, Deserialize, Debug)]
+pub struct Ed25519SignatureOffsets {
+    pub signature_offset: u16, // offset to ed25519 signature of 64 bytes
+    pub signature_instruction_index: u8,
+    pub public_key_offset: u16, // offset to public key of 32 bytes
+    pub public_key_instruction_index: u8,
+    pub message_data_offset: u16, // offset to start of message data
+    pub message_data_size: u16,   // size of message data
+    pub message_instruction_index: u8,
+}
+
+/// Build an instruction verifying `keypair`'s signature over `message_arr`.  The offsets it
+/// encodes refer to instruction 0, so it must be the first instruction of its transaction.
+pub fn new_ed25519_instruction(keypair: &Keypair, message_arr: &[u8]) -> Instruction {
+    let signature = keypair.sign_message(message_arr);
+    let pubkey = keypair.pubkey();
+
+    let mut instruction_data = vec![];
+    let data_start = 1 + SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+    instruction_data.resize(
+        data_start + PUBKEY_SERIALIZED_SIZE + SIGNATURE_SERIALIZED_SIZE + message_arr.len(),
+        0,
+    );
+    let public_key_offset = data_start;
+    instruction_data[public_key_offset..public_key_offset + PUBKEY_SERIALIZED_SIZE]
+        .copy_from_slice(pubkey.as_ref());
+
+    let signature_offset = public_key_offset + PUBKEY_SERIALIZED_SIZE;
+    instruction_data[signature_offset..signature_offset + SIGNATURE_SERIALIZED_SIZE]
+        .copy_from_slice(signature.as_ref());
+
+    let message_data_offset = signature_offset + SIGNATURE_SERIALIZED_SIZE;
+    instruction_data[message_data_offset..].copy_from_slice(message_arr);
+
+    let num_signatures = 1;
+    instruction_data[0] = num_signatures;
+    let offsets = Ed25519SignatureOffsets {
+        signature_offset: signature_offset as u16,
+        signature_instruction_index: 0,
+        public_key_offset: public_key_offset as u16,
+        public_key_instruction_index: 0,
+        message_data_offset: message_data_offset as u16,
+        message_data_size: message_arr.len() as u16,
+        message_instruction_index: 0,
+    };
+    let writer = std::io::Cursor::new(&mut instruction_data[1..data_start]);
+    bincode::serialize_into(writer, &offsets).unwrap();
+
+    Instruction {
+        program_id: solana_sdk::ed25519_program::id(),
+        accounts: vec![],
+        data: instruction_data,
+    }
+}
+
+/// Verify every signature described by the offsets in `data`, with the same `Signature::verify`
+/// the CPU sigverify path in `perf` uses for transaction signatures.
+pub fn verify_signatures(data: &[u8], instruction_datas: &[&[u8]]) -> Result<(), Ed25519Error> {
+    if data.is_empty() {
+        return Err(Ed25519Error::InvalidInstructionDataSize);
+    }
+    let count = data[0] as usize;
+    let expected_data_size = 1 + count * SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+    if data.len() < expected_data_size {
+        return Err(Ed25519Error::InvalidInstructionDataSize);
+    }
+    for i in 0..count {
+        let start = 1 + i * SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+        let end = start + SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+
+        let offsets: Ed25519SignatureOffsets =
+            bincode::deserialize(&data[start..end]).map_err(|_| Ed25519Error::InvalidSignature)?;
+
+        // Parse out signature
+        let signature = get_data_slice(
+            &instruction_datas,
+            offsets.signature_instruction_index,
+            offsets.signature_offset,
+            SIGNATURE_SERIALIZED_SIZE,
+        )?;
+
+        // Parse out pubkey
+        let pubkey = get_data_slice(
+            &instruction_datas,
+            offsets.public_key_instruction_index,
+            offsets.public_key_offset,
+            PUBKEY_SERIALIZED_SIZE,
+        )?;
+
+        // Parse out message
+        let message = get_data_slice(
+            &instruction_datas,
+            offsets.message_instruction_index,
+            offsets.message_data_offset,
+            offsets.message_data_size as usize,
+        )?;
+
+        if !Signature::new(signature).verify(pubkey, message) {
+            return Err(Ed25519Error::InvalidSignature);
+        }
+    }
+    Ok(())
+}
+
+fn get_data_slice<'a>(
+    instruction_datas: &'a [&[u8]],
+    instruction_index: u8,
+    offset_start: u16,
+    size: usize,
+) -> Result<&'a [u8], Ed25519Error> {
+    let instruction_index = instruction_index as usize;
+    if instruction_index >= instruction_datas.len() {
+        return Err(Ed25519Error::InvalidDataOffsets);
+    }
+    let instruction = &instruction_datas[instruction_index];
+    let start = offset_start as usize;
+    let end = start + size;
+    if end > instruction.len() {
+        return Err(Ed25519Error::InvalidSignature);
+    }
+
+    Ok(&instruction[start..end])
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn test_case(
+        num_signatures: u8,
+        offsets: &Ed25519SignatureOffsets,
+    ) -> Result<(), Ed25519Error> {
+        let mut instruction_data = vec![0u8; 1 + SIGNATURE_OFFSETS_SERIALIZED_SIZE];
+        instruction_data[0] = num_signatures;
+        let writer = std::io::Cursor::new(&mut instruction_data[1..]);
+        bincode::serialize_into(writer, &offsets).unwrap();
+
+        verify_signatures(&instruction_data, &[&[0u8; 100]])
+    }
+
+    #[test]
+    fn test_offsets_serialized_size() {
+        assert_eq!(
+            bincode::serialized_size(&Ed25519SignatureOffsets::default()).unwrap() as usize,
+            SIGNATURE_OFFSETS_SERIALIZED_SIZE
+        );
+    }
+
+    #[test]
+    fn test_invalid_offsets() {
+        let mut instruction_data = vec![0u8; 1 + SIGNATURE_OFFSETS_SERIALIZED_SIZE];
+        let offsets = Ed25519SignatureOffsets::default();
+        instruction_data[0] = 1;
+        let writer = std::io::Cursor::new(&mut instruction_data[1..]);
+        bincode::serialize_into(writer, &offsets).unwrap();
+        instruction_data.truncate(instruction_data.len() - 1);
+
+        assert_eq!(
+            verify_signatures(&instruction_data, &[&[0u8; 100]]),
+            Err(Ed25519Error::InvalidInstructionDataSize)
+        );
+
+        let offsets = Ed25519SignatureOffsets {
+            signature_instruction_index: 1,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(
+            test_case(1, &offsets),
+            Err(Ed25519Error::InvalidDataOffsets)
+        );
+
+        let offsets = Ed25519SignatureOffsets {
+            message_instruction_index: 1,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(
+            test_case(1, &offsets),
+            Err(Ed25519Error::InvalidDataOffsets)
+        );
+
+        let offsets = Ed25519SignatureOffsets {
+            public_key_instruction_index: 1,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(
+            test_case(1, &offsets),
+            Err(Ed25519Error::InvalidDataOffsets)
+        );
+    }
+
+    #[test]
+    fn test_data_offsets() {
+        let offsets = Ed25519SignatureOffsets {
+            message_data_offset: 100,
+            message_data_size: 1,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(test_case(1, &offsets), Err(Ed25519Error::InvalidSignature));
+
+        let offsets = Ed25519SignatureOffsets {
+            message_data_offset: std::u16::MAX,
+            message_data_size: std::u16::MAX,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(test_case(1, &offsets), Err(Ed25519Error::InvalidSignature));
+
+        let offsets = Ed25519SignatureOffsets {
+            public_key_offset: 100 - PUBKEY_SERIALIZED_SIZE as u16 + 1,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(test_case(1, &offsets), Err(Ed25519Error::InvalidSignature));
+
+        let offsets = Ed25519SignatureOffsets {
+            signature_offset: 100 - SIGNATURE_SERIALIZED_SIZE as u16 + 1,
+            ..Ed25519SignatureOffsets::default()
+        };
+        assert_eq!(test_case(1, &offsets), Err(Ed25519Error::InvalidSignature));
+    }
+
+    #[test]
+    fn test_verify_signatures() {
+        let keypair = Keypair::new();
+        let message_arr = b"hello";
+        let mut instruction = new_ed25519_instruction(&keypair, message_arr);
+        assert_eq!(
+            verify_signatures(&instruction.data, &[&instruction.data]),
+            Ok(())
+        );
+
+        // Corrupt the message
+        let last = instruction.data.len() - 1;
+        instruction.data[last] ^= 1;
+        assert_eq!(
+            verify_signatures(&instruction.data, &[&instruction.data]),
+            Err(Ed25519Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_verify_signatures_rejects_small_order_pubkey() {
+        // The identity point as the public key, with R = identity and S = 0, satisfies the
+        // cofactored verification equation for every message, so only strict verification
+        // rejects it
+        let mut pubkey = [0u8; PUBKEY_SERIALIZED_SIZE];
+        pubkey[0] = 1;
+        let mut signature = [0u8; SIGNATURE_SERIALIZED_SIZE];
+        signature[0] = 1;
+        let message = b"hello";
+
+        let public_key_offset = 1 + SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+        let signature_offset = public_key_offset + PUBKEY_SERIALIZED_SIZE;
+        let message_data_offset = signature_offset + SIGNATURE_SERIALIZED_SIZE;
+        let offsets = Ed25519SignatureOffsets {
+            signature_offset: signature_offset as u16,
+            public_key_offset: public_key_offset as u16,
+            message_data_offset: message_data_offset as u16,
+            message_data_size: message.len() as u16,
+            ..Ed25519SignatureOffsets::default()
+        };
+        let mut instruction_data = vec![1u8];
+        instruction_data.extend_from_slice(&bincode::serialize(&offsets).unwrap());
+        instruction_data.extend_from_slice(&pubkey);
+        instruction_data.extend_from_slice(&signature);
+        instruction_data.extend_from_slice(message);
+
+        assert_eq!(
+            verify_signatures(&instruction_data, &[&instruction_data]),
+            Err(Ed25519Error::InvalidSignature)
+        );
+    }
+}
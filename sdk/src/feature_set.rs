@@ -94,6 +94,46 @@ pub mod rewrite_stake {
     solana_sdk::declare_id!("6ap2eGy7wx5JmsWUmQ5sHwEWrFSDUxSti2k5Hbfv5BZG");
 }
 
+pub mod bpf_loader_upgradeable_program {
+    solana_sdk::declare_id!("E5sL11MgyGvgMcwxrKkrBQqBWERwmcX3MgVb3NewUyrx");
+}
+
+pub mod sysvar_syscalls {
+    solana_sdk::declare_id!("2knUoHK8AKCEBvSggnXth23TsjqgdprURKRsLQsdkgbB");
+}
+
+pub mod return_data_syscall_enabled {
+    solana_sdk::declare_id!("47waAzQwGbUuMY2k2sgbs57eb35ZxzhGXZdQHvmPAfJC");
+}
+
+pub mod transaction_wide_compute_cap {
+    solana_sdk::declare_id!("425A3hk5URcq24W4UvRkWVCCgQ96HcNsY9emXsAjVGR4");
+}
+
+pub mod address_lookup_table_program {
+    solana_sdk::declare_id!("BegMXTykfDeHpKRmUc4A73yFTGZeVjEP6sZMDdwSqUYm");
+}
+
+pub mod stake_redelegate_instruction {
+    solana_sdk::declare_id!("GQkiw1cwhenQLMyFU7oAjGa1miYVEpiUN1YBp9fqMxYw");
+}
+
+pub mod ed25519_program_enabled {
+    solana_sdk::declare_id!("HPSkATRoGAxN8VxMKeZgCG9SMPuePY5toCXR35hoEsHc");
+}
+
+pub mod log_data_syscall_enabled {
+    solana_sdk::declare_id!("V4qh7C2VkvFKtTkXcxAtJe4oJ8vGuZbZKzx98ELPfTz");
+}
+
+pub mod validator_info_schema {
+    solana_sdk::declare_id!("GccsdEG75Py5Wzb8bTjHPwRY6n6W9jyjL3hXjkwP2hED");
+}
+
+pub mod vote_state_update {
+    solana_sdk::declare_id!("9Gy3FwkEtEp3aquEyeFDrFZLaqEU5vZmchirLeEsHYx2");
+}
+
 lazy_static! {
     /// Map of feature identifiers to user-visible description
     pub static ref FEATURE_NAMES: HashMap<Pubkey, &'static str> = [
@@ -119,6 +159,16 @@ lazy_static! {
         (timestamp_bounding::id(), "add timestamp-correction bounding #13120"),
         (stake_program_v2::id(), "solana_stake_program v2"),
         (rewrite_stake::id(), "rewrite stake"),
+        (bpf_loader_upgradeable_program::id(), "upgradeable bpf loader"),
+        (sysvar_syscalls::id(), "sysvar syscalls"),
+        (return_data_syscall_enabled::id(), "enable sol_{set,get}_return_data syscall"),
+        (transaction_wide_compute_cap::id(), "transaction-wide compute cap"),
+        (address_lookup_table_program::id(), "address lookup table program"),
+        (stake_redelegate_instruction::id(), "enable the redelegate stake instruction"),
+        (vote_state_update::id(), "vote state updates replace per-slot vote instructions"),
+        (ed25519_program_enabled::id(), "ed25519 program"),
+        (log_data_syscall_enabled::id(), "enable sol_log_data syscall"),
+        (validator_info_schema::id(), "validate validator info against its schema"),
         /*************** ADD NEW FEATURES HERE ***************/
     ]
     .iter()
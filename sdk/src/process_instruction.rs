@@ -9,7 +9,7 @@ use solana_sdk::{
     message::Message,
     pubkey::Pubkey,
 };
-use std::{cell::RefCell, fmt::Debug, rc::Rc, sync::Arc};
+use std::{cell::RefCell, collections::HashSet, fmt::Debug, rc::Rc, sync::Arc};
 
 // Prototype of a native loader entry point
 ///
@@ -61,6 +61,12 @@ pub trait InvokeContext {
     fn record_instruction(&self, instruction: &Instruction);
     /// Get the bank's active feature set
     fn is_feature_active(&self, feature_id: &Pubkey) -> bool;
+    /// Get the serialized data of a sysvar account
+    fn get_sysvar_data(&self, id: &Pubkey) -> Option<&[u8]>;
+    /// Set the return data of the currently executing program
+    fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError>;
+    /// Get the most recently set return data and the id of the program that set it
+    fn get_return_data(&self) -> (Pubkey, &[u8]);
 }
 
 #[derive(Clone, Copy, Debug, AbiExample)]
@@ -89,6 +95,15 @@ pub struct BpfComputeBudget {
     pub stack_frame_size: usize,
     /// Number of compute units consumed by logging a `Pubkey`
     pub log_pubkey_units: u64,
+    /// Number of compute units consumed to get a sysvar (not including the
+    /// size of the sysvar)
+    pub sysvar_base_cost: u64,
+    /// Number of compute units consumed to set or get return data (not
+    /// including the size of the data)
+    pub return_data_base_cost: u64,
+    /// Number of compute units consumed per byte of data logged by a log_data call, in addition
+    /// to `log_units`
+    pub log_data_byte_cost: u64,
 }
 impl Default for BpfComputeBudget {
     fn default() -> Self {
@@ -111,6 +126,9 @@ impl BpfComputeBudget {
             max_call_depth: 20,
             stack_frame_size: 4_096,
             log_pubkey_units: 0,
+            sysvar_base_cost: 100,
+            return_data_base_cost: 100,
+            log_data_byte_cost: 1,
         };
 
         if feature_set.is_active(&bpf_compute_budget_balancing::id()) {
@@ -163,6 +181,10 @@ pub trait Logger {
     /// Unless explicitly stated, log messages are not considered stable and may change in the
     /// future as necessary
     fn log(&self, message: &str);
+
+    /// Record a structured event a program logged with `sol_log_data`. Events are kept even if
+    /// the log messages are truncated, up to a size limit of their own
+    fn log_event(&self, _program_id: &Pubkey, _data: &[&[u8]]) {}
 }
 
 ///
@@ -203,6 +225,27 @@ pub mod stable_log {
         }
     }
 
+    /// Log structured data from the program itself, as a list of byte slices.
+    ///
+    /// The general form is:
+    ///     "Program data: <base64 slice> <base64 slice> ..."
+    /// That is, each slice is base64-encoded and separated from the next by a single space.
+    /// The data is also recorded as an event of `program_id`
+    pub fn program_data(logger: &Rc<RefCell<dyn Logger>>, program_id: &Pubkey, data: &[&[u8]]) {
+        if let Ok(logger) = logger.try_borrow_mut() {
+            logger.log_event(program_id, data);
+            if logger.log_enabled() {
+                logger.log(&format!(
+                    "Program data: {}",
+                    data.iter()
+                        .map(base64::encode)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ));
+            }
+        }
+    }
+
     /// Log successful program execution.
     ///
     /// The general form is:
@@ -281,6 +324,9 @@ pub struct MockInvokeContext {
     pub bpf_compute_budget: BpfComputeBudget,
     pub compute_meter: MockComputeMeter,
     pub programs: Vec<(Pubkey, ProcessInstructionWithContext)>,
+    pub sysvars: Vec<(Pubkey, Vec<u8>)>,
+    pub return_data: (Pubkey, Vec<u8>),
+    pub disabled_features: HashSet<Pubkey>,
     invoke_depth: usize,
 }
 impl Default for MockInvokeContext {
@@ -293,6 +339,9 @@ impl Default for MockInvokeContext {
                 remaining: std::i64::MAX as u64,
             },
             programs: vec![],
+            sysvars: vec![],
+            return_data: (Pubkey::default(), vec![]),
+            disabled_features: HashSet::default(),
             invoke_depth: 0,
         }
     }
@@ -336,7 +385,20 @@ impl InvokeContext for MockInvokeContext {
         None
     }
     fn record_instruction(&self, _instruction: &Instruction) {}
-    fn is_feature_active(&self, _feature_id: &Pubkey) -> bool {
-        true
+    fn is_feature_active(&self, feature_id: &Pubkey) -> bool {
+        !self.disabled_features.contains(feature_id)
+    }
+    fn get_sysvar_data(&self, id: &Pubkey) -> Option<&[u8]> {
+        self.sysvars
+            .iter()
+            .find(|(key, _)| key == id)
+            .map(|(_, data)| data.as_slice())
+    }
+    fn set_return_data(&mut self, data: Vec<u8>) -> Result<(), InstructionError> {
+        self.return_data = (self.key, data);
+        Ok(())
+    }
+    fn get_return_data(&self) -> (Pubkey, &[u8]) {
+        (self.return_data.0, &self.return_data.1)
     }
 }
@@ -4,7 +4,7 @@
 use crate::{pubkey::Pubkey, transaction::TransactionError};
 use ed25519_dalek::Signer as DalekSigner;
 use generic_array::{typenum::U64, GenericArray};
-use hmac::Hmac;
+use hmac::{Hmac, Mac};
 use itertools::Itertools;
 use rand::{rngs::OsRng, CryptoRng, RngCore};
 use std::{
@@ -388,10 +388,35 @@ pub fn keypair_from_seed(seed: &[u8]) -> Result<Keypair, Box<dyn error::Error>>
     Ok(Keypair(dalek_keypair))
 }
 
-pub fn keypair_from_seed_phrase_and_passphrase(
-    seed_phrase: &str,
-    passphrase: &str,
+/// Derive the keypair at `path` below `seed`, as specified by SLIP-0010 for ed25519. Every
+/// index of the path must be hardened.
+pub fn keypair_from_seed_and_derivation_path(
+    seed: &[u8],
+    path: &[u32],
 ) -> Result<Keypair, Box<dyn error::Error>> {
+    const HARDENED_BIT: u32 = 1 << 31;
+
+    let hmac_sha512 = |key: &[u8], data: &[u8]| {
+        let mut mac = Hmac::<sha2::Sha512>::new_varkey(key).unwrap();
+        mac.input(data);
+        mac.result().code()
+    };
+    let mut node = hmac_sha512(b"ed25519 seed", seed);
+    for index in path {
+        if index & HARDENED_BIT == 0 {
+            return Err(format!("Derivation path index {} is not hardened", index).into());
+        }
+        let mut data = Vec::with_capacity(37);
+        data.push(0);
+        data.extend_from_slice(&node[..32]);
+        data.extend_from_slice(&index.to_be_bytes());
+        node = hmac_sha512(&node[32..], &data);
+    }
+    keypair_from_seed(&node[..32])
+}
+
+/// Return the BIP39 seed of a seed phrase and passphrase, without validating the phrase
+pub fn seed_from_seed_phrase_and_passphrase(seed_phrase: &str, passphrase: &str) -> Vec<u8> {
     const PBKDF2_ROUNDS: usize = 2048;
     const PBKDF2_BYTES: usize = 64;
 
@@ -404,7 +429,17 @@ pub fn keypair_from_seed_phrase_and_passphrase(
         PBKDF2_ROUNDS,
         &mut seed,
     );
-    keypair_from_seed(&seed[..])
+    seed
+}
+
+pub fn keypair_from_seed_phrase_and_passphrase(
+    seed_phrase: &str,
+    passphrase: &str,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    keypair_from_seed(&seed_from_seed_phrase_and_passphrase(
+        seed_phrase,
+        passphrase,
+    ))
 }
 
 #[cfg(test)]
@@ -489,6 +524,31 @@ mod tests {
         assert!(keypair_from_seed(&too_short_seed).is_err());
     }
 
+    #[test]
+    fn test_keypair_from_seed_and_derivation_path() {
+        // SLIP-0010 ed25519 test vector 1
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let hardened = |index: u32| index | (1 << 31);
+
+        let keypair = keypair_from_seed_and_derivation_path(&seed, &[]).unwrap();
+        assert_eq!(
+            hex::encode(keypair.pubkey()),
+            "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed"
+        );
+        let keypair =
+            keypair_from_seed_and_derivation_path(&seed, &[hardened(0), hardened(1)]).unwrap();
+        assert_eq!(
+            hex::encode(&keypair.to_bytes()[..32]),
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2"
+        );
+        assert_eq!(
+            hex::encode(keypair.pubkey()),
+            "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187"
+        );
+
+        assert!(keypair_from_seed_and_derivation_path(&seed, &[0]).is_err());
+    }
+
     #[test]
     fn test_signature_fromstr() {
         let signature = Keypair::new().sign_message(&[0u8]);
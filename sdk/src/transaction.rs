@@ -2,12 +2,13 @@
 
 #![cfg(feature = "full")]
 
+use crate::ed25519_instruction::verify_signatures;
 use crate::sanitize::{Sanitize, SanitizeError};
 use crate::secp256k1_instruction::verify_eth_addresses;
 use crate::{
     hash::Hash,
     instruction::{CompiledInstruction, Instruction, InstructionError},
-    message::Message,
+    message::{Message, VersionedMessage},
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     short_vec,
@@ -92,6 +93,22 @@ pub enum TransactionError {
 
     #[error("Transactions are currently disabled due to cluster maintenance")]
     ClusterMaintenance,
+
+    /// Transaction loads an address lookup table account that does not exist
+    #[error("Transaction loads an address table account that doesn't exist")]
+    AddressLookupTableNotFound,
+
+    /// Transaction loads an address lookup table account with an invalid owner
+    #[error("Transaction loads an address table account with an invalid owner")]
+    InvalidAddressLookupTableOwner,
+
+    /// Transaction loads an address lookup table account with invalid data
+    #[error("Transaction loads an address table account with invalid data")]
+    InvalidAddressLookupTableData,
+
+    /// Transaction references an address lookup table index that does not exist
+    #[error("Transaction address table lookup uses an invalid index")]
+    InvalidAddressLookupTableIndex,
 }
 
 pub type Result<T> = result::Result<T, TransactionError>;
@@ -336,26 +353,17 @@ impl Transaction {
         }
     }
 
-    pub fn verify_precompiles(&self) -> Result<()> {
-        for instruction in &self.message().instructions {
-            // The Transaction may not be sanitized at this point
-            if instruction.program_id_index as usize >= self.message().account_keys.len() {
-                return Err(TransactionError::AccountNotFound);
-            }
-            let program_id = &self.message().account_keys[instruction.program_id_index as usize];
-            if crate::secp256k1_program::check_id(program_id) {
-                let instruction_datas: Vec<_> = self
-                    .message()
-                    .instructions
-                    .iter()
-                    .map(|instruction| instruction.data.as_ref())
-                    .collect();
-                let data = &instruction.data;
-                let e = verify_eth_addresses(data, &instruction_datas);
-                e.map_err(|_| TransactionError::InvalidAccountIndex)?;
-            }
-        }
-        Ok(())
+    pub fn verify_precompiles(
+        &self,
+        secp256k1_program_enabled: bool,
+        ed25519_program_enabled: bool,
+    ) -> Result<()> {
+        verify_precompiles(
+            &self.message().account_keys,
+            &self.message().instructions,
+            secp256k1_program_enabled,
+            ed25519_program_enabled,
+        )
     }
 
     /// Get the positions of the pubkeys in `account_keys` associated with signing keypairs
@@ -400,6 +408,153 @@ impl Transaction {
     }
 }
 
+/// A transaction whose message is either in the legacy format or in a versioned format which
+/// may load accounts from address lookup tables
+#[derive(Debug, PartialEq, Default, Eq, Clone, Serialize, Deserialize)]
+pub struct VersionedTransaction {
+    /// Signatures of the serialized `message`, signed by the first
+    /// `num_required_signatures` of the message's own `account_keys`
+    #[serde(with = "short_vec")]
+    pub signatures: Vec<Signature>,
+
+    /// The message to sign.
+    pub message: VersionedMessage,
+}
+
+impl Sanitize for VersionedTransaction {
+    fn sanitize(&self) -> std::result::Result<(), SanitizeError> {
+        if self.message.header().num_required_signatures as usize > self.signatures.len() {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        if self.signatures.len() > self.message.static_account_keys().len() {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        self.message.sanitize()
+    }
+}
+
+impl From<Transaction> for VersionedTransaction {
+    fn from(transaction: Transaction) -> Self {
+        Self {
+            signatures: transaction.signatures,
+            message: VersionedMessage::Legacy(transaction.message),
+        }
+    }
+}
+
+impl VersionedTransaction {
+    /// Sign `message` with every one of its required signers
+    pub fn try_new<T: Signers>(
+        message: VersionedMessage,
+        keypairs: &T,
+    ) -> result::Result<Self, SignerError> {
+        let num_required_signatures = message.header().num_required_signatures as usize;
+        let signer_keys = &message.static_account_keys()[..num_required_signatures];
+        let pubkeys = keypairs.pubkeys();
+        if pubkeys.len() < num_required_signatures {
+            return Err(SignerError::NotEnoughSigners);
+        }
+        if pubkeys.len() > num_required_signatures
+            || signer_keys.iter().any(|key| !pubkeys.contains(key))
+        {
+            return Err(SignerError::KeypairPubkeyMismatch);
+        }
+
+        let signatures = keypairs.try_sign_message(&message.serialize())?;
+        let mut ordered_signatures = vec![Signature::default(); num_required_signatures];
+        for (pubkey, signature) in pubkeys.iter().zip(signatures) {
+            let position = signer_keys.iter().position(|key| key == pubkey).unwrap();
+            ordered_signatures[position] = signature;
+        }
+        Ok(Self {
+            signatures: ordered_signatures,
+            message,
+        })
+    }
+
+    pub fn verify_with_results(&self) -> Vec<bool> {
+        let message_data = self.message.serialize();
+        self.signatures
+            .iter()
+            .zip(self.message.static_account_keys())
+            .map(|(signature, pubkey)| signature.verify(pubkey.as_ref(), &message_data))
+            .collect()
+    }
+
+    /// Verify the transaction
+    pub fn verify(&self) -> Result<()> {
+        if !self
+            .verify_with_results()
+            .iter()
+            .all(|verify_result| *verify_result)
+        {
+            Err(TransactionError::SignatureFailure)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verify the precompile instructions, whose programs are always stored in the message
+    /// itself rather than loaded from address lookup tables
+    pub fn verify_precompiles(
+        &self,
+        secp256k1_program_enabled: bool,
+        ed25519_program_enabled: bool,
+    ) -> Result<()> {
+        verify_precompiles(
+            self.message.static_account_keys(),
+            self.message.instructions(),
+            secp256k1_program_enabled,
+            ed25519_program_enabled,
+        )
+    }
+
+    /// Returns the legacy transaction, if this transaction's message is in the legacy format
+    pub fn into_legacy_transaction(self) -> Option<Transaction> {
+        match self.message {
+            VersionedMessage::Legacy(message) => Some(Transaction {
+                signatures: self.signatures,
+                message,
+            }),
+            VersionedMessage::V0(_) => None,
+        }
+    }
+}
+
+/// Verify the instructions of a message which invoke the secp256k1 or ed25519 precompiles
+fn verify_precompiles(
+    account_keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+    secp256k1_program_enabled: bool,
+    ed25519_program_enabled: bool,
+) -> Result<()> {
+    for instruction in instructions {
+        // The Transaction may not be sanitized at this point
+        if instruction.program_id_index as usize >= account_keys.len() {
+            return Err(TransactionError::AccountNotFound);
+        }
+        let program_id = &account_keys[instruction.program_id_index as usize];
+        let is_secp256k1 =
+            secp256k1_program_enabled && crate::secp256k1_program::check_id(program_id);
+        let is_ed25519 = ed25519_program_enabled && crate::ed25519_program::check_id(program_id);
+        if is_secp256k1 || is_ed25519 {
+            let instruction_datas: Vec<_> = instructions
+                .iter()
+                .map(|instruction| instruction.data.as_ref())
+                .collect();
+            let data = &instruction.data;
+            if is_secp256k1 {
+                let e = verify_eth_addresses(data, &instruction_datas);
+                e.map_err(|_| TransactionError::InvalidAccountIndex)?;
+            } else {
+                let e = verify_signatures(data, &instruction_datas);
+                e.map_err(|_| TransactionError::InvalidAccountIndex)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn uses_durable_nonce(tx: &Transaction) -> Option<&CompiledInstruction> {
     let message = tx.message();
     message
@@ -431,6 +586,7 @@ mod tests {
     use crate::{
         hash::hash,
         instruction::AccountMeta,
+        message::{MessageAddressTableLookup, MessageV0},
         signature::{Keypair, Presigner, Signer},
         system_instruction,
     };
@@ -937,4 +1093,61 @@ mod tests {
         nonce_ix.accounts[0] = 255u8;
         assert_eq!(get_nonce_pubkey_from_instruction(&nonce_ix, &tx), None,);
     }
+
+    #[test]
+    fn test_versioned_transaction() {
+        let payer = Keypair::new();
+        let signer = Keypair::new();
+        let instruction = Instruction::new(
+            Pubkey::new_unique(),
+            &0,
+            vec![
+                AccountMeta::new(signer.pubkey(), true),
+                AccountMeta::new(Pubkey::new_unique(), false),
+            ],
+        );
+        let message = Message::new(&[instruction], Some(&payer.pubkey()));
+        let legacy_tx = Transaction::new(&[&payer, &signer], message, Hash::default());
+
+        // Legacy transactions keep their wire format and signatures
+        let versioned_tx = VersionedTransaction::from(legacy_tx.clone());
+        assert_eq!(
+            serialize(&versioned_tx).unwrap(),
+            serialize(&legacy_tx).unwrap()
+        );
+        assert_eq!(versioned_tx.verify(), Ok(()));
+        assert_eq!(
+            versioned_tx.into_legacy_transaction(),
+            Some(legacy_tx.clone())
+        );
+
+        let message = VersionedMessage::V0(MessageV0 {
+            header: legacy_tx.message.header.clone(),
+            account_keys: legacy_tx.message.account_keys.clone(),
+            recent_blockhash: legacy_tx.message.recent_blockhash,
+            instructions: legacy_tx.message.instructions.clone(),
+            address_table_lookups: vec![MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            }],
+        });
+        assert_eq!(
+            VersionedTransaction::try_new(message.clone(), &[&payer]),
+            Err(SignerError::NotEnoughSigners)
+        );
+        assert_eq!(
+            VersionedTransaction::try_new(message.clone(), &[&payer, &Keypair::new()]),
+            Err(SignerError::KeypairPubkeyMismatch)
+        );
+
+        // Signers may be given in any order
+        let versioned_tx = VersionedTransaction::try_new(message, &[&signer, &payer]).unwrap();
+        assert_eq!(versioned_tx.sanitize(), Ok(()));
+        assert_eq!(versioned_tx.verify(), Ok(()));
+        let deserialized_tx: VersionedTransaction =
+            deserialize(&serialize(&versioned_tx).unwrap()).unwrap();
+        assert_eq!(deserialized_tx, versioned_tx);
+        assert_eq!(versioned_tx.into_legacy_transaction(), None);
+    }
 }
@@ -1,5 +1,5 @@
 use crate::args::{
-    Args, AuthorizeArgs, Command, CountArgs, MoveArgs, NewArgs, QueryArgs, RebaseArgs,
+    Args, AuditArgs, AuthorizeArgs, Command, CountArgs, MoveArgs, NewArgs, QueryArgs, RebaseArgs,
     SetLockupArgs,
 };
 use clap::{value_t, value_t_or_exit, App, Arg, ArgMatches, SubCommand};
@@ -273,6 +273,63 @@ where
                 .arg(new_withdraw_authority_arg())
                 .arg(num_accounts_arg()),
         )
+        .subcommand(
+            SubCommand::with_name("audit")
+                .about("Compare derived and split stake accounts against their expected state")
+                .arg(base_pubkey_arg().index(1))
+                .arg(num_accounts_arg())
+                .arg(
+                    Arg::with_name("stake_authority")
+                        .long("stake-authority")
+                        .required(true)
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .help("Expected stake authority"),
+                )
+                .arg(
+                    Arg::with_name("withdraw_authority")
+                        .long("withdraw-authority")
+                        .required(true)
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .help("Expected withdraw authority"),
+                )
+                .arg(
+                    Arg::with_name("custodian")
+                        .long("custodian")
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .help("Expected lockup custodian"),
+                )
+                .arg(lockup_epoch_arg())
+                .arg(lockup_date_arg())
+                .arg(
+                    Arg::with_name("vote_account")
+                        .long("vote-account")
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .help("Vote account every stake account is expected to be delegated to"),
+                )
+                .arg(
+                    Arg::with_name("fee_payer")
+                        .long("fee-payer")
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .help("Fee payer of the fix-up transactions [default: withdraw authority]"),
+                )
+                .arg(
+                    Arg::with_name("output_path")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Write the reconciliation report to FILE instead of stdout"),
+                ),
+        )
         .get_matches_from(args)
 }
 
@@ -344,6 +401,21 @@ fn parse_move_args(matches: &ArgMatches<'_>) -> MoveArgs<String, String> {
     }
 }
 
+fn parse_audit_args(matches: &ArgMatches<'_>) -> AuditArgs<String> {
+    AuditArgs {
+        base_pubkey: value_t_or_exit!(matches, "base_pubkey", String),
+        num_accounts: value_t_or_exit!(matches, "num_accounts", usize),
+        fee_payer: value_t!(matches, "fee_payer", String).ok(),
+        stake_authority: value_t_or_exit!(matches, "stake_authority", String),
+        withdraw_authority: value_t_or_exit!(matches, "withdraw_authority", String),
+        custodian: value_t!(matches, "custodian", String).ok(),
+        lockup_epoch: value_t!(matches, "lockup_epoch", u64).ok(),
+        lockup_date: unix_timestamp_from_rfc3339_datetime(matches, "lockup_date"),
+        vote_account: value_t!(matches, "vote_account", String).ok(),
+        output_path: value_t!(matches, "output_path", String).ok(),
+    }
+}
+
 pub(crate) fn parse_args<I, T>(args: I) -> Args<String, String>
 where
     I: IntoIterator<Item = T>,
@@ -362,6 +434,7 @@ where
         ("set-lockup", Some(matches)) => Command::SetLockup(parse_set_lockup_args(matches)),
         ("rebase", Some(matches)) => Command::Rebase(parse_rebase_args(matches)),
         ("move", Some(matches)) => Command::Move(Box::new(parse_move_args(matches))),
+        ("audit", Some(matches)) => Command::Audit(parse_audit_args(matches)),
         _ => {
             eprintln!("{}", matches.usage());
             exit(1);
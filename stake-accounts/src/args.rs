@@ -63,6 +63,19 @@ pub(crate) struct MoveArgs<P, K> {
     pub authorize_args: AuthorizeArgs<P, K>,
 }
 
+pub(crate) struct AuditArgs<P> {
+    pub base_pubkey: P,
+    pub num_accounts: usize,
+    pub fee_payer: Option<P>,
+    pub stake_authority: P,
+    pub withdraw_authority: P,
+    pub custodian: Option<P>,
+    pub lockup_epoch: Option<Epoch>,
+    pub lockup_date: Option<UnixTimestamp>,
+    pub vote_account: Option<P>,
+    pub output_path: Option<String>,
+}
+
 pub(crate) enum Command<P, K> {
     New(NewArgs<P, K>),
     Count(CountArgs<P>),
@@ -72,6 +85,7 @@ pub(crate) enum Command<P, K> {
     SetLockup(SetLockupArgs<P, K>),
     Rebase(RebaseArgs<P, K>),
     Move(Box<MoveArgs<P, K>>),
+    Audit(AuditArgs<P>),
 }
 
 pub(crate) struct Args<P, K> {
@@ -213,6 +227,54 @@ fn resolve_rebase_args(
     Ok(resolved_args)
 }
 
+fn resolve_optional_pubkey(
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+    key_url: &Option<String>,
+    keypair_name: &str,
+) -> Result<Option<Pubkey>, Box<dyn Error>> {
+    let matches = ArgMatches::default();
+    let pubkey = match key_url {
+        None => None,
+        Some(key_url) => Some(pubkey_from_path(
+            &matches,
+            key_url,
+            keypair_name,
+            wallet_manager,
+        )?),
+    };
+    Ok(pubkey)
+}
+
+fn resolve_audit_args(
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+    args: &AuditArgs<String>,
+) -> Result<AuditArgs<Pubkey>, Box<dyn Error>> {
+    let matches = ArgMatches::default();
+    let resolved_args = AuditArgs {
+        base_pubkey: resolve_base_pubkey(wallet_manager, &args.base_pubkey)?,
+        num_accounts: args.num_accounts,
+        fee_payer: resolve_optional_pubkey(wallet_manager, &args.fee_payer, "fee-payer")?,
+        stake_authority: pubkey_from_path(
+            &matches,
+            &args.stake_authority,
+            "stake authority",
+            wallet_manager,
+        )?,
+        withdraw_authority: pubkey_from_path(
+            &matches,
+            &args.withdraw_authority,
+            "withdraw authority",
+            wallet_manager,
+        )?,
+        custodian: resolve_optional_pubkey(wallet_manager, &args.custodian, "custodian")?,
+        lockup_epoch: args.lockup_epoch,
+        lockup_date: args.lockup_date,
+        vote_account: resolve_optional_pubkey(wallet_manager, &args.vote_account, "vote account")?,
+        output_path: args.output_path.clone(),
+    };
+    Ok(resolved_args)
+}
+
 pub(crate) fn resolve_command(
     command: &Command<String, String>,
 ) -> Result<Command<Pubkey, Box<dyn Signer>>, Box<dyn Error>> {
@@ -290,5 +352,9 @@ pub(crate) fn resolve_command(
             };
             Ok(Command::Move(Box::new(resolved_args)))
         }
+        Command::Audit(args) => {
+            let resolved_args = resolve_audit_args(&mut wallet_manager, &args)?;
+            Ok(Command::Audit(resolved_args))
+        }
     }
 }
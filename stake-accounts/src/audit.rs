@@ -0,0 +1,638 @@
+use crate::stake_accounts::{apply_lockup_changes, derive_stake_account_address};
+use serde::Serialize;
+use solana_client::partially_signed_transaction::PartiallySignedTransaction;
+use solana_sdk::{
+    account::Account, clock::Clock, hash::Hash, instruction::Instruction, message::Message,
+    pubkey::Pubkey,
+};
+use solana_stake_program::{
+    stake_instruction::{self, LockupArgs},
+    stake_state::{Meta, Stake, StakeAuthorize, StakeState},
+};
+use std::collections::BTreeMap;
+
+/// Offset of `Authorized::staker` in the data of an initialized or delegated stake account
+pub(crate) const STAKER_OFFSET: usize = 12;
+
+/// Offset of `Authorized::withdrawer` in the data of an initialized or delegated stake account
+pub(crate) const WITHDRAWER_OFFSET: usize = 44;
+
+/// The state every audited stake account should be in
+pub(crate) struct ExpectedStakeState {
+    pub staker: Pubkey,
+    pub withdrawer: Pubkey,
+    pub lockup: LockupArgs,
+    pub voter: Option<Pubkey>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub(crate) enum Drift {
+    /// No account exists at a derived address
+    Missing,
+    /// The account exists but holds no stake state
+    Uninitialized,
+    StakeAuthority {
+        expected: String,
+        actual: String,
+    },
+    WithdrawAuthority {
+        expected: String,
+        actual: String,
+    },
+    LockupCustodian {
+        expected: String,
+        actual: String,
+    },
+    LockupUnixTimestamp {
+        expected: i64,
+        actual: i64,
+    },
+    LockupEpoch {
+        expected: u64,
+        actual: u64,
+    },
+    Delegation {
+        expected: String,
+        actual: Option<String>,
+        deactivated: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditedStakeAccount {
+    pub address: String,
+    /// Seed index of a derived account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// The account is not derived from the base pubkey, but has the same authorities as one that
+    /// is, most likely because it was split from it
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub untracked: bool,
+    pub lamports: u64,
+    pub drift: Vec<Drift>,
+    /// Unsigned transaction in the format read by `solana tx sign`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixup: Option<PartiallySignedTransaction>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditReport {
+    pub base_pubkey: String,
+    /// Blockhash the fix-up transactions were built with; rerun the audit if it has expired
+    pub recent_blockhash: String,
+    pub accounts: Vec<AuditedStakeAccount>,
+}
+
+impl AuditReport {
+    pub fn drifted(&self) -> usize {
+        self.accounts
+            .iter()
+            .filter(|account| !account.drift.is_empty())
+            .count()
+    }
+
+    pub fn fixups(&self) -> usize {
+        self.accounts
+            .iter()
+            .filter(|account| account.fixup.is_some())
+            .count()
+    }
+}
+
+fn audit_delegation(
+    address: &Pubkey,
+    meta: &Meta,
+    stake: Option<&Stake>,
+    voter: &Pubkey,
+) -> Option<(Drift, Instruction)> {
+    let staker = &meta.authorized.staker;
+    match stake {
+        None => Some((
+            Drift::Delegation {
+                expected: voter.to_string(),
+                actual: None,
+                deactivated: false,
+            },
+            stake_instruction::delegate_stake(address, staker, voter),
+        )),
+        Some(stake) => {
+            let delegation = &stake.delegation;
+            let deactivated = delegation.deactivation_epoch != std::u64::MAX;
+            if delegation.voter_pubkey == *voter && !deactivated {
+                return None;
+            }
+            // Active stake must cool down before it can be delegated elsewhere
+            let instruction = if deactivated {
+                stake_instruction::delegate_stake(address, staker, voter)
+            } else {
+                stake_instruction::deactivate_stake(address, staker)
+            };
+            Some((
+                Drift::Delegation {
+                    expected: voter.to_string(),
+                    actual: Some(delegation.voter_pubkey.to_string()),
+                    deactivated,
+                },
+                instruction,
+            ))
+        }
+    }
+}
+
+/// Return the lockup drift, and the instruction that fixes it unless the lockup is in force
+/// without a custodian, in which case nobody can change it until it expires
+fn audit_lockup(
+    address: &Pubkey,
+    meta: &Meta,
+    expected_lockup: &LockupArgs,
+    clock: &Clock,
+) -> Option<(Vec<Drift>, Option<Instruction>)> {
+    let lockup = &meta.lockup;
+    let changes = apply_lockup_changes(expected_lockup, lockup);
+    if changes == LockupArgs::default() {
+        return None;
+    }
+    let mut drift = vec![];
+    if let Some(custodian) = changes.custodian {
+        drift.push(Drift::LockupCustodian {
+            expected: custodian.to_string(),
+            actual: lockup.custodian.to_string(),
+        });
+    }
+    if let Some(unix_timestamp) = changes.unix_timestamp {
+        drift.push(Drift::LockupUnixTimestamp {
+            expected: unix_timestamp,
+            actual: lockup.unix_timestamp,
+        });
+    }
+    if let Some(epoch) = changes.epoch {
+        drift.push(Drift::LockupEpoch {
+            expected: epoch,
+            actual: lockup.epoch,
+        });
+    }
+    // Only the custodian may change a lockup that is in force
+    let authority = if !lockup.is_in_force(clock, None) {
+        &meta.authorized.withdrawer
+    } else if lockup.custodian != Pubkey::default() {
+        &lockup.custodian
+    } else {
+        return Some((drift, None));
+    };
+    let instruction = stake_instruction::set_lockup(address, &changes, authority);
+    Some((drift, Some(instruction)))
+}
+
+fn audit_authorities(
+    address: &Pubkey,
+    meta: &Meta,
+    expected: &ExpectedStakeState,
+) -> (Vec<Drift>, Vec<Instruction>) {
+    let authorized = &meta.authorized;
+    let mut drift = vec![];
+    let mut instructions = vec![];
+    if authorized.staker != expected.staker {
+        drift.push(Drift::StakeAuthority {
+            expected: expected.staker.to_string(),
+            actual: authorized.staker.to_string(),
+        });
+        instructions.push(stake_instruction::authorize(
+            address,
+            &authorized.staker,
+            &expected.staker,
+            StakeAuthorize::Staker,
+        ));
+    }
+    if authorized.withdrawer != expected.withdrawer {
+        drift.push(Drift::WithdrawAuthority {
+            expected: expected.withdrawer.to_string(),
+            actual: authorized.withdrawer.to_string(),
+        });
+        instructions.push(stake_instruction::authorize(
+            address,
+            &authorized.withdrawer,
+            &expected.withdrawer,
+            StakeAuthorize::Withdrawer,
+        ));
+    }
+    (drift, instructions)
+}
+
+pub(crate) fn audit_stake_account(
+    address: &Pubkey,
+    index: Option<usize>,
+    account: Option<&Account>,
+    expected: &ExpectedStakeState,
+    clock: &Clock,
+    fee_payer_pubkey: &Pubkey,
+    recent_blockhash: &Hash,
+) -> AuditedStakeAccount {
+    let mut drift = vec![];
+    let untracked = index.is_none();
+    let lamports = account.map(|account| account.lamports).unwrap_or_default();
+    let state = account.and_then(StakeState::from);
+    let (meta, stake) = match state {
+        Some(StakeState::Initialized(meta)) => (meta, None),
+        Some(StakeState::Stake(meta, stake)) => (meta, Some(stake)),
+        _ => {
+            drift.push(if account.is_none() {
+                Drift::Missing
+            } else {
+                Drift::Uninitialized
+            });
+            return AuditedStakeAccount {
+                address: address.to_string(),
+                index,
+                untracked,
+                lamports,
+                drift,
+                fixup: None,
+            };
+        }
+    };
+
+    // Authorities are changed last so that the other fixes are signed by the current ones
+    let mut instructions = vec![];
+    if let Some(voter) = &expected.voter {
+        if let Some((delegation_drift, instruction)) =
+            audit_delegation(address, &meta, stake.as_ref(), voter)
+        {
+            drift.push(delegation_drift);
+            instructions.push(instruction);
+        }
+    }
+    if let Some((lockup_drift, instruction)) = audit_lockup(address, &meta, &expected.lockup, clock)
+    {
+        drift.extend(lockup_drift);
+        instructions.extend(instruction);
+    }
+    let (authority_drift, authority_instructions) = audit_authorities(address, &meta, expected);
+    drift.extend(authority_drift);
+    instructions.extend(authority_instructions);
+
+    let fixup = if instructions.is_empty() {
+        None
+    } else {
+        let mut message = Message::new(&instructions, Some(fee_payer_pubkey));
+        message.recent_blockhash = *recent_blockhash;
+        Some(PartiallySignedTransaction::from(&message))
+    };
+    AuditedStakeAccount {
+        address: address.to_string(),
+        index,
+        untracked,
+        lamports,
+        drift,
+        fixup,
+    }
+}
+
+/// Audit the first `num_accounts` derived stake accounts, followed by every other account in
+/// `accounts`, which should hold the derived accounts and any found by authority
+pub(crate) fn audit_stake_accounts(
+    base_pubkey: &Pubkey,
+    num_accounts: usize,
+    accounts: &BTreeMap<Pubkey, Account>,
+    expected: &ExpectedStakeState,
+    clock: &Clock,
+    fee_payer_pubkey: &Pubkey,
+    recent_blockhash: &Hash,
+) -> AuditReport {
+    let mut derived_addresses = vec![];
+    let mut audited_accounts = vec![];
+    for i in 0..num_accounts {
+        let address = derive_stake_account_address(base_pubkey, i);
+        audited_accounts.push(audit_stake_account(
+            &address,
+            Some(i),
+            accounts.get(&address),
+            expected,
+            clock,
+            fee_payer_pubkey,
+            recent_blockhash,
+        ));
+        derived_addresses.push(address);
+    }
+    for (address, account) in accounts {
+        if derived_addresses.contains(address) {
+            continue;
+        }
+        audited_accounts.push(audit_stake_account(
+            address,
+            None,
+            Some(account),
+            expected,
+            clock,
+            fee_payer_pubkey,
+            recent_blockhash,
+        ));
+    }
+    AuditReport {
+        base_pubkey: base_pubkey.to_string(),
+        recent_blockhash: recent_blockhash.to_string(),
+        accounts: audited_accounts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stake_accounts::new_stake_account;
+    use solana_client::partially_signed_transaction::PartialSignature;
+    use solana_runtime::{bank::Bank, bank_client::BankClient};
+    use solana_sdk::{
+        client::SyncClient,
+        genesis_config::create_genesis_config,
+        signature::{Keypair, Signer},
+    };
+    use solana_stake_program::stake_state::{Authorized, Lockup};
+
+    fn fixup_message(audited_account: &AuditedStakeAccount) -> Message {
+        let fixup = audited_account.fixup.as_ref().unwrap();
+        fixup.to_transaction().unwrap().message
+    }
+
+    #[test]
+    fn test_authority_offsets() {
+        let staker = solana_sdk::pubkey::new_rand();
+        let withdrawer = solana_sdk::pubkey::new_rand();
+        let state = StakeState::Initialized(Meta {
+            authorized: Authorized { staker, withdrawer },
+            ..Meta::default()
+        });
+        let data = bincode::serialize(&state).unwrap();
+        assert_eq!(&data[STAKER_OFFSET..STAKER_OFFSET + 32], staker.as_ref());
+        assert_eq!(
+            &data[WITHDRAWER_OFFSET..WITHDRAWER_OFFSET + 32],
+            withdrawer.as_ref()
+        );
+    }
+
+    #[test]
+    fn test_audit_missing_and_uninitialized() {
+        let expected = ExpectedStakeState {
+            staker: Pubkey::default(),
+            withdrawer: Pubkey::default(),
+            lockup: LockupArgs::default(),
+            voter: None,
+        };
+        let base_pubkey = solana_sdk::pubkey::new_rand();
+        let mut accounts = BTreeMap::new();
+        accounts.insert(
+            derive_stake_account_address(&base_pubkey, 1),
+            Account::new(1, 0, &solana_stake_program::id()),
+        );
+        let report = audit_stake_accounts(
+            &base_pubkey,
+            2,
+            &accounts,
+            &expected,
+            &Clock::default(),
+            &Pubkey::default(),
+            &Hash::default(),
+        );
+        assert_eq!(report.accounts.len(), 2);
+        assert_eq!(report.accounts[0].drift, vec![Drift::Missing]);
+        assert_eq!(report.accounts[1].drift, vec![Drift::Uninitialized]);
+        assert_eq!(report.accounts[1].lamports, 1);
+        assert_eq!(report.drifted(), 2);
+        assert_eq!(report.fixups(), 0);
+    }
+
+    #[test]
+    fn test_audit_lockup_in_force() {
+        let address = solana_sdk::pubkey::new_rand();
+        let withdrawer = solana_sdk::pubkey::new_rand();
+        let custodian = solana_sdk::pubkey::new_rand();
+        let meta = Meta {
+            authorized: Authorized {
+                staker: withdrawer,
+                withdrawer,
+            },
+            lockup: Lockup {
+                unix_timestamp: 10,
+                epoch: 0,
+                custodian,
+            },
+            ..Meta::default()
+        };
+        let expected_lockup = LockupArgs {
+            unix_timestamp: Some(20),
+            ..LockupArgs::default()
+        };
+
+        let clock = Clock {
+            unix_timestamp: 5,
+            ..Clock::default()
+        };
+        let (drift, instruction) = audit_lockup(&address, &meta, &expected_lockup, &clock).unwrap();
+        assert_eq!(
+            drift,
+            vec![Drift::LockupUnixTimestamp {
+                expected: 20,
+                actual: 10
+            }]
+        );
+        assert_eq!(instruction.unwrap().accounts[1].pubkey, custodian);
+
+        // Without a custodian, a lockup in force can't be changed
+        let no_custodian = Meta {
+            lockup: Lockup {
+                custodian: Pubkey::default(),
+                ..meta.lockup
+            },
+            ..meta
+        };
+        let (drift, instruction) =
+            audit_lockup(&address, &no_custodian, &expected_lockup, &clock).unwrap();
+        assert_eq!(drift.len(), 1);
+        assert_eq!(instruction, None);
+
+        let clock = Clock {
+            unix_timestamp: 15,
+            ..Clock::default()
+        };
+        let (_, instruction) = audit_lockup(&address, &meta, &expected_lockup, &clock).unwrap();
+        assert_eq!(instruction.unwrap().accounts[1].pubkey, withdrawer);
+        let (_, instruction) =
+            audit_lockup(&address, &no_custodian, &expected_lockup, &clock).unwrap();
+        assert_eq!(instruction.unwrap().accounts[1].pubkey, withdrawer);
+    }
+
+    #[test]
+    fn test_audit_delegation() {
+        let address = solana_sdk::pubkey::new_rand();
+        let voter = solana_sdk::pubkey::new_rand();
+        let meta = Meta::default();
+        let (drift, instruction) = audit_delegation(&address, &meta, None, &voter).unwrap();
+        assert_eq!(
+            drift,
+            Drift::Delegation {
+                expected: voter.to_string(),
+                actual: None,
+                deactivated: false,
+            }
+        );
+        assert_eq!(
+            instruction,
+            stake_instruction::delegate_stake(&address, &meta.authorized.staker, &voter)
+        );
+
+        let mut stake = Stake::default();
+        stake.delegation.voter_pubkey = voter;
+        assert_eq!(
+            audit_delegation(&address, &meta, Some(&stake), &voter),
+            None
+        );
+
+        // Delegated elsewhere, so deactivate first
+        let other_voter = solana_sdk::pubkey::new_rand();
+        let (_, instruction) =
+            audit_delegation(&address, &meta, Some(&stake), &other_voter).unwrap();
+        assert_eq!(
+            instruction,
+            stake_instruction::deactivate_stake(&address, &meta.authorized.staker)
+        );
+
+        stake.delegation.deactivation_epoch = 1;
+        let (drift, instruction) = audit_delegation(&address, &meta, Some(&stake), &voter).unwrap();
+        assert_eq!(
+            drift,
+            Drift::Delegation {
+                expected: voter.to_string(),
+                actual: Some(voter.to_string()),
+                deactivated: true,
+            }
+        );
+        assert_eq!(
+            instruction,
+            stake_instruction::delegate_stake(&address, &meta.authorized.staker, &voter)
+        );
+    }
+
+    #[test]
+    fn test_audit_stake_accounts() {
+        let (genesis_config, funding_keypair) = create_genesis_config(10_000_000);
+        let bank = Bank::new(&genesis_config);
+        let rent = bank.get_minimum_balance_for_rent_exemption(std::mem::size_of::<StakeState>());
+        let bank_client = BankClient::new(bank);
+        let funding_pubkey = funding_keypair.pubkey();
+
+        let base_keypair = Keypair::new();
+        let base_pubkey = base_keypair.pubkey();
+        let stake_authority_keypair = Keypair::new();
+        let stake_authority_pubkey = stake_authority_keypair.pubkey();
+        let withdraw_authority_keypair = Keypair::new();
+        let withdraw_authority_pubkey = withdraw_authority_keypair.pubkey();
+
+        let message = new_stake_account(
+            &funding_pubkey,
+            &funding_pubkey,
+            &base_pubkey,
+            2 * rent + 2,
+            &stake_authority_pubkey,
+            &withdraw_authority_pubkey,
+            &Pubkey::default(),
+            0,
+        );
+        bank_client
+            .send_and_confirm_message(&[&funding_keypair, &base_keypair], message)
+            .unwrap();
+
+        // Split half of the derived account to an address the tool doesn't know about
+        let split_keypair = Keypair::new();
+        let split_pubkey = split_keypair.pubkey();
+        let instructions = stake_instruction::split(
+            &derive_stake_account_address(&base_pubkey, 0),
+            &stake_authority_pubkey,
+            rent + 1,
+            &split_pubkey,
+        );
+        let message = Message::new(&instructions, Some(&funding_pubkey));
+        bank_client
+            .send_and_confirm_message(
+                &[&funding_keypair, &stake_authority_keypair, &split_keypair],
+                message,
+            )
+            .unwrap();
+
+        let new_stake_authority_keypair = Keypair::new();
+        let expected = ExpectedStakeState {
+            staker: new_stake_authority_keypair.pubkey(),
+            withdrawer: withdraw_authority_pubkey,
+            lockup: LockupArgs::default(),
+            voter: None,
+        };
+        let get_accounts = || {
+            let mut accounts = BTreeMap::new();
+            for address in &[derive_stake_account_address(&base_pubkey, 0), split_pubkey] {
+                let account = bank_client.get_account(address).unwrap().unwrap();
+                accounts.insert(*address, account);
+            }
+            accounts
+        };
+        let report = audit_stake_accounts(
+            &base_pubkey,
+            1,
+            &get_accounts(),
+            &expected,
+            &Clock::default(),
+            &funding_pubkey,
+            &Hash::default(),
+        );
+        assert_eq!(report.accounts.len(), 2);
+        assert_eq!(report.accounts[0].index, Some(0));
+        assert_eq!(report.accounts[1].address, split_pubkey.to_string());
+        assert_eq!(report.accounts[1].index, None);
+        assert!(report.accounts[1].untracked);
+        assert_eq!(
+            report.accounts[1].drift,
+            vec![Drift::StakeAuthority {
+                expected: expected.staker.to_string(),
+                actual: stake_authority_pubkey.to_string(),
+            }]
+        );
+        assert_eq!(report.fixups(), 2);
+
+        let fixup = report.accounts[0].fixup.as_ref().unwrap();
+        assert_eq!(
+            fixup.signers,
+            vec![
+                PartialSignature {
+                    pubkey: funding_pubkey.to_string(),
+                    signature: None,
+                },
+                PartialSignature {
+                    pubkey: stake_authority_pubkey.to_string(),
+                    signature: None,
+                },
+            ]
+        );
+        for audited_account in &report.accounts {
+            bank_client
+                .send_and_confirm_message(
+                    &[&funding_keypair, &stake_authority_keypair],
+                    fixup_message(audited_account),
+                )
+                .unwrap();
+        }
+
+        // The split account is still untracked, but no longer drifts
+        let report = audit_stake_accounts(
+            &base_pubkey,
+            1,
+            &get_accounts(),
+            &expected,
+            &Clock::default(),
+            &funding_pubkey,
+            &Hash::default(),
+        );
+        assert_eq!(report.drifted(), 0);
+        assert!(report.accounts[1].untracked);
+        assert_eq!(report.accounts[1].drift, vec![]);
+        assert_eq!(report.fixups(), 0);
+    }
+}
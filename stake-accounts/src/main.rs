@@ -1,28 +1,41 @@
 mod arg_parser;
 mod args;
+mod audit;
 mod stake_accounts;
 
 use crate::arg_parser::parse_args;
 use crate::args::{
-    resolve_command, AuthorizeArgs, Command, MoveArgs, NewArgs, RebaseArgs, SetLockupArgs,
+    resolve_command, AuditArgs, AuthorizeArgs, Command, MoveArgs, NewArgs, RebaseArgs,
+    SetLockupArgs,
 };
+use crate::audit::{AuditReport, ExpectedStakeState, STAKER_OFFSET, WITHDRAWER_OFFSET};
+use solana_account_decoder::UiAccountEncoding;
 use solana_cli_config::Config;
 use solana_client::client_error::ClientError;
 use solana_client::rpc_client::RpcClient;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, MemcmpEncoding, RpcFilterType},
+};
 use solana_sdk::{
+    account::{from_account, Account},
+    clock::Clock,
     message::Message,
     native_token::lamports_to_sol,
     pubkey::Pubkey,
     signature::{unique_signers, Signature, Signer},
     signers::Signers,
+    sysvar,
     transaction::Transaction,
 };
 use solana_stake_program::{
     stake_instruction::LockupArgs,
     stake_state::{Lockup, StakeState},
 };
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::error::Error;
+use std::fs::File;
 
 fn get_balance_at(client: &RpcClient, pubkey: &Pubkey, i: usize) -> Result<u64, ClientError> {
     let address = stake_accounts::derive_stake_account_address(pubkey, i);
@@ -64,6 +77,88 @@ fn get_lockups(
         .collect()
 }
 
+// Return every stake account with the given stake and withdraw authorities
+fn get_stake_accounts_by_authorities(
+    client: &RpcClient,
+    staker: &Pubkey,
+    withdrawer: &Pubkey,
+) -> Result<Vec<(Pubkey, Account)>, ClientError> {
+    let memcmp = |offset, authority: &Pubkey| {
+        RpcFilterType::Memcmp(Memcmp {
+            offset,
+            bytes: MemcmpEncodedBytes::Binary(authority.to_string()),
+            encoding: Some(MemcmpEncoding::Binary),
+        })
+    };
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            memcmp(STAKER_OFFSET, staker),
+            memcmp(WITHDRAWER_OFFSET, withdrawer),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+    };
+    client.get_program_accounts_with_config(&solana_stake_program::id(), config)
+}
+
+fn process_audit_stake_accounts(
+    client: &RpcClient,
+    args: &AuditArgs<Pubkey>,
+) -> Result<AuditReport, Box<dyn Error>> {
+    let mut accounts = BTreeMap::new();
+    let addresses =
+        stake_accounts::derive_stake_account_addresses(&args.base_pubkey, args.num_accounts);
+    for address in addresses {
+        let account = client
+            .get_account_with_commitment(&address, client.commitment())?
+            .value;
+        if let Some(account) = account {
+            accounts.insert(address, account);
+        }
+    }
+
+    // Accounts split from a derived account keep both of its authorities, so search for those
+    // pairs. The expected authorities are not searched, as they may also hold stake accounts
+    // that have nothing to do with the base pubkey
+    let authority_pairs: BTreeSet<_> = accounts
+        .values()
+        .filter_map(StakeState::authorized_from)
+        .map(|authorized| (authorized.staker, authorized.withdrawer))
+        .collect();
+    for (staker, withdrawer) in &authority_pairs {
+        for (address, account) in get_stake_accounts_by_authorities(client, staker, withdrawer)? {
+            accounts.entry(address).or_insert(account);
+        }
+    }
+
+    let clock_account = client.get_account(&sysvar::clock::id())?;
+    let clock: Clock = from_account(&clock_account).ok_or("Failed to deserialize clock sysvar")?;
+    let (recent_blockhash, _fee_calculator) = client.get_recent_blockhash()?;
+
+    let expected = ExpectedStakeState {
+        staker: args.stake_authority,
+        withdrawer: args.withdraw_authority,
+        lockup: LockupArgs {
+            epoch: args.lockup_epoch,
+            unix_timestamp: args.lockup_date,
+            custodian: args.custodian,
+        },
+        voter: args.vote_account,
+    };
+    let fee_payer = args.fee_payer.unwrap_or(args.withdraw_authority);
+    Ok(audit::audit_stake_accounts(
+        &args.base_pubkey,
+        args.num_accounts,
+        &accounts,
+        &expected,
+        &clock,
+        &fee_payer,
+        &recent_blockhash,
+    ))
+}
+
 fn process_new_stake_account(
     client: &RpcClient,
     args: &NewArgs<Pubkey, Box<dyn Signer>>,
@@ -277,6 +372,23 @@ fn main() -> Result<(), Box<dyn Error>> {
         Command::Move(args) => {
             process_move_stake_accounts(&client, &args)?;
         }
+        Command::Audit(args) => {
+            let report = process_audit_stake_accounts(&client, &args)?;
+            match &args.output_path {
+                Some(output_path) => {
+                    let file = File::create(output_path)?;
+                    serde_json::to_writer_pretty(file, &report)?;
+                    eprintln!(
+                        "Audited {} accounts: {} drifted, {} fix-up transactions written to {}",
+                        report.accounts.len(),
+                        report.drifted(),
+                        report.fixups(),
+                        output_path,
+                    );
+                }
+                None => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+        }
     }
     Ok(())
 }
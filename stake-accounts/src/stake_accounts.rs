@@ -173,7 +173,7 @@ fn extend_lockup(lockup: &LockupArgs, years: f64) -> LockupArgs {
     }
 }
 
-fn apply_lockup_changes(lockup: &LockupArgs, existing_lockup: &Lockup) -> LockupArgs {
+pub(crate) fn apply_lockup_changes(lockup: &LockupArgs, existing_lockup: &Lockup) -> LockupArgs {
     let custodian = match lockup.custodian {
         Some(x) if x == existing_lockup.custodian => None,
         x => x,
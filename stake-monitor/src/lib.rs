@@ -10,7 +10,11 @@ use solana_stake_program::{stake_instruction::StakeInstruction, stake_state::Loc
 use solana_transaction_status::{
     EncodedConfirmedBlock, UiTransactionEncoding, UiTransactionStatusMeta,
 };
-use std::{collections::HashMap, thread::sleep, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    thread::sleep,
+    time::Duration,
+};
 
 pub type PubkeyString = String;
 pub type SignatureString = String;
@@ -24,6 +28,8 @@ pub enum AccountOperation {
     SystemAccountEnroll,
     FailedToMaintainMinimumBalance,
     MergeSource,
+    RedelegateSource,
+    RedelegateDestination,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -62,6 +68,15 @@ impl AccountsInfo {
             },
         );
     }
+
+    /// Addresses of the accounts that are not in compliance
+    pub fn noncompliant_accounts(&self) -> HashSet<PubkeyString> {
+        self.account_info
+            .iter()
+            .filter(|(_, account_info)| account_info.compliant_since.is_none())
+            .map(|(account_address, _)| account_address.clone())
+            .collect()
+    }
 }
 
 fn process_transaction(
@@ -216,6 +231,47 @@ fn process_transaction(
                             }
                         }
                     }
+                    StakeInstruction::Redelegate => {
+                        // Redelegate is permitted and propagates compliance to the new stake
+                        // account
+                        let source_stake_account_index = instruction.accounts[0] as usize;
+                        let redelegated_stake_account_index = instruction.accounts[1] as usize;
+
+                        let source_stake_pubkey =
+                            message.account_keys[source_stake_account_index].to_string();
+                        let redelegated_stake_pubkey =
+                            message.account_keys[redelegated_stake_account_index].to_string();
+
+                        // The amount moved is the redelegated stake account's balance change
+                        let lamports = meta.post_balances[redelegated_stake_account_index]
+                            .saturating_sub(meta.pre_balances[redelegated_stake_account_index]);
+
+                        if let Some(mut source_account_info) =
+                            accounts.get_mut(&source_stake_pubkey)
+                        {
+                            if source_account_info.compliant_since.is_some() {
+                                source_account_info
+                                    .transactions
+                                    .push(AccountTransactionInfo {
+                                        op: AccountOperation::RedelegateSource,
+                                        slot,
+                                        signature: signature.clone(),
+                                    });
+                                source_account_info.lamports -= lamports;
+
+                                let redelegated_account_info = AccountInfo {
+                                    compliant_since: source_account_info.compliant_since,
+                                    lamports,
+                                    transactions: vec![AccountTransactionInfo {
+                                        op: AccountOperation::RedelegateDestination,
+                                        slot,
+                                        signature: signature.clone(),
+                                    }],
+                                };
+                                accounts.insert(redelegated_stake_pubkey, redelegated_account_info);
+                            }
+                        }
+                    }
                     StakeInstruction::SetLockup(_lockup_args) => {
                         // No processing is required because SetLockup requires a custodian key,
                         // and this is already blocked in the StakeInstruction::Initialize
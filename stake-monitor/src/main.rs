@@ -9,9 +9,10 @@ use solana_clap_utils::{
 };
 use solana_client::rpc_client::RpcClient;
 use solana_metrics::datapoint_error;
+use solana_notifier::{Notification, Notifier, Severity};
 use solana_sdk::{clock::Slot, native_token::lamports_to_sol, pubkey::Pubkey, system_program};
 use solana_stake_monitor::*;
-use std::{fs, io, process};
+use std::{collections::HashSet, fs, io, process};
 
 fn load_accounts_info(data_file: &str) -> AccountsInfo {
     let data_file_new = data_file.to_owned() + "new";
@@ -32,6 +33,54 @@ fn save_accounts_info(data_file: &str, accounts_info: &AccountsInfo) -> io::Resu
     fs::rename(&data_file_new, data_file)
 }
 
+// Each account that falls out of compliance opens its own incident, which is resolved if the
+// account is in compliance again
+fn notify_compliance_changes(
+    notifier: &Notifier,
+    noncompliant_accounts: &HashSet<PubkeyString>,
+    accounts_info: &AccountsInfo,
+) {
+    for (account_address, account_info) in &accounts_info.account_info {
+        let compliant = account_info.compliant_since.is_some();
+        if compliant != noncompliant_accounts.contains(account_address) {
+            // No change since the last batch
+            continue;
+        }
+        let title = if compliant {
+            format!("{} is in compliance again", account_address)
+        } else {
+            format!("{} is not in compliance", account_address)
+        };
+        let details = account_info
+            .transactions
+            .last()
+            .map(|transaction| {
+                format!(
+                    "{:?} in slot {}: {}",
+                    transaction.op, transaction.slot, transaction.signature
+                )
+            })
+            .unwrap_or_default();
+        notifier.notify(&Notification {
+            id: format!("solana-stake-monitor/{}", account_address),
+            severity: Severity::Warning,
+            title,
+            details,
+            resolved: compliant,
+        });
+    }
+}
+
+fn save_failure_notification(title: String, resolved: bool) -> Notification {
+    Notification {
+        id: "solana-stake-monitor/save-accounts-info".to_string(),
+        severity: Severity::Critical,
+        title,
+        details: String::new(),
+        resolved,
+    }
+}
+
 fn command_record(data_file: &str, json_rpc_url: String, first_slot: Slot, batch_size: u64) {
     let mut accounts_info = load_accounts_info(&data_file);
 
@@ -41,18 +90,32 @@ fn command_record(data_file: &str, json_rpc_url: String, first_slot: Slot, batch
         accounts_info.slot = first_slot;
     }
 
+    let notifier = Notifier::default();
+    let mut save_failed = false;
     loop {
+        let noncompliant_accounts = accounts_info.noncompliant_accounts();
         process_slots(&rpc_client, &mut accounts_info, batch_size);
-        save_accounts_info(data_file, &accounts_info).unwrap_or_else(|err| {
-            datapoint_error!(
-                "stake-monitor-failure",
-                (
-                    "err",
-                    format!("failed to save accounts_info: {}", err),
-                    String
-                )
-            );
-        });
+        notify_compliance_changes(&notifier, &noncompliant_accounts, &accounts_info);
+
+        match save_accounts_info(data_file, &accounts_info) {
+            Ok(()) => {
+                if save_failed {
+                    notifier.notify(&save_failure_notification(
+                        format!("Saving {} works again", data_file),
+                        true,
+                    ));
+                    save_failed = false;
+                }
+            }
+            Err(err) => {
+                let msg = format!("failed to save accounts_info: {}", err);
+                datapoint_error!("stake-monitor-failure", ("err", msg.clone(), String));
+                if !save_failed {
+                    notifier.notify(&save_failure_notification(msg, false));
+                    save_failed = true;
+                }
+            }
+        }
     }
 }
 
@@ -635,7 +635,9 @@ mod tests {
     use super::*;
     use crate::StoredConfirmedBlock;
     use prost::Message;
-    use solana_sdk::{hash::Hash, signature::Keypair, system_transaction};
+    use solana_sdk::{
+        hash::Hash, message::LoadedAddresses, signature::Keypair, system_transaction,
+    };
     use solana_storage_proto::convert::generated;
     use solana_transaction_status::{
         ConfirmedBlock, TransactionStatusMeta, TransactionWithStatusMeta,
@@ -648,7 +650,7 @@ mod tests {
         let recipient = solana_sdk::pubkey::new_rand();
         let transaction = system_transaction::transfer(&from, &recipient, 42, Hash::default());
         let with_meta = TransactionWithStatusMeta {
-            transaction,
+            transaction: transaction.into(),
             meta: Some(TransactionStatusMeta {
                 status: Ok(()),
                 fee: 1,
@@ -656,6 +658,12 @@ mod tests {
                 post_balances: vec![0, 42, 1],
                 inner_instructions: Some(vec![]),
                 log_messages: Some(vec![]),
+                return_data: None,
+                log_events: Some(vec![]),
+                loaded_addresses: LoadedAddresses {
+                    writable: vec![solana_sdk::pubkey::new_rand()],
+                    readonly: vec![solana_sdk::pubkey::new_rand()],
+                },
             }),
         };
         let block = ConfirmedBlock {
@@ -705,6 +713,9 @@ mod tests {
             if let Some(meta) = &mut block.transactions[0].meta {
                 meta.inner_instructions = None; // Legacy bincode implementation does not support inner_instructions
                 meta.log_messages = None; // Legacy bincode implementation does not support log_messages
+                meta.return_data = None; // Legacy bincode implementation does not support return_data
+                meta.log_events = None; // Legacy bincode implementation does not support log_events
+                meta.loaded_addresses = LoadedAddresses::default(); // Legacy bincode implementation does not support loaded_addresses
             }
             assert_eq!(block, bincode_block.into());
         } else {
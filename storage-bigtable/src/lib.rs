@@ -2,10 +2,11 @@ use log::*;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
     clock::{Slot, UnixTimestamp},
+    message::LoadedAddresses,
     pubkey::Pubkey,
     signature::Signature,
     sysvar::is_sysvar_id,
-    transaction::{Transaction, TransactionError},
+    transaction::{TransactionError, VersionedTransaction},
 };
 use solana_storage_proto::convert::generated;
 use solana_transaction_status::{
@@ -134,7 +135,7 @@ impl From<StoredConfirmedBlock> for ConfirmedBlock {
 
 #[derive(Serialize, Deserialize)]
 struct StoredConfirmedBlockTransaction {
-    transaction: Transaction,
+    transaction: VersionedTransaction,
     meta: Option<StoredConfirmedBlockTransactionStatusMeta>,
 }
 
@@ -183,6 +184,9 @@ impl From<StoredConfirmedBlockTransactionStatusMeta> for TransactionStatusMeta {
             post_balances,
             inner_instructions: None,
             log_messages: None,
+            return_data: None,
+            log_events: None,
+            loaded_addresses: LoadedAddresses::default(),
         }
     }
 }
@@ -483,7 +487,16 @@ impl LedgerStorage {
             let index = index as u32;
             let signature = transaction.signatures[0];
 
-            for address in &transaction.message.account_keys {
+            let loaded_addresses = meta.iter().flat_map(|meta| {
+                let LoadedAddresses { writable, readonly } = &meta.loaded_addresses;
+                writable.iter().chain(readonly)
+            });
+            for address in transaction
+                .message
+                .static_account_keys()
+                .iter()
+                .chain(loaded_addresses)
+            {
                 if !is_sysvar_id(&address) {
                     by_addr
                         .entry(address)
@@ -37,6 +37,10 @@ pub struct Message {
     pub recent_blockhash: std::vec::Vec<u8>,
     #[prost(message, repeated, tag = "4")]
     pub instructions: ::std::vec::Vec<CompiledInstruction>,
+    #[prost(bool, tag = "5")]
+    pub versioned: bool,
+    #[prost(message, repeated, tag = "6")]
+    pub address_table_lookups: ::std::vec::Vec<MessageAddressTableLookup>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MessageHeader {
@@ -48,6 +52,15 @@ pub struct MessageHeader {
     pub num_readonly_unsigned_accounts: u32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageAddressTableLookup {
+    #[prost(bytes, tag = "1")]
+    pub account_key: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub writable_indexes: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "3")]
+    pub readonly_indexes: std::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TransactionStatusMeta {
     #[prost(message, optional, tag = "1")]
     pub err: ::std::option::Option<TransactionError>,
@@ -61,6 +74,28 @@ pub struct TransactionStatusMeta {
     pub inner_instructions: ::std::vec::Vec<InnerInstructions>,
     #[prost(string, repeated, tag = "6")]
     pub log_messages: ::std::vec::Vec<std::string::String>,
+    #[prost(message, optional, tag = "7")]
+    pub return_data: ::std::option::Option<ReturnData>,
+    #[prost(message, repeated, tag = "8")]
+    pub log_events: ::std::vec::Vec<LogEvent>,
+    #[prost(bytes, repeated, tag = "9")]
+    pub loaded_writable_addresses: ::std::vec::Vec<std::vec::Vec<u8>>,
+    #[prost(bytes, repeated, tag = "10")]
+    pub loaded_readonly_addresses: ::std::vec::Vec<std::vec::Vec<u8>>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReturnData {
+    #[prost(bytes, tag = "1")]
+    pub program_id: std::vec::Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub data: std::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogEvent {
+    #[prost(bytes, tag = "1")]
+    pub program_id: std::vec::Vec<u8>,
+    #[prost(bytes, repeated, tag = "2")]
+    pub data: ::std::vec::Vec<std::vec::Vec<u8>>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TransactionError {
@@ -2,14 +2,17 @@ use crate::StoredExtendedRewards;
 use solana_sdk::{
     hash::Hash,
     instruction::CompiledInstruction,
-    message::{Message, MessageHeader},
+    message::{
+        LoadedAddresses, Message, MessageAddressTableLookup, MessageHeader, MessageV0,
+        VersionedMessage,
+    },
     pubkey::Pubkey,
     signature::Signature,
-    transaction::Transaction,
+    transaction::VersionedTransaction,
 };
 use solana_transaction_status::{
-    ConfirmedBlock, InnerInstructions, Reward, RewardType, TransactionStatusMeta,
-    TransactionWithStatusMeta,
+    ConfirmedBlock, InnerInstructions, Reward, RewardType, TransactionLogEvent,
+    TransactionReturnData, TransactionStatusMeta, TransactionWithStatusMeta,
 };
 use std::convert::{TryFrom, TryInto};
 
@@ -175,8 +178,8 @@ impl TryFrom<generated::ConfirmedTransaction> for TransactionWithStatusMeta {
     }
 }
 
-impl From<Transaction> for generated::Transaction {
-    fn from(value: Transaction) -> Self {
+impl From<VersionedTransaction> for generated::Transaction {
+    fn from(value: VersionedTransaction) -> Self {
         Self {
             signatures: value
                 .signatures
@@ -188,7 +191,7 @@ impl From<Transaction> for generated::Transaction {
     }
 }
 
-impl From<generated::Transaction> for Transaction {
+impl From<generated::Transaction> for VersionedTransaction {
     fn from(value: generated::Transaction) -> Self {
         Self {
             signatures: value
@@ -201,32 +204,98 @@ impl From<generated::Transaction> for Transaction {
     }
 }
 
-impl From<Message> for generated::Message {
-    fn from(value: Message) -> Self {
-        Self {
-            header: Some(value.header.into()),
-            account_keys: value
-                .account_keys
-                .into_iter()
-                .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
-                .collect(),
-            recent_blockhash: value.recent_blockhash.to_bytes().into(),
-            instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+impl From<VersionedMessage> for generated::Message {
+    fn from(value: VersionedMessage) -> Self {
+        match value {
+            VersionedMessage::Legacy(message) => Self {
+                header: Some(message.header.into()),
+                account_keys: message
+                    .account_keys
+                    .into_iter()
+                    .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+                    .collect(),
+                recent_blockhash: message.recent_blockhash.to_bytes().into(),
+                instructions: message
+                    .instructions
+                    .into_iter()
+                    .map(|ix| ix.into())
+                    .collect(),
+                versioned: false,
+                address_table_lookups: vec![],
+            },
+            VersionedMessage::V0(message) => Self {
+                header: Some(message.header.into()),
+                account_keys: message
+                    .account_keys
+                    .into_iter()
+                    .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+                    .collect(),
+                recent_blockhash: message.recent_blockhash.to_bytes().into(),
+                instructions: message
+                    .instructions
+                    .into_iter()
+                    .map(|ix| ix.into())
+                    .collect(),
+                versioned: true,
+                address_table_lookups: message
+                    .address_table_lookups
+                    .into_iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            },
         }
     }
 }
 
-impl From<generated::Message> for Message {
+impl From<generated::Message> for VersionedMessage {
     fn from(value: generated::Message) -> Self {
+        let header = value.header.expect("header is required").into();
+        let account_keys = value
+            .account_keys
+            .into_iter()
+            .map(|key| Pubkey::new(&key))
+            .collect();
+        let recent_blockhash = Hash::new(&value.recent_blockhash);
+        let instructions = value.instructions.into_iter().map(|ix| ix.into()).collect();
+        if value.versioned {
+            Self::V0(MessageV0 {
+                header,
+                account_keys,
+                recent_blockhash,
+                instructions,
+                address_table_lookups: value
+                    .address_table_lookups
+                    .into_iter()
+                    .map(|lookup| lookup.into())
+                    .collect(),
+            })
+        } else {
+            Self::Legacy(Message {
+                header,
+                account_keys,
+                recent_blockhash,
+                instructions,
+            })
+        }
+    }
+}
+
+impl From<MessageAddressTableLookup> for generated::MessageAddressTableLookup {
+    fn from(value: MessageAddressTableLookup) -> Self {
         Self {
-            header: value.header.expect("header is required").into(),
-            account_keys: value
-                .account_keys
-                .into_iter()
-                .map(|key| Pubkey::new(&key))
-                .collect(),
-            recent_blockhash: Hash::new(&value.recent_blockhash),
-            instructions: value.instructions.into_iter().map(|ix| ix.into()).collect(),
+            account_key: <Pubkey as AsRef<[u8]>>::as_ref(&value.account_key).into(),
+            writable_indexes: value.writable_indexes,
+            readonly_indexes: value.readonly_indexes,
+        }
+    }
+}
+
+impl From<generated::MessageAddressTableLookup> for MessageAddressTableLookup {
+    fn from(value: generated::MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: Pubkey::new(&value.account_key),
+            writable_indexes: value.writable_indexes,
+            readonly_indexes: value.readonly_indexes,
         }
     }
 }
@@ -260,6 +329,9 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             post_balances,
             inner_instructions,
             log_messages,
+            return_data,
+            log_events,
+            loaded_addresses,
         } = value;
         let err = match status {
             Ok(()) => None,
@@ -273,6 +345,22 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             .map(|ii| ii.into())
             .collect();
         let log_messages = log_messages.unwrap_or_default();
+        let return_data = return_data.map(|return_data| return_data.into());
+        let log_events = log_events
+            .unwrap_or_default()
+            .into_iter()
+            .map(|event| event.into())
+            .collect();
+        let loaded_writable_addresses = loaded_addresses
+            .writable
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
+        let loaded_readonly_addresses = loaded_addresses
+            .readonly
+            .into_iter()
+            .map(|key| <Pubkey as AsRef<[u8]>>::as_ref(&key).into())
+            .collect();
         Self {
             err,
             fee,
@@ -280,6 +368,10 @@ impl From<TransactionStatusMeta> for generated::TransactionStatusMeta {
             post_balances,
             inner_instructions,
             log_messages,
+            return_data,
+            log_events,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
         }
     }
 }
@@ -295,6 +387,10 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             post_balances,
             inner_instructions,
             log_messages,
+            return_data,
+            log_events,
+            loaded_writable_addresses,
+            loaded_readonly_addresses,
         } = value;
         let status = match &err {
             None => Ok(()),
@@ -307,6 +403,18 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
                 .collect(),
         );
         let log_messages = Some(log_messages);
+        let return_data = return_data.map(|return_data| return_data.into());
+        let log_events = Some(log_events.into_iter().map(|event| event.into()).collect());
+        let loaded_addresses = LoadedAddresses {
+            writable: loaded_writable_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+            readonly: loaded_readonly_addresses
+                .into_iter()
+                .map(|key| Pubkey::new(&key))
+                .collect(),
+        };
         Ok(Self {
             status,
             fee,
@@ -314,10 +422,49 @@ impl TryFrom<generated::TransactionStatusMeta> for TransactionStatusMeta {
             post_balances,
             inner_instructions,
             log_messages,
+            return_data,
+            log_events,
+            loaded_addresses,
         })
     }
 }
 
+impl From<TransactionReturnData> for generated::ReturnData {
+    fn from(value: TransactionReturnData) -> Self {
+        Self {
+            program_id: value.program_id.to_bytes().into(),
+            data: value.data,
+        }
+    }
+}
+
+impl From<generated::ReturnData> for TransactionReturnData {
+    fn from(value: generated::ReturnData) -> Self {
+        Self {
+            program_id: Pubkey::new(&value.program_id),
+            data: value.data,
+        }
+    }
+}
+
+impl From<TransactionLogEvent> for generated::LogEvent {
+    fn from(value: TransactionLogEvent) -> Self {
+        Self {
+            program_id: value.program_id.to_bytes().into(),
+            data: value.data,
+        }
+    }
+}
+
+impl From<generated::LogEvent> for TransactionLogEvent {
+    fn from(value: generated::LogEvent) -> Self {
+        Self {
+            program_id: Pubkey::new(&value.program_id),
+            data: value.data,
+        }
+    }
+}
+
 impl From<InnerInstructions> for generated::InnerInstructions {
     fn from(value: InnerInstructions) -> Self {
         Self {
@@ -387,4 +534,41 @@ mod test {
         let gen_reward: generated::Reward = reward.clone().into();
         assert_eq!(reward, gen_reward.into());
     }
+
+    #[test]
+    fn test_versioned_transaction_encode() {
+        let message = Message::new_with_compiled_instructions(
+            1,
+            0,
+            1,
+            vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            Hash::new_unique(),
+            vec![CompiledInstruction::new(1, &(), vec![0])],
+        );
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::Legacy(message.clone()),
+        };
+        let gen_transaction: generated::Transaction = transaction.clone().into();
+        assert!(!gen_transaction.message.as_ref().unwrap().versioned);
+        assert_eq!(transaction, gen_transaction.into());
+
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(MessageV0 {
+                header: message.header,
+                account_keys: message.account_keys,
+                recent_blockhash: message.recent_blockhash,
+                instructions: message.instructions,
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0, 2],
+                    readonly_indexes: vec![1],
+                }],
+            }),
+        };
+        let gen_transaction: generated::Transaction = transaction.clone().into();
+        assert!(gen_transaction.message.as_ref().unwrap().versioned);
+        assert_eq!(transaction, gen_transaction.into());
+    }
 }
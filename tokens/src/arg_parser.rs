@@ -1,5 +1,6 @@
 use crate::args::{
     Args, BalancesArgs, Command, DistributeTokensArgs, SplTokenArgs, StakeArgs, TransactionLogArgs,
+    VestingReportArgs,
 };
 use clap::{
     crate_description, crate_name, value_t, value_t_or_exit, App, Arg, ArgMatches, SubCommand,
@@ -327,6 +328,18 @@ where
                         .help("Output file"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("vesting-report")
+                .about("Locked and unlocked amounts distributed to each recipient")
+                .arg(
+                    Arg::with_name("db_path")
+                        .long("db-path")
+                        .required(true)
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Location of database to query"),
+                ),
+        )
         .get_matches_from(args)
 }
 
@@ -501,6 +514,12 @@ fn parse_balances_args(matches: &ArgMatches<'_>) -> Result<BalancesArgs, Box<dyn
     })
 }
 
+fn parse_vesting_report_args(matches: &ArgMatches<'_>) -> VestingReportArgs {
+    VestingReportArgs {
+        transaction_db: value_t_or_exit!(matches, "db_path", String),
+    }
+}
+
 fn parse_transaction_log_args(matches: &ArgMatches<'_>) -> TransactionLogArgs {
     TransactionLogArgs {
         transaction_db: value_t_or_exit!(matches, "db_path", String),
@@ -532,6 +551,9 @@ where
         ("transaction-log", Some(matches)) => {
             Command::TransactionLog(parse_transaction_log_args(matches))
         }
+        ("vesting-report", Some(matches)) => {
+            Command::VestingReport(parse_vesting_report_args(matches))
+        }
         _ => {
             eprintln!("{}", matches.usage());
             exit(1);
@@ -37,10 +37,15 @@ pub struct TransactionLogArgs {
     pub output_path: String,
 }
 
+pub struct VestingReportArgs {
+    pub transaction_db: String,
+}
+
 pub enum Command {
     DistributeTokens(DistributeTokensArgs),
     Balances(BalancesArgs),
     TransactionLog(TransactionLogArgs),
+    VestingReport(VestingReportArgs),
 }
 
 pub struct Args {
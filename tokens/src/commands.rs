@@ -1,10 +1,13 @@
 use crate::{
-    args::{BalancesArgs, DistributeTokensArgs, StakeArgs, TransactionLogArgs},
+    args::{
+        BalancesArgs, DistributeTokensArgs, SplTokenArgs, StakeArgs, TransactionLogArgs,
+        VestingReportArgs,
+    },
     db::{self, TransactionInfo},
     spl_token::*,
     token_display::Token,
 };
-use chrono::prelude::*;
+use chrono::{prelude::*, SecondsFormat};
 use console::style;
 use csv::{ReaderBuilder, Trim};
 use indexmap::IndexMap;
@@ -36,16 +39,24 @@ use spl_associated_token_account_v1_0::get_associated_token_address;
 use spl_token_v2_0::solana_program::program_error::ProgramError;
 use std::{
     cmp::{self},
+    collections::BTreeMap,
     io,
     thread::sleep,
     time::Duration,
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct Allocation {
     pub recipient: String,
     pub amount: u64,
     pub lockup_date: String,
+    /// SPL token mint, empty for the token of the distribution's source account
+    pub mint: String,
+    /// Lockup custodian of a stake allocation, empty for none
+    pub custodian: String,
+    /// Index of the allocation within its recipient's vesting schedule.  The unlocked SOL of a
+    /// stake allocation is only sent with tranche 0.
+    pub tranche: u32,
 }
 
 #[derive(Debug, PartialEq)]
@@ -109,16 +120,39 @@ fn merge_allocations(allocations: &[Allocation]) -> Vec<Allocation> {
                 recipient: allocation.recipient.clone(),
                 amount: 0,
                 lockup_date: "".to_string(),
+                mint: allocation.mint.clone(),
+                ..Allocation::default()
             })
             .amount += allocation.amount;
     }
     allocation_map.values().cloned().collect()
 }
 
-/// Return true if the recipient and lockups are the same
+/// Group allocations by mint, in order of first appearance.  Allocations of `default_mint` are
+/// filed under an empty mint, the way the transaction db records them.
+fn group_allocations_by_mint(
+    allocations: Vec<Allocation>,
+    default_mint: &Pubkey,
+) -> IndexMap<String, Vec<Allocation>> {
+    let default_mint = default_mint.to_string();
+    let mut allocation_map: IndexMap<String, Vec<Allocation>> = IndexMap::new();
+    for mut allocation in allocations {
+        if allocation.mint == default_mint {
+            allocation.mint = "".to_string();
+        }
+        allocation_map
+            .entry(allocation.mint.clone())
+            .or_insert_with(Vec::new)
+            .push(allocation);
+    }
+    allocation_map
+}
+
+/// Return true if the recipient, lockups and mints are the same
 fn has_same_recipient(allocation: &Allocation, transaction_info: &TransactionInfo) -> bool {
     allocation.recipient == transaction_info.recipient.to_string()
         && allocation.lockup_date.parse().ok() == transaction_info.lockup_date
+        && allocation.mint.parse().ok() == transaction_info.mint
 }
 
 fn apply_previous_transactions(
@@ -164,10 +198,20 @@ fn distribution_instructions(
     allocation: &Allocation,
     new_stake_account_address: &Pubkey,
     args: &DistributeTokensArgs,
+    spl_token_args: Option<&SplTokenArgs>,
     lockup_date: Option<DateTime<Utc>>,
     do_create_associated_token_account: bool,
 ) -> Vec<Instruction> {
-    if args.stake_args.is_none() && args.spl_token_args.is_none() {
+    if let Some(spl_token_args) = spl_token_args {
+        return build_spl_token_instructions(
+            allocation,
+            args,
+            spl_token_args,
+            do_create_associated_token_account,
+        );
+    }
+
+    if args.stake_args.is_none() {
         let from = args.sender_keypair.pubkey();
         let to = allocation.recipient.parse().unwrap();
         let lamports = allocation.amount;
@@ -175,12 +219,8 @@ fn distribution_instructions(
         return vec![instruction];
     }
 
-    if args.spl_token_args.is_some() {
-        return build_spl_token_instructions(allocation, args, do_create_associated_token_account);
-    }
-
     let stake_args = args.stake_args.as_ref().unwrap();
-    let unlocked_sol = stake_args.unlocked_sol;
+    let unlocked_sol = unlocked_sol(allocation, stake_args);
     let sender_pubkey = args.sender_keypair.pubkey();
     let stake_authority = stake_args.stake_authority.pubkey();
     let withdraw_authority = stake_args.withdraw_authority.pubkey();
@@ -211,16 +251,21 @@ fn distribution_instructions(
     ));
 
     // Add lockup
-    if let Some(lockup_date) = lockup_date {
+    let custodian = if allocation.custodian == "" {
+        None
+    } else {
+        Some(allocation.custodian.parse().unwrap())
+    };
+    if lockup_date.is_some() || custodian.is_some() {
         let lockup_authority = stake_args
             .lockup_authority
             .as_ref()
             .map(|signer| signer.pubkey())
             .unwrap();
         let lockup = LockupArgs {
-            unix_timestamp: Some(lockup_date.timestamp()),
+            unix_timestamp: lockup_date.map(|lockup_date| lockup_date.timestamp()),
             epoch: None,
-            custodian: None,
+            custodian,
         };
         instructions.push(stake_instruction::set_lockup(
             &new_stake_account_address,
@@ -229,20 +274,32 @@ fn distribution_instructions(
         ));
     }
 
-    instructions.push(system_instruction::transfer(
-        &sender_pubkey,
-        &recipient,
-        unlocked_sol,
-    ));
+    if unlocked_sol > 0 {
+        instructions.push(system_instruction::transfer(
+            &sender_pubkey,
+            &recipient,
+            unlocked_sol,
+        ));
+    }
 
     instructions
 }
 
+/// Lamports of a stake allocation sent to the recipient's system account rather than staked
+fn unlocked_sol(allocation: &Allocation, stake_args: &StakeArgs) -> u64 {
+    if allocation.tranche == 0 {
+        stake_args.unlocked_sol
+    } else {
+        0
+    }
+}
+
 fn distribute_allocations(
     client: &RpcClient,
     db: &mut PickleDb,
     allocations: &[Allocation],
     args: &DistributeTokensArgs,
+    spl_token_args: Option<&SplTokenArgs>,
 ) -> Result<(), Error> {
     type StakeExtras = Vec<(Keypair, Option<DateTime<Utc>>)>;
     let mut messages: Vec<Message> = vec![];
@@ -257,7 +314,7 @@ fn distribute_allocations(
         };
 
         let (display_amount, decimals, do_create_associated_token_account) =
-            if let Some(spl_token_args) = &args.spl_token_args {
+            if let Some(spl_token_args) = spl_token_args {
                 let wallet_address = allocation.recipient.parse().unwrap();
                 let associated_token_address = get_associated_token_address(
                     &wallet_address,
@@ -287,6 +344,7 @@ fn distribute_allocations(
             allocation,
             &new_stake_account_keypair.pubkey(),
             args,
+            spl_token_args,
             lockup_date,
             do_create_associated_token_account,
         );
@@ -300,8 +358,15 @@ fn distribute_allocations(
         .iter()
         .map(|message| message.header.num_required_signatures as usize)
         .sum();
-    if args.spl_token_args.is_some() {
-        check_spl_token_balances(num_signatures, allocations, client, args, created_accounts)?;
+    if let Some(spl_token_args) = spl_token_args {
+        check_spl_token_balances(
+            num_signatures,
+            allocations,
+            client,
+            args,
+            spl_token_args,
+            created_accounts,
+        )?;
     } else {
         check_payer_balances(num_signatures, allocations, client, args)?;
     }
@@ -316,7 +381,7 @@ fn distribute_allocations(
             signers.push(&*stake_args.stake_authority);
             signers.push(&*stake_args.withdraw_authority);
             signers.push(&new_stake_account_keypair);
-            if allocation.lockup_date != "" {
+            if allocation.lockup_date != "" || allocation.custodian != "" {
                 if let Some(lockup_authority) = &stake_args.lockup_authority {
                     signers.push(&**lockup_authority);
                 } else {
@@ -352,6 +417,10 @@ fn distribute_allocations(
                     false,
                     last_valid_slot,
                     lockup_date,
+                    allocation.mint.parse().ok().as_ref(),
+                    args.stake_args
+                        .as_ref()
+                        .map_or(0, |stake_args| unlocked_sol(allocation, stake_args)),
                 )?;
             }
             Err(e) => {
@@ -362,6 +431,105 @@ fn distribute_allocations(
     Ok(())
 }
 
+/// A stake allocation row with optional vesting columns
+#[derive(Deserialize)]
+struct VestingRecord {
+    recipient: String,
+    amount: f64,
+    lockup_date: String,
+    #[serde(default)]
+    custodian: String,
+    #[serde(default)]
+    tranches: Option<u32>,
+}
+
+/// Split a vesting allocation into `tranches` allocations of equal size, the first unlocking at
+/// `lockup_date` and each later one a month after the previous.  The first tranche takes any
+/// remainder.
+fn vesting_tranches(record: VestingRecord) -> io::Result<Vec<Allocation>> {
+    let amount = sol_to_lamports(record.amount);
+    let tranches = record.tranches.unwrap_or(1).max(1);
+    let start_date = if record.lockup_date == "" {
+        if tranches > 1 {
+            return Err(invalid_allocation(
+                &record.recipient,
+                "vesting tranches require a lockup_date",
+            ));
+        }
+        None
+    } else {
+        Some(parse_lockup_date(&record.recipient, &record.lockup_date)?)
+    };
+    Ok((0..tranches)
+        .map(|tranche| {
+            let (tranche_amount, lockup_date) = if tranche == 0 {
+                (
+                    amount / u64::from(tranches) + amount % u64::from(tranches),
+                    record.lockup_date.clone(),
+                )
+            } else {
+                (
+                    amount / u64::from(tranches),
+                    add_months(start_date.unwrap(), tranche)
+                        .to_rfc3339_opts(SecondsFormat::Secs, true),
+                )
+            };
+            Allocation {
+                recipient: record.recipient.clone(),
+                amount: tranche_amount,
+                lockup_date,
+                mint: "".to_string(),
+                custodian: record.custodian.clone(),
+                tranche,
+            }
+        })
+        .collect())
+}
+
+fn invalid_allocation(recipient: &str, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid allocation for {}: {}", recipient, message),
+    )
+}
+
+fn parse_lockup_date(recipient: &str, lockup_date: &str) -> io::Result<DateTime<Utc>> {
+    lockup_date
+        .parse()
+        .map_err(|err| invalid_allocation(recipient, &format!("lockup_date: {}", err)))
+}
+
+/// Check the fields of an allocation that are parsed when it is distributed
+fn validate_allocation(allocation: &Allocation) -> io::Result<()> {
+    if allocation.lockup_date != "" {
+        parse_lockup_date(&allocation.recipient, &allocation.lockup_date)?;
+    }
+    if allocation.custodian != "" {
+        allocation.custodian.parse::<Pubkey>().map_err(|err| {
+            invalid_allocation(&allocation.recipient, &format!("custodian: {}", err))
+        })?;
+    }
+    if allocation.mint != "" {
+        allocation
+            .mint
+            .parse::<Pubkey>()
+            .map_err(|err| invalid_allocation(&allocation.recipient, &format!("mint: {}", err)))?;
+    }
+    Ok(())
+}
+
+/// Add calendar months to `date`, clamping the day to the end of shorter months
+fn add_months(date: DateTime<Utc>, months: u32) -> DateTime<Utc> {
+    let month0 = date.month0() + months;
+    let year = date.year() + (month0 / 12) as i32;
+    let month = month0 % 12 + 1;
+    let day = (1..=date.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .unwrap();
+    DateTime::from_utc(day.and_time(date.time()), Utc)
+}
+
 fn read_allocations(
     input_csv: &str,
     transfer_amount: Option<u64>,
@@ -369,6 +537,11 @@ fn read_allocations(
     raw_amount: bool,
 ) -> io::Result<Vec<Allocation>> {
     let mut rdr = ReaderBuilder::new().trim(Trim::All).from_path(input_csv)?;
+    let has_vesting_columns = require_lockup_heading
+        && rdr
+            .headers()?
+            .iter()
+            .any(|x| x == "custodian" || x == "tranches");
     let allocations = if let Some(amount) = transfer_amount {
         let recipients: Vec<String> = rdr
             .deserialize()
@@ -379,9 +552,16 @@ fn read_allocations(
             .map(|recipient| Allocation {
                 recipient,
                 amount,
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             })
             .collect()
+    } else if has_vesting_columns {
+        let records: Vec<VestingRecord> = rdr.deserialize().map(|record| record.unwrap()).collect();
+        let mut allocations = vec![];
+        for record in records {
+            allocations.extend(vesting_tranches(record)?);
+        }
+        allocations
     } else if require_lockup_heading {
         let recipients: Vec<(String, f64, String)> = rdr
             .deserialize()
@@ -393,6 +573,22 @@ fn read_allocations(
                 recipient,
                 amount: sol_to_lamports(amount),
                 lockup_date,
+                ..Allocation::default()
+            })
+            .collect()
+    } else if raw_amount && rdr.headers()?.get(2) == Some("mint") {
+        let recipients: Vec<(String, u64, String)> = rdr
+            .deserialize()
+            .map(|recipient| recipient.unwrap())
+            .collect();
+        recipients
+            .into_iter()
+            .map(|(recipient, amount, mint)| Allocation {
+                recipient,
+                amount,
+                lockup_date: "".to_string(),
+                mint,
+                ..Allocation::default()
             })
             .collect()
     } else if raw_amount {
@@ -405,7 +601,7 @@ fn read_allocations(
             .map(|(recipient, amount)| Allocation {
                 recipient,
                 amount,
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             })
             .collect()
     } else {
@@ -418,10 +614,13 @@ fn read_allocations(
             .map(|(recipient, amount)| Allocation {
                 recipient,
                 amount: sol_to_lamports(amount),
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             })
             .collect()
     };
+    for allocation in &allocations {
+        validate_allocation(allocation)?;
+    }
     Ok(allocations)
 }
 
@@ -438,52 +637,108 @@ pub fn process_allocations(
     args: &DistributeTokensArgs,
 ) -> Result<Option<usize>, Error> {
     let require_lockup_heading = args.stake_args.is_some();
-    let mut allocations: Vec<Allocation> = read_allocations(
+    let allocations: Vec<Allocation> = read_allocations(
         &args.input_csv,
         args.transfer_amount,
         require_lockup_heading,
         args.spl_token_args.is_some(),
     )?;
 
-    let starting_total_tokens = allocations.iter().map(|x| x.amount).sum();
-    let starting_total_tokens = if let Some(spl_token_args) = &args.spl_token_args {
-        Token::spl_token(starting_total_tokens, spl_token_args.decimals)
-    } else {
-        Token::sol(starting_total_tokens)
-    };
-    println!(
-        "{} {}",
-        style("Total in input_csv:").bold(),
-        starting_total_tokens,
-    );
-
     let mut db = db::open_db(&args.transaction_db, args.dry_run)?;
 
     // Start by finalizing any transactions from the previous run.
     let confirmations = finalize_transactions(client, &mut db, args.dry_run)?;
 
     let transaction_infos = db::read_transaction_infos(&db);
-    apply_previous_transactions(&mut allocations, &transaction_infos);
+    let mut has_work = false;
+    if let Some(spl_token_args) = &args.spl_token_args {
+        for (mint, allocations) in group_allocations_by_mint(allocations, &spl_token_args.mint) {
+            let spl_token_args = if mint.is_empty() {
+                SplTokenArgs {
+                    token_account_address: spl_token_args.token_account_address,
+                    mint: spl_token_args.mint,
+                    decimals: spl_token_args.decimals,
+                }
+            } else {
+                associated_spl_token_args(
+                    client,
+                    &mint.parse().unwrap(),
+                    &args.sender_keypair.pubkey(),
+                )?
+            };
+            has_work |= process_distribution(
+                client,
+                &mut db,
+                allocations,
+                &transaction_infos,
+                args,
+                Some(&spl_token_args),
+            )?;
+        }
+    } else {
+        has_work =
+            process_distribution(client, &mut db, allocations, &transaction_infos, args, None)?;
+    }
 
-    if allocations.is_empty() {
+    if !has_work {
         eprintln!("No work to do");
         return Ok(confirmations);
     }
 
-    let distributed_tokens = transaction_infos.iter().map(|x| x.amount).sum();
-    let undistributed_tokens = allocations.iter().map(|x| x.amount).sum();
-    let (distributed_tokens, undistributed_tokens) =
-        if let Some(spl_token_args) = &args.spl_token_args {
-            (
-                Token::spl_token(distributed_tokens, spl_token_args.decimals),
-                Token::spl_token(undistributed_tokens, spl_token_args.decimals),
-            )
+    let opt_confirmations = finalize_transactions(client, &mut db, args.dry_run)?;
+
+    if !args.dry_run {
+        if let Some(output_path) = &args.output_path {
+            db::write_transaction_log(&db, &output_path)?;
+        }
+    }
+
+    Ok(opt_confirmations)
+}
+
+/// Distribute the allocations of a single token, skipping what previous runs already sent.
+/// Return false if there was nothing left to distribute.
+fn process_distribution(
+    client: &RpcClient,
+    db: &mut PickleDb,
+    mut allocations: Vec<Allocation>,
+    transaction_infos: &[TransactionInfo],
+    args: &DistributeTokensArgs,
+    spl_token_args: Option<&SplTokenArgs>,
+) -> Result<bool, Error> {
+    let token = |amount: u64| {
+        if let Some(spl_token_args) = spl_token_args {
+            Token::spl_token(amount, spl_token_args.decimals)
         } else {
-            (
-                Token::sol(distributed_tokens),
-                Token::sol(undistributed_tokens),
-            )
-        };
+            Token::sol(amount)
+        }
+    };
+    if let Some(spl_token_args) = spl_token_args {
+        println!("{} {}", style("Token:").bold(), spl_token_args.mint);
+    }
+
+    let starting_total_tokens = allocations.iter().map(|x| x.amount).sum();
+    println!(
+        "{} {}",
+        style("Total in input_csv:").bold(),
+        token(starting_total_tokens),
+    );
+
+    // Only transactions of this token count against its allocations
+    let mint = allocations.first().and_then(|x| x.mint.parse().ok());
+    let transaction_infos: Vec<_> = transaction_infos
+        .iter()
+        .filter(|info| info.mint == mint)
+        .cloned()
+        .collect();
+    apply_previous_transactions(&mut allocations, &transaction_infos);
+
+    if allocations.is_empty() {
+        return Ok(false);
+    }
+
+    let distributed_tokens = token(transaction_infos.iter().map(|x| x.amount).sum());
+    let undistributed_tokens = token(allocations.iter().map(|x| x.amount).sum());
     println!("{} {}", style("Distributed:").bold(), distributed_tokens,);
     println!(
         "{} {}",
@@ -501,17 +756,8 @@ pub fn process_allocations(
         style(format!("{:<44}  {:>24}", "Recipient", "Expected Balance",)).bold()
     );
 
-    distribute_allocations(client, &mut db, &allocations, args)?;
-
-    let opt_confirmations = finalize_transactions(client, &mut db, args.dry_run)?;
-
-    if !args.dry_run {
-        if let Some(output_path) = &args.output_path {
-            db::write_transaction_log(&db, &output_path)?;
-        }
-    }
-
-    Ok(opt_confirmations)
+    distribute_allocations(client, db, &allocations, args, spl_token_args)?;
+    Ok(true)
 }
 
 fn finalize_transactions(
@@ -609,7 +855,10 @@ fn check_payer_balances(
         .unwrap();
 
     let (distribution_source, unlocked_sol_source) = if let Some(stake_args) = &args.stake_args {
-        let total_unlocked_sol = allocations.len() as u64 * stake_args.unlocked_sol;
+        let total_unlocked_sol: u64 = allocations
+            .iter()
+            .map(|allocation| unlocked_sol(allocation, stake_args))
+            .sum();
         undistributed_tokens -= total_unlocked_sol;
         (
             stake_args.stake_account_address,
@@ -681,15 +930,52 @@ fn check_payer_balances(
 pub fn process_balances(client: &RpcClient, args: &BalancesArgs) -> Result<(), Error> {
     let allocations: Vec<Allocation> =
         read_allocations(&args.input_csv, None, false, args.spl_token_args.is_some())?;
+
+    if let Some(spl_token_args) = &args.spl_token_args {
+        for (mint, allocations) in group_allocations_by_mint(allocations, &spl_token_args.mint) {
+            let spl_token_args = if mint.is_empty() {
+                SplTokenArgs {
+                    mint: spl_token_args.mint,
+                    decimals: spl_token_args.decimals,
+                    ..SplTokenArgs::default()
+                }
+            } else {
+                let mint = mint.parse().unwrap();
+                SplTokenArgs {
+                    mint,
+                    decimals: get_mint_decimals(client, &mint)?,
+                    ..SplTokenArgs::default()
+                }
+            };
+            println!("{} {}", style("Token:").bold(), spl_token_args.mint);
+            print_balances_heading();
+            for allocation in &merge_allocations(&allocations) {
+                print_token_balances(client, allocation, &spl_token_args)?;
+            }
+        }
+        return Ok(());
+    }
+
     let allocations = merge_allocations(&allocations);
+    println!("{} {}", style("Token:").bold(), "◎");
+    print_balances_heading();
+    for allocation in &allocations {
+        let address: Pubkey = allocation.recipient.parse().unwrap();
+        let expected = lamports_to_sol(allocation.amount);
+        let actual = lamports_to_sol(client.get_balance(&address).unwrap());
+        println!(
+            "{:<44}  {:>24.9}  {:>24.9}  {:>24.9}",
+            allocation.recipient,
+            expected,
+            actual,
+            actual - expected,
+        );
+    }
 
-    let token = if let Some(spl_token_args) = &args.spl_token_args {
-        spl_token_args.mint.to_string()
-    } else {
-        "◎".to_string()
-    };
-    println!("{} {}", style("Token:").bold(), token);
+    Ok(())
+}
 
+fn print_balances_heading() {
     println!(
         "{}",
         style(format!(
@@ -698,30 +984,74 @@ pub fn process_balances(client: &RpcClient, args: &BalancesArgs) -> Result<(), E
         ))
         .bold()
     );
+}
 
-    for allocation in &allocations {
-        if let Some(spl_token_args) = &args.spl_token_args {
-            print_token_balances(client, allocation, spl_token_args)?;
-        } else {
-            let address: Pubkey = allocation.recipient.parse().unwrap();
-            let expected = lamports_to_sol(allocation.amount);
-            let actual = lamports_to_sol(client.get_balance(&address).unwrap());
-            println!(
-                "{:<44}  {:>24.9}  {:>24.9}  {:>24.9}",
-                allocation.recipient,
-                expected,
-                actual,
-                actual - expected,
-            );
+pub fn process_transaction_log(args: &TransactionLogArgs) -> Result<(), Error> {
+    let db = db::open_db(&args.transaction_db, true)?;
+    db::write_transaction_log(&db, &args.output_path)?;
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct VestingSummary {
+    tranches: usize,
+    locked: u64,
+    unlocked: u64,
+    next_unlock_date: Option<DateTime<Utc>>,
+}
+
+/// Total the distributed amounts of each recipient by whether their lockup has expired at `now`.
+/// Unlocked SOL sent along with a stake allocation is never locked.
+fn vesting_summaries(
+    transaction_infos: &[TransactionInfo],
+    now: DateTime<Utc>,
+) -> BTreeMap<String, VestingSummary> {
+    let mut summaries: BTreeMap<String, VestingSummary> = BTreeMap::new();
+    for info in transaction_infos {
+        let summary = summaries.entry(info.recipient.to_string()).or_default();
+        summary.tranches += 1;
+        summary.unlocked += info.unlocked_amount;
+        let staked_amount = info.amount.saturating_sub(info.unlocked_amount);
+        match info.lockup_date {
+            Some(lockup_date) if lockup_date > now => {
+                summary.locked += staked_amount;
+                summary.next_unlock_date = Some(
+                    summary
+                        .next_unlock_date
+                        .map_or(lockup_date, |date| cmp::min(date, lockup_date)),
+                );
+            }
+            _ => summary.unlocked += staked_amount,
         }
     }
-
-    Ok(())
+    summaries
 }
 
-pub fn process_transaction_log(args: &TransactionLogArgs) -> Result<(), Error> {
+pub fn process_vesting_report(args: &VestingReportArgs) -> Result<(), Error> {
     let db = db::open_db(&args.transaction_db, true)?;
-    db::write_transaction_log(&db, &args.output_path)?;
+    let transaction_infos = db::read_transaction_infos(&db);
+
+    println!(
+        "{}",
+        style(format!(
+            "{:<44}  {:>8}  {:>24}  {:>24}  {:>24}",
+            "Recipient", "Tranches", "Locked", "Unlocked", "Next Unlock"
+        ))
+        .bold()
+    );
+    for (recipient, summary) in vesting_summaries(&transaction_infos, Utc::now()) {
+        println!(
+            "{:<44}  {:>8}  {:>24.9}  {:>24.9}  {:>24}",
+            recipient,
+            summary.tranches,
+            lamports_to_sol(summary.locked),
+            lamports_to_sol(summary.unlocked),
+            summary
+                .next_unlock_date
+                .map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .unwrap_or_default(),
+        );
+    }
     Ok(())
 }
 
@@ -1002,7 +1332,7 @@ mod tests {
         let allocation = Allocation {
             recipient: alice_pubkey.to_string(),
             amount: 42,
-            lockup_date: "".to_string(),
+            ..Allocation::default()
         };
         let file = NamedTempFile::new().unwrap();
         let input_csv = file.path().to_str().unwrap().to_string();
@@ -1018,7 +1348,7 @@ mod tests {
         let allocation_sol = Allocation {
             recipient: alice_pubkey.to_string(),
             amount: sol_to_lamports(42.0),
-            lockup_date: "".to_string(),
+            ..Allocation::default()
         };
 
         assert_eq!(
@@ -1052,12 +1382,12 @@ mod tests {
             Allocation {
                 recipient: pubkey0.to_string(),
                 amount: sol_to_lamports(42.0),
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
             Allocation {
                 recipient: pubkey1.to_string(),
                 amount: sol_to_lamports(43.0),
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
         ];
         assert_eq!(
@@ -1084,12 +1414,12 @@ mod tests {
             Allocation {
                 recipient: pubkey0.to_string(),
                 amount: sol_to_lamports(42.0),
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
             Allocation {
                 recipient: pubkey1.to_string(),
                 amount: sol_to_lamports(43.0),
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
         ];
         assert_eq!(
@@ -1118,17 +1448,17 @@ mod tests {
             Allocation {
                 recipient: pubkey0.to_string(),
                 amount,
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
             Allocation {
                 recipient: pubkey1.to_string(),
                 amount,
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
             Allocation {
                 recipient: pubkey2.to_string(),
                 amount,
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
         ];
         assert_eq!(
@@ -1145,12 +1475,12 @@ mod tests {
             Allocation {
                 recipient: alice.to_string(),
                 amount: sol_to_lamports(1.0),
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
             Allocation {
                 recipient: bob.to_string(),
                 amount: sol_to_lamports(1.0),
-                lockup_date: "".to_string(),
+                ..Allocation::default()
             },
         ];
         let transaction_infos = vec![TransactionInfo {
@@ -1175,12 +1505,13 @@ mod tests {
         let alice_alloc = Allocation {
             recipient: alice_pubkey.to_string(),
             amount: sol_to_lamports(1.0),
-            lockup_date: "".to_string(),
+            ..Allocation::default()
         };
         let alice_alloc_lockup0 = Allocation {
             recipient: alice_pubkey.to_string(),
             amount: sol_to_lamports(1.0),
             lockup_date: lockup0.clone(),
+            ..Allocation::default()
         };
         let alice_info = TransactionInfo {
             recipient: alice_pubkey,
@@ -1215,6 +1546,120 @@ mod tests {
         )); // Same recipient, same lockups
     }
 
+    #[test]
+    fn test_has_same_recipient_mint() {
+        let alice_pubkey = solana_sdk::pubkey::new_rand();
+        let mint = solana_sdk::pubkey::new_rand();
+        let alice_alloc = Allocation {
+            recipient: alice_pubkey.to_string(),
+            amount: 42,
+            ..Allocation::default()
+        };
+        let alice_alloc_mint = Allocation {
+            mint: mint.to_string(),
+            ..alice_alloc.clone()
+        };
+        let alice_info = TransactionInfo {
+            recipient: alice_pubkey,
+            ..TransactionInfo::default()
+        };
+        let alice_info_mint = TransactionInfo {
+            recipient: alice_pubkey,
+            mint: Some(mint),
+            ..TransactionInfo::default()
+        };
+        assert!(has_same_recipient(&alice_alloc, &alice_info));
+        assert!(has_same_recipient(&alice_alloc_mint, &alice_info_mint));
+        assert!(!has_same_recipient(&alice_alloc, &alice_info_mint));
+        assert!(!has_same_recipient(&alice_alloc_mint, &alice_info));
+    }
+
+    #[test]
+    fn test_read_allocations_mint() {
+        let pubkey0 = solana_sdk::pubkey::new_rand();
+        let pubkey1 = solana_sdk::pubkey::new_rand();
+        let mint = solana_sdk::pubkey::new_rand();
+        let file = NamedTempFile::new().unwrap();
+        let input_csv = file.path().to_str().unwrap().to_string();
+        let mut wtr = csv::WriterBuilder::new().from_writer(file);
+        wtr.serialize(("recipient", "amount", "mint")).unwrap();
+        wtr.serialize((&pubkey0.to_string(), 42, "")).unwrap();
+        wtr.serialize((&pubkey1.to_string(), 43, &mint.to_string()))
+            .unwrap();
+        wtr.flush().unwrap();
+
+        let expected_allocations = vec![
+            Allocation {
+                recipient: pubkey0.to_string(),
+                amount: 42,
+                ..Allocation::default()
+            },
+            Allocation {
+                recipient: pubkey1.to_string(),
+                amount: 43,
+                lockup_date: "".to_string(),
+                mint: mint.to_string(),
+                ..Allocation::default()
+            },
+        ];
+        assert_eq!(
+            read_allocations(&input_csv, None, false, true).unwrap(),
+            expected_allocations
+        );
+
+        let file = NamedTempFile::new().unwrap();
+        let input_csv = file.path().to_str().unwrap().to_string();
+        let mut wtr = csv::WriterBuilder::new().from_writer(file);
+        wtr.serialize(("recipient", "amount", "mint")).unwrap();
+        wtr.serialize((&pubkey0.to_string(), 42, "not a pubkey"))
+            .unwrap();
+        wtr.flush().unwrap();
+        let err = read_allocations(&input_csv, None, false, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_group_allocations_by_mint() {
+        let alice = solana_sdk::pubkey::new_rand().to_string();
+        let bob = solana_sdk::pubkey::new_rand().to_string();
+        let default_mint = solana_sdk::pubkey::new_rand();
+        let other_mint = solana_sdk::pubkey::new_rand();
+        let allocation = |recipient: &str, mint: String| Allocation {
+            recipient: recipient.to_string(),
+            amount: 1,
+            lockup_date: "".to_string(),
+            mint,
+            ..Allocation::default()
+        };
+        let allocations = vec![
+            allocation(&alice, other_mint.to_string()),
+            allocation(&alice, "".to_string()),
+            allocation(&bob, default_mint.to_string()),
+            allocation(&bob, other_mint.to_string()),
+        ];
+
+        let groups = group_allocations_by_mint(allocations, &default_mint);
+        assert_eq!(
+            groups.keys().collect::<Vec<_>>(),
+            vec![&other_mint.to_string(), &"".to_string()]
+        );
+        assert_eq!(
+            groups[&other_mint.to_string()],
+            vec![
+                allocation(&alice, other_mint.to_string()),
+                allocation(&bob, other_mint.to_string()),
+            ]
+        );
+        // Allocations naming the default mint are filed with those naming no mint
+        assert_eq!(
+            groups[""],
+            vec![
+                allocation(&alice, "".to_string()),
+                allocation(&bob, "".to_string()),
+            ]
+        );
+    }
+
     const SET_LOCKUP_INDEX: usize = 4;
 
     #[test]
@@ -1224,6 +1669,7 @@ mod tests {
             recipient: Pubkey::default().to_string(),
             amount: sol_to_lamports(1.0),
             lockup_date: lockup_date_str.to_string(),
+            ..Allocation::default()
         };
         let stake_account_address = solana_sdk::pubkey::new_rand();
         let new_stake_account_address = solana_sdk::pubkey::new_rand();
@@ -1251,6 +1697,7 @@ mod tests {
             &allocation,
             &new_stake_account_address,
             &args,
+            None,
             Some(lockup_date),
             false,
         );
@@ -1265,6 +1712,204 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vesting_tranche_instructions() {
+        let custodian = solana_sdk::pubkey::new_rand();
+        let lockup_date_str = "2021-02-07T00:00:00Z";
+        let allocation = Allocation {
+            recipient: Pubkey::default().to_string(),
+            amount: sol_to_lamports(10.0),
+            lockup_date: lockup_date_str.to_string(),
+            mint: "".to_string(),
+            custodian: custodian.to_string(),
+            tranche: 1,
+        };
+        let stake_args = StakeArgs {
+            stake_account_address: solana_sdk::pubkey::new_rand(),
+            stake_authority: Box::new(Keypair::new()),
+            withdraw_authority: Box::new(Keypair::new()),
+            lockup_authority: Some(Box::new(Keypair::new())),
+            unlocked_sol: sol_to_lamports(1.0),
+        };
+        let args = DistributeTokensArgs {
+            fee_payer: Box::new(Keypair::new()),
+            dry_run: false,
+            input_csv: "".to_string(),
+            transaction_db: "".to_string(),
+            output_path: None,
+            stake_args: Some(stake_args),
+            spl_token_args: None,
+            sender_keypair: Box::new(Keypair::new()),
+            transfer_amount: None,
+        };
+        let lockup_date: DateTime<Utc> = lockup_date_str.parse().unwrap();
+        let instructions = distribution_instructions(
+            &allocation,
+            &solana_sdk::pubkey::new_rand(),
+            &args,
+            None,
+            Some(lockup_date),
+            false,
+        );
+
+        // Later tranches carry no unlocked SOL
+        assert_eq!(instructions.len(), SET_LOCKUP_INDEX + 1);
+        let lockup_instruction =
+            bincode::deserialize(&instructions[SET_LOCKUP_INDEX].data).unwrap();
+        if let StakeInstruction::SetLockup(lockup_args) = lockup_instruction {
+            assert_eq!(lockup_args.unix_timestamp, Some(lockup_date.timestamp()));
+            assert_eq!(lockup_args.custodian, Some(custodian));
+        } else {
+            panic!("expected SetLockup instruction");
+        }
+    }
+
+    #[test]
+    fn test_read_allocations_vesting() {
+        let pubkey0 = solana_sdk::pubkey::new_rand();
+        let pubkey1 = solana_sdk::pubkey::new_rand();
+        let custodian = solana_sdk::pubkey::new_rand();
+        let file = NamedTempFile::new().unwrap();
+        let input_csv = file.path().to_str().unwrap().to_string();
+        let mut wtr = csv::WriterBuilder::new().from_writer(file);
+        wtr.serialize((
+            "recipient",
+            "amount",
+            "lockup_date",
+            "custodian",
+            "tranches",
+        ))
+        .unwrap();
+        wtr.serialize((&pubkey0.to_string(), 42.0, "", "", ""))
+            .unwrap();
+        wtr.serialize((
+            &pubkey1.to_string(),
+            10.0,
+            "2021-01-31T00:00:00Z",
+            &custodian.to_string(),
+            "3",
+        ))
+        .unwrap();
+        wtr.flush().unwrap();
+
+        let tranche = |amount, lockup_date: &str, index| Allocation {
+            recipient: pubkey1.to_string(),
+            amount,
+            lockup_date: lockup_date.to_string(),
+            mint: "".to_string(),
+            custodian: custodian.to_string(),
+            tranche: index,
+        };
+        let expected_allocations = vec![
+            Allocation {
+                recipient: pubkey0.to_string(),
+                amount: sol_to_lamports(42.0),
+                ..Allocation::default()
+            },
+            tranche(3_333_333_334, "2021-01-31T00:00:00Z", 0),
+            tranche(3_333_333_333, "2021-02-28T00:00:00Z", 1),
+            tranche(3_333_333_333, "2021-03-31T00:00:00Z", 2),
+        ];
+        assert_eq!(
+            read_allocations(&input_csv, None, true, false).unwrap(),
+            expected_allocations
+        );
+    }
+
+    #[test]
+    fn test_read_allocations_vesting_invalid() {
+        let recipient = solana_sdk::pubkey::new_rand().to_string();
+        for (lockup_date, custodian, tranches) in &[
+            ("", "", "2"),
+            ("2021-02-30T00:00:00Z", "", "1"),
+            ("2021-01-31T00:00:00Z", "not a pubkey", "1"),
+        ] {
+            let file = NamedTempFile::new().unwrap();
+            let input_csv = file.path().to_str().unwrap().to_string();
+            let mut wtr = csv::WriterBuilder::new().from_writer(file);
+            wtr.serialize((
+                "recipient",
+                "amount",
+                "lockup_date",
+                "custodian",
+                "tranches",
+            ))
+            .unwrap();
+            wtr.serialize((&recipient, 10.0, lockup_date, custodian, tranches))
+                .unwrap();
+            wtr.flush().unwrap();
+
+            let err = read_allocations(&input_csv, None, true, false).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_add_months() {
+        let date = |date: &str| date.parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            add_months(date("2021-01-07T12:00:00Z"), 0),
+            date("2021-01-07T12:00:00Z")
+        );
+        assert_eq!(
+            add_months(date("2021-01-31T12:00:00Z"), 1),
+            date("2021-02-28T12:00:00Z")
+        );
+        assert_eq!(
+            add_months(date("2021-11-30T00:00:00Z"), 3),
+            date("2022-02-28T00:00:00Z")
+        );
+        assert_eq!(
+            add_months(date("2023-12-31T00:00:00Z"), 2),
+            date("2024-02-29T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_vesting_summaries() {
+        let alice = solana_sdk::pubkey::new_rand();
+        let bob = solana_sdk::pubkey::new_rand();
+        let now = "2021-03-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let info = |recipient, amount, lockup_date: Option<&str>| TransactionInfo {
+            recipient,
+            amount,
+            lockup_date: lockup_date.map(|date| date.parse().unwrap()),
+            ..TransactionInfo::default()
+        };
+        let transaction_infos = vec![
+            TransactionInfo {
+                unlocked_amount: 16,
+                ..info(alice, 17, Some("2021-02-01T00:00:00Z"))
+            },
+            info(alice, 2, Some("2021-05-01T00:00:00Z")),
+            TransactionInfo {
+                unlocked_amount: 16,
+                ..info(alice, 20, Some("2021-04-01T00:00:00Z"))
+            },
+            info(bob, 8, None),
+        ];
+
+        let summaries = vesting_summaries(&transaction_infos, now);
+        assert_eq!(
+            summaries[&alice.to_string()],
+            VestingSummary {
+                tranches: 3,
+                locked: 6,
+                unlocked: 33,
+                next_unlock_date: "2021-04-01T00:00:00Z".parse().ok(),
+            }
+        );
+        assert_eq!(
+            summaries[&bob.to_string()],
+            VestingSummary {
+                tranches: 1,
+                locked: 0,
+                unlocked: 8,
+                next_unlock_date: None,
+            }
+        );
+    }
+
     fn tmp_file_path(name: &str, pubkey: &Pubkey) -> String {
         use std::env;
         let out_dir = env::var("FARF_DIR").unwrap_or_else(|_| "farf".to_string());
@@ -1282,7 +1927,7 @@ mod tests {
         let allocations = vec![Allocation {
             recipient: recipient.to_string(),
             amount: allocation_amount,
-            lockup_date: "".to_string(),
+            ..Allocation::default()
         }];
         let args = DistributeTokensArgs {
             sender_keypair: read_keypair_file(sender_keypair_file).unwrap().into(),
@@ -1535,7 +2180,7 @@ mod tests {
         let expensive_allocations = vec![Allocation {
             recipient: solana_sdk::pubkey::new_rand().to_string(),
             amount: sol_to_lamports(expensive_allocation_amount),
-            lockup_date: "".to_string(),
+            ..Allocation::default()
         }];
         let err_result =
             check_payer_balances(1, &expensive_allocations, &client, &args).unwrap_err();
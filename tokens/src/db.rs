@@ -14,6 +14,13 @@ pub struct TransactionInfo {
     pub transaction: Transaction,
     pub last_valid_slot: Slot,
     pub lockup_date: Option<DateTime<Utc>>,
+    /// SPL token mint, if other than the mint of the distribution's source token account
+    #[serde(default)]
+    pub mint: Option<Pubkey>,
+    /// Lamports of a stake allocation sent to the recipient's system account, which no lockup
+    /// applies to
+    #[serde(default)]
+    pub unlocked_amount: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
@@ -24,6 +31,8 @@ struct SignedTransactionInfo {
     new_stake_account_address: String,
     finalized_date: Option<DateTime<Utc>>,
     signature: String,
+    #[serde(default)]
+    mint: String,
 }
 
 impl Default for TransactionInfo {
@@ -40,6 +49,8 @@ impl Default for TransactionInfo {
             transaction,
             last_valid_slot: 0,
             lockup_date: None,
+            mint: None,
+            unlocked_amount: 0,
         }
     }
 }
@@ -89,6 +100,7 @@ pub fn write_transaction_log<P: AsRef<Path>>(db: &PickleDb, path: &P) -> Result<
                 .unwrap_or_else(|| "".to_string()),
             finalized_date: info.finalized_date,
             signature: info.transaction.signatures[0].to_string(),
+            mint: info.mint.map(|x| x.to_string()).unwrap_or_default(),
         };
         wtr.serialize(&signed_info)?;
     }
@@ -101,6 +113,7 @@ pub fn read_transaction_infos(db: &PickleDb) -> Vec<TransactionInfo> {
         .collect()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn set_transaction_info(
     db: &mut PickleDb,
     recipient: &Pubkey,
@@ -110,6 +123,8 @@ pub fn set_transaction_info(
     finalized: bool,
     last_valid_slot: Slot,
     lockup_date: Option<DateTime<Utc>>,
+    mint: Option<&Pubkey>,
+    unlocked_amount: u64,
 ) -> Result<(), Error> {
     let finalized_date = if finalized { Some(Utc::now()) } else { None };
     let transaction_info = TransactionInfo {
@@ -120,6 +135,8 @@ pub fn set_transaction_info(
         transaction: transaction.clone(),
         last_valid_slot,
         lockup_date,
+        mint: mint.cloned(),
+        unlocked_amount,
     };
     let signature = transaction.signatures[0];
     db.set(&signature.to_string(), &transaction_info)?;
@@ -201,6 +218,7 @@ pub(crate) fn check_output_file(path: &str, db: &PickleDb) {
                 .unwrap_or_else(|| "".to_string()),
             finalized_date: info.finalized_date,
             signature: info.transaction.signatures[0].to_string(),
+            mint: info.mint.map(|x| x.to_string()).unwrap_or_default(),
         })
         .collect();
     assert_eq!(logged_infos, transaction_infos);
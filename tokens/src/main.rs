@@ -30,6 +30,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         Command::TransactionLog(args) => {
             commands::process_transaction_log(&args)?;
         }
+        Command::VestingReport(args) => {
+            commands::process_vesting_report(&args)?;
+        }
     }
     Ok(())
 }
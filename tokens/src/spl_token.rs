@@ -7,7 +7,7 @@ use solana_account_decoder::parse_token::{
     pubkey_from_spl_token_v2_0, spl_token_v2_0_pubkey, token_amount_to_ui_amount,
 };
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{instruction::Instruction, native_token::lamports_to_sol};
+use solana_sdk::{instruction::Instruction, native_token::lamports_to_sol, pubkey::Pubkey};
 use solana_transaction_status::parse_token::spl_token_v2_0_instruction;
 use spl_associated_token_account_v1_0::{
     create_associated_token_account, get_associated_token_address,
@@ -32,13 +32,32 @@ pub fn update_token_args(client: &RpcClient, args: &mut Option<SplTokenArgs>) ->
 
 pub fn update_decimals(client: &RpcClient, args: &mut Option<SplTokenArgs>) -> Result<(), Error> {
     if let Some(spl_token_args) = args {
-        let mint_account = client.get_account(&spl_token_args.mint).unwrap_or_default();
-        let mint = Mint::unpack(&mint_account.data)?;
-        spl_token_args.decimals = mint.decimals;
+        spl_token_args.decimals = get_mint_decimals(client, &spl_token_args.mint)?;
     }
     Ok(())
 }
 
+pub fn get_mint_decimals(client: &RpcClient, mint: &Pubkey) -> Result<u8, Error> {
+    let mint_account = client.get_account(mint).unwrap_or_default();
+    Ok(Mint::unpack(&mint_account.data)?.decimals)
+}
+
+/// Token args for allocations of `mint`, another mint than that of the `--from` token account.
+/// These allocations are sent from `owner`'s associated token account for the mint.
+pub fn associated_spl_token_args(
+    client: &RpcClient,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<SplTokenArgs, Error> {
+    let token_account_address =
+        get_associated_token_address(&spl_token_v2_0_pubkey(owner), &spl_token_v2_0_pubkey(mint));
+    Ok(SplTokenArgs {
+        token_account_address: pubkey_from_spl_token_v2_0(&token_account_address),
+        mint: *mint,
+        decimals: get_mint_decimals(client, mint)?,
+    })
+}
+
 pub fn spl_token_amount(amount: f64, decimals: u8) -> u64 {
     (amount * 10_usize.pow(decimals as u32) as f64) as u64
 }
@@ -46,12 +65,9 @@ pub fn spl_token_amount(amount: f64, decimals: u8) -> u64 {
 pub fn build_spl_token_instructions(
     allocation: &Allocation,
     args: &DistributeTokensArgs,
+    spl_token_args: &SplTokenArgs,
     do_create_associated_token_account: bool,
 ) -> Vec<Instruction> {
-    let spl_token_args = args
-        .spl_token_args
-        .as_ref()
-        .expect("spl_token_args must be some");
     let wallet_address = allocation.recipient.parse().unwrap();
     let associated_token_address = get_associated_token_address(
         &wallet_address,
@@ -88,12 +104,9 @@ pub fn check_spl_token_balances(
     allocations: &[Allocation],
     client: &RpcClient,
     args: &DistributeTokensArgs,
+    spl_token_args: &SplTokenArgs,
     created_accounts: u64,
 ) -> Result<(), Error> {
-    let spl_token_args = args
-        .spl_token_args
-        .as_ref()
-        .expect("spl_token_args must be some");
     let allocation_amount: u64 = allocations.iter().map(|x| x.amount).sum();
 
     let fee_calculator = client.get_recent_blockhash()?.1;
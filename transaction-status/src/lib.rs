@@ -12,19 +12,21 @@ pub mod parse_token;
 pub mod parse_vote;
 
 use crate::{
-    parse_accounts::{parse_accounts, ParsedAccount},
-    parse_instruction::{parse, ParsedInstruction},
+    parse_accounts::{parse_accounts, parse_resolved_accounts, ParsedAccount},
+    parse_instruction::{parse, parse_event, ParsedInstruction, ParsedInstructionEnum},
 };
-pub use solana_runtime::bank::RewardType;
+pub use solana_runtime::bank::{RewardType, TransactionLogEvent, TransactionReturnData};
 use solana_sdk::{
     clock::{Slot, UnixTimestamp},
     commitment_config::CommitmentConfig,
     deserialize_utils::default_on_eof,
     instruction::CompiledInstruction,
-    message::{Message, MessageHeader},
+    message::{
+        LoadedAddresses, Message, MessageAddressTableLookup, MessageHeader, VersionedMessage,
+    },
     pubkey::Pubkey,
     signature::Signature,
-    transaction::{Result, Transaction, TransactionError},
+    transaction::{Result, Transaction, TransactionError, VersionedTransaction},
 };
 use std::fmt;
 
@@ -152,6 +154,12 @@ pub struct TransactionStatusMeta {
     pub inner_instructions: Option<Vec<InnerInstructions>>,
     #[serde(deserialize_with = "default_on_eof")]
     pub log_messages: Option<Vec<String>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub return_data: Option<TransactionReturnData>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub log_events: Option<Vec<TransactionLogEvent>>,
+    #[serde(deserialize_with = "default_on_eof")]
+    pub loaded_addresses: LoadedAddresses,
 }
 
 impl Default for TransactionStatusMeta {
@@ -163,6 +171,83 @@ impl Default for TransactionStatusMeta {
             post_balances: vec![],
             inner_instructions: None,
             log_messages: None,
+            return_data: None,
+            log_events: None,
+            loaded_addresses: LoadedAddresses::default(),
+        }
+    }
+}
+
+/// A duplicate representation of LoadedAddresses for pretty JSON serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl From<&LoadedAddresses> for UiLoadedAddresses {
+    fn from(loaded_addresses: &LoadedAddresses) -> Self {
+        Self {
+            writable: loaded_addresses
+                .writable
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+            readonly: loaded_addresses
+                .readonly
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// A duplicate representation of TransactionReturnData for pretty JSON serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransactionReturnData {
+    pub program_id: String,
+    /// Base64-encoded return data
+    pub data: String,
+}
+
+impl From<TransactionReturnData> for UiTransactionReturnData {
+    fn from(return_data: TransactionReturnData) -> Self {
+        Self {
+            program_id: return_data.program_id.to_string(),
+            data: base64::encode(return_data.data),
+        }
+    }
+}
+
+/// A duplicate representation of TransactionLogEvent for pretty JSON serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTransactionLogEvent {
+    pub program_id: String,
+    /// Base64-encoded event data
+    pub data: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed: Option<ParsedInstructionEnum>,
+}
+
+impl UiTransactionLogEvent {
+    fn parse(event: TransactionLogEvent) -> Self {
+        let parsed = parse_event(&event.program_id, &event.data).ok();
+        Self {
+            parsed,
+            ..event.into()
+        }
+    }
+}
+
+impl From<TransactionLogEvent> for UiTransactionLogEvent {
+    fn from(event: TransactionLogEvent) -> Self {
+        Self {
+            program_id: event.program_id.to_string(),
+            data: event.data.iter().map(base64::encode).collect(),
+            parsed: None,
         }
     }
 }
@@ -178,6 +263,13 @@ pub struct UiTransactionStatusMeta {
     pub post_balances: Vec<u64>,
     pub inner_instructions: Option<Vec<UiInnerInstructions>>,
     pub log_messages: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_data: Option<UiTransactionReturnData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_events: Option<Vec<UiTransactionLogEvent>>,
+    /// Accounts loaded from address lookup tables, omitted for transactions that load none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loaded_addresses: Option<UiLoadedAddresses>,
 }
 
 impl UiTransactionStatusMeta {
@@ -194,6 +286,14 @@ impl UiTransactionStatusMeta {
                     .collect()
             }),
             log_messages: meta.log_messages,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            log_events: meta.log_events.map(|events| {
+                events
+                    .into_iter()
+                    .map(UiTransactionLogEvent::parse)
+                    .collect()
+            }),
+            loaded_addresses: ui_loaded_addresses(&meta.loaded_addresses),
         }
     }
 }
@@ -210,10 +310,23 @@ impl From<TransactionStatusMeta> for UiTransactionStatusMeta {
                 .inner_instructions
                 .map(|ixs| ixs.into_iter().map(|ix| ix.into()).collect()),
             log_messages: meta.log_messages,
+            return_data: meta.return_data.map(|return_data| return_data.into()),
+            log_events: meta
+                .log_events
+                .map(|events| events.into_iter().map(|event| event.into()).collect()),
+            loaded_addresses: ui_loaded_addresses(&meta.loaded_addresses),
         }
     }
 }
 
+fn ui_loaded_addresses(loaded_addresses: &LoadedAddresses) -> Option<UiLoadedAddresses> {
+    if loaded_addresses.is_empty() {
+        None
+    } else {
+        Some(loaded_addresses.into())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionStatus {
@@ -337,6 +450,28 @@ pub struct UiRawMessage {
     pub account_keys: Vec<String>,
     pub recent_blockhash: String,
     pub instructions: Vec<UiCompiledInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
+}
+
+/// A duplicate representation of a MessageAddressTableLookup, in raw format, for pretty JSON
+/// serialization
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAddressTableLookup {
+    pub account_key: String,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+impl From<&MessageAddressTableLookup> for UiAddressTableLookup {
+    fn from(lookup: &MessageAddressTableLookup) -> Self {
+        Self {
+            account_key: lookup.account_key.to_string(),
+            writable_indexes: lookup.writable_indexes.clone(),
+            readonly_indexes: lookup.readonly_indexes.clone(),
+        }
+    }
 }
 
 /// A duplicate representation of a Message, in parsed format, for pretty JSON serialization
@@ -351,16 +486,43 @@ pub struct UiParsedMessage {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionWithStatusMeta {
-    pub transaction: Transaction,
+    pub transaction: VersionedTransaction,
     pub meta: Option<TransactionStatusMeta>,
 }
 
 impl TransactionWithStatusMeta {
+    /// Returns the transaction with its message resolved to a legacy message, using the
+    /// addresses the transaction loaded from lookup tables
+    pub fn resolved_transaction(&self) -> Transaction {
+        let message = match &self.transaction.message {
+            VersionedMessage::Legacy(message) => message.clone(),
+            VersionedMessage::V0(message) => message
+                .clone()
+                .into_legacy_message(&self.loaded_addresses()),
+        };
+        Transaction {
+            signatures: self.transaction.signatures.clone(),
+            message,
+        }
+    }
+
+    fn loaded_addresses(&self) -> LoadedAddresses {
+        self.meta
+            .as_ref()
+            .map(|meta| meta.loaded_addresses.clone())
+            .unwrap_or_default()
+    }
+
     fn encode(self, encoding: UiTransactionEncoding) -> EncodedTransactionWithStatusMeta {
-        let message = self.transaction.message();
-        let meta = self.meta.map(|meta| meta.encode(encoding, message));
+        let loaded_addresses = self.loaded_addresses();
+        let message = self.resolved_transaction().message;
+        let meta = self.meta.map(|meta| meta.encode(encoding, &message));
         EncodedTransactionWithStatusMeta {
-            transaction: EncodedTransaction::encode(self.transaction, encoding),
+            transaction: EncodedTransaction::encode_versioned(
+                self.transaction,
+                &loaded_addresses,
+                encoding,
+            ),
             meta,
         }
     }
@@ -439,6 +601,7 @@ impl EncodedTransaction {
                             .iter()
                             .map(|instruction| instruction.into())
                             .collect(),
+                        address_table_lookups: None,
                     })
                 } else {
                     UiMessage::Parsed(UiParsedMessage {
@@ -465,26 +628,109 @@ impl EncodedTransaction {
             }
         }
     }
+
+    /// Encode a versioned transaction. Address table lookups are shown as-is in the `Json`
+    /// encoding; `JsonParsed` shows the message resolved with `loaded_addresses`, marking which
+    /// accounts were loaded from lookup tables.
+    pub fn encode_versioned(
+        transaction: VersionedTransaction,
+        loaded_addresses: &LoadedAddresses,
+        encoding: UiTransactionEncoding,
+    ) -> Self {
+        let message = match transaction.message {
+            VersionedMessage::Legacy(message) => {
+                return Self::encode(
+                    Transaction {
+                        signatures: transaction.signatures,
+                        message,
+                    },
+                    encoding,
+                );
+            }
+            VersionedMessage::V0(message) => message,
+        };
+        let signatures = transaction
+            .signatures
+            .iter()
+            .map(|sig| sig.to_string())
+            .collect();
+        let message = match encoding {
+            UiTransactionEncoding::Binary
+            | UiTransactionEncoding::Base58
+            | UiTransactionEncoding::Base64 => {
+                let transaction = VersionedTransaction {
+                    signatures: transaction.signatures,
+                    message: VersionedMessage::V0(message),
+                };
+                let bytes = bincode::serialize(&transaction).unwrap();
+                return match encoding {
+                    UiTransactionEncoding::Base64 => {
+                        EncodedTransaction::Binary(base64::encode(bytes), encoding)
+                    }
+                    UiTransactionEncoding::Base58 => {
+                        EncodedTransaction::Binary(bs58::encode(bytes).into_string(), encoding)
+                    }
+                    _ => EncodedTransaction::LegacyBinary(bs58::encode(bytes).into_string()),
+                };
+            }
+            UiTransactionEncoding::Json => UiMessage::Raw(UiRawMessage {
+                header: message.header,
+                account_keys: message
+                    .account_keys
+                    .iter()
+                    .map(|pubkey| pubkey.to_string())
+                    .collect(),
+                recent_blockhash: message.recent_blockhash.to_string(),
+                instructions: message
+                    .instructions
+                    .iter()
+                    .map(|instruction| instruction.into())
+                    .collect(),
+                address_table_lookups: Some(
+                    message
+                        .address_table_lookups
+                        .iter()
+                        .map(|lookup| lookup.into())
+                        .collect(),
+                ),
+            }),
+            UiTransactionEncoding::JsonParsed => {
+                let message = message.into_legacy_message(loaded_addresses);
+                UiMessage::Parsed(UiParsedMessage {
+                    account_keys: parse_resolved_accounts(&message, loaded_addresses),
+                    recent_blockhash: message.recent_blockhash.to_string(),
+                    instructions: message
+                        .instructions
+                        .iter()
+                        .map(|instruction| UiInstruction::parse(instruction, &message))
+                        .collect(),
+                })
+            }
+        };
+        EncodedTransaction::Json(UiTransaction {
+            signatures,
+            message,
+        })
+    }
+
+    /// Decode a legacy transaction, versioned transactions decode to `None`
     pub fn decode(&self) -> Option<Transaction> {
-        match self {
+        self.decode_versioned()?.into_legacy_transaction()
+    }
+
+    pub fn decode_versioned(&self) -> Option<VersionedTransaction> {
+        let bytes = match self {
             EncodedTransaction::Json(_) => None,
-            EncodedTransaction::LegacyBinary(blob) => bs58::decode(blob)
-                .into_vec()
-                .ok()
-                .and_then(|bytes| bincode::deserialize(&bytes).ok()),
+            EncodedTransaction::LegacyBinary(blob) => bs58::decode(blob).into_vec().ok(),
             EncodedTransaction::Binary(blob, encoding) => match *encoding {
-                UiTransactionEncoding::Base58 => bs58::decode(blob)
-                    .into_vec()
-                    .ok()
-                    .and_then(|bytes| bincode::deserialize(&bytes).ok()),
-                UiTransactionEncoding::Base64 => base64::decode(blob)
-                    .ok()
-                    .and_then(|bytes| bincode::deserialize(&bytes).ok()),
+                UiTransactionEncoding::Base58 => bs58::decode(blob).into_vec().ok(),
+                UiTransactionEncoding::Base64 => base64::decode(blob).ok(),
                 UiTransactionEncoding::Binary
                 | UiTransactionEncoding::Json
                 | UiTransactionEncoding::JsonParsed => None,
             },
-        }
+        }?;
+        bincode::deserialize(&bytes).ok()
     }
 }
 
@@ -514,4 +760,148 @@ mod test {
         assert!(!status.satisfies_commitment(CommitmentConfig::default()));
         assert!(status.satisfies_commitment(CommitmentConfig::recent()));
     }
+
+    #[test]
+    fn test_ui_transaction_status_meta_loaded_addresses() {
+        let meta = TransactionStatusMeta::default();
+        assert_eq!(UiTransactionStatusMeta::from(meta).loaded_addresses, None);
+
+        let writable = Pubkey::new_unique();
+        let readonly = Pubkey::new_unique();
+        let meta = TransactionStatusMeta {
+            loaded_addresses: LoadedAddresses {
+                writable: vec![writable],
+                readonly: vec![readonly],
+            },
+            ..TransactionStatusMeta::default()
+        };
+        assert_eq!(
+            UiTransactionStatusMeta::from(meta).loaded_addresses,
+            Some(UiLoadedAddresses {
+                writable: vec![writable.to_string()],
+                readonly: vec![readonly.to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_encode_versioned_transaction() {
+        use solana_sdk::{message::MessageV0, system_instruction};
+
+        let payer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let table_key = Pubkey::new_unique();
+        let message = MessageV0::new_with_lookup_tables(
+            &[system_instruction::transfer(&payer, &to, 1)],
+            Some(&payer),
+            &[(table_key, vec![to])],
+        );
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(message),
+        };
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![to],
+            readonly: vec![],
+        };
+
+        let encoded = EncodedTransaction::encode_versioned(
+            transaction.clone(),
+            &loaded_addresses,
+            UiTransactionEncoding::Base64,
+        );
+        assert_eq!(encoded.decode_versioned(), Some(transaction.clone()));
+        assert_eq!(encoded.decode(), None);
+
+        match EncodedTransaction::encode_versioned(
+            transaction.clone(),
+            &loaded_addresses,
+            UiTransactionEncoding::Json,
+        ) {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Raw(message),
+                ..
+            }) => {
+                assert_eq!(message.account_keys.len(), 2);
+                assert_eq!(
+                    message.address_table_lookups,
+                    Some(vec![UiAddressTableLookup {
+                        account_key: table_key.to_string(),
+                        writable_indexes: vec![0],
+                        readonly_indexes: vec![],
+                    }])
+                );
+            }
+            encoded => panic!("unexpected encoding: {:?}", encoded),
+        }
+
+        match EncodedTransaction::encode_versioned(
+            transaction,
+            &loaded_addresses,
+            UiTransactionEncoding::JsonParsed,
+        ) {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Parsed(message),
+                ..
+            }) => {
+                assert_eq!(message.account_keys.len(), 3);
+                assert_eq!(message.account_keys[1].pubkey, to.to_string());
+                assert_eq!(
+                    message.account_keys[1].source,
+                    Some(parse_accounts::ParsedAccountSource::LookupTable)
+                );
+            }
+            encoded => panic!("unexpected encoding: {:?}", encoded),
+        }
+    }
+
+    #[test]
+    fn test_encode_transaction_with_status_meta_versioned() {
+        use solana_sdk::{message::MessageV0, system_instruction};
+
+        let payer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let message = MessageV0::new_with_lookup_tables(
+            &[system_instruction::transfer(&payer, &to, 1)],
+            Some(&payer),
+            &[(Pubkey::new_unique(), vec![to])],
+        );
+        let transaction_with_meta = TransactionWithStatusMeta {
+            transaction: VersionedTransaction {
+                signatures: vec![Signature::default()],
+                message: VersionedMessage::V0(message),
+            },
+            meta: Some(TransactionStatusMeta {
+                pre_balances: vec![2, 0, 1],
+                post_balances: vec![1, 1, 1],
+                loaded_addresses: LoadedAddresses {
+                    writable: vec![to],
+                    readonly: vec![],
+                },
+                ..TransactionStatusMeta::default()
+            }),
+        };
+
+        let resolved_transaction = transaction_with_meta.resolved_transaction();
+        assert_eq!(resolved_transaction.message.account_keys.len(), 3);
+        assert_eq!(resolved_transaction.message.account_keys[1], to);
+
+        let encoded = transaction_with_meta.encode(UiTransactionEncoding::JsonParsed);
+        match encoded.transaction {
+            EncodedTransaction::Json(UiTransaction {
+                message: UiMessage::Parsed(message),
+                ..
+            }) => {
+                assert_eq!(message.account_keys[1].pubkey, to.to_string());
+            }
+            encoded => panic!("unexpected encoding: {:?}", encoded),
+        }
+        assert_eq!(
+            encoded.meta.unwrap().loaded_addresses,
+            Some(UiLoadedAddresses {
+                writable: vec![to.to_string()],
+                readonly: vec![],
+            })
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use solana_sdk::message::Message;
+use solana_sdk::message::{LoadedAddresses, Message};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -6,6 +6,15 @@ pub struct ParsedAccount {
     pub pubkey: String,
     pub writable: bool,
     pub signer: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ParsedAccountSource>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ParsedAccountSource {
+    Transaction,
+    LookupTable,
 }
 
 pub fn parse_accounts(message: &Message) -> Vec<ParsedAccount> {
@@ -15,6 +24,26 @@ pub fn parse_accounts(message: &Message) -> Vec<ParsedAccount> {
             pubkey: account_key.to_string(),
             writable: message.is_writable(i),
             signer: message.is_signer(i),
+            source: None,
+        });
+    }
+    accounts
+}
+
+/// Parse the account keys of a message resolved from a versioned message, marking each key with
+/// whether it was included in the transaction or loaded from an address lookup table
+pub fn parse_resolved_accounts(
+    message: &Message,
+    loaded_addresses: &LoadedAddresses,
+) -> Vec<ParsedAccount> {
+    let mut accounts = parse_accounts(message);
+    for (account, account_key) in accounts.iter_mut().zip(message.account_keys.iter()) {
+        let loaded = loaded_addresses.writable.contains(account_key)
+            || loaded_addresses.readonly.contains(account_key);
+        account.source = Some(if loaded {
+            ParsedAccountSource::LookupTable
+        } else {
+            ParsedAccountSource::Transaction
         });
     }
     accounts
@@ -46,21 +75,67 @@ mod test {
                     pubkey: pubkey0.to_string(),
                     writable: true,
                     signer: true,
+                    source: None,
                 },
                 ParsedAccount {
                     pubkey: pubkey1.to_string(),
                     writable: false,
                     signer: true,
+                    source: None,
                 },
                 ParsedAccount {
                     pubkey: pubkey2.to_string(),
                     writable: true,
                     signer: false,
+                    source: None,
                 },
                 ParsedAccount {
                     pubkey: pubkey3.to_string(),
                     writable: false,
                     signer: false,
+                    source: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_resolved_accounts() {
+        let pubkey0 = solana_sdk::pubkey::new_rand();
+        let pubkey1 = solana_sdk::pubkey::new_rand();
+        let pubkey2 = solana_sdk::pubkey::new_rand();
+        let mut message = Message::default();
+        message.header = MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        };
+        message.account_keys = vec![pubkey0, pubkey1, pubkey2];
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![pubkey1],
+            readonly: vec![pubkey2],
+        };
+
+        assert_eq!(
+            parse_resolved_accounts(&message, &loaded_addresses),
+            vec![
+                ParsedAccount {
+                    pubkey: pubkey0.to_string(),
+                    writable: true,
+                    signer: true,
+                    source: Some(ParsedAccountSource::Transaction),
+                },
+                ParsedAccount {
+                    pubkey: pubkey1.to_string(),
+                    writable: true,
+                    signer: false,
+                    source: Some(ParsedAccountSource::LookupTable),
+                },
+                ParsedAccount {
+                    pubkey: pubkey2.to_string(),
+                    writable: false,
+                    signer: false,
+                    source: Some(ParsedAccountSource::LookupTable),
                 },
             ]
         );
@@ -3,12 +3,16 @@ use crate::{
     parse_token::parse_token, parse_vote::parse_vote,
 };
 use inflector::Inflector;
-use serde_json::Value;
+use serde_json::{Map, Value};
 use solana_account_decoder::parse_token::spl_token_id_v2_0;
 use solana_sdk::{instruction::CompiledInstruction, pubkey::Pubkey, system_program};
 use std::{
     collections::HashMap,
+    convert::TryInto,
+    fs::File,
+    path::Path,
     str::{from_utf8, FromStr},
+    sync::RwLock,
 };
 use thiserror::Error;
 
@@ -30,6 +34,8 @@ lazy_static! {
         m.insert(*VOTE_PROGRAM_ID, ParsableProgram::Vote);
         m
     };
+    static ref EVENT_LAYOUTS: RwLock<HashMap<Pubkey, Vec<EventLayout>>> =
+        RwLock::new(HashMap::new());
 }
 
 #[derive(Error, Debug)]
@@ -43,6 +49,9 @@ pub enum ParseInstructionError {
     #[error("Program not parsable")]
     ProgramNotParsable,
 
+    #[error("Program event not parsable")]
+    EventNotParsable,
+
     #[error("Internal error, please report")]
     SerdeJsonError(#[from] serde_json::error::Error),
 }
@@ -55,7 +64,7 @@ pub struct ParsedInstruction {
     pub parsed: Value,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ParsedInstructionEnum {
     #[serde(rename = "type")]
@@ -100,6 +109,116 @@ pub fn parse(
     })
 }
 
+/// Type of an event field, decoded from one slice of the event data.  Integers are little-endian
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum EventFieldType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I64,
+    Pubkey,
+    String,
+    Bytes,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: EventFieldType,
+}
+
+/// IDL-like description of an event a program emits with `sol_log_data`.  The event's first
+/// slice identifies it and must equal `discriminator`; each following slice is one field
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventLayout {
+    pub name: String,
+    pub discriminator: Vec<u8>,
+    pub fields: Vec<EventField>,
+}
+
+/// Register the events `program_id` emits, so `jsonParsed` output decodes them.  Replaces any
+/// layouts previously registered for the program
+pub fn register_event_layouts(program_id: Pubkey, layouts: Vec<EventLayout>) {
+    EVENT_LAYOUTS.write().unwrap().insert(program_id, layouts);
+}
+
+/// Register the event layouts in a JSON file that maps base-58 program ids to their layouts
+pub fn register_event_layouts_from_file(path: &Path) -> Result<(), String> {
+    let file = File::open(path).map_err(|err| format!("Unable to open {:?}: {}", path, err))?;
+    let programs: HashMap<String, Vec<EventLayout>> = serde_json::from_reader(file)
+        .map_err(|err| format!("Unable to parse {:?}: {}", path, err))?;
+    for (program_id, layouts) in programs {
+        let program_id = Pubkey::from_str(&program_id)
+            .map_err(|err| format!("Invalid program id {}: {}", program_id, err))?;
+        register_event_layouts(program_id, layouts);
+    }
+    Ok(())
+}
+
+pub fn parse_event(
+    program_id: &Pubkey,
+    data: &[Vec<u8>],
+) -> Result<ParsedInstructionEnum, ParseInstructionError> {
+    let event_layouts = EVENT_LAYOUTS.read().unwrap();
+    let layouts = event_layouts
+        .get(program_id)
+        .ok_or(ParseInstructionError::ProgramNotParsable)?;
+    let (discriminator, fields) = data
+        .split_first()
+        .ok_or(ParseInstructionError::EventNotParsable)?;
+    let layout = layouts
+        .iter()
+        .find(|layout| &layout.discriminator == discriminator)
+        .ok_or(ParseInstructionError::EventNotParsable)?;
+    if layout.fields.len() != fields.len() {
+        return Err(ParseInstructionError::EventNotParsable);
+    }
+
+    let mut info = Map::new();
+    for (field, bytes) in layout.fields.iter().zip(fields) {
+        let value = parse_event_field(field.field_type, bytes)
+            .ok_or(ParseInstructionError::EventNotParsable)?;
+        info.insert(field.name.clone(), value);
+    }
+    Ok(ParsedInstructionEnum {
+        instruction_type: layout.name.clone(),
+        info: Value::Object(info),
+    })
+}
+
+fn parse_event_field(field_type: EventFieldType, bytes: &[u8]) -> Option<Value> {
+    let value = match field_type {
+        EventFieldType::Bool => match bytes {
+            [0] => Value::Bool(false),
+            [1] => Value::Bool(true),
+            _ => return None,
+        },
+        EventFieldType::U8 => Value::from(u8::from_le_bytes(bytes.try_into().ok()?)),
+        EventFieldType::U16 => Value::from(u16::from_le_bytes(bytes.try_into().ok()?)),
+        EventFieldType::U32 => Value::from(u32::from_le_bytes(bytes.try_into().ok()?)),
+        // Stringify 64-bit integers, which JSON consumers can't represent exactly as numbers
+        EventFieldType::U64 => {
+            Value::String(u64::from_le_bytes(bytes.try_into().ok()?).to_string())
+        }
+        EventFieldType::I64 => {
+            Value::String(i64::from_le_bytes(bytes.try_into().ok()?).to_string())
+        }
+        EventFieldType::Pubkey => {
+            let bytes: [u8; 32] = bytes.try_into().ok()?;
+            Value::String(Pubkey::new_from_array(bytes).to_string())
+        }
+        EventFieldType::String => Value::String(from_utf8(bytes).ok()?.to_string()),
+        EventFieldType::Bytes => Value::String(base64::encode(bytes)),
+    };
+    Some(value)
+}
+
 fn parse_memo(instruction: &CompiledInstruction) -> Value {
     Value::String(from_utf8(&instruction.data).unwrap().to_string())
 }
@@ -142,4 +261,58 @@ mod test {
         let non_parsable_program_id = Pubkey::new(&[1; 32]);
         assert!(parse(&non_parsable_program_id, &memo_instruction, &[]).is_err());
     }
+
+    #[test]
+    fn test_parse_event() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let data = vec![
+            b"transfer".to_vec(),
+            owner.to_bytes().to_vec(),
+            42u64.to_le_bytes().to_vec(),
+            vec![1],
+            b"memo".to_vec(),
+        ];
+        assert!(parse_event(&program_id, &data).is_err());
+
+        register_event_layouts(
+            program_id,
+            serde_json::from_value(json!([{
+                "name": "transfer",
+                "discriminator": b"transfer".to_vec(),
+                "fields": [
+                    {"name": "owner", "type": "pubkey"},
+                    {"name": "amount", "type": "u64"},
+                    {"name": "final", "type": "bool"},
+                    {"name": "memo", "type": "string"},
+                ],
+            }]))
+            .unwrap(),
+        );
+        assert_eq!(
+            parse_event(&program_id, &data).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "transfer".to_string(),
+                info: json!({
+                    "owner": owner.to_string(),
+                    "amount": "42",
+                    "final": true,
+                    "memo": "memo",
+                }),
+            }
+        );
+
+        // Unknown discriminator
+        let mut bad_data = data.clone();
+        bad_data[0] = b"burn".to_vec();
+        assert!(parse_event(&program_id, &bad_data).is_err());
+
+        // Field count mismatch
+        assert!(parse_event(&program_id, &data[..4]).is_err());
+
+        // Field size mismatch
+        let mut bad_data = data;
+        bad_data[2] = 42u32.to_le_bytes().to_vec();
+        assert!(parse_event(&program_id, &bad_data).is_err());
+    }
 }
@@ -163,6 +163,21 @@ pub fn parse_stake(
                 }),
             })
         }
+        StakeInstruction::Redelegate => {
+            check_num_stake_accounts(&instruction.accounts, 7)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "redelegate".to_string(),
+                info: json!({
+                    "stakeAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "newStakeAccount": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "voteAccount": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "clockSysvar": account_keys[instruction.accounts[3] as usize].to_string(),
+                    "stakeHistorySysvar": account_keys[instruction.accounts[4] as usize].to_string(),
+                    "stakeConfigAccount": account_keys[instruction.accounts[5] as usize].to_string(),
+                    "stakeAuthority": account_keys[instruction.accounts[6] as usize].to_string(),
+                }),
+            })
+        }
     }
 }
 
@@ -183,7 +198,7 @@ mod test {
     #[allow(clippy::same_item_push)]
     fn test_parse_stake_instruction() {
         let mut keys: Vec<Pubkey> = vec![];
-        for _ in 0..6 {
+        for _ in 0..7 {
             keys.push(solana_sdk::pubkey::new_rand());
         }
 
@@ -368,6 +383,26 @@ mod test {
             }
         );
         assert!(parse_stake(&message.instructions[0], &keys[0..1]).is_err());
+
+        let instruction =
+            stake_instruction::redelegate(&keys[1], &keys[0], &keys[3], &keys[2]).remove(1);
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_stake(&message.instructions[0], &keys[0..7]).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "redelegate".to_string(),
+                info: json!({
+                    "stakeAccount": keys[1].to_string(),
+                    "newStakeAccount": keys[2].to_string(),
+                    "voteAccount": keys[3].to_string(),
+                    "clockSysvar": keys[4].to_string(),
+                    "stakeHistorySysvar": keys[5].to_string(),
+                    "stakeConfigAccount": keys[6].to_string(),
+                    "stakeAuthority": keys[0].to_string(),
+                }),
+            }
+        );
+        assert!(parse_stake(&message.instructions[0], &keys[0..6]).is_err());
     }
 
     #[test]
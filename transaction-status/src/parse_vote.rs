@@ -2,9 +2,9 @@ use crate::parse_instruction::{
     check_num_accounts, ParsableProgram, ParseInstructionError, ParsedInstructionEnum,
 };
 use bincode::deserialize;
-use serde_json::json;
+use serde_json::{json, Value};
 use solana_sdk::{instruction::CompiledInstruction, pubkey::Pubkey};
-use solana_vote_program::vote_instruction::VoteInstruction;
+use solana_vote_program::{vote_instruction::VoteInstruction, vote_state::VoteStateUpdate};
 
 pub fn parse_vote(
     instruction: &CompiledInstruction,
@@ -121,9 +121,55 @@ pub fn parse_vote(
                 }),
             })
         }
+        VoteInstruction::UpdateVoteState(vote_state_update) => {
+            check_num_vote_accounts(&instruction.accounts, 4)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "updateVoteState".to_string(),
+                info: json!({
+                    "voteAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "slotHashesSysvar": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "clockSysvar": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "voteAuthority": account_keys[instruction.accounts[3] as usize].to_string(),
+                    "voteStateUpdate": parse_vote_state_update(&vote_state_update),
+                }),
+            })
+        }
+        VoteInstruction::UpdateVoteStateSwitch(vote_state_update, hash) => {
+            check_num_vote_accounts(&instruction.accounts, 4)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "updateVoteStateSwitch".to_string(),
+                info: json!({
+                    "voteAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "slotHashesSysvar": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "clockSysvar": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "voteAuthority": account_keys[instruction.accounts[3] as usize].to_string(),
+                    "voteStateUpdate": parse_vote_state_update(&vote_state_update),
+                    "hash": hash.to_string(),
+                }),
+            })
+        }
     }
 }
 
+fn parse_vote_state_update(vote_state_update: &VoteStateUpdate) -> Value {
+    let lockouts: Vec<Value> = vote_state_update
+        .lockouts
+        .iter()
+        .map(|lockout| {
+            json!({
+                "slot": lockout.slot,
+                "confirmationCount": lockout.confirmation_count,
+            })
+        })
+        .collect();
+    json!({
+        "lockouts": lockouts,
+        "root": vote_state_update.root,
+        "hash": vote_state_update.hash.to_string(),
+        "timestamp": vote_state_update.timestamp,
+    })
+}
+
 fn check_num_vote_accounts(accounts: &[u8], num: usize) -> Result<(), ParseInstructionError> {
     check_num_accounts(accounts, num, ParsableProgram::Vote)
 }
@@ -134,7 +180,7 @@ mod test {
     use solana_sdk::{hash::Hash, message::Message, pubkey::Pubkey};
     use solana_vote_program::{
         vote_instruction,
-        vote_state::{Vote, VoteAuthorize, VoteInit},
+        vote_state::{Lockout, Vote, VoteAuthorize, VoteInit},
     };
 
     #[test]
@@ -294,5 +340,45 @@ mod test {
             }
         );
         assert!(parse_vote(&message.instructions[0], &keys[0..3]).is_err());
+
+        let mut vote_state_update = VoteStateUpdate::new(
+            vec![
+                Lockout {
+                    slot: 2,
+                    confirmation_count: 2,
+                },
+                Lockout::new(4),
+            ]
+            .into_iter()
+            .collect(),
+            Some(1),
+            hash,
+        );
+        vote_state_update.timestamp = Some(1_234_567_890);
+        let instruction =
+            vote_instruction::update_vote_state(&keys[1], &keys[0], vote_state_update);
+        let message = Message::new(&[instruction], None);
+        assert_eq!(
+            parse_vote(&message.instructions[0], &keys[0..4]).unwrap(),
+            ParsedInstructionEnum {
+                instruction_type: "updateVoteState".to_string(),
+                info: json!({
+                    "voteAccount": keys[1].to_string(),
+                    "slotHashesSysvar": keys[2].to_string(),
+                    "clockSysvar": keys[3].to_string(),
+                    "voteAuthority": keys[0].to_string(),
+                    "voteStateUpdate": {
+                        "lockouts": [
+                            {"slot": 2, "confirmationCount": 2},
+                            {"slot": 4, "confirmationCount": 1},
+                        ],
+                        "root": 1,
+                        "hash": hash.to_string(),
+                        "timestamp": 1_234_567_890,
+                    },
+                }),
+            }
+        );
+        assert!(parse_vote(&message.instructions[0], &keys[0..3]).is_err());
     }
 }
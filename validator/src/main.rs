@@ -3,17 +3,19 @@ use clap::{
     ArgMatches,
 };
 use log::*;
-use rand::{thread_rng, Rng};
+use rand::{seq::SliceRandom, thread_rng, Rng};
 use solana_clap_utils::{
-    input_parsers::{keypair_of, keypairs_of, pubkey_of},
+    input_parsers::{keypair_of, keypairs_or_remote_signers_of, pubkey_of},
     input_validators::{
-        is_keypair_or_ask_keyword, is_parsable, is_pubkey, is_pubkey_or_keypair, is_slot,
+        is_keypair_or_ask_keyword, is_keypair_or_ask_keyword_or_remote_signer, is_parsable,
+        is_pubkey, is_pubkey_or_keypair, is_slot, is_url,
     },
-    keypair::SKIP_SEED_PHRASE_VALIDATION_ARG,
+    keypair::{REMOTE_SIGNER_PREFIX, SKIP_SEED_PHRASE_VALIDATION_ARG},
 };
 use solana_client::rpc_client::RpcClient;
 use solana_core::ledger_cleanup_service::{
-    DEFAULT_MAX_LEDGER_SHREDS, DEFAULT_MIN_MAX_LEDGER_SHREDS,
+    LedgerRetentionConfig, RetentionPolicy, DEFAULT_MAX_LEDGER_SHREDS,
+    DEFAULT_MIN_MAX_LEDGER_SHREDS,
 };
 use solana_core::{
     cluster_info::{ClusterInfo, Node, MINIMUM_VALIDATOR_PORT_RANGE_WIDTH, VALIDATOR_PORT_RANGE},
@@ -21,6 +23,7 @@ use solana_core::{
     gossip_service::GossipService,
     rpc::JsonRpcConfig,
     rpc_pubsub_service::PubSubConfig,
+    tower_storage::{EtcdKeyValueStore, RemoteTowerStorage, TowerStorage},
     validator::{Validator, ValidatorConfig},
 };
 use solana_download_utils::{download_genesis_if_missing, download_snapshot};
@@ -55,12 +58,27 @@ use std::{
     time::{Duration, Instant},
 };
 
+// Maximum number of RPC nodes a snapshot is downloaded from in parallel
+const MAX_SNAPSHOT_DOWNLOAD_PEERS: usize = 4;
+
 fn port_validator(port: String) -> Result<(), String> {
     port.parse::<u16>()
         .map(|_| ())
         .map_err(|e| format!("{:?}", e))
 }
 
+// The identity also signs gossip messages and shreds, which a remote signer refuses to sign, so
+// only the authorized voters may be remote signers
+fn identity_validator(identity: String) -> Result<(), String> {
+    if identity.starts_with(REMOTE_SIGNER_PREFIX) {
+        return Err(
+            "the identity must be a keypair, use a remote signer with --authorized-voter instead"
+                .to_string(),
+        );
+    }
+    is_keypair_or_ask_keyword(identity)
+}
+
 fn port_range_validator(port_range: String) -> Result<(), String> {
     if let Some((start, end)) = solana_net_utils::parse_port_range(&port_range) {
         if end - start < MINIMUM_VALIDATOR_PORT_RANGE_WIDTH {
@@ -148,7 +166,7 @@ fn get_rpc_node(
     snapshot_not_required: bool,
     no_untrusted_rpc: bool,
     ledger_path: &std::path::Path,
-) -> Option<(ContactInfo, Option<(Slot, Hash)>)> {
+) -> Option<(ContactInfo, Option<(Slot, Hash)>, Vec<SocketAddr>)> {
     let mut blacklist_timeout = Instant::now();
     let mut newer_cluster_snapshot_timeout = None;
     let mut retry_reason = None;
@@ -309,7 +327,19 @@ fn get_rpc_node(
         if !eligible_rpc_peers.is_empty() {
             let contact_info =
                 &eligible_rpc_peers[thread_rng().gen_range(0, eligible_rpc_peers.len())];
-            return Some((contact_info.clone(), highest_snapshot_hash));
+
+            // Every eligible peer advertises the same snapshot hash, so the snapshot can be
+            // fetched from several of them at once, led by the chosen RPC node
+            let mut snapshot_peers: Vec<_> = eligible_rpc_peers
+                .iter()
+                .filter(|rpc_peer| rpc_peer.id != contact_info.id)
+                .map(|rpc_peer| rpc_peer.rpc)
+                .collect();
+            snapshot_peers.shuffle(&mut thread_rng());
+            snapshot_peers.truncate(MAX_SNAPSHOT_DOWNLOAD_PEERS - 1);
+            snapshot_peers.insert(0, contact_info.rpc);
+
+            return Some((contact_info.clone(), highest_snapshot_hash, snapshot_peers));
         } else {
             retry_reason = Some("No snapshots available".to_owned());
         }
@@ -389,6 +419,23 @@ fn hardforks_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Slot>> {
     }
 }
 
+fn retention_policy_of(
+    matches: &ArgMatches<'_>,
+    size_name: &str,
+    age_name: &str,
+) -> Option<RetentionPolicy> {
+    if matches.is_present(size_name) || matches.is_present(age_name) {
+        Some(RetentionPolicy {
+            max_bytes: value_t!(matches, size_name, u64).ok(),
+            max_age: value_t!(matches, age_name, u64)
+                .ok()
+                .map(|hours| Duration::from_secs(hours * 60 * 60)),
+        })
+    } else {
+        None
+    }
+}
+
 fn validators_set(
     identity_pubkey: &Pubkey,
     matches: &ArgMatches<'_>,
@@ -634,7 +681,7 @@ fn rpc_bootstrap(
     identity_keypair: &Arc<Keypair>,
     ledger_path: &Path,
     vote_account: &Pubkey,
-    authorized_voter_keypairs: &[Arc<Keypair>],
+    authorized_voter_keypairs: &[Arc<dyn Signer + Send + Sync>],
     cluster_entrypoint: &ContactInfo,
     validator_config: &mut ValidatorConfig,
     bootstrap_config: RpcBootstrapConfig,
@@ -676,7 +723,7 @@ fn rpc_bootstrap(
         if rpc_node_details.is_none() {
             return;
         }
-        let (rpc_contact_info, snapshot_hash) = rpc_node_details.unwrap();
+        let (rpc_contact_info, snapshot_hash, snapshot_peers) = rpc_node_details.unwrap();
 
         info!(
             "Using RPC service from node {}: {:?}",
@@ -749,7 +796,7 @@ fn rpc_bootstrap(
                                 gossip.take().unwrap();
                             gossip_exit_flag.store(true, Ordering::Relaxed);
                             let ret = download_snapshot(
-                                &rpc_contact_info.rpc,
+                                &snapshot_peers,
                                 &ledger_path,
                                 snapshot_hash,
                                 use_progress_bar,
@@ -815,7 +862,7 @@ fn create_validator(
     identity_keypair: &Arc<Keypair>,
     ledger_path: &Path,
     vote_account: &Pubkey,
-    authorized_voter_keypairs: Vec<Arc<Keypair>>,
+    authorized_voter_keypairs: Vec<Arc<dyn Signer + Send + Sync>>,
     cluster_entrypoint: Option<ContactInfo>,
     mut validator_config: ValidatorConfig,
     rpc_bootstrap_config: RpcBootstrapConfig,
@@ -881,7 +928,7 @@ pub fn main() {
                 .long("identity")
                 .value_name("PATH")
                 .takes_value(true)
-                .validator(is_keypair_or_ask_keyword)
+                .validator(identity_validator)
                 .help("Validator identity keypair"),
         )
         .arg(
@@ -889,10 +936,10 @@ pub fn main() {
                 .long("authorized-voter")
                 .value_name("PATH")
                 .takes_value(true)
-                .validator(is_keypair_or_ask_keyword)
+                .validator(is_keypair_or_ask_keyword_or_remote_signer)
                 .requires("vote_account")
                 .multiple(true)
-                .help("Include an additional authorized voter keypair. \
+                .help("Include an additional authorized voter keypair, or a remote: signer. \
                        May be specified multiple times. \
                        [default: the --identity keypair]"),
         )
@@ -1037,6 +1084,15 @@ pub fn main() {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("rpc_event_layouts")
+                .long("rpc-event-layouts")
+                .value_name("FILE")
+                .takes_value(true)
+                .requires("enable_rpc_transaction_history")
+                .help("JSON file mapping program ids to the layouts of the events they log \
+                       with sol_log_data, used to decode events in \"jsonParsed\" RPC output"),
+        )
         .arg(
             Arg::with_name("health_check_slot_distance")
                 .long("health-check-slot-distance")
@@ -1164,6 +1220,65 @@ pub fn main() {
                 /* .default_value() intentionally not used here! */
                 .help("Keep this amount of shreds in root slots."),
         )
+        .arg(
+            Arg::with_name("limit_shreds_size")
+                .long("limit-shreds-size")
+                .value_name("BYTES")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Purge the oldest shreds once they use more than this much disk space"),
+        )
+        .arg(
+            Arg::with_name("limit_shreds_age")
+                .long("limit-shreds-age")
+                .value_name("HOURS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                // Block ages come from the block times that are only cached along with the
+                // transaction history
+                .requires("enable_rpc_transaction_history")
+                .help("Purge shreds of blocks older than this"),
+        )
+        .arg(
+            Arg::with_name("limit_transaction_status_size")
+                .long("limit-transaction-status-size")
+                .value_name("BYTES")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Retain transaction statuses and address signatures separately from \
+                       shreds, purging the oldest once they use more than this much disk space \
+                       [default: purged along with shreds]"),
+        )
+        .arg(
+            Arg::with_name("limit_transaction_status_age")
+                .long("limit-transaction-status-age")
+                .value_name("HOURS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .requires("enable_rpc_transaction_history")
+                .help("Retain transaction statuses and address signatures separately from \
+                       shreds, purging those of blocks older than this \
+                       [default: purged along with shreds]"),
+        )
+        .arg(
+            Arg::with_name("limit_rewards_size")
+                .long("limit-rewards-size")
+                .value_name("BYTES")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Retain rewards separately from shreds, purging the oldest once they use \
+                       more than this much disk space [default: purged along with shreds]"),
+        )
+        .arg(
+            Arg::with_name("limit_rewards_age")
+                .long("limit-rewards-age")
+                .value_name("HOURS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .requires("enable_rpc_transaction_history")
+                .help("Retain rewards separately from shreds, purging those of blocks older \
+                       than this [default: purged along with shreds]"),
+        )
         .arg(
             Arg::with_name("skip_poh_verify")
                 .long("skip-poh-verify")
@@ -1182,6 +1297,34 @@ pub fn main() {
                 .takes_value(false)
                 .help("Refuse to start if saved tower state is not found"),
         )
+        .arg(
+            Arg::with_name("tower_storage")
+                .long("tower-storage")
+                .possible_values(&["file", "etcd"])
+                .default_value("file")
+                .value_name("STORAGE")
+                .takes_value(true)
+                .help("Where to persist the tower state.  With \"etcd\", the tower is \
+                       updated with compare-and-swap semantics so that only one validator \
+                       instance sharing the same identity can vote at a time"),
+        )
+        .arg(
+            Arg::with_name("etcd_endpoint")
+                .long("etcd-endpoint")
+                .value_name("URL")
+                .takes_value(true)
+                .required_if("tower_storage", "etcd")
+                .validator(is_url)
+                .help("Client URL of the etcd cluster member used by the etcd tower storage"),
+        )
+        .arg(
+            Arg::with_name("etcd_key_prefix")
+                .long("etcd-key-prefix")
+                .value_name("PREFIX")
+                .takes_value(true)
+                .default_value("solana")
+                .help("Prefix of the keys under which the etcd tower storage saves towers"),
+        )
         .arg(
             Arg::with_name("expected_genesis_hash")
                 .long("expected-genesis-hash")
@@ -1402,9 +1545,13 @@ pub fn main() {
 
     let identity_keypair = Arc::new(keypair_of(&matches, "identity").unwrap_or_else(Keypair::new));
 
-    let authorized_voter_keypairs = keypairs_of(&matches, "authorized_voter_keypairs")
-        .map(|keypairs| keypairs.into_iter().map(Arc::new).collect())
-        .unwrap_or_else(|| vec![identity_keypair.clone()]);
+    let authorized_voter_keypairs =
+        keypairs_or_remote_signers_of(&matches, "authorized_voter_keypairs")
+            .unwrap_or_else(|err| {
+                eprintln!("Unable to load an authorized voter: {}", err);
+                exit(1);
+            })
+            .unwrap_or_else(|| vec![identity_keypair.clone() as Arc<dyn Signer + Send + Sync>]);
 
     let ledger_path = PathBuf::from(matches.value_of("ledger_path").unwrap());
     let init_complete_file = matches.value_of("init_complete_file");
@@ -1495,6 +1642,7 @@ pub fn main() {
             enable_bigtable_ledger_storage: matches
                 .is_present("enable_rpc_bigtable_ledger_storage"),
             enable_bigtable_ledger_upload: matches.is_present("enable_bigtable_ledger_upload"),
+            event_layouts_path: matches.value_of("rpc_event_layouts").map(PathBuf::from),
             identity_pubkey: identity_keypair.pubkey(),
             faucet_addr: matches.value_of("rpc_faucet_addr").map(|address| {
                 solana_net_utils::parse_host_port(address).expect("failed to parse faucet address")
@@ -1653,10 +1801,36 @@ pub fn main() {
         validator_config.max_ledger_shreds = Some(limit_ledger_size);
     }
 
+    validator_config.ledger_retention = LedgerRetentionConfig {
+        shreds: retention_policy_of(&matches, "limit_shreds_size", "limit_shreds_age")
+            .unwrap_or_default(),
+        transaction_status: retention_policy_of(
+            &matches,
+            "limit_transaction_status_size",
+            "limit_transaction_status_age",
+        ),
+        rewards: retention_policy_of(&matches, "limit_rewards_size", "limit_rewards_age"),
+    };
+
     if matches.is_present("halt_on_trusted_validators_accounts_hash_mismatch") {
         validator_config.halt_on_trusted_validators_accounts_hash_mismatch = true;
     }
 
+    validator_config.tower_storage = match matches.value_of("tower_storage").unwrap() {
+        // The tower file is kept in the ledger directory
+        "file" => None,
+        "etcd" => {
+            let tower_storage: Arc<dyn TowerStorage> = Arc::new(RemoteTowerStorage::new(
+                Arc::new(EtcdKeyValueStore::new(
+                    matches.value_of("etcd_endpoint").unwrap(),
+                )),
+                matches.value_of("etcd_key_prefix").unwrap(),
+            ));
+            Some(tower_storage)
+        }
+        _ => unreachable!(),
+    };
+
     if matches.value_of("signer_addr").is_some() {
         warn!("--vote-signer-address ignored");
     }
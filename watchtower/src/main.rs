@@ -1,8 +1,12 @@
 //! A command-line executable for monitoring the health of a cluster
 
+mod rules;
+
 use {
+    chrono::Utc,
     clap::{crate_description, crate_name, value_t, value_t_or_exit, App, Arg},
     log::*,
+    rules::{ClusterSnapshot, RuleEngine, RulesConfig},
     solana_clap_utils::{
         input_parsers::pubkeys_of,
         input_validators::{is_pubkey_or_keypair, is_url},
@@ -13,15 +17,19 @@ use {
         rpc_response::RpcVoteAccountStatus,
     },
     solana_metrics::{datapoint_error, datapoint_info},
-    solana_notifier::Notifier,
+    solana_notifier::{Notification, Notifier, Severity},
     solana_sdk::{
+        clock::{Epoch, Slot},
+        commitment_config::CommitmentConfig,
+        feature,
         hash::Hash,
         native_token::{sol_to_lamports, Sol},
         pubkey::Pubkey,
     },
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         error,
+        process::exit,
         thread::sleep,
         time::{Duration, Instant},
     },
@@ -32,6 +40,7 @@ struct Config {
     interval: Duration,
     json_rpc_url: String,
     monitor_active_stake: bool,
+    rules: Option<RulesConfig>,
     unhealthy_threshold: usize,
     validator_identity_pubkeys: Vec<Pubkey>,
 }
@@ -56,7 +65,18 @@ fn get_config() -> Config {
         and a sending number owned by that account,
         define environment variable before running `solana-watchtower`:
 
-        export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'")
+        export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'
+
+        To post notifications as JSON to a webhook, and to open and resolve
+        incidents in PagerDuty or Opsgenie:
+
+        export WEBHOOK_URL=...
+        export PAGERDUTY_ROUTING_KEY=...
+        export OPSGENIE_API_KEY=...
+
+        Alert rules for skip rate, vote lag, balances, stake changes and feature
+        activations can be loaded with --rules; see the watchtower README for the
+        file format.")
         .arg({
             let arg = Arg::with_name("config_file")
                 .short("C")
@@ -116,6 +136,13 @@ fn get_config() -> Config {
                 .takes_value(false)
                 .help("Alert when the current stake for the cluster drops below 80%"),
         )
+        .arg(
+            Arg::with_name("rules")
+                .long("rules")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Alert rules file to evaluate on every check of the cluster"),
+        )
         .get_matches();
 
     let config = if let Some(config_file) = matches.value_of("config_file") {
@@ -134,12 +161,19 @@ fn get_config() -> Config {
         .collect();
 
     let monitor_active_stake = matches.is_present("monitor_active_stake");
+    let rules = matches.value_of("rules").map(|rules_file| {
+        RulesConfig::load(rules_file).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        })
+    });
 
     let config = Config {
         address_labels: config.address_labels,
         interval,
         json_rpc_url,
         monitor_active_stake,
+        rules,
         unhealthy_threshold,
         validator_identity_pubkeys,
     };
@@ -176,6 +210,128 @@ fn get_cluster_info(
     ))
 }
 
+/// Counts the leader slots and skipped slots of each validator over the rooted slots of the
+/// current epoch seen since watchtower started
+#[derive(Default)]
+struct BlockProductionTracker {
+    epoch: Option<Epoch>,
+    first_slot_in_epoch: Slot,
+    next_slot: Slot,
+    slot_leaders: HashMap<usize, String>,
+    block_production: HashMap<String, (u64, u64)>,
+}
+
+impl BlockProductionTracker {
+    fn update(&mut self, rpc_client: &RpcClient) -> ClientResult<HashMap<String, (u64, u64)>> {
+        let epoch_info = rpc_client.get_epoch_info_with_commitment(CommitmentConfig::root())?;
+        let root = epoch_info.absolute_slot;
+        if self.epoch != Some(epoch_info.epoch) {
+            let first_slot_in_epoch = root - epoch_info.slot_index;
+            let leader_schedule = rpc_client
+                .get_leader_schedule_with_commitment(Some(root), CommitmentConfig::root())?
+                .unwrap_or_default();
+            self.slot_leaders = leader_schedule
+                .into_iter()
+                .flat_map(|(leader, slot_indexes)| {
+                    slot_indexes
+                        .into_iter()
+                        .map(move |slot_index| (slot_index, leader.clone()))
+                })
+                .collect();
+            // Start from the beginning of an epoch that began while watching, otherwise from
+            // the current root
+            self.next_slot = if self.epoch.is_some() {
+                first_slot_in_epoch
+            } else {
+                root
+            };
+            self.epoch = Some(epoch_info.epoch);
+            self.first_slot_in_epoch = first_slot_in_epoch;
+            self.block_production.clear();
+        }
+        if root >= self.next_slot {
+            let confirmed_blocks: HashSet<Slot> = rpc_client
+                .get_confirmed_blocks(self.next_slot, Some(root))?
+                .into_iter()
+                .collect();
+            for slot in self.next_slot..=root {
+                let slot_index = (slot - self.first_slot_in_epoch) as usize;
+                if let Some(leader) = self.slot_leaders.get(&slot_index) {
+                    let entry = self
+                        .block_production
+                        .entry(leader.clone())
+                        .or_insert((0, 0));
+                    entry.0 += 1;
+                    if !confirmed_blocks.contains(&slot) {
+                        entry.1 += 1;
+                    }
+                }
+            }
+            self.next_slot = root + 1;
+        }
+        Ok(self.block_production.clone())
+    }
+}
+
+fn get_cluster_snapshot(
+    rules: &RulesConfig,
+    rpc_client: &RpcClient,
+    block_production_tracker: &mut BlockProductionTracker,
+) -> ClientResult<ClusterSnapshot> {
+    let epoch_info = rpc_client.get_epoch_info_with_commitment(CommitmentConfig::recent())?;
+    let vote_accounts = rpc_client.get_vote_accounts_with_commitment(CommitmentConfig::recent())?;
+
+    let mut balances = HashMap::new();
+    for account in rules.balance_accounts() {
+        balances.insert(account, rpc_client.get_balance(&account)?);
+    }
+
+    let block_production = if rules.needs_block_production() {
+        block_production_tracker.update(rpc_client)?
+    } else {
+        HashMap::new()
+    };
+
+    let features = if rules.needs_features() {
+        Some(
+            rpc_client
+                .get_program_accounts(&feature::id())?
+                .into_iter()
+                .filter_map(|(feature_id, account)| {
+                    feature::from_account(&account)
+                        .map(|feature| (feature_id, feature.activated_at))
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    Ok(ClusterSnapshot {
+        slot: epoch_info.absolute_slot,
+        epoch: epoch_info.epoch,
+        vote_accounts: vote_accounts
+            .current
+            .into_iter()
+            .chain(vote_accounts.delinquent.into_iter())
+            .collect(),
+        balances,
+        block_production,
+        features,
+    })
+}
+
+// The sanity checks share one incident, which stays open until all of them pass
+fn sanity_notification(title: String, resolved: bool) -> Notification {
+    Notification {
+        id: "solana-watchtower/sanity".to_string(),
+        severity: Severity::Critical,
+        title,
+        details: String::new(),
+        resolved,
+    }
+}
+
 fn main() -> Result<(), Box<dyn error::Error>> {
     solana_logger::setup_with_default("solana=info");
     solana_metrics::set_panic_hook("watchtower");
@@ -189,6 +345,11 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let mut last_notification_msg = "".into();
     let mut num_consecutive_failures = 0;
     let mut last_success = Instant::now();
+    let mut rule_engine = config
+        .rules
+        .clone()
+        .map(|rules| RuleEngine::new(rules, config.address_labels.clone()));
+    let mut block_production_tracker = BlockProductionTracker::default();
 
     loop {
         let failure = match get_cluster_info(&config, &rpc_client) {
@@ -306,7 +467,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
             if num_consecutive_failures > config.unhealthy_threshold {
                 datapoint_info!("watchtower-sanity", ("ok", false, bool));
                 if last_notification_msg != notification_msg {
-                    notifier.send(&notification_msg);
+                    notifier.notify(&sanity_notification(notification_msg.clone(), false));
                 }
                 datapoint_error!(
                     "watchtower-sanity-failure",
@@ -332,12 +493,42 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     humantime::format_duration(alarm_duration)
                 );
                 info!("{}", all_clear_msg);
-                notifier.send(&format!("solana-watchtower: {}", all_clear_msg));
+                notifier.notify(&sanity_notification(
+                    format!("solana-watchtower: {}", all_clear_msg),
+                    true,
+                ));
             }
             last_notification_msg = "".into();
             last_success = Instant::now();
             num_consecutive_failures = 0;
         }
+
+        if let Some(rule_engine) = rule_engine.as_mut() {
+            match get_cluster_snapshot(
+                rule_engine.config(),
+                &rpc_client,
+                &mut block_production_tracker,
+            ) {
+                Ok(snapshot) => {
+                    for notification in rule_engine.evaluate(&snapshot, Utc::now()) {
+                        info!("{}", notification);
+                        datapoint_info!(
+                            "watchtower-rule-alert",
+                            ("rule", notification.rule, String),
+                            ("severity", notification.severity.to_string(), String),
+                            ("subject", notification.subject, String),
+                            ("recovered", notification.recovered, bool)
+                        );
+                        if notification.event {
+                            notifier.notify_event(&(&notification).into());
+                        } else {
+                            notifier.notify(&(&notification).into());
+                        }
+                    }
+                }
+                Err(err) => warn!("Unable to evaluate alert rules: {}", err),
+            }
+        }
         sleep(config.interval);
     }
 }
@@ -0,0 +1,841 @@
+//! Alert rules loaded from a configuration file, and the engine that evaluates them against
+//! each poll of the cluster
+
+use {
+    chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday},
+    serde::Deserialize,
+    solana_cli_output::display::format_labeled_address,
+    solana_client::rpc_response::RpcVoteAccountInfo,
+    solana_notifier::Severity,
+    solana_sdk::{
+        clock::{Epoch, Slot},
+        native_token::{sol_to_lamports, Sol},
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        fmt,
+        fs::File,
+        str::FromStr,
+    },
+};
+
+fn default_dedup_window_secs() -> u64 {
+    3600
+}
+
+fn default_notify_recovery() -> bool {
+    true
+}
+
+fn default_min_leader_slots() -> u64 {
+    1
+}
+
+/// The condition a rule alerts on. Validators are named by identity pubkey
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// Skipped leader slots over the slots observed so far in the current epoch
+    SkipRate {
+        validators: Vec<String>,
+        max_percent: f64,
+        #[serde(default = "default_min_leader_slots")]
+        min_leader_slots: u64,
+    },
+    /// Slots between the cluster's latest slot and each validator's latest vote
+    VoteLag {
+        validators: Vec<String>,
+        max_slots: u64,
+    },
+    Balance {
+        accounts: Vec<String>,
+        min_sol: f64,
+    },
+    /// Change in the activated stake of each validator between polls
+    StakeChange {
+        validators: Vec<String>,
+        max_percent: f64,
+    },
+    /// New feature proposals, and feature activations
+    FeatureActivation {},
+}
+
+impl Check {
+    /// Event checks report something that happened once, and so never recover
+    fn is_event(&self) -> bool {
+        matches!(
+            self,
+            Check::StakeChange { .. } | Check::FeatureActivation { .. }
+        )
+    }
+
+    fn pubkeys(&self) -> &[String] {
+        match self {
+            Check::SkipRate { validators, .. }
+            | Check::VoteLag { validators, .. }
+            | Check::StakeChange { validators, .. } => validators,
+            Check::Balance { accounts, .. } => accounts,
+            Check::FeatureActivation {} => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default)]
+    pub severity: Severity,
+    /// Minimum time between notifications for the same ongoing alert
+    #[serde(default = "default_dedup_window_secs")]
+    pub dedup_window_secs: u64,
+    /// Notify when an alert clears
+    #[serde(default = "default_notify_recovery")]
+    pub notify_recovery: bool,
+    pub check: Check,
+}
+
+/// A period in which notifications are held back. Either a one-off window between `from` and
+/// `until`, or a window between the `start` and `end` times of day (UTC) repeating on `days`,
+/// or every day if no days are given
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Silence {
+    /// Rules silenced, or all rules if empty
+    #[serde(default)]
+    pub rules: Vec<String>,
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub days: Vec<String>,
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub end: Option<String>,
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|err| format!("invalid time of day {}: {}", time, err))
+}
+
+impl Silence {
+    fn validate(&self) -> Result<(), String> {
+        match (self.from, self.until, &self.start, &self.end) {
+            (Some(_), Some(_), None, None) => Ok(()),
+            (None, None, Some(start), Some(end)) => {
+                parse_time(start)?;
+                parse_time(end)?;
+                for day in &self.days {
+                    Weekday::from_str(day).map_err(|_| format!("invalid day {}", day))?;
+                }
+                Ok(())
+            }
+            _ => Err("a silence needs either `from` and `until`, or `start` and `end`".to_string()),
+        }
+    }
+
+    fn covers(&self, rule: &str, now: DateTime<Utc>) -> bool {
+        if !self.rules.is_empty() && !self.rules.iter().any(|name| name == rule) {
+            return false;
+        }
+        if let (Some(from), Some(until)) = (self.from, self.until) {
+            return from <= now && now < until;
+        }
+        let (start, end) = match (&self.start, &self.end) {
+            (Some(start), Some(end)) => match (parse_time(start), parse_time(end)) {
+                (Ok(start), Ok(end)) => (start, end),
+                _ => return false,
+            },
+            _ => return false,
+        };
+        let on_day = |weekday: Weekday| {
+            self.days.is_empty()
+                || self
+                    .days
+                    .iter()
+                    .any(|day| Weekday::from_str(day).ok() == Some(weekday))
+        };
+        let time = now.time();
+        if start <= end {
+            on_day(now.weekday()) && start <= time && time < end
+        } else {
+            // The window runs past midnight, and belongs to the day it starts on
+            (on_day(now.weekday()) && start <= time) || (on_day(now.weekday().pred()) && time < end)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct RulesConfig {
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub silences: Vec<Silence>,
+}
+
+impl RulesConfig {
+    pub fn load(rules_file: &str) -> Result<Self, String> {
+        let file = File::open(rules_file)
+            .map_err(|err| format!("unable to open {}: {}", rules_file, err))?;
+        let config: Self = serde_yaml::from_reader(file)
+            .map_err(|err| format!("{} is not a rules file: {}", rules_file, err))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut names = HashSet::new();
+        for rule in &self.rules {
+            if !names.insert(&rule.name) {
+                return Err(format!("duplicate rule {}", rule.name));
+            }
+            for pubkey in rule.check.pubkeys() {
+                Pubkey::from_str(pubkey)
+                    .map_err(|_| format!("rule {}: invalid pubkey {}", rule.name, pubkey))?;
+            }
+        }
+        for silence in &self.silences {
+            silence.validate()?;
+        }
+        Ok(())
+    }
+
+    fn is_silenced(&self, rule: &str, now: DateTime<Utc>) -> bool {
+        self.silences
+            .iter()
+            .any(|silence| silence.covers(rule, now))
+    }
+
+    /// Accounts whose balance the rules need
+    pub fn balance_accounts(&self) -> Vec<Pubkey> {
+        let mut accounts: Vec<_> = self
+            .rules
+            .iter()
+            .filter(|rule| matches!(rule.check, Check::Balance { .. }))
+            .flat_map(|rule| rule.check.pubkeys())
+            .filter_map(|pubkey| Pubkey::from_str(pubkey).ok())
+            .collect();
+        accounts.sort();
+        accounts.dedup();
+        accounts
+    }
+
+    pub fn needs_block_production(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| matches!(rule.check, Check::SkipRate { .. }))
+    }
+
+    pub fn needs_features(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| matches!(rule.check, Check::FeatureActivation {}))
+    }
+}
+
+/// The state of the cluster the rules are evaluated against
+#[derive(Debug, Default)]
+pub struct ClusterSnapshot {
+    pub slot: Slot,
+    pub epoch: Epoch,
+    /// Current and delinquent vote accounts
+    pub vote_accounts: Vec<RpcVoteAccountInfo>,
+    pub balances: HashMap<Pubkey, u64>,
+    /// Leader slots and skipped slots of each validator identity in the current epoch
+    pub block_production: HashMap<String, (u64, u64)>,
+    /// Activation slot of every feature account, if the features were fetched
+    pub features: Option<HashMap<Pubkey, Option<Slot>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub rule: String,
+    pub severity: Severity,
+    pub subject: String,
+    pub message: String,
+    pub recovered: bool,
+    /// An event, which has no incident to open or resolve
+    pub event: bool,
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.recovered {
+            write!(
+                f,
+                "solana-watchtower: [RESOLVED] {}: {}",
+                self.rule, self.message
+            )
+        } else {
+            write!(
+                f,
+                "solana-watchtower: [{}] {}: {}",
+                self.severity, self.rule, self.message
+            )
+        }
+    }
+}
+
+impl From<&Notification> for solana_notifier::Notification {
+    fn from(notification: &Notification) -> Self {
+        Self {
+            id: format!(
+                "solana-watchtower/{}/{}",
+                notification.rule, notification.subject
+            ),
+            severity: notification.severity,
+            title: notification.to_string(),
+            details: String::new(),
+            resolved: notification.recovered,
+        }
+    }
+}
+
+struct ActiveAlert {
+    since: DateTime<Utc>,
+    last_notified: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+struct RuleState {
+    active: HashMap<String, ActiveAlert>,
+    /// Events and recoveries held back by a silence, sent once it ends
+    pending: Vec<Notification>,
+    previous_stakes: HashMap<String, u64>,
+    known_features: Option<HashMap<Pubkey, Option<Slot>>>,
+}
+
+impl Rule {
+    fn notification(&self, subject: String, message: String, recovered: bool) -> Notification {
+        Notification {
+            rule: self.name.clone(),
+            severity: self.severity,
+            subject,
+            message,
+            recovered,
+            event: self.check.is_event(),
+        }
+    }
+}
+
+pub struct RuleEngine {
+    config: RulesConfig,
+    address_labels: HashMap<String, String>,
+    states: Vec<RuleState>,
+}
+
+impl RuleEngine {
+    pub fn new(config: RulesConfig, address_labels: HashMap<String, String>) -> Self {
+        let states = config.rules.iter().map(|_| RuleState::default()).collect();
+        Self {
+            config,
+            address_labels,
+            states,
+        }
+    }
+
+    pub fn config(&self) -> &RulesConfig {
+        &self.config
+    }
+
+    /// Evaluate every rule, returning the notifications that are due after deduplication and
+    /// silencing
+    pub fn evaluate(
+        &mut self,
+        snapshot: &ClusterSnapshot,
+        now: DateTime<Utc>,
+    ) -> Vec<Notification> {
+        let mut notifications = vec![];
+        for (rule, state) in self.config.rules.iter().zip(self.states.iter_mut()) {
+            let findings = check_findings(&rule.check, snapshot, state, &self.address_labels);
+            let silenced = self.config.is_silenced(&rule.name, now);
+            let dedup_window = chrono::Duration::seconds(rule.dedup_window_secs as i64);
+            if !silenced {
+                notifications.append(&mut state.pending);
+            }
+
+            let mut present = HashSet::new();
+            for (subject, message) in findings {
+                // Every event is a new occurrence, so is neither deduplicated nor tracked
+                if rule.check.is_event() {
+                    let notification = rule.notification(subject, message, false);
+                    if silenced {
+                        state.pending.push(notification);
+                    } else {
+                        notifications.push(notification);
+                    }
+                    continue;
+                }
+                let alert = state.active.entry(subject.clone()).or_insert(ActiveAlert {
+                    since: now,
+                    last_notified: None,
+                });
+                let due = alert
+                    .last_notified
+                    .map(|last_notified| now - last_notified >= dedup_window)
+                    .unwrap_or(true);
+                if due && !silenced {
+                    alert.last_notified = Some(now);
+                    notifications.push(rule.notification(subject.clone(), message, false));
+                }
+                present.insert(subject);
+            }
+
+            // An alert that clears before it was ever sent needs no recovery
+            let address_labels = &self.address_labels;
+            let pending = &mut state.pending;
+            state.active.retain(|subject, alert| {
+                if present.contains(subject) {
+                    return true;
+                }
+                if rule.notify_recovery && alert.last_notified.is_some() {
+                    let duration = (now - alert.since).to_std().unwrap_or_default();
+                    let duration = std::time::Duration::from_secs(duration.as_secs());
+                    let notification = rule.notification(
+                        subject.clone(),
+                        format!(
+                            "{} recovered after {}",
+                            format_labeled_address(subject, address_labels),
+                            humantime::format_duration(duration)
+                        ),
+                        true,
+                    );
+                    if silenced {
+                        pending.push(notification);
+                    } else {
+                        notifications.push(notification);
+                    }
+                }
+                false
+            });
+        }
+        notifications
+    }
+}
+
+fn validator_stake(snapshot: &ClusterSnapshot, validator: &str) -> Option<u64> {
+    let mut vote_accounts = snapshot
+        .vote_accounts
+        .iter()
+        .filter(|vote_account| vote_account.node_pubkey == validator)
+        .peekable();
+    vote_accounts.peek()?;
+    Some(
+        vote_accounts
+            .map(|vote_account| vote_account.activated_stake)
+            .sum(),
+    )
+}
+
+/// The subjects a check fails for this poll, with a message for each
+fn check_findings(
+    check: &Check,
+    snapshot: &ClusterSnapshot,
+    state: &mut RuleState,
+    address_labels: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    let label = |pubkey: &str| format_labeled_address(pubkey, address_labels);
+    let mut findings = vec![];
+    match check {
+        Check::SkipRate {
+            validators,
+            max_percent,
+            min_leader_slots,
+        } => {
+            for validator in validators {
+                if let Some((leader_slots, skipped_slots)) =
+                    snapshot.block_production.get(validator)
+                {
+                    let skip_rate = *skipped_slots as f64 * 100. / *leader_slots as f64;
+                    if *leader_slots >= *min_leader_slots && skip_rate > *max_percent {
+                        findings.push((
+                            validator.clone(),
+                            format!(
+                                "{} skipped {} of {} leader slots ({:.1}%) in epoch {}",
+                                label(validator),
+                                skipped_slots,
+                                leader_slots,
+                                skip_rate,
+                                snapshot.epoch
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        Check::VoteLag {
+            validators,
+            max_slots,
+        } => {
+            for validator in validators {
+                let last_vote = snapshot
+                    .vote_accounts
+                    .iter()
+                    .filter(|vote_account| vote_account.node_pubkey == *validator)
+                    .map(|vote_account| vote_account.last_vote)
+                    .max();
+                let message = match last_vote {
+                    Some(last_vote) if snapshot.slot.saturating_sub(last_vote) > *max_slots => {
+                        format!(
+                            "{} last voted on slot {}, {} slots behind",
+                            label(validator),
+                            last_vote,
+                            snapshot.slot - last_vote
+                        )
+                    }
+                    Some(_) => continue,
+                    None => format!("{} has no vote account", label(validator)),
+                };
+                findings.push((validator.clone(), message));
+            }
+        }
+        Check::Balance { accounts, min_sol } => {
+            for account in accounts {
+                let balance = Pubkey::from_str(account)
+                    .ok()
+                    .and_then(|pubkey| snapshot.balances.get(&pubkey));
+                if let Some(balance) = balance {
+                    if *balance < sol_to_lamports(*min_sol) {
+                        findings.push((
+                            account.clone(),
+                            format!("{} has {}", label(account), Sol(*balance)),
+                        ));
+                    }
+                }
+            }
+        }
+        Check::StakeChange {
+            validators,
+            max_percent,
+        } => {
+            for validator in validators {
+                let stake = match validator_stake(snapshot, validator) {
+                    Some(stake) => stake,
+                    None => continue,
+                };
+                if let Some(previous_stake) = state.previous_stakes.insert(validator.clone(), stake)
+                {
+                    let change = (stake as f64 - previous_stake as f64).abs() * 100.
+                        / previous_stake.max(1) as f64;
+                    if stake != previous_stake && change > *max_percent {
+                        findings.push((
+                            validator.clone(),
+                            format!(
+                                "{} stake changed from {} to {} in epoch {}",
+                                label(validator),
+                                Sol(previous_stake),
+                                Sol(stake),
+                                snapshot.epoch
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        Check::FeatureActivation {} => {
+            if let Some(features) = &snapshot.features {
+                // The first poll only records the features already known to the cluster
+                if let Some(known_features) = &state.known_features {
+                    for (feature_id, activated_at) in features {
+                        let message = match (known_features.get(feature_id), activated_at) {
+                            (None, None) => format!("feature {} proposed", feature_id),
+                            (None, Some(slot)) | (Some(None), Some(slot)) => {
+                                format!("feature {} activated at slot {}", feature_id, slot)
+                            }
+                            _ => continue,
+                        };
+                        findings.push((feature_id.to_string(), message));
+                    }
+                }
+                state.known_features = Some(features.clone());
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn vote_account(
+        node_pubkey: &str,
+        activated_stake: u64,
+        last_vote: Slot,
+    ) -> RpcVoteAccountInfo {
+        RpcVoteAccountInfo {
+            vote_pubkey: Pubkey::new_unique().to_string(),
+            node_pubkey: node_pubkey.to_string(),
+            activated_stake,
+            commission: 0,
+            epoch_vote_account: true,
+            epoch_credits: vec![],
+            last_vote,
+            root_slot: 0,
+        }
+    }
+
+    fn rule(name: &str, check: Check) -> Rule {
+        Rule {
+            name: name.to_string(),
+            severity: Severity::Critical,
+            dedup_window_secs: 600,
+            notify_recovery: true,
+            check,
+        }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let validator = Pubkey::new_unique();
+        let yaml = format!(
+            r#"
+rules:
+  - name: vote-lag
+    severity: critical
+    check:
+      vote_lag:
+        validators: [{}]
+        max_slots: 150
+  - name: features
+    notify_recovery: false
+    check:
+      feature_activation: {{}}
+silences:
+  - rules: [vote-lag]
+    days: [sat, sun]
+    start: "22:00"
+    end: "02:00"
+  - from: 2020-12-24T00:00:00Z
+    until: 2020-12-26T00:00:00Z
+"#,
+            validator
+        );
+        let config: RulesConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.rules[0].severity, Severity::Critical);
+        assert_eq!(config.rules[1].severity, Severity::Warning);
+        assert_eq!(config.rules[1].dedup_window_secs, 3600);
+        assert_eq!(config.rules[1].check, Check::FeatureActivation {});
+        assert!(config.needs_features());
+        assert!(!config.needs_block_production());
+
+        // Saturday night, and the early hours of Sunday and Monday
+        let saturday = Utc.ymd(2020, 12, 5);
+        assert!(config.is_silenced("vote-lag", saturday.and_hms(23, 0, 0)));
+        assert!(config.is_silenced("vote-lag", Utc.ymd(2020, 12, 6).and_hms(1, 0, 0)));
+        assert!(config.is_silenced("vote-lag", Utc.ymd(2020, 12, 7).and_hms(1, 0, 0)));
+        assert!(!config.is_silenced("vote-lag", Utc.ymd(2020, 12, 5).and_hms(1, 0, 0)));
+        assert!(!config.is_silenced("vote-lag", saturday.and_hms(12, 0, 0)));
+        assert!(!config.is_silenced("features", saturday.and_hms(23, 0, 0)));
+        assert!(config.is_silenced("features", Utc.ymd(2020, 12, 25).and_hms(12, 0, 0)));
+
+        let bad_silence = RulesConfig {
+            silences: vec![Silence {
+                start: Some("25:00".to_string()),
+                end: Some("02:00".to_string()),
+                ..Silence::default()
+            }],
+            ..config
+        };
+        assert!(bad_silence.validate().is_err());
+    }
+
+    #[test]
+    fn test_dedup_and_recovery() {
+        let validator = Pubkey::new_unique().to_string();
+        let config = RulesConfig {
+            rules: vec![rule(
+                "vote-lag",
+                Check::VoteLag {
+                    validators: vec![validator.clone()],
+                    max_slots: 100,
+                },
+            )],
+            silences: vec![],
+        };
+        let mut engine = RuleEngine::new(config, HashMap::new());
+        let start = Utc.ymd(2020, 12, 1).and_hms(0, 0, 0);
+        let mut snapshot = ClusterSnapshot {
+            slot: 1000,
+            vote_accounts: vec![vote_account(&validator, 1, 500)],
+            ..ClusterSnapshot::default()
+        };
+
+        let notifications = engine.evaluate(&snapshot, start);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].severity, Severity::Critical);
+        assert!(!notifications[0].recovered);
+        // Still lagging, inside the dedup window
+        let later = start + chrono::Duration::seconds(300);
+        assert!(engine.evaluate(&snapshot, later).is_empty());
+        // The dedup window has passed
+        let later = start + chrono::Duration::seconds(600);
+        assert_eq!(engine.evaluate(&snapshot, later).len(), 1);
+
+        snapshot.vote_accounts = vec![vote_account(&validator, 1, 990)];
+        let later = start + chrono::Duration::seconds(660);
+        let notifications = engine.evaluate(&snapshot, later);
+        assert_eq!(notifications.len(), 1);
+        assert!(notifications[0].recovered);
+        assert_eq!(
+            notifications[0].message,
+            format!("{} recovered after 11m", validator)
+        );
+        assert!(engine.evaluate(&snapshot, later).is_empty());
+    }
+
+    #[test]
+    fn test_silenced_alerts() {
+        let account = Pubkey::new_unique();
+        let config = RulesConfig {
+            rules: vec![rule(
+                "balance",
+                Check::Balance {
+                    accounts: vec![account.to_string()],
+                    min_sol: 10.,
+                },
+            )],
+            silences: vec![Silence {
+                start: Some("00:00".to_string()),
+                end: Some("01:00".to_string()),
+                ..Silence::default()
+            }],
+        };
+        let mut engine = RuleEngine::new(config, HashMap::new());
+        let mut snapshot = ClusterSnapshot::default();
+        snapshot.balances.insert(account, sol_to_lamports(1.));
+
+        let silenced = Utc.ymd(2020, 12, 1).and_hms(0, 30, 0);
+        assert!(engine.evaluate(&snapshot, silenced).is_empty());
+        // Alerts held back by a silence go out once it ends
+        let notifications = engine.evaluate(&snapshot, Utc.ymd(2020, 12, 1).and_hms(1, 0, 0));
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(
+            notifications[0].to_string(),
+            format!(
+                "solana-watchtower: [CRITICAL] balance: {} has ◎1.000000000",
+                account
+            )
+        );
+
+        // A recovery during a silence is held back too
+        snapshot.balances.insert(account, sol_to_lamports(20.));
+        let silenced = Utc.ymd(2020, 12, 2).and_hms(0, 30, 0);
+        assert!(engine.evaluate(&snapshot, silenced).is_empty());
+        let notifications = engine.evaluate(&snapshot, Utc.ymd(2020, 12, 2).and_hms(1, 0, 0));
+        assert_eq!(notifications.len(), 1);
+        assert!(notifications[0].recovered);
+
+        // An alert raised and cleared within a silence is never sent
+        snapshot.balances.insert(account, sol_to_lamports(1.));
+        assert!(engine
+            .evaluate(&snapshot, Utc.ymd(2020, 12, 3).and_hms(0, 10, 0))
+            .is_empty());
+        snapshot.balances.insert(account, sol_to_lamports(20.));
+        assert!(engine
+            .evaluate(&snapshot, Utc.ymd(2020, 12, 3).and_hms(0, 20, 0))
+            .is_empty());
+        assert!(engine
+            .evaluate(&snapshot, Utc.ymd(2020, 12, 3).and_hms(1, 0, 0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_silenced_events() {
+        let config = RulesConfig {
+            rules: vec![rule("features", Check::FeatureActivation {})],
+            silences: vec![Silence {
+                start: Some("00:00".to_string()),
+                end: Some("01:00".to_string()),
+                ..Silence::default()
+            }],
+        };
+        let mut engine = RuleEngine::new(config, HashMap::new());
+        let feature_id = Pubkey::new_unique();
+        let mut snapshot = ClusterSnapshot {
+            features: Some(HashMap::new()),
+            ..ClusterSnapshot::default()
+        };
+        assert!(engine
+            .evaluate(&snapshot, Utc.ymd(2020, 12, 1).and_hms(0, 10, 0))
+            .is_empty());
+
+        snapshot.features = Some(vec![(feature_id, None)].into_iter().collect());
+        assert!(engine
+            .evaluate(&snapshot, Utc.ymd(2020, 12, 1).and_hms(0, 20, 0))
+            .is_empty());
+        snapshot.features = Some(vec![(feature_id, Some(42))].into_iter().collect());
+        assert!(engine
+            .evaluate(&snapshot, Utc.ymd(2020, 12, 1).and_hms(0, 30, 0))
+            .is_empty());
+
+        // Both events go out once the silence ends
+        let notifications = engine.evaluate(&snapshot, Utc.ymd(2020, 12, 1).and_hms(1, 0, 0));
+        assert_eq!(
+            notifications
+                .iter()
+                .map(|notification| notification.message.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                format!("feature {} proposed", feature_id),
+                format!("feature {} activated at slot 42", feature_id),
+            ]
+        );
+        assert!(notifications.iter().all(|notification| notification.event));
+        assert!(engine
+            .evaluate(&snapshot, Utc.ymd(2020, 12, 1).and_hms(1, 10, 0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_event_checks() {
+        let validator = Pubkey::new_unique().to_string();
+        let feature_id = Pubkey::new_unique();
+        let config = RulesConfig {
+            rules: vec![
+                rule(
+                    "stake",
+                    Check::StakeChange {
+                        validators: vec![validator.clone()],
+                        max_percent: 10.,
+                    },
+                ),
+                rule("features", Check::FeatureActivation {}),
+            ],
+            silences: vec![],
+        };
+        let mut engine = RuleEngine::new(config, HashMap::new());
+        let now = Utc.ymd(2020, 12, 1).and_hms(0, 0, 0);
+        let mut snapshot = ClusterSnapshot {
+            vote_accounts: vec![vote_account(&validator, 100, 0)],
+            features: Some(HashMap::new()),
+            ..ClusterSnapshot::default()
+        };
+        assert!(engine.evaluate(&snapshot, now).is_empty());
+
+        snapshot.vote_accounts = vec![vote_account(&validator, 105, 0)];
+        snapshot.features = Some(vec![(feature_id, None)].into_iter().collect());
+        let notifications = engine.evaluate(&snapshot, now);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(
+            notifications[0].message,
+            format!("feature {} proposed", feature_id)
+        );
+
+        snapshot.vote_accounts = vec![vote_account(&validator, 50, 0)];
+        snapshot.features = Some(vec![(feature_id, Some(42))].into_iter().collect());
+        let notifications = engine.evaluate(&snapshot, now);
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0].rule, "stake");
+        assert_eq!(
+            notifications[1].message,
+            format!("feature {} activated at slot 42", feature_id)
+        );
+
+        // Events never recover
+        assert!(engine.evaluate(&snapshot, now).is_empty());
+    }
+}